@@ -0,0 +1,45 @@
+CREATE TABLE IF NOT EXISTS lnurl_auth_keys (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    linking_key TEXT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    is_deleted BOOLEAN NOT NULL DEFAULT 0,
+    deleted_at DATETIME DEFAULT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_lnurl_auth_keys_user_id ON lnurl_auth_keys(user_id);
+CREATE UNIQUE INDEX idx_lnurl_auth_keys_linking_key_unique ON lnurl_auth_keys(linking_key) WHERE is_deleted = 0;
+
+CREATE TRIGGER lnurl_auth_keys_updated_at
+    AFTER UPDATE ON lnurl_auth_keys
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE lnurl_auth_keys SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TABLE IF NOT EXISTS lnurl_auth_challenges (
+    k1 TEXT PRIMARY KEY,
+    action TEXT NOT NULL, -- Login or Link
+    user_id TEXT DEFAULT NULL, -- Set up-front for Link, after verification for Login
+    linking_key TEXT DEFAULT NULL,
+    status TEXT NOT NULL DEFAULT 'Pending',
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_lnurl_auth_challenges_status ON lnurl_auth_challenges(status);
+CREATE INDEX idx_lnurl_auth_challenges_expires_at ON lnurl_auth_challenges(expires_at);
+
+CREATE TRIGGER lnurl_auth_challenges_updated_at
+    AFTER UPDATE ON lnurl_auth_challenges
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE lnurl_auth_challenges SET updated_at = CURRENT_TIMESTAMP WHERE k1 = NEW.k1;
+END;
@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS jobs (
+    id TEXT PRIMARY KEY,
+    job_type TEXT NOT NULL,
+    payload TEXT NOT NULL DEFAULT '{}', -- JSON arguments for the job handler
+    status TEXT NOT NULL DEFAULT 'Pending', -- Pending, Running, Completed or Failed
+    account_id TEXT DEFAULT NULL, -- NULL for system-wide jobs
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 5,
+    run_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    locked_at DATETIME DEFAULT NULL,
+    last_error TEXT DEFAULT NULL,
+    completed_at DATETIME DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_jobs_status_run_at ON jobs(status, run_at);
+CREATE INDEX idx_jobs_account_id ON jobs(account_id);
+CREATE INDEX idx_jobs_job_type ON jobs(job_type);
+
+CREATE TRIGGER jobs_updated_at
+    AFTER UPDATE ON jobs
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE jobs SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
@@ -0,0 +1,53 @@
+CREATE TABLE IF NOT EXISTS scheduled_tasks (
+    id TEXT PRIMARY KEY,
+    task_type TEXT NOT NULL,
+    account_id TEXT DEFAULT NULL, -- NULL for system-wide tasks
+    cron_expression TEXT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    last_run_at DATETIME DEFAULT NULL,
+    next_run_at DATETIME DEFAULT NULL,
+    last_status TEXT DEFAULT NULL, -- Queued, Succeeded or Failed
+    last_error TEXT DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_scheduled_tasks_account_id ON scheduled_tasks(account_id);
+CREATE INDEX idx_scheduled_tasks_next_run_at ON scheduled_tasks(next_run_at);
+CREATE UNIQUE INDEX idx_scheduled_tasks_type_account_unique ON scheduled_tasks(task_type, IFNULL(account_id, ''));
+
+CREATE TRIGGER scheduled_tasks_updated_at
+    AFTER UPDATE ON scheduled_tasks
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE scheduled_tasks SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TABLE IF NOT EXISTS balance_snapshots (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    local_balance_sat INTEGER NOT NULL,
+    remote_balance_sat INTEGER NOT NULL,
+    capacity_sat INTEGER NOT NULL,
+    num_channels INTEGER NOT NULL,
+    num_active_channels INTEGER NOT NULL,
+    btc_price_usd REAL DEFAULT NULL,
+    captured_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_balance_snapshots_account_id ON balance_snapshots(account_id);
+CREATE INDEX idx_balance_snapshots_node_id_captured_at ON balance_snapshots(node_id, captured_at);
+
+CREATE TABLE IF NOT EXISTS btc_prices (
+    id TEXT PRIMARY KEY,
+    price_usd REAL NOT NULL,
+    recorded_at DATETIME NOT NULL, -- Truncated to the hour
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_btc_prices_recorded_at_unique ON btc_prices(recorded_at);
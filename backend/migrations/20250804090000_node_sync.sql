@@ -0,0 +1,51 @@
+-- Local copies of node data, refreshed by a full resync
+CREATE TABLE IF NOT EXISTS synced_channels (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    chan_id TEXT NOT NULL,
+    alias TEXT DEFAULT NULL,
+    channel_state TEXT NOT NULL,
+    private BOOLEAN NOT NULL DEFAULT 0,
+    local_balance_sat INTEGER NOT NULL,
+    remote_balance_sat INTEGER NOT NULL,
+    capacity_sat INTEGER NOT NULL,
+    synced_at DATETIME NOT NULL,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_synced_channels_node_chan_unique ON synced_channels(account_id, node_id, chan_id);
+
+CREATE TABLE IF NOT EXISTS synced_payments (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    payment_hash TEXT NOT NULL,
+    state TEXT NOT NULL,
+    payment_type TEXT NOT NULL,
+    amount_sat INTEGER NOT NULL,
+    routing_fee_sat INTEGER DEFAULT NULL,
+    created_at_node DATETIME DEFAULT NULL,
+    completed_at_node DATETIME DEFAULT NULL,
+    synced_at DATETIME NOT NULL,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_synced_payments_node_id ON synced_payments(account_id, node_id);
+CREATE INDEX idx_synced_payments_payment_hash ON synced_payments(payment_hash);
+
+CREATE TABLE IF NOT EXISTS synced_invoices (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    payment_hash TEXT NOT NULL,
+    state TEXT NOT NULL,
+    memo TEXT NOT NULL DEFAULT '',
+    value_msat INTEGER NOT NULL,
+    created_at_node DATETIME DEFAULT NULL,
+    settled_at_node DATETIME DEFAULT NULL,
+    synced_at DATETIME NOT NULL,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_synced_invoices_node_hash_unique ON synced_invoices(account_id, node_id, payment_hash);
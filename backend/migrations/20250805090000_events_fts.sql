@@ -0,0 +1,53 @@
+-- Full-text index over event titles, descriptions and JSON data values.
+-- Only scalar JSON values are indexed so searches don't match field names.
+CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(
+    event_id UNINDEXED,
+    title,
+    description,
+    data_values,
+    tokenize = 'unicode61'
+);
+
+INSERT INTO events_fts (event_id, title, description, data_values)
+SELECT
+    e.id,
+    e.title,
+    e.description,
+    (SELECT group_concat(j.value, ' ') FROM json_tree(e.data) j WHERE j.type NOT IN ('object', 'array'))
+FROM events e
+WHERE json_valid(e.data);
+
+CREATE TRIGGER events_fts_insert
+    AFTER INSERT ON events
+BEGIN
+    INSERT INTO events_fts (event_id, title, description, data_values)
+    VALUES (
+        NEW.id,
+        NEW.title,
+        NEW.description,
+        CASE WHEN json_valid(NEW.data) THEN
+            (SELECT group_concat(j.value, ' ') FROM json_tree(NEW.data) j WHERE j.type NOT IN ('object', 'array'))
+        END
+    );
+END;
+
+CREATE TRIGGER events_fts_update
+    AFTER UPDATE OF title, description, data ON events
+BEGIN
+    DELETE FROM events_fts WHERE event_id = OLD.id;
+    INSERT INTO events_fts (event_id, title, description, data_values)
+    VALUES (
+        NEW.id,
+        NEW.title,
+        NEW.description,
+        CASE WHEN json_valid(NEW.data) THEN
+            (SELECT group_concat(j.value, ' ') FROM json_tree(NEW.data) j WHERE j.type NOT IN ('object', 'array'))
+        END
+    );
+END;
+
+CREATE TRIGGER events_fts_delete
+    AFTER DELETE ON events
+BEGIN
+    DELETE FROM events_fts WHERE event_id = OLD.id;
+END;
@@ -0,0 +1,32 @@
+CREATE TABLE IF NOT EXISTS audit_logs (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    actor_user_id TEXT DEFAULT NULL, -- NULL for system actions or after the actor's data was purged
+    action TEXT NOT NULL,
+    target_type TEXT NOT NULL,
+    target_id TEXT DEFAULT NULL,
+    details TEXT NOT NULL DEFAULT '{}', -- JSON
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_audit_logs_account_id ON audit_logs(account_id);
+CREATE INDEX idx_audit_logs_actor_user_id ON audit_logs(actor_user_id);
+CREATE INDEX idx_audit_logs_target ON audit_logs(target_type, target_id);
+CREATE INDEX idx_audit_logs_created_at ON audit_logs(created_at);
+
+CREATE TABLE IF NOT EXISTS data_purge_requests (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    requested_by TEXT NOT NULL,
+    confirmation_token TEXT NOT NULL,
+    expires_at DATETIME NOT NULL,
+    confirmed_at DATETIME DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_data_purge_requests_user_id ON data_purge_requests(user_id);
+CREATE UNIQUE INDEX idx_data_purge_requests_token_unique ON data_purge_requests(confirmation_token);
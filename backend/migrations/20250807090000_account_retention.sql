@@ -0,0 +1,19 @@
+-- Per-account retention windows in days. NULL keeps data indefinitely.
+CREATE TABLE IF NOT EXISTS account_retention_settings (
+    account_id TEXT PRIMARY KEY,
+    events_days INTEGER DEFAULT NULL,
+    deliveries_days INTEGER DEFAULT NULL,
+    audit_logs_days INTEGER DEFAULT NULL,
+    balance_history_days INTEGER DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER account_retention_settings_updated_at
+    AFTER UPDATE ON account_retention_settings
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE account_retention_settings SET updated_at = CURRENT_TIMESTAMP WHERE account_id = NEW.account_id;
+END;
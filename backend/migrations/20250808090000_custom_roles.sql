@@ -0,0 +1,47 @@
+-- no-transaction
+-- Account-defined roles. Built-in roles keep a NULL account_id; custom roles
+-- belong to one account and list their permission scopes as a JSON array.
+-- Role names only need to be unique within an account, so the table is
+-- rebuilt without the global UNIQUE(name) constraint.
+PRAGMA foreign_keys = OFF;
+
+BEGIN;
+
+CREATE TABLE roles_new (
+    id TEXT PRIMARY KEY,
+    account_id TEXT DEFAULT NULL,
+    name TEXT NOT NULL,
+    permissions TEXT NOT NULL DEFAULT '[]',
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    is_deleted BOOLEAN NOT NULL DEFAULT 0,
+    deleted_at DATETIME DEFAULT NULL,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+INSERT INTO roles_new (id, name, is_active, created_at, updated_at, is_deleted, deleted_at)
+SELECT id, name, is_active, created_at, updated_at, is_deleted, deleted_at FROM roles;
+
+DROP TABLE roles;
+ALTER TABLE roles_new RENAME TO roles;
+
+CREATE INDEX idx_roles_name ON roles(name);
+CREATE INDEX idx_roles_account_id ON roles(account_id);
+CREATE UNIQUE INDEX idx_roles_account_name ON roles(IFNULL(account_id, ''), name)
+    WHERE is_deleted = 0;
+
+CREATE TRIGGER roles_updated_at
+    AFTER UPDATE ON roles
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE roles SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- Role an invitee receives on acceptance; NULL falls back to Member.
+ALTER TABLE invites ADD COLUMN role_id TEXT DEFAULT NULL REFERENCES roles(id) ON DELETE SET NULL;
+
+COMMIT;
+
+PRAGMA foreign_keys = ON;
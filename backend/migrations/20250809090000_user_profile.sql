@@ -0,0 +1,19 @@
+-- Optional name shown instead of the username.
+ALTER TABLE users ADD COLUMN display_name TEXT DEFAULT NULL;
+
+-- Tokens issued before this time are rejected (set on password change).
+ALTER TABLE users ADD COLUMN sessions_invalidated_at DATETIME DEFAULT NULL;
+
+-- Email changes wait here until the new address is verified.
+CREATE TABLE IF NOT EXISTS email_change_requests (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    new_email TEXT NOT NULL,
+    token TEXT NOT NULL UNIQUE,
+    expires_at DATETIME NOT NULL,
+    confirmed_at DATETIME DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_email_change_requests_user_id ON email_change_requests(user_id);
@@ -0,0 +1,18 @@
+-- Account-wide display preferences. Accounts without a row use the defaults.
+CREATE TABLE IF NOT EXISTS account_settings (
+    account_id TEXT PRIMARY KEY,
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    display_currency TEXT NOT NULL DEFAULT 'USD',
+    default_unit TEXT NOT NULL DEFAULT 'Sats',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER account_settings_updated_at
+    AFTER UPDATE ON account_settings
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE account_settings SET updated_at = CURRENT_TIMESTAMP WHERE account_id = NEW.account_id;
+END;
@@ -0,0 +1,28 @@
+-- Rules applied to inbound channel opens on an account's LND nodes.
+-- Peer lists are JSON arrays of hex public keys.
+CREATE TABLE IF NOT EXISTS channel_acceptor_policies (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    is_enabled BOOLEAN NOT NULL DEFAULT 0,
+    min_channel_size_sat INTEGER DEFAULT NULL,
+    allowed_peers TEXT NOT NULL DEFAULT '[]',
+    blocked_peers TEXT NOT NULL DEFAULT '[]',
+    private_only BOOLEAN NOT NULL DEFAULT 0,
+    updated_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, node_id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id)
+);
+
+CREATE INDEX idx_channel_acceptor_policies_enabled ON channel_acceptor_policies(is_enabled);
+
+CREATE TRIGGER channel_acceptor_policies_updated_at
+    AFTER UPDATE ON channel_acceptor_policies
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE channel_acceptor_policies SET updated_at = CURRENT_TIMESTAMP
+    WHERE account_id = NEW.account_id AND node_id = NEW.node_id;
+END;
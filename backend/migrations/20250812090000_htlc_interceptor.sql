@@ -0,0 +1,28 @@
+-- Rules applied to forwards held by the HTLC interceptor on an account's LND nodes.
+-- blocked_peers is a JSON array of hex public keys; maintenance_windows is a
+-- JSON array of {"starts_at", "ends_at"} objects.
+CREATE TABLE IF NOT EXISTS htlc_interceptor_policies (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    is_enabled BOOLEAN NOT NULL DEFAULT 0,
+    blocked_peers TEXT NOT NULL DEFAULT '[]',
+    max_htlc_msat INTEGER DEFAULT NULL,
+    maintenance_windows TEXT NOT NULL DEFAULT '[]',
+    updated_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, node_id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id)
+);
+
+CREATE INDEX idx_htlc_interceptor_policies_enabled ON htlc_interceptor_policies(is_enabled);
+
+CREATE TRIGGER htlc_interceptor_policies_updated_at
+    AFTER UPDATE ON htlc_interceptor_policies
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE htlc_interceptor_policies SET updated_at = CURRENT_TIMESTAMP
+    WHERE account_id = NEW.account_id AND node_id = NEW.node_id;
+END;
@@ -0,0 +1,45 @@
+-- Local mirror of the public channel graph as seen by each connected node.
+-- Rows are keyed by the node the graph was read from, since nodes on
+-- different networks (or with different gossip) see different graphs.
+CREATE TABLE IF NOT EXISTS graph_nodes (
+    source_node_id TEXT NOT NULL,
+    pubkey TEXT NOT NULL,
+    alias TEXT NOT NULL DEFAULT '',
+    last_update INTEGER DEFAULT NULL,
+    PRIMARY KEY (source_node_id, pubkey)
+);
+
+CREATE TABLE IF NOT EXISTS graph_channels (
+    source_node_id TEXT NOT NULL,
+    chan_id TEXT NOT NULL,
+    node1_pub TEXT NOT NULL,
+    node2_pub TEXT NOT NULL,
+    capacity_sat INTEGER NOT NULL,
+    PRIMARY KEY (source_node_id, chan_id)
+);
+
+CREATE INDEX idx_graph_channels_node1 ON graph_channels(source_node_id, node1_pub);
+CREATE INDEX idx_graph_channels_node2 ON graph_channels(source_node_id, node2_pub);
+
+-- The routing policy each end of a channel advertises.
+CREATE TABLE IF NOT EXISTS graph_policies (
+    source_node_id TEXT NOT NULL,
+    chan_id TEXT NOT NULL,
+    node_pubkey TEXT NOT NULL,
+    fee_base_msat INTEGER NOT NULL,
+    fee_rate_ppm INTEGER NOT NULL,
+    min_htlc_msat INTEGER NOT NULL,
+    max_htlc_msat INTEGER DEFAULT NULL,
+    time_lock_delta INTEGER NOT NULL,
+    disabled BOOLEAN NOT NULL DEFAULT 0,
+    last_update INTEGER DEFAULT NULL,
+    PRIMARY KEY (source_node_id, chan_id, node_pubkey)
+);
+
+CREATE INDEX idx_graph_policies_node ON graph_policies(source_node_id, node_pubkey);
+
+CREATE TABLE IF NOT EXISTS graph_sync_state (
+    source_node_id TEXT PRIMARY KEY,
+    last_full_sync_at DATETIME DEFAULT NULL,
+    last_update_at DATETIME DEFAULT NULL
+);
@@ -0,0 +1,18 @@
+-- Centrality and capacity rank of a node and its peers, computed from the
+-- graph mirror after each full sync. Kept over time to chart trends.
+CREATE TABLE IF NOT EXISTS network_position_snapshots (
+    id TEXT PRIMARY KEY,
+    source_node_id TEXT NOT NULL,
+    pubkey TEXT NOT NULL,
+    betweenness REAL NOT NULL,
+    closeness REAL NOT NULL,
+    capacity_rank INTEGER NOT NULL,
+    channel_count INTEGER NOT NULL,
+    capacity_sat INTEGER NOT NULL,
+    captured_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_network_position_snapshots_lookup
+    ON network_position_snapshots(source_node_id, pubkey, captured_at);
+CREATE INDEX idx_network_position_snapshots_captured_at
+    ON network_position_snapshots(source_node_id, captured_at);
@@ -0,0 +1,40 @@
+-- Inbound channels purchased from LSPS1 liquidity providers. The order is
+-- refreshed from the LSP until it completes or fails; once the channel is
+-- funded, funding_outpoint ties the purchase cost to the channel.
+CREATE TABLE IF NOT EXISTS liquidity_orders (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    lsp_url TEXT NOT NULL,
+    lsp_order_id TEXT NOT NULL,
+    order_state TEXT NOT NULL DEFAULT 'Created',
+    payment_state TEXT DEFAULT NULL,
+    lsp_balance_sat INTEGER NOT NULL,
+    client_balance_sat INTEGER NOT NULL DEFAULT 0,
+    channel_expiry_blocks INTEGER NOT NULL,
+    announce_channel BOOLEAN NOT NULL DEFAULT 0,
+    fee_total_sat INTEGER NOT NULL,
+    order_total_sat INTEGER NOT NULL,
+    bolt11_invoice TEXT NOT NULL,
+    payment_expires_at DATETIME DEFAULT NULL,
+    funding_outpoint TEXT DEFAULT NULL, -- txid:vout
+    channel_funded_at DATETIME DEFAULT NULL,
+    channel_expires_at DATETIME DEFAULT NULL,
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (lsp_url, lsp_order_id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE INDEX idx_liquidity_orders_account_node ON liquidity_orders(account_id, node_id);
+CREATE INDEX idx_liquidity_orders_funding_outpoint ON liquidity_orders(node_id, funding_outpoint);
+
+CREATE TRIGGER liquidity_orders_updated_at
+    AFTER UPDATE ON liquidity_orders
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE liquidity_orders SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
@@ -0,0 +1,38 @@
+-- Boltz swaps used to rebalance a node. Submarine swaps move on-chain funds
+-- into the node's channels; reverse swaps move channel balance on-chain.
+-- status is Boltz's own status string (e.g. "transaction.claimed").
+CREATE TABLE IF NOT EXISTS swaps (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    swap_type TEXT NOT NULL,
+    boltz_swap_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    is_final BOOLEAN NOT NULL DEFAULT 0,
+    amount_sat INTEGER NOT NULL, -- lightning side
+    onchain_amount_sat INTEGER NOT NULL,
+    service_fee_sat INTEGER NOT NULL,
+    miner_fee_sat INTEGER NOT NULL,
+    invoice TEXT NOT NULL,
+    lockup_address TEXT NOT NULL,
+    timeout_block_height INTEGER NOT NULL,
+    channel_id TEXT DEFAULT NULL, -- channel the swap rebalances, if pinned
+    rescue_data TEXT NOT NULL, -- encrypted keys and swap tree
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE INDEX idx_swaps_account_node ON swaps(account_id, node_id);
+CREATE INDEX idx_swaps_is_final ON swaps(is_final);
+CREATE INDEX idx_swaps_channel ON swaps(node_id, channel_id);
+
+CREATE TRIGGER swaps_updated_at
+    AFTER UPDATE ON swaps
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE swaps SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
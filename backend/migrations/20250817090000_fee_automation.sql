@@ -0,0 +1,45 @@
+-- Fee automation rules for an account's nodes. rules is a JSON array of
+-- {"min_local_pct", "max_local_pct", "fee_rate_ppm", "base_fee_msat"} objects,
+-- evaluated in order against each channel's local balance.
+CREATE TABLE IF NOT EXISTS fee_automation_policies (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    is_enabled BOOLEAN NOT NULL DEFAULT 0,
+    dry_run BOOLEAN NOT NULL DEFAULT 0,
+    rules TEXT NOT NULL DEFAULT '[]',
+    updated_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, node_id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id)
+);
+
+CREATE TRIGGER fee_automation_policies_updated_at
+    AFTER UPDATE ON fee_automation_policies
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE fee_automation_policies SET updated_at = CURRENT_TIMESTAMP
+    WHERE account_id = NEW.account_id AND node_id = NEW.node_id;
+END;
+
+-- Every fee change fee automation made or, in dry-run mode, would have made.
+CREATE TABLE IF NOT EXISTS fee_policy_changes (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    channel_id TEXT NOT NULL,
+    rule_index INTEGER NOT NULL,
+    local_balance_pct REAL NOT NULL,
+    old_base_fee_msat INTEGER,
+    old_fee_rate_ppm INTEGER,
+    new_base_fee_msat INTEGER NOT NULL,
+    new_fee_rate_ppm INTEGER NOT NULL,
+    dry_run BOOLEAN NOT NULL,
+    error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_fee_policy_changes_node ON fee_policy_changes(account_id, node_id, created_at);
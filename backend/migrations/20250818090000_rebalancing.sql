@@ -0,0 +1,49 @@
+-- Auto-rebalancing settings for an account's nodes. targets is a JSON array
+-- of {"channel_id", "target_local_pct"} objects; blackout_windows is a JSON
+-- array of {"starts_at", "ends_at"} objects.
+CREATE TABLE IF NOT EXISTS rebalance_policies (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    is_enabled BOOLEAN NOT NULL DEFAULT 0,
+    targets TEXT NOT NULL DEFAULT '[]',
+    tolerance_pct REAL NOT NULL,
+    max_fee_ppm INTEGER NOT NULL,
+    monthly_budget_sat INTEGER NOT NULL,
+    blackout_windows TEXT NOT NULL DEFAULT '[]',
+    updated_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, node_id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id)
+);
+
+CREATE TRIGGER rebalance_policies_updated_at
+    AFTER UPDATE ON rebalance_policies
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE rebalance_policies SET updated_at = CURRENT_TIMESTAMP
+    WHERE account_id = NEW.account_id AND node_id = NEW.node_id;
+END;
+
+-- Every circular rebalance attempted, manual or automatic. route is a JSON
+-- array of {"pubkey", "channel_id", "amount_to_forward_sat", "fee_sat"} hops.
+CREATE TABLE IF NOT EXISTS rebalance_attempts (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    outgoing_channel_id TEXT NOT NULL,
+    incoming_channel_id TEXT NOT NULL,
+    amount_sat INTEGER NOT NULL,
+    fee_msat INTEGER NOT NULL DEFAULT 0,
+    route TEXT NOT NULL DEFAULT '[]',
+    succeeded BOOLEAN NOT NULL,
+    error TEXT,
+    is_automatic BOOLEAN NOT NULL,
+    created_by TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_rebalance_attempts_node ON rebalance_attempts(account_id, node_id, created_at);
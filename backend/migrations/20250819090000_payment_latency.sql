@@ -0,0 +1,17 @@
+-- Time-to-settle of outgoing payments, recorded by the PaymentLatency task.
+-- htlc_ms and first_hop_channel_id are only known for LND nodes.
+CREATE TABLE IF NOT EXISTS payment_latencies (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    payment_hash TEXT NOT NULL,
+    destination TEXT,
+    first_hop_channel_id TEXT,
+    settle_ms INTEGER NOT NULL,
+    htlc_ms INTEGER,
+    settled_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, node_id, payment_hash),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_payment_latencies_settled ON payment_latencies(account_id, node_id, settled_at);
@@ -0,0 +1,11 @@
+-- Node event types an account has stopped ingesting. The event writer drops
+-- events of a paused type instead of storing them.
+CREATE TABLE IF NOT EXISTS paused_event_types (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    paused_by TEXT NOT NULL,
+    paused_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, node_id, event_type),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,12 @@
+-- Peer metadata fetched from Amboss and 1ML, cached per public key.
+-- community_tags is a JSON array of tag names.
+CREATE TABLE IF NOT EXISTS peer_metadata (
+    pubkey TEXT PRIMARY KEY,
+    community_tags TEXT NOT NULL DEFAULT '[]',
+    rank INTEGER,
+    email TEXT,
+    twitter TEXT,
+    telegram TEXT,
+    website TEXT,
+    fetched_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
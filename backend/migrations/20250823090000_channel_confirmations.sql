@@ -0,0 +1,22 @@
+-- Funding and closing transactions of pending channels, followed block by
+-- block until final. confirmation_height stays NULL while the transaction is
+-- unconfirmed; notified_confirmations is the last milestone (1, 3 or 6)
+-- reported as an event.
+CREATE TABLE IF NOT EXISTS channel_confirmations (
+    node_id TEXT NOT NULL,
+    txid TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    node_alias TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('opening', 'closing')),
+    channel_point TEXT NOT NULL,
+    remote_pubkey TEXT NOT NULL,
+    confirmation_height INTEGER,
+    notified_confirmations INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (node_id, txid),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_channel_confirmations_pending
+    ON channel_confirmations(node_id, notified_confirmations);
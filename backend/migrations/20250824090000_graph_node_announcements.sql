@@ -0,0 +1,5 @@
+-- Addresses and feature bits from each node's announcement, as JSON arrays.
+-- NULL until the node is next synced, so announcements mirrored before these
+-- columns existed don't read as changed.
+ALTER TABLE graph_nodes ADD COLUMN addresses TEXT DEFAULT NULL;
+ALTER TABLE graph_nodes ADD COLUMN features TEXT DEFAULT NULL;
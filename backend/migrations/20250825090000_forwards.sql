@@ -0,0 +1,32 @@
+-- Settled forwards, written from the HTLC event stream as they settle and
+-- backfilled by the ForwardHistory task. HTLC ids and received_at are only
+-- known for forwards seen on the stream or reported by CLN.
+CREATE TABLE IF NOT EXISTS forwards (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    chan_id_in TEXT NOT NULL,
+    chan_id_out TEXT NOT NULL,
+    incoming_htlc_id INTEGER,
+    outgoing_htlc_id INTEGER,
+    amt_in_msat INTEGER NOT NULL,
+    amt_out_msat INTEGER NOT NULL,
+    fee_msat INTEGER NOT NULL,
+    received_at DATETIME,
+    settled_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_forwards_circuit ON forwards(account_id, node_id, chan_id_in, incoming_htlc_id)
+    WHERE incoming_htlc_id IS NOT NULL;
+CREATE INDEX idx_forwards_settled ON forwards(account_id, node_id, settled_at);
+
+-- How far back each node's forwarding history has been read into forwards.
+CREATE TABLE IF NOT EXISTS forward_backfills (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    backfilled_until DATETIME NOT NULL,
+    PRIMARY KEY (account_id, node_id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,15 @@
+-- Planned maintenance of a node. Events raised during a window are labelled
+-- and, unless critical, not sent to notification endpoints.
+CREATE TABLE IF NOT EXISTS node_maintenance_windows (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    starts_at DATETIME NOT NULL,
+    ends_at DATETIME NOT NULL,
+    reason TEXT DEFAULT NULL,
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_node_maintenance_windows_node ON node_maintenance_windows(account_id, node_id, ends_at);
@@ -0,0 +1,3 @@
+-- Limits a notification endpoint to events touching one channel, given as a
+-- short channel id (stored as the integer form) or a channel point.
+ALTER TABLE notifications ADD COLUMN channel TEXT DEFAULT NULL;
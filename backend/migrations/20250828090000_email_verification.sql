@@ -0,0 +1,5 @@
+-- When the user confirmed they receive mail at their current address.
+-- Users created before verification existed are treated as verified.
+ALTER TABLE users ADD COLUMN email_verified_at DATETIME DEFAULT NULL;
+
+UPDATE users SET email_verified_at = CURRENT_TIMESTAMP;
@@ -0,0 +1,15 @@
+-- Names and notes operators give their nodes, kept apart from credentials so
+-- they survive a node being registered again.
+CREATE TABLE IF NOT EXISTS node_labels (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    display_name TEXT DEFAULT NULL,
+    environment TEXT DEFAULT NULL,
+    notes TEXT DEFAULT NULL,
+    updated_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, node_id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id)
+);
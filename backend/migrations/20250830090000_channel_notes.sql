@@ -0,0 +1,19 @@
+-- Notes operators keep on a node's channels and peers. target_id is the short
+-- channel id of a channel or the public key of a peer; label is the short tag
+-- channel lists can be filtered by, e.g. "ring of fire #42" or "do not close".
+CREATE TABLE IF NOT EXISTS channel_notes (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    target_type TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    label TEXT NOT NULL,
+    note TEXT DEFAULT NULL,
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE INDEX idx_channel_notes_node ON channel_notes(account_id, node_id, target_type, target_id);
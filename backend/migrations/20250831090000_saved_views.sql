@@ -0,0 +1,15 @@
+-- Named filter configurations users save for list endpoints. filters is a
+-- JSON object of query parameters; sort is passed through as given.
+CREATE TABLE IF NOT EXISTS saved_views (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    endpoint TEXT NOT NULL,
+    filters TEXT NOT NULL DEFAULT '{}',
+    sort TEXT DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, name),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
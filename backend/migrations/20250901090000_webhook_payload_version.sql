@@ -0,0 +1,3 @@
+-- Schema version of the webhook body an endpoint receives. Endpoints created
+-- before bodies were versioned keep the original flat body, version 1.
+ALTER TABLE notifications ADD COLUMN payload_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,5 @@
+-- Keysend receipts have no invoice of their own; keep the message the payer
+-- attached alongside the synced payment and the invoice the node made for it.
+ALTER TABLE synced_payments ADD COLUMN keysend_message TEXT DEFAULT NULL;
+ALTER TABLE synced_invoices ADD COLUMN is_keysend BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE synced_invoices ADD COLUMN keysend_message TEXT DEFAULT NULL;
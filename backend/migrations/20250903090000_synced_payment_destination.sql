@@ -0,0 +1,5 @@
+-- Payee of each outgoing synced payment, read from its invoice, so payments
+-- can be filtered by who they went to.
+ALTER TABLE synced_payments ADD COLUMN destination_pubkey TEXT DEFAULT NULL;
+
+CREATE INDEX idx_synced_payments_destination ON synced_payments(account_id, node_id, destination_pubkey);
@@ -0,0 +1,6 @@
+-- Amount actually paid for each synced invoice, and indexes for searching
+-- invoices by memo and by amount paid.
+ALTER TABLE synced_invoices ADD COLUMN amt_paid_msat INTEGER DEFAULT NULL;
+
+CREATE INDEX idx_synced_invoices_memo ON synced_invoices(account_id, node_id, memo);
+CREATE INDEX idx_synced_invoices_amt_paid ON synced_invoices(account_id, node_id, amt_paid_msat);
@@ -0,0 +1,3 @@
+-- Payments imported from other tools' history exports, tagged with the tool.
+-- A resync keeps them, except where the node itself reports the payment.
+ALTER TABLE synced_payments ADD COLUMN imported_from TEXT DEFAULT NULL;
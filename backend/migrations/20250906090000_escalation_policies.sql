@@ -0,0 +1,53 @@
+-- Escalation chains for critical events. steps is a JSON array of
+-- {"notification_id", "delay_minutes"}: each endpoint is notified in turn
+-- while the event stays unacknowledged.
+CREATE TABLE IF NOT EXISTS escalation_policies (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    node_id TEXT DEFAULT NULL, -- NULL for every node of the account
+    steps TEXT NOT NULL DEFAULT '[]',
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (account_id, name),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+-- One critical event going through a policy's chain. steps_sent counts the
+-- steps notified so far.
+CREATE TABLE IF NOT EXISTS escalations (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    policy_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'Pending', -- Pending, Acknowledged, Exhausted or Cancelled
+    steps_sent INTEGER NOT NULL DEFAULT 0,
+    next_step_at DATETIME DEFAULT NULL,
+    acknowledged_by TEXT DEFAULT NULL,
+    acknowledged_at DATETIME DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (policy_id) REFERENCES escalation_policies(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_escalations_account_status ON escalations(account_id, status, created_at);
+
+CREATE TRIGGER escalation_policies_updated_at
+    AFTER UPDATE ON escalation_policies
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE escalation_policies SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER escalations_updated_at
+    AFTER UPDATE ON escalations
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE escalations SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
@@ -0,0 +1,42 @@
+-- Route probing settings for an account's nodes. targets is a JSON array of
+-- {"destination", "outgoing_channel_id"} objects, the channel being optional.
+CREATE TABLE IF NOT EXISTS probe_policies (
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    is_enabled BOOLEAN NOT NULL DEFAULT 0,
+    targets TEXT NOT NULL DEFAULT '[]',
+    amount_sat INTEGER NOT NULL,
+    max_fee_sat INTEGER NOT NULL,
+    updated_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, node_id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id)
+);
+
+CREATE TRIGGER probe_policies_updated_at
+    AFTER UPDATE ON probe_policies
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE probe_policies SET updated_at = CURRENT_TIMESTAMP
+    WHERE account_id = NEW.account_id AND node_id = NEW.node_id;
+END;
+
+-- Every probe sent by the RouteProbe task. reached means the destination
+-- rejected the probe's unknown payment hash, so the route carried it.
+CREATE TABLE IF NOT EXISTS probe_results (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    outgoing_channel_id TEXT,
+    reached BOOLEAN NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    failure_reason TEXT,
+    probed_at DATETIME NOT NULL,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_probe_results_node ON probe_results(account_id, node_id, probed_at);
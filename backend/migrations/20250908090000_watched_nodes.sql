@@ -0,0 +1,18 @@
+-- External nodes watched through the graph mirror of one of the account's
+-- nodes. They have no credentials; snapshot holds the announcement, channels
+-- and fee policies seen at the last check, as JSON, for spotting changes.
+CREATE TABLE IF NOT EXISTS watched_nodes (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    pubkey TEXT NOT NULL,
+    label TEXT DEFAULT NULL,
+    snapshot TEXT DEFAULT NULL,
+    last_checked_at DATETIME DEFAULT NULL,
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (account_id, node_id, pubkey),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_watched_nodes_node ON watched_nodes(node_id);
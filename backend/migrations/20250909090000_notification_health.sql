@@ -0,0 +1,16 @@
+-- Latest periodic check of each notification endpoint. failing_since is when
+-- the current run of failed checks started and alerted_at when that run was
+-- reported, so an endpoint that stays down raises a single event.
+CREATE TABLE IF NOT EXISTS notification_health (
+    notification_id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    healthy BOOLEAN NOT NULL,
+    last_error TEXT DEFAULT NULL,
+    last_checked_at DATETIME NOT NULL,
+    failing_since DATETIME DEFAULT NULL,
+    alerted_at DATETIME DEFAULT NULL,
+    FOREIGN KEY (notification_id) REFERENCES notifications(id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_notification_health_account ON notification_health(account_id);
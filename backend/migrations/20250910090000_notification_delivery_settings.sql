@@ -0,0 +1,7 @@
+-- Per-endpoint delivery settings. timeout_secs overrides the dispatcher's
+-- default request timeout; headers holds an encrypted JSON object of extra
+-- request headers; verify_tls can be turned off for internal endpoints with
+-- self-signed certificates.
+ALTER TABLE notifications ADD COLUMN timeout_secs INTEGER DEFAULT NULL;
+ALTER TABLE notifications ADD COLUMN headers TEXT DEFAULT NULL;
+ALTER TABLE notifications ADD COLUMN verify_tls BOOLEAN NOT NULL DEFAULT 1;
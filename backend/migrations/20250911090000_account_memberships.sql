@@ -0,0 +1,19 @@
+-- Accounts a user belongs to besides the one they signed up to. The home
+-- account keeps living on the users row; each extra membership carries its
+-- own role, so a consultant can be an analyst in one operator's account and
+-- a member in another.
+CREATE TABLE IF NOT EXISTS account_memberships (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    role_access_level TEXT NOT NULL DEFAULT 'Read',
+    created_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, account_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_account_memberships_account ON account_memberships(account_id);
@@ -0,0 +1,17 @@
+-- Node access scoping for multi-node accounts. Roles and invites may list
+-- the node IDs they grant as a JSON array; NULL grants every node of the
+-- account. A user's own scope within an account, taken from the invite or
+-- membership that brought them in, is kept in user_node_scopes; without a
+-- row the user is limited by their role alone.
+ALTER TABLE roles ADD COLUMN node_ids TEXT DEFAULT NULL;
+
+ALTER TABLE invites ADD COLUMN node_ids TEXT DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS user_node_scopes (
+    user_id TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    node_ids TEXT NOT NULL,
+    PRIMARY KEY (user_id, account_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- Daily per-account usage for fair-use enforcement and billing. Rows outlive
+-- the events and deliveries they count, which retention may prune.
+CREATE TABLE IF NOT EXISTS account_usage (
+    account_id TEXT NOT NULL,
+    day DATE NOT NULL, -- UTC day
+    api_requests INTEGER NOT NULL DEFAULT 0,
+    events_stored INTEGER NOT NULL DEFAULT 0,
+    notifications_sent INTEGER NOT NULL DEFAULT 0,
+    nodes_connected INTEGER NOT NULL DEFAULT 0, -- most connected at once during the day
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, day),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
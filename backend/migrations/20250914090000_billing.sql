@@ -0,0 +1,17 @@
+-- Billing for hosted deployments. Accounts without a row are on the Free
+-- plan; the Stripe IDs are filled in by checkout and kept current by the
+-- webhook.
+CREATE TABLE IF NOT EXISTS account_subscriptions (
+    account_id TEXT PRIMARY KEY,
+    plan TEXT NOT NULL, -- Free, Pro or Business
+    status TEXT NOT NULL, -- Active, Trialing, PastDue, Canceled or Incomplete
+    stripe_customer_id TEXT DEFAULT NULL,
+    stripe_subscription_id TEXT DEFAULT NULL,
+    current_period_end DATETIME DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_account_subscriptions_stripe_subscription_id
+ON account_subscriptions(stripe_subscription_id);
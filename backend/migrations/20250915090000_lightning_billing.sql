@@ -0,0 +1,25 @@
+-- Lightning billing: plans paid with monthly BOLT11 invoices from the
+-- operator's treasury node instead of through Stripe.
+ALTER TABLE account_subscriptions ADD COLUMN provider TEXT NOT NULL DEFAULT 'Stripe'; -- Stripe or Lightning
+
+-- Set while the account is suspended for an unpaid invoice, so paying it
+-- reactivates only accounts that billing suspended
+ALTER TABLE account_subscriptions ADD COLUMN suspended_at DATETIME DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS billing_invoices (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    plan TEXT NOT NULL,
+    amount_sat INTEGER NOT NULL,
+    payment_request TEXT NOT NULL,
+    payment_hash TEXT NOT NULL UNIQUE,
+    period_start DATETIME NOT NULL,
+    period_end DATETIME NOT NULL,
+    status TEXT NOT NULL DEFAULT 'Open', -- Open, Paid or Expired
+    expires_at DATETIME NOT NULL,
+    paid_at DATETIME DEFAULT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_billing_invoices_account_id ON billing_invoices(account_id, status);
@@ -0,0 +1,3 @@
+-- Peer of each synced channel, so the channel list can be served from the
+-- last sync while the node is unreachable.
+ALTER TABLE synced_channels ADD COLUMN remote_pubkey TEXT DEFAULT NULL;
@@ -0,0 +1,3 @@
+-- Amounts are reported in their native sat/msat fields and no endpoint
+-- converted them, so the stored default unit was never applied.
+ALTER TABLE account_settings DROP COLUMN default_unit;
@@ -0,0 +1,18 @@
+-- Lightning billing runs once, system-wide: per-account copies of the task
+-- each renewed every account and issued duplicate invoices.
+DELETE FROM scheduled_tasks WHERE task_type = 'LightningBilling' AND account_id IS NOT NULL;
+
+-- Keep one invoice per billing period, preferring a paid one, before making
+-- the period unique.
+DELETE FROM billing_invoices
+WHERE EXISTS (
+    SELECT 1 FROM billing_invoices AS kept
+    WHERE kept.account_id = billing_invoices.account_id
+      AND kept.period_start = billing_invoices.period_start
+      AND (
+          (kept.status = 'Paid' AND billing_invoices.status != 'Paid')
+          OR ((kept.status = 'Paid') = (billing_invoices.status = 'Paid') AND kept.id < billing_invoices.id)
+      )
+);
+
+CREATE UNIQUE INDEX idx_billing_invoices_account_period_unique ON billing_invoices(account_id, period_start);
@@ -0,0 +1,58 @@
+-- Key the full-text index by the events' rowid instead of an unindexed
+-- event_id column, so the update and delete triggers find an event's row
+-- by index instead of scanning the whole index.
+DROP TRIGGER IF EXISTS events_fts_insert;
+DROP TRIGGER IF EXISTS events_fts_update;
+DROP TRIGGER IF EXISTS events_fts_delete;
+DROP TABLE IF EXISTS events_fts;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(
+    title,
+    description,
+    data_values,
+    tokenize = 'unicode61'
+);
+
+INSERT INTO events_fts (rowid, title, description, data_values)
+SELECT
+    e.rowid,
+    e.title,
+    e.description,
+    (SELECT group_concat(j.value, ' ') FROM json_tree(e.data) j WHERE j.type NOT IN ('object', 'array'))
+FROM events e
+WHERE json_valid(e.data);
+
+CREATE TRIGGER events_fts_insert
+    AFTER INSERT ON events
+BEGIN
+    INSERT INTO events_fts (rowid, title, description, data_values)
+    VALUES (
+        NEW.rowid,
+        NEW.title,
+        NEW.description,
+        CASE WHEN json_valid(NEW.data) THEN
+            (SELECT group_concat(j.value, ' ') FROM json_tree(NEW.data) j WHERE j.type NOT IN ('object', 'array'))
+        END
+    );
+END;
+
+CREATE TRIGGER events_fts_update
+    AFTER UPDATE OF title, description, data ON events
+BEGIN
+    DELETE FROM events_fts WHERE rowid = OLD.rowid;
+    INSERT INTO events_fts (rowid, title, description, data_values)
+    VALUES (
+        NEW.rowid,
+        NEW.title,
+        NEW.description,
+        CASE WHEN json_valid(NEW.data) THEN
+            (SELECT group_concat(j.value, ' ') FROM json_tree(NEW.data) j WHERE j.type NOT IN ('object', 'array'))
+        END
+    );
+END;
+
+CREATE TRIGGER events_fts_delete
+    AFTER DELETE ON events
+BEGIN
+    DELETE FROM events_fts WHERE rowid = OLD.rowid;
+END;
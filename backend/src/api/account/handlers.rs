@@ -6,11 +6,21 @@
 use crate::api::common::{
     ApiResponse, PaginatedData, PaginationFilter, PaginationMeta, service_error_to_http,
 };
-use crate::database::models::{Account, CreateNewAccount, User, UserWithAccount};
+use crate::config::Config;
+use crate::database::models::{
+    Account, AccountMembership, AccountSettings, AccountUsage, AccountUsageQuery,
+    AddAccountMemberRequest, CreateNewAccount, RetentionPreview, RetentionSettings,
+    UpdateAccountSettingsRequest, UpdateRetentionRequest, User, UserWithAccount,
+};
+use crate::errors::ErrorCode;
 use crate::services::account_service::AccountService;
+use crate::services::account_settings_service::AccountSettingsService;
+use crate::services::profile_service::ProfileService;
+use crate::services::retention_service::RetentionService;
+use crate::services::usage_meter::UsageService;
 use crate::services::user_service::UserService;
 use crate::utils::jwt::Claims;
-use axum::extract::Query;
+use axum::extract::{Path, Query};
 use axum::{
     extract::{Extension, Json},
     http::StatusCode,
@@ -25,11 +35,20 @@ pub async fn create_account(
 ) -> Result<ResponseJson<ApiResponse<UserWithAccount>>, (StatusCode, String)> {
     tracing::info!("Creating new account with payload: {:?}", payload);
 
+    let config = Config::from_env().map_err(|e| service_error_to_http(e.into()))?;
     let service = AccountService::new(&pool);
-
-    match service.create_account(payload).await {
+    match service
+        .register_account(payload, config.registration_enabled)
+        .await
+    {
         Ok(account) => {
             tracing::debug!("Account created successfully: {:?}", account);
+            if let Err(e) = ProfileService::new(&pool, &config)
+                .start_email_verification(&account.user)
+                .await
+            {
+                tracing::warn!("Failed to start email verification: {}", e);
+            }
             Ok(ResponseJson(ApiResponse::success(
                 account,
                 "Account created successfully",
@@ -57,7 +76,7 @@ pub async fn get_account(
             tracing::error!("Account not found for ID {}: {}", account_id, e);
             let error_response = ApiResponse::<()>::error(
                 "Account not found".to_string(),
-                "account_not_found",
+                ErrorCode::NotFound,
                 None,
             );
             (
@@ -92,7 +111,7 @@ pub async fn get_account_admin_user(
             tracing::error!("Admin user not found for account ID {}: {}", account_id, e);
             let error_response = ApiResponse::<()>::error(
                 "Admin user not found".to_string(),
-                "admin_user_not_found",
+                ErrorCode::NotFound,
                 None,
             );
             (
@@ -132,7 +151,7 @@ pub async fn get_account_users(
         .map_err(|e| {
             tracing::error!("Users not found for account ID {}: {}", account_id, e);
             let error_response =
-                ApiResponse::<()>::error("Users not found".to_string(), "users_not_found", None);
+                ApiResponse::<()>::error("Users not found".to_string(), ErrorCode::NotFound, None);
             (
                 StatusCode::NOT_FOUND,
                 serde_json::to_string(&error_response).unwrap(),
@@ -153,3 +172,137 @@ pub async fn get_account_users(
         pagination_meta,
     )))
 }
+
+/// Retrieves the account's data retention settings.
+#[axum::debug_handler]
+pub async fn get_retention_settings(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<Json<ApiResponse<RetentionSettings>>, (StatusCode, String)> {
+    let service = RetentionService::new(&pool);
+    match service.get_settings(claims.account_id()).await {
+        Ok(settings) => Ok(Json(ApiResponse::success(
+            settings,
+            "Retention settings retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the account's daily usage over the requested number of days.
+#[axum::debug_handler]
+pub async fn get_account_usage(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Query(query): Query<AccountUsageQuery>,
+) -> Result<Json<ApiResponse<AccountUsage>>, (StatusCode, String)> {
+    let service = UsageService::new(&pool);
+    match service.get_usage(claims.account_id(), query.days).await {
+        Ok(usage) => Ok(Json(ApiResponse::success(
+            usage,
+            "Account usage retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Estimates how much data the proposed retention settings would remove.
+#[axum::debug_handler]
+pub async fn preview_retention_settings(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<UpdateRetentionRequest>,
+) -> Result<Json<ApiResponse<RetentionPreview>>, (StatusCode, String)> {
+    let service = RetentionService::new(&pool);
+    match service.preview(claims.account_id(), payload).await {
+        Ok(preview) => Ok(Json(ApiResponse::success(
+            preview,
+            "Retention preview calculated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Replaces the account's data retention settings.
+#[axum::debug_handler]
+pub async fn update_retention_settings(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<UpdateRetentionRequest>,
+) -> Result<Json<ApiResponse<RetentionSettings>>, (StatusCode, String)> {
+    let service = RetentionService::new(&pool);
+    match service.update_settings(claims.account_id(), payload).await {
+        Ok(settings) => Ok(Json(ApiResponse::success(
+            settings,
+            "Retention settings updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Retrieves the account's display settings.
+#[axum::debug_handler]
+pub async fn get_account_settings(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<Json<ApiResponse<AccountSettings>>, (StatusCode, String)> {
+    let service = AccountSettingsService::new(&pool);
+    match service.get_settings(claims.account_id()).await {
+        Ok(settings) => Ok(Json(ApiResponse::success(
+            settings,
+            "Account settings retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Updates the account's display settings.
+#[axum::debug_handler]
+pub async fn update_account_settings(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<UpdateAccountSettingsRequest>,
+) -> Result<Json<ApiResponse<AccountSettings>>, (StatusCode, String)> {
+    let service = AccountSettingsService::new(&pool);
+    match service.update_settings(claims.account_id(), payload).await {
+        Ok(settings) => Ok(Json(ApiResponse::success(
+            settings,
+            "Account settings updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Adds an existing user to the account.
+#[axum::debug_handler]
+pub async fn add_account_member(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<AddAccountMemberRequest>,
+) -> Result<Json<ApiResponse<AccountMembership>>, (StatusCode, String)> {
+    let service = AccountService::new(&pool);
+    match service.add_member(&claims, payload).await {
+        Ok(membership) => Ok(Json(ApiResponse::success(
+            membership,
+            "Account member added successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Removes a user who joined from another account.
+#[axum::debug_handler]
+pub async fn remove_account_member(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, String)> {
+    let service = AccountService::new(&pool);
+    match service.remove_member(&claims, &user_id).await {
+        Ok(()) => Ok(Json(ApiResponse::success(
+            (),
+            "Account member removed successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
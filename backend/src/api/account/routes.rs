@@ -3,11 +3,15 @@
 //! These routes provide endpoints for accessing and updating account-specific
 //! data.
 
-use super::handlers::{create_account, get_account, get_account_admin_user, get_account_users};
-use crate::auth::middleware::jwt_auth;
+use super::handlers::{
+    add_account_member, create_account, get_account, get_account_admin_user, get_account_settings,
+    get_account_usage, get_account_users, get_retention_settings, preview_retention_settings,
+    remove_account_member, update_account_settings, update_retention_settings,
+};
+use crate::auth::middleware::{admin_auth, jwt_auth};
 use axum::{
     Router, middleware,
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
 
 pub async fn account_router() -> Router {
@@ -25,4 +29,45 @@ pub async fn account_router() -> Router {
             "/get-account-users",
             get(get_account_users).layer(middleware::from_fn(jwt_auth)),
         )
+        .route(
+            "/settings",
+            get(get_account_settings).layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/settings",
+            put(update_account_settings)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/retention",
+            get(get_retention_settings)
+                .put(update_retention_settings)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/retention/preview",
+            post(preview_retention_settings)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/usage",
+            get(get_account_usage)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/members",
+            post(add_account_member)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/members/{user_id}",
+            delete(remove_account_member)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
 }
@@ -0,0 +1,262 @@
+//! Handler functions for administration API endpoints.
+
+use crate::api::common::{
+    ApiResponse, PaginatedData, PaginationFilter, PaginationMeta, service_error_to_http,
+    validation_error_response,
+};
+use crate::config::Config;
+use crate::database::models::{
+    JobFilters, JobResponse, JobStatus, ScheduledTask, UpdateScheduledTaskRequest,
+};
+use crate::services::account_service::AccountService;
+use crate::services::event_manager::{EventWriterMetrics, event_writer_metrics};
+use crate::services::job_queue::JobQueue;
+use crate::services::key_rotation::{EncryptionStatus, KeyRotationService, RekeyReport};
+use crate::services::node_breaker::{NodeBreakerMetrics, account_breaker_metrics};
+use crate::services::polar_bootstrap::{
+    BootstrappedNode, PolarBootstrapRequest, PolarBootstrapService,
+};
+use crate::services::scheduler::SchedulerService;
+use crate::services::subscription_health::{
+    SubscriptionKind, SubscriptionService, SubscriptionStats,
+};
+use crate::utils::jwt::Claims;
+use axum::{
+    Json,
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    response::Json as ResponseJson,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use validator::Validate;
+
+/// Most running jobs listed in the task overview
+const MAX_RUNNING_JOBS: i64 = 100;
+
+/// Everything running in the background for an account
+#[derive(Debug, Serialize)]
+pub struct BackgroundTasks {
+    pub scheduled: Vec<ScheduledTask>,
+    /// Streams held open against the account's nodes
+    pub subscriptions: Vec<SubscriptionStats>,
+    pub running_jobs: Vec<JobResponse>,
+}
+
+/// Lists background jobs for the account, and system-wide jobs for the
+/// operator.
+#[axum::debug_handler]
+pub async fn get_jobs(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Query(pagination): Query<PaginationFilter>,
+    Query(filters): Query<JobFilters>,
+) -> Result<ResponseJson<ApiResponse<PaginatedData<JobResponse>>>, (StatusCode, String)> {
+    if let Err(validation_errors) = pagination.validate() {
+        return Err(validation_error_response(validation_errors));
+    }
+
+    let config = Config::from_env().map_err(|e| service_error_to_http(e.into()))?;
+    let is_operator = AccountService::new(&pool)
+        .is_operator_account(&config, claims.account_id())
+        .await
+        .map_err(service_error_to_http)?;
+    let service = JobQueue::new(&pool);
+    let (jobs, total) = service
+        .get_jobs(
+            claims.account_id(),
+            is_operator,
+            filters,
+            pagination.limit(),
+            pagination.offset(),
+        )
+        .await
+        .map_err(service_error_to_http)?;
+
+    Ok(ResponseJson(ApiResponse::ok_paginated(
+        PaginatedData::new(jobs, total as u64),
+        PaginationMeta::from_filter(&pagination, total as u64),
+    )))
+}
+
+/// Lists everything running in the background: scheduled tasks with their
+/// last and next run (system-wide ones only for the operator), node
+/// subscriptions with their uptime and last error, and jobs currently being
+/// worked on (system-wide ones again only for the operator).
+#[axum::debug_handler]
+pub async fn get_tasks(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<ResponseJson<ApiResponse<BackgroundTasks>>, (StatusCode, String)> {
+    let account_id = claims.account_id();
+    let config = Config::from_env().map_err(|e| service_error_to_http(e.into()))?;
+    let is_operator = AccountService::new(&pool)
+        .is_operator_account(&config, account_id)
+        .await
+        .map_err(service_error_to_http)?;
+    let scheduled = SchedulerService::new(&pool)
+        .list_tasks(account_id, is_operator)
+        .await
+        .map_err(service_error_to_http)?;
+    let subscriptions = SubscriptionService::new(&pool)
+        .get_account_subscriptions(account_id)
+        .await
+        .map_err(service_error_to_http)?;
+    let (running_jobs, _) = JobQueue::new(&pool)
+        .get_jobs(
+            account_id,
+            is_operator,
+            JobFilters {
+                status: Some(JobStatus::Running),
+                job_type: None,
+            },
+            MAX_RUNNING_JOBS,
+            0,
+        )
+        .await
+        .map_err(service_error_to_http)?;
+
+    Ok(ResponseJson(ApiResponse::success(
+        BackgroundTasks {
+            scheduled,
+            subscriptions,
+            running_jobs,
+        },
+        "Background tasks retrieved successfully",
+    )))
+}
+
+/// Queues an immediate run of one of the account's scheduled tasks.
+#[axum::debug_handler]
+pub async fn run_task(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<ResponseJson<ApiResponse<JobResponse>>, (StatusCode, String)> {
+    let service = SchedulerService::new(&pool);
+    match service.run_task_now(claims.account_id(), &id).await {
+        Ok(job) => Ok(ResponseJson(ApiResponse::success(
+            JobResponse::from(job),
+            "Scheduled task queued successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Restarts one of a node's subscriptions, e.g. a stream that stopped
+/// delivering without disconnecting.
+#[axum::debug_handler]
+pub async fn restart_subscription(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path((node_id, kind)): Path<(String, SubscriptionKind)>,
+) -> Result<ResponseJson<ApiResponse<Vec<SubscriptionStats>>>, (StatusCode, String)> {
+    let service = SubscriptionService::new(&pool);
+    match service
+        .restart(claims.account_id(), claims.user_id(), &node_id, kind)
+        .await
+    {
+        Ok(subscriptions) => Ok(ResponseJson(ApiResponse::success(
+            subscriptions,
+            "Subscription restarted successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Updates the schedule or active flag of one of the account's tasks.
+#[axum::debug_handler]
+pub async fn update_task(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateScheduledTaskRequest>,
+) -> Result<ResponseJson<ApiResponse<ScheduledTask>>, (StatusCode, String)> {
+    let service = SchedulerService::new(&pool);
+    match service.update_task(claims.account_id(), &id, payload).await {
+        Ok(task) => Ok(ResponseJson(ApiResponse::success(
+            task,
+            "Scheduled task updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Reports how the batched event writer has been doing since startup.
+#[axum::debug_handler]
+pub async fn get_event_writer_metrics()
+-> Result<ResponseJson<ApiResponse<EventWriterMetrics>>, (StatusCode, String)> {
+    Ok(ResponseJson(ApiResponse::success(
+        event_writer_metrics(),
+        "Event writer metrics retrieved successfully",
+    )))
+}
+
+/// Reports the circuit breaker state of each of the account's nodes.
+#[axum::debug_handler]
+pub async fn get_node_breakers(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<ResponseJson<ApiResponse<Vec<NodeBreakerMetrics>>>, (StatusCode, String)> {
+    match account_breaker_metrics(&pool, claims.account_id()).await {
+        Ok(metrics) => Ok(ResponseJson(ApiResponse::success(
+            metrics,
+            "Node breaker metrics retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Reports the current encryption key version and how many stored secrets
+/// are still under older keys or not encrypted yet.
+#[axum::debug_handler]
+pub async fn get_encryption_status(
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<ResponseJson<ApiResponse<EncryptionStatus>>, (StatusCode, String)> {
+    match KeyRotationService::new(&pool).status().await {
+        Ok(status) => Ok(ResponseJson(ApiResponse::success(
+            status,
+            "Encryption status retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Re-encrypts every stored secret under the current encryption key.
+#[axum::debug_handler]
+pub async fn rekey_secrets(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<ResponseJson<ApiResponse<RekeyReport>>, (StatusCode, String)> {
+    match KeyRotationService::new(&pool)
+        .rekey(claims.account_id(), claims.user_id())
+        .await
+    {
+        Ok(report) => Ok(ResponseJson(ApiResponse::success(
+            report,
+            "Secrets rekeyed successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Registers the LND and CLN nodes of a local Polar network with the account
+/// and starts their event streams.
+#[axum::debug_handler]
+pub async fn bootstrap_polar_network(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<PolarBootstrapRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<BootstrappedNode>>>, (StatusCode, String)> {
+    let service = PolarBootstrapService::new(&pool);
+    match service
+        .bootstrap(claims.account_id(), claims.user_id(), payload)
+        .await
+    {
+        Ok(nodes) => Ok(ResponseJson(ApiResponse::success(
+            nodes,
+            "Polar network bootstrapped successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
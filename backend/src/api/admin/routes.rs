@@ -0,0 +1,42 @@
+//! Defines the HTTP routes for account administration.
+
+use super::handlers::{
+    bootstrap_polar_network, get_encryption_status, get_event_writer_metrics, get_jobs,
+    get_node_breakers, get_tasks, rekey_secrets, restart_subscription, run_task, update_task,
+};
+use crate::auth::middleware::{admin_auth, jwt_auth, operator_auth, require_node_access};
+use axum::{
+    Router, middleware,
+    routing::{get, post, put},
+};
+
+pub async fn admin_router() -> Router {
+    Router::new()
+        .route("/jobs", get(get_jobs))
+        .route("/tasks", get(get_tasks))
+        .route("/tasks/{id}", put(update_task))
+        .route("/tasks/{id}/run", post(run_task))
+        .route(
+            "/tasks/subscriptions/{node_id}/{kind}/restart",
+            post(restart_subscription).layer(middleware::from_fn(require_node_access)),
+        )
+        .route(
+            "/event-writer",
+            get(get_event_writer_metrics).layer(middleware::from_fn(operator_auth)),
+        )
+        .route("/node-breakers", get(get_node_breakers))
+        .route(
+            "/encryption",
+            get(get_encryption_status).layer(middleware::from_fn(operator_auth)),
+        )
+        .route(
+            "/encryption/rekey",
+            post(rekey_secrets).layer(middleware::from_fn(operator_auth)),
+        )
+        .route(
+            "/dev/bootstrap",
+            post(bootstrap_polar_network).layer(middleware::from_fn(operator_auth)),
+        )
+        .layer(middleware::from_fn(admin_auth))
+        .layer(middleware::from_fn(jwt_auth))
+}
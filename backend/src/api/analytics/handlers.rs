@@ -0,0 +1,187 @@
+//! Handler functions for the analytics API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{
+    ChannelRecommendation, ChannelRecommendationQuery, FailureHeatmap, FailureHeatmapQuery,
+    FeePositionReport, InvoiceLatencyQuery, InvoiceLatencyReport, LiquidityFlow,
+    LiquidityFlowQuery, NetworkPositionQuery, NetworkPositionResponse, PaymentLatencyQuery,
+    PaymentLatencyReport, PaymentRouteQuery, PaymentRouteReport,
+};
+use crate::services::channel_recommendations::ChannelRecommendationService;
+use crate::services::fee_position::FeePositionService;
+use crate::services::forward_failures::ForwardFailureService;
+use crate::services::invoice_latency::InvoiceLatencyService;
+use crate::services::liquidity_flow::LiquidityFlowService;
+use crate::services::network_position::NetworkPositionService;
+use crate::services::payment_latency::PaymentLatencyService;
+use crate::services::payment_routes::PaymentRouteService;
+use crate::utils::handlers_common::NodeContext;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Query},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Returns the centrality and capacity rank of the node in the token and its
+/// peers, along with the node's trend over the requested number of days.
+#[axum::debug_handler]
+pub async fn get_network_position(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<NetworkPositionQuery>,
+) -> Result<Json<ApiResponse<NetworkPositionResponse>>, (StatusCode, String)> {
+    match NetworkPositionService::new(&pool)
+        .get_position(node_context.node_id(), query.days)
+        .await
+    {
+        Ok(position) => Ok(Json(ApiResponse::success(
+            position,
+            "Network position retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Suggests new channel partners for the node in the token, best first.
+#[axum::debug_handler]
+pub async fn get_channel_recommendations(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<ChannelRecommendationQuery>,
+) -> Result<Json<ApiResponse<Vec<ChannelRecommendation>>>, (StatusCode, String)> {
+    match ChannelRecommendationService::new(&pool)
+        .get_recommendations(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(recommendations) => Ok(Json(ApiResponse::success(
+            recommendations,
+            "Channel recommendations retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns p50/p95 settle times of the node's outgoing payments, overall and
+/// per destination and first hop.
+#[axum::debug_handler]
+pub async fn get_payment_latency(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<PaymentLatencyQuery>,
+) -> Result<Json<ApiResponse<PaymentLatencyReport>>, (StatusCode, String)> {
+    match PaymentLatencyService::new(&pool)
+        .get_latency(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(report) => Ok(Json(ApiResponse::success(
+            report,
+            "Payment latency retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the node's failed forwards per outgoing peer and UTC hour of day.
+#[axum::debug_handler]
+pub async fn get_failure_heatmap(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<FailureHeatmapQuery>,
+) -> Result<Json<ApiResponse<FailureHeatmap>>, (StatusCode, String)> {
+    match ForwardFailureService::new(&pool)
+        .get_heatmap(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(heatmap) => Ok(Json(ApiResponse::success(
+            heatmap,
+            "Failure heatmap retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the node's forwarded volume per pair of incoming and outgoing
+/// channels over a date range, as Sankey nodes and links.
+#[axum::debug_handler]
+pub async fn get_liquidity_flow(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<LiquidityFlowQuery>,
+) -> Result<Json<ApiResponse<LiquidityFlow>>, (StatusCode, String)> {
+    match LiquidityFlowService::new(&pool)
+        .get_flow(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(flow) => Ok(Json(ApiResponse::success(
+            flow,
+            "Liquidity flow retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns how long the node's invoices took to settle and how many expired,
+/// overall and per day or week of a date range.
+#[axum::debug_handler]
+pub async fn get_invoice_latency(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<InvoiceLatencyQuery>,
+) -> Result<Json<ApiResponse<InvoiceLatencyReport>>, (StatusCode, String)> {
+    match InvoiceLatencyService::new(&pool)
+        .get_latency(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(report) => Ok(Json(ApiResponse::success(
+            report,
+            "Invoice latency retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Places the fee rate of each channel of the node in the token within the
+/// network and among competitors into the same peer, with suggested rates.
+#[axum::debug_handler]
+pub async fn get_fee_position(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<FeePositionReport>>, (StatusCode, String)> {
+    match FeePositionService::new(&pool)
+        .get_fee_position(node_context.node_id())
+        .await
+    {
+        Ok(report) => Ok(Json(ApiResponse::success(
+            report,
+            "Fee position retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Suggests fee limits and first hops for the destinations the node pays
+/// most often, flagging those it overpays.
+#[axum::debug_handler]
+pub async fn get_payment_routes(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<PaymentRouteQuery>,
+) -> Result<Json<ApiResponse<PaymentRouteReport>>, (StatusCode, String)> {
+    match PaymentRouteService::new(&pool)
+        .get_route_advice(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(report) => Ok(Json(ApiResponse::success(
+            report,
+            "Payment route advice retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -0,0 +1,10 @@
+//! Module for analytics API endpoints.
+//!
+//! This module serves metrics derived from the local graph mirror, such as
+//! where the node sits in the network, how its fees compare and who to open
+//! channels with, along with payment and invoice latency percentiles, fee
+//! advice for frequent payment destinations, a heatmap of failed forwards
+//! and where forwarded liquidity flows.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,68 @@
+//! Defines the HTTP routes for graph and payment analytics.
+
+use super::handlers::{
+    get_channel_recommendations, get_failure_heatmap, get_fee_position, get_invoice_latency,
+    get_liquidity_flow, get_network_position, get_payment_latency, get_payment_routes,
+};
+use crate::auth::middleware::{jwt_auth, node_credentials_required, require_node_read};
+use axum::{Router, middleware, routing::get};
+
+pub async fn analytics_router() -> Router {
+    Router::new()
+        .route(
+            "/network-position",
+            get(get_network_position)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/channel-recommendations",
+            get(get_channel_recommendations)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/failures",
+            get(get_failure_heatmap)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/flow",
+            get(get_liquidity_flow)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/fee-position",
+            get(get_fee_position)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/invoice-latency",
+            get(get_invoice_latency)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/latency",
+            get(get_payment_latency)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/payment-routes",
+            get(get_payment_routes)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+}
@@ -0,0 +1,131 @@
+//! Handler functions for the billing API endpoints.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{
+    BillingInvoice, BillingOverview, CheckoutSession, CreateCheckoutRequest,
+    LightningSubscribeRequest, PlanDetails,
+};
+use crate::errors::ServiceError;
+use crate::services::billing::BillingService;
+use crate::services::lightning_billing::LightningBillingService;
+use crate::utils::jwt::Claims;
+use axum::{
+    body::Bytes,
+    extract::{Extension, Json},
+    http::{HeaderMap, StatusCode},
+};
+use sqlx::SqlitePool;
+
+/// Lists the plans and what each allows.
+#[axum::debug_handler]
+pub async fn list_plans(
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<Json<ApiResponse<Vec<PlanDetails>>>, (StatusCode, String)> {
+    match BillingService::new(&pool).list_plans() {
+        Ok(plans) => Ok(Json(ApiResponse::success(
+            plans,
+            "Plans retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the account's plan, its limits and current usage.
+#[axum::debug_handler]
+pub async fn get_subscription(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<Json<ApiResponse<BillingOverview>>, (StatusCode, String)> {
+    match BillingService::new(&pool)
+        .get_overview(claims.account_id())
+        .await
+    {
+        Ok(overview) => Ok(Json(ApiResponse::success(
+            overview,
+            "Subscription retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Starts a Stripe checkout for a paid plan.
+#[axum::debug_handler]
+pub async fn create_checkout(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<CreateCheckoutRequest>,
+) -> Result<Json<ApiResponse<CheckoutSession>>, (StatusCode, String)> {
+    match BillingService::new(&pool)
+        .create_checkout(&claims, payload)
+        .await
+    {
+        Ok(session) => Ok(Json(ApiResponse::success(
+            session,
+            "Checkout session created successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Issues a Lightning invoice that subscribes the account to a paid plan.
+#[axum::debug_handler]
+pub async fn subscribe_lightning(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<LightningSubscribeRequest>,
+) -> Result<Json<ApiResponse<BillingInvoice>>, (StatusCode, String)> {
+    match LightningBillingService::new(&pool)
+        .subscribe(&claims, payload)
+        .await
+    {
+        Ok(invoice) => Ok(Json(ApiResponse::success(
+            invoice,
+            "Invoice created successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Lists the account's Lightning billing invoices.
+#[axum::debug_handler]
+pub async fn list_billing_invoices(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<Json<ApiResponse<Vec<BillingInvoice>>>, (StatusCode, String)> {
+    match LightningBillingService::new(&pool)
+        .list_invoices(claims.account_id())
+        .await
+    {
+        Ok(invoices) => Ok(Json(ApiResponse::success(
+            invoices,
+            "Invoices retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Receives Stripe webhook deliveries. The body is taken raw because the
+/// signature covers its exact bytes.
+#[axum::debug_handler]
+pub async fn stripe_webhook(
+    Extension(pool): Extension<SqlitePool>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, String)> {
+    let signature = headers
+        .get("Stripe-Signature")
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| {
+            service_error_to_http(ServiceError::permission_denied(
+                "Missing Stripe-Signature header",
+            ))
+        })?;
+
+    match BillingService::new(&pool)
+        .handle_webhook(&body, signature)
+        .await
+    {
+        Ok(()) => Ok(Json(ApiResponse::success((), "Webhook processed"))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -0,0 +1,7 @@
+//! Module for billing API endpoints.
+//!
+//! This module lists plans, reports the account's subscription and starts
+//! Stripe checkouts, and receives Stripe's webhook.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,40 @@
+//! Defines the HTTP routes for billing.
+//!
+//! Only admins may start a checkout or a Lightning subscription. The webhook
+//! is unauthenticated; Stripe's signature is checked instead.
+
+use super::handlers::{
+    create_checkout, get_subscription, list_billing_invoices, list_plans, stripe_webhook,
+    subscribe_lightning,
+};
+use crate::auth::middleware::{admin_auth, jwt_auth};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
+
+pub async fn billing_router() -> Router {
+    Router::new()
+        .route("/plans", get(list_plans))
+        .route(
+            "/subscription",
+            get(get_subscription).layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/checkout",
+            post(create_checkout)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/lightning/subscribe",
+            post(subscribe_lightning)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/invoices",
+            get(list_billing_invoices).layer(middleware::from_fn(jwt_auth)),
+        )
+        .route("/webhook", post(stripe_webhook))
+}
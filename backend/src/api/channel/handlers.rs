@@ -1,65 +1,376 @@
-use crate::utils::handlers_common::{
-    create_node_client, extract_node_credentials, handle_node_error, parse_public_key,
+use crate::database::models::{
+    ChannelNote, ChannelNoteQuery, ChannelRevenue, ChannelRevenueQuery, ChannelStats,
+    CreateChannelNoteRequest, OpenChannelEstimate, OpenChannelEstimateRequest,
+    UpdateChannelNoteRequest,
 };
+use crate::errors::ErrorCode;
+use crate::services::channel_confirmations::annotate_pending_channels;
+use crate::services::channel_costs::fill_onchain_fees;
+use crate::services::channel_notes::{ChannelNoteService, has_label, notes_for};
+use crate::services::channel_open_estimate::estimate_channel_open;
+use crate::services::channel_revenue::ChannelRevenueService;
+use crate::services::channel_stats::ChannelStatsService;
+use crate::services::liquidity_service::LiquidityService;
+use crate::services::node_breaker::NodeBreaker;
+use crate::services::node_sync::NodeSyncService;
+use crate::services::peer_channels::matches_peer;
+use crate::services::peer_enrichment::PeerEnrichmentService;
+use crate::services::swap_service::SwapService;
+use crate::utils::handlers_common::{NodeContext, handle_node_error};
 use crate::utils::jwt::Claims;
 use crate::{
     api::common::{
-        ApiResponse, FilterRequest, NumericOperator, PaginatedData, PaginationFilter,
-        PaginationMeta, apply_pagination, validation_error_response,
+        ApiResponse, FieldSelection, FilterRequest, NodeStatusMeta, NumericOperator, PaginatedData,
+        PaginationFilter, PaginationMeta, Sparse, apply_pagination, service_error_to_http,
+        validation_error_response,
     },
-    utils::{ChannelDetails, ChannelState, ChannelSummary, ShortChannelID},
+    utils::{ChannelDetails, ChannelState, ChannelSummary, PendingChannel, ShortChannelID},
 };
 use axum::{
     Json,
     extract::{Extension, Path, Query},
     http::StatusCode,
 };
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::SqlitePool;
 use std::str::FromStr;
 use validator::Validate;
 
 #[axum::debug_handler]
 pub async fn get_channel_info(
+    Extension(pool): Extension<SqlitePool>,
     Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
     Path(channel_id): Path<String>,
 ) -> Result<Json<ApiResponse<ChannelDetails>>, (StatusCode, String)> {
     let scid = parse_short_channel_id(&channel_id)?;
-    let node_credentials = extract_node_credentials(&claims)?;
-    let public_key = parse_public_key(&node_credentials.node_id)?;
 
-    let node_client = create_node_client(node_credentials, public_key).await?;
+    let node_client = node_context.connect().await?;
 
-    let channel_details = node_client
+    let mut channel_details = node_client
         .get_channel_info(&scid)
         .await
         .map_err(|e| handle_node_error(e, "get channel info"))?;
 
+    // Channels bought from an LSP carry the purchase price as their opening cost.
+    if let (None, Some(txid), Some(vout)) = (
+        channel_details.opening_cost_sat,
+        channel_details.txid,
+        channel_details.vout,
+    ) {
+        channel_details.opening_cost_sat = LiquidityService::new(&pool)
+            .get_channel_cost(node_context.node_id(), &format!("{txid}:{vout}"))
+            .await
+            .map_err(service_error_to_http)?;
+    }
+
+    fill_onchain_fees(&*node_client, &mut channel_details).await;
+    // Otherwise opening the channel cost what its funding transaction paid.
+    if channel_details.opening_cost_sat.is_none() {
+        channel_details.opening_cost_sat = channel_details.funding_fee_sat;
+    }
+
+    channel_details.rebalance_cost_sat = SwapService::new(&pool)
+        .get_channel_swap_cost(node_context.node_id(), &scid)
+        .await
+        .map_err(service_error_to_http)?;
+
+    let peer = channel_details.remote_pubkey.to_string();
+    channel_details.peer_metadata = PeerEnrichmentService::new(&pool)
+        .get_metadata(std::slice::from_ref(&peer))
+        .await
+        .remove(&peer);
+
+    let notes = node_notes(&pool, &claims, node_context.node_id()).await?;
+    channel_details.notes = notes_for(&notes, &scid.to_string(), &peer);
+
     Ok(Json(ApiResponse::success(
         channel_details,
         "Channel details retrieved successfully",
     )))
 }
 
+/// Returns the routing fees one of the node's channels earned, split by the
+/// leg it played in each forward, with its volume and a daily series.
+#[axum::debug_handler]
+pub async fn get_channel_revenue(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(channel_id): Path<String>,
+    Query(query): Query<ChannelRevenueQuery>,
+) -> Result<Json<ApiResponse<ChannelRevenue>>, (StatusCode, String)> {
+    let scid = parse_short_channel_id(&channel_id)?;
+
+    match ChannelRevenueService::new(&pool)
+        .get_revenue(
+            claims.account_id(),
+            node_context.node_id(),
+            &scid.to_string(),
+            query,
+        )
+        .await
+    {
+        Ok(revenue) => Ok(Json(ApiResponse::success(
+            revenue,
+            "Channel revenue retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the capacity distribution and balance split of the node's open
+/// channels, as of its last resync.
+#[axum::debug_handler]
+pub async fn get_channel_stats(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<ChannelStats>>, (StatusCode, String)> {
+    match ChannelStatsService::new(&pool)
+        .get_stats(claims.account_id(), node_context.node_id())
+        .await
+    {
+        Ok(stats) => Ok(Json(ApiResponse::success(
+            stats,
+            "Channel stats retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Lists channels waiting on their funding or closing transaction, with the
+/// confirmations each transaction has and still needs.
+#[axum::debug_handler]
+pub async fn list_pending_channels(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<Vec<PendingChannel>>>, (StatusCode, String)> {
+    let node_client = node_context.connect().await?;
+
+    let mut channels = node_client
+        .list_pending_channels()
+        .await
+        .map_err(|e| handle_node_error(e, "list pending channels"))?;
+    let node_height = node_client
+        .get_block_height()
+        .await
+        .map_err(|e| handle_node_error(e, "get block height"))?;
+
+    annotate_pending_channels(&pool, node_context.node_id(), node_height, &mut channels)
+        .await
+        .map_err(service_error_to_http)?;
+
+    Ok(Json(ApiResponse::success(
+        channels,
+        "Pending channels retrieved successfully",
+    )))
+}
+
+/// Restricts a channel list to channels with a note, on the channel or its
+/// peer, carrying this label.
+#[derive(Debug, Deserialize)]
+pub struct ChannelLabelFilter {
+    pub label: Option<String>,
+}
+
+/// Restricts a channel list by remote peer, matched on pubkey prefix or
+/// alias, and by whether the channel is private.
+#[derive(Debug, Deserialize)]
+pub struct ChannelPeerFilter {
+    pub peer: Option<String>,
+    pub private: Option<bool>,
+}
+
 /// Handler for listing all channels with filtering and pagination
 #[axum::debug_handler]
 pub async fn list_channels(
+    Extension(pool): Extension<SqlitePool>,
     Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
     Query(filter): Query<ChannelFilter>,
-) -> Result<Json<ApiResponse<PaginatedData<ChannelSummary>>>, (StatusCode, String)> {
+    Query(label_filter): Query<ChannelLabelFilter>,
+    Query(peer_filter): Query<ChannelPeerFilter>,
+    Query(fields): Query<FieldSelection>,
+) -> Result<Json<ApiResponse<PaginatedData<Sparse<ChannelSummary>>>>, (StatusCode, String)> {
     if let Err(validation_errors) = filter.validate() {
         return Err(validation_error_response(validation_errors));
     }
 
-    let node_credentials = extract_node_credentials(&claims)?;
-    let public_key = parse_public_key(&node_credentials.node_id)?;
+    // While the node is down or its circuit is open, serve the channels from
+    // its last sync.
+    let (mut channels, node_status) = match fetch_channels(&node_context).await {
+        Ok(channels) => (
+            channels,
+            NodeStatusMeta {
+                reachable: true,
+                stale: false,
+                as_of: Utc::now(),
+            },
+        ),
+        Err((status, body))
+            if status == StatusCode::BAD_GATEWAY
+                || (status == StatusCode::SERVICE_UNAVAILABLE
+                    && NodeBreaker::global().is_open(node_context.node_id())) =>
+        {
+            let Some((channels, as_of)) = NodeSyncService::new(&pool)
+                .channel_snapshot(claims.account_id(), node_context.node_id())
+                .await
+                .map_err(service_error_to_http)?
+            else {
+                return Err((status, body));
+            };
+            tracing::warn!(
+                "Node {} is unreachable, serving channels synced at {}",
+                node_context.node_id(),
+                as_of
+            );
+            (
+                channels,
+                NodeStatusMeta {
+                    reachable: false,
+                    stale: true,
+                    as_of,
+                },
+            )
+        }
+        Err(error) => return Err(error),
+    };
+
+    let notes = node_notes(&pool, &claims, node_context.node_id()).await?;
+    for channel in &mut channels {
+        channel.notes = notes_for(&notes, &channel.chan_id.to_string(), &channel.remote_pubkey);
+    }
+    if let Some(label) = &label_filter.label {
+        channels.retain(|channel| has_label(&channel.notes, label));
+    }
+    if let Some(peer) = &peer_filter.peer {
+        channels.retain(|channel| matches_peer(channel, peer));
+    }
+    if let Some(private) = peer_filter.private {
+        channels.retain(|channel| channel.private == private);
+    }
 
-    let node_client = create_node_client(node_credentials, public_key).await?;
+    process_channels_with_filters(channels, &filter, &fields)
+        .await
+        .map(|Json(response)| Json(response.with_node_status(node_status)))
+}
 
-    let channels = node_client
+async fn fetch_channels(
+    node_context: &NodeContext,
+) -> Result<Vec<ChannelSummary>, (StatusCode, String)> {
+    let node_client = node_context.connect().await?;
+    node_client
         .list_channels()
         .await
-        .map_err(|e| handle_node_error(e, "list channels"))?;
+        .map_err(|e| handle_node_error(e, "list channels"))
+}
+
+/// Lists the notes kept on the node's channels and peers.
+#[axum::debug_handler]
+pub async fn list_channel_notes(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<ChannelNoteQuery>,
+) -> Result<Json<ApiResponse<Vec<ChannelNote>>>, (StatusCode, String)> {
+    match ChannelNoteService::new(&pool)
+        .list_notes(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(notes) => Ok(Json(ApiResponse::success(
+            notes,
+            "Channel notes retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Adds a labelled note to one of the node's channels or peers.
+#[axum::debug_handler]
+pub async fn create_channel_note(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<CreateChannelNoteRequest>,
+) -> Result<Json<ApiResponse<ChannelNote>>, (StatusCode, String)> {
+    match ChannelNoteService::new(&pool)
+        .create_note(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            payload,
+        )
+        .await
+    {
+        Ok(note) => Ok(Json(ApiResponse::success(
+            note,
+            "Channel note created successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Changes the label or text of a channel or peer note.
+#[axum::debug_handler]
+pub async fn update_channel_note(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateChannelNoteRequest>,
+) -> Result<Json<ApiResponse<ChannelNote>>, (StatusCode, String)> {
+    match ChannelNoteService::new(&pool)
+        .update_note(claims.account_id(), node_context.node_id(), &id, payload)
+        .await
+    {
+        Ok(note) => Ok(Json(ApiResponse::success(
+            note,
+            "Channel note updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Deletes a channel or peer note.
+#[axum::debug_handler]
+pub async fn delete_channel_note(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, String)> {
+    match ChannelNoteService::new(&pool)
+        .delete_note(claims.account_id(), node_context.node_id(), &id)
+        .await
+    {
+        Ok(()) => Ok(Json(ApiResponse::success(
+            (),
+            "Channel note deleted successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
 
-    process_channels_with_filters(channels, &filter).await
+/// Every note kept on the node's channels and peers.
+async fn node_notes(
+    pool: &SqlitePool,
+    claims: &Claims,
+    node_id: &str,
+) -> Result<Vec<ChannelNote>, (StatusCode, String)> {
+    ChannelNoteService::new(pool)
+        .list_notes(
+            claims.account_id(),
+            node_id,
+            ChannelNoteQuery {
+                target_type: None,
+                target_id: None,
+                label: None,
+            },
+        )
+        .await
+        .map_err(service_error_to_http)
 }
 
 pub type ChannelFilter = FilterRequest<ChannelState>;
@@ -143,13 +454,15 @@ fn apply_channel_filters(
 async fn process_channels_with_filters(
     all_channels: Vec<ChannelSummary>,
     filter: &ChannelFilter,
-) -> Result<Json<ApiResponse<PaginatedData<ChannelSummary>>>, (StatusCode, String)> {
+    fields: &FieldSelection,
+) -> Result<Json<ApiResponse<PaginatedData<Sparse<ChannelSummary>>>>, (StatusCode, String)> {
     let filtered_channels = apply_channel_filters(all_channels, filter);
     let total_filtered_count = filtered_channels.len() as u64;
     let pagination_filter = filter.to_pagination_filter();
     let paginated_channels = apply_pagination(filtered_channels, &pagination_filter);
     let pagination_meta = PaginationMeta::from_filter(&pagination_filter, total_filtered_count);
-    let paginated_data = PaginatedData::new(paginated_channels, total_filtered_count);
+    let paginated_data =
+        PaginatedData::new(fields.select(paginated_channels), total_filtered_count);
 
     Ok(Json(ApiResponse::ok_paginated(
         paginated_data,
@@ -161,7 +474,7 @@ fn parse_short_channel_id(channel_id: &str) -> Result<ShortChannelID, (StatusCod
     ShortChannelID::from_str(channel_id).map_err(|e| {
         let error_response = ApiResponse::<()>::error(
             format!("Invalid channel ID format: {e}"),
-            "invalid_channel_id",
+            ErrorCode::InvalidChannelId,
             None,
         );
         (
@@ -170,3 +483,18 @@ fn parse_short_channel_id(channel_id: &str) -> Result<ShortChannelID, (StatusCod
         )
     })
 }
+
+/// Estimates what opening a channel would cost on chain at several fee rates,
+/// how soon each should confirm, and how much of it reserves would tie up.
+#[axum::debug_handler]
+pub async fn estimate_open_channel(
+    Json(payload): Json<OpenChannelEstimateRequest>,
+) -> Result<Json<ApiResponse<OpenChannelEstimate>>, (StatusCode, String)> {
+    match estimate_channel_open(payload).await {
+        Ok(estimate) => Ok(Json(ApiResponse::success(
+            estimate,
+            "Channel open estimated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -1,18 +1,78 @@
-use super::handlers::{get_channel_info, list_channels};
-use crate::auth::middleware::{jwt_auth, node_credentials_required};
-use axum::{Router, middleware, routing::get};
+use super::handlers::{
+    create_channel_note, delete_channel_note, estimate_open_channel, get_channel_info,
+    get_channel_revenue, get_channel_stats, list_channel_notes, list_channels,
+    list_pending_channels, update_channel_note,
+};
+use crate::auth::middleware::{
+    jwt_auth, node_credentials_required, require_channels_read, require_read_write_access_level,
+};
+use axum::{
+    Router, middleware,
+    routing::{get, post, put},
+};
 
 pub async fn channel_router() -> Router {
     Router::new()
+        .route(
+            "/stats",
+            get(get_channel_stats)
+                .layer(middleware::from_fn(require_channels_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/pending",
+            get(list_pending_channels)
+                .layer(middleware::from_fn(require_channels_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/open/estimate",
+            post(estimate_open_channel)
+                .layer(middleware::from_fn(require_channels_read))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/notes",
+            get(list_channel_notes)
+                .layer(middleware::from_fn(require_channels_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/notes",
+            post(create_channel_note)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/notes/{id}",
+            put(update_channel_note)
+                .delete(delete_channel_note)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{channel_id}/revenue",
+            get(get_channel_revenue)
+                .layer(middleware::from_fn(require_channels_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
         .route(
             "/{channel_id}",
             get(get_channel_info)
+                .layer(middleware::from_fn(require_channels_read))
                 .layer(middleware::from_fn(node_credentials_required))
                 .layer(middleware::from_fn(jwt_auth)),
         )
         .route(
             "/",
             get(list_channels)
+                .layer(middleware::from_fn(require_channels_read))
                 .layer(middleware::from_fn(node_credentials_required))
                 .layer(middleware::from_fn(jwt_auth)),
         )
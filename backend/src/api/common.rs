@@ -8,11 +8,13 @@
 //! - Pagination support for list endpoints
 //! - Flexible filtering system for different data types
 //! - In-memory filtering capabilities
+//! - Sparse fieldsets (`fields=`) for trimming list items
 //!
 //! # Response Format
 //! All errors return consistent JSON responses containing:
 //! - `error`: Human-readable message
-//! - `error_type`: Machine-readable error category
+//! - `code`: Stable machine-readable error code (see `ErrorCode`)
+//! - `error_type`: Lower-case form of `code`, kept for older clients
 //! - `details`: Optional field-specific validation errors
 //!
 //! Paginated responses include:
@@ -31,7 +33,8 @@
 //! - Generic state filtering that works with any enum
 //! - In-memory filtering for collections
 
-use crate::errors::ServiceError;
+use crate::errors::{ErrorCode, ServiceError};
+use crate::utils::redact::redact;
 use axum::http::StatusCode;
 use chrono::{DateTime, Utc};
 use serde::{
@@ -40,6 +43,7 @@ use serde::{
 };
 use std::fmt::Debug;
 use std::str::FromStr;
+use std::sync::Arc;
 use validator::Validate;
 
 /// Standard API response wrapper for all endpoints
@@ -58,10 +62,25 @@ pub struct ApiResponse<T> {
     /// Pagination metadata (present for paginated responses)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pagination: Option<PaginationMeta>,
+    /// Reachability of the node the data came from (present for responses
+    /// that can fall back to synced data)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<NodeStatusMeta>,
     /// Request timestamp
     pub timestamp: String,
 }
 
+/// Whether the node answered and how current the returned data is
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeStatusMeta {
+    /// Whether the node could be reached for this request
+    pub reachable: bool,
+    /// Whether the data is the last synced snapshot rather than live
+    pub stale: bool,
+    /// When the data was read from the node
+    pub as_of: DateTime<Utc>,
+}
+
 /// Pagination metadata for list responses
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaginationMeta {
@@ -97,7 +116,9 @@ pub struct PaginatedData<T> {
 /// Error details for failed requests
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorDetails {
-    /// Machine-readable error type identifier
+    /// Stable machine-readable error code
+    pub code: ErrorCode,
+    /// Lower-case form of `code`
     pub error_type: String,
     /// Field-specific validation errors when applicable
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -124,6 +145,24 @@ pub struct PaginationFilter {
     pub per_page: Option<u32>,
 }
 
+/// Sparse fieldset selection for list endpoints.
+///
+/// `?fields=chan_id,capacity` trims every listed item to those top-level
+/// fields; names an item doesn't have are ignored. Without the parameter
+/// items are returned whole.
+#[derive(Debug, Default, Deserialize)]
+pub struct FieldSelection {
+    #[serde(default, deserialize_with = "deserialize_states")]
+    pub fields: Option<Vec<String>>,
+}
+
+/// A list item that serializes only the selected fields.
+#[derive(Debug)]
+pub struct Sparse<T> {
+    item: T,
+    fields: Option<Arc<[String]>>,
+}
+
 // Numeric comparison operators for filtering
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -167,6 +206,8 @@ where
     /// End date (inclusive)
     pub to: Option<DateTime<Utc>>,
 
+    /// Comma-separated states; LND and CLN terms are accepted as synonyms of
+    /// the canonical names (see [`crate::utils::states`])
     #[serde(default, deserialize_with = "deserialize_states")]
     pub states: Option<Vec<T>>,
 }
@@ -263,6 +304,7 @@ impl<T> ApiResponse<T> {
             message: message.into(),
             error: None,
             pagination: None,
+            node: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -280,6 +322,7 @@ impl<T> ApiResponse<T> {
             message: message.into(),
             error: None,
             pagination: Some(pagination),
+            node: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -289,26 +332,65 @@ impl<T> ApiResponse<T> {
         Self::paginated(data, pagination, "Request successful")
     }
 
-    /// Create an error response
+    /// Attach the reachability of the node the data came from
+    pub fn with_node_status(mut self, node: NodeStatusMeta) -> Self {
+        self.node = Some(node);
+        self
+    }
+
+    /// Create an error response. Secrets echoed into the message (e.g. by
+    /// a failed node connection) are redacted.
     pub fn error(
         message: impl Into<String>,
-        error_type: impl Into<String>,
+        code: ErrorCode,
         details: Option<Vec<FieldError>>,
     ) -> ApiResponse<()> {
         ApiResponse {
             success: false,
             data: None,
-            message: message.into(),
+            message: redact(&message.into()),
             error: Some(ErrorDetails {
-                error_type: error_type.into(),
+                code,
+                error_type: code.as_str().to_ascii_lowercase(),
                 details,
             }),
             pagination: None,
+            node: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
 }
 
+impl FieldSelection {
+    /// Wraps items so they serialize with only the selected fields.
+    pub fn select<T>(&self, items: Vec<T>) -> Vec<Sparse<T>> {
+        let fields: Option<Arc<[String]>> = self.fields.as_deref().map(Arc::from);
+        items
+            .into_iter()
+            .map(|item| Sparse {
+                item,
+                fields: fields.clone(),
+            })
+            .collect()
+    }
+}
+
+impl<T: Serialize> Serialize for Sparse<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(fields) = &self.fields else {
+            return self.item.serialize(serializer);
+        };
+
+        match serde_json::to_value(&self.item).map_err(serde::ser::Error::custom)? {
+            serde_json::Value::Object(mut object) => {
+                object.retain(|key, _| fields.iter().any(|field| field == key));
+                object.serialize(serializer)
+            }
+            other => other.serialize(serializer),
+        }
+    }
+}
+
 impl PaginationFilter {
     /// Get page number with default
     pub fn page(&self) -> u32 {
@@ -342,48 +424,38 @@ impl Default for PaginationFilter {
 
 /// Converts ServiceError to appropriate HTTP response with standard format
 pub fn service_error_to_http(error: ServiceError) -> (StatusCode, String) {
-    let (status, error_type, message) = match error {
-        ServiceError::Validation { message } => {
-            (StatusCode::BAD_REQUEST, "validation_error", message)
-        }
+    let code = error.code();
+    let (status, message) = match error {
+        ServiceError::Validation { message } => (StatusCode::BAD_REQUEST, message),
         ServiceError::NotFound { entity, identifier } => (
             StatusCode::NOT_FOUND,
-            "not_found",
             format!("{entity} '{identifier}' not found"),
         ),
         ServiceError::AlreadyExists { entity, identifier } => (
             StatusCode::CONFLICT,
-            "already_exists",
             format!("{entity} '{identifier}' already exists"),
         ),
-        ServiceError::PermissionDenied { message } => {
-            (StatusCode::FORBIDDEN, "permission_denied", message)
-        }
-        ServiceError::InvalidOperation { message } => {
-            (StatusCode::BAD_REQUEST, "invalid_operation", message)
-        }
+        ServiceError::PermissionDenied { message } => (StatusCode::FORBIDDEN, message),
+        ServiceError::InvalidOperation { message } => (StatusCode::BAD_REQUEST, message),
+        ServiceError::UpgradeRequired { message } => (StatusCode::PAYMENT_REQUIRED, message),
         ServiceError::Database { source } => {
             tracing::error!("Database error: {}", source);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "database_error",
                 "Internal server error".to_string(),
             )
         }
-        ServiceError::ExternalService { message } => {
-            (StatusCode::BAD_GATEWAY, "external_service_error", message)
-        }
+        ServiceError::ExternalService { message } => (StatusCode::BAD_GATEWAY, message),
         ServiceError::InternalError { message } => {
             tracing::error!("Internal error: {}", message);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "internal_error",
                 "Internal server error".to_string(),
             )
         }
     };
 
-    let error_response = ApiResponse::<()>::error(message, error_type, None);
+    let error_response = ApiResponse::<()>::error(message, code, None);
     (status, serde_json::to_string(&error_response).unwrap())
 }
 
@@ -408,8 +480,11 @@ pub fn validation_errors_to_field_errors(errors: validator::ValidationErrors) ->
 /// Helper to create validation error response
 pub fn validation_error_response(errors: validator::ValidationErrors) -> (StatusCode, String) {
     let field_errors = validation_errors_to_field_errors(errors);
-    let error_response =
-        ApiResponse::<()>::error("Validation failed", "validation_error", Some(field_errors));
+    let error_response = ApiResponse::<()>::error(
+        "Validation failed",
+        ErrorCode::ValidationError,
+        Some(field_errors),
+    );
     (
         StatusCode::BAD_REQUEST,
         serde_json::to_string(&error_response).unwrap(),
@@ -479,6 +554,49 @@ mod tests {
         assert_eq!(filter.limit(), 50);
     }
 
+    #[test]
+    fn test_service_error_code_in_response() {
+        let (status, body) = service_error_to_http(ServiceError::not_found("Channel", "42"));
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(response["error"]["code"], "NOT_FOUND");
+        assert_eq!(response["error"]["error_type"], "not_found");
+    }
+
+    #[test]
+    fn test_field_selection() {
+        #[derive(Serialize)]
+        struct Item {
+            id: u32,
+            name: &'static str,
+            capacity: u64,
+        }
+
+        let selection: FieldSelection =
+            serde_json::from_value(serde_json::json!({ "fields": "id, capacity,unknown" }))
+                .unwrap();
+        let items = selection.select(vec![Item {
+            id: 1,
+            name: "alice",
+            capacity: 500,
+        }]);
+        assert_eq!(
+            serde_json::to_value(&items).unwrap(),
+            serde_json::json!([{ "id": 1, "capacity": 500 }])
+        );
+
+        let items = FieldSelection::default().select(vec![Item {
+            id: 1,
+            name: "alice",
+            capacity: 500,
+        }]);
+        assert_eq!(
+            serde_json::to_value(&items).unwrap(),
+            serde_json::json!([{ "id": 1, "name": "alice", "capacity": 500 }])
+        );
+    }
+
     #[test]
     fn test_pagination_helper() {
         let items = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
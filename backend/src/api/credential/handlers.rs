@@ -2,3 +2,53 @@
 //!
 //! These functions process requests for credential data, interact with the database
 //! or relevant services, and return credential-specific information.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::CredentialSummary;
+use crate::services::credential_service::CredentialService;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Lists the account's stored node credentials by fingerprint.
+#[axum::debug_handler]
+pub async fn list_credentials(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<ApiResponse<Vec<CredentialSummary>>>, (StatusCode, String)> {
+    match CredentialService::new(&pool)
+        .list_credentials(claims.account_id())
+        .await
+    {
+        Ok(mut credentials) => {
+            credentials.retain(|credential| claims.can_access_node(&credential.node_id));
+            Ok(Json(ApiResponse::success(
+                credentials,
+                "Credentials retrieved successfully",
+            )))
+        }
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Deletes a stored node credential.
+#[axum::debug_handler]
+pub async fn delete_credential(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, String)> {
+    match CredentialService::new(&pool)
+        .delete_credential(claims.account_id(), claims.user_id(), &id)
+        .await
+    {
+        Ok(()) => Ok(Json(ApiResponse::success(
+            (),
+            "Credential deleted successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -1,4 +1,28 @@
-//! Defines the HTTP routes for user profile and management.
+//! Defines the HTTP routes for stored node credentials.
 //!
-//! These routes provide endpoints for accessing and updating user-specific
-//! data beyond authentication credentials.
+//! Credentials are only ever listed by fingerprint, and only for nodes in the
+//! user's node scope; listing, adding, rotating and deleting them requires the
+//! `credentials:manage` permission.
+
+use super::handlers::{delete_credential, list_credentials};
+use crate::auth::middleware::{jwt_auth, require_credentials_manage};
+use axum::{
+    Router, middleware,
+    routing::{delete, get},
+};
+
+pub async fn credential_router() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(list_credentials)
+                .layer(middleware::from_fn(require_credentials_manage))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}",
+            delete(delete_credential)
+                .layer(middleware::from_fn(require_credentials_manage))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+}
@@ -0,0 +1,130 @@
+//! Handler functions for the alert escalation API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{
+    Escalation, EscalationPolicyRequest, EscalationPolicyResponse, EscalationQuery,
+};
+use crate::services::escalations::EscalationService;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Lists the account's escalations, newest first.
+#[axum::debug_handler]
+pub async fn list_escalations(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<EscalationQuery>,
+) -> Result<Json<ApiResponse<Vec<Escalation>>>, (StatusCode, String)> {
+    match EscalationService::new(&pool)
+        .get_escalations(claims.account_id(), query)
+        .await
+    {
+        Ok(mut escalations) => {
+            escalations.retain(|escalation| claims.can_access_node(&escalation.node_id));
+            Ok(Json(ApiResponse::success(
+                escalations,
+                "Escalations retrieved successfully",
+            )))
+        }
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Acknowledges an escalation, stopping its remaining steps.
+#[axum::debug_handler]
+pub async fn acknowledge_escalation(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Escalation>>, (StatusCode, String)> {
+    match EscalationService::new(&pool)
+        .acknowledge(claims.account_id(), claims.user_id(), &id)
+        .await
+    {
+        Ok(escalation) => Ok(Json(ApiResponse::success(
+            escalation,
+            "Escalation acknowledged",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Lists the account's escalation policies.
+#[axum::debug_handler]
+pub async fn list_escalation_policies(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<ApiResponse<Vec<EscalationPolicyResponse>>>, (StatusCode, String)> {
+    match EscalationService::new(&pool)
+        .list_policies(claims.account_id())
+        .await
+    {
+        Ok(policies) => Ok(Json(ApiResponse::success(
+            policies,
+            "Escalation policies retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Creates an escalation policy.
+#[axum::debug_handler]
+pub async fn create_escalation_policy(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<EscalationPolicyRequest>,
+) -> Result<Json<ApiResponse<EscalationPolicyResponse>>, (StatusCode, String)> {
+    match EscalationService::new(&pool)
+        .create_policy(claims.account_id(), claims.user_id(), payload)
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy,
+            "Escalation policy created successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Replaces an escalation policy's settings.
+#[axum::debug_handler]
+pub async fn update_escalation_policy(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(payload): Json<EscalationPolicyRequest>,
+) -> Result<Json<ApiResponse<EscalationPolicyResponse>>, (StatusCode, String)> {
+    match EscalationService::new(&pool)
+        .update_policy(claims.account_id(), claims.user_id(), &id, payload)
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy,
+            "Escalation policy updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Deletes an escalation policy, cancelling its escalations.
+#[axum::debug_handler]
+pub async fn delete_escalation_policy(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, String)> {
+    match EscalationService::new(&pool)
+        .delete_policy(claims.account_id(), &id)
+        .await
+    {
+        Ok(()) => Ok(Json(ApiResponse::success(
+            (),
+            "Escalation policy deleted successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -0,0 +1,7 @@
+//! Module for alert escalation API endpoints.
+//!
+//! This module manages the chains of notification endpoints critical events
+//! are escalated through, and lets users acknowledge escalations under way.
+
+pub mod handlers;
+pub mod routes;
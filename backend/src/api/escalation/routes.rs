@@ -0,0 +1,40 @@
+//! Defines the HTTP routes for alert escalation.
+
+use super::handlers::{
+    acknowledge_escalation, create_escalation_policy, delete_escalation_policy,
+    list_escalation_policies, list_escalations, update_escalation_policy,
+};
+use crate::auth::middleware::{
+    jwt_auth, require_events_read, require_notifications_read, require_notifications_write,
+};
+use axum::{
+    Router, middleware,
+    routing::{get, post, put},
+};
+
+pub async fn escalation_router() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(list_escalations).layer(middleware::from_fn(require_events_read)),
+        )
+        .route(
+            "/{id}/acknowledge",
+            post(acknowledge_escalation).layer(middleware::from_fn(require_events_read)),
+        )
+        .route(
+            "/policies",
+            get(list_escalation_policies).layer(middleware::from_fn(require_notifications_read)),
+        )
+        .route(
+            "/policies",
+            post(create_escalation_policy).layer(middleware::from_fn(require_notifications_write)),
+        )
+        .route(
+            "/policies/{id}",
+            put(update_escalation_policy)
+                .delete(delete_escalation_policy)
+                .layer(middleware::from_fn(require_notifications_write)),
+        )
+        .layer(middleware::from_fn(jwt_auth))
+}
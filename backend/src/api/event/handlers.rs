@@ -1,41 +1,104 @@
 //! Handler functions for event management API endpoints.
 
-use crate::api::common::{ApiResponse, PaginatedData, service_error_to_http};
-use crate::database::models::EventResponse;
+use crate::api::common::{
+    ApiResponse, PaginatedData, PaginationFilter, PaginationMeta, service_error_to_http,
+    validation_error_response,
+};
+use crate::database::models::{
+    EventFilters, EventResponse, EventStats, EventStatsQuery, JobResponse, RedispatchEventQuery,
+};
+use crate::errors::ErrorCode;
 use crate::services::event_service::EventService;
+use crate::services::notification_service::NotificationService;
 use crate::utils::jwt::Claims;
 use axum::{
-    extract::{Extension, Path},
+    extract::{Extension, Path, Query},
     http::StatusCode,
     response::Json as ResponseJson,
 };
+use serde::Deserialize;
 use sqlx::SqlitePool;
+use validator::Validate;
 
-/// Retrieves events for the user's account.
+/// Query parameters for listing events.
+#[derive(Debug, Deserialize)]
+pub struct EventSearchQuery {
+    /// Full-text search over title, description and data values
+    pub q: Option<String>,
+}
+
+/// Retrieves a page of events for the user's account, from nodes in the
+/// user's node scope.
 #[axum::debug_handler]
 pub async fn get_events(
     Extension(pool): Extension<SqlitePool>,
     Extension(claims): Extension<Claims>,
+    Query(pagination): Query<PaginationFilter>,
+    Query(query): Query<EventSearchQuery>,
 ) -> Result<ResponseJson<ApiResponse<PaginatedData<EventResponse>>>, (StatusCode, String)> {
+    if let Err(validation_errors) = pagination.validate() {
+        return Err(validation_error_response(validation_errors));
+    }
+
     let account_id = claims.account_id();
+    let filters = EventFilters {
+        event_types: None,
+        severities: None,
+        node_ids: claims.node_scope.clone(),
+        start_date: None,
+        end_date: None,
+        limit: Some(pagination.limit()),
+        offset: Some(pagination.offset()),
+    };
 
     let service = EventService::new(&pool);
 
-    // Get all events for the account
-    let events = service
-        .get_events_for_account(&pool, account_id, None)
-        .await
-        .map_err(service_error_to_http)?;
-
-    let total = events.len() as u64;
-    let response = PaginatedData::new(events, total);
+    let (events, total) = match query.q.as_deref().filter(|q| !q.trim().is_empty()) {
+        Some(q) => service
+            .search_events_for_account(account_id, q, Some(filters))
+            .await
+            .map_err(service_error_to_http)?,
+        // Get all events for the account
+        None => {
+            let events = service
+                .get_events_for_account(&pool, account_id, Some(filters.clone()))
+                .await
+                .map_err(service_error_to_http)?;
+            let total = service
+                .count_events_for_account(&pool, account_id, Some(filters))
+                .await
+                .map_err(service_error_to_http)?;
+            (events, total)
+        }
+    };
 
-    Ok(ResponseJson(ApiResponse::success(
-        response,
+    Ok(ResponseJson(ApiResponse::paginated(
+        PaginatedData::new(events, total as u64),
+        PaginationMeta::from_filter(&pagination, total as u64),
         "Events retrieved successfully",
     )))
 }
 
+/// Counts the account's events over a date range by severity, event type and
+/// node, for dashboard charts. Only nodes in the user's node scope are counted.
+#[axum::debug_handler]
+pub async fn get_event_stats(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<EventStatsQuery>,
+) -> Result<ResponseJson<ApiResponse<EventStats>>, (StatusCode, String)> {
+    match EventService::new(&pool)
+        .get_event_stats(claims.account_id(), claims.node_scope.as_deref(), query)
+        .await
+    {
+        Ok(stats) => Ok(ResponseJson(ApiResponse::success(
+            stats,
+            "Event statistics retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
 /// Retrieves a specific event by ID.
 #[axum::debug_handler]
 pub async fn get_event_by_id(
@@ -43,27 +106,61 @@ pub async fn get_event_by_id(
     Extension(claims): Extension<Claims>,
     Path(id): Path<String>,
 ) -> Result<ResponseJson<ApiResponse<EventResponse>>, (StatusCode, String)> {
-    let account_id = claims.account_id();
-
-    let service = EventService::new(&pool);
-
-    // Get all events for the account
-    let events = service
-        .get_events_for_account(&pool, account_id, None)
-        .await
-        .map_err(service_error_to_http)?;
-
-    // Find the specific event by ID
-    let event = events.into_iter().find(|e| e.id == id).ok_or_else(|| {
-        let error_response = ApiResponse::<()>::error("Event not found", "not_found", None);
-        (
-            StatusCode::NOT_FOUND,
-            serde_json::to_string(&error_response).unwrap(),
-        )
-    })?;
+    let event = scoped_event(&pool, &claims, &id).await?;
 
     Ok(ResponseJson(ApiResponse::success(
         event,
         "Event retrieved successfully",
     )))
 }
+
+/// Queues an event to be delivered again to its notification endpoints, or
+/// to the one given, e.g. after a consumer that missed it has been fixed.
+#[axum::debug_handler]
+pub async fn redispatch_event(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Query(query): Query<RedispatchEventQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<JobResponse>>>, (StatusCode, String)> {
+    scoped_event(&pool, &claims, &id).await?;
+
+    let service = NotificationService::new(&pool);
+    match service
+        .redispatch_event(
+            claims.account_id(),
+            claims.user_id(),
+            &id,
+            query.notification_id.as_deref(),
+        )
+        .await
+    {
+        Ok(jobs) => Ok(ResponseJson(ApiResponse::success(
+            jobs,
+            "Event queued for redelivery",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Loads one of the account's events, treating events of nodes outside the
+/// user's node scope as missing.
+async fn scoped_event(
+    pool: &SqlitePool,
+    claims: &Claims,
+    id: &str,
+) -> Result<EventResponse, (StatusCode, String)> {
+    EventService::new(pool)
+        .get_event_for_account(claims.account_id(), id)
+        .await
+        .map_err(service_error_to_http)?
+        .filter(|event| claims.can_access_node(&event.node_id))
+        .ok_or_else(|| {
+            let error_response =
+                ApiResponse::<()>::error("Event not found", ErrorCode::NotFound, None);
+            (
+                StatusCode::NOT_FOUND,
+                serde_json::to_string(&error_response).unwrap(),
+            )
+        })
+}
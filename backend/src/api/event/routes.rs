@@ -1,12 +1,29 @@
 //! Defines the HTTP routes for event management.
 
-use super::handlers::{get_event_by_id, get_events};
-use crate::auth::middleware::jwt_auth;
-use axum::{Router, middleware, routing::get};
+use super::handlers::{get_event_by_id, get_event_stats, get_events, redispatch_event};
+use crate::auth::middleware::{jwt_auth, require_events_read, require_notifications_write};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
 
 pub async fn event_router() -> Router {
     Router::new()
-        .route("/", get(get_events))
-        .route("/{id}", get(get_event_by_id))
+        .route(
+            "/",
+            get(get_events).layer(middleware::from_fn(require_events_read)),
+        )
+        .route(
+            "/stats",
+            get(get_event_stats).layer(middleware::from_fn(require_events_read)),
+        )
+        .route(
+            "/{id}",
+            get(get_event_by_id).layer(middleware::from_fn(require_events_read)),
+        )
+        .route(
+            "/{id}/redispatch",
+            post(redispatch_event).layer(middleware::from_fn(require_notifications_write)),
+        )
         .layer(middleware::from_fn(jwt_auth))
 }
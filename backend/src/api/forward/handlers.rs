@@ -0,0 +1,32 @@
+//! Handler functions for the forward API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{ForwardFailureList, ForwardFailureQuery};
+use crate::services::forward_failures::ForwardFailureService;
+use crate::utils::handlers_common::NodeContext;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Query},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Lists the node's failed forwards in a date range with their causes.
+#[axum::debug_handler]
+pub async fn get_forward_failures(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<ForwardFailureQuery>,
+) -> Result<Json<ApiResponse<ForwardFailureList>>, (StatusCode, String)> {
+    match ForwardFailureService::new(&pool)
+        .get_failures(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(failures) => Ok(Json(ApiResponse::success(
+            failures,
+            "Forward failures retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -0,0 +1,7 @@
+//! Module for forward API endpoints.
+//!
+//! This module lists the failed forwards of the node in the token, with
+//! whether each was down to our liquidity, our policy or a node downstream.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,15 @@
+//! Defines the HTTP routes for forwards.
+
+use super::handlers::get_forward_failures;
+use crate::auth::middleware::{jwt_auth, node_credentials_required, require_node_read};
+use axum::{Router, middleware, routing::get};
+
+pub async fn forward_router() -> Router {
+    Router::new().route(
+        "/failures",
+        get(get_forward_failures)
+            .layer(middleware::from_fn(require_node_read))
+            .layer(middleware::from_fn(node_credentials_required))
+            .layer(middleware::from_fn(jwt_auth)),
+    )
+}
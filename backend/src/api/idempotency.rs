@@ -0,0 +1,212 @@
+//! Idempotency-Key support for mutating endpoints.
+//!
+//! A client that sends `Idempotency-Key: <key>` with a request can safely
+//! retry it: the first response is stored per account and key, and every
+//! retry gets that stored response back (with `Idempotent-Replayed: true`)
+//! instead of running the handler again. Reusing a key for a different
+//! request is rejected, as is a retry that arrives while the original is
+//! still running. Server errors are stored and replayed too: the endpoints
+//! using keys move funds, and a swap, rebalance or order may already have
+//! been submitted when the error came back, so running it again could pay
+//! twice. After one, clients check the outcome before retrying with a new
+//! key. Keys are kept for at least `IDEMPOTENCY_KEY_TTL_HOURS`.
+//!
+//! The middleware must run after `jwt_auth`, which provides the account.
+
+use crate::api::common::ApiResponse;
+use crate::errors::ErrorCode;
+use crate::repositories::idempotency_repository::IdempotencyRepository;
+use crate::utils::jwt::Claims;
+use axum::{
+    body::{Body, to_bytes},
+    extract::{OriginalUri, Request},
+    http::{HeaderValue, StatusCode, header::CONTENT_TYPE},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use bitcoin::hashes::{Hash, sha256};
+use sqlx::SqlitePool;
+
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// How long keys are remembered before the retention task removes them
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Largest request or response body the middleware buffers
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Replays the stored response for a repeated Idempotency-Key.
+pub async fn idempotency(request: Request, next: Next) -> Result<Response, Response> {
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(next.run(request).await);
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_string(),
+        _ => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Idempotency-Key must be 1-{MAX_KEY_LENGTH} visible ASCII characters"),
+                ErrorCode::ValidationError,
+            ));
+        }
+    };
+
+    let (Some(pool), Some(claims)) = (
+        request.extensions().get::<SqlitePool>().cloned(),
+        request.extensions().get::<Claims>().cloned(),
+    ) else {
+        tracing::error!("Idempotency middleware is missing the database pool or claims");
+        return Err(internal_error());
+    };
+    let account_id = claims.account_id();
+
+    let method = request.method().to_string();
+    // Nested routers strip their prefix from the request URI.
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or(request.uri().path(), |uri| uri.path())
+        .to_string();
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_BYTES).await.map_err(|_| {
+        error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "Request body is too large".to_string(),
+            ErrorCode::ValidationError,
+        )
+    })?;
+    let request_hash = request_hash(&method, &path, &body);
+
+    let repo = IdempotencyRepository::new(&pool);
+    let reserved = repo
+        .reserve(account_id, &key, &method, &path, &request_hash)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to reserve idempotency key: {}", e);
+            internal_error()
+        })?;
+
+    if !reserved {
+        let record = repo.get(account_id, &key).await.map_err(|e| {
+            tracing::error!("Failed to load idempotency key: {}", e);
+            internal_error()
+        })?;
+        let Some(record) = record else {
+            // Expired and removed between our insert and lookup.
+            return Err(error_response(
+                StatusCode::CONFLICT,
+                "The original request with this Idempotency-Key has expired; retry it".to_string(),
+                ErrorCode::RequestInProgress,
+            ));
+        };
+        if record.request_hash != request_hash {
+            return Err(error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Idempotency-Key was already used for a different request".to_string(),
+                ErrorCode::IdempotencyKeyReused,
+            ));
+        }
+        let Some(status_code) = record.status_code else {
+            return Err(error_response(
+                StatusCode::CONFLICT,
+                "A request with this Idempotency-Key is still in progress".to_string(),
+                ErrorCode::RequestInProgress,
+            ));
+        };
+
+        let mut response = Response::new(Body::from(record.response_body.unwrap_or_default()));
+        *response.status_mut() = StatusCode::from_u16(status_code as u16).unwrap_or(StatusCode::OK);
+        if let Some(content_type) = record
+            .content_type
+            .and_then(|content_type| HeaderValue::from_str(&content_type).ok())
+        {
+            response.headers_mut().insert(CONTENT_TYPE, content_type);
+        }
+        response
+            .headers_mut()
+            .insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+        return Ok(response);
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    let (parts, body) = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => (parts, body),
+        Err(e) => {
+            // The handler has run, so the key still gets a response to replay.
+            tracing::error!("Failed to buffer response for idempotency key: {}", e);
+            let (parts, body) = internal_error().into_parts();
+            (
+                parts,
+                to_bytes(body, MAX_BODY_BYTES).await.unwrap_or_default(),
+            )
+        }
+    };
+    let content_type = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    if let Err(e) = repo
+        .complete(
+            account_id,
+            &key,
+            parts.status.as_u16() as i64,
+            content_type,
+            &body,
+        )
+        .await
+    {
+        tracing::error!("Failed to store idempotent response: {}", e);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Fingerprint of a request, so a key can't be replayed for another one.
+fn request_hash(method: &str, path: &str, body: &[u8]) -> String {
+    let mut data = Vec::with_capacity(method.len() + path.len() + body.len() + 2);
+    data.extend_from_slice(method.as_bytes());
+    data.push(b'\n');
+    data.extend_from_slice(path.as_bytes());
+    data.push(b'\n');
+    data.extend_from_slice(body);
+    sha256::Hash::hash(&data).to_string()
+}
+
+fn error_response(status: StatusCode, message: String, code: ErrorCode) -> Response {
+    (status, Json(ApiResponse::<()>::error(message, code, None))).into_response()
+}
+
+fn internal_error() -> Response {
+    error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Internal server error".to_string(),
+        ErrorCode::InternalError,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_hash_covers_path_and_body() {
+        let hash = request_hash("POST", "/api/swaps", b"{\"amount_sat\":1000}");
+        assert_eq!(
+            hash,
+            request_hash("POST", "/api/swaps", b"{\"amount_sat\":1000}")
+        );
+        assert_ne!(
+            hash,
+            request_hash("POST", "/api/swaps", b"{\"amount_sat\":2000}")
+        );
+        assert_ne!(
+            hash,
+            request_hash("POST", "/api/rebalance", b"{\"amount_sat\":1000}")
+        );
+    }
+}
@@ -0,0 +1,34 @@
+//! Handler functions for the incidents API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{Incident, IncidentQuery};
+use crate::services::incidents::IncidentService;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Query},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Returns the account's recent incidents, newest first, each with the
+/// timeline of events it groups.
+#[axum::debug_handler]
+pub async fn get_incidents(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<IncidentQuery>,
+) -> Result<Json<ApiResponse<Vec<Incident>>>, (StatusCode, String)> {
+    match IncidentService::new(&pool)
+        .get_incidents(claims.account_id(), query)
+        .await
+    {
+        Ok(mut incidents) => {
+            incidents.retain(|incident| claims.can_access_node(&incident.node_id));
+            Ok(Json(ApiResponse::success(
+                incidents,
+                "Incidents retrieved successfully",
+            )))
+        }
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -0,0 +1,7 @@
+//! Module for incident API endpoints.
+//!
+//! This module groups related events of the account's nodes into incident
+//! timelines for post-mortems.
+
+pub mod handlers;
+pub mod routes;
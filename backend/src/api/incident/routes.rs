@@ -0,0 +1,14 @@
+//! Defines the HTTP routes for incidents.
+
+use super::handlers::get_incidents;
+use crate::auth::middleware::{jwt_auth, require_events_read};
+use axum::{Router, middleware, routing::get};
+
+pub async fn incident_router() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(get_incidents).layer(middleware::from_fn(require_events_read)),
+        )
+        .layer(middleware::from_fn(jwt_auth))
+}
@@ -6,7 +6,9 @@
 use crate::api::common::ApiResponse;
 use crate::config::Config;
 use crate::database::models::{AcceptInviteRequest, CreateInviteRequest, Invite, User};
+use crate::errors::ErrorCode;
 use crate::services::invite_service::InviteService;
+use crate::services::profile_service::ProfileService;
 use crate::services::user_service::UserService;
 use crate::utils::jwt::Claims;
 use axum::{
@@ -29,12 +31,12 @@ pub async fn create_invite(
 
     let user_service = UserService::new(&pool);
     let user = user_service
-        .get_user_required(user_id.as_str())
+        .get_user_for_claims(&claims)
         .await
         .map_err(|e| {
             tracing::error!("User not found for ID {}: {}", user_id, e);
             let error_response =
-                ApiResponse::<()>::error("User not found".to_string(), "user_not_found", None);
+                ApiResponse::<()>::error("User not found".to_string(), ErrorCode::NotFound, None);
             (
                 StatusCode::NOT_FOUND,
                 serde_json::to_string(&error_response).unwrap(),
@@ -47,7 +49,7 @@ pub async fn create_invite(
         tracing::error!("Failed to create invite for user {}: {}", user_id, e);
         let error_response = ApiResponse::<()>::error(
             format!("Failed to create invite: {e}"),
-            "invite_creation_error",
+            ErrorCode::InternalError,
             None,
         );
         (
@@ -77,12 +79,12 @@ pub async fn get_invite_by_id(
 
     let user_service = UserService::new(&pool);
     let user = user_service
-        .get_user_required(user_id.as_str())
+        .get_user_for_claims(&claims)
         .await
         .map_err(|e| {
             tracing::error!("User not found for ID {}: {}", user_id, e);
             let error_response =
-                ApiResponse::<()>::error("User not found".to_string(), "user_not_found", None);
+                ApiResponse::<()>::error("User not found".to_string(), ErrorCode::NotFound, None);
             (
                 StatusCode::NOT_FOUND,
                 serde_json::to_string(&error_response).unwrap(),
@@ -97,7 +99,7 @@ pub async fn get_invite_by_id(
             tracing::error!("Failed to find invite {}: {}", id, e);
             let error_response = ApiResponse::<()>::error(
                 format!("Failed to find invite: {e}"),
-                "invite_not_found",
+                ErrorCode::NotFound,
                 None,
             );
             (
@@ -126,12 +128,12 @@ pub async fn get_invites(
 
     let user_service = UserService::new(&pool);
     let user = user_service
-        .get_user_required(user_id.as_str())
+        .get_user_for_claims(&claims)
         .await
         .map_err(|e| {
             tracing::error!("User not found for ID {}: {}", user_id, e);
             let error_response =
-                ApiResponse::<()>::error("User not found".to_string(), "user_not_found", None);
+                ApiResponse::<()>::error("User not found".to_string(), ErrorCode::NotFound, None);
             (
                 StatusCode::NOT_FOUND,
                 serde_json::to_string(&error_response).unwrap(),
@@ -146,7 +148,7 @@ pub async fn get_invites(
             tracing::error!("No invites found for account {}: {}", user.account_id, e);
             let error_response = ApiResponse::<()>::error(
                 format!("No invites found: {e}"),
-                "invites_not_found",
+                ErrorCode::NotFound,
                 None,
             );
             (
@@ -176,12 +178,12 @@ pub async fn resend_invite(
 
     let user_service = UserService::new(&pool);
     let user = user_service
-        .get_user_required(user_id.as_str())
+        .get_user_for_claims(&claims)
         .await
         .map_err(|e| {
             tracing::error!("User not found for ID {}: {}", user_id, e);
             let error_response =
-                ApiResponse::<()>::error("User not found".to_string(), "user_not_found", None);
+                ApiResponse::<()>::error("User not found".to_string(), ErrorCode::NotFound, None);
             (
                 StatusCode::NOT_FOUND,
                 serde_json::to_string(&error_response).unwrap(),
@@ -193,7 +195,7 @@ pub async fn resend_invite(
         tracing::error!("Failed to resend invite {} for user {}: {}", id, user_id, e);
         let error_response = ApiResponse::<()>::error(
             format!("Failed to resend invite: {e}"),
-            "invite_resend_error",
+            ErrorCode::InternalError,
             None,
         );
         (
@@ -228,7 +230,7 @@ pub async fn accept_invite(
         );
         let error_response = ApiResponse::<()>::error(
             format!("Failed to accept invitation: {e}"),
-            "invite_accept_error",
+            ErrorCode::InternalError,
             None,
         );
         (
@@ -241,6 +243,12 @@ pub async fn accept_invite(
         "Invite accepted successfully for token: {}",
         accept_invite.token
     );
+    if let Err(e) = ProfileService::new(&pool, &config)
+        .start_email_verification(&user)
+        .await
+    {
+        tracing::warn!("Failed to start email verification: {}", e);
+    }
     Ok(Json(ApiResponse::success(
         user,
         "Invite accepted successfully",
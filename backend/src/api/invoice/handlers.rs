@@ -1,13 +1,13 @@
-use crate::utils::handlers_common::{
-    create_node_client, extract_node_credentials, handle_node_error, parse_payment_hash,
-    parse_public_key,
-};
+use crate::utils::handlers_common::{NodeContext, handle_node_error, parse_payment_hash};
 use crate::utils::jwt::Claims;
 use crate::{
     api::common::{
-        ApiResponse, FilterRequest, NumericOperator, PaginatedData, PaginationFilter,
-        PaginationMeta, apply_pagination, validation_error_response,
+        ApiResponse, FieldSelection, FilterRequest, NumericOperator, PaginatedData,
+        PaginationFilter, PaginationMeta, Sparse, apply_pagination, service_error_to_http,
+        validation_error_response,
     },
+    database::models::{InvoiceStats, InvoiceStatsQuery},
+    services::{invoice_stats::InvoiceStatsService, node_sync::NodeSyncService},
     utils::{CustomInvoice, InvoiceStatus},
 };
 use axum::{
@@ -15,19 +15,40 @@ use axum::{
     extract::{Extension, Path, Query},
     http::StatusCode,
 };
+use serde::Deserialize;
+use sqlx::SqlitePool;
 use validator::Validate;
 
+/// Returns the node's invoice counts and totals by state over a date range,
+/// as of its last resync.
+#[axum::debug_handler]
+pub async fn get_invoice_stats(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<InvoiceStatsQuery>,
+) -> Result<Json<ApiResponse<InvoiceStats>>, (StatusCode, String)> {
+    match InvoiceStatsService::new(&pool)
+        .get_stats(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(stats) => Ok(Json(ApiResponse::success(
+            stats,
+            "Invoice stats retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
 /// Handler for getting invoice details
 #[axum::debug_handler]
 pub async fn get_invoice_details(
-    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
     Path(payment_hash): Path<String>,
 ) -> Result<Json<ApiResponse<CustomInvoice>>, (StatusCode, String)> {
     let payment_hash = parse_payment_hash(&payment_hash)?;
-    let node_credentials = extract_node_credentials(&claims)?;
-    let public_key = parse_public_key(&node_credentials.node_id)?;
 
-    let node_client = create_node_client(node_credentials, public_key).await?;
+    let node_client = node_context.connect().await?;
 
     let invoice_details = node_client
         .get_invoice_details(&payment_hash)
@@ -43,24 +64,83 @@ pub async fn get_invoice_details(
 /// Handler for listing all invoices with filtering and pagination
 #[axum::debug_handler]
 pub async fn list_invoices(
+    Extension(pool): Extension<SqlitePool>,
     Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
     Query(filter): Query<InvoiceFilter>,
-) -> Result<Json<ApiResponse<PaginatedData<CustomInvoice>>>, (StatusCode, String)> {
+    Query(search): Query<InvoiceSearchFilter>,
+    Query(fields): Query<FieldSelection>,
+) -> Result<Json<ApiResponse<PaginatedData<Sparse<CustomInvoice>>>>, (StatusCode, String)> {
     if let Err(validation_errors) = filter.validate() {
         return Err(validation_error_response(validation_errors));
     }
+    if let Err(validation_errors) = search.validate() {
+        return Err(validation_error_response(validation_errors));
+    }
 
-    let node_credentials = extract_node_credentials(&claims)?;
-    let public_key = parse_public_key(&node_credentials.node_id)?;
+    // Memo and amount-paid searches run against the synced invoices, where
+    // both columns are indexed.
+    let memo_contains = search
+        .memo_contains
+        .as_deref()
+        .map(str::trim)
+        .filter(|memo| !memo.is_empty());
+    let (min_paid_msat, max_paid_msat) = match (&search.amt_paid_operator, search.amt_paid) {
+        (Some(operator), Some(amt_paid)) => amt_paid_msat_bounds(operator, amt_paid),
+        _ => (None, None),
+    };
+    let matching_hashes =
+        if memo_contains.is_some() || min_paid_msat.is_some() || max_paid_msat.is_some() {
+            Some(
+                NodeSyncService::new(&pool)
+                    .invoice_hashes_matching(
+                        claims.account_id(),
+                        node_context.node_id(),
+                        memo_contains,
+                        min_paid_msat,
+                        max_paid_msat,
+                    )
+                    .await
+                    .map_err(service_error_to_http)?,
+            )
+        } else {
+            None
+        };
 
-    let node_client = create_node_client(node_credentials, public_key).await?;
+    let node_client = node_context.connect().await?;
 
-    let invoices = node_client
+    let mut invoices = node_client
         .list_invoices()
         .await
         .map_err(|e| handle_node_error(e, "list invoices"))?;
 
-    process_invoices_with_filters(invoices, &filter).await
+    if let Some(hashes) = matching_hashes {
+        invoices.retain(|invoice| hashes.contains(&invoice.payment_hash));
+    }
+
+    process_invoices_with_filters(invoices, &filter, &fields).await
+}
+
+/// Searches invoices by memo and by the amount actually paid, in sats.
+#[derive(Debug, Deserialize, Validate)]
+pub struct InvoiceSearchFilter {
+    #[validate(length(max = 256))]
+    pub memo_contains: Option<String>,
+    pub amt_paid_operator: Option<NumericOperator>,
+    #[validate(range(min = 0))]
+    pub amt_paid: Option<i64>,
+}
+
+/// Inclusive msat bounds matching a comparison on the amount paid in whole sats.
+fn amt_paid_msat_bounds(operator: &NumericOperator, sat: i64) -> (Option<i64>, Option<i64>) {
+    let msat = sat.saturating_mul(1000);
+    match operator {
+        NumericOperator::Gte => (Some(msat), None),
+        NumericOperator::Gt => (Some(msat.saturating_add(1000)), None),
+        NumericOperator::Lte => (None, Some(msat.saturating_add(999))),
+        NumericOperator::Lt => (None, Some(msat - 1)),
+        NumericOperator::Eq => (Some(msat), Some(msat.saturating_add(999))),
+    }
 }
 
 pub type InvoiceFilter = FilterRequest<InvoiceStatus>;
@@ -136,13 +216,15 @@ fn apply_invoice_filters(
 async fn process_invoices_with_filters(
     all_invoices: Vec<CustomInvoice>,
     filter: &InvoiceFilter,
-) -> Result<Json<ApiResponse<PaginatedData<CustomInvoice>>>, (StatusCode, String)> {
+    fields: &FieldSelection,
+) -> Result<Json<ApiResponse<PaginatedData<Sparse<CustomInvoice>>>>, (StatusCode, String)> {
     let filtered_invoices = apply_invoice_filters(all_invoices, filter);
     let total_filtered_count = filtered_invoices.len() as u64;
     let pagination_filter = filter.to_pagination_filter();
     let paginated_invoices = apply_pagination(filtered_invoices, &pagination_filter);
     let pagination_meta = PaginationMeta::from_filter(&pagination_filter, total_filtered_count);
-    let paginated_data = PaginatedData::new(paginated_invoices, total_filtered_count);
+    let paginated_data =
+        PaginatedData::new(fields.select(paginated_invoices), total_filtered_count);
 
     Ok(Json(ApiResponse::ok_paginated(
         paginated_data,
@@ -0,0 +1,77 @@
+//! Handler functions for the liquidity API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{CreateLiquidityOrderRequest, LiquidityOrder};
+use crate::services::liquidity_service::LiquidityService;
+use crate::utils::handlers_common::NodeContext;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Orders an inbound channel to the node in the token. The response carries
+/// the invoice to pay for it.
+#[axum::debug_handler]
+pub async fn create_liquidity_order(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<CreateLiquidityOrderRequest>,
+) -> Result<Json<ApiResponse<LiquidityOrder>>, (StatusCode, String)> {
+    match LiquidityService::new(&pool)
+        .create_order(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            payload,
+        )
+        .await
+    {
+        Ok(order) => Ok(Json(ApiResponse::success(
+            order,
+            "Liquidity order created successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Lists the liquidity orders of the node in the token.
+#[axum::debug_handler]
+pub async fn list_liquidity_orders(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<Vec<LiquidityOrder>>>, (StatusCode, String)> {
+    match LiquidityService::new(&pool)
+        .list_orders(claims.account_id(), node_context.node_id())
+        .await
+    {
+        Ok(orders) => Ok(Json(ApiResponse::success(
+            orders,
+            "Liquidity orders retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns a liquidity order with its latest status from the LSP.
+#[axum::debug_handler]
+pub async fn get_liquidity_order(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<LiquidityOrder>>, (StatusCode, String)> {
+    match LiquidityService::new(&pool)
+        .get_order(claims.account_id(), node_context.node_id(), &id)
+        .await
+    {
+        Ok(order) => Ok(Json(ApiResponse::success(
+            order,
+            "Liquidity order retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
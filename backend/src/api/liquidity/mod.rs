@@ -0,0 +1,7 @@
+//! Module for liquidity API endpoints.
+//!
+//! This module handles buying inbound channels from LSPS1 providers and
+//! following those orders until the channel is open.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,35 @@
+//! Defines the HTTP routes for liquidity purchases.
+
+use super::handlers::{create_liquidity_order, get_liquidity_order, list_liquidity_orders};
+use crate::api::idempotency::idempotency;
+use crate::auth::middleware::{admin_auth, jwt_auth, node_credentials_required, require_node_read};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
+
+pub async fn liquidity_router() -> Router {
+    Router::new()
+        .route(
+            "/orders",
+            get(list_liquidity_orders)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/orders",
+            post(create_liquidity_order)
+                .layer(middleware::from_fn(idempotency))
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/orders/{id}",
+            get(get_liquidity_order)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+}
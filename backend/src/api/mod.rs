@@ -5,13 +5,194 @@
 //! authentication routes which are handled separately.
 
 pub mod account;
+pub mod admin;
+pub mod analytics;
+pub mod billing;
 pub mod channel;
 pub mod common;
 pub mod credential;
+pub mod escalation;
 pub mod event;
+pub mod forward;
+pub mod idempotency;
+pub mod incident;
 pub mod invite;
 pub mod invoice;
+pub mod liquidity;
 pub mod node;
 pub mod notification;
+pub mod offer;
 pub mod payment;
+pub mod peer;
+pub mod probe;
+pub mod rebalance;
+pub mod report;
+pub mod role;
+pub mod swap;
 pub mod user;
+pub mod view;
+pub mod watch;
+
+use crate::auth::routes::auth_router;
+use crate::middleware::deprecation::deprecated_path;
+use axum::{Router, middleware};
+
+/// A published version of the API, served under `/api/<version>`.
+///
+/// A breaking change ships as a new variant with its own router, mounted
+/// next to the existing ones so clients can move over at their own pace.
+/// Routers of different versions can share handlers for everything that
+/// didn't change.
+#[derive(Debug, Clone, Copy)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 1] = [ApiVersion::V1];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/api/v1",
+        }
+    }
+
+    pub async fn router(self) -> Router {
+        match self {
+            ApiVersion::V1 => v1_resources().await.nest("/auth", auth_router()),
+        }
+    }
+}
+
+async fn v1_resources() -> Router {
+    Router::new()
+        .nest("/node", node::routes::node_router().await)
+        .nest("/account", account::routes::account_router().await)
+        .nest("/billing", billing::routes::billing_router().await)
+        .nest("/invite", invite::routes::invite_router().await)
+        .nest(
+            "/notification",
+            notification::routes::notification_router().await,
+        )
+        .nest("/events", event::routes::event_router().await)
+        .nest("/incidents", incident::routes::incident_router().await)
+        .nest(
+            "/escalations",
+            escalation::routes::escalation_router().await,
+        )
+        .nest("/channels", channel::routes::channel_router().await)
+        .nest("/payments", payment::routes::payment_router().await)
+        .nest("/forwards", forward::routes::forward_router().await)
+        .nest("/peers", peer::routes::peer_router().await)
+        .nest("/invoices", invoice::routes::invoice_router().await)
+        .nest("/offers", offer::routes::offer_router().await)
+        .nest("/user", user::routes::user_router().await)
+        .nest("/roles", role::routes::role_router().await)
+        .nest("/admin", admin::routes::admin_router().await)
+        .nest("/liquidity", liquidity::routes::liquidity_router().await)
+        .nest("/swaps", swap::routes::swap_router().await)
+        .nest("/reports", report::routes::report_router().await)
+        .nest("/rebalance", rebalance::routes::rebalance_router().await)
+        .nest("/probes", probe::routes::probe_router().await)
+        .nest("/analytics", analytics::routes::analytics_router().await)
+        .nest("/views", view::routes::view_router().await)
+        .nest("/watched-nodes", watch::routes::watch_router().await)
+        .nest(
+            "/credentials",
+            credential::routes::credential_router().await,
+        )
+}
+
+/// Routes whose bodies may exceed `DEFAULT_BODY_LIMIT_BYTES`, under every
+/// versioned and legacy prefix. Each caps its body with its own limit, and
+/// the router is merged after the default limit is layered so that one
+/// doesn't apply to them.
+pub async fn large_body_router() -> Router {
+    let mut router = Router::new();
+    for version in ApiVersion::ALL {
+        router = router.nest(version.prefix(), large_body_resources().await);
+    }
+    router.nest(
+        "/api",
+        large_body_resources()
+            .await
+            .layer(middleware::from_fn(deprecated_path)),
+    )
+}
+
+async fn large_body_resources() -> Router {
+    Router::new().nest("/node", node::routes::node_import_router().await)
+}
+
+/// The unversioned paths (`/api/...` and `/auth/...`) from before versioning.
+/// They serve v1 unchanged but mark every response as deprecated.
+pub async fn legacy_router() -> Router {
+    Router::new()
+        .nest("/api", v1_resources().await)
+        .nest("/auth", auth_router())
+        .layer(middleware::from_fn(deprecated_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::body_limit::{
+        DEFAULT_BODY_LIMIT_BYTES, IMPORT_BODY_LIMIT_BYTES, limit_body,
+    };
+    use axum::{
+        body::Body,
+        http::{
+            StatusCode,
+            header::{CONTENT_LENGTH, CONTENT_TYPE},
+        },
+        routing::post,
+    };
+    use tower::Service;
+
+    fn json_request(path: &str, body: String) -> axum::http::Request<Body> {
+        axum::http::Request::post(path)
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_accepts_exports_over_the_default_limit() {
+        let mut app = Router::new()
+            .route("/api/v1/other", post(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                DEFAULT_BODY_LIMIT_BYTES,
+                limit_body,
+            ))
+            .merge(large_body_router().await);
+        // An export body of exactly `size` bytes
+        let export = |size: usize| {
+            let prefix = r#"{"source":"bos","kind":"forwards","format":"csv","content":""#;
+            format!("{prefix}{}\"}}", "x".repeat(size - prefix.len() - 2))
+        };
+        let near_limit = export(IMPORT_BODY_LIMIT_BYTES - 1024);
+
+        // Past the body limit, the unauthenticated request stops at auth.
+        for path in ["/api/v1/node/node-a/import", "/api/node/node-a/import"] {
+            let response = app
+                .call(json_request(path, near_limit.clone()))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        let response = app
+            .call(json_request("/api/v1/other", near_limit))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let response = app
+            .call(json_request(
+                "/api/v1/node/node-a/import",
+                export(IMPORT_BODY_LIMIT_BYTES + 1),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
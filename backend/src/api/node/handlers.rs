@@ -1,23 +1,49 @@
 //! Handler functions for the node observability API.
-use crate::api::common::ApiResponse;
-use crate::database::models::CreateCredential;
+use crate::api::common::{ApiResponse, service_error_to_http, validation_error_response};
+use crate::database::models::{
+    ChannelAcceptorPolicyResponse, CreateCredential, EventSubscriptionStatus,
+    FeeAutomationPolicyResponse, FeePercentiles, FeePolicyChange, FeePolicyChangeQuery,
+    GraphNodeDetails, GraphSummary, HtlcInterceptorPolicyResponse, ImportHistoryRequest,
+    ImportSummary, JobResponse, MaintenanceWindow, NodeExport, NodeLabel, Permission,
+    StartMaintenanceRequest, UpdateChannelAcceptorRequest, UpdateFeeAutomationRequest,
+    UpdateHtlcInterceptorRequest, UpdateNodeLabelRequest,
+};
 use crate::errors::LightningError;
+use crate::repositories::audit_log_repository::AuditLogRepository;
 use crate::repositories::credential_repository::CredentialRepository;
-use crate::services::event_manager::{EventCollector, EventHandler, NodeSpecificEvent};
+use crate::services::channel_acceptor::ChannelAcceptorService;
+use crate::services::cln_rest::ClnRestNode;
+use crate::services::event_manager::{EventCollector, EventHandler, event_channel};
+use crate::services::fee_automation::FeeAutomationService;
+use crate::services::graph_sync::GraphService;
+use crate::services::history_import::HistoryImportService;
+use crate::services::htlc_interceptor::HtlcInterceptorService;
+use crate::services::maintenance::MaintenanceService;
+use crate::services::node_export::NodeExportService;
+use crate::services::node_labels::NodeLabelService;
 use crate::services::node_manager::LightningClient;
-use crate::services::node_manager::{
-    ClnConnection, ClnNode, ConnectionRequest, LndConnection, LndNode,
-};
+use crate::services::node_manager::{ClnNode, ConnectionRequest, connect_lnd};
+use crate::services::node_sync::NodeSyncService;
+use crate::services::subscription_health::{SubscriptionService, SubscriptionStats};
+use crate::utils::handlers_common::{NodeContext, handle_node_error, parse_public_key};
 use crate::utils::jwt::Claims;
-use crate::utils::{NodeId, NodeInfo};
+use crate::utils::{MessageVerification, NodeInfo};
 use axum::{
-    extract::{Extension, Json},
-    http::StatusCode,
+    body::{Body, Bytes},
+    extract::{Extension, Json, Path, Query},
+    http::{
+        StatusCode,
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    },
+    response::{IntoResponse, Response},
 };
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sqlx::SqlitePool;
 use std::sync::Arc;
+use tokio::io::AsyncReadExt;
 use tokio::sync::Mutex;
-use tokio::sync::mpsc;
+use validator::Validate;
 
 use uuid::Uuid;
 
@@ -29,6 +55,29 @@ pub struct NodeAuthResponse {
     pub credential_id: Option<String>,
 }
 
+/// Message to sign with the node's identity key
+#[derive(Debug, Deserialize, Validate)]
+pub struct SignMessageRequest {
+    #[validate(length(min = 1, max = 65536))]
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignMessageResponse {
+    pub message: String,
+    /// zbase32 signature, as produced by both LND and CLN
+    pub signature: String,
+}
+
+/// Signed message to check against the node
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyMessageRequest {
+    #[validate(length(min = 1, max = 65536))]
+    pub message: String,
+    #[validate(length(min = 1))]
+    pub signature: String,
+}
+
 #[axum::debug_handler]
 pub async fn authenticate_node(
     Extension(pool): Extension<SqlitePool>,
@@ -39,17 +88,17 @@ pub async fn authenticate_node(
     let node_info = match &payload {
         ConnectionRequest::Lnd(lnd_conn) => {
             tracing::info!("Attempting to authenticate LND node: {:?}", lnd_conn.id);
-            match LndNode::new(lnd_conn.clone()).await {
+            match connect_lnd(lnd_conn.clone()).await {
                 Ok(lnd_node) => {
-                    tracing::info!("LND node authenticated: {:?}", lnd_node.info);
+                    tracing::info!("LND node authenticated: {:?}", lnd_node.get_info());
 
-                    let info = lnd_node.info.clone();
+                    let info = lnd_node.get_info().clone();
 
-                    let (sender, receiver) = mpsc::channel::<NodeSpecificEvent>(32);
+                    let (sender, receiver) = event_channel();
 
                     let collector = EventCollector::new(sender);
                     let lnd_node_: Arc<Mutex<Box<dyn LightningClient + Send + Sync + 'static>>> =
-                        Arc::new(Mutex::new(Box::new(lnd_node)));
+                        Arc::new(Mutex::new(lnd_node));
 
                     collector.start_sending(info.pubkey, lnd_node_).await;
 
@@ -78,7 +127,7 @@ pub async fn authenticate_node(
                     tracing::error!("Failed to authenticate LND node: {}", e);
                     let error_response = ApiResponse::<()>::error(
                         format!("LND authentication failed: {e}"),
-                        "node_authentication_error",
+                        e.code(),
                         None,
                     );
                     return Err((
@@ -96,7 +145,7 @@ pub async fn authenticate_node(
 
                     let info = cln_node.info.clone();
 
-                    let (sender, receiver) = mpsc::channel::<NodeSpecificEvent>(32);
+                    let (sender, receiver) = event_channel();
 
                     let collector = EventCollector::new(sender);
                     let cln_node_: Arc<Mutex<Box<dyn LightningClient + Send + Sync + 'static>>> =
@@ -130,7 +179,32 @@ pub async fn authenticate_node(
                     tracing::error!("Failed to authenticate CLN node: {}", e);
                     let error_response = ApiResponse::<()>::error(
                         format!("CLN authentication failed: {e}"),
-                        "node_authentication_error",
+                        e.code(),
+                        None,
+                    );
+                    return Err((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        serde_json::to_string(&error_response).unwrap(),
+                    ));
+                }
+            }
+        }
+        ConnectionRequest::ClnRest(cln_rest_conn) => {
+            tracing::info!(
+                "Attempting to authenticate CLN REST node: {:?}",
+                cln_rest_conn.id
+            );
+            match ClnRestNode::new(cln_rest_conn.clone()).await {
+                Ok(cln_rest_node) => {
+                    // clnrest has no event subscription, so no collector is started here.
+                    tracing::info!("CLN REST node authenticated: {:?}", cln_rest_node.info);
+                    cln_rest_node.info
+                }
+                Err(e) => {
+                    tracing::error!("Failed to authenticate CLN REST node: {}", e);
+                    let error_response = ApiResponse::<()>::error(
+                        format!("CLN REST authentication failed: {e}"),
+                        e.code(),
                         None,
                     );
                     return Err((
@@ -142,16 +216,25 @@ pub async fn authenticate_node(
         }
     };
 
-    // If user is authenticated (has JWT token), store the credentials
+    // If user is authenticated (has JWT token) and may manage credentials, store them
     let (credential_stored, credential_id) = if let Some(user_claims) = claims {
-        match store_node_credentials(&pool, &user_claims, &payload, &node_info).await {
-            Ok(credential_id) => {
-                tracing::info!("Node credentials stored for user: {}", user_claims.sub);
-                (true, Some(credential_id))
-            }
-            Err(e) => {
-                tracing::warn!("Failed to store credentials: {}", e);
-                (false, None)
+        if !user_claims.has_permission(Permission::CredentialsManage) {
+            tracing::info!(
+                "User {} lacks {}, skipping credential storage",
+                user_claims.sub,
+                Permission::CredentialsManage
+            );
+            (false, None)
+        } else {
+            match store_node_credentials(&pool, &user_claims, &payload, &node_info).await {
+                Ok(credential_id) => {
+                    tracing::info!("Node credentials stored for user: {}", user_claims.sub);
+                    (true, Some(credential_id))
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to store credentials: {}", e);
+                    (false, None)
+                }
             }
         }
     } else {
@@ -217,6 +300,15 @@ async fn store_node_credentials(
                 Some(cln_conn.client_key.clone()),
                 Some(cln_conn.ca_cert.clone()),
             ),
+            ConnectionRequest::ClnRest(cln_rest_conn) => (
+                Some("clnrest".to_string()),
+                cln_rest_conn.rune.clone(), // The rune takes the macaroon's place
+                "".to_string(),
+                cln_rest_conn.address.clone(),
+                None,
+                None,
+                cln_rest_conn.ca_cert.clone(),
+            ),
         };
 
     // Create new credential record with all required fields
@@ -246,89 +338,11 @@ async fn store_node_credentials(
 /// Get node info using JWT token credentials
 #[axum::debug_handler]
 pub async fn get_node_info_jwt(
-    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
 ) -> Result<Json<NodeInfo>, (StatusCode, String)> {
-    let node_credentials = claims.node_credentials().ok_or_else(|| {
-        (
-            StatusCode::BAD_REQUEST,
-            "No node credentials found in token. Please authenticate your node first.".to_string(),
-        )
-    })?;
-
-    // Create connection request based on node type
-    match node_credentials.node_type.as_str() {
-        "lnd" => {
-            let lnd_conn = LndConnection {
-                id: NodeId::PublicKey(
-                    node_credentials
-                        .node_id
-                        .parse()
-                        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid node ID: {e}")))?,
-                ),
-                address: node_credentials.address.clone(),
-                macaroon: node_credentials.macaroon.clone(),
-                cert: node_credentials.tls_cert.clone(),
-            };
-
-            match LndNode::new(lnd_conn).await {
-                Ok(lnd_node) => Ok(Json(lnd_node.info)),
-                Err(e) => {
-                    tracing::error!("Failed to connect to LND node: {}", e);
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("LND connection failed: {e}"),
-                    ))
-                }
-            }
-        }
-        "cln" => {
-            let client_cert = node_credentials.client_cert.as_ref().ok_or_else(|| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    "Missing client certificate for CLN".to_string(),
-                )
-            })?;
-
-            let client_key = node_credentials.client_key.as_ref().ok_or_else(|| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    "Missing client key for CLN".to_string(),
-                )
-            })?;
-
-            let ca_cert = node_credentials.ca_cert.as_ref().ok_or_else(|| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    "Missing CA certificate for CLN".to_string(),
-                )
-            })?;
-
-            let cln_conn = ClnConnection {
-                id: NodeId::PublicKey(
-                    node_credentials
-                        .node_id
-                        .parse()
-                        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid node ID: {e}")))?,
-                ),
-                address: node_credentials.address.clone(),
-                ca_cert: ca_cert.clone(),
-                client_cert: client_cert.clone(),
-                client_key: client_key.clone(),
-            };
-
-            match ClnNode::new(cln_conn).await {
-                Ok(cln_node) => Ok(Json(cln_node.info)),
-                Err(e) => {
-                    tracing::error!("Failed to connect to CLN node: {}", e);
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("CLN connection failed: {e}"),
-                    ))
-                }
-            }
-        }
-        _ => Err((StatusCode::BAD_REQUEST, "Unsupported node type".to_string())),
-    }
+    let node_client = node_context.connect().await?;
+
+    Ok(Json(node_client.get_info().clone()))
 }
 
 // Keep existing functions...
@@ -337,13 +351,17 @@ pub async fn connect_lightning(
 ) -> Result<Box<dyn LightningClient + Send>, LightningError> {
     match conn {
         ConnectionRequest::Lnd(lnd_conn) => {
-            let node = LndNode::new(lnd_conn).await?;
-            Ok(Box::new(node))
+            let node: Box<dyn LightningClient + Send> = connect_lnd(lnd_conn).await?;
+            Ok(node)
         }
         ConnectionRequest::Cln(cln_conn) => {
             let node = ClnNode::new(cln_conn).await?;
             Ok(Box::new(node))
         }
+        ConnectionRequest::ClnRest(cln_rest_conn) => {
+            let node = ClnRestNode::new(cln_rest_conn).await?;
+            Ok(Box::new(node))
+        }
     }
 }
 
@@ -359,3 +377,556 @@ pub async fn get_node_info(
         }
     }
 }
+
+/// Queues a full reconciliation of channels, payments and invoices from a node.
+///
+/// Progress and the outcome are reported as `node_resync` events.
+#[axum::debug_handler]
+pub async fn resync_node(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(node_id): Path<String>,
+) -> Result<Json<ApiResponse<JobResponse>>, (StatusCode, String)> {
+    let service = NodeSyncService::new(&pool);
+    match service
+        .request_resync(claims.account_id(), claims.user_id(), &node_id)
+        .await
+    {
+        Ok(job) => Ok(Json(ApiResponse::success(
+            JobResponse::from(job),
+            "Node resync queued",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Queues an NDJSON export of everything stored about a node: channels,
+/// payments, invoices, forwards and events.
+#[axum::debug_handler]
+pub async fn export_node(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(node_id): Path<String>,
+) -> Result<Json<ApiResponse<NodeExport>>, (StatusCode, String)> {
+    match NodeExportService::new(&pool)
+        .request_export(claims.account_id(), &node_id)
+        .await
+    {
+        Ok(export) => Ok(Json(ApiResponse::success(export, "Node export queued"))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Downloads a finished node export bundle.
+#[axum::debug_handler]
+pub async fn download_node_export(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path((node_id, export_id)): Path<(String, String)>,
+) -> Result<Response, (StatusCode, String)> {
+    let file = NodeExportService::new(&pool)
+        .open_export(claims.account_id(), &node_id, &export_id)
+        .await
+        .map_err(service_error_to_http)?;
+
+    // Streamed in chunks; bundles of busy nodes run to hundreds of megabytes.
+    let body = Body::from_stream(futures::stream::try_unfold(file, |mut file| async move {
+        let mut buf = vec![0u8; 64 * 1024];
+        let read = file.read(&mut buf).await?;
+        buf.truncate(read);
+        Ok::<_, std::io::Error>((read > 0).then(|| (Bytes::from(buf), file)))
+    }));
+    let disposition = format!("attachment; filename=\"nodegaze-export-{export_id}.ndjson\"");
+
+    Ok((
+        [
+            (CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Imports forwarding or payment history exported by BoS, LNDg or
+/// ThunderHub, so the node's analytics don't start from zero.
+#[axum::debug_handler]
+pub async fn import_node_history(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(node_id): Path<String>,
+    Json(payload): Json<ImportHistoryRequest>,
+) -> Result<Json<ApiResponse<ImportSummary>>, (StatusCode, String)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err(validation_error_response(validation_errors));
+    }
+
+    match HistoryImportService::new(&pool)
+        .import(claims.account_id(), &node_id, payload)
+        .await
+    {
+        Ok(summary) => Ok(Json(ApiResponse::success(summary, "History imported"))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Puts a node under maintenance for a while: events raised meanwhile are
+/// labelled, and only critical ones are sent to notification endpoints.
+#[axum::debug_handler]
+pub async fn start_maintenance(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(node_id): Path<String>,
+    Json(payload): Json<StartMaintenanceRequest>,
+) -> Result<Json<ApiResponse<MaintenanceWindow>>, (StatusCode, String)> {
+    let service = MaintenanceService::new(&pool);
+    match service
+        .start(claims.account_id(), claims.user_id(), &node_id, payload)
+        .await
+    {
+        Ok(window) => Ok(Json(ApiResponse::success(
+            window,
+            "Node maintenance started",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Retrieves the display name, environment and notes given to a node.
+#[axum::debug_handler]
+pub async fn get_node_label(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(node_id): Path<String>,
+) -> Result<Json<ApiResponse<Option<NodeLabel>>>, (StatusCode, String)> {
+    let service = NodeLabelService::new(&pool);
+    match service.get_label(claims.account_id(), &node_id).await {
+        Ok(label) => Ok(Json(ApiResponse::success(
+            label,
+            "Node label retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Updates the display name, environment or notes of a node.
+#[axum::debug_handler]
+pub async fn update_node_label(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(node_id): Path<String>,
+    Json(payload): Json<UpdateNodeLabelRequest>,
+) -> Result<Json<ApiResponse<NodeLabel>>, (StatusCode, String)> {
+    let service = NodeLabelService::new(&pool);
+    match service
+        .update_label(claims.account_id(), claims.user_id(), &node_id, payload)
+        .await
+    {
+        Ok(label) => Ok(Json(ApiResponse::success(
+            label,
+            "Node label updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Lists the background subscriptions held open against a node and how
+/// they're doing, so a stream that silently stopped delivering shows up.
+#[axum::debug_handler]
+pub async fn get_node_subscriptions(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(node_id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<SubscriptionStats>>>, (StatusCode, String)> {
+    let service = SubscriptionService::new(&pool);
+    match service
+        .get_subscriptions(claims.account_id(), &node_id)
+        .await
+    {
+        Ok(subscriptions) => Ok(Json(ApiResponse::success(
+            subscriptions,
+            "Subscription health retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Shows whether one event type of a node is being ingested.
+#[axum::debug_handler]
+pub async fn get_event_subscription(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path((node_id, event_type)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<EventSubscriptionStatus>>, (StatusCode, String)> {
+    let service = SubscriptionService::new(&pool);
+    match service
+        .get_event_subscription(claims.account_id(), &node_id, &event_type)
+        .await
+    {
+        Ok(status) => Ok(Json(ApiResponse::success(
+            status,
+            "Event subscription retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Resumes ingesting an event type, restarting the node's event stream if it
+/// has died.
+#[axum::debug_handler]
+pub async fn subscribe_event_type(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path((node_id, event_type)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<EventSubscriptionStatus>>, (StatusCode, String)> {
+    let service = SubscriptionService::new(&pool);
+    match service
+        .subscribe(claims.account_id(), claims.user_id(), &node_id, &event_type)
+        .await
+    {
+        Ok(status) => Ok(Json(ApiResponse::success(
+            status,
+            "Event subscription resumed successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Pauses ingesting an event type, e.g. payments on a high-volume node.
+#[axum::debug_handler]
+pub async fn unsubscribe_event_type(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path((node_id, event_type)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<EventSubscriptionStatus>>, (StatusCode, String)> {
+    let service = SubscriptionService::new(&pool);
+    match service
+        .unsubscribe(claims.account_id(), claims.user_id(), &node_id, &event_type)
+        .await
+    {
+        Ok(status) => Ok(Json(ApiResponse::success(
+            status,
+            "Event subscription paused successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Signs a message with the node's key, e.g. to prove ownership of the node.
+#[axum::debug_handler]
+pub async fn sign_message(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<SignMessageRequest>,
+) -> Result<Json<ApiResponse<SignMessageResponse>>, (StatusCode, String)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err(validation_error_response(validation_errors));
+    }
+
+    let node_client = node_context.connect().await?;
+
+    let signature = node_client
+        .sign_message(&payload.message)
+        .await
+        .map_err(|e| handle_node_error(e, "sign message"))?;
+
+    // The signature is already out, so a failed audit write is only logged.
+    if let Err(e) = AuditLogRepository::new(&pool)
+        .create_log(
+            claims.account_id(),
+            Some(claims.user_id()),
+            "node_message_signed",
+            "node",
+            Some(node_context.node_id()),
+            &json!({ "message": payload.message }),
+        )
+        .await
+    {
+        tracing::warn!("Failed to audit message signing: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(
+        SignMessageResponse {
+            message: payload.message,
+            signature,
+        },
+        "Message signed successfully",
+    )))
+}
+
+/// Verifies a signed message and reports which node key produced it.
+#[axum::debug_handler]
+pub async fn verify_message(
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<VerifyMessageRequest>,
+) -> Result<Json<ApiResponse<MessageVerification>>, (StatusCode, String)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err(validation_error_response(validation_errors));
+    }
+
+    let node_client = node_context.connect().await?;
+
+    let verification = node_client
+        .verify_message(&payload.message, &payload.signature)
+        .await
+        .map_err(|e| handle_node_error(e, "verify message"))?;
+
+    Ok(Json(ApiResponse::success(
+        verification,
+        "Message verified successfully",
+    )))
+}
+
+/// Returns the channel acceptor rules of the node in the token.
+#[axum::debug_handler]
+pub async fn get_channel_acceptor(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<ChannelAcceptorPolicyResponse>>, (StatusCode, String)> {
+    let service = ChannelAcceptorService::new(&pool);
+    match service
+        .get_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "Channel acceptor policy retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Replaces the channel acceptor rules of the node in the token (LND only).
+#[axum::debug_handler]
+pub async fn update_channel_acceptor(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<UpdateChannelAcceptorRequest>,
+) -> Result<Json<ApiResponse<ChannelAcceptorPolicyResponse>>, (StatusCode, String)> {
+    let service = ChannelAcceptorService::new(&pool);
+    match service
+        .update_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            payload,
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "Channel acceptor policy updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the HTLC interceptor rules of the node in the token.
+#[axum::debug_handler]
+pub async fn get_htlc_interceptor(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<HtlcInterceptorPolicyResponse>>, (StatusCode, String)> {
+    let service = HtlcInterceptorService::new(&pool);
+    match service
+        .get_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "HTLC interceptor policy retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Replaces the HTLC interceptor rules of the node in the token (LND only).
+#[axum::debug_handler]
+pub async fn update_htlc_interceptor(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<UpdateHtlcInterceptorRequest>,
+) -> Result<Json<ApiResponse<HtlcInterceptorPolicyResponse>>, (StatusCode, String)> {
+    let service = HtlcInterceptorService::new(&pool);
+    match service
+        .update_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            payload,
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "HTLC interceptor policy updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the fee automation rules of the node in the token.
+#[axum::debug_handler]
+pub async fn get_fee_automation(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<FeeAutomationPolicyResponse>>, (StatusCode, String)> {
+    let service = FeeAutomationService::new(&pool);
+    match service
+        .get_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "Fee automation policy retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Replaces the fee automation rules of the node in the token.
+#[axum::debug_handler]
+pub async fn update_fee_automation(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<UpdateFeeAutomationRequest>,
+) -> Result<Json<ApiResponse<FeeAutomationPolicyResponse>>, (StatusCode, String)> {
+    let service = FeeAutomationService::new(&pool);
+    match service
+        .update_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            payload,
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "Fee automation policy updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Runs the fee automation rules of the node in the token now, returning the
+/// changes made (or, in dry-run mode, proposed).
+#[axum::debug_handler]
+pub async fn run_fee_automation(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<Vec<FeePolicyChange>>>, (StatusCode, String)> {
+    let service = FeeAutomationService::new(&pool);
+    match service
+        .run_now(
+            claims.account_id(),
+            claims.user_id(),
+            &node_context.credentials,
+        )
+        .await
+    {
+        Ok(changes) => Ok(Json(ApiResponse::success(
+            changes,
+            "Fee automation run completed successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Lists the fee changes fee automation made on the node in the token.
+#[axum::debug_handler]
+pub async fn get_fee_automation_history(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<FeePolicyChangeQuery>,
+) -> Result<Json<ApiResponse<Vec<FeePolicyChange>>>, (StatusCode, String)> {
+    let service = FeeAutomationService::new(&pool);
+    match service
+        .get_history(claims.account_id(), node_context.node_id(), query.limit)
+        .await
+    {
+        Ok(changes) => Ok(Json(ApiResponse::success(
+            changes,
+            "Fee automation history retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the size and freshness of the graph mirrored from the node in the token.
+#[axum::debug_handler]
+pub async fn get_graph_summary(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<GraphSummary>>, (StatusCode, String)> {
+    match GraphService::new(&pool)
+        .get_summary(node_context.node_id())
+        .await
+    {
+        Ok(summary) => Ok(Json(ApiResponse::success(
+            summary,
+            "Graph summary retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Compares the node's fee rates with those advertised across the mirrored graph.
+#[axum::debug_handler]
+pub async fn get_graph_fee_percentiles(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<FeePercentiles>>, (StatusCode, String)> {
+    match GraphService::new(&pool)
+        .get_fee_percentiles(node_context.node_id())
+        .await
+    {
+        Ok(percentiles) => Ok(Json(ApiResponse::success(
+            percentiles,
+            "Fee percentiles retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Looks up a node in the mirrored graph by public key.
+#[axum::debug_handler]
+pub async fn get_graph_node(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<ApiResponse<GraphNodeDetails>>, (StatusCode, String)> {
+    let pubkey = parse_public_key(&pubkey)?.to_string();
+
+    match GraphService::new(&pool)
+        .get_node(node_context.node_id(), &pubkey)
+        .await
+    {
+        Ok(node) => Ok(Json(ApiResponse::success(
+            node,
+            "Graph node retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -3,11 +3,29 @@
 //! These routes map specific API paths to handler functions responsible for
 //! serving channel statistics, node events, and other lightning-related information.
 
-use super::handlers::{authenticate_node, get_node_info, get_node_info_jwt};
-use crate::auth::middleware::{jwt_auth, node_credentials_required, optional_jwt_auth};
+use super::handlers::{
+    authenticate_node, download_node_export, export_node, get_channel_acceptor,
+    get_event_subscription, get_fee_automation, get_fee_automation_history,
+    get_graph_fee_percentiles, get_graph_node, get_graph_summary, get_htlc_interceptor,
+    get_node_info, get_node_info_jwt, get_node_label, get_node_subscriptions, import_node_history,
+    resync_node, run_fee_automation, sign_message, start_maintenance, subscribe_event_type,
+    unsubscribe_event_type, update_channel_acceptor, update_fee_automation,
+    update_htlc_interceptor, update_node_label, verify_message,
+};
+use crate::auth::middleware::{
+    admin_auth, jwt_auth, node_credentials_required, optional_jwt_auth, require_node_access,
+    require_node_read, require_node_sign, require_read_write_access_level,
+};
+use crate::middleware::body_limit::{
+    CREDENTIAL_BODY_LIMIT_BYTES, IMPORT_BODY_LIMIT_BYTES, limit_body,
+};
+use crate::middleware::plan_limits::enforce_plan_limit;
+use crate::services::billing::PlanResource;
 use axum::{
-    Router, middleware,
-    routing::{get, post},
+    Router,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, patch, post, put},
 };
 
 pub async fn node_router() -> Router {
@@ -15,15 +33,201 @@ pub async fn node_router() -> Router {
         // Node authentication - can work with or without JWT token
         .route(
             "/auth",
-            post(authenticate_node).layer(middleware::from_fn(optional_jwt_auth)), // This adds Option<Claims>
+            post(authenticate_node)
+                .layer(middleware::from_fn_with_state(
+                    PlanResource::Nodes,
+                    enforce_plan_limit,
+                ))
+                .layer(middleware::from_fn_with_state(
+                    CREDENTIAL_BODY_LIMIT_BYTES,
+                    limit_body,
+                ))
+                .layer(middleware::from_fn(optional_jwt_auth)), // This adds Option<Claims>
         )
         // Public route (no authentication required)
-        .route("/info", post(get_node_info))
+        .route(
+            "/info",
+            post(get_node_info).layer(middleware::from_fn_with_state(
+                CREDENTIAL_BODY_LIMIT_BYTES,
+                limit_body,
+            )),
+        )
         // Protected routes (require JWT token with node credentials)
         .route(
             "/info/jwt",
             get(get_node_info_jwt)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/sign-message",
+            post(sign_message)
+                .layer(middleware::from_fn(require_node_sign))
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/verify-message",
+            post(verify_message)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/channel-acceptor",
+            get(get_channel_acceptor)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/channel-acceptor",
+            put(update_channel_acceptor)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/htlc-interceptor",
+            get(get_htlc_interceptor)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/htlc-interceptor",
+            put(update_htlc_interceptor)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/fee-automation",
+            get(get_fee_automation)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/fee-automation",
+            put(update_fee_automation)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/fee-automation/run",
+            post(run_fee_automation)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/fee-automation/history",
+            get(get_fee_automation_history)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/graph",
+            get(get_graph_summary)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/graph/fees",
+            get(get_graph_fee_percentiles)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/graph/nodes/{pubkey}",
+            get(get_graph_node)
+                .layer(middleware::from_fn(require_node_read))
                 .layer(middleware::from_fn(node_credentials_required))
                 .layer(middleware::from_fn(jwt_auth)),
         )
+        .route(
+            "/{id}",
+            get(get_node_label)
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}",
+            patch(update_node_label)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/resync",
+            post(resync_node)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/export",
+            post(export_node)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/export/{export_id}",
+            get(download_node_export)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/maintenance",
+            post(start_maintenance)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/subscriptions",
+            get(get_node_subscriptions)
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/subscriptions/{event_type}",
+            get(get_event_subscription)
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/subscriptions/{event_type}",
+            post(subscribe_event_type)
+                .delete(unsubscribe_event_type)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(require_node_access))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+}
+
+/// History import, which takes bodies over the default limit and so is
+/// mounted by `api::large_body_router`.
+pub async fn node_import_router() -> Router {
+    Router::new().route(
+        "/{id}/import",
+        post(import_node_history)
+            .layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT_BYTES))
+            .layer(middleware::from_fn(require_read_write_access_level))
+            .layer(middleware::from_fn(require_node_access))
+            .layer(middleware::from_fn(jwt_auth))
+            .layer(middleware::from_fn_with_state(
+                IMPORT_BODY_LIMIT_BYTES,
+                limit_body,
+            )),
+    )
 }
@@ -4,8 +4,11 @@ use crate::api::common::{
     ApiResponse, PaginatedData, PaginationFilter, PaginationMeta, service_error_to_http,
 };
 use crate::database::models::{
-    CreateNotificationRequest, EventResponse, Notification, UpdateNotificationRequest,
+    CreateNotificationRequest, EventResponse, FirehoseBatch, FirehoseQuery, Notification,
+    NotificationListQuery, NotificationResponse, NotificationTestResult, Permission,
+    TestNotificationRequest, UpdateNotificationRequest,
 };
+use crate::errors::{ErrorCode, ServiceError};
 use crate::services::notification_service::NotificationService;
 use crate::services::user_service::UserService;
 use crate::utils::jwt::Claims;
@@ -27,14 +30,18 @@ pub async fn create_notification(
 
     // Get user details
     let user_service = UserService::new(&pool);
-    let user = user_service.get_user_required(user_id).await.map_err(|e| {
-        tracing::error!("User not found for ID {}: {}", user_id, e);
-        let error_response = ApiResponse::<()>::error("User not found", "user_not_found", None);
-        (
-            StatusCode::NOT_FOUND,
-            serde_json::to_string(&error_response).unwrap(),
-        )
-    })?;
+    let user = user_service
+        .get_user_for_claims(&claims)
+        .await
+        .map_err(|e| {
+            tracing::error!("User not found for ID {}: {}", user_id, e);
+            let error_response =
+                ApiResponse::<()>::error("User not found", ErrorCode::NotFound, None);
+            (
+                StatusCode::NOT_FOUND,
+                serde_json::to_string(&error_response).unwrap(),
+            )
+        })?;
 
     let service = NotificationService::new(&pool);
     match service.create_notification(payload, &user).await {
@@ -46,16 +53,27 @@ pub async fn create_notification(
     }
 }
 
-/// Retrieves all notifications for the user's account.
+/// Retrieves all notifications for the user's account, marking unhealthy ones.
+/// Admins may include deleted ones with `?include_deleted=true`.
 #[axum::debug_handler]
 pub async fn get_notifications(
     Extension(pool): Extension<SqlitePool>,
     Extension(claims): Extension<Claims>,
-) -> Result<ResponseJson<ApiResponse<Vec<Notification>>>, (StatusCode, String)> {
+    Query(query): Query<NotificationListQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<NotificationResponse>>>, (StatusCode, String)> {
     let account_id = claims.account_id();
 
+    if query.include_deleted && !claims.is_admin() {
+        return Err(service_error_to_http(ServiceError::permission_denied(
+            "Only admins can list deleted notifications",
+        )));
+    }
+
     let service = NotificationService::new(&pool);
-    match service.get_notifications_for_account(account_id).await {
+    match service
+        .get_notifications_for_account(account_id, query.include_deleted)
+        .await
+    {
         Ok(notifications) => Ok(ResponseJson(ApiResponse::success(
             notifications,
             "Notifications retrieved successfully",
@@ -113,7 +131,10 @@ pub async fn delete_notification(
     let account_id = claims.account_id();
 
     let service = NotificationService::new(&pool);
-    match service.delete_notification(&id, account_id).await {
+    match service
+        .delete_notification(&id, account_id, claims.user_id())
+        .await
+    {
         Ok(_) => Ok(ResponseJson(ApiResponse::success(
             (),
             "Notification deleted successfully",
@@ -122,6 +143,28 @@ pub async fn delete_notification(
     }
 }
 
+/// Restores a recently deleted notification.
+#[axum::debug_handler]
+pub async fn restore_notification(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<ResponseJson<ApiResponse<Notification>>, (StatusCode, String)> {
+    let account_id = claims.account_id();
+
+    let service = NotificationService::new(&pool);
+    match service
+        .restore_notification(&id, account_id, claims.user_id())
+        .await
+    {
+        Ok(notification) => Ok(ResponseJson(ApiResponse::success(
+            notification,
+            "Notification restored successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
 /// Retrieves events for a specific notification endpoint.
 #[axum::debug_handler]
 pub async fn get_notification_events(
@@ -162,3 +205,76 @@ pub async fn get_notification_events(
         Err(error) => Err(service_error_to_http(error)),
     }
 }
+
+/// Pages through what a firehose endpoint was sent, from a cursor.
+#[axum::debug_handler]
+pub async fn get_firehose_events(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Query(query): Query<FirehoseQuery>,
+) -> Result<ResponseJson<ApiResponse<FirehoseBatch>>, (StatusCode, String)> {
+    let service = NotificationService::new(&pool);
+    match service
+        .firehose_catch_up(&id, claims.account_id(), query)
+        .await
+    {
+        Ok(batch) => Ok(ResponseJson(ApiResponse::success(
+            batch,
+            "Firehose events retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Previews (or fires) a notification against a historical or sample event.
+/// Previews only need read access; actually delivering one needs
+/// `notifications:write`.
+#[axum::debug_handler]
+pub async fn test_notification(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(payload): Json<TestNotificationRequest>,
+) -> Result<ResponseJson<ApiResponse<NotificationTestResult>>, (StatusCode, String)> {
+    if !payload.dry_run && !claims.has_permission(Permission::NotificationsWrite) {
+        let error_response = ApiResponse::<()>::error(
+            format!("Permission '{}' required", Permission::NotificationsWrite),
+            ErrorCode::PermissionDenied,
+            None,
+        );
+        return Err((
+            StatusCode::FORBIDDEN,
+            serde_json::to_string(&error_response).unwrap(),
+        ));
+    }
+
+    let user_id = claims.sub.as_str();
+
+    let user_service = UserService::new(&pool);
+    let user = user_service
+        .get_user_for_claims(&claims)
+        .await
+        .map_err(|e| {
+            tracing::error!("User not found for ID {}: {}", user_id, e);
+            let error_response =
+                ApiResponse::<()>::error("User not found", ErrorCode::NotFound, None);
+            (
+                StatusCode::NOT_FOUND,
+                serde_json::to_string(&error_response).unwrap(),
+            )
+        })?;
+
+    let service = NotificationService::new(&pool);
+    match service.test_notification(&id, payload, &user).await {
+        Ok(result) => {
+            let message = if result.dry_run {
+                "Notification preview rendered successfully"
+            } else {
+                "Test notification processed"
+            };
+            Ok(ResponseJson(ApiResponse::success(result, message)))
+        }
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
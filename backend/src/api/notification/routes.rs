@@ -1,10 +1,15 @@
 //! Defines the HTTP routes for notification management.
 
 use super::handlers::{
-    create_notification, delete_notification, get_notification_by_id, get_notification_events,
-    get_notifications, update_notification,
+    create_notification, delete_notification, get_firehose_events, get_notification_by_id,
+    get_notification_events, get_notifications, restore_notification, test_notification,
+    update_notification,
 };
-use crate::auth::middleware::jwt_auth;
+use crate::auth::middleware::{
+    jwt_auth, require_notifications_read, require_notifications_write, require_verified_email,
+};
+use crate::middleware::plan_limits::enforce_plan_limit;
+use crate::services::billing::PlanResource;
 use axum::{
     Router, middleware,
     routing::{delete, get, post, put},
@@ -12,16 +17,62 @@ use axum::{
 
 pub async fn notification_router() -> Router {
     Router::new()
-        .route("/", post(create_notification))
+        .route(
+            "/",
+            post(create_notification)
+                .layer(middleware::from_fn_with_state(
+                    PlanResource::NotificationChannels,
+                    enforce_plan_limit,
+                ))
+                .layer(middleware::from_fn(require_verified_email))
+                .layer(middleware::from_fn(require_notifications_write)),
+        )
+        .layer(middleware::from_fn(jwt_auth))
+        .route(
+            "/",
+            get(get_notifications).layer(middleware::from_fn(require_notifications_read)),
+        )
+        .layer(middleware::from_fn(jwt_auth))
+        .route(
+            "/{id}",
+            get(get_notification_by_id).layer(middleware::from_fn(require_notifications_read)),
+        )
+        .layer(middleware::from_fn(jwt_auth))
+        .route(
+            "/{id}",
+            put(update_notification)
+                .layer(middleware::from_fn(require_verified_email))
+                .layer(middleware::from_fn(require_notifications_write)),
+        )
         .layer(middleware::from_fn(jwt_auth))
-        .route("/", get(get_notifications))
+        .route(
+            "/{id}",
+            delete(delete_notification).layer(middleware::from_fn(require_notifications_write)),
+        )
         .layer(middleware::from_fn(jwt_auth))
-        .route("/{id}", get(get_notification_by_id))
+        .route(
+            "/{id}/restore",
+            post(restore_notification)
+                .layer(middleware::from_fn_with_state(
+                    PlanResource::NotificationChannels,
+                    enforce_plan_limit,
+                ))
+                .layer(middleware::from_fn(require_notifications_write)),
+        )
         .layer(middleware::from_fn(jwt_auth))
-        .route("/{id}", put(update_notification))
+        .route(
+            "/{id}/events",
+            get(get_notification_events).layer(middleware::from_fn(require_notifications_read)),
+        )
         .layer(middleware::from_fn(jwt_auth))
-        .route("/{id}", delete(delete_notification))
+        .route(
+            "/{id}/firehose",
+            get(get_firehose_events).layer(middleware::from_fn(require_notifications_read)),
+        )
         .layer(middleware::from_fn(jwt_auth))
-        .route("/{id}/events", get(get_notification_events))
+        .route(
+            "/{id}/test",
+            post(test_notification).layer(middleware::from_fn(require_notifications_read)),
+        )
         .layer(middleware::from_fn(jwt_auth))
 }
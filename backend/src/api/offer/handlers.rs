@@ -0,0 +1,43 @@
+//! Handler functions for the offers API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{OfferStats, OfferStatsQuery};
+use crate::services::account_settings_service::AccountSettingsService;
+use crate::services::offer_analytics::{offer_stats, validate_offer_id};
+use crate::utils::handlers_common::{NodeContext, handle_node_error};
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+};
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+/// Returns invoice requests, payments, volume and daily payments of one of
+/// the node's BOLT12 offers.
+#[axum::debug_handler]
+pub async fn get_offer_stats(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(offer_id): Path<String>,
+    Query(query): Query<OfferStatsQuery>,
+) -> Result<Json<ApiResponse<OfferStats>>, (StatusCode, String)> {
+    validate_offer_id(&offer_id).map_err(service_error_to_http)?;
+    let tz = AccountSettingsService::new(&pool)
+        .get_settings(claims.account_id())
+        .await
+        .map_err(service_error_to_http)?
+        .tz();
+
+    let node_client = node_context.connect().await?;
+    let invoices = node_client
+        .list_offer_invoices(&offer_id)
+        .await
+        .map_err(|e| handle_node_error(e, "list offer invoices"))?;
+
+    Ok(Json(ApiResponse::success(
+        offer_stats(&offer_id, &invoices, query, Utc::now(), tz),
+        "Offer stats retrieved successfully",
+    )))
+}
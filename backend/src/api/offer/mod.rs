@@ -0,0 +1,7 @@
+//! Module for BOLT12 offer API endpoints.
+//!
+//! This module reports how the node's offers perform: invoice requests,
+//! payments and volume per offer (CLN only).
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,15 @@
+//! Defines the HTTP routes for BOLT12 offers.
+
+use super::handlers::get_offer_stats;
+use crate::auth::middleware::{jwt_auth, node_credentials_required, require_node_read};
+use axum::{Router, middleware, routing::get};
+
+pub async fn offer_router() -> Router {
+    Router::new().route(
+        "/{offer_id}/stats",
+        get(get_offer_stats)
+            .layer(middleware::from_fn(require_node_read))
+            .layer(middleware::from_fn(node_credentials_required))
+            .layer(middleware::from_fn(jwt_auth)),
+    )
+}
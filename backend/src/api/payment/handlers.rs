@@ -2,38 +2,61 @@
 //!
 //! These functions process requests for payment data and return payment-specific information.
 
-use crate::utils::handlers_common::{
-    create_node_client, extract_node_credentials, handle_node_error, parse_payment_hash,
-    parse_public_key,
-};
+use crate::utils::handlers_common::{NodeContext, handle_node_error, parse_payment_hash};
 use crate::utils::jwt::Claims;
 use crate::{
     api::common::{
-        ApiResponse, NumericOperator, PaginatedData, PaginationFilter, PaginationMeta,
-        apply_pagination, deserialize_states, validation_error_response,
+        ApiResponse, FieldSelection, NumericOperator, PaginatedData, PaginationFilter,
+        PaginationMeta, Sparse, apply_pagination, deserialize_states, service_error_to_http,
+        validation_error_response,
     },
+    database::models::{PaymentStats, PaymentStatsQuery},
+    services::{node_sync::NodeSyncService, payment_stats::PaymentStatsService},
     utils::{PaymentDetails, PaymentState, PaymentSummary, PaymentType, deserialize_payment_types},
 };
 use axum::{
     Json,
     extract::{Extension, Path, Query},
     http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
 };
 use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::convert::Infallible;
 use validator::Validate;
 
+/// Returns the node's payment counts, volume, fees and failure rate over a
+/// window, as of its last resync.
+#[axum::debug_handler]
+pub async fn get_payment_stats(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<PaymentStatsQuery>,
+) -> Result<Json<ApiResponse<PaymentStats>>, (StatusCode, String)> {
+    match PaymentStatsService::new(&pool)
+        .get_stats(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(stats) => Ok(Json(ApiResponse::success(
+            stats,
+            "Payment stats retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
 /// Handler for getting payment details
 #[axum::debug_handler]
 pub async fn get_payment_details(
-    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
     Path(payment_hash): Path<String>,
 ) -> Result<Json<ApiResponse<PaymentDetails>>, (StatusCode, String)> {
     let payment_hash = parse_payment_hash(&payment_hash)?;
-    let node_credentials = extract_node_credentials(&claims)?;
-    let public_key = parse_public_key(&node_credentials.node_id)?;
 
-    let node_client = create_node_client(node_credentials, public_key).await?;
+    let node_client = node_context.connect().await?;
 
     let payment_details = node_client
         .get_payment_details(&payment_hash)
@@ -46,27 +69,78 @@ pub async fn get_payment_details(
     )))
 }
 
+/// Streams live progress of an outgoing payment as server-sent events.
+///
+/// Each `progress` event carries a `PaymentProgress` snapshot and the stream
+/// ends once the payment settles or fails. Node errors arrive as `error` events.
+#[axum::debug_handler]
+pub async fn track_payment(
+    Extension(node_context): Extension<NodeContext>,
+    Path(payment_hash): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let payment_hash = parse_payment_hash(&payment_hash)?;
+
+    let node_client = node_context.connect().await?;
+
+    let updates = node_client
+        .track_payment(&payment_hash)
+        .await
+        .map_err(|e| handle_node_error(e, "track payment"))?;
+
+    // The stream owns its own RPC handle, so give the node slot back while it runs.
+    drop(node_client);
+
+    let events = updates.map(|update| {
+        let event = match update {
+            Ok(progress) => Event::default()
+                .event("progress")
+                .json_data(&progress)
+                .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+            Err(e) => Event::default().event("error").data(e.to_string()),
+        };
+        Ok(event)
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
 /// Handler for listing all payments
 #[axum::debug_handler]
 pub async fn list_payments(
+    Extension(pool): Extension<SqlitePool>,
     Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
     Query(filter): Query<PaymentFilter>,
-) -> Result<Json<ApiResponse<PaginatedData<PaymentSummary>>>, (StatusCode, String)> {
+    Query(fields): Query<FieldSelection>,
+) -> Result<Json<ApiResponse<PaginatedData<Sparse<PaymentSummary>>>>, (StatusCode, String)> {
     if let Err(validation_errors) = filter.validate() {
         return Err(validation_error_response(validation_errors));
     }
 
-    let node_credentials = extract_node_credentials(&claims)?;
-    let public_key = parse_public_key(&node_credentials.node_id)?;
+    // Destinations are matched against the synced payments, which know who
+    // each outgoing payment went to.
+    let destination_hashes = match filter.destination.as_deref().map(str::trim) {
+        Some(destination) if !destination.is_empty() => Some(
+            NodeSyncService::new(&pool)
+                .payment_hashes_to(claims.account_id(), node_context.node_id(), destination)
+                .await
+                .map_err(service_error_to_http)?,
+        ),
+        _ => None,
+    };
 
-    let node_client = create_node_client(node_credentials, public_key).await?;
+    let node_client = node_context.connect().await?;
 
-    let all_payments = node_client
+    let mut all_payments = node_client
         .list_payments()
         .await
         .map_err(|e| handle_node_error(e, "list payments"))?;
 
-    process_payments_with_filters(all_payments, &filter).await
+    if let Some(hashes) = destination_hashes {
+        all_payments.retain(|payment| hashes.contains(&payment.payment_hash));
+    }
+
+    process_payments_with_filters(all_payments, &filter, &fields).await
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -98,6 +172,10 @@ pub struct PaymentFilterRequest {
     /// Payment type filter (NEW - only for payments)
     #[serde(default, deserialize_with = "deserialize_payment_types")]
     pub payment_types: Option<Vec<PaymentType>>,
+
+    /// Destination pubkey prefix or alias substring, as of the last resync
+    #[validate(length(max = 66))]
+    pub destination: Option<String>,
 }
 
 pub type PaymentFilter = PaymentFilterRequest;
@@ -177,13 +255,15 @@ fn apply_payment_filters(
 async fn process_payments_with_filters(
     all_payments: Vec<PaymentSummary>,
     filter: &PaymentFilter,
-) -> Result<Json<ApiResponse<PaginatedData<PaymentSummary>>>, (StatusCode, String)> {
+    fields: &FieldSelection,
+) -> Result<Json<ApiResponse<PaginatedData<Sparse<PaymentSummary>>>>, (StatusCode, String)> {
     let filtered_payments = apply_payment_filters(all_payments, filter);
     let total_filtered_count = filtered_payments.len() as u64;
     let pagination_filter = filter.to_pagination_filter();
     let paginated_payments = apply_pagination(filtered_payments, &pagination_filter);
     let pagination_meta = PaginationMeta::from_filter(&pagination_filter, total_filtered_count);
-    let paginated_data = PaginatedData::new(paginated_payments, total_filtered_count);
+    let paginated_data =
+        PaginatedData::new(fields.select(paginated_payments), total_filtered_count);
 
     Ok(Json(ApiResponse::ok_paginated(
         paginated_data,
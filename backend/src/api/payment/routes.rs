@@ -3,21 +3,37 @@
 //! These routes provide endpoints for accessing and updating payment-specific
 //! data.
 
-use super::handlers::{get_payment_details, list_payments};
-use crate::auth::middleware::{jwt_auth, node_credentials_required};
+use super::handlers::{get_payment_details, get_payment_stats, list_payments, track_payment};
+use crate::auth::middleware::{jwt_auth, node_credentials_required, require_payments_read};
 use axum::{Router, middleware, routing::get};
 
 pub async fn payment_router() -> Router {
     Router::new()
+        .route(
+            "/stats",
+            get(get_payment_stats)
+                .layer(middleware::from_fn(require_payments_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
         .route(
             "/{payment_hash}",
             get(get_payment_details)
+                .layer(middleware::from_fn(require_payments_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{payment_hash}/track",
+            get(track_payment)
+                .layer(middleware::from_fn(require_payments_read))
                 .layer(middleware::from_fn(node_credentials_required))
                 .layer(middleware::from_fn(jwt_auth)),
         )
         .route(
             "/",
             get(list_payments)
+                .layer(middleware::from_fn(require_payments_read))
                 .layer(middleware::from_fn(node_credentials_required))
                 .layer(middleware::from_fn(jwt_auth)),
         )
@@ -0,0 +1,32 @@
+//! Handler functions for peer API endpoints.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::PeerChannels;
+use crate::services::peer_channels::PeerChannelService;
+use crate::utils::jwt::Claims;
+use axum::{
+    Json,
+    extract::{Extension, Path},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Lists the channels the account's nodes have with a peer, limited to the
+/// nodes the caller may access.
+#[axum::debug_handler]
+pub async fn get_peer_channels(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<ApiResponse<PeerChannels>>, (StatusCode, String)> {
+    match PeerChannelService::new(&pool)
+        .channels_with_peer(&claims, &pubkey)
+        .await
+    {
+        Ok(channels) => Ok(Json(ApiResponse::success(
+            channels,
+            "Peer channels retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -0,0 +1,6 @@
+//! Module for peer API endpoints.
+//!
+//! This module looks up a remote peer across all of the account's nodes.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,14 @@
+//! Defines the HTTP routes for peer lookups.
+
+use super::handlers::get_peer_channels;
+use crate::auth::middleware::{jwt_auth, require_channels_read};
+use axum::{Router, middleware, routing::get};
+
+pub async fn peer_router() -> Router {
+    Router::new().route(
+        "/{pubkey}/channels",
+        get(get_peer_channels)
+            .layer(middleware::from_fn(require_channels_read))
+            .layer(middleware::from_fn(jwt_auth)),
+    )
+}
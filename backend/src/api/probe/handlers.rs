@@ -0,0 +1,82 @@
+//! Handler functions for the route probing API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{
+    ProbePolicyResponse, ProbeReport, ProbeReportQuery, UpdateProbePolicyRequest,
+};
+use crate::services::route_probes::ProbeService;
+use crate::utils::handlers_common::NodeContext;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Query},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Reports the success rate and latency of each probed route of the node in the token.
+#[axum::debug_handler]
+pub async fn get_probe_report(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<ProbeReportQuery>,
+) -> Result<Json<ApiResponse<ProbeReport>>, (StatusCode, String)> {
+    match ProbeService::new(&pool)
+        .get_report(claims.account_id(), node_context.node_id(), query)
+        .await
+    {
+        Ok(report) => Ok(Json(ApiResponse::success(
+            report,
+            "Probe report retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the route probing settings of the node in the token.
+#[axum::debug_handler]
+pub async fn get_probe_policy(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<ProbePolicyResponse>>, (StatusCode, String)> {
+    match ProbeService::new(&pool)
+        .get_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "Probe policy retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Replaces the route probing settings of the node in the token.
+#[axum::debug_handler]
+pub async fn update_probe_policy(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<UpdateProbePolicyRequest>,
+) -> Result<Json<ApiResponse<ProbePolicyResponse>>, (StatusCode, String)> {
+    match ProbeService::new(&pool)
+        .update_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            payload,
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "Probe policy updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
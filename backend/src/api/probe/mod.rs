@@ -0,0 +1,7 @@
+//! Module for route probing API endpoints.
+//!
+//! This module handles the node's route probing settings and the report of
+//! how reliably each probed route carried its probes.
+
+pub mod handlers;
+pub mod routes;
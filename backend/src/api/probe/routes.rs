@@ -0,0 +1,33 @@
+//! Defines the HTTP routes for route probing.
+
+use super::handlers::{get_probe_policy, get_probe_report, update_probe_policy};
+use crate::auth::middleware::{admin_auth, jwt_auth, node_credentials_required, require_node_read};
+use axum::{
+    Router, middleware,
+    routing::{get, put},
+};
+
+pub async fn probe_router() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(get_probe_report)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/policy",
+            get(get_probe_policy)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/policy",
+            put(update_probe_policy)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+}
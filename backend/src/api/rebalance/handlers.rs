@@ -0,0 +1,109 @@
+//! Handler functions for the rebalancing API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{
+    CreateRebalanceRequest, RebalanceAttemptQuery, RebalanceAttemptResponse,
+    RebalancePolicyResponse, UpdateRebalancePolicyRequest,
+};
+use crate::services::rebalance_service::RebalanceService;
+use crate::utils::handlers_common::NodeContext;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Query},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Rebalances between two channels of the node in the token (LND only).
+/// Responds once the payment has succeeded or failed.
+#[axum::debug_handler]
+pub async fn create_rebalance(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<CreateRebalanceRequest>,
+) -> Result<Json<ApiResponse<RebalanceAttemptResponse>>, (StatusCode, String)> {
+    match RebalanceService::new(&pool)
+        .create_rebalance(
+            claims.account_id(),
+            claims.user_id(),
+            &node_context.credentials,
+            payload,
+        )
+        .await
+    {
+        Ok(attempt) => Ok(Json(ApiResponse::success(
+            attempt.into(),
+            "Rebalance attempted successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Lists the rebalance attempts of the node in the token, with their routes and costs.
+#[axum::debug_handler]
+pub async fn list_rebalance_attempts(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<RebalanceAttemptQuery>,
+) -> Result<Json<ApiResponse<Vec<RebalanceAttemptResponse>>>, (StatusCode, String)> {
+    match RebalanceService::new(&pool)
+        .list_attempts(claims.account_id(), node_context.node_id(), query.limit)
+        .await
+    {
+        Ok(attempts) => Ok(Json(ApiResponse::success(
+            attempts.into_iter().map(Into::into).collect(),
+            "Rebalance attempts retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns the auto-rebalancing settings of the node in the token.
+#[axum::debug_handler]
+pub async fn get_rebalance_policy(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<RebalancePolicyResponse>>, (StatusCode, String)> {
+    match RebalanceService::new(&pool)
+        .get_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "Rebalance policy retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Replaces the auto-rebalancing settings of the node in the token.
+#[axum::debug_handler]
+pub async fn update_rebalance_policy(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<UpdateRebalancePolicyRequest>,
+) -> Result<Json<ApiResponse<RebalancePolicyResponse>>, (StatusCode, String)> {
+    match RebalanceService::new(&pool)
+        .update_policy(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            payload,
+        )
+        .await
+    {
+        Ok(policy) => Ok(Json(ApiResponse::success(
+            policy.into(),
+            "Rebalance policy updated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
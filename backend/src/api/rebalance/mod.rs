@@ -0,0 +1,7 @@
+//! Module for rebalancing API endpoints.
+//!
+//! This module handles manual circular rebalances, the node's auto-rebalancing
+//! settings and the log of every attempt.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,44 @@
+//! Defines the HTTP routes for rebalancing.
+
+use super::handlers::{
+    create_rebalance, get_rebalance_policy, list_rebalance_attempts, update_rebalance_policy,
+};
+use crate::api::idempotency::idempotency;
+use crate::auth::middleware::{admin_auth, jwt_auth, node_credentials_required, require_node_read};
+use axum::{
+    Router, middleware,
+    routing::{get, post, put},
+};
+
+pub async fn rebalance_router() -> Router {
+    Router::new()
+        .route(
+            "/",
+            post(create_rebalance)
+                .layer(middleware::from_fn(idempotency))
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/attempts",
+            get(list_rebalance_attempts)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/policy",
+            get(get_rebalance_policy)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/policy",
+            put(update_rebalance_policy)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+}
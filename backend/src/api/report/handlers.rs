@@ -0,0 +1,31 @@
+//! Handler functions for the report API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{StaleChannelQuery, StaleChannelReport};
+use crate::services::report_service::ReportService;
+use crate::utils::handlers_common::NodeContext;
+use axum::{
+    extract::{Extension, Json, Query},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Lists channels of the node in the token that saw no forwards or payments
+/// in the requested window, with what their idle balance cost.
+#[axum::debug_handler]
+pub async fn get_stale_channels(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(node_context): Extension<NodeContext>,
+    Query(query): Query<StaleChannelQuery>,
+) -> Result<Json<ApiResponse<StaleChannelReport>>, (StatusCode, String)> {
+    match ReportService::new(&pool)
+        .get_stale_channels(&node_context.credentials, query)
+        .await
+    {
+        Ok(report) => Ok(Json(ApiResponse::success(
+            report,
+            "Stale channel report generated successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
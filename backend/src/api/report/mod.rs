@@ -0,0 +1,7 @@
+//! Module for report API endpoints.
+//!
+//! This module handles operational reports that help operators decide what
+//! to do with their channels.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,15 @@
+//! Defines the HTTP routes for channel reports.
+
+use super::handlers::get_stale_channels;
+use crate::auth::middleware::{jwt_auth, node_credentials_required, require_node_read};
+use axum::{Router, middleware, routing::get};
+
+pub async fn report_router() -> Router {
+    Router::new().route(
+        "/stale-channels",
+        get(get_stale_channels)
+            .layer(middleware::from_fn(require_node_read))
+            .layer(middleware::from_fn(node_credentials_required))
+            .layer(middleware::from_fn(jwt_auth)),
+    )
+}
@@ -0,0 +1,50 @@
+//! Handler functions for role endpoints.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{CreateRole, Role};
+use crate::services::role_service::RoleService;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Lists the built-in roles and the account's custom roles.
+#[axum::debug_handler]
+pub async fn list_roles(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<Json<ApiResponse<Vec<Role>>>, (StatusCode, String)> {
+    let service = RoleService::new(&pool);
+    match service.list_roles(claims.account_id()).await {
+        Ok(roles) => Ok(Json(ApiResponse::success(
+            roles,
+            "Roles retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Defines a custom role for the account.
+#[axum::debug_handler]
+pub async fn create_role(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<CreateRole>,
+) -> Result<Json<ApiResponse<Role>>, (StatusCode, String)> {
+    tracing::info!(
+        "Creating role '{}' for account: {}",
+        payload.name,
+        claims.account_id()
+    );
+
+    let service = RoleService::new(&pool);
+    match service.create_role(&claims, payload).await {
+        Ok(role) => Ok(Json(ApiResponse::success(
+            role,
+            "Role created successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -0,0 +1,7 @@
+//! Module for role API endpoints.
+//!
+//! This module handles listing the roles available to an account and defining
+//! custom roles from permission scopes.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,18 @@
+//! Defines the HTTP routes for role management.
+
+use super::handlers::{create_role, list_roles};
+use crate::auth::middleware::{admin_auth, jwt_auth};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
+
+pub async fn role_router() -> Router {
+    Router::new()
+        .route("/", get(list_roles))
+        .route(
+            "/",
+            post(create_role).layer(middleware::from_fn(admin_auth)),
+        )
+        .layer(middleware::from_fn(jwt_auth))
+}
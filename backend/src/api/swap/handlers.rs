@@ -0,0 +1,121 @@
+//! Handler functions for the swap API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{CreateSwapRequest, Swap, SwapQuote, SwapQuoteQuery};
+use crate::services::swap_service::SwapService;
+use crate::utils::handlers_common::NodeContext;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+};
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+/// Prices a swap of the given type and amount at Boltz's current fees.
+#[axum::debug_handler]
+pub async fn get_swap_quote(
+    Extension(pool): Extension<SqlitePool>,
+    Query(query): Query<SwapQuoteQuery>,
+) -> Result<Json<ApiResponse<SwapQuote>>, (StatusCode, String)> {
+    match SwapService::new(&pool)
+        .get_quote(query.swap_type, query.amount_sat)
+        .await
+    {
+        Ok(quote) => Ok(Json(ApiResponse::success(
+            quote,
+            "Swap quote retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Creates a swap for the node in the token. Reverse swaps pay Boltz's
+/// invoice before returning.
+#[axum::debug_handler]
+pub async fn create_swap(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<CreateSwapRequest>,
+) -> Result<Json<ApiResponse<Swap>>, (StatusCode, String)> {
+    match SwapService::new(&pool)
+        .create_swap(
+            claims.account_id(),
+            claims.user_id(),
+            &node_context.credentials,
+            payload,
+        )
+        .await
+    {
+        Ok(swap) => Ok(Json(ApiResponse::success(
+            swap,
+            "Swap created successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Lists the swaps of the node in the token.
+#[axum::debug_handler]
+pub async fn list_swaps(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<Vec<Swap>>>, (StatusCode, String)> {
+    match SwapService::new(&pool)
+        .list_swaps(claims.account_id(), node_context.node_id())
+        .await
+    {
+        Ok(swaps) => Ok(Json(ApiResponse::success(
+            swaps,
+            "Swaps retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns a single swap with its last known status.
+#[axum::debug_handler]
+pub async fn get_swap(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Swap>>, (StatusCode, String)> {
+    match SwapService::new(&pool)
+        .get_swap(claims.account_id(), node_context.node_id(), &id)
+        .await
+    {
+        Ok(swap) => Ok(Json(ApiResponse::success(
+            swap,
+            "Swap retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns a swap's keys in the format Boltz's rescue tool accepts.
+#[axum::debug_handler]
+pub async fn get_swap_rescue_data(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Value>>, (StatusCode, String)> {
+    match SwapService::new(&pool)
+        .get_rescue_data(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            &id,
+        )
+        .await
+    {
+        Ok(data) => Ok(Json(ApiResponse::success(
+            data,
+            "Swap rescue data retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
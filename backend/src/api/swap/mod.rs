@@ -0,0 +1,7 @@
+//! Module for swap API endpoints.
+//!
+//! This module handles quoting, creating and following Boltz swaps used to
+//! rebalance the node's channels.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,49 @@
+//! Defines the HTTP routes for rebalancing swaps.
+
+use super::handlers::{create_swap, get_swap, get_swap_quote, get_swap_rescue_data, list_swaps};
+use crate::api::idempotency::idempotency;
+use crate::auth::middleware::{admin_auth, jwt_auth, node_credentials_required, require_node_read};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
+
+pub async fn swap_router() -> Router {
+    Router::new()
+        .route(
+            "/quote",
+            get(get_swap_quote)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/",
+            get(list_swaps)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/",
+            post(create_swap)
+                .layer(middleware::from_fn(idempotency))
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}",
+            get(get_swap)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/rescue",
+            get(get_swap_rescue_data)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+}
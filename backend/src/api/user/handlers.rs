@@ -3,12 +3,22 @@
 //! These functions process requests for user data, interact with the database
 //! or relevant services, and return user-specific information.
 
-use crate::api::common::ApiResponse;
-use crate::database::models::User;
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::auth::models::LoginResponse;
+use crate::auth::service::AuthService;
+use crate::config::Config;
+use crate::database::models::{
+    AssignRoleRequest, ChangePasswordRequest, DataPurgeQuery, DataPurgeResponse, ProfileResponse,
+    UpdateProfileRequest, User, VerifyEmailRequest,
+};
+use crate::errors::ErrorCode;
+use crate::services::data_purge_service::DataPurgeService;
+use crate::services::profile_service::ProfileService;
+use crate::services::role_service::RoleService;
 use crate::services::user_service::UserService;
 use crate::utils::jwt::Claims;
 use axum::{
-    extract::{Extension, Json, Path},
+    extract::{Extension, Json, Path, Query},
     http::StatusCode,
 };
 use sqlx::SqlitePool;
@@ -31,7 +41,7 @@ pub async fn get_user_by_id(
         .map_err(|e| {
             tracing::error!("User not found for ID {}: {}", id, e);
             let error_response =
-                ApiResponse::<()>::error("User not found".to_string(), "user_not_found", None);
+                ApiResponse::<()>::error("User not found".to_string(), ErrorCode::NotFound, None);
             (
                 StatusCode::NOT_FOUND,
                 serde_json::to_string(&error_response).unwrap(),
@@ -76,7 +86,7 @@ pub async fn change_user_role_access_level(
             tracing::error!("Failed to change role access level for ID {}: {}", id, e);
             let error_response = ApiResponse::<()>::error(
                 "Failed to change role access level".to_string(),
-                "role_change_failed",
+                ErrorCode::InternalError,
                 None,
             );
             (
@@ -90,3 +100,117 @@ pub async fn change_user_role_access_level(
         "User role access level changed successfully",
     )))
 }
+
+/// Updates the signed-in user's display name and/or email address.
+///
+/// A new email address is held as pending until it is verified.
+#[axum::debug_handler]
+pub async fn update_profile(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<UpdateProfileRequest>,
+) -> Result<Json<ApiResponse<ProfileResponse>>, (StatusCode, String)> {
+    let config = Config::from_env().map_err(|e| service_error_to_http(e.into()))?;
+    let service = ProfileService::new(&pool, &config);
+    match service.update_profile(claims.user_id(), payload).await {
+        Ok(profile) => {
+            let message = if profile.pending_email.is_some() {
+                "Profile updated. Check the new email address for a verification link"
+            } else {
+                "Profile updated successfully"
+            };
+            Ok(Json(ApiResponse::success(profile, message)))
+        }
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Confirms an email change using the token from the verification link.
+#[axum::debug_handler]
+pub async fn verify_email(
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<Json<ApiResponse<User>>, (StatusCode, String)> {
+    let config = Config::from_env().map_err(|e| service_error_to_http(e.into()))?;
+    let service = ProfileService::new(&pool, &config);
+    match service.verify_email(&payload.token).await {
+        Ok(user) => Ok(Json(ApiResponse::success(
+            user,
+            "Email address verified successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Changes the signed-in user's password and signs out their other sessions.
+#[axum::debug_handler]
+pub async fn change_password(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<Json<ApiResponse<LoginResponse>>, (StatusCode, String)> {
+    let auth_service = AuthService::new(&pool).map_err(service_error_to_http)?;
+    match auth_service
+        .change_password(claims.user_id(), payload)
+        .await
+    {
+        Ok(tokens) => Ok(Json(ApiResponse::success(
+            tokens,
+            "Password changed successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Assigns a built-in or custom role to a user.
+#[axum::debug_handler]
+pub async fn assign_user_role(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Path(id): Path<String>,
+    Json(payload): Json<AssignRoleRequest>,
+) -> Result<Json<ApiResponse<User>>, (StatusCode, String)> {
+    tracing::info!(
+        "Assigning role {} to user {} by admin: {}",
+        payload.role_id,
+        id,
+        claims.user_id()
+    );
+
+    let service = RoleService::new(&pool);
+    match service.assign_role(&claims, &id, &payload.role_id).await {
+        Ok(user) => Ok(Json(ApiResponse::success(
+            user,
+            "User role assigned successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Purges a user's personal data.
+///
+/// Without `confirmation_token` this returns a token to confirm with; calling
+/// again with the token anonymizes the user.
+#[axum::debug_handler]
+pub async fn purge_user_data(
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<SqlitePool>,
+    Path(id): Path<String>,
+    Query(query): Query<DataPurgeQuery>,
+) -> Result<Json<ApiResponse<DataPurgeResponse>>, (StatusCode, String)> {
+    let service = DataPurgeService::new(&pool);
+    match service
+        .purge_user_data(&claims, &id, query.confirmation_token.as_deref())
+        .await
+    {
+        Ok(response) => {
+            let message = if response.confirmation_token.is_some() {
+                "Confirm the purge by repeating the request with the confirmation token"
+            } else {
+                "User data purged successfully"
+            };
+            Ok(Json(ApiResponse::success(response, message)))
+        }
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -3,11 +3,14 @@
 //! These routes provide endpoints for accessing and updating user-specific
 //! data beyond authentication credentials.
 
-use super::handlers::{change_user_role_access_level, get_user_by_id};
-use crate::auth::middleware::jwt_auth;
+use super::handlers::{
+    assign_user_role, change_password, change_user_role_access_level, get_user_by_id,
+    purge_user_data, update_profile, verify_email,
+};
+use crate::auth::middleware::{admin_auth, jwt_auth};
 use axum::{
     Router, middleware,
-    routing::{get, post},
+    routing::{delete, get, patch, post, put},
 };
 
 pub async fn user_router() -> Router {
@@ -20,4 +23,23 @@ pub async fn user_router() -> Router {
             "/change-user-role-access-level/{id}",
             post(change_user_role_access_level).layer(middleware::from_fn(jwt_auth)),
         )
+        .route(
+            "/me",
+            patch(update_profile).layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/me/password",
+            post(change_password).layer(middleware::from_fn(jwt_auth)),
+        )
+        .route("/verify-email", post(verify_email))
+        .route(
+            "/{id}/role",
+            put(assign_user_role)
+                .layer(middleware::from_fn(admin_auth))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}/data",
+            delete(purge_user_data).layer(middleware::from_fn(jwt_auth)),
+        )
 }
@@ -0,0 +1,68 @@
+//! Handler functions for saved view API endpoints.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{CreateSavedViewRequest, SavedViewQuery, SavedViewResponse};
+use crate::services::saved_views::SavedViewService;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Lists the signed-in user's saved views.
+#[axum::debug_handler]
+pub async fn list_views(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<SavedViewQuery>,
+) -> Result<Json<ApiResponse<Vec<SavedViewResponse>>>, (StatusCode, String)> {
+    match SavedViewService::new(&pool)
+        .list_views(claims.user_id(), query.endpoint.as_deref())
+        .await
+    {
+        Ok(views) => Ok(Json(ApiResponse::success(
+            views,
+            "Saved views retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Saves a named filter configuration for a list endpoint.
+#[axum::debug_handler]
+pub async fn create_view(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreateSavedViewRequest>,
+) -> Result<Json<ApiResponse<SavedViewResponse>>, (StatusCode, String)> {
+    match SavedViewService::new(&pool)
+        .create_view(claims.account_id(), claims.user_id(), payload)
+        .await
+    {
+        Ok(view) => Ok(Json(ApiResponse::success(
+            view,
+            "Saved view created successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Deletes one of the signed-in user's saved views.
+#[axum::debug_handler]
+pub async fn delete_view(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, String)> {
+    match SavedViewService::new(&pool)
+        .delete_view(claims.user_id(), &id)
+        .await
+    {
+        Ok(()) => Ok(Json(ApiResponse::success(
+            (),
+            "Saved view deleted successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
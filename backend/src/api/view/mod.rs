@@ -0,0 +1,7 @@
+//! Module for saved view API endpoints.
+//!
+//! This module lets users save named filter configurations for list
+//! endpoints and load them again.
+
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,15 @@
+//! Defines the HTTP routes for saved filter views.
+
+use super::handlers::{create_view, delete_view, list_views};
+use crate::auth::middleware::jwt_auth;
+use axum::{
+    Router, middleware,
+    routing::{delete, get},
+};
+
+pub async fn view_router() -> Router {
+    Router::new()
+        .route("/", get(list_views).post(create_view))
+        .route("/{id}", delete(delete_view))
+        .layer(middleware::from_fn(jwt_auth))
+}
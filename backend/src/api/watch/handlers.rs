@@ -0,0 +1,101 @@
+//! Handler functions for the watch-only node API.
+
+use crate::api::common::{ApiResponse, service_error_to_http};
+use crate::database::models::{CreateWatchedNodeRequest, WatchedNodeResponse};
+use crate::services::watched_nodes::WatchedNodeService;
+use crate::utils::handlers_common::NodeContext;
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+};
+use sqlx::SqlitePool;
+
+/// Lists the external nodes watched through the node in the token.
+#[axum::debug_handler]
+pub async fn list_watched_nodes(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+) -> Result<Json<ApiResponse<Vec<WatchedNodeResponse>>>, (StatusCode, String)> {
+    match WatchedNodeService::new(&pool)
+        .list(claims.account_id(), node_context.node_id())
+        .await
+    {
+        Ok(watched) => Ok(Json(ApiResponse::success(
+            watched,
+            "Watched nodes retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Starts watching an external node by pubkey.
+#[axum::debug_handler]
+pub async fn create_watched_node(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Json(payload): Json<CreateWatchedNodeRequest>,
+) -> Result<Json<ApiResponse<WatchedNodeResponse>>, (StatusCode, String)> {
+    match WatchedNodeService::new(&pool)
+        .create(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            payload,
+        )
+        .await
+    {
+        Ok(watched) => Ok(Json(ApiResponse::success(
+            watched,
+            "Node watched successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Returns a watched node with its channels and fee policies.
+#[axum::debug_handler]
+pub async fn get_watched_node(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<WatchedNodeResponse>>, (StatusCode, String)> {
+    match WatchedNodeService::new(&pool)
+        .get(claims.account_id(), node_context.node_id(), &id)
+        .await
+    {
+        Ok(watched) => Ok(Json(ApiResponse::success(
+            watched,
+            "Watched node retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Stops watching a node.
+#[axum::debug_handler]
+pub async fn delete_watched_node(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Extension(node_context): Extension<NodeContext>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, (StatusCode, String)> {
+    match WatchedNodeService::new(&pool)
+        .delete(
+            claims.account_id(),
+            claims.user_id(),
+            node_context.node_id(),
+            &id,
+        )
+        .await
+    {
+        Ok(()) => Ok(Json(ApiResponse::success(
+            (),
+            "Watched node removed successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
@@ -0,0 +1,7 @@
+//! Module for watch-only node API endpoints.
+//!
+//! This module handles the external nodes an account watches through the
+//! graph mirror of the node in the token.
+
+pub mod handlers;
+pub mod routes;
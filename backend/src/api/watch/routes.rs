@@ -0,0 +1,44 @@
+//! Defines the HTTP routes for watch-only nodes.
+
+use super::handlers::{
+    create_watched_node, delete_watched_node, get_watched_node, list_watched_nodes,
+};
+use crate::auth::middleware::{
+    jwt_auth, node_credentials_required, require_node_read, require_read_write_access_level,
+};
+use axum::{
+    Router, middleware,
+    routing::{delete, get, post},
+};
+
+pub async fn watch_router() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(list_watched_nodes)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/",
+            post(create_watched_node)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}",
+            get(get_watched_node)
+                .layer(middleware::from_fn(require_node_read))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/{id}",
+            delete(delete_watched_node)
+                .layer(middleware::from_fn(require_read_write_access_level))
+                .layer(middleware::from_fn(node_credentials_required))
+                .layer(middleware::from_fn(jwt_auth)),
+        )
+}
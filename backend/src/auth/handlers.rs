@@ -7,10 +7,14 @@
 use crate::api::common::{ApiResponse, service_error_to_http};
 use crate::auth::models::*;
 use crate::auth::service::AuthService;
+use crate::config::Config;
+use crate::database::models::{AccountMembershipDetails, CreateNewAccount};
+use crate::errors::ErrorCode;
 use crate::repositories::credential_repository::CredentialRepository;
+use crate::services::profile_service::ProfileService;
 use crate::utils::jwt::Claims;
 use axum::{
-    extract::{Extension, Json},
+    extract::{Extension, Json, Query},
     http::StatusCode,
     response::Json as ResponseJson,
 };
@@ -36,6 +40,108 @@ pub async fn login(
     }
 }
 
+/// Sign up a new account and its admin user
+#[axum::debug_handler]
+pub async fn register(
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<CreateNewAccount>,
+) -> Result<ResponseJson<ApiResponse<LoginResponse>>, (StatusCode, String)> {
+    let auth_service = match AuthService::new(&pool) {
+        Ok(service) => service,
+        Err(error) => return Err(service_error_to_http(error)),
+    };
+
+    match auth_service.register(payload).await {
+        Ok(response) => Ok(ResponseJson(ApiResponse::success(
+            response,
+            "Account registered successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Sign in to the demo account without credentials
+#[axum::debug_handler]
+pub async fn demo_login(
+    Extension(pool): Extension<SqlitePool>,
+) -> Result<ResponseJson<ApiResponse<LoginResponse>>, (StatusCode, String)> {
+    let auth_service = match AuthService::new(&pool) {
+        Ok(service) => service,
+        Err(error) => return Err(service_error_to_http(error)),
+    };
+
+    match auth_service.demo_login().await {
+        Ok(response) => Ok(ResponseJson(ApiResponse::success(
+            response,
+            "Demo login successful",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Issue an LNURL-auth challenge (login when anonymous, link when authenticated)
+#[axum::debug_handler]
+pub async fn lnurl_challenge(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Option<Claims>>,
+) -> Result<ResponseJson<ApiResponse<LnurlChallengeResponse>>, (StatusCode, String)> {
+    let auth_service = match AuthService::new(&pool) {
+        Ok(service) => service,
+        Err(error) => return Err(service_error_to_http(error)),
+    };
+
+    match auth_service.issue_lnurl_challenge(claims.as_ref()).await {
+        Ok(response) => Ok(ResponseJson(ApiResponse::success(
+            response,
+            "LNURL-auth challenge issued",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// LNURL-auth callback invoked by the wallet with its signature
+#[axum::debug_handler]
+pub async fn lnurl_callback(
+    Extension(pool): Extension<SqlitePool>,
+    Query(query): Query<LnurlCallbackQuery>,
+) -> ResponseJson<LnurlStatusResponse> {
+    let auth_service = match AuthService::new(&pool) {
+        Ok(service) => service,
+        Err(error) => {
+            tracing::error!("Failed to initialise auth service: {}", error);
+            return ResponseJson(LnurlStatusResponse::error("Internal server error"));
+        }
+    };
+
+    match auth_service.verify_lnurl_callback(query).await {
+        Ok(()) => ResponseJson(LnurlStatusResponse::ok()),
+        Err(error) => {
+            tracing::warn!("LNURL-auth verification failed: {}", error);
+            ResponseJson(LnurlStatusResponse::error(error.to_string()))
+        }
+    }
+}
+
+/// Exchange a signed LNURL-auth challenge for session tokens
+#[axum::debug_handler]
+pub async fn lnurl_token(
+    Extension(pool): Extension<SqlitePool>,
+    Json(payload): Json<LnurlTokenRequest>,
+) -> Result<ResponseJson<ApiResponse<LoginResponse>>, (StatusCode, String)> {
+    let auth_service = match AuthService::new(&pool) {
+        Ok(service) => service,
+        Err(error) => return Err(service_error_to_http(error)),
+    };
+
+    match auth_service.complete_lnurl_login(payload).await {
+        Ok(response) => Ok(ResponseJson(ApiResponse::success(
+            response,
+            "Login successful",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
 /// Handle token refresh request
 #[axum::debug_handler]
 pub async fn refresh_token(
@@ -75,12 +181,13 @@ pub async fn me(
     // Get user information from database using claims
     let user = match sqlx::query!(
         r#"
-        SELECT u.username, u.email, a.name as account_name, r.name as role_name
+        SELECT u.username, u.email, a.name as account_name,
+        u.email_verified_at IS NOT NULL as "email_verified!: bool"
         FROM users u
-        JOIN accounts a ON u.account_id = a.id
-        JOIN roles r ON u.role_id = r.id
+        JOIN accounts a ON a.id = ?
         WHERE u.id = ? AND u.is_deleted = 0
         "#,
+        claims.account_id,
         claims.sub
     )
     .fetch_optional(&pool)
@@ -88,14 +195,16 @@ pub async fn me(
     {
         Ok(Some(user)) => user,
         Ok(None) => {
-            let error_response = ApiResponse::<()>::error("User not found", "not_found", None);
+            let error_response =
+                ApiResponse::<()>::error("User not found", ErrorCode::NotFound, None);
             return Err((
                 StatusCode::NOT_FOUND,
                 serde_json::to_string(&error_response).unwrap(),
             ));
         }
         Err(_e) => {
-            let error_response = ApiResponse::<()>::error("Database error", "database_error", None);
+            let error_response =
+                ApiResponse::<()>::error("Database error", ErrorCode::DatabaseError, None);
             return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 serde_json::to_string(&error_response).unwrap(),
@@ -109,8 +218,9 @@ pub async fn me(
         email: user.email,
         account_id: claims.account_id.clone(),
         account_name: user.account_name,
-        role: user.role_name,
+        role: claims.role.clone(),
         has_node_credentials: claims.has_node_credentials(),
+        email_verified: user.email_verified,
     };
 
     Ok(ResponseJson(ApiResponse::success(
@@ -119,6 +229,66 @@ pub async fn me(
     )))
 }
 
+/// List the accounts the user belongs to
+#[axum::debug_handler]
+pub async fn get_accounts(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<ResponseJson<ApiResponse<Vec<AccountMembershipDetails>>>, (StatusCode, String)> {
+    let auth_service = match AuthService::new(&pool) {
+        Ok(service) => service,
+        Err(error) => return Err(service_error_to_http(error)),
+    };
+
+    match auth_service.list_accounts(&claims).await {
+        Ok(accounts) => Ok(ResponseJson(ApiResponse::success(
+            accounts,
+            "Accounts retrieved successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Issue tokens for another account the user belongs to
+#[axum::debug_handler]
+pub async fn switch_account(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<SwitchAccountRequest>,
+) -> Result<ResponseJson<ApiResponse<LoginResponse>>, (StatusCode, String)> {
+    let auth_service = match AuthService::new(&pool) {
+        Ok(service) => service,
+        Err(error) => return Err(service_error_to_http(error)),
+    };
+
+    match auth_service.switch_account(&claims, payload).await {
+        Ok(response) => Ok(ResponseJson(ApiResponse::success(
+            response,
+            "Switched account successfully",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
+/// Sends the signed-in user a new link to verify their email address
+#[axum::debug_handler]
+pub async fn resend_email_verification(
+    Extension(pool): Extension<SqlitePool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<ResponseJson<ApiResponse<()>>, (StatusCode, String)> {
+    let config = Config::from_env().map_err(|e| service_error_to_http(e.into()))?;
+    match ProfileService::new(&pool, &config)
+        .resend_email_verification(claims.user_id())
+        .await
+    {
+        Ok(()) => Ok(ResponseJson(ApiResponse::success(
+            (),
+            "Verification email sent",
+        ))),
+        Err(error) => Err(service_error_to_http(error)),
+    }
+}
+
 /// Handle node credentials revocation request
 #[axum::debug_handler]
 pub async fn revoke_node_credentials(
@@ -132,14 +302,15 @@ pub async fn revoke_node_credentials(
         Ok(Some(cred)) => cred,
         Ok(None) => {
             let error_response =
-                ApiResponse::<()>::error("No node credentials found", "not_found", None);
+                ApiResponse::<()>::error("No node credentials found", ErrorCode::NotFound, None);
             return Err((
                 StatusCode::NOT_FOUND,
                 serde_json::to_string(&error_response).unwrap(),
             ));
         }
         Err(_e) => {
-            let error_response = ApiResponse::<()>::error("Database error", "database_error", None);
+            let error_response =
+                ApiResponse::<()>::error("Database error", ErrorCode::DatabaseError, None);
             return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 serde_json::to_string(&error_response).unwrap(),
@@ -149,8 +320,11 @@ pub async fn revoke_node_credentials(
 
     // Soft delete the credential
     if let Err(_e) = credential_repo.delete_credential(&credential.id).await {
-        let error_response =
-            ApiResponse::<()>::error("Failed to revoke credentials", "database_error", None);
+        let error_response = ApiResponse::<()>::error(
+            "Failed to revoke credentials",
+            ErrorCode::DatabaseError,
+            None,
+        );
         return Err((
             StatusCode::INTERNAL_SERVER_ERROR,
             serde_json::to_string(&error_response).unwrap(),
@@ -162,7 +336,7 @@ pub async fn revoke_node_credentials(
         Ok(utils) => utils,
         Err(_e) => {
             let error_response =
-                ApiResponse::<()>::error("JWT configuration error", "configuration_error", None);
+                ApiResponse::<()>::error("JWT configuration error", ErrorCode::InternalError, None);
             return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 serde_json::to_string(&error_response).unwrap(),
@@ -175,12 +349,14 @@ pub async fn revoke_node_credentials(
         claims.account_id,
         claims.role,
         claims.role_access_level,
+        claims.permissions,
         None, // No node credentials
+        claims.node_scope,
     ) {
         Ok(token) => token,
         Err(_e) => {
             let error_response =
-                ApiResponse::<()>::error("Token generation failed", "token_error", None);
+                ApiResponse::<()>::error("Token generation failed", ErrorCode::InternalError, None);
             return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 serde_json::to_string(&error_response).unwrap(),
@@ -4,15 +4,24 @@
 //! and enforcing user permissions across the API endpoints.
 
 use crate::api::common::ApiResponse;
-use crate::database::models::RoleAccessLevel;
+use crate::config::Config;
+use crate::database::models::{Permission, RoleAccessLevel};
+use crate::errors::{ErrorCode, ServiceError};
+use crate::repositories::user_repository::UserRepository;
+use crate::services::account_service::AccountService;
+use crate::services::usage_meter::UsageMeter;
+use crate::services::user_service::UserService;
+use crate::utils::handlers_common::NodeContext;
 use crate::utils::jwt::JwtUtils;
 use axum::response::IntoResponse;
 use axum::{
-    extract::Request,
+    extract::{Path, Request},
     http::{StatusCode, header::AUTHORIZATION},
     middleware::Next,
     response::{Json, Response},
 };
+use sqlx::SqlitePool;
+use std::collections::HashMap;
 
 /// JWT authentication middleware
 pub async fn jwt_auth(mut request: Request, next: Next) -> Result<Response, Response> {
@@ -27,7 +36,7 @@ pub async fn jwt_auth(mut request: Request, next: Next) -> Result<Response, Resp
         None => {
             let error_response = ApiResponse::<()>::error(
                 "Missing authorization header",
-                "authentication_error",
+                ErrorCode::Unauthenticated,
                 None,
             );
             return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
@@ -38,7 +47,7 @@ pub async fn jwt_auth(mut request: Request, next: Next) -> Result<Response, Resp
     if !auth_header.starts_with("Bearer ") {
         let error_response = ApiResponse::<()>::error(
             "Invalid authorization header format. Expected: Bearer <token>",
-            "authentication_error",
+            ErrorCode::Unauthenticated,
             None,
         );
         return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
@@ -51,13 +60,70 @@ pub async fn jwt_auth(mut request: Request, next: Next) -> Result<Response, Resp
         Ok(utils) => utils,
         Err(_) => {
             let error_response =
-                ApiResponse::<()>::error("Internal server error", "server_error", None);
+                ApiResponse::<()>::error("Internal server error", ErrorCode::InternalError, None);
             return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)).into_response());
         }
     };
 
     match jwt_utils.validate_token(token) {
-        Ok(claims) => {
+        Ok(mut claims) => {
+            // Reject tokens issued before the user's sessions were revoked
+            if let Some(pool) = request.extensions().get::<SqlitePool>().cloned() {
+                match UserService::new(&pool)
+                    .is_token_revoked(&claims.sub, claims.iat)
+                    .await
+                {
+                    Ok(false) => {}
+                    Ok(true) => {
+                        let error_response = ApiResponse::<()>::error(
+                            "Session has been revoked. Please log in again.",
+                            ErrorCode::Unauthenticated,
+                            None,
+                        );
+                        return Err(
+                            (StatusCode::UNAUTHORIZED, Json(error_response)).into_response()
+                        );
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to check session revocation: {}", e);
+                        let error_response = ApiResponse::<()>::error(
+                            "Internal server error",
+                            ErrorCode::InternalError,
+                            None,
+                        );
+                        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+                            .into_response());
+                    }
+                }
+
+                // The role and node scope in the token may predate changes to them
+                match UserService::new(&pool).refresh_claims(&mut claims).await {
+                    Ok(()) => {}
+                    Err(ServiceError::NotFound { .. }) => {
+                        let error_response = ApiResponse::<()>::error(
+                            "Account access has been removed. Please log in again.",
+                            ErrorCode::Unauthenticated,
+                            None,
+                        );
+                        return Err(
+                            (StatusCode::UNAUTHORIZED, Json(error_response)).into_response()
+                        );
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to resolve the user's role: {}", e);
+                        let error_response = ApiResponse::<()>::error(
+                            "Internal server error",
+                            ErrorCode::InternalError,
+                            None,
+                        );
+                        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+                            .into_response());
+                    }
+                }
+            }
+
+            UsageMeter::global().record_request(claims.account_id());
+
             // Add claims to request extensions for use in handlers
             request.extensions_mut().insert(claims);
             Ok(next.run(request).await)
@@ -65,7 +131,7 @@ pub async fn jwt_auth(mut request: Request, next: Next) -> Result<Response, Resp
         Err(e) => {
             let error_response = ApiResponse::<()>::error(
                 format!("Invalid or expired token: {e}"),
-                "authentication_error",
+                ErrorCode::Unauthenticated,
                 None,
             );
             Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response())
@@ -85,8 +151,11 @@ pub async fn optional_jwt_auth(mut request: Request, next: Next) -> Result<Respo
             let jwt_utils = match JwtUtils::new() {
                 Ok(utils) => utils,
                 Err(_) => {
-                    let error_response =
-                        ApiResponse::<()>::error("Internal server error", "server_error", None);
+                    let error_response = ApiResponse::<()>::error(
+                        "Internal server error",
+                        ErrorCode::InternalError,
+                        None,
+                    );
                     return Err(
                         (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)).into_response()
                     );
@@ -117,49 +186,171 @@ pub async fn admin_auth(request: Request, next: Next) -> Result<Response, Respon
     let claims = match claims {
         Some(claims) => claims,
         None => {
-            let error_response =
-                ApiResponse::<()>::error("Authentication required", "authentication_error", None);
+            let error_response = ApiResponse::<()>::error(
+                "Authentication required",
+                ErrorCode::Unauthenticated,
+                None,
+            );
             return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
         }
     };
 
     // Check if user has admin role
     if !claims.is_admin() {
-        let error_response =
-            ApiResponse::<()>::error("Admin privileges required", "authorization_error", None);
+        let error_response = ApiResponse::<()>::error(
+            "Admin privileges required",
+            ErrorCode::PermissionDenied,
+            None,
+        );
         return Err((StatusCode::FORBIDDEN, Json(error_response)).into_response());
     }
 
     Ok(next.run(request).await)
 }
 
-/// Node credentials required middleware
-pub async fn node_credentials_required(request: Request, next: Next) -> Result<Response, Response> {
-    // Get claims from request extensions
-    let claims = request.extensions().get::<crate::utils::jwt::Claims>();
+/// Operator authorization middleware
+///
+/// Guards instance-wide operations, which reach every account's data, so
+/// that only admins of the operator's account can run them. Goes inside
+/// `admin_auth`.
+pub async fn operator_auth(request: Request, next: Next) -> Result<Response, Response> {
+    let (Some(claims), Some(pool)) = (
+        request
+            .extensions()
+            .get::<crate::utils::jwt::Claims>()
+            .cloned(),
+        request.extensions().get::<SqlitePool>().cloned(),
+    ) else {
+        let error_response =
+            ApiResponse::<()>::error("Authentication required", ErrorCode::Unauthenticated, None);
+        return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
+    };
 
-    let claims = match claims {
-        Some(claims) => claims,
-        None => {
-            let error_response =
-                ApiResponse::<()>::error("Authentication required", "authentication_error", None);
-            return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
+    let is_operator = match Config::from_env() {
+        Ok(config) => AccountService::new(&pool)
+            .is_operator_account(&config, claims.account_id())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to resolve the operator account: {}", e);
+                false
+            }),
+        Err(e) => {
+            tracing::error!("Failed to load config: {}", e);
+            false
         }
     };
+    if !is_operator {
+        let error_response = ApiResponse::<()>::error(
+            "Operator privileges required",
+            ErrorCode::PermissionDenied,
+            None,
+        );
+        return Err((StatusCode::FORBIDDEN, Json(error_response)).into_response());
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Node credentials required middleware
+///
+/// Also loads the stored credential the token names, validates the node once
+/// and adds its [`NodeContext`] to the request extensions for the handlers.
+pub async fn node_credentials_required(
+    mut request: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    // Get claims and pool from request extensions
+    let (Some(claims), Some(pool)) = (
+        request
+            .extensions()
+            .get::<crate::utils::jwt::Claims>()
+            .cloned(),
+        request.extensions().get::<SqlitePool>().cloned(),
+    ) else {
+        let error_response =
+            ApiResponse::<()>::error("Authentication required", ErrorCode::Unauthenticated, None);
+        return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
+    };
 
     // Check if user has node credentials
     if !claims.has_node_credentials() {
         let error_response = ApiResponse::<()>::error(
             "Node credentials required. Please authenticate your node first.",
-            "node_credentials_required",
+            ErrorCode::NodeCredentialsRequired,
             None,
         );
         return Err((StatusCode::BAD_REQUEST, Json(error_response)).into_response());
     }
 
+    let node_context = NodeContext::load(&pool, &claims)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    request.extensions_mut().insert(node_context);
+
     Ok(next.run(request).await)
 }
 
+/// Node access middleware
+///
+/// Refuses routes whose `{node_id}` or `{id}` path segment names a node
+/// outside the user's node scope.
+pub async fn require_node_access(
+    Path(params): Path<HashMap<String, String>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let Some(claims) = request.extensions().get::<crate::utils::jwt::Claims>() else {
+        let error_response =
+            ApiResponse::<()>::error("Authentication required", ErrorCode::Unauthenticated, None);
+        return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
+    };
+
+    let node_id = params.get("node_id").or_else(|| params.get("id"));
+    if node_id.is_some_and(|node_id| !claims.can_access_node(node_id)) {
+        let error_response =
+            ApiResponse::<()>::error("No access to this node", ErrorCode::PermissionDenied, None);
+        return Err((StatusCode::FORBIDDEN, Json(error_response)).into_response());
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Verified email required middleware
+///
+/// Guards routes that send data to destinations the user supplies, so an
+/// account can't be used to point them somewhere under a spoofed address.
+pub async fn require_verified_email(request: Request, next: Next) -> Result<Response, Response> {
+    let (Some(claims), Some(pool)) = (
+        request.extensions().get::<crate::utils::jwt::Claims>(),
+        request.extensions().get::<SqlitePool>(),
+    ) else {
+        let error_response =
+            ApiResponse::<()>::error("Authentication required", ErrorCode::Unauthenticated, None);
+        return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
+    };
+
+    match UserRepository::new(pool)
+        .is_email_verified(&claims.sub)
+        .await
+    {
+        Ok(true) => Ok(next.run(request).await),
+        Ok(false) => {
+            let error_response = ApiResponse::<()>::error(
+                "Verify your email address before configuring notifications",
+                ErrorCode::EmailNotVerified,
+                None,
+            );
+            Err((StatusCode::FORBIDDEN, Json(error_response)).into_response())
+        }
+        Err(e) => {
+            tracing::error!("Failed to check email verification: {}", e);
+            let error_response =
+                ApiResponse::<()>::error("Internal server error", ErrorCode::InternalError, None);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)).into_response())
+        }
+    }
+}
+
 /// Macro to generate access level middleware functions
 macro_rules! create_access_level_middleware {
     ($fn_name:ident, $required_level:expr, $level_name:expr) => {
@@ -171,7 +362,7 @@ macro_rules! create_access_level_middleware {
                 None => {
                     let error_response = ApiResponse::<()>::error(
                         "Authentication required",
-                        "authentication_error",
+                        ErrorCode::Unauthenticated,
                         None,
                     );
                     return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
@@ -181,7 +372,7 @@ macro_rules! create_access_level_middleware {
             if claims.role_access_level != $required_level {
                 let error_response = ApiResponse::<()>::error(
                     format!("Access level '{}' required", $level_name),
-                    "insufficient_permissions",
+                    ErrorCode::PermissionDenied,
                     None,
                 );
                 return Err((StatusCode::FORBIDDEN, Json(error_response)).into_response());
@@ -199,3 +390,90 @@ create_access_level_middleware!(
     RoleAccessLevel::ReadWrite,
     "read-write"
 );
+
+/// Macro to generate permission scope middleware functions
+macro_rules! create_permission_middleware {
+    ($fn_name:ident, $permission:expr) => {
+        pub async fn $fn_name(request: Request, next: Next) -> Result<Response, Response> {
+            let claims = request.extensions().get::<crate::utils::jwt::Claims>();
+
+            let claims = match claims {
+                Some(claims) => claims,
+                None => {
+                    let error_response = ApiResponse::<()>::error(
+                        "Authentication required",
+                        ErrorCode::Unauthenticated,
+                        None,
+                    );
+                    return Err((StatusCode::UNAUTHORIZED, Json(error_response)).into_response());
+                }
+            };
+
+            if !claims.has_permission($permission) {
+                let error_response = ApiResponse::<()>::error(
+                    format!("Permission '{}' required", $permission),
+                    ErrorCode::PermissionDenied,
+                    None,
+                );
+                return Err((StatusCode::FORBIDDEN, Json(error_response)).into_response());
+            }
+
+            Ok(next.run(request).await)
+        }
+    };
+}
+
+// Generate the permission scope middleware functions
+create_permission_middleware!(require_node_read, Permission::NodeRead);
+create_permission_middleware!(require_node_sign, Permission::NodeSign);
+create_permission_middleware!(require_channels_read, Permission::ChannelsRead);
+create_permission_middleware!(require_payments_read, Permission::PaymentsRead);
+create_permission_middleware!(require_invoices_read, Permission::InvoicesRead);
+create_permission_middleware!(require_events_read, Permission::EventsRead);
+create_permission_middleware!(require_notifications_read, Permission::NotificationsRead);
+create_permission_middleware!(require_notifications_write, Permission::NotificationsWrite);
+create_permission_middleware!(require_credentials_manage, Permission::CredentialsManage);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::jwt::Claims;
+    use axum::{Router, body::Body, routing::post};
+    use tower::Service;
+
+    #[tokio::test]
+    async fn test_node_access_refuses_nodes_outside_scope() {
+        let mut app = Router::new().route(
+            "/{id}/export",
+            post(|| async { "ok" })
+                .layer(axum::middleware::from_fn(require_node_access))
+                .layer(axum::middleware::from_fn(
+                    |mut request: Request, next: Next| async move {
+                        request.extensions_mut().insert(Claims {
+                            sub: "user".to_string(),
+                            account_id: "account".to_string(),
+                            role: "Member".to_string(),
+                            role_access_level: RoleAccessLevel::ReadWrite,
+                            permissions: Vec::new(),
+                            node_credential: None,
+                            node_scope: Some(vec!["node-a".to_string()]),
+                            exp: 0,
+                            iat: 0,
+                        });
+                        next.run(request).await
+                    },
+                )),
+        );
+
+        for (node_id, status) in [
+            ("node-a", StatusCode::OK),
+            ("node-b", StatusCode::FORBIDDEN),
+        ] {
+            let request = axum::http::Request::post(format!("/{node_id}/export"))
+                .body(Body::empty())
+                .unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), status);
+        }
+    }
+}
@@ -36,6 +36,7 @@ pub struct UserInfo {
     pub account_name: String,
     pub role: String,
     pub has_node_credentials: bool,
+    pub email_verified: bool,
 }
 
 /// Token refresh request
@@ -61,7 +62,7 @@ pub struct StoreNodeCredentialsRequest {
     pub node_alias: String,
 
     #[validate(length(min = 1, message = "Node type is required"))]
-    pub node_type: String, // "lnd" or "cln"
+    pub node_type: String, // "lnd", "cln" or "clnrest"
 
     #[validate(length(min = 1, message = "Macaroon is required"))]
     pub macaroon: String,
@@ -93,3 +94,60 @@ pub struct RevokeNodeCredentialsResponse {
     pub revoked: bool,
     pub expires_in: u64,
 }
+
+/// LNURL-auth challenge handed to the frontend for rendering as a QR code
+#[derive(Debug, Serialize)]
+pub struct LnurlChallengeResponse {
+    pub k1: String,
+    pub lnurl: String,
+    pub callback_url: String,
+    pub action: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Query parameters sent by the wallet to the LNURL-auth callback
+#[derive(Debug, Deserialize)]
+pub struct LnurlCallbackQuery {
+    pub tag: String,
+    pub k1: String,
+    pub sig: String,
+    pub key: String,
+}
+
+/// LUD-01 status response; wallets expect exactly this shape, not `ApiResponse`
+#[derive(Debug, Serialize)]
+pub struct LnurlStatusResponse {
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl LnurlStatusResponse {
+    pub fn ok() -> Self {
+        Self {
+            status: "OK".to_string(),
+            reason: None,
+        }
+    }
+
+    pub fn error(reason: impl Into<String>) -> Self {
+        Self {
+            status: "ERROR".to_string(),
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// Exchange a signed LNURL-auth challenge for session tokens
+#[derive(Debug, Deserialize, Validate)]
+pub struct LnurlTokenRequest {
+    #[validate(length(equal = 64, message = "k1 must be 64 hex characters"))]
+    pub k1: String,
+}
+
+/// Switch the session to another account the user belongs to
+#[derive(Debug, Deserialize, Validate)]
+pub struct SwitchAccountRequest {
+    #[validate(length(min = 1, message = "Account ID is required"))]
+    pub account_id: String,
+}
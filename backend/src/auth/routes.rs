@@ -3,6 +3,7 @@
 //! These routes handle endpoints like user login, registration, and token refreshing.
 //! These are designed to be integrated into the main Axum router.
 
+use crate::api::user::handlers::verify_email;
 use crate::auth::handlers::*;
 use crate::auth::middleware::*;
 use axum::{
@@ -14,11 +15,34 @@ use axum::{
 pub fn auth_router() -> Router {
     Router::new()
         .route("/login", post(login))
+        .route("/register", post(register))
+        .route("/demo", post(demo_login))
         .route("/refresh", post(refresh_token))
         .route("/logout", post(logout))
+        .route(
+            "/lnurl",
+            get(lnurl_challenge).layer(middleware::from_fn(optional_jwt_auth)),
+        )
+        .route("/lnurl/callback", get(lnurl_callback))
+        .route("/lnurl/token", post(lnurl_token))
         .route("/me", get(me).layer(middleware::from_fn(jwt_auth)))
+        .route(
+            "/accounts",
+            get(get_accounts).layer(middleware::from_fn(jwt_auth)),
+        )
+        .route(
+            "/switch-account",
+            post(switch_account).layer(middleware::from_fn(jwt_auth)),
+        )
+        .route("/verify-email", post(verify_email))
+        .route(
+            "/verify-email/resend",
+            post(resend_email_verification).layer(middleware::from_fn(jwt_auth)),
+        )
         .route(
             "/revoke-node-credentials",
-            delete(revoke_node_credentials).layer(middleware::from_fn(jwt_auth)),
+            delete(revoke_node_credentials)
+                .layer(middleware::from_fn(require_credentials_manage))
+                .layer(middleware::from_fn(jwt_auth)),
         )
 }
@@ -2,15 +2,32 @@
 
 use crate::auth::models::*;
 use crate::config::Config;
+use crate::database::models::{
+    AccountMembershipDetails, ChangePasswordRequest, CreateNewAccount, Credential, LnurlAuthAction,
+    LnurlChallengeStatus, Role, User,
+};
 use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::account_membership_repository::AccountMembershipRepository;
 use crate::repositories::account_repository::AccountRepository;
 use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::lnurl_auth_repository::LnurlAuthRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::account_service::AccountService;
+use crate::services::demo::DEMO_USERNAME;
+use crate::services::profile_service::ProfileService;
 use crate::services::user_service::UserService;
-use crate::utils::jwt::{Claims, JwtUtils, NodeCredentials};
+use crate::utils::jwt::{Claims, JwtUtils, NodeCredentialRef};
+use crate::utils::lnurl::{encode_lnurl, verify_auth_signature};
+use crate::utils::password::{hash_password, validate_password_strength};
+use chrono::{Duration, Utc};
+use rand::RngCore;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 use validator::Validate;
 
+/// How long an LNURL-auth challenge stays valid after issuance
+const LNURL_CHALLENGE_TTL_MINUTES: i64 = 5;
+
 /// Authentication service for handling login, token generation, and user management
 pub struct AuthService<'a> {
     pool: &'a SqlitePool,
@@ -60,6 +77,122 @@ impl<'a> AuthService<'a> {
             .authenticate_user(&login_request.username, &login_request.password)
             .await?;
 
+        self.build_login_response(user).await
+    }
+
+    /// Signs up a new account with its admin user and signs the admin in.
+    /// Once an account exists, this needs registration to be enabled.
+    pub async fn register(&self, request: CreateNewAccount) -> ServiceResult<LoginResponse> {
+        let created = AccountService::new(self.pool)
+            .register_account(request, self.config.registration_enabled)
+            .await?;
+        if let Err(e) = ProfileService::new(self.pool, &self.config)
+            .start_email_verification(&created.user)
+            .await
+        {
+            tracing::warn!("Failed to start email verification: {}", e);
+        }
+
+        self.build_login_response(created.user).await
+    }
+
+    /// Signs a visitor in as the demo account's read-only user. Only
+    /// available when the server runs in demo mode.
+    pub async fn demo_login(&self) -> ServiceResult<LoginResponse> {
+        if !self.config.demo_mode {
+            return Err(ServiceError::permission_denied("Demo mode is not enabled"));
+        }
+
+        let user = UserRepository::new(self.pool)
+            .get_user_by_username(DEMO_USERNAME)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("User", DEMO_USERNAME))?;
+
+        self.build_login_response(user).await
+    }
+
+    /// Changes the user's password after checking the current one.
+    ///
+    /// Every previously issued token is revoked; the returned tokens keep the
+    /// caller signed in.
+    pub async fn change_password(
+        &self,
+        user_id: &str,
+        request: ChangePasswordRequest,
+    ) -> ServiceResult<LoginResponse> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let user = self.user_service.get_user_required(user_id).await?;
+        // Reuses the login check so a wrong password gets the same treatment.
+        let user = self
+            .user_service
+            .authenticate_user(&user.username, &request.current_password)
+            .await
+            .map_err(|_| ServiceError::validation("Current password is incorrect"))?;
+
+        validate_password_strength(&request.new_password, &user.username)?;
+        let password_hash = hash_password(&request.new_password).await?;
+
+        // `iat` has whole-second precision, so the tokens returned below stay valid.
+        UserRepository::new(self.pool)
+            .update_password(&user.id, &password_hash, Utc::now())
+            .await?;
+
+        tracing::info!(
+            "Password changed for user {}; other sessions revoked",
+            user.id
+        );
+
+        self.build_login_response(user).await
+    }
+
+    /// Lists the accounts the caller can switch to, marking the one their
+    /// token was issued for.
+    pub async fn list_accounts(
+        &self,
+        claims: &Claims,
+    ) -> ServiceResult<Vec<AccountMembershipDetails>> {
+        Ok(AccountMembershipRepository::new(self.pool)
+            .get_accounts_for_user(claims.user_id(), claims.account_id())
+            .await?)
+    }
+
+    /// Issues tokens for another account the caller belongs to, carrying the
+    /// role they hold there.
+    pub async fn switch_account(
+        &self,
+        claims: &Claims,
+        request: SwitchAccountRequest,
+    ) -> ServiceResult<LoginResponse> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let user = self
+            .user_service
+            .get_user_in_account(claims.user_id(), &request.account_id)
+            .await
+            .map_err(|e| match e {
+                ServiceError::NotFound { .. } => {
+                    ServiceError::not_found("Account membership", &request.account_id)
+                }
+                e => e,
+            })?;
+
+        if !user.is_active {
+            return Err(ServiceError::validation(
+                "User account is inactive".to_string(),
+            ));
+        }
+
+        self.build_login_response(user).await
+    }
+
+    /// Issues access and refresh tokens for an already authenticated user,
+    /// scoped to the account in `user.account_id`
+    async fn build_login_response(&self, user: User) -> ServiceResult<LoginResponse> {
         // Get account information
         let account_repo = AccountRepository::new(self.pool);
         let account = account_repo
@@ -75,50 +208,40 @@ impl<'a> AuthService<'a> {
         // Store user ID before potential moves
         let user_id = user.id.clone();
         let account_id = account.id.clone();
-        let _user_account_id = user.account_id.clone();
         let user_role_id = user.role_id.clone();
         let role_access_level = user.role_access_level.clone();
 
-        // Check for existing node credentials and convert them to JWT format
-        let credential_repo = CredentialRepository::new(self.pool);
-        let node_credentials =
-            if let Some(credential) = credential_repo.get_credential_by_user_id(&user_id).await? {
-                Some(NodeCredentials {
-                    node_id: credential.node_id,
-                    node_alias: credential.node_alias,
-                    node_type: credential.node_type.unwrap_or_else(|| "lnd".to_string()),
-                    macaroon: credential.macaroon,
-                    tls_cert: credential.tls_cert,
-                    client_cert: credential.client_cert,
-                    client_key: credential.client_key,
-                    ca_cert: credential.ca_cert,
-                    address: credential.address,
-                })
-            } else {
-                None
-            };
+        // Get user role and the nodes it leaves the user
+        let role = self.get_user_role(&user_role_id).await?;
+        let node_scope = self.user_service.node_scope(&user, &role).await?;
 
-        // Get user role name
-        let role_name = self.get_user_role_name(&user_role_id).await?;
+        // Check for existing node credentials and convert them to JWT format
+        let node_credentials = self
+            .account_node_credential(&user, node_scope.as_deref())
+            .await?
+            .map(|credential| NodeCredentialRef::from(&credential));
+        let has_node_credentials = node_credentials.is_some();
 
         // Generate tokens with node credentials if available
         let access_token = self.jwt_utils.generate_token(
             user_id.clone(),
             account_id.clone(),
-            role_name.clone(),
+            role.name.clone(),
             role_access_level.clone(),
+            role.permissions(),
             node_credentials,
+            node_scope,
         )?;
 
-        let refresh_token = self
-            .jwt_utils
-            .generate_refresh_token(user_id.clone(), role_access_level.clone())?;
+        let refresh_token = self.jwt_utils.generate_refresh_token(
+            user_id.clone(),
+            account_id.clone(),
+            role_access_level.clone(),
+        )?;
 
-        // Check if user has credentials for the response
-        let has_node_credentials = credential_repo
-            .get_credential_by_user_id(&user_id)
-            .await?
-            .is_some();
+        let email_verified = UserRepository::new(self.pool)
+            .is_email_verified(&user_id)
+            .await?;
 
         // Get expires_in from config
         let expires_in = self.config.jwt_expires_in_seconds;
@@ -129,8 +252,9 @@ impl<'a> AuthService<'a> {
             email: user.email,
             account_id,
             account_name: account.name,
-            role: role_name,
+            role: role.name,
             has_node_credentials,
+            email_verified,
         };
 
         Ok(LoginResponse {
@@ -141,6 +265,43 @@ impl<'a> AuthService<'a> {
         })
     }
 
+    /// The node credential a token for `user.account_id` carries: the user's
+    /// own when stored under that account or, for users who joined the
+    /// account as a member or were limited to some of its nodes, the
+    /// account's first node they may access.
+    async fn account_node_credential(
+        &self,
+        user: &User,
+        node_scope: Option<&[String]>,
+    ) -> ServiceResult<Option<Credential>> {
+        let in_scope = |credential: &Credential| {
+            node_scope.is_none_or(|node_ids| node_ids.contains(&credential.node_id))
+        };
+
+        let credential_repo = CredentialRepository::new(self.pool);
+        if let Some(credential) = credential_repo
+            .get_credential_by_user_id(&user.id)
+            .await?
+            .filter(|credential| credential.account_id == user.account_id && in_scope(credential))
+        {
+            return Ok(Some(credential));
+        }
+
+        let is_member = AccountMembershipRepository::new(self.pool)
+            .get_membership(&user.id, &user.account_id)
+            .await?
+            .is_some();
+        if !is_member && node_scope.is_none() {
+            return Ok(None);
+        }
+
+        Ok(credential_repo
+            .get_credentials_by_account_id(&user.account_id)
+            .await?
+            .into_iter()
+            .find(in_scope))
+    }
+
     /// Store node credentials in database after authentication
     pub async fn store_node_credentials(
         &self,
@@ -198,26 +359,18 @@ impl<'a> AuthService<'a> {
         // Store in database
         let credential = credential_repo.create_credential(create_credential).await?;
 
-        // Create node credentials for new token
-        let node_credentials = NodeCredentials {
-            node_id: request.node_id,
-            node_alias: request.node_alias,
-            node_type: request.node_type,
-            macaroon: request.macaroon,
-            tls_cert: request.tls_cert,
-            client_cert: request.client_cert,
-            client_key: request.client_key,
-            ca_cert: request.ca_cert,
-            address: request.address,
-        };
+        // The token only names the stored credential; its secrets stay server-side
+        let node_credential = NodeCredentialRef::from(&credential);
 
-        // Generate new token with node credentials
+        // Generate new token bound to the stored credential
         let access_token = self.jwt_utils.generate_token(
             claims.sub,
             claims.account_id,
             claims.role,
             claims.role_access_level,
-            Some(node_credentials),
+            claims.permissions,
+            Some(node_credential),
+            claims.node_scope,
         )?;
 
         Ok(StoreNodeCredentialsResponse {
@@ -249,7 +402,9 @@ impl<'a> AuthService<'a> {
             claims.account_id,
             claims.role,
             claims.role_access_level,
+            claims.permissions,
             None, // No node credentials
+            claims.node_scope,
         )?;
 
         Ok(RevokeNodeCredentialsResponse {
@@ -270,12 +425,35 @@ impl<'a> AuthService<'a> {
         // Get user to ensure they still exist and are active
         let user = self.user_service.get_user_required(&claims.sub).await?;
 
+        if self
+            .user_service
+            .is_token_revoked(&user.id, claims.iat)
+            .await?
+        {
+            return Err(ServiceError::validation("Refresh token has been revoked"));
+        }
+
         if !user.is_active {
             return Err(ServiceError::validation(
                 "User account is inactive".to_string(),
             ));
         }
 
+        // Stay on the account the session was switched to while the user is
+        // still a member of it; older refresh tokens carry no account.
+        let user = match claims.account_id() {
+            "" => user,
+            account_id => match self
+                .user_service
+                .get_user_in_account(&user.id, account_id)
+                .await
+            {
+                Ok(member) => member,
+                Err(ServiceError::NotFound { .. }) => user,
+                Err(e) => return Err(e),
+            },
+        };
+
         // Store needed values before potential moves
         let user_id = user.id.clone();
         let user_account_id = user.account_id.clone();
@@ -283,31 +461,22 @@ impl<'a> AuthService<'a> {
         let role_access_level = user.role_access_level.clone();
 
         // Check for existing node credentials
-        let credential_repo = CredentialRepository::new(self.pool);
-        let node_credentials =
-            if let Some(credential) = credential_repo.get_credential_by_user_id(&user_id).await? {
-                Some(NodeCredentials {
-                    node_id: credential.node_id,
-                    node_alias: credential.node_alias,
-                    node_type: credential.node_type.unwrap_or_else(|| "lnd".to_string()),
-                    macaroon: credential.macaroon,
-                    tls_cert: credential.tls_cert,
-                    client_cert: credential.client_cert,
-                    client_key: credential.client_key,
-                    ca_cert: credential.ca_cert,
-                    address: credential.address,
-                })
-            } else {
-                None
-            };
+        let role = self.get_user_role(&user_role_id).await?;
+        let node_scope = self.user_service.node_scope(&user, &role).await?;
+        let node_credentials = self
+            .account_node_credential(&user, node_scope.as_deref())
+            .await?
+            .map(|credential| NodeCredentialRef::from(&credential));
 
         // Generate new access token with node credentials if available
         let access_token = self.jwt_utils.generate_token(
             user_id,
             user_account_id,
-            self.get_user_role_name(&user_role_id).await?,
+            role.name.clone(),
             role_access_level,
+            role.permissions(),
             node_credentials,
+            node_scope,
         )?;
 
         Ok(RefreshTokenResponse {
@@ -316,6 +485,190 @@ impl<'a> AuthService<'a> {
         })
     }
 
+    /// Issues an LNURL-auth challenge.
+    ///
+    /// Anonymous callers get a login challenge; authenticated callers get a link
+    /// challenge that attaches the signing wallet's key to their user.
+    pub async fn issue_lnurl_challenge(
+        &self,
+        claims: Option<&Claims>,
+    ) -> ServiceResult<LnurlChallengeResponse> {
+        let mut k1_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut k1_bytes);
+        let k1 = hex::encode(k1_bytes);
+
+        let (action, user_id) = match claims {
+            Some(claims) => (LnurlAuthAction::Link, Some(claims.sub.as_str())),
+            None => (LnurlAuthAction::Login, None),
+        };
+
+        let callback_url = format!(
+            "{}/api/v1/auth/lnurl/callback?tag=login&k1={}&action={}",
+            self.config.api_base_url.trim_end_matches('/'),
+            k1,
+            action
+        );
+        let lnurl = encode_lnurl(&callback_url).map_err(ServiceError::internal_error)?;
+
+        let repo = LnurlAuthRepository::new(self.pool);
+        let challenge = repo
+            .create_challenge(
+                &k1,
+                action,
+                user_id,
+                Utc::now() + Duration::minutes(LNURL_CHALLENGE_TTL_MINUTES),
+            )
+            .await?;
+
+        Ok(LnurlChallengeResponse {
+            k1: challenge.k1,
+            lnurl,
+            callback_url,
+            action: challenge.action.to_string(),
+            expires_at: challenge.expires_at,
+        })
+    }
+
+    /// Verifies the signature a wallet posts to the LNURL-auth callback.
+    ///
+    /// On success the challenge is marked as verified; link challenges also
+    /// store the linking key against the user that requested them.
+    pub async fn verify_lnurl_callback(&self, query: LnurlCallbackQuery) -> ServiceResult<()> {
+        if query.tag != "login" {
+            return Err(ServiceError::validation("Unsupported LNURL tag"));
+        }
+
+        let repo = LnurlAuthRepository::new(self.pool);
+        let challenge = repo
+            .get_challenge(&query.k1)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("LNURL challenge", &query.k1))?;
+
+        if challenge.status != LnurlChallengeStatus::Pending {
+            return Err(ServiceError::invalid_operation(
+                "Challenge has already been used",
+            ));
+        }
+        if challenge.expires_at <= Utc::now() {
+            return Err(ServiceError::validation("Challenge has expired"));
+        }
+
+        verify_auth_signature(&query.k1, &query.sig, &query.key)
+            .map_err(ServiceError::validation)?;
+
+        let linking_key = query.key.to_lowercase();
+        let existing_key = repo.get_key_by_linking_key(&linking_key).await?;
+
+        let user_id = match challenge.action {
+            LnurlAuthAction::Link => {
+                let user_id = challenge.user_id.ok_or_else(|| {
+                    ServiceError::internal_error("Link challenge is missing its user")
+                })?;
+                match existing_key {
+                    Some(key) if key.user_id != user_id => {
+                        return Err(ServiceError::already_exists("Linking key", &linking_key));
+                    }
+                    Some(_) => {}
+                    None => {
+                        repo.create_key(&Uuid::now_v7().to_string(), &user_id, &linking_key)
+                            .await?;
+                    }
+                }
+                user_id
+            }
+            LnurlAuthAction::Login => {
+                existing_key
+                    .ok_or_else(|| ServiceError::not_found("User for linking key", &linking_key))?
+                    .user_id
+            }
+        };
+
+        if !repo
+            .mark_challenge_verified(&query.k1, &user_id, &linking_key)
+            .await?
+        {
+            return Err(ServiceError::invalid_operation(
+                "Challenge has already been used",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Exchanges a verified login challenge for session tokens.
+    ///
+    /// The frontend polls this after displaying the QR code; it succeeds exactly
+    /// once per challenge.
+    pub async fn complete_lnurl_login(
+        &self,
+        request: LnurlTokenRequest,
+    ) -> ServiceResult<LoginResponse> {
+        if let Err(validation_errors) = request.validate() {
+            let error_messages: Vec<String> = validation_errors
+                .field_errors()
+                .into_iter()
+                .flat_map(|(field, errors)| {
+                    errors.iter().map(move |error| {
+                        format!(
+                            "{}: {}",
+                            field,
+                            error.message.as_ref().unwrap_or(&"Invalid value".into())
+                        )
+                    })
+                })
+                .collect();
+            return Err(ServiceError::validation(error_messages.join(", ")));
+        }
+
+        let repo = LnurlAuthRepository::new(self.pool);
+        let challenge = repo
+            .get_challenge(&request.k1)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("LNURL challenge", &request.k1))?;
+
+        if challenge.action != LnurlAuthAction::Login {
+            return Err(ServiceError::invalid_operation(
+                "Challenge was not issued for login",
+            ));
+        }
+
+        match challenge.status {
+            LnurlChallengeStatus::Pending if challenge.expires_at <= Utc::now() => {
+                return Err(ServiceError::validation("Challenge has expired"));
+            }
+            LnurlChallengeStatus::Pending => {
+                return Err(ServiceError::invalid_operation(
+                    "Challenge has not been signed yet",
+                ));
+            }
+            LnurlChallengeStatus::Consumed => {
+                return Err(ServiceError::invalid_operation(
+                    "Challenge has already been used",
+                ));
+            }
+            LnurlChallengeStatus::Verified => {}
+        }
+
+        let user_id = challenge
+            .user_id
+            .ok_or_else(|| ServiceError::internal_error("Verified challenge has no user"))?;
+
+        if !repo.consume_challenge(&request.k1).await? {
+            return Err(ServiceError::invalid_operation(
+                "Challenge has already been used",
+            ));
+        }
+
+        let user = self.user_service.get_user_required(&user_id).await?;
+        if !user.is_active {
+            return Err(ServiceError::validation(
+                "User account is inactive".to_string(),
+            ));
+        }
+
+        self.build_login_response(user).await
+    }
+
     /// Helper method to determine node type from macaroon or other stored data
     fn determine_node_type(&self, _macaroon: &str) -> ServiceResult<String> {
         // For now, return a default. You might want to store this explicitly in the database
@@ -323,14 +676,14 @@ impl<'a> AuthService<'a> {
         Ok("lnd".to_string())
     }
 
-    /// Helper method to get user role name
-    async fn get_user_role_name(&self, role_id: &str) -> ServiceResult<String> {
+    /// Helper method to get the user's role
+    async fn get_user_role(&self, role_id: &str) -> ServiceResult<Role> {
         let role_repo = crate::repositories::role_repository::RoleRepository::new(self.pool);
         let role = role_repo
             .get_role_by_id(role_id)
             .await?
             .ok_or_else(|| ServiceError::not_found("Role", role_id))?;
 
-        Ok(role.name)
+        Ok(role)
     }
 }
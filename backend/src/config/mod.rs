@@ -4,6 +4,7 @@
 //! database URLs, server port, and paths to sensitive files (macaroons, certs).
 
 use anyhow::{Context, Result};
+use bitcoin::Network;
 use std::env;
 
 #[derive(Debug, Clone)]
@@ -15,6 +16,10 @@ pub struct Config {
     pub jwt_expires_in_seconds: u64,
     pub server_port: u16,
     pub encryption_key: String,
+    /// Version recorded with values wrapped by `encryption_key`
+    pub encryption_key_version: u32,
+    /// Earlier keys by version, kept to read values until they're rekeyed
+    pub retired_encryption_keys: Vec<(u32, String)>,
 
     // Email configuration
     pub smtp_host: Option<String>,
@@ -24,6 +29,112 @@ pub struct Config {
     pub from_email: Option<String>,
     pub from_name: Option<String>,
     pub base_url: String,
+    /// Publicly reachable URL of this API, used for callbacks such as LNURL-auth
+    pub api_base_url: String,
+
+    // Node RPC limits
+    pub node_max_inflight_requests: usize,
+    pub node_queue_timeout_ms: u64,
+    /// Consecutive failed connections after which a node's circuit opens
+    pub node_breaker_threshold: u32,
+    /// How long an open circuit fails requests fast before letting one through
+    pub node_breaker_cooldown_secs: u64,
+
+    /// Number of background job workers
+    pub job_workers: usize,
+
+    pub notification_unhealthy_after_minutes: i64,
+
+    // Scheduled database backups
+    pub backup_dir: String,
+    pub backup_retention: usize,
+
+    /// Directory node export bundles are written to
+    pub export_dir: String,
+
+    /// Base URL of the Boltz API used for rebalancing swaps
+    pub boltz_api_url: String,
+
+    // Buffer between each node's event stream and the event writer
+    pub event_channel_capacity: usize,
+    pub event_overflow_policy: EventOverflowPolicy,
+
+    /// Provision the demo account and allow anonymous demo logins
+    pub demo_mode: bool,
+
+    /// Directory of Polar networks that `/admin/dev/bootstrap` may read;
+    /// None disables the endpoint
+    pub polar_networks_dir: Option<String>,
+
+    /// Allow anyone to sign up a new account; without it only the first
+    /// account can be registered
+    pub registration_enabled: bool,
+    /// Account whose admins run instance-wide operations such as rekeying
+    /// secrets; when unset that is the first account created, unless
+    /// registration is enabled
+    pub operator_account_id: Option<String>,
+
+    /// Plans with limits, paid through Stripe; off for self-hosted deployments,
+    /// which have no limits
+    pub billing_enabled: bool,
+    pub stripe_api_url: String,
+    pub stripe_secret_key: Option<String>,
+    pub stripe_webhook_secret: Option<String>,
+    /// Stripe price IDs of the paid plans
+    pub stripe_price_pro: Option<String>,
+    pub stripe_price_business: Option<String>,
+    /// Account and node whose credential issues Lightning billing invoices;
+    /// Lightning billing is off unless both are set
+    pub treasury_account_id: Option<String>,
+    pub treasury_node_id: Option<String>,
+    /// Monthly prices of the paid plans when paid over Lightning
+    pub lightning_price_pro_sat: Option<i64>,
+    pub lightning_price_business_sat: Option<i64>,
+    /// Days an unpaid Lightning invoice is tolerated after the period ends
+    /// before the account is suspended
+    pub billing_grace_days: i64,
+
+    // Peer metadata from Amboss and 1ML
+    pub peer_enrichment_enabled: bool,
+    pub amboss_api_url: String,
+    pub amboss_api_key: Option<String>,
+    pub one_ml_api_url: String,
+
+    /// mempool.space websocket for new blocks; None disables the block stream
+    pub mempool_ws_url: Option<String>,
+    /// Chain the mempool.space instance follows; only nodes on it get block events
+    pub mempool_network: Network,
+    /// mempool.space REST API, used to look up where channel transactions confirmed
+    pub mempool_api_url: String,
+}
+
+/// What a node's event stream does when the event buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventOverflowPolicy {
+    /// Wait for room, slowing down the subscription to the node
+    Block,
+    /// Discard the oldest buffered events and count them
+    DropOldest,
+}
+
+impl std::fmt::Display for EventOverflowPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventOverflowPolicy::Block => write!(f, "block"),
+            EventOverflowPolicy::DropOldest => write!(f, "drop_oldest"),
+        }
+    }
+}
+
+impl std::str::FromStr for EventOverflowPolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(EventOverflowPolicy::Block),
+            "drop_oldest" => Ok(EventOverflowPolicy::DropOldest),
+            _ => Err(format!("Invalid event overflow policy: {s}")),
+        }
+    }
 }
 
 impl Config {
@@ -57,6 +168,29 @@ impl Config {
 
         let encryption_key = env::var("ENCRYPTION_KEY").context("ENCRYPTION_KEY not set")?;
 
+        let encryption_key_version = env::var("ENCRYPTION_KEY_VERSION")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse::<u32>()
+            .ok()
+            .filter(|version| *version > 0)
+            .context("ENCRYPTION_KEY_VERSION must be a positive number")?;
+
+        // Comma separated `version:key` pairs
+        let retired_encryption_keys = env::var("ENCRYPTION_KEYS_RETIRED")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (version, key) = entry.split_once(':')?;
+                let version = version.trim().parse::<u32>().ok()?;
+                (version != encryption_key_version).then(|| (version, key.trim().to_string()))
+            })
+            .collect::<Option<Vec<_>>>()
+            .context(
+                "ENCRYPTION_KEYS_RETIRED must be comma separated version:key pairs, none using ENCRYPTION_KEY_VERSION",
+            )?;
+
         // Optional email configuration
         let smtp_host = env::var("SMTP_HOST").ok();
         let smtp_port = env::var("SMTP_PORT").ok().and_then(|p| p.parse().ok());
@@ -66,6 +200,137 @@ impl Config {
         let from_name = env::var("FROM_NAME").ok();
         // Base URL for the application, used in email links
         let base_url = env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        let api_base_url =
+            env::var("API_BASE_URL").unwrap_or_else(|_| format!("http://localhost:{server_port}"));
+
+        let node_max_inflight_requests = env::var("NODE_MAX_INFLIGHT_REQUESTS")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse::<usize>()
+            .context("NODE_MAX_INFLIGHT_REQUESTS must be a valid number")?;
+
+        let node_queue_timeout_ms = env::var("NODE_QUEUE_TIMEOUT_MS")
+            .unwrap_or_else(|_| "5000".to_string())
+            .parse::<u64>()
+            .context("NODE_QUEUE_TIMEOUT_MS must be a valid number")?;
+
+        let node_breaker_threshold = env::var("NODE_BREAKER_THRESHOLD")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<u32>()
+            .context("NODE_BREAKER_THRESHOLD must be a valid number")?;
+
+        let node_breaker_cooldown_secs = env::var("NODE_BREAKER_COOLDOWN_SECS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<u64>()
+            .context("NODE_BREAKER_COOLDOWN_SECS must be a valid number")?;
+
+        let job_workers = env::var("JOB_WORKERS")
+            .unwrap_or_else(|_| "2".to_string())
+            .parse::<usize>()
+            .context("JOB_WORKERS must be a valid number")?;
+
+        let notification_unhealthy_after_minutes = env::var("NOTIFICATION_UNHEALTHY_AFTER_MINUTES")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse::<i64>()
+            .ok()
+            .filter(|minutes| *minutes >= 0)
+            .context("NOTIFICATION_UNHEALTHY_AFTER_MINUTES must be a non-negative number")?;
+
+        let backup_dir = env::var("BACKUP_DIR").unwrap_or_else(|_| "backups".to_string());
+
+        let backup_retention = env::var("BACKUP_RETENTION")
+            .unwrap_or_else(|_| "7".to_string())
+            .parse::<usize>()
+            .context("BACKUP_RETENTION must be a valid number")?;
+
+        let export_dir = env::var("EXPORT_DIR").unwrap_or_else(|_| "exports".to_string());
+
+        let boltz_api_url =
+            env::var("BOLTZ_API_URL").unwrap_or_else(|_| "https://api.boltz.exchange".to_string());
+
+        let event_channel_capacity = env::var("EVENT_CHANNEL_CAPACITY")
+            .unwrap_or_else(|_| "32".to_string())
+            .parse::<usize>()
+            .context("EVENT_CHANNEL_CAPACITY must be a valid number")?;
+
+        let event_overflow_policy = env::var("EVENT_OVERFLOW_POLICY")
+            .unwrap_or_else(|_| "block".to_string())
+            .parse::<EventOverflowPolicy>()
+            .map_err(anyhow::Error::msg)
+            .context("EVENT_OVERFLOW_POLICY must be either block or drop_oldest")?;
+
+        let demo_mode = env::var("DEMO_MODE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .context("DEMO_MODE must be true or false")?;
+
+        let polar_networks_dir = env::var("POLAR_NETWORKS_DIR")
+            .ok()
+            .filter(|dir| !dir.is_empty());
+
+        let registration_enabled = env::var("REGISTRATION_ENABLED")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .context("REGISTRATION_ENABLED must be true or false")?;
+        let operator_account_id = env::var("OPERATOR_ACCOUNT_ID")
+            .ok()
+            .filter(|id| !id.is_empty());
+
+        let billing_enabled = env::var("BILLING_ENABLED")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .context("BILLING_ENABLED must be true or false")?;
+        let stripe_api_url =
+            env::var("STRIPE_API_URL").unwrap_or_else(|_| "https://api.stripe.com".to_string());
+        let optional = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let stripe_secret_key = optional("STRIPE_SECRET_KEY");
+        let stripe_webhook_secret = optional("STRIPE_WEBHOOK_SECRET");
+        let stripe_price_pro = optional("STRIPE_PRICE_PRO");
+        let stripe_price_business = optional("STRIPE_PRICE_BUSINESS");
+        let treasury_account_id = optional("TREASURY_ACCOUNT_ID");
+        let treasury_node_id = optional("TREASURY_NODE_ID");
+        let price_sat = |name: &str| -> Result<Option<i64>> {
+            optional(name)
+                .map(|value| {
+                    value
+                        .parse::<i64>()
+                        .ok()
+                        .filter(|sats| *sats > 0)
+                        .with_context(|| format!("{name} must be a positive number"))
+                })
+                .transpose()
+        };
+        let lightning_price_pro_sat = price_sat("LIGHTNING_PRICE_PRO_SAT")?;
+        let lightning_price_business_sat = price_sat("LIGHTNING_PRICE_BUSINESS_SAT")?;
+        let billing_grace_days = env::var("BILLING_GRACE_DAYS")
+            .unwrap_or_else(|_| "7".to_string())
+            .parse::<i64>()
+            .ok()
+            .filter(|days| *days >= 0)
+            .context("BILLING_GRACE_DAYS must be a non-negative number")?;
+
+        let peer_enrichment_enabled = env::var("PEER_ENRICHMENT")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .context("PEER_ENRICHMENT must be true or false")?;
+        let amboss_api_url = env::var("AMBOSS_API_URL")
+            .unwrap_or_else(|_| "https://api.amboss.space/graphql".to_string());
+        let amboss_api_key = env::var("AMBOSS_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty());
+        let one_ml_api_url =
+            env::var("ONE_ML_API_URL").unwrap_or_else(|_| "https://1ml.com".to_string());
+
+        let mempool_ws_url = match env::var("MEMPOOL_WS_URL") {
+            Ok(url) if url.is_empty() => None,
+            Ok(url) => Some(url),
+            Err(_) => Some("wss://mempool.space/api/v1/ws".to_string()),
+        };
+        let mempool_network = env::var("MEMPOOL_NETWORK")
+            .unwrap_or_else(|_| "bitcoin".to_string())
+            .parse::<Network>()
+            .context("MEMPOOL_NETWORK must be bitcoin, testnet, signet or regtest")?;
+        let mempool_api_url =
+            env::var("MEMPOOL_API_URL").unwrap_or_else(|_| "https://mempool.space/api".to_string());
 
         Ok(Config {
             database_url,
@@ -75,6 +340,8 @@ impl Config {
             jwt_expires_in_seconds,
             server_port,
             encryption_key,
+            encryption_key_version,
+            retired_encryption_keys,
             smtp_host,
             smtp_port,
             smtp_username,
@@ -82,6 +349,41 @@ impl Config {
             from_email,
             from_name,
             base_url,
+            api_base_url,
+            node_max_inflight_requests,
+            node_queue_timeout_ms,
+            node_breaker_threshold,
+            node_breaker_cooldown_secs,
+            job_workers,
+            notification_unhealthy_after_minutes,
+            backup_dir,
+            backup_retention,
+            export_dir,
+            boltz_api_url,
+            event_channel_capacity,
+            event_overflow_policy,
+            demo_mode,
+            polar_networks_dir,
+            registration_enabled,
+            operator_account_id,
+            billing_enabled,
+            stripe_api_url,
+            stripe_secret_key,
+            stripe_webhook_secret,
+            stripe_price_pro,
+            stripe_price_business,
+            treasury_account_id,
+            treasury_node_id,
+            lightning_price_pro_sat,
+            lightning_price_business_sat,
+            billing_grace_days,
+            peer_enrichment_enabled,
+            amboss_api_url,
+            amboss_api_key,
+            one_ml_api_url,
+            mempool_ws_url,
+            mempool_network,
+            mempool_api_url,
         })
     }
 
@@ -109,6 +411,25 @@ impl Config {
         }
     }
 
+    /// Whether plans can be paid with invoices from the treasury node
+    pub fn lightning_billing_enabled(&self) -> bool {
+        self.billing_enabled
+            && self.treasury_account_id.is_some()
+            && self.treasury_node_id.is_some()
+    }
+
+    /// Whether admins of the account may run instance-wide operations:
+    /// `OPERATOR_ACCOUNT_ID` or, when it is unset and registration is
+    /// disabled, `first_account_id`, the first account ever created. Nobody
+    /// is the operator once registration is enabled without the variable.
+    pub fn is_operator_account(&self, account_id: &str, first_account_id: Option<&str>) -> bool {
+        operator_account(
+            self.operator_account_id.as_deref(),
+            self.registration_enabled,
+            first_account_id,
+        ) == Some(account_id)
+    }
+
     /// Check if email is configured
     pub fn is_email_configured(&self) -> bool {
         self.email_config().is_some()
@@ -126,3 +447,40 @@ pub struct EmailConfig {
     pub from_name: String,
     pub base_url: String,
 }
+
+/// The account whose admins run instance-wide operations, if any.
+fn operator_account<'a>(
+    operator_account_id: Option<&'a str>,
+    registration_enabled: bool,
+    first_account_id: Option<&'a str>,
+) -> Option<&'a str> {
+    match operator_account_id {
+        Some(operator_account_id) => Some(operator_account_id),
+        None if registration_enabled => None,
+        None => first_account_id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operator_account_is_one_account() {
+        let accounts = ["first", "second", "third"];
+        let operators = |operator_account_id, registration_enabled| -> Vec<&str> {
+            accounts
+                .into_iter()
+                .filter(|&account_id| {
+                    operator_account(operator_account_id, registration_enabled, Some("first"))
+                        == Some(account_id)
+                })
+                .collect()
+        };
+
+        assert_eq!(operators(None, false), ["first"]);
+        assert_eq!(operators(None, true), Vec::<&str>::new());
+        assert_eq!(operators(Some("second"), false), ["second"]);
+        assert_eq!(operators(Some("second"), true), ["second"]);
+    }
+}
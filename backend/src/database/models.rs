@@ -4,9 +4,10 @@
 //! from the database, often used by an ORM. Note that these may differ from
 //! API-specific models.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -70,6 +71,7 @@ pub struct User {
     pub id: String,
     pub account_id: String,
     pub username: String,
+    pub display_name: Option<String>,
     pub password_hash: String,
     pub email: String,
     pub role_id: String,
@@ -81,6 +83,54 @@ pub struct User {
     pub deleted_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateProfileRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Display name must be between 1-255 characters"
+    ))]
+    pub display_name: Option<String>,
+    /// New email address; only applied once verified
+    #[validate(
+        email(message = "Must be a valid email"),
+        length(max = 255, message = "Email too long")
+    )]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileResponse {
+    pub user: User,
+    /// Email address awaiting verification, if a change was requested
+    pub pending_email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    pub current_password: String,
+    #[validate(length(min = 8, message = "New password must be at least 8 characters"))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EmailChangeRequest {
+    pub id: String,
+    pub user_id: String,
+    pub new_email: String,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq, PartialOrd)]
 #[sqlx(type_name = "TEXT")] // Store as TEXT in SQLite
 pub enum RoleAccessLevel {
@@ -164,7 +214,12 @@ pub struct CreateUser {
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Role {
     pub id: String,
+    /// Owning account for custom roles; `None` for the built-in Admin and Member roles
+    pub account_id: Option<String>,
     pub name: String,
+    pub permissions: String, // JSON array of permission scopes
+    /// JSON array of the node IDs the role is limited to; `None` for every node
+    pub node_ids: Option<String>,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -176,6 +231,137 @@ pub struct Role {
 pub struct CreateRole {
     #[validate(length(min = 1, max = 255, message = "Name must be between 1-255 characters"))]
     pub name: String,
+    #[validate(length(min = 1, message = "At least one permission is required"))]
+    pub permissions: Vec<Permission>,
+    /// Nodes of the account the role is limited to; every node when omitted
+    #[validate(length(min = 1, message = "At least one node is required"))]
+    pub node_ids: Option<Vec<String>>,
+}
+
+impl Role {
+    /// Whether this is one of the built-in roles rather than an account-defined one.
+    pub fn is_built_in(&self) -> bool {
+        self.account_id.is_none()
+    }
+
+    /// Permission scopes granted by this role. Built-in roles grant every scope
+    /// except `credentials:manage`, which the Admin role holds anyway; what they
+    /// may modify is still governed by the user's access level.
+    pub fn permissions(&self) -> Vec<Permission> {
+        if self.is_built_in() {
+            return Permission::ALL
+                .into_iter()
+                .filter(|permission| *permission != Permission::CredentialsManage)
+                .collect();
+        }
+        serde_json::from_str(&self.permissions).unwrap_or_default()
+    }
+
+    /// Nodes this role is limited to, or `None` if it grants every node of
+    /// the account.
+    pub fn node_ids(&self) -> Option<Vec<String>> {
+        self.node_ids
+            .as_deref()
+            .map(|node_ids| serde_json::from_str(node_ids).unwrap_or_default())
+    }
+}
+
+/// Fine-grained scopes that custom roles are composed of.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Permission {
+    #[serde(rename = "node:read")]
+    NodeRead,
+    #[serde(rename = "node:sign")]
+    NodeSign,
+    #[serde(rename = "channels:read")]
+    ChannelsRead,
+    #[serde(rename = "payments:read")]
+    PaymentsRead,
+    #[serde(rename = "invoices:read")]
+    InvoicesRead,
+    #[serde(rename = "events:read")]
+    EventsRead,
+    #[serde(rename = "notifications:read")]
+    NotificationsRead,
+    #[serde(rename = "notifications:write")]
+    NotificationsWrite,
+    #[serde(rename = "credentials:manage")]
+    CredentialsManage,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 9] = [
+        Permission::NodeRead,
+        Permission::NodeSign,
+        Permission::ChannelsRead,
+        Permission::PaymentsRead,
+        Permission::InvoicesRead,
+        Permission::EventsRead,
+        Permission::NotificationsRead,
+        Permission::NotificationsWrite,
+        Permission::CredentialsManage,
+    ];
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Permission::NodeRead => write!(f, "node:read"),
+            Permission::NodeSign => write!(f, "node:sign"),
+            Permission::ChannelsRead => write!(f, "channels:read"),
+            Permission::PaymentsRead => write!(f, "payments:read"),
+            Permission::InvoicesRead => write!(f, "invoices:read"),
+            Permission::EventsRead => write!(f, "events:read"),
+            Permission::NotificationsRead => write!(f, "notifications:read"),
+            Permission::NotificationsWrite => write!(f, "notifications:write"),
+            Permission::CredentialsManage => write!(f, "credentials:manage"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AssignRoleRequest {
+    #[validate(length(min = 1, message = "Role ID is required"))]
+    pub role_id: String,
+}
+
+/// A user's membership of an account other than their home account.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccountMembership {
+    pub id: String,
+    pub user_id: String,
+    pub account_id: String,
+    pub role_id: String,
+    pub role_access_level: RoleAccessLevel,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An account a user can sign in to, with the role they hold there.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccountMembershipDetails {
+    pub account_id: String,
+    pub account_name: String,
+    pub role_id: String,
+    pub role: String,
+    pub is_active: bool,
+    /// Whether this is the account the user signed up to
+    pub is_home: bool,
+    /// Whether the caller's token is issued for this account
+    pub is_current: bool,
+}
+
+/// Adds an existing user to the caller's account.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AddAccountMemberRequest {
+    #[validate(email(message = "Must be a valid email"))]
+    pub email: String,
+    /// Role within the account; Member when omitted
+    pub role_id: Option<String>,
+    /// Nodes of the account the user may access; every node the role grants
+    /// when omitted
+    #[validate(length(min = 1, message = "At least one node is required"))]
+    pub node_ids: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -185,12 +371,15 @@ pub struct Credential {
     pub account_id: String,
     pub node_id: String,
     pub node_alias: String,
+    /// Never serialized, so a credential can't leak through a response by accident
+    #[serde(skip_serializing)]
     pub macaroon: String,
     pub tls_cert: String,
     pub address: String,
-    pub node_type: Option<String>,   // "lnd" or "cln"
+    pub node_type: Option<String>,   // "lnd", "cln" or "clnrest"
     pub client_cert: Option<String>, // For CLN
-    pub client_key: Option<String>,  // For CLN
+    #[serde(skip_serializing)]
+    pub client_key: Option<String>, // For CLN
     pub ca_cert: Option<String>,     // For CLN
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
@@ -199,6 +388,27 @@ pub struct Credential {
     pub deleted_at: Option<DateTime<Utc>>,
 }
 
+/// A stored credential as the API shows it: metadata and fingerprints of
+/// the secrets, never the secrets themselves.
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialSummary {
+    pub id: String,
+    pub user_id: String,
+    pub node_id: String,
+    pub node_alias: String,
+    pub node_type: String,
+    pub address: String,
+    /// Fingerprint of the macaroon, or of the rune for clnrest nodes
+    pub macaroon_fingerprint: Option<String>,
+    pub tls_cert_fingerprint: Option<String>,
+    pub client_cert_fingerprint: Option<String>,
+    pub client_key_fingerprint: Option<String>,
+    pub ca_cert_fingerprint: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateCredential {
     #[validate(length(min = 1, message = "Credential ID is required"))]
@@ -243,6 +453,56 @@ fn validate_socket_address(address: &str) -> Result<(), validator::ValidationErr
     Ok(())
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LnurlAuthKey {
+    pub id: String,
+    pub user_id: String,
+    pub linking_key: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub is_deleted: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum LnurlAuthAction {
+    /// Anonymous challenge, resolved to a user through an already linked key
+    Login,
+    /// Challenge issued to a logged-in user to attach a new linking key
+    Link,
+}
+
+impl std::fmt::Display for LnurlAuthAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LnurlAuthAction::Login => write!(f, "login"),
+            LnurlAuthAction::Link => write!(f, "link"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum LnurlChallengeStatus {
+    Pending,
+    Verified,
+    Consumed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LnurlChallenge {
+    pub k1: String,
+    pub action: LnurlAuthAction,
+    pub user_id: Option<String>,
+    pub linking_key: Option<String>,
+    pub status: LnurlChallengeStatus,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Invite {
     pub id: String,
@@ -251,6 +511,9 @@ pub struct Invite {
     pub invitee_email: String,
     pub token: String,
     pub invite_status: InviteStatus,
+    pub role_id: Option<String>,
+    /// JSON array of the node IDs the invitee is limited to; `None` for every node
+    pub node_ids: Option<String>,
     pub is_active: bool,
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
@@ -301,6 +564,8 @@ pub struct CreateInvite {
     #[validate(custom(function = "validate_expiry_time"))]
     pub expires_at: DateTime<Utc>,
     pub invite_status: InviteStatus,
+    pub role_id: Option<String>,
+    pub node_ids: Option<String>,
 }
 
 /// Validates that the expiry time is in the future
@@ -320,6 +585,12 @@ pub struct CreateInviteRequest {
         length(max = 255, message = "Email too long")
     )]
     pub email: String,
+    /// Role the invitee receives; defaults to Member
+    pub role_id: Option<String>,
+    /// Nodes of the account the invitee may access; every node the role
+    /// grants when omitted
+    #[validate(length(min = 1, message = "At least one node is required"))]
+    pub node_ids: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -363,11 +634,40 @@ pub struct Notification {
     pub name: String,
     pub notification_type: NotificationType,
     pub url: String,
+    /// Only events touching this channel are delivered: a short channel id
+    /// or a channel point
+    pub channel: Option<String>,
+    /// Schema version of the webhook body the endpoint receives
+    pub payload_version: i64,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub is_deleted: bool,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// Request timeout; the dispatcher's default when `None`
+    pub timeout_secs: Option<i64>,
+    /// JSON object of extra request headers. Only their names are returned,
+    /// since values are usually credentials.
+    #[serde(
+        rename(serialize = "header_names"),
+        serialize_with = "serialize_header_names"
+    )]
+    pub headers: Option<String>,
+    /// Whether the endpoint's TLS certificate is verified
+    pub verify_tls: bool,
+}
+
+fn serialize_header_names<S: serde::Serializer>(
+    headers: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut names: Vec<String> = headers
+        .as_deref()
+        .and_then(|headers| serde_json::from_str::<HashMap<String, String>>(headers).ok())
+        .map(|headers| headers.into_keys().collect())
+        .unwrap_or_default();
+    names.sort();
+    names.serialize(serializer)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
@@ -375,6 +675,8 @@ pub struct Notification {
 pub enum NotificationType {
     Webhook,
     Discord,
+    /// A webhook receiving every event of the account, unfiltered
+    Firehose,
 }
 
 impl std::fmt::Display for NotificationType {
@@ -382,6 +684,7 @@ impl std::fmt::Display for NotificationType {
         match self {
             NotificationType::Webhook => write!(f, "webhook"),
             NotificationType::Discord => write!(f, "discord"),
+            NotificationType::Firehose => write!(f, "firehose"),
         }
     }
 }
@@ -392,11 +695,44 @@ impl std::str::FromStr for NotificationType {
         match s.to_lowercase().as_str() {
             "webhook" => Ok(NotificationType::Webhook),
             "discord" => Ok(NotificationType::Discord),
+            "firehose" => Ok(NotificationType::Firehose),
             _ => Err(format!("Invalid notification type: {s}")),
         }
     }
 }
 
+/// Outcome of the latest periodic check of a notification endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationHealth {
+    pub notification_id: String,
+    pub account_id: String,
+    pub healthy: bool,
+    pub last_error: Option<String>,
+    pub last_checked_at: DateTime<Utc>,
+    /// Start of the current run of failed checks
+    pub failing_since: Option<DateTime<Utc>>,
+    /// When the current run of failed checks was reported
+    pub alerted_at: Option<DateTime<Utc>>,
+}
+
+/// A notification endpoint with its latest health check, `None` until the
+/// first check ran.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationResponse {
+    #[serde(flatten)]
+    pub notification: Notification,
+    pub health: Option<NotificationHealth>,
+    /// Until when a deleted endpoint can still be restored
+    pub restorable_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationListQuery {
+    /// Also list deleted endpoints; admins only
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateNotification {
     #[validate(length(min = 1, message = "Notification ID is required"))]
@@ -410,6 +746,12 @@ pub struct CreateNotification {
     pub notification_type: NotificationType,
     #[validate(url(message = "Must be a valid URL"))]
     pub url: String,
+    pub channel: Option<String>,
+    pub payload_version: i64,
+    pub timeout_secs: Option<i64>,
+    /// JSON object of extra request headers, stored encrypted
+    pub headers: Option<String>,
+    pub verify_tls: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -419,6 +761,19 @@ pub struct CreateNotificationRequest {
     pub notification_type: NotificationType,
     #[validate(url(message = "Must be a valid URL"))]
     pub url: String,
+    /// Short channel id (integer or `BLOCKxTXxOUT`) or channel point to
+    /// limit the endpoint to
+    pub channel: Option<String>,
+    /// Webhook body schema version, the latest when omitted
+    pub payload_version: Option<i64>,
+    /// Request timeout in seconds, 10 when omitted
+    #[validate(range(min = 1, max = 60, message = "Timeout must be between 1-60 seconds"))]
+    pub timeout_secs: Option<i64>,
+    /// Extra request headers, such as an Authorization header the endpoint
+    /// expects
+    pub headers: Option<HashMap<String, String>>,
+    /// Set to `false` for internal endpoints with self-signed certificates
+    pub verify_tls: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -427,7 +782,77 @@ pub struct UpdateNotificationRequest {
     pub name: Option<String>,
     #[validate(url(message = "Must be a valid URL"))]
     pub url: Option<String>,
+    /// Channel to limit the endpoint to; an empty string removes the limit
+    pub channel: Option<String>,
+    pub payload_version: Option<i64>,
     pub is_active: Option<bool>,
+    /// Request timeout in seconds; 0 restores the default
+    #[validate(range(min = 0, max = 60, message = "Timeout must be between 0-60 seconds"))]
+    pub timeout_secs: Option<i64>,
+    /// Replaces the extra request headers; an empty object removes them
+    pub headers: Option<HashMap<String, String>>,
+    pub verify_tls: Option<bool>,
+}
+
+/// Request body for previewing (or firing) a notification against an event.
+///
+/// Either `event_id` selects a historical event, or `event_type` builds a
+/// synthetic sample. When neither is given an `InvoiceSettled` sample is used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestNotificationRequest {
+    pub event_id: Option<String>,
+    pub event_type: Option<EventType>,
+    /// Defaults to `true`; set to `false` to actually deliver the rendered payload
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+/// Query parameters for sending an event to notification endpoints again.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedispatchEventQuery {
+    /// Only send it to this endpoint; otherwise to every endpoint that
+    /// would receive it
+    pub notification_id: Option<String>,
+}
+
+/// Query parameters for catching up on a firehose endpoint's events.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FirehoseQuery {
+    /// `next_cursor` of the previous batch; the first batch starts at the
+    /// oldest event
+    pub after: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// A page of the events a firehose endpoint was sent, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirehoseBatch {
+    /// Bodies exactly as they are POSTed to the endpoint
+    pub events: Vec<serde_json::Value>,
+    /// Pass as `after` to fetch the next batch
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationTestResult {
+    pub notification_id: String,
+    pub notification_type: NotificationType,
+    pub dry_run: bool,
+    /// `historical` or `sample`
+    pub event_source: String,
+    pub event: EventResponse,
+    /// The exact body that is (or would be) POSTed to the endpoint
+    pub payload: serde_json::Value,
+    pub would_deliver: bool,
+    pub reasons: Vec<String>,
+    /// Populated only when `dry_run` is `false`
+    pub delivered: Option<bool>,
+    pub delivery_error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -453,6 +878,7 @@ pub struct Event {
 impl From<Event> for EventResponse {
     fn from(event: Event) -> Self {
         Self {
+            data: EventData::parse(&event.event_type, &event.data),
             id: event.id,
             account_id: event.account_id,
             user_id: event.user_id,
@@ -462,7 +888,6 @@ impl From<Event> for EventResponse {
             severity: event.severity,
             title: event.title,
             description: event.description,
-            data: serde_json::from_str(&event.data).unwrap_or(serde_json::Value::Null),
             timestamp: event.timestamp,
             notifications_id: event.notifications_id,
             created_at: event.created_at,
@@ -484,6 +909,25 @@ pub enum EventType {
     PaymentFailed,
     NodeConnected,
     NodeDisconnected,
+    NodeResync,
+    ChannelAcceptorDecision,
+    HtlcIntercepted,
+    SwapUpdated,
+    PaymentLatencyDegraded,
+    ForwardFailed,
+    SubscriptionDegraded,
+    BlockConnected,
+    ChannelConfirmation,
+    PeerAnnouncementChanged,
+    ChannelBalanceChanged,
+    InvoicePaymentMismatch,
+    KeysendReceived,
+    RouteProbeFailed,
+    WatchedNodeChannelOpened,
+    WatchedNodeChannelClosed,
+    WatchedNodeFeeChanged,
+    WatchedNodeAnnouncementChanged,
+    NotificationEndpointUnhealthy,
 }
 
 impl std::fmt::Display for EventType {
@@ -500,6 +944,29 @@ impl std::fmt::Display for EventType {
             EventType::PaymentFailed => write!(f, "payment_failed"),
             EventType::NodeConnected => write!(f, "node_connected"),
             EventType::NodeDisconnected => write!(f, "node_disconnected"),
+            EventType::NodeResync => write!(f, "node_resync"),
+            EventType::ChannelAcceptorDecision => write!(f, "channel_acceptor_decision"),
+            EventType::HtlcIntercepted => write!(f, "htlc_intercepted"),
+            EventType::SwapUpdated => write!(f, "swap_updated"),
+            EventType::PaymentLatencyDegraded => write!(f, "payment_latency_degraded"),
+            EventType::ForwardFailed => write!(f, "forward_failed"),
+            EventType::SubscriptionDegraded => write!(f, "subscription_degraded"),
+            EventType::BlockConnected => write!(f, "block_connected"),
+            EventType::ChannelConfirmation => write!(f, "channel_confirmation"),
+            EventType::PeerAnnouncementChanged => write!(f, "peer_announcement_changed"),
+            EventType::ChannelBalanceChanged => write!(f, "channel_balance_changed"),
+            EventType::InvoicePaymentMismatch => write!(f, "invoice_payment_mismatch"),
+            EventType::KeysendReceived => write!(f, "keysend_received"),
+            EventType::RouteProbeFailed => write!(f, "route_probe_failed"),
+            EventType::WatchedNodeChannelOpened => write!(f, "watched_node_channel_opened"),
+            EventType::WatchedNodeChannelClosed => write!(f, "watched_node_channel_closed"),
+            EventType::WatchedNodeFeeChanged => write!(f, "watched_node_fee_changed"),
+            EventType::WatchedNodeAnnouncementChanged => {
+                write!(f, "watched_node_announcement_changed")
+            }
+            EventType::NotificationEndpointUnhealthy => {
+                write!(f, "notification_endpoint_unhealthy")
+            }
         }
     }
 }
@@ -519,6 +986,25 @@ impl std::str::FromStr for EventType {
             "payment_failed" => Ok(EventType::PaymentFailed),
             "node_connected" => Ok(EventType::NodeConnected),
             "node_disconnected" => Ok(EventType::NodeDisconnected),
+            "node_resync" => Ok(EventType::NodeResync),
+            "channel_acceptor_decision" => Ok(EventType::ChannelAcceptorDecision),
+            "htlc_intercepted" => Ok(EventType::HtlcIntercepted),
+            "swap_updated" => Ok(EventType::SwapUpdated),
+            "payment_latency_degraded" => Ok(EventType::PaymentLatencyDegraded),
+            "forward_failed" => Ok(EventType::ForwardFailed),
+            "subscription_degraded" => Ok(EventType::SubscriptionDegraded),
+            "block_connected" => Ok(EventType::BlockConnected),
+            "channel_confirmation" => Ok(EventType::ChannelConfirmation),
+            "peer_announcement_changed" => Ok(EventType::PeerAnnouncementChanged),
+            "channel_balance_changed" => Ok(EventType::ChannelBalanceChanged),
+            "invoice_payment_mismatch" => Ok(EventType::InvoicePaymentMismatch),
+            "keysend_received" => Ok(EventType::KeysendReceived),
+            "route_probe_failed" => Ok(EventType::RouteProbeFailed),
+            "watched_node_channel_opened" => Ok(EventType::WatchedNodeChannelOpened),
+            "watched_node_channel_closed" => Ok(EventType::WatchedNodeChannelClosed),
+            "watched_node_fee_changed" => Ok(EventType::WatchedNodeFeeChanged),
+            "watched_node_announcement_changed" => Ok(EventType::WatchedNodeAnnouncementChanged),
+            "notification_endpoint_unhealthy" => Ok(EventType::NotificationEndpointUnhealthy),
             _ => Err(format!("Invalid event type: {s}")),
         }
     }
@@ -588,11 +1074,221 @@ pub struct EventResponse {
     pub title: String,
     pub description: String,
     pub notifications_id: Option<String>,
-    pub data: serde_json::Value, // Parsed JSON
+    pub data: EventData,
     pub timestamp: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
 }
 
+/// Data of a `channel_opened` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelOpenedData {
+    pub active: bool,
+    pub channel_id: u64,
+    pub counterparty_node_id: String,
+    pub channel_point: String,
+    pub capacity: i64,
+    pub local_balance: i64,
+    pub remote_balance: i64,
+    #[serde(default)]
+    pub total_satoshis_sent: i64,
+    #[serde(default)]
+    pub total_satoshis_received: i64,
+}
+
+/// Data of a `channel_closed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelClosedData {
+    pub chan_id: u64,
+    pub remote_pubkey: String,
+    pub channel_point: String,
+    #[serde(default)]
+    pub chain_hash: String,
+    pub closing_tx_hash: String,
+    pub capacity: i64,
+    #[serde(default)]
+    pub close_height: u32,
+    pub settled_balance: i64,
+    #[serde(default)]
+    pub time_locked_balance: i64,
+    #[serde(default)]
+    pub close_type: i32,
+    #[serde(default)]
+    pub open_initiator: i32,
+    #[serde(default)]
+    pub close_initiator: i32,
+}
+
+/// Data of the invoice lifecycle events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceEventData {
+    /// Hex preimage, empty until the invoice settles
+    #[serde(default)]
+    pub preimage: String,
+    pub hash: String,
+    pub value_msat: i64,
+    #[serde(default)]
+    pub state: i32,
+    pub memo: String,
+    pub creation_date: i64,
+    #[serde(default)]
+    pub payment_request: String,
+}
+
+/// Data of the payment events, one per payment part.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentEventData {
+    pub payment_hash: String,
+    #[serde(default)]
+    pub part_id: u64,
+    #[serde(default)]
+    pub group_id: u64,
+    pub amount_msat: u64,
+    /// Fee paid on top of the amount, set once the part completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_msat: Option<u64>,
+    pub destination: Option<String>,
+    /// Why the part failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Data of a `forward_failed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardFailedData {
+    pub incoming_chan_id: u64,
+    pub outgoing_chan_id: u64,
+    pub timestamp_ns: u64,
+    pub outgoing_amt_msat: Option<u64>,
+    pub link_failure: bool,
+    pub reason: String,
+}
+
+/// What moved a channel's local balance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceChangeCause {
+    Forward,
+    Payment,
+    Invoice,
+}
+
+/// Data of a `channel_balance_changed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelBalanceChangedData {
+    pub chan_id: u64,
+    /// Net change of the local balance, negative when it shrank
+    pub delta_msat: i64,
+    pub causes: Vec<BalanceChangeCause>,
+    /// Settled HTLCs folded into the change
+    pub htlcs: u64,
+}
+
+/// Data of an `invoice_payment_mismatch` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoicePaymentMismatchData {
+    pub hash: String,
+    pub value_msat: u64,
+    pub amount_paid_msat: u64,
+    pub mismatch: crate::utils::PaymentMismatch,
+}
+
+/// Data of a `keysend_received` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeysendReceivedData {
+    pub payment_hash: String,
+    pub amount_msat: u64,
+    /// Text message the payer attached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The typed part of an event's data column.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum EventPayload {
+    ChannelOpened(ChannelOpenedData),
+    ChannelClosed(ChannelClosedData),
+    Invoice(InvoiceEventData),
+    Payment(PaymentEventData),
+    ForwardFailed(ForwardFailedData),
+    ChannelBalanceChanged(ChannelBalanceChangedData),
+    InvoicePaymentMismatch(InvoicePaymentMismatchData),
+    KeysendReceived(KeysendReceivedData),
+    /// Data of an event type without a payload struct, or stored in a shape
+    /// its payload struct doesn't read
+    Other(serde_json::Map<String, serde_json::Value>),
+}
+
+/// An event's data column, read into the payload of its event type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "serde_json::Map<String, serde_json::Value>")]
+pub struct EventData {
+    #[serde(flatten)]
+    pub payload: EventPayload,
+    /// Keys added to the data after the event was built, like its node label
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl EventData {
+    /// Reads the data column of an event of `event_type`.
+    pub fn parse(event_type: &EventType, data: &str) -> Self {
+        let mut fields: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(data).unwrap_or_default();
+        let payload = match event_type {
+            EventType::ChannelOpened => take_payload(&mut fields, EventPayload::ChannelOpened),
+            EventType::ChannelClosed => take_payload(&mut fields, EventPayload::ChannelClosed),
+            EventType::InvoiceCreated
+            | EventType::InvoiceSettled
+            | EventType::InvoiceCancelled
+            | EventType::InvoiceAccepted => take_payload(&mut fields, EventPayload::Invoice),
+            EventType::PaymentSent | EventType::PaymentReceived | EventType::PaymentFailed => {
+                take_payload(&mut fields, EventPayload::Payment)
+            }
+            EventType::ForwardFailed => take_payload(&mut fields, EventPayload::ForwardFailed),
+            EventType::ChannelBalanceChanged => {
+                take_payload(&mut fields, EventPayload::ChannelBalanceChanged)
+            }
+            EventType::InvoicePaymentMismatch => {
+                take_payload(&mut fields, EventPayload::InvoicePaymentMismatch)
+            }
+            EventType::KeysendReceived => take_payload(&mut fields, EventPayload::KeysendReceived),
+            _ => None,
+        };
+
+        match payload {
+            Some(payload) => Self {
+                payload,
+                extra: fields,
+            },
+            None => Self::from(fields),
+        }
+    }
+}
+
+impl From<serde_json::Map<String, serde_json::Value>> for EventData {
+    fn from(fields: serde_json::Map<String, serde_json::Value>) -> Self {
+        Self {
+            payload: EventPayload::Other(fields),
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+/// Reads `T` out of `fields`, leaving only the keys `T` doesn't have.
+fn take_payload<T: Serialize + serde::de::DeserializeOwned>(
+    fields: &mut serde_json::Map<String, serde_json::Value>,
+    payload: fn(T) -> EventPayload,
+) -> Option<EventPayload> {
+    let typed: T = serde_json::from_value(serde_json::Value::Object(fields.clone())).ok()?;
+    if let Ok(serde_json::Value::Object(typed_fields)) = serde_json::to_value(&typed) {
+        for key in typed_fields.keys() {
+            fields.remove(key);
+        }
+    }
+    Some(payload(typed))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventFilters {
     pub event_types: Option<Vec<EventType>>,
@@ -603,3 +1299,2538 @@ pub struct EventFilters {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventStatsQuery {
+    /// Start of the range (default 30 days before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default now)
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Events of one severity, type and node, as counted by the database.
+#[derive(Debug, Clone, FromRow)]
+pub struct EventCountRow {
+    pub severity: EventSeverity,
+    pub event_type: EventType,
+    pub node_id: String,
+    pub node_alias: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeverityEventCount {
+    pub severity: EventSeverity,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventTypeCount {
+    pub event_type: EventType,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeEventCount {
+    pub node_id: String,
+    pub node_alias: String,
+    pub count: i64,
+}
+
+/// Events of an account in a date range, counted by severity, event type
+/// and node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventStats {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total: i64,
+    /// Every severity, most severe first, including those without events
+    pub by_severity: Vec<SeverityEventCount>,
+    /// Event types that occurred, most frequent first
+    pub by_event_type: Vec<EventTypeCount>,
+    /// Nodes that raised events, most events first
+    pub by_node: Vec<NodeEventCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum JobType {
+    NotificationDelivery,
+    ScheduledTask,
+    NodeResync,
+    NodeExport,
+    AlertEscalation,
+}
+
+impl std::fmt::Display for JobType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobType::NotificationDelivery => write!(f, "notification_delivery"),
+            JobType::ScheduledTask => write!(f, "scheduled_task"),
+            JobType::NodeResync => write!(f, "node_resync"),
+            JobType::NodeExport => write!(f, "node_export"),
+            JobType::AlertEscalation => write!(f, "alert_escalation"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Job {
+    pub id: String,
+    pub job_type: JobType,
+    pub payload: String, // JSON string
+    pub status: JobStatus,
+    pub account_id: Option<String>,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub run_at: DateTime<Utc>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResponse {
+    pub id: String,
+    pub job_type: JobType,
+    pub payload: serde_json::Value, // Parsed JSON
+    pub status: JobStatus,
+    pub account_id: Option<String>,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub run_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A queued node export and where to download it once its job completes.
+#[derive(Debug, Serialize)]
+pub struct NodeExport {
+    pub id: String,
+    pub node_id: String,
+    pub job: JobResponse,
+    /// Serves the bundle once the job has completed, 404 until then
+    pub download_url: String,
+}
+
+impl From<Job> for JobResponse {
+    fn from(job: Job) -> Self {
+        Self {
+            payload: serde_json::from_str(&job.payload).unwrap_or(serde_json::Value::Null),
+            id: job.id,
+            job_type: job.job_type,
+            status: job.status,
+            account_id: job.account_id,
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            run_at: job.run_at,
+            last_error: job.last_error,
+            completed_at: job.completed_at,
+            created_at: job.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobFilters {
+    pub status: Option<JobStatus>,
+    pub job_type: Option<JobType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum TaskType {
+    BalanceSnapshot,
+    PriceBackfill,
+    EventDigest,
+    DatabaseBackup,
+    DataRetention,
+    GraphSync,
+    FeeAutomation,
+    AutoRebalance,
+    PaymentLatency,
+    ForwardHistory,
+    RouteProbe,
+    NotificationHealthCheck,
+    UsageRollup,
+    LightningBilling,
+}
+
+impl std::fmt::Display for TaskType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskType::BalanceSnapshot => write!(f, "balance_snapshot"),
+            TaskType::PriceBackfill => write!(f, "price_backfill"),
+            TaskType::EventDigest => write!(f, "event_digest"),
+            TaskType::DatabaseBackup => write!(f, "database_backup"),
+            TaskType::DataRetention => write!(f, "data_retention"),
+            TaskType::GraphSync => write!(f, "graph_sync"),
+            TaskType::FeeAutomation => write!(f, "fee_automation"),
+            TaskType::AutoRebalance => write!(f, "auto_rebalance"),
+            TaskType::PaymentLatency => write!(f, "payment_latency"),
+            TaskType::ForwardHistory => write!(f, "forward_history"),
+            TaskType::RouteProbe => write!(f, "route_probe"),
+            TaskType::NotificationHealthCheck => write!(f, "notification_health_check"),
+            TaskType::UsageRollup => write!(f, "usage_rollup"),
+            TaskType::LightningBilling => write!(f, "lightning_billing"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum TaskRunStatus {
+    Queued,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScheduledTask {
+    pub id: String,
+    pub task_type: TaskType,
+    pub account_id: Option<String>,
+    pub cron_expression: String,
+    pub is_active: bool,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<TaskRunStatus>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateScheduledTaskRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Cron expression must be 1-100 characters"
+    ))]
+    pub cron_expression: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BalanceSnapshot {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub local_balance_sat: i64,
+    pub remote_balance_sat: i64,
+    pub capacity_sat: i64,
+    pub num_channels: i64,
+    pub num_active_channels: i64,
+    pub btc_price_usd: Option<f64>,
+    pub captured_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SyncedChannel {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub chan_id: String,
+    pub remote_pubkey: Option<String>,
+    pub alias: Option<String>,
+    pub channel_state: String,
+    pub private: bool,
+    pub local_balance_sat: i64,
+    pub remote_balance_sat: i64,
+    pub capacity_sat: i64,
+    pub synced_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SyncedPayment {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub payment_hash: String,
+    pub state: String,
+    pub payment_type: String,
+    pub amount_sat: i64,
+    pub routing_fee_sat: Option<i64>,
+    pub created_at_node: Option<DateTime<Utc>>,
+    pub completed_at_node: Option<DateTime<Utc>>,
+    pub keysend_message: Option<String>,
+    /// Payee of an outgoing payment, when its invoice is known
+    pub destination_pubkey: Option<String>,
+    /// Tool whose history export the payment was imported from; `None` for
+    /// payments synced from the node
+    pub imported_from: Option<String>,
+    pub synced_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SyncedInvoice {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub payment_hash: String,
+    pub state: String,
+    pub memo: String,
+    pub value_msat: i64,
+    pub amt_paid_msat: Option<i64>,
+    pub created_at_node: Option<DateTime<Utc>>,
+    pub settled_at_node: Option<DateTime<Utc>>,
+    pub is_keysend: bool,
+    pub keysend_message: Option<String>,
+    pub synced_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditLog {
+    pub id: String,
+    pub account_id: String,
+    pub actor_user_id: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub details: String, // JSON string
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DataPurgeRequest {
+    pub id: String,
+    pub account_id: String,
+    pub user_id: String,
+    pub requested_by: String,
+    pub confirmation_token: String,
+    pub expires_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataPurgeQuery {
+    /// Token from the first request; when present the purge is carried out
+    pub confirmation_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataPurgeResponse {
+    pub user_id: String,
+    /// `pending_confirmation` or `purged`
+    pub status: String,
+    pub confirmation_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Account-wide display preferences.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccountSettings {
+    pub account_id: String,
+    /// IANA time zone name, e.g. "Europe/Berlin"
+    pub timezone: String,
+    /// ISO 4217 code fiat amounts are converted to
+    pub display_currency: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl AccountSettings {
+    /// Defaults for accounts that never saved settings.
+    pub fn default_for(account_id: &str) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            timezone: "UTC".to_string(),
+            display_currency: "USD".to_string(),
+            updated_at: None,
+        }
+    }
+
+    /// The account's time zone, falling back to UTC if the stored name is unknown.
+    pub fn tz(&self) -> chrono_tz::Tz {
+        self.timezone.parse().unwrap_or(chrono_tz::UTC)
+    }
+}
+
+/// Fields left out keep their current value.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateAccountSettingsRequest {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Timezone must be between 1-64 characters"
+    ))]
+    pub timezone: Option<String>,
+    #[validate(length(equal = 3, message = "Currency must be a 3-letter code"))]
+    pub display_currency: Option<String>,
+}
+
+/// Retention windows in days; `None` keeps data indefinitely.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RetentionSettings {
+    pub account_id: String,
+    pub events_days: Option<i64>,
+    pub deliveries_days: Option<i64>,
+    pub audit_logs_days: Option<i64>,
+    pub balance_history_days: Option<i64>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateRetentionRequest {
+    #[validate(range(min = 1, max = 3650, message = "Retention must be 1-3650 days"))]
+    pub events_days: Option<i64>,
+    #[validate(range(min = 1, max = 3650, message = "Retention must be 1-3650 days"))]
+    pub deliveries_days: Option<i64>,
+    #[validate(range(min = 1, max = 3650, message = "Retention must be 1-3650 days"))]
+    pub audit_logs_days: Option<i64>,
+    #[validate(range(min = 1, max = 3650, message = "Retention must be 1-3650 days"))]
+    pub balance_history_days: Option<i64>,
+}
+
+/// Data that a retention window would remove right now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionEstimate {
+    pub category: String,
+    pub rows: i64,
+    /// Approximate payload size of those rows
+    pub bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPreview {
+    pub estimates: Vec<RetentionEstimate>,
+    pub total_rows: i64,
+    pub total_bytes: i64,
+}
+
+/// An account's usage on one UTC day.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccountUsageDay {
+    pub day: NaiveDate,
+    pub api_requests: i64,
+    pub events_stored: i64,
+    pub notifications_sent: i64,
+    /// Most nodes connected at once during the day
+    pub nodes_connected: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountUsageQuery {
+    /// How many days of usage to return (default 30, at most 365)
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountUsageTotals {
+    pub api_requests: i64,
+    pub events_stored: i64,
+    pub notifications_sent: i64,
+    /// Most nodes connected at once during the window
+    pub peak_nodes_connected: i64,
+}
+
+/// An account's usage over a window of days. API requests are recorded every
+/// few minutes, so today's count may lag slightly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountUsage {
+    pub account_id: String,
+    pub days: i64,
+    pub totals: AccountUsageTotals,
+    /// One entry per day of the window, oldest first
+    pub series: Vec<AccountUsageDay>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT")]
+pub enum Plan {
+    Free,
+    Pro,
+    Business,
+}
+
+impl std::fmt::Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Plan::Free => write!(f, "Free"),
+            Plan::Pro => write!(f, "Pro"),
+            Plan::Business => write!(f, "Business"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT")]
+pub enum SubscriptionStatus {
+    Active,
+    Trialing,
+    /// A renewal payment failed; the plan stays in force while it is retried
+    PastDue,
+    Canceled,
+    /// Checkout finished but the first payment hasn't gone through
+    Incomplete,
+}
+
+/// How an account pays for its plan.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT")]
+pub enum BillingProvider {
+    Stripe,
+    /// Monthly invoices from the operator's treasury node
+    Lightning,
+}
+
+/// What a plan allows; `None` is unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlanLimits {
+    pub max_nodes: Option<i64>,
+    pub max_notification_channels: Option<i64>,
+    /// Longest retention window that may be configured
+    pub max_retention_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDetails {
+    pub plan: Plan,
+    pub limits: PlanLimits,
+    /// Whether the plan can be bought through checkout
+    pub purchasable: bool,
+    /// Monthly price when paid over Lightning; `None` if it can't be
+    pub price_sat: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccountSubscription {
+    pub account_id: String,
+    pub plan: Plan,
+    pub status: SubscriptionStatus,
+    pub provider: BillingProvider,
+    pub stripe_customer_id: Option<String>,
+    pub stripe_subscription_id: Option<String>,
+    pub current_period_end: Option<DateTime<Utc>>,
+    /// When the account was suspended for an unpaid Lightning invoice
+    pub suspended_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingUsage {
+    pub nodes: i64,
+    pub notification_channels: i64,
+}
+
+/// The plan an account is on, what it allows and how much of it is used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingOverview {
+    pub billing_enabled: bool,
+    /// The plan in force: Free unless a subscription is active
+    pub plan: Plan,
+    pub status: Option<SubscriptionStatus>,
+    pub provider: Option<BillingProvider>,
+    pub limits: PlanLimits,
+    pub usage: BillingUsage,
+    pub current_period_end: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCheckoutRequest {
+    pub plan: Plan,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutSession {
+    pub id: String,
+    /// Stripe-hosted checkout page to send the user to
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT")]
+pub enum BillingInvoiceStatus {
+    Open,
+    Paid,
+    /// Lapsed unpaid; a new invoice is issued if the period still needs paying
+    Expired,
+}
+
+/// A BOLT11 invoice from the treasury node for one month of a plan.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BillingInvoice {
+    pub id: String,
+    pub account_id: String,
+    pub plan: Plan,
+    pub amount_sat: i64,
+    pub payment_request: String,
+    pub payment_hash: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub status: BillingInvoiceStatus,
+    pub expires_at: DateTime<Utc>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightningSubscribeRequest {
+    pub plan: Plan,
+}
+
+/// Rules applied to inbound channel opens on an LND node.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChannelAcceptorPolicy {
+    pub account_id: String,
+    pub node_id: String,
+    pub is_enabled: bool,
+    /// Smallest channel accepted, in sats. None accepts any size.
+    pub min_channel_size_sat: Option<i64>,
+    /// JSON array of peer public keys. When non-empty, only these peers may open channels.
+    pub allowed_peers: String,
+    /// JSON array of peer public keys that are always rejected
+    pub blocked_peers: String,
+    /// Reject channels that would be announced to the network
+    pub private_only: bool,
+    pub updated_by: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ChannelAcceptorPolicy {
+    pub fn allowed_peers(&self) -> Vec<String> {
+        serde_json::from_str(&self.allowed_peers).unwrap_or_default()
+    }
+
+    pub fn blocked_peers(&self) -> Vec<String> {
+        serde_json::from_str(&self.blocked_peers).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelAcceptorPolicyResponse {
+    pub node_id: String,
+    pub is_enabled: bool,
+    pub min_channel_size_sat: Option<i64>,
+    pub allowed_peers: Vec<String>,
+    pub blocked_peers: Vec<String>,
+    pub private_only: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<ChannelAcceptorPolicy> for ChannelAcceptorPolicyResponse {
+    fn from(policy: ChannelAcceptorPolicy) -> Self {
+        Self {
+            allowed_peers: policy.allowed_peers(),
+            blocked_peers: policy.blocked_peers(),
+            node_id: policy.node_id,
+            is_enabled: policy.is_enabled,
+            min_channel_size_sat: policy.min_channel_size_sat,
+            private_only: policy.private_only,
+            updated_at: policy.updated_at,
+        }
+    }
+}
+
+/// Replaces a node's channel acceptor rules.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateChannelAcceptorRequest {
+    pub is_enabled: bool,
+    #[validate(range(min = 1, message = "Minimum channel size must be positive"))]
+    pub min_channel_size_sat: Option<i64>,
+    #[serde(default)]
+    pub allowed_peers: Vec<String>,
+    #[serde(default)]
+    pub blocked_peers: Vec<String>,
+    #[serde(default)]
+    pub private_only: bool,
+}
+
+/// A period during which the HTLC interceptor fails every forward.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MaintenanceWindow {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+impl MaintenanceWindow {
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.starts_at <= time && time < self.ends_at
+    }
+}
+
+/// Rules applied to forwards held by the HTLC interceptor on an LND node.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct HtlcInterceptorPolicy {
+    pub account_id: String,
+    pub node_id: String,
+    pub is_enabled: bool,
+    /// JSON array of peer public keys whose forwards are always failed
+    pub blocked_peers: String,
+    /// Largest outgoing HTLC forwarded, in msat. None forwards any size.
+    pub max_htlc_msat: Option<i64>,
+    /// JSON array of `MaintenanceWindow`s
+    pub maintenance_windows: String,
+    pub updated_by: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl HtlcInterceptorPolicy {
+    pub fn blocked_peers(&self) -> Vec<String> {
+        serde_json::from_str(&self.blocked_peers).unwrap_or_default()
+    }
+
+    pub fn maintenance_windows(&self) -> Vec<MaintenanceWindow> {
+        serde_json::from_str(&self.maintenance_windows).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HtlcInterceptorPolicyResponse {
+    pub node_id: String,
+    pub is_enabled: bool,
+    pub blocked_peers: Vec<String>,
+    pub max_htlc_msat: Option<i64>,
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<HtlcInterceptorPolicy> for HtlcInterceptorPolicyResponse {
+    fn from(policy: HtlcInterceptorPolicy) -> Self {
+        Self {
+            blocked_peers: policy.blocked_peers(),
+            maintenance_windows: policy.maintenance_windows(),
+            node_id: policy.node_id,
+            is_enabled: policy.is_enabled,
+            max_htlc_msat: policy.max_htlc_msat,
+            updated_at: policy.updated_at,
+        }
+    }
+}
+
+/// Replaces a node's HTLC interceptor rules.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateHtlcInterceptorRequest {
+    pub is_enabled: bool,
+    #[serde(default)]
+    pub blocked_peers: Vec<String>,
+    #[validate(range(min = 1, message = "Maximum HTLC size must be positive"))]
+    pub max_htlc_msat: Option<i64>,
+    #[serde(default)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+}
+
+/// Size and freshness of a node's graph mirror.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphSummary {
+    pub node_id: String,
+    pub node_count: i64,
+    pub channel_count: i64,
+    pub total_capacity_sat: i64,
+    pub last_full_sync_at: Option<DateTime<Utc>>,
+    pub last_update_at: Option<DateTime<Utc>>,
+}
+
+/// A graph node as stored in the mirror, with its public channels aggregated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNodeDetails {
+    pub pubkey: String,
+    pub alias: String,
+    pub last_update: Option<i64>,
+    pub channel_count: i64,
+    pub capacity_sat: i64,
+    /// Position when nodes are ordered by channel count, 1 being the most connected
+    pub degree_rank: i64,
+}
+
+/// Distribution of advertised fee rates across the graph, in ppm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeePercentiles {
+    pub sample_size: usize,
+    pub p25: Option<i64>,
+    pub p50: Option<i64>,
+    pub p75: Option<i64>,
+    pub p90: Option<i64>,
+    /// Median fee rate across the node's own channels
+    pub own_median_ppm: Option<i64>,
+    /// Share of graph policies charging less than the node's median, 0-100
+    pub own_percentile: Option<f64>,
+}
+
+/// Where one of the node's channels is priced against the network and
+/// against competitors on the same corridor, the other nodes with a channel
+/// to the same peer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelFeePosition {
+    pub chan_id: String,
+    pub peer_pubkey: String,
+    pub peer_alias: Option<String>,
+    pub fee_rate_ppm: i64,
+    /// Share of graph policies charging less, 0-100
+    pub network_percentile: Option<f64>,
+    pub competitor_count: usize,
+    pub competitor_p25: Option<i64>,
+    pub competitor_p50: Option<i64>,
+    pub competitor_p75: Option<i64>,
+    /// Share of competitors charging less, 0-100
+    pub corridor_percentile: Option<f64>,
+    pub suggested_fee_rate_ppm: Option<i64>,
+    /// Why the suggestion was made
+    pub suggestion: Option<String>,
+}
+
+/// The node's fee rates against the network and its corridor competitors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeePositionReport {
+    pub network: FeePercentiles,
+    /// Channels with a suggested adjustment first
+    pub channels: Vec<ChannelFeePosition>,
+}
+
+/// An external node, without credentials, watched through the graph mirror
+/// of one of the account's nodes.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WatchedNode {
+    pub id: String,
+    pub account_id: String,
+    /// The account's node whose graph mirror the watched node is read from
+    pub node_id: String,
+    pub pubkey: String,
+    pub label: Option<String>,
+    /// JSON `WatchedNodeSnapshot` taken at the last check
+    pub snapshot: Option<String>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WatchedNode {
+    pub fn snapshot(&self) -> Option<WatchedNodeSnapshot> {
+        serde_json::from_str(self.snapshot.as_deref()?).ok()
+    }
+}
+
+/// A public channel of a watched node and the policy the node advertises on it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchedChannel {
+    pub chan_id: String,
+    pub peer_pubkey: String,
+    pub capacity_sat: i64,
+    /// Unknown until the node has announced a policy for the channel
+    pub fee_base_msat: Option<i64>,
+    pub fee_rate_ppm: Option<i64>,
+    pub disabled: Option<bool>,
+}
+
+/// What the graph mirror knows about a watched node at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedNodeSnapshot {
+    pub announcement: crate::utils::GraphNode,
+    /// Ordered by channel ID
+    pub channels: Vec<WatchedChannel>,
+}
+
+/// A watched node as currently seen in the graph mirror.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedNodeResponse {
+    pub id: String,
+    pub pubkey: String,
+    pub label: Option<String>,
+    /// None while the node is not in the graph mirror
+    pub alias: Option<String>,
+    pub addresses: Vec<String>,
+    pub channel_count: usize,
+    pub capacity_sat: i64,
+    /// Median fee rate the node charges across its enabled channels
+    pub median_fee_rate_ppm: Option<i64>,
+    pub channels: Vec<WatchedChannel>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Starts watching an external node.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateWatchedNodeRequest {
+    #[validate(length(equal = 66, message = "Public key must be 66 hex characters"))]
+    pub pubkey: String,
+    #[validate(length(max = 100, message = "Label must be at most 100 characters"))]
+    pub label: Option<String>,
+}
+
+/// Where a node sits in the channel graph at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkPositionSnapshot {
+    pub id: String,
+    pub source_node_id: String,
+    pub pubkey: String,
+    /// Alias from the graph mirror, empty if the node has not announced one
+    pub alias: String,
+    /// Normalized betweenness centrality, 0-1
+    pub betweenness: f64,
+    /// Normalized closeness centrality, 0-1
+    pub closeness: f64,
+    /// Position when nodes are ordered by total capacity, 1 being the largest
+    pub capacity_rank: i64,
+    pub channel_count: i64,
+    pub capacity_sat: i64,
+    pub captured_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkPositionPoint {
+    pub captured_at: DateTime<Utc>,
+    pub betweenness: f64,
+    pub closeness: f64,
+    pub capacity_rank: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkPositionQuery {
+    /// How many days of trend points to return (default 30, at most 365)
+    pub days: Option<i64>,
+}
+
+/// The node's latest position, its peers' and the node's trend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkPositionResponse {
+    pub node: Option<NetworkPositionSnapshot>,
+    pub peers: Vec<NetworkPositionSnapshot>,
+    pub history: Vec<NetworkPositionPoint>,
+    /// Third-party metadata per peer pubkey, when peer enrichment is enabled
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub peer_metadata: HashMap<String, PeerMetadata>,
+}
+
+/// A funding or closing transaction followed until it is final.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChannelConfirmation {
+    pub node_id: String,
+    pub txid: String,
+    pub account_id: String,
+    pub user_id: String,
+    pub node_alias: String,
+    /// `opening` or `closing`
+    pub kind: String,
+    pub channel_point: String,
+    pub remote_pubkey: String,
+    /// Height of the block that confirmed the transaction
+    pub confirmation_height: Option<i64>,
+    /// Last confirmation milestone reported as an event
+    pub notified_confirmations: i64,
+}
+
+/// Ways to reach a node's operator, as published on Amboss.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerContact {
+    pub email: Option<String>,
+    pub twitter: Option<String>,
+    pub telegram: Option<String>,
+    pub website: Option<String>,
+}
+
+/// What Amboss and 1ML know about a peer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerMetadata {
+    pub pubkey: String,
+    /// Amboss community tags, e.g. "Plebnet"
+    pub community_tags: Vec<String>,
+    /// 1ML's rank of the node by capacity, 1 being the largest
+    pub rank: Option<i64>,
+    pub contact: PeerContact,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelRecommendationQuery {
+    /// Number of suggestions (default 10, at most 50)
+    pub limit: Option<usize>,
+    /// Days of failed forwards to weigh as unserved demand (default 30, at most 365)
+    pub days: Option<i64>,
+}
+
+/// A suggested new channel partner. Component scores are 0-1; `score` is
+/// their weighted total scaled to 0-100.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelRecommendation {
+    pub pubkey: String,
+    pub alias: String,
+    pub score: f64,
+    pub position_score: f64,
+    pub demand_score: f64,
+    pub uptime_score: f64,
+    pub fee_score: f64,
+    pub channel_count: i64,
+    pub capacity_sat: i64,
+    /// Failed forwards through our peers that share a channel with this node
+    pub failed_forwards: i64,
+    /// Median fee rate the node's peers charge to reach it
+    pub median_inbound_fee_ppm: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum LiquidityOrderState {
+    Created,
+    Completed,
+    Failed,
+}
+
+/// An inbound channel bought from an LSPS1 provider.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LiquidityOrder {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub lsp_url: String,
+    pub lsp_order_id: String,
+    pub order_state: LiquidityOrderState,
+    /// The LSP's state for the lightning payment, e.g. `EXPECT_PAYMENT` or `PAID`
+    pub payment_state: Option<String>,
+    pub lsp_balance_sat: i64,
+    pub client_balance_sat: i64,
+    pub channel_expiry_blocks: i64,
+    pub announce_channel: bool,
+    pub fee_total_sat: i64,
+    pub order_total_sat: i64,
+    /// Invoice to pay for the channel
+    pub bolt11_invoice: String,
+    pub payment_expires_at: Option<DateTime<Utc>>,
+    /// `txid:vout` of the channel once the LSP has funded it
+    pub funding_outpoint: Option<String>,
+    pub channel_funded_at: Option<DateTime<Utc>>,
+    pub channel_expires_at: Option<DateTime<Utc>>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Requests an inbound channel from an LSPS1 provider.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateLiquidityOrderRequest {
+    /// Base URL of the LSP's LSPS1 HTTP API
+    #[validate(url(message = "Must be a valid URL"))]
+    pub lsp_url: String,
+    #[validate(range(min = 1, message = "Inbound liquidity must be positive"))]
+    pub lsp_balance_sat: u64,
+    #[validate(range(min = 1, message = "Channel expiry must be positive"))]
+    pub channel_expiry_blocks: u32,
+    #[serde(default)]
+    pub announce_channel: bool,
+    /// Coupon or API token issued by the LSP
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+#[serde(rename_all = "snake_case")]
+pub enum SwapType {
+    /// On-chain to lightning, adding outbound liquidity
+    Submarine,
+    /// Lightning to on-chain, adding inbound liquidity
+    Reverse,
+}
+
+/// A Boltz swap made to rebalance a node.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Swap {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub swap_type: SwapType,
+    pub boltz_swap_id: String,
+    /// Boltz's status, e.g. `transaction.mempool` or `invoice.settled`
+    pub status: String,
+    pub is_final: bool,
+    pub amount_sat: i64,
+    pub onchain_amount_sat: i64,
+    pub service_fee_sat: i64,
+    pub miner_fee_sat: i64,
+    /// For submarine swaps our node's invoice; for reverse swaps Boltz's
+    pub invoice: String,
+    /// Where the on-chain side is locked up
+    pub lockup_address: String,
+    pub timeout_block_height: i64,
+    pub channel_id: Option<String>,
+    #[serde(skip_serializing)]
+    pub rescue_data: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapQuoteQuery {
+    pub swap_type: SwapType,
+    pub amount_sat: u64,
+}
+
+/// Boltz's current price for a swap of a given size.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SwapQuote {
+    pub swap_type: SwapType,
+    pub amount_sat: u64,
+    /// What gets locked on-chain (submarine) or received on-chain (reverse)
+    pub onchain_amount_sat: u64,
+    pub service_fee_sat: u64,
+    pub miner_fee_sat: u64,
+    pub total_fee_sat: u64,
+    pub min_amount_sat: u64,
+    pub max_amount_sat: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateSwapRequest {
+    pub swap_type: SwapType,
+    #[validate(range(min = 1, message = "Amount must be positive"))]
+    pub amount_sat: u64,
+    /// Short channel ID of the channel to pay out of (reverse swaps only)
+    pub channel_id: Option<String>,
+    /// Routing fee budget for paying Boltz's invoice (reverse swaps only)
+    pub max_routing_fee_sat: Option<u64>,
+}
+
+/// One fee automation rule. A rule matches a channel whose local balance, as a
+/// percentage of capacity, is at least `min_local_pct` and below
+/// `max_local_pct`; a missing bound is open.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct FeeRule {
+    #[validate(range(
+        min = 0.0,
+        max = 100.0,
+        message = "Percentages must be between 0 and 100"
+    ))]
+    pub min_local_pct: Option<f64>,
+    #[validate(range(
+        min = 0.0,
+        max = 100.0,
+        message = "Percentages must be between 0 and 100"
+    ))]
+    pub max_local_pct: Option<f64>,
+    #[validate(range(max = 100_000, message = "Fee rate must be at most 100000 ppm"))]
+    pub fee_rate_ppm: u32,
+    /// Base fee to set alongside the rate. None keeps the channel's current one.
+    pub base_fee_msat: Option<u64>,
+}
+
+/// A node's fee automation rules, applied in order on every run.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeeAutomationPolicy {
+    pub account_id: String,
+    pub node_id: String,
+    pub is_enabled: bool,
+    /// Record the changes a run would make without applying them
+    pub dry_run: bool,
+    /// JSON array of `FeeRule`s
+    pub rules: String,
+    pub updated_by: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl FeeAutomationPolicy {
+    pub fn rules(&self) -> Vec<FeeRule> {
+        serde_json::from_str(&self.rules).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeAutomationPolicyResponse {
+    pub node_id: String,
+    pub is_enabled: bool,
+    pub dry_run: bool,
+    pub rules: Vec<FeeRule>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<FeeAutomationPolicy> for FeeAutomationPolicyResponse {
+    fn from(policy: FeeAutomationPolicy) -> Self {
+        Self {
+            rules: policy.rules(),
+            node_id: policy.node_id,
+            is_enabled: policy.is_enabled,
+            dry_run: policy.dry_run,
+            updated_at: policy.updated_at,
+        }
+    }
+}
+
+/// Replaces a node's fee automation rules.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateFeeAutomationRequest {
+    pub is_enabled: bool,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[validate(nested)]
+    pub rules: Vec<FeeRule>,
+}
+
+/// A fee change made, or in dry-run mode proposed, by fee automation.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeePolicyChange {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub channel_id: String,
+    /// Position of the matching rule in the policy
+    pub rule_index: i64,
+    pub local_balance_pct: f64,
+    pub old_base_fee_msat: Option<i64>,
+    pub old_fee_rate_ppm: Option<i64>,
+    pub new_base_fee_msat: i64,
+    pub new_fee_rate_ppm: i64,
+    pub dry_run: bool,
+    /// Why the node refused the update, if it did
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeePolicyChangeQuery {
+    pub limit: Option<i64>,
+}
+
+/// Share of a channel's capacity auto-rebalancing keeps on our side.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct RebalanceTarget {
+    /// Short channel ID
+    pub channel_id: String,
+    #[validate(range(
+        min = 0.0,
+        max = 100.0,
+        message = "Percentages must be between 0 and 100"
+    ))]
+    pub target_local_pct: f64,
+}
+
+/// A node's auto-rebalancing settings.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RebalancePolicy {
+    pub account_id: String,
+    pub node_id: String,
+    pub is_enabled: bool,
+    /// JSON array of `RebalanceTarget`s
+    pub targets: String,
+    /// How far below its target a channel may drift before it is refilled
+    pub tolerance_pct: f64,
+    /// Most a single rebalance may pay in fees, relative to its amount
+    pub max_fee_ppm: i64,
+    /// Routing fees automatic rebalances may spend per calendar month (UTC)
+    pub monthly_budget_sat: i64,
+    /// JSON array of `MaintenanceWindow`s during which no automatic runs happen
+    pub blackout_windows: String,
+    pub updated_by: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl RebalancePolicy {
+    pub fn targets(&self) -> Vec<RebalanceTarget> {
+        serde_json::from_str(&self.targets).unwrap_or_default()
+    }
+
+    pub fn blackout_windows(&self) -> Vec<MaintenanceWindow> {
+        serde_json::from_str(&self.blackout_windows).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalancePolicyResponse {
+    pub node_id: String,
+    pub is_enabled: bool,
+    pub targets: Vec<RebalanceTarget>,
+    pub tolerance_pct: f64,
+    pub max_fee_ppm: i64,
+    pub monthly_budget_sat: i64,
+    pub blackout_windows: Vec<MaintenanceWindow>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<RebalancePolicy> for RebalancePolicyResponse {
+    fn from(policy: RebalancePolicy) -> Self {
+        Self {
+            targets: policy.targets(),
+            blackout_windows: policy.blackout_windows(),
+            node_id: policy.node_id,
+            is_enabled: policy.is_enabled,
+            tolerance_pct: policy.tolerance_pct,
+            max_fee_ppm: policy.max_fee_ppm,
+            monthly_budget_sat: policy.monthly_budget_sat,
+            updated_at: policy.updated_at,
+        }
+    }
+}
+
+/// Replaces a node's auto-rebalancing settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateRebalancePolicyRequest {
+    pub is_enabled: bool,
+    #[validate(nested)]
+    pub targets: Vec<RebalanceTarget>,
+    #[validate(range(min = 1.0, max = 50.0, message = "Tolerance must be between 1 and 50"))]
+    pub tolerance_pct: f64,
+    #[validate(range(
+        min = 1,
+        max = 50_000,
+        message = "Maximum fee must be between 1 and 50000 ppm"
+    ))]
+    pub max_fee_ppm: i64,
+    #[validate(range(min = 0, message = "Budget cannot be negative"))]
+    pub monthly_budget_sat: i64,
+    #[serde(default)]
+    pub blackout_windows: Vec<MaintenanceWindow>,
+}
+
+/// Moves liquidity from one of the node's channels to another.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateRebalanceRequest {
+    /// Short channel ID of the channel to send out of
+    pub outgoing_channel_id: String,
+    /// Short channel ID of the channel the payment returns through
+    pub incoming_channel_id: String,
+    #[validate(range(min = 1, message = "Amount must be positive"))]
+    pub amount_sat: u64,
+    #[validate(range(
+        min = 1,
+        max = 50_000,
+        message = "Maximum fee must be between 1 and 50000 ppm"
+    ))]
+    pub max_fee_ppm: u64,
+}
+
+/// Channel open to estimate the cost of.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct OpenChannelEstimateRequest {
+    #[validate(range(min = 20_000, message = "Channels must be at least 20000 sats"))]
+    pub local_funding_sat: i64,
+    /// Amount handed to the peer on open
+    #[serde(default)]
+    #[validate(range(min = 0, message = "Push amount cannot be negative"))]
+    pub push_sat: i64,
+    /// Wallet inputs the funding transaction spends (default 1)
+    #[validate(range(min = 1, max = 100, message = "Inputs must be between 1 and 100"))]
+    pub inputs: Option<i64>,
+    /// Anchor channels, the default, need a wallet reserve for fee bumping
+    #[serde(default = "default_anchors")]
+    pub anchors: bool,
+    /// A fee rate of the caller's own to estimate next to the recommended ones
+    #[validate(range(
+        min = 1.0,
+        max = 1_000.0,
+        message = "Fee rate must be between 1 and 1000 sat/vB"
+    ))]
+    pub sat_per_vbyte: Option<f64>,
+}
+
+fn default_anchors() -> bool {
+    true
+}
+
+/// Cost of funding a channel at one fee rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenChannelFeeTier {
+    /// `fastest`, `half_hour`, `hour`, `economy` or `custom`
+    pub label: String,
+    pub sat_per_vbyte: f64,
+    /// Blocks the rate is expected to confirm within; unknown for custom rates
+    pub target_blocks: Option<u32>,
+    pub estimated_minutes: Option<u32>,
+    pub funding_fee_sat: i64,
+    /// Funding amount, fee and anchor reserve together
+    pub total_wallet_spend_sat: i64,
+}
+
+/// Expected cost and reserves of a channel open, before it is made.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenChannelEstimate {
+    pub capacity_sat: i64,
+    pub push_sat: i64,
+    /// Estimated size of the funding transaction
+    pub funding_vbytes: i64,
+    pub tiers: Vec<OpenChannelFeeTier>,
+    /// Balance each side must keep in the channel, per the usual 1% policy
+    pub channel_reserve_sat: i64,
+    /// Value of the two anchor outputs, paid by the opener
+    pub anchor_outputs_sat: i64,
+    /// Fee of the initial commitment transaction, paid by the opener
+    pub commitment_fee_sat: i64,
+    /// On-chain balance kept back in the wallet to fee-bump anchor closes
+    pub wallet_anchor_reserve_sat: i64,
+    /// What we could send over the channel right after it opens
+    pub spendable_local_sat: i64,
+    /// Set when the recommended rates could not be fetched
+    pub fee_rates_error: Option<String>,
+}
+
+/// One hop of a rebalance's route.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RebalanceHop {
+    pub pubkey: String,
+    pub channel_id: String,
+    pub amount_to_forward_sat: u64,
+    pub fee_sat: u64,
+}
+
+/// A manual or automatic rebalance attempt.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RebalanceAttempt {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub outgoing_channel_id: String,
+    pub incoming_channel_id: String,
+    pub amount_sat: i64,
+    pub fee_msat: i64,
+    /// JSON array of `RebalanceHop`s
+    pub route: String,
+    pub succeeded: bool,
+    pub error: Option<String>,
+    pub is_automatic: bool,
+    /// The user who started a manual rebalance
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RebalanceAttempt {
+    pub fn route(&self) -> Vec<RebalanceHop> {
+        serde_json::from_str(&self.route).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceAttemptResponse {
+    pub id: String,
+    pub outgoing_channel_id: String,
+    pub incoming_channel_id: String,
+    pub amount_sat: i64,
+    pub fee_msat: i64,
+    pub route: Vec<RebalanceHop>,
+    pub succeeded: bool,
+    pub error: Option<String>,
+    pub is_automatic: bool,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RebalanceAttempt> for RebalanceAttemptResponse {
+    fn from(attempt: RebalanceAttempt) -> Self {
+        Self {
+            route: attempt.route(),
+            id: attempt.id,
+            outgoing_channel_id: attempt.outgoing_channel_id,
+            incoming_channel_id: attempt.incoming_channel_id,
+            amount_sat: attempt.amount_sat,
+            fee_msat: attempt.fee_msat,
+            succeeded: attempt.succeeded,
+            error: attempt.error,
+            is_automatic: attempt.is_automatic,
+            created_by: attempt.created_by,
+            created_at: attempt.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceAttemptQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleChannelQuery {
+    /// Channels without activity in this many days are reported (default 30, at most 365)
+    pub days: Option<i64>,
+    /// Yearly return the idle local balance could earn elsewhere, in percent (default 5)
+    pub annual_rate_pct: Option<f64>,
+}
+
+/// A channel with no forwards or payments during the report's window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleChannel {
+    pub channel_id: String,
+    /// Unknown for private channels, which the graph mirror doesn't hold
+    pub peer_pubkey: Option<String>,
+    pub peer_alias: Option<String>,
+    pub capacity_sat: u64,
+    pub local_balance_sat: u64,
+    pub active: bool,
+    /// Now if the channel is active, else the peer's latest gossip
+    pub peer_last_seen: Option<DateTime<Utc>>,
+    /// Last forward or payment through the channel, if any happened in the
+    /// forwarding history the node returned
+    pub last_activity_at: Option<DateTime<Utc>>,
+    /// Days since the last activity; at least the report's window
+    pub idle_days: i64,
+    /// Return the local balance forwent while idle
+    pub opportunity_cost_sat: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleChannelReport {
+    pub days: i64,
+    pub annual_rate_pct: f64,
+    pub total_local_balance_sat: u64,
+    pub total_opportunity_cost_sat: u64,
+    pub channels: Vec<StaleChannel>,
+}
+
+/// Time-to-settle of one outgoing payment.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PaymentLatencySample {
+    pub account_id: String,
+    pub node_id: String,
+    pub payment_hash: String,
+    pub destination: Option<String>,
+    pub first_hop_channel_id: Option<String>,
+    pub settle_ms: i64,
+    pub htlc_ms: Option<i64>,
+    pub settled_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentLatencyQuery {
+    /// How many days of payments to include (default 7, at most 90)
+    pub days: Option<i64>,
+}
+
+/// Latency percentiles of the payments sharing a destination or first hop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyStats {
+    /// Destination pubkey or first-hop channel ID
+    pub key: String,
+    pub alias: Option<String>,
+    pub sample_count: usize,
+    pub p50_ms: i64,
+    pub p95_ms: i64,
+    /// Round trip of the settling HTLC alone; LND nodes only
+    pub htlc_p50_ms: Option<i64>,
+    pub htlc_p95_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentLatencyReport {
+    pub days: i64,
+    pub sample_count: usize,
+    pub p50_ms: Option<i64>,
+    pub p95_ms: Option<i64>,
+    pub by_destination: Vec<LatencyStats>,
+    pub by_first_hop: Vec<LatencyStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentRouteQuery {
+    /// How many days of payments to analyze (default 30, at most 365)
+    pub days: Option<i64>,
+    /// Fewest settled payments a destination needs to be analyzed (default 3)
+    pub min_payments: Option<i64>,
+}
+
+/// An outgoing synced payment with the channel it left through, when the
+/// latency tracker recorded it.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PaymentRouteSample {
+    pub destination: String,
+    pub state: String,
+    pub amount_sat: i64,
+    pub routing_fee_sat: Option<i64>,
+    pub first_hop_channel_id: Option<String>,
+}
+
+/// Fees of the settled payments to a destination that left through one of
+/// our channels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirstHopFees {
+    pub channel_id: String,
+    pub peer_pubkey: Option<String>,
+    pub peer_alias: Option<String>,
+    pub payments: i64,
+    pub p50_fee_ppm: i64,
+}
+
+/// What the node paid to reach a frequent destination, with a suggested fee
+/// limit and first hop for future payments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentRouteAdvice {
+    pub destination: String,
+    pub alias: Option<String>,
+    pub settled: i64,
+    pub failed: i64,
+    pub amount_sat: i64,
+    pub fees_sat: i64,
+    /// Routing fee percentiles of settled payments, in parts per million
+    pub p10_fee_ppm: i64,
+    pub p50_fee_ppm: i64,
+    pub p90_fee_ppm: i64,
+    /// A fee limit the large majority of past successful payments fit under
+    pub suggested_fee_limit_ppm: i64,
+    /// The first hop whose payments paid the lowest typical fee
+    pub preferred_first_hop: Option<FirstHopFees>,
+    /// First hops of settled payments, cheapest first; LND nodes only
+    pub first_hops: Vec<FirstHopFees>,
+    /// Whether the typical fee is well above the cheapest routes that
+    /// succeeded to the same destination
+    pub overpaying: bool,
+    /// Fees paid above the cheapest successful rate
+    pub overpaid_sat: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentRouteReport {
+    pub days: i64,
+    /// Destinations we overpay first, then by fees paid
+    pub destinations: Vec<PaymentRouteAdvice>,
+    /// When the payments were last synced from the node; `None` if never
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// A route the prober checks: a destination, optionally reached through a
+/// specific channel of ours.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProbeTarget {
+    /// Pubkey of the node probed
+    pub destination: String,
+    /// Short channel ID the probe must leave through
+    #[serde(default)]
+    pub outgoing_channel_id: Option<String>,
+}
+
+/// A node's route probing settings.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProbePolicy {
+    pub account_id: String,
+    pub node_id: String,
+    pub is_enabled: bool,
+    /// JSON array of `ProbeTarget`s
+    pub targets: String,
+    /// Amount each probe tries to carry
+    pub amount_sat: i64,
+    /// Routing fees a probe's route may charge; never actually paid
+    pub max_fee_sat: i64,
+    pub updated_by: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ProbePolicy {
+    pub fn targets(&self) -> Vec<ProbeTarget> {
+        serde_json::from_str(&self.targets).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbePolicyResponse {
+    pub node_id: String,
+    pub is_enabled: bool,
+    pub targets: Vec<ProbeTarget>,
+    pub amount_sat: i64,
+    pub max_fee_sat: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<ProbePolicy> for ProbePolicyResponse {
+    fn from(policy: ProbePolicy) -> Self {
+        Self {
+            targets: policy.targets(),
+            node_id: policy.node_id,
+            is_enabled: policy.is_enabled,
+            amount_sat: policy.amount_sat,
+            max_fee_sat: policy.max_fee_sat,
+            updated_at: policy.updated_at,
+        }
+    }
+}
+
+/// Replaces a node's route probing settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateProbePolicyRequest {
+    pub is_enabled: bool,
+    #[validate(length(max = 20, message = "At most 20 routes can be probed"))]
+    pub targets: Vec<ProbeTarget>,
+    #[validate(range(
+        min = 1,
+        max = 100_000,
+        message = "Probe amount must be between 1 and 100000 sats"
+    ))]
+    pub amount_sat: i64,
+    #[validate(range(
+        min = 0,
+        max = 1_000,
+        message = "Maximum fee must be between 0 and 1000 sats"
+    ))]
+    pub max_fee_sat: i64,
+}
+
+/// Outcome of one probe along a target route.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProbeResult {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub destination: String,
+    pub outgoing_channel_id: Option<String>,
+    pub reached: bool,
+    pub latency_ms: i64,
+    pub failure_reason: Option<String>,
+    pub probed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeReportQuery {
+    /// How many days of probes to include (default 7, at most 30)
+    pub days: Option<i64>,
+}
+
+/// How reliably one target route carried probes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteHealth {
+    pub destination: String,
+    pub alias: Option<String>,
+    pub outgoing_channel_id: Option<String>,
+    pub probes: usize,
+    pub reached: usize,
+    pub success_rate_pct: f64,
+    /// Median time until a probe that reached resolved
+    pub p50_latency_ms: Option<i64>,
+    pub last_reached: bool,
+    pub last_failure_reason: Option<String>,
+    pub last_probed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeReport {
+    pub days: i64,
+    pub routes: Vec<RouteHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureHeatmapQuery {
+    /// How many days of failed forwards to include (default 30, at most 90)
+    pub days: Option<i64>,
+}
+
+/// Failed forwards through one peer's channels, bucketed by UTC hour of day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerFailureHeatmap {
+    /// Unknown when none of the channels is in the graph mirror, in which
+    /// case each channel gets its own row
+    pub peer_pubkey: Option<String>,
+    pub peer_alias: Option<String>,
+    pub channel_ids: Vec<String>,
+    pub total_failures: i64,
+    /// 24 counts, index 0 being 00:00-00:59 UTC
+    pub hourly: Vec<i64>,
+    pub peak_hour: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureHeatmap {
+    pub days: i64,
+    pub total_failures: i64,
+    pub peers: Vec<PeerFailureHeatmap>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardFailureQuery {
+    /// Start of the range (default 7 days before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default now)
+    pub to: Option<DateTime<Utc>>,
+    /// Only failures into or out of this channel
+    pub channel_id: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Why a forward failed, as far as the node can tell.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForwardFailureCause {
+    /// Our outgoing channel couldn't carry the amount: a case for rebalancing
+    LocalLiquidity,
+    /// Our outgoing link refused the HTLC for another reason, like its fee,
+    /// HTLC limits or a disabled channel
+    LocalPolicy,
+    /// A node past ours failed the HTLC
+    Downstream,
+}
+
+/// One failed forward of the node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardFailure {
+    pub event_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub incoming_channel_id: String,
+    pub incoming_peer_pubkey: Option<String>,
+    pub outgoing_channel_id: String,
+    pub outgoing_peer_pubkey: Option<String>,
+    /// Only reported for failures on our outgoing link
+    pub amount_sat: Option<u64>,
+    pub reason: String,
+    pub cause: ForwardFailureCause,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForwardFailureCauseCounts {
+    pub local_liquidity: i64,
+    pub local_policy: i64,
+    pub downstream: i64,
+}
+
+/// A page of the node's failed forwards in a date range, with counts by
+/// cause over the whole range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardFailureList {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total: i64,
+    pub by_cause: ForwardFailureCauseCounts,
+    pub failures: Vec<ForwardFailure>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfferStatsQuery {
+    /// How many days of daily points to return (default 30, at most 365)
+    pub days: Option<i64>,
+}
+
+/// Payments received for an offer on one UTC day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfferStatsPoint {
+    pub date: NaiveDate,
+    pub payments: u64,
+    pub volume_sat: u64,
+}
+
+/// How a BOLT12 offer performs. Totals cover the offer's whole history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfferStats {
+    pub offer_id: String,
+    pub days: i64,
+    /// Invoice requests received, each answered with an invoice
+    pub invoice_requests: u64,
+    pub payments: u64,
+    pub volume_sat: u64,
+    /// Payers reuse no key across requests, so this counts distinct payer
+    /// notes, plus every payment without one
+    pub unique_payers: u64,
+    /// One point per day of the window, oldest first
+    pub series: Vec<OfferStatsPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceStatsQuery {
+    /// Start of the range (default 30 days before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default now)
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Invoices in one state and the sum of their values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvoiceStateTotals {
+    pub count: i64,
+    pub value_sat: i64,
+}
+
+/// Invoices of a node created in a date range, by state, as of its last sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceStats {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub open: InvoiceStateTotals,
+    pub settled: InvoiceStateTotals,
+    pub expired: InvoiceStateTotals,
+    pub canceled: InvoiceStateTotals,
+    pub total: InvoiceStateTotals,
+    /// Settled share of the invoices that were settled, expired or canceled;
+    /// `None` when none were
+    pub settlement_rate: Option<f64>,
+    /// When the invoices were last synced from the node; `None` if never
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Length of the periods invoice latency is broken down by.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LatencyPeriod {
+    #[default]
+    Day,
+    /// ISO weeks, starting on Monday
+    Week,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceLatencyQuery {
+    /// Start of the range (default 30 days before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default now)
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub period: LatencyPeriod,
+}
+
+/// Creation and settlement time of a synced invoice.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InvoiceTiming {
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+    pub settled_at: Option<DateTime<Utc>>,
+}
+
+/// How quickly a set of invoices was paid, and how many were left to expire.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvoiceLatencyStats {
+    pub invoices: i64,
+    pub settled: i64,
+    pub expired: i64,
+    /// Seconds from creation to settlement
+    pub p50_secs: Option<i64>,
+    pub p90_secs: Option<i64>,
+    /// Expired share of the invoices that were settled, expired or canceled;
+    /// `None` when none were
+    pub expiry_rate: Option<f64>,
+}
+
+/// Invoice latency of the invoices created in one period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceLatencyPoint {
+    pub period_start: NaiveDate,
+    #[serde(flatten)]
+    pub stats: InvoiceLatencyStats,
+}
+
+/// Time to settlement of a node's invoices created in a date range, overall
+/// and per period, as of its last invoice sync. Keysend payments have no
+/// invoice to wait on and are left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceLatencyReport {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub period: LatencyPeriod,
+    #[serde(flatten)]
+    pub overall: InvoiceLatencyStats,
+    /// One point per period of the range, oldest first
+    pub periods: Vec<InvoiceLatencyPoint>,
+    /// When the invoices were last synced from the node; `None` if never
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentStatsQuery {
+    /// How many days of payments to include (default 30, at most 365)
+    pub days: Option<i64>,
+}
+
+/// Payments in one direction: all attempts, and the settled volume.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaymentDirectionStats {
+    pub count: i64,
+    pub settled: i64,
+    pub failed: i64,
+    pub volume_sat: i64,
+}
+
+/// Payments of a node over a window, as of its last sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentStats {
+    pub days: i64,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub outgoing: PaymentDirectionStats,
+    /// Invoice and keysend receipts
+    pub incoming: PaymentDirectionStats,
+    /// Settled volume in both directions
+    pub total_volume_sat: i64,
+    /// Routing fees of settled outgoing payments
+    pub total_fees_sat: i64,
+    /// Fees per million sats sent; `None` when nothing was sent
+    pub average_fee_ppm: Option<f64>,
+    /// Failed share of the outgoing payments that settled or failed
+    pub failure_rate: Option<f64>,
+    /// The account's display currency
+    pub currency: String,
+    /// `None` when the price feed is unreachable, along with the fiat amounts
+    pub btc_price: Option<f64>,
+    pub total_volume_fiat: Option<f64>,
+    pub total_fees_fiat: Option<f64>,
+    /// When the payments were last synced from the node; `None` if never
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Channels whose capacity falls in `[min_sat, max_sat)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityBucket {
+    pub min_sat: i64,
+    /// `None` for the open-ended largest bucket
+    pub max_sat: Option<i64>,
+    pub count: i64,
+}
+
+/// Capacity and balance distribution of a node's open channels, as of its
+/// last sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelStats {
+    pub channels: i64,
+    pub public: i64,
+    pub private: i64,
+    pub total_capacity_sat: i64,
+    pub median_capacity_sat: i64,
+    pub mean_capacity_sat: i64,
+    pub local_balance_sat: i64,
+    pub remote_balance_sat: i64,
+    /// Local share of the local and remote balance; `None` without channels
+    pub local_ratio: Option<f64>,
+    pub histogram: Vec<CapacityBucket>,
+    /// When the channels were last synced from the node; `None` if never
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// One of the account's channels with a given peer.
+#[derive(Debug, Serialize)]
+pub struct PeerChannel {
+    /// Our node the channel belongs to
+    pub node_id: String,
+    pub node_alias: String,
+    #[serde(flatten)]
+    pub channel: crate::utils::ChannelSummary,
+}
+
+/// The channels every node of the account has with one peer.
+#[derive(Debug, Serialize)]
+pub struct PeerChannels {
+    pub pubkey: String,
+    pub channels: Vec<PeerChannel>,
+    /// Nodes whose channels couldn't be listed
+    pub unreachable_nodes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentQuery {
+    /// How many days of events to group (default 7, at most 30)
+    pub days: Option<i64>,
+    /// Longest quiet gap, in minutes, between two events of one incident
+    /// (default 30, at most 1440)
+    pub gap_minutes: Option<i64>,
+}
+
+/// Related events of one node, grouped into a timeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    /// ID of the incident's first event
+    pub id: String,
+    pub node_id: String,
+    pub node_alias: String,
+    /// Highest severity among the incident's events
+    pub severity: EventSeverity,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// The last event was informational, like a reconnect or the close that
+    /// settled a dispute, or nothing related happened for a full gap since
+    pub resolved: bool,
+    /// Peers and channels the events have in common
+    pub peers: Vec<String>,
+    pub channels: Vec<String>,
+    /// The incident's events, oldest first
+    pub events: Vec<EventResponse>,
+}
+
+/// One link of an escalation chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EscalationStep {
+    /// Endpoint notified at this step
+    pub notification_id: String,
+    /// Minutes without acknowledgement, since the event or the previous
+    /// step, before this step is notified
+    pub delay_minutes: i64,
+}
+
+/// A chain of endpoints critical events are re-sent to until acknowledged.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EscalationPolicy {
+    pub id: String,
+    pub account_id: String,
+    pub name: String,
+    /// Only events of this node are escalated; `None` for every node
+    pub node_id: Option<String>,
+    pub steps: String, // JSON array of EscalationStep
+    pub is_active: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EscalationPolicy {
+    pub fn steps(&self) -> Vec<EscalationStep> {
+        serde_json::from_str(&self.steps).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationPolicyResponse {
+    pub id: String,
+    pub name: String,
+    pub node_id: Option<String>,
+    pub steps: Vec<EscalationStep>,
+    pub is_active: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<EscalationPolicy> for EscalationPolicyResponse {
+    fn from(policy: EscalationPolicy) -> Self {
+        Self {
+            steps: policy.steps(),
+            id: policy.id,
+            name: policy.name,
+            node_id: policy.node_id,
+            is_active: policy.is_active,
+            created_by: policy.created_by,
+            created_at: policy.created_at,
+            updated_at: policy.updated_at,
+        }
+    }
+}
+
+/// Creates or replaces an escalation policy.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct EscalationPolicyRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+    pub node_id: Option<String>,
+    #[validate(length(min = 1, max = 5, message = "A policy needs 1-5 steps"))]
+    pub steps: Vec<EscalationStep>,
+    /// Defaults to `true`
+    #[serde(default = "default_policy_active")]
+    pub is_active: bool,
+}
+
+fn default_policy_active() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum EscalationStatus {
+    /// Waiting for the next step or an acknowledgement
+    Pending,
+    Acknowledged,
+    /// Every step was notified without an acknowledgement
+    Exhausted,
+    /// The policy was deleted or disabled, or the event purged, meanwhile
+    Cancelled,
+}
+
+/// A critical event going through a policy's escalation chain.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Escalation {
+    pub id: String,
+    pub account_id: String,
+    pub policy_id: String,
+    pub event_id: String,
+    pub node_id: String,
+    pub status: EscalationStatus,
+    /// Steps notified so far
+    pub steps_sent: i64,
+    /// When the next step is due, while pending
+    pub next_step_at: Option<DateTime<Utc>>,
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EscalationQuery {
+    /// Only list escalations in this state
+    pub status: Option<EscalationStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// A period during which a node is under planned maintenance.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MaintenanceWindow {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Puts a node under maintenance from now on.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct StartMaintenanceRequest {
+    #[validate(range(
+        min = 1,
+        max = 10080,
+        message = "Duration must be between 1 minute and 7 days"
+    ))]
+    pub duration_minutes: i64,
+    #[validate(length(max = 500, message = "Reason must be at most 500 characters"))]
+    pub reason: Option<String>,
+}
+
+/// The name and notes operators gave one of their nodes.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NodeLabel {
+    pub account_id: String,
+    pub node_id: String,
+    pub display_name: Option<String>,
+    /// Free-form, e.g. `prod` or `staging`
+    pub environment: Option<String>,
+    pub notes: Option<String>,
+    pub updated_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Changes a node's label. Omitted fields are left as they are; an empty
+/// string clears one.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateNodeLabelRequest {
+    #[validate(length(max = 100, message = "Display name must be at most 100 characters"))]
+    pub display_name: Option<String>,
+    #[validate(length(max = 50, message = "Environment must be at most 50 characters"))]
+    pub environment: Option<String>,
+    #[validate(length(max = 2000, message = "Notes must be at most 2000 characters"))]
+    pub notes: Option<String>,
+}
+
+/// What a channel note is kept on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "TEXT")]
+pub enum NoteTarget {
+    /// A channel, by short channel id
+    Channel,
+    /// A peer, by public key
+    Peer,
+}
+
+/// A labelled note kept on one of a node's channels or peers.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChannelNote {
+    pub id: String,
+    pub account_id: String,
+    pub node_id: String,
+    pub target_type: NoteTarget,
+    pub target_id: String,
+    pub label: String,
+    pub note: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateChannelNoteRequest {
+    pub target_type: NoteTarget,
+    /// Short channel id of the channel or public key of the peer
+    #[validate(length(min = 1, message = "Target ID is required"))]
+    pub target_id: String,
+    #[validate(length(min = 1, max = 100, message = "Label must be 1-100 characters"))]
+    pub label: String,
+    #[validate(length(max = 2000, message = "Note must be at most 2000 characters"))]
+    pub note: Option<String>,
+}
+
+/// Changes a channel note. An empty `note` removes the text.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateChannelNoteRequest {
+    #[validate(length(min = 1, max = 100, message = "Label must be 1-100 characters"))]
+    pub label: Option<String>,
+    #[validate(length(max = 2000, message = "Note must be at most 2000 characters"))]
+    pub note: Option<String>,
+}
+
+/// Narrows the channel notes listed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChannelNoteQuery {
+    pub target_type: Option<NoteTarget>,
+    pub target_id: Option<String>,
+    pub label: Option<String>,
+}
+
+/// A saved filter configuration as stored in the saved_views table.
+#[derive(Debug, Clone, FromRow)]
+pub struct SavedView {
+    pub id: String,
+    pub account_id: String,
+    pub user_id: String,
+    pub name: String,
+    pub endpoint: String,
+    pub filters: String, // JSON object
+    pub sort: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedViewResponse {
+    pub id: String,
+    pub name: String,
+    /// List endpoint the view is for, e.g. `/payments`
+    pub endpoint: String,
+    /// Query parameters to send to the endpoint
+    pub filters: serde_json::Value,
+    pub sort: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<SavedView> for SavedViewResponse {
+    fn from(view: SavedView) -> Self {
+        Self {
+            id: view.id,
+            name: view.name,
+            endpoint: view.endpoint,
+            filters: serde_json::from_str(&view.filters).unwrap_or_default(),
+            sort: view.sort,
+            created_at: view.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateSavedViewRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+    #[validate(length(min = 2, max = 200, message = "Endpoint must be 2-200 characters"))]
+    pub endpoint: String,
+    #[serde(default)]
+    pub filters: serde_json::Map<String, serde_json::Value>,
+    #[validate(length(min = 1, max = 100, message = "Sort must be 1-100 characters"))]
+    pub sort: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SavedViewQuery {
+    /// Only list views for this endpoint
+    pub endpoint: Option<String>,
+}
+
+/// A settled forward as stored in the forwards table.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ForwardRecord {
+    pub chan_id_in: String,
+    pub chan_id_out: String,
+    pub amt_in_msat: i64,
+    pub amt_out_msat: i64,
+    pub fee_msat: i64,
+    pub settled_at: DateTime<Utc>,
+}
+
+/// Tool a history export comes from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    Bos,
+    Lndg,
+    Thunderhub,
+}
+
+impl std::fmt::Display for ImportSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportSource::Bos => write!(f, "bos"),
+            ImportSource::Lndg => write!(f, "lndg"),
+            ImportSource::Thunderhub => write!(f, "thunderhub"),
+        }
+    }
+}
+
+/// What a history export holds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportKind {
+    Forwards,
+    Payments,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+/// A history export from another tool, passed through as text. Exports over
+/// the request size limit can be split and sent in several requests, since
+/// rows already stored are skipped.
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ImportHistoryRequest {
+    pub source: ImportSource,
+    pub kind: ImportKind,
+    pub format: ImportFormat,
+    #[validate(length(min = 1, message = "Export content is required"))]
+    pub content: String,
+}
+
+/// A row of a history export that couldn't be imported.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedImportRow {
+    /// 1-based position among the export's records
+    pub row: usize,
+    pub reason: String,
+}
+
+/// Outcome of a history import.
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    pub source: ImportSource,
+    pub kind: ImportKind,
+    /// Records read from the export
+    pub rows: usize,
+    /// Records stored
+    pub imported: u64,
+    /// Records already stored, from the node or an earlier import
+    pub duplicates: u64,
+    /// Records that couldn't be read; only the first few are listed
+    pub skipped: usize,
+    pub skipped_rows: Vec<SkippedImportRow>,
+}
+
+/// A settled forward with every stored column, as exported.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StoredForward {
+    pub id: String,
+    pub chan_id_in: String,
+    pub chan_id_out: String,
+    pub incoming_htlc_id: Option<i64>,
+    pub outgoing_htlc_id: Option<i64>,
+    pub amt_in_msat: i64,
+    pub amt_out_msat: i64,
+    pub fee_msat: i64,
+    pub received_at: Option<DateTime<Utc>>,
+    pub settled_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelRevenueQuery {
+    /// How many days of forwards to include (default 30, at most 365)
+    pub days: Option<i64>,
+}
+
+/// Fees a channel earned and the volume it routed on one UTC day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelRevenuePoint {
+    pub date: NaiveDate,
+    pub inbound_fee_msat: u64,
+    pub outbound_fee_msat: u64,
+    pub volume_msat: u64,
+}
+
+/// What a channel earned routing forwards. A forward's fee is counted for
+/// both of its channels: as inbound for the one it arrived on and as
+/// outbound for the one it left through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelRevenue {
+    pub channel_id: String,
+    pub days: i64,
+    /// Forwards that arrived through the channel
+    pub inbound_forwards: u64,
+    /// Forwards that left through the channel
+    pub outbound_forwards: u64,
+    pub inbound_fee_msat: u64,
+    pub outbound_fee_msat: u64,
+    /// Amount received on the channel for inbound forwards plus the amount
+    /// sent out on it for outbound ones
+    pub volume_msat: u64,
+    /// One point per day of the window, oldest first
+    pub series: Vec<ChannelRevenuePoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityFlowQuery {
+    /// Start of the range (default 30 days before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default now)
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Settled forwards from one channel into another, summed.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChannelPairFlow {
+    pub chan_id_in: String,
+    pub chan_id_out: String,
+    pub forwards: i64,
+    /// Amount sent out on `chan_id_out`
+    pub amount_msat: i64,
+    pub fee_msat: i64,
+}
+
+/// Which leg of a forward a flow node stands for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum FlowSide {
+    Incoming,
+    Outgoing,
+}
+
+/// A channel on one side of the flow diagram. A channel that both received
+/// and sent forwards appears once per side, which keeps the graph acyclic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityFlowNode {
+    /// `in:<channel id>` or `out:<channel id>`
+    pub id: String,
+    pub channel_id: String,
+    pub side: FlowSide,
+    pub peer_pubkey: Option<String>,
+    pub peer_alias: Option<String>,
+    pub amount_msat: u64,
+}
+
+/// Forwards from an incoming to an outgoing channel. `source` and `target`
+/// are indexes into the flow's nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityFlowLink {
+    pub source: usize,
+    pub target: usize,
+    pub forwards: u64,
+    pub amount_msat: u64,
+    pub fee_msat: u64,
+}
+
+/// Where liquidity moved through the node in a date range, shaped for a
+/// Sankey diagram: incoming channels on the left, outgoing on the right.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityFlow {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_forwards: u64,
+    pub total_amount_msat: u64,
+    pub total_fee_msat: u64,
+    pub nodes: Vec<LiquidityFlowNode>,
+    pub links: Vec<LiquidityFlowLink>,
+}
+
+/// A node event type the account has stopped ingesting.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PausedEventType {
+    pub account_id: String,
+    pub node_id: String,
+    pub event_type: String,
+    pub paused_by: String,
+    pub paused_at: DateTime<Utc>,
+}
+
+/// Whether one event type of a node is being ingested.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSubscriptionStatus {
+    pub node_id: String,
+    pub event_type: String,
+    pub is_paused: bool,
+    pub paused_by: Option<String>,
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Whether the node's event stream is currently up
+    pub stream_connected: bool,
+}
+
+/// A request made with an Idempotency-Key and, once handled, its response.
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyRecord {
+    pub request_hash: String,
+    /// `None` while the original request is still in flight
+    pub status_code: Option<i64>,
+    pub content_type: Option<String>,
+    pub response_body: Option<Vec<u8>>,
+}
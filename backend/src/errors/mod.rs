@@ -4,8 +4,98 @@
 //! backend application and provides mechanisms for consistent error handling
 //! and response formatting.
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Stable, machine-readable error codes returned in every error response.
+///
+/// Clients should branch on these rather than on messages. Codes are only
+/// ever added; an existing code keeps its name and meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    ValidationError,
+    NotFound,
+    AlreadyExists,
+    InvalidOperation,
+    /// Missing, malformed, expired or revoked access token
+    Unauthenticated,
+    PermissionDenied,
+    /// The user must verify their email address first
+    EmailNotVerified,
+    /// The request needs a node but the token carries no node credentials
+    NodeCredentialsRequired,
+    /// Stored node credentials are incomplete or don't match the node
+    InvalidNodeCredentials,
+    UnsupportedNodeType,
+    /// The node implementation can't perform the operation
+    UnsupportedOperation,
+    /// The node could not be reached
+    NodeUnreachable,
+    /// The node's request budget is exhausted; retry shortly
+    NodeBusy,
+    /// Recent requests to the node failed; it isn't tried again until a
+    /// cooldown passes
+    NodeCircuitOpen,
+    /// The node was reached but rejected or failed the request
+    NodeRequestFailed,
+    InvalidPublicKey,
+    InvalidPaymentHash,
+    InvalidChannelId,
+    /// The Idempotency-Key was already used for a different request
+    IdempotencyKeyReused,
+    /// A request with the same Idempotency-Key has not finished yet
+    RequestInProgress,
+    /// The request body exceeds the route's size limit
+    PayloadTooLarge,
+    /// The request body isn't JSON
+    UnsupportedMediaType,
+    /// The account's plan doesn't allow this; upgrade to a higher plan
+    UpgradeRequired,
+    DatabaseError,
+    ExternalServiceError,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::ValidationError => "VALIDATION_ERROR",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::AlreadyExists => "ALREADY_EXISTS",
+            ErrorCode::InvalidOperation => "INVALID_OPERATION",
+            ErrorCode::Unauthenticated => "UNAUTHENTICATED",
+            ErrorCode::PermissionDenied => "PERMISSION_DENIED",
+            ErrorCode::EmailNotVerified => "EMAIL_NOT_VERIFIED",
+            ErrorCode::NodeCredentialsRequired => "NODE_CREDENTIALS_REQUIRED",
+            ErrorCode::InvalidNodeCredentials => "INVALID_NODE_CREDENTIALS",
+            ErrorCode::UnsupportedNodeType => "UNSUPPORTED_NODE_TYPE",
+            ErrorCode::UnsupportedOperation => "UNSUPPORTED_OPERATION",
+            ErrorCode::NodeUnreachable => "NODE_UNREACHABLE",
+            ErrorCode::NodeBusy => "NODE_BUSY",
+            ErrorCode::NodeCircuitOpen => "NODE_CIRCUIT_OPEN",
+            ErrorCode::NodeRequestFailed => "NODE_REQUEST_FAILED",
+            ErrorCode::InvalidPublicKey => "INVALID_PUBLIC_KEY",
+            ErrorCode::InvalidPaymentHash => "INVALID_PAYMENT_HASH",
+            ErrorCode::InvalidChannelId => "INVALID_CHANNEL_ID",
+            ErrorCode::IdempotencyKeyReused => "IDEMPOTENCY_KEY_REUSED",
+            ErrorCode::RequestInProgress => "REQUEST_IN_PROGRESS",
+            ErrorCode::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            ErrorCode::UnsupportedMediaType => "UNSUPPORTED_MEDIA_TYPE",
+            ErrorCode::UpgradeRequired => "UPGRADE_REQUIRED",
+            ErrorCode::DatabaseError => "DATABASE_ERROR",
+            ErrorCode::ExternalServiceError => "EXTERNAL_SERVICE_ERROR",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Represents errors that can occur during Lightning Network operations.
 #[derive(Debug, Error)]
 pub enum LightningError {
@@ -45,6 +135,35 @@ pub enum LightningError {
     #[error("Network error: {0}")]
     /// Network error.
     NetworkError(String),
+    /// Error that occurred while signing or verifying a message.
+    #[error("Message signing error: {0}")]
+    SigningError(String),
+    /// The node implementation does not support the requested operation.
+    #[error("Unsupported operation: {0}")]
+    Unsupported(String),
+}
+
+impl LightningError {
+    /// Error code for a failed node call, shared by every node backend.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            LightningError::ConnectionError(_) | LightningError::NetworkError(_) => {
+                ErrorCode::NodeUnreachable
+            }
+            LightningError::ValidationError(_) => ErrorCode::InvalidNodeCredentials,
+            LightningError::NotFound(_) => ErrorCode::NotFound,
+            LightningError::Unsupported(_) => ErrorCode::UnsupportedOperation,
+            LightningError::GetInfoError(_)
+            | LightningError::PaymentError(_)
+            | LightningError::InvoiceError(_)
+            | LightningError::GetNodeInfoError(_)
+            | LightningError::GetGraphError(_)
+            | LightningError::StreamingError(_)
+            | LightningError::ChannelError(_)
+            | LightningError::Parse(_)
+            | LightningError::SigningError(_) => ErrorCode::NodeRequestFailed,
+        }
+    }
 }
 
 /// Generic service error that can be used across all entities
@@ -65,6 +184,9 @@ pub enum ServiceError {
     #[error("Invalid operation: {message}")]
     InvalidOperation { message: String },
 
+    #[error("Upgrade required: {message}")]
+    UpgradeRequired { message: String },
+
     #[error("Database error: {source}")]
     Database {
         #[from]
@@ -79,6 +201,20 @@ pub enum ServiceError {
 pub type ServiceResult<T> = Result<T, ServiceError>;
 
 impl ServiceError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ServiceError::Validation { .. } => ErrorCode::ValidationError,
+            ServiceError::NotFound { .. } => ErrorCode::NotFound,
+            ServiceError::AlreadyExists { .. } => ErrorCode::AlreadyExists,
+            ServiceError::PermissionDenied { .. } => ErrorCode::PermissionDenied,
+            ServiceError::InvalidOperation { .. } => ErrorCode::InvalidOperation,
+            ServiceError::UpgradeRequired { .. } => ErrorCode::UpgradeRequired,
+            ServiceError::Database { .. } => ErrorCode::DatabaseError,
+            ServiceError::ExternalService { .. } => ErrorCode::ExternalServiceError,
+            ServiceError::InternalError { .. } => ErrorCode::InternalError,
+        }
+    }
+
     // Helper constructors for common patterns
 
     pub fn validation(message: impl Into<String>) -> Self {
@@ -113,6 +249,12 @@ impl ServiceError {
         }
     }
 
+    pub fn upgrade_required(message: impl Into<String>) -> Self {
+        Self::UpgradeRequired {
+            message: message.into(),
+        }
+    }
+
     pub fn external_service(message: impl Into<String>) -> Self {
         Self::ExternalService {
             message: message.into(),
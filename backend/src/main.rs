@@ -9,6 +9,7 @@ mod auth;
 mod config;
 mod database;
 mod errors;
+mod middleware;
 mod repositories;
 mod services;
 mod utils;
@@ -18,40 +19,44 @@ use axum::{Extension, Router, response::Json, routing::get};
 use config::Config;
 use database::Database;
 use tracing::info;
-use tracing_subscriber::fmt::init;
+use utils::redact::RedactingWriter;
 
 #[tokio::main]
 async fn main() {
-    init();
+    tracing_subscriber::fmt()
+        .with_writer(|| RedactingWriter)
+        .init();
 
     let config = Config::from_env().unwrap();
     let db = Database::new(&config).await.unwrap();
     let pool = db.pool().clone();
 
-    let app = Router::new()
-        .route("/", get(root_handler))
-        .nest("/api/node", api::node::routes::node_router().await)
-        .nest("/api/account", api::account::routes::account_router().await)
-        .nest("/auth", auth::routes::auth_router())
-        .nest("/api/invite", api::invite::routes::invite_router().await)
-        .nest(
-            "/api/notification",
-            api::notification::routes::notification_router().await,
-        )
-        .nest("/api/events", api::event::routes::event_router().await)
-        .nest(
-            "/api/channels",
-            api::channel::routes::channel_router().await,
-        )
-        .nest(
-            "/api/payments",
-            api::payment::routes::payment_router().await,
-        )
-        .nest(
-            "/api/invoices",
-            api::invoice::routes::invoice_router().await,
-        )
-        .nest("/api/user", api::user::routes::user_router().await)
+    services::job_queue::start_workers(pool.clone(), config.job_workers).await;
+    services::scheduler::start_scheduler(pool.clone()).await;
+    services::channel_acceptor::start_channel_acceptors(pool.clone()).await;
+    services::htlc_interceptor::start_htlc_interceptors(pool.clone()).await;
+    services::graph_sync::start_graph_subscriptions(pool.clone()).await;
+    services::swap_service::start_swap_tracker(pool.clone()).await;
+    services::block_stream::start_block_stream(pool.clone()).await;
+    if config.demo_mode {
+        services::demo::start_demo(pool.clone()).await;
+    }
+
+    let mut app = Router::new().route("/", get(root_handler));
+    for version in api::ApiVersion::ALL {
+        app = app.nest(version.prefix(), version.router().await);
+    }
+    let app = app
+        .merge(api::legacy_router().await)
+        .layer(axum::middleware::from_fn_with_state(
+            middleware::body_limit::DEFAULT_BODY_LIMIT_BYTES,
+            middleware::body_limit::limit_body,
+        ))
+        .merge(api::large_body_router().await)
+        .layer(axum::middleware::from_fn(
+            middleware::retry_after::retry_after,
+        ))
+        .layer(middleware::compression::compression_layer())
         .layer(Extension(pool));
 
     let bind_address = format!("0.0.0.0:{}", config.server_port);
@@ -0,0 +1,115 @@
+//! Request body size limits and content-type checking.
+//!
+//! Every request body is capped at `DEFAULT_BODY_LIMIT_BYTES`; routes that
+//! take node credentials (base64 TLS certs, macaroons and runes) get the much
+//! lower `CREDENTIAL_BODY_LIMIT_BYTES`. History imports carry whole exports
+//! and get `IMPORT_BODY_LIMIT_BYTES` instead, so they are served outside the
+//! default limit (see `api::large_body_router`). A declared `Content-Length` over the
+//! limit is rejected before any of the body is read, and a body sent without
+//! one is buffered up to the limit at most. Bodies must be JSON, since every
+//! endpoint takes JSON. Rejections use the usual error envelope rather than
+//! axum's plain-text ones.
+
+use crate::api::common::ApiResponse;
+use crate::errors::ErrorCode;
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{CONTENT_LENGTH, CONTENT_TYPE, TRANSFER_ENCODING},
+    },
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+
+/// Largest request body accepted by any route
+pub const DEFAULT_BODY_LIMIT_BYTES: usize = 1024 * 1024;
+
+/// Largest body of a request carrying node credentials
+pub const CREDENTIAL_BODY_LIMIT_BYTES: usize = 64 * 1024;
+
+/// Largest body of a history import, which holds an entire export
+pub const IMPORT_BODY_LIMIT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Rejects request bodies over `limit` bytes or not declared as JSON.
+///
+/// Applied with `middleware::from_fn_with_state(limit, limit_body)`.
+pub async fn limit_body(
+    State(limit): State<usize>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let headers = request.headers();
+    let content_length = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if !has_body(headers, content_length) {
+        return Ok(next.run(request).await);
+    }
+
+    if !is_json(headers.get(CONTENT_TYPE)) {
+        return Err(error_response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Request body must be sent as application/json".to_string(),
+            ErrorCode::UnsupportedMediaType,
+        ));
+    }
+
+    match content_length {
+        Some(length) if length > limit => Err(too_large(limit)),
+        // Hyper stops reading at the declared length.
+        Some(_) => Ok(next.run(request).await),
+        None => {
+            let (parts, body) = request.into_parts();
+            let body = to_bytes(body, limit).await.map_err(|_| too_large(limit))?;
+            Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+        }
+    }
+}
+
+/// Whether the request declares a body, by length or by chunked encoding.
+fn has_body(headers: &HeaderMap, content_length: Option<usize>) -> bool {
+    match content_length {
+        Some(length) => length > 0,
+        None => headers.contains_key(TRANSFER_ENCODING),
+    }
+}
+
+/// Whether the content type is `application/json`, with or without
+/// parameters such as `charset`.
+fn is_json(content_type: Option<&HeaderValue>) -> bool {
+    content_type
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+}
+
+fn too_large(limit: usize) -> Response {
+    error_response(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!("Request body is larger than {limit} bytes"),
+        ErrorCode::PayloadTooLarge,
+    )
+}
+
+fn error_response(status: StatusCode, message: String, code: ErrorCode) -> Response {
+    (status, Json(ApiResponse::<()>::error(message, code, None))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_json() {
+        let json = |value: &'static str| is_json(Some(&HeaderValue::from_static(value)));
+
+        assert!(json("application/json"));
+        assert!(json("Application/JSON; charset=utf-8"));
+        assert!(!json("text/plain"));
+        assert!(!json("application/jsonp"));
+        assert!(!is_json(None));
+    }
+}
@@ -0,0 +1,26 @@
+//! Response compression negotiated through `Accept-Encoding`.
+//!
+//! Payment, graph and export responses can run to several megabytes of JSON
+//! or CSV, so anything above `MIN_COMPRESSED_BYTES` is compressed with
+//! brotli or gzip, whichever the client prefers. Bodies are compressed as
+//! they stream, so large exports never have to be buffered whole. Server-sent
+//! event streams are left alone, since compression would hold events back
+//! until enough output accumulates.
+
+use tower_http::compression::{
+    CompressionLayer, DefaultPredicate, Predicate,
+    predicate::{And, SizeAbove},
+};
+
+/// Responses smaller than this are sent uncompressed
+const MIN_COMPRESSED_BYTES: u16 = 1024;
+
+/// Compresses responses for clients that accept brotli or gzip.
+///
+/// `DefaultPredicate` already skips event streams, gRPC and images.
+pub fn compression_layer() -> CompressionLayer<And<DefaultPredicate, SizeAbove>> {
+    CompressionLayer::new()
+        .br(true)
+        .gzip(true)
+        .compress_when(DefaultPredicate::new().and(SizeAbove::new(MIN_COMPRESSED_BYTES)))
+}
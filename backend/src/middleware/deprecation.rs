@@ -0,0 +1,58 @@
+//! Deprecation headers for the unversioned API paths.
+//!
+//! Responses on `/api/...` and `/auth/...` carry `Deprecation`, a `Sunset`
+//! date after which the paths may be removed, and a `Link` to the same
+//! resource under `/api/v1`.
+
+use axum::{
+    extract::{OriginalUri, Request},
+    http::{HeaderName, HeaderValue, header::LINK},
+    middleware::Next,
+    response::Response,
+};
+
+/// When the unversioned paths may stop being served (an HTTP-date)
+const LEGACY_SUNSET: &str = "Fri, 01 Jan 2027 00:00:00 GMT";
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+pub async fn deprecated_path(request: Request, next: Next) -> Response {
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or(request.uri().path(), |uri| uri.path())
+        .to_string();
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION, HeaderValue::from_static("true"));
+    headers.insert(SUNSET, HeaderValue::from_static(LEGACY_SUNSET));
+    if let Ok(link) = HeaderValue::from_str(&format!(
+        "<{}>; rel=\"successor-version\"",
+        successor_path(&path)
+    )) {
+        headers.insert(LINK, link);
+    }
+
+    response
+}
+
+/// The `/api/v1` path that replaces an unversioned one.
+fn successor_path(path: &str) -> String {
+    match path.strip_prefix("/api") {
+        Some(rest) => format!("/api/v1{rest}"),
+        None => format!("/api/v1{path}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_successor_path() {
+        assert_eq!(successor_path("/api/channels/42"), "/api/v1/channels/42");
+        assert_eq!(successor_path("/auth/login"), "/api/v1/auth/login");
+    }
+}
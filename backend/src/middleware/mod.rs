@@ -2,4 +2,10 @@
 //!
 //! This module contains reusable middleware components (e.g., for logging,
 //! CORS, or rate limiting) that can be applied to different parts of the
-//! Axum router.
\ No newline at end of file
+//! Axum router.
+
+pub mod body_limit;
+pub mod compression;
+pub mod deprecation;
+pub mod plan_limits;
+pub mod retry_after;
@@ -0,0 +1,48 @@
+//! Plan limit enforcement for routes that add billable resources.
+//!
+//! Applied with `middleware::from_fn_with_state(PlanResource::Nodes,
+//! enforce_plan_limit)` after authentication. Requests from accounts at their
+//! plan's cap are rejected with 402 and `UPGRADE_REQUIRED`; unauthenticated
+//! requests pass through, since they can't add anything to an account.
+
+use crate::api::common::service_error_to_http;
+use crate::services::billing::{BillingService, PlanResource};
+use crate::utils::jwt::Claims;
+use axum::{
+    extract::{Request, State},
+    http::header::CONTENT_TYPE,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sqlx::SqlitePool;
+
+/// Rejects the request when the account can't add another `resource`.
+pub async fn enforce_plan_limit(
+    State(resource): State<PlanResource>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    // Routes with optional authentication carry `Option<Claims>`.
+    let claims = request.extensions().get::<Claims>().cloned().or_else(|| {
+        request
+            .extensions()
+            .get::<Option<Claims>>()
+            .cloned()
+            .flatten()
+    });
+    let pool = request.extensions().get::<SqlitePool>().cloned();
+    let (Some(claims), Some(pool)) = (claims, pool) else {
+        return Ok(next.run(request).await);
+    };
+
+    match BillingService::new(&pool)
+        .check_limit(&claims, resource)
+        .await
+    {
+        Ok(()) => Ok(next.run(request).await),
+        Err(error) => {
+            let (status, body) = service_error_to_http(error);
+            Err((status, [(CONTENT_TYPE, "application/json")], body).into_response())
+        }
+    }
+}
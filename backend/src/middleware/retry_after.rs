@@ -0,0 +1,72 @@
+//! Adds a `Retry-After` header to backpressure 503 responses.
+//!
+//! Handlers report errors as `(StatusCode, String)` and cannot set headers, so
+//! code turning a request away for a while (a saturated node, an open node
+//! circuit) leaves the wait with `hint_retry_after` and it is attached here.
+//! Other 503s get no header, since how long they last isn't known.
+
+use axum::{
+    extract::Request,
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::Response,
+};
+use std::cell::Cell;
+
+tokio::task_local! {
+    static RETRY_AFTER_HINT: Cell<Option<u64>>;
+}
+
+/// Sets the `Retry-After` seconds of the current request's response, should
+/// it be a 503. Does nothing outside a request.
+pub fn hint_retry_after(seconds: u64) {
+    let _ = RETRY_AFTER_HINT.try_with(|hint| hint.set(Some(seconds)));
+}
+
+pub async fn retry_after(request: Request, next: Next) -> Response {
+    RETRY_AFTER_HINT
+        .scope(Cell::new(None), async move {
+            let mut response = next.run(request).await;
+
+            if response.status() == StatusCode::SERVICE_UNAVAILABLE
+                && !response.headers().contains_key(RETRY_AFTER)
+            {
+                if let Some(seconds) = RETRY_AFTER_HINT.with(|hint| hint.get()) {
+                    response
+                        .headers_mut()
+                        .insert(RETRY_AFTER, HeaderValue::from(seconds));
+                }
+            }
+
+            response
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::Service;
+
+    #[tokio::test]
+    async fn test_only_hinted_503s_get_retry_after() {
+        let mut app = Router::new()
+            .route(
+                "/busy",
+                get(|| async {
+                    hint_retry_after(7);
+                    StatusCode::SERVICE_UNAVAILABLE
+                }),
+            )
+            .route("/down", get(|| async { StatusCode::SERVICE_UNAVAILABLE }))
+            .layer(axum::middleware::from_fn(retry_after));
+
+        let request = |path: &str| axum::http::Request::get(path).body(Body::empty()).unwrap();
+        let busy = app.call(request("/busy")).await.unwrap();
+        assert_eq!(busy.headers()[RETRY_AFTER], "7");
+        let down = app.call(request("/down")).await.unwrap();
+        assert_eq!(down.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!down.headers().contains_key(RETRY_AFTER));
+    }
+}
@@ -0,0 +1,150 @@
+//! Database repository for users' memberships of accounts other than their
+//! home account.
+
+use crate::database::models::{AccountMembership, AccountMembershipDetails, RoleAccessLevel};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for account memberships.
+pub struct AccountMembershipRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> AccountMembershipRepository<'a> {
+    /// Creates a new AccountMembershipRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Adds a user to an account with the given role.
+    pub async fn create_membership(
+        &self,
+        id: &str,
+        user_id: &str,
+        account_id: &str,
+        role_id: &str,
+        created_by: &str,
+    ) -> Result<AccountMembership> {
+        let membership = sqlx::query_as!(
+            AccountMembership,
+            r#"
+            INSERT INTO account_memberships (id, user_id, account_id, role_id, role_access_level, created_by)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            user_id as "user_id!",
+            account_id as "account_id!",
+            role_id as "role_id!",
+            role_access_level as "role_access_level: RoleAccessLevel",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>"
+            "#,
+            id,
+            user_id,
+            account_id,
+            role_id,
+            RoleAccessLevel::Read,
+            created_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(membership)
+    }
+
+    /// Retrieves a user's membership of an account, if the account and the
+    /// role it grants still exist.
+    pub async fn get_membership(
+        &self,
+        user_id: &str,
+        account_id: &str,
+    ) -> Result<Option<AccountMembership>> {
+        let membership = sqlx::query_as!(
+            AccountMembership,
+            r#"
+            SELECT
+            m.id as "id!",
+            m.user_id as "user_id!",
+            m.account_id as "account_id!",
+            m.role_id as "role_id!",
+            m.role_access_level as "role_access_level: RoleAccessLevel",
+            m.created_by as "created_by!",
+            m.created_at as "created_at!: DateTime<Utc>"
+            FROM account_memberships m
+            JOIN accounts a ON a.id = m.account_id
+            JOIN roles r ON r.id = m.role_id
+            WHERE m.user_id = ? AND m.account_id = ?
+            AND a.is_deleted = 0 AND r.is_deleted = 0
+            "#,
+            user_id,
+            account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(membership)
+    }
+
+    /// Lists every account a user can sign in to, home account first, marking
+    /// `current_account_id` as current.
+    pub async fn get_accounts_for_user(
+        &self,
+        user_id: &str,
+        current_account_id: &str,
+    ) -> Result<Vec<AccountMembershipDetails>> {
+        let accounts = sqlx::query_as!(
+            AccountMembershipDetails,
+            r#"
+            SELECT
+            a.id as "account_id!",
+            a.name as "account_name!",
+            r.id as "role_id!",
+            r.name as "role!",
+            a.is_active as "is_active!: bool",
+            1 as "is_home!: bool",
+            a.id = ? as "is_current!: bool"
+            FROM users u
+            JOIN accounts a ON a.id = u.account_id
+            JOIN roles r ON r.id = u.role_id
+            WHERE u.id = ? AND u.is_deleted = 0 AND a.is_deleted = 0
+            UNION ALL
+            SELECT
+            a.id,
+            a.name,
+            r.id,
+            r.name,
+            a.is_active,
+            0,
+            a.id = ?
+            FROM account_memberships m
+            JOIN accounts a ON a.id = m.account_id
+            JOIN roles r ON r.id = m.role_id
+            WHERE m.user_id = ? AND a.is_deleted = 0 AND r.is_deleted = 0
+            ORDER BY 6 DESC, 2 ASC
+            "#,
+            current_account_id,
+            user_id,
+            current_account_id,
+            user_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(accounts)
+    }
+
+    /// Removes a user from an account. Returns whether they were a member.
+    pub async fn delete_membership(&self, user_id: &str, account_id: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM account_memberships WHERE user_id = ? AND account_id = ?",
+            user_id,
+            account_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
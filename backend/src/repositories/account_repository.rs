@@ -165,6 +165,28 @@ impl<'a> AccountRepository<'a> {
         Ok(count.count)
     }
 
+    /// Returns the IDs of all active, non-deleted accounts.
+    pub async fn get_active_account_ids(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query!(
+            r#"SELECT id as "id!" FROM accounts WHERE is_deleted = 0 AND is_active = 1"#
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.id).collect())
+    }
+
+    /// Returns the ID of the first account ever created, deleted or not.
+    pub async fn get_first_account_id(&self) -> Result<Option<String>> {
+        let row = sqlx::query!(
+            r#"SELECT id as "id!" FROM accounts ORDER BY created_at ASC, rowid ASC LIMIT 1"#
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(row.map(|row| row.id))
+    }
+
     /// Checks if an account name already exists.
     ///
     /// # Arguments
@@ -233,4 +255,22 @@ impl<'a> AccountRepository<'a> {
 
         Ok(())
     }
+
+    /// Activates or deactivates an account. Members of an inactive account
+    /// cannot log in.
+    pub async fn set_active(&self, id: &str, is_active: bool) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE accounts
+            SET is_active = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND is_deleted = 0
+            "#,
+            is_active,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
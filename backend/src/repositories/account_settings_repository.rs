@@ -0,0 +1,65 @@
+//! Database repository for account display settings.
+
+use crate::database::models::AccountSettings;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for per-account display preferences.
+pub struct AccountSettingsRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> AccountSettingsRepository<'a> {
+    /// Creates a new AccountSettingsRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves an account's settings, if any were saved.
+    pub async fn get_settings(&self, account_id: &str) -> Result<Option<AccountSettings>> {
+        let settings = sqlx::query_as!(
+            AccountSettings,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            timezone as "timezone!",
+            display_currency as "display_currency!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM account_settings WHERE account_id = ?
+            "#,
+            account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    /// Creates or replaces an account's settings.
+    pub async fn upsert_settings(&self, settings: &AccountSettings) -> Result<AccountSettings> {
+        let saved = sqlx::query_as!(
+            AccountSettings,
+            r#"
+            INSERT INTO account_settings (account_id, timezone, display_currency)
+            VALUES (?, ?, ?)
+            ON CONFLICT(account_id) DO UPDATE SET
+                timezone = excluded.timezone,
+                display_currency = excluded.display_currency
+            RETURNING
+            account_id as "account_id!",
+            timezone as "timezone!",
+            display_currency as "display_currency!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            settings.account_id,
+            settings.timezone,
+            settings.display_currency
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+}
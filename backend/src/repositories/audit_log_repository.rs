@@ -0,0 +1,62 @@
+//! Database repository for the audit trail.
+
+use crate::database::models::AuditLog;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Repository for audit log persistence.
+pub struct AuditLogRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> AuditLogRepository<'a> {
+    /// Creates a new AuditLogRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Appends an entry to the audit trail.
+    pub async fn create_log(
+        &self,
+        account_id: &str,
+        actor_user_id: Option<&str>,
+        action: &str,
+        target_type: &str,
+        target_id: Option<&str>,
+        details: &serde_json::Value,
+    ) -> Result<AuditLog> {
+        let id = Uuid::now_v7().to_string();
+        let details = details.to_string();
+
+        let log = sqlx::query_as!(
+            AuditLog,
+            r#"
+            INSERT INTO audit_logs (id, account_id, actor_user_id, action, target_type, target_id, details)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            actor_user_id as "actor_user_id?",
+            action as "action!",
+            target_type as "target_type!",
+            target_id as "target_id?",
+            details as "details!",
+            created_at as "created_at!: DateTime<Utc>"
+            "#,
+            id,
+            account_id,
+            actor_user_id,
+            action,
+            target_type,
+            target_id,
+            details
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(log)
+    }
+}
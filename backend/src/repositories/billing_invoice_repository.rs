@@ -0,0 +1,173 @@
+//! Database repository for Lightning billing invoices.
+
+use crate::database::models::{BillingInvoice, BillingInvoiceStatus, Plan};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for the invoices accounts pay their plans with.
+pub struct BillingInvoiceRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> BillingInvoiceRepository<'a> {
+    /// Creates a new BillingInvoiceRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a newly issued invoice. Returns `None` when the account already
+    /// has an invoice for the same period.
+    pub async fn create_invoice(&self, invoice: &BillingInvoice) -> Result<Option<BillingInvoice>> {
+        let created = sqlx::query_as!(
+            BillingInvoice,
+            r#"
+            INSERT INTO billing_invoices (
+                id, account_id, plan, amount_sat, payment_request, payment_hash,
+                period_start, period_end, status, expires_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, period_start) DO NOTHING
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            plan as "plan: Plan",
+            amount_sat as "amount_sat!",
+            payment_request as "payment_request!",
+            payment_hash as "payment_hash!",
+            period_start as "period_start!: DateTime<Utc>",
+            period_end as "period_end!: DateTime<Utc>",
+            status as "status: BillingInvoiceStatus",
+            expires_at as "expires_at!: DateTime<Utc>",
+            paid_at as "paid_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>"
+            "#,
+            invoice.id,
+            invoice.account_id,
+            invoice.plan,
+            invoice.amount_sat,
+            invoice.payment_request,
+            invoice.payment_hash,
+            invoice.period_start,
+            invoice.period_end,
+            invoice.status,
+            invoice.expires_at
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    /// Retrieves the invoice with the given payment hash.
+    pub async fn get_by_payment_hash(&self, payment_hash: &str) -> Result<Option<BillingInvoice>> {
+        let invoice = sqlx::query_as!(
+            BillingInvoice,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            plan as "plan: Plan",
+            amount_sat as "amount_sat!",
+            payment_request as "payment_request!",
+            payment_hash as "payment_hash!",
+            period_start as "period_start!: DateTime<Utc>",
+            period_end as "period_end!: DateTime<Utc>",
+            status as "status: BillingInvoiceStatus",
+            expires_at as "expires_at!: DateTime<Utc>",
+            paid_at as "paid_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM billing_invoices WHERE payment_hash = ?
+            "#,
+            payment_hash
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(invoice)
+    }
+
+    /// Lists an account's invoices, newest first.
+    pub async fn list_for_account(&self, account_id: &str) -> Result<Vec<BillingInvoice>> {
+        let invoices = sqlx::query_as!(
+            BillingInvoice,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            plan as "plan: Plan",
+            amount_sat as "amount_sat!",
+            payment_request as "payment_request!",
+            payment_hash as "payment_hash!",
+            period_start as "period_start!: DateTime<Utc>",
+            period_end as "period_end!: DateTime<Utc>",
+            status as "status: BillingInvoiceStatus",
+            expires_at as "expires_at!: DateTime<Utc>",
+            paid_at as "paid_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM billing_invoices WHERE account_id = ?
+            ORDER BY created_at DESC
+            "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(invoices)
+    }
+
+    /// Lists every invoice still waiting to be paid.
+    pub async fn list_open(&self) -> Result<Vec<BillingInvoice>> {
+        let invoices = sqlx::query_as!(
+            BillingInvoice,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            plan as "plan: Plan",
+            amount_sat as "amount_sat!",
+            payment_request as "payment_request!",
+            payment_hash as "payment_hash!",
+            period_start as "period_start!: DateTime<Utc>",
+            period_end as "period_end!: DateTime<Utc>",
+            status as "status: BillingInvoiceStatus",
+            expires_at as "expires_at!: DateTime<Utc>",
+            paid_at as "paid_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM billing_invoices WHERE status = 'Open'
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(invoices)
+    }
+
+    /// Marks an open invoice paid. Returns `false` when it was not open, so
+    /// a payment seen twice is applied once.
+    pub async fn mark_paid(&self, id: &str, paid_at: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE billing_invoices SET status = 'Paid', paid_at = ? WHERE id = ? AND status = 'Open'",
+            paid_at,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Marks an open invoice expired.
+    pub async fn mark_expired(&self, id: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE billing_invoices SET status = 'Expired' WHERE id = ? AND status = 'Open'",
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
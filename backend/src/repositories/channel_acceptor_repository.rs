@@ -0,0 +1,119 @@
+//! Database repository for channel acceptor policies.
+
+use crate::database::models::ChannelAcceptorPolicy;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for per-node channel acceptor rules.
+pub struct ChannelAcceptorRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ChannelAcceptorRepository<'a> {
+    /// Creates a new ChannelAcceptorRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves the policy of one of an account's nodes, if one was saved.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<ChannelAcceptorPolicy>> {
+        let policy = sqlx::query_as!(
+            ChannelAcceptorPolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            min_channel_size_sat as "min_channel_size_sat?",
+            allowed_peers as "allowed_peers!",
+            blocked_peers as "blocked_peers!",
+            private_only as "private_only!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM channel_acceptor_policies WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Lists every enabled policy, across all accounts.
+    pub async fn get_enabled_policies(&self) -> Result<Vec<ChannelAcceptorPolicy>> {
+        let policies = sqlx::query_as!(
+            ChannelAcceptorPolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            min_channel_size_sat as "min_channel_size_sat?",
+            allowed_peers as "allowed_peers!",
+            blocked_peers as "blocked_peers!",
+            private_only as "private_only!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM channel_acceptor_policies WHERE is_enabled = 1
+            "#
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(policies)
+    }
+
+    /// Creates or replaces a node's policy.
+    pub async fn upsert_policy(
+        &self,
+        policy: &ChannelAcceptorPolicy,
+    ) -> Result<ChannelAcceptorPolicy> {
+        let saved = sqlx::query_as!(
+            ChannelAcceptorPolicy,
+            r#"
+            INSERT INTO channel_acceptor_policies (
+                account_id, node_id, is_enabled, min_channel_size_sat,
+                allowed_peers, blocked_peers, private_only, updated_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, node_id) DO UPDATE SET
+                is_enabled = excluded.is_enabled,
+                min_channel_size_sat = excluded.min_channel_size_sat,
+                allowed_peers = excluded.allowed_peers,
+                blocked_peers = excluded.blocked_peers,
+                private_only = excluded.private_only,
+                updated_by = excluded.updated_by
+            RETURNING
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            min_channel_size_sat as "min_channel_size_sat?",
+            allowed_peers as "allowed_peers!",
+            blocked_peers as "blocked_peers!",
+            private_only as "private_only!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            policy.account_id,
+            policy.node_id,
+            policy.is_enabled,
+            policy.min_channel_size_sat,
+            policy.allowed_peers,
+            policy.blocked_peers,
+            policy.private_only,
+            policy.updated_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+}
@@ -0,0 +1,127 @@
+//! Database repository for confirmation tracking of channel transactions.
+
+use crate::database::models::ChannelConfirmation;
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+/// Repository for funding and closing transactions of pending channels.
+pub struct ChannelConfirmationRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ChannelConfirmationRepository<'a> {
+    /// Creates a new ChannelConfirmationRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Starts following a transaction; does nothing if it is already followed.
+    pub async fn track(&self, confirmation: &ChannelConfirmation) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO channel_confirmations (
+                node_id, txid, account_id, user_id, node_alias, kind,
+                channel_point, remote_pubkey, confirmation_height, notified_confirmations
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(node_id, txid) DO NOTHING
+            "#,
+            confirmation.node_id,
+            confirmation.txid,
+            confirmation.account_id,
+            confirmation.user_id,
+            confirmation.node_alias,
+            confirmation.kind,
+            confirmation.channel_point,
+            confirmation.remote_pubkey,
+            confirmation.confirmation_height,
+            confirmation.notified_confirmations
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves the transactions of a node below `final_confirmations`.
+    pub async fn get_unfinished(
+        &self,
+        node_id: &str,
+        final_confirmations: i64,
+    ) -> Result<Vec<ChannelConfirmation>> {
+        let confirmations = sqlx::query_as!(
+            ChannelConfirmation,
+            r#"
+            SELECT
+            node_id as "node_id!",
+            txid as "txid!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            node_alias as "node_alias!",
+            kind as "kind!",
+            channel_point as "channel_point!",
+            remote_pubkey as "remote_pubkey!",
+            confirmation_height as "confirmation_height?: i64",
+            notified_confirmations as "notified_confirmations!: i64"
+            FROM channel_confirmations
+            WHERE node_id = ? AND notified_confirmations < ?
+            "#,
+            node_id,
+            final_confirmations
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(confirmations)
+    }
+
+    /// Retrieves the confirmation height of each transaction followed for a
+    /// node, None while unconfirmed.
+    pub async fn get_confirmation_heights(
+        &self,
+        node_id: &str,
+    ) -> Result<Vec<(String, Option<i64>)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT txid as "txid!", confirmation_height as "confirmation_height?: i64"
+            FROM channel_confirmations
+            WHERE node_id = ?
+            "#,
+            node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.txid, row.confirmation_height))
+            .collect())
+    }
+
+    /// Records where a transaction confirmed (None if a reorg unconfirmed it)
+    /// and the last milestone reported for it.
+    pub async fn update(
+        &self,
+        node_id: &str,
+        txid: &str,
+        confirmation_height: Option<i64>,
+        notified_confirmations: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE channel_confirmations
+            SET confirmation_height = ?, notified_confirmations = ?
+            WHERE node_id = ? AND txid = ?
+            "#,
+            confirmation_height,
+            notified_confirmations,
+            node_id,
+            txid
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
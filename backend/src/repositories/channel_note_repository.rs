@@ -0,0 +1,169 @@
+//! Database repository for channel and peer notes.
+
+use crate::database::models::{ChannelNote, CreateChannelNoteRequest, NoteTarget};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Repository for notes kept on a node's channels and peers.
+pub struct ChannelNoteRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ChannelNoteRepository<'a> {
+    /// Creates a new ChannelNoteRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Records a note on a channel or peer of a node.
+    pub async fn create_note(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        created_by: &str,
+        request: &CreateChannelNoteRequest,
+    ) -> Result<ChannelNote> {
+        let id = Uuid::now_v7().to_string();
+
+        let note = sqlx::query_as!(
+            ChannelNote,
+            r#"
+            INSERT INTO channel_notes (
+                id, account_id, node_id, target_type, target_id, label, note, created_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            target_type as "target_type!: NoteTarget",
+            target_id as "target_id!",
+            label as "label!",
+            note,
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            id,
+            account_id,
+            node_id,
+            request.target_type,
+            request.target_id,
+            request.label,
+            request.note,
+            created_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(note)
+    }
+
+    /// Retrieves one of a node's notes.
+    pub async fn get_note(
+        &self,
+        id: &str,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<ChannelNote>> {
+        let note = sqlx::query_as!(
+            ChannelNote,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            target_type as "target_type!: NoteTarget",
+            target_id as "target_id!",
+            label as "label!",
+            note,
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM channel_notes
+            WHERE id = ? AND account_id = ? AND node_id = ?
+            "#,
+            id,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(note)
+    }
+
+    /// Lists a node's notes, optionally only those on one kind of target,
+    /// one target or with one label (case-insensitively), oldest first.
+    pub async fn get_notes(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        target_type: Option<NoteTarget>,
+        target_id: Option<&str>,
+        label: Option<&str>,
+    ) -> Result<Vec<ChannelNote>> {
+        let notes = sqlx::query_as!(
+            ChannelNote,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            target_type as "target_type!: NoteTarget",
+            target_id as "target_id!",
+            label as "label!",
+            note,
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM channel_notes
+            WHERE account_id = ? AND node_id = ?
+            AND (? IS NULL OR target_type = ?)
+            AND (? IS NULL OR target_id = ?)
+            AND (? IS NULL OR label = ? COLLATE NOCASE)
+            ORDER BY created_at ASC
+            "#,
+            account_id,
+            node_id,
+            target_type,
+            target_type,
+            target_id,
+            target_id,
+            label,
+            label
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(notes)
+    }
+
+    /// Replaces a note's label and text.
+    pub async fn update_note(&self, id: &str, label: &str, note: Option<&str>) -> Result<bool> {
+        let now = Utc::now();
+        let result = sqlx::query!(
+            "UPDATE channel_notes SET label = ?, note = ?, updated_at = ? WHERE id = ?",
+            label,
+            note,
+            now,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes a note.
+    pub async fn delete_note(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query!("DELETE FROM channel_notes WHERE id = ?", id)
+            .execute(self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -2,10 +2,23 @@
 //!
 //! Provides CRUD operations for node credentials.
 use crate::database::models::{CreateCredential, Credential};
+use crate::utils::crypto::StringCrypto;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 
+/// Decrypts the macaroon and client key of a credential read from the
+/// database. Rows stored before they were encrypted at rest come back as is.
+fn reveal_secrets(mut credential: Credential) -> Result<Credential> {
+    let crypto = StringCrypto::from_env()?;
+    credential.macaroon = crypto.reveal(&credential.macaroon)?;
+    credential.client_key = credential
+        .client_key
+        .map(|key| crypto.reveal(&key))
+        .transpose()?;
+    Ok(credential)
+}
+
 /// Repository for credential database operations.
 ///
 /// Manages persistence of node authentication credentials including:
@@ -37,8 +50,15 @@ impl<'a> CredentialRepository<'a> {
     ///
     /// # Security
     /// - Sets `is_active` to true by default for new credentials
-    /// - Stores sensitive data (macaroon, TLS cert) encrypted at rest
+    /// - Stores the secrets (macaroon or rune, client key) encrypted at rest
     pub async fn create_credential(&self, credential: CreateCredential) -> Result<Credential> {
+        let crypto = StringCrypto::from_env()?;
+        let macaroon = crypto.encrypt(&credential.macaroon)?;
+        let client_key = credential
+            .client_key
+            .as_deref()
+            .map(|key| crypto.encrypt(key))
+            .transpose()?;
         let credential = sqlx::query_as!(
             Credential,
             r#"
@@ -68,19 +88,19 @@ impl<'a> CredentialRepository<'a> {
             credential.account_id,
             credential.node_id,
             credential.node_alias,
-            credential.macaroon,
+            macaroon,
             credential.tls_cert,
             credential.address,
             credential.node_type,
             credential.client_cert,
-            credential.client_key,
+            client_key,
             credential.ca_cert,
             true
         )
         .fetch_one(self.pool)
         .await?;
 
-        Ok(credential)
+        reveal_secrets(credential)
     }
 
     /// Retrieves credentials by their unique identifier.
@@ -123,7 +143,7 @@ impl<'a> CredentialRepository<'a> {
         .fetch_optional(self.pool)
         .await?;
 
-        Ok(credential)
+        credential.map(reveal_secrets).transpose()
     }
 
     /// Retrieves credentials associated with a specific user.
@@ -162,7 +182,7 @@ impl<'a> CredentialRepository<'a> {
         .fetch_optional(self.pool)
         .await?;
 
-        Ok(credential)
+        credential.map(reveal_secrets).transpose()
     }
 
     /// Retrieves all credentials in the system.
@@ -201,7 +221,136 @@ impl<'a> CredentialRepository<'a> {
         .fetch_all(self.pool)
         .await?;
 
-        Ok(credentials)
+        credentials.into_iter().map(reveal_secrets).collect()
+    }
+
+    /// Retrieves the credential for a node within an account.
+    ///
+    /// # Arguments
+    /// * `account_id` - Account that owns the node
+    /// * `node_id` - Node public key
+    ///
+    /// # Returns
+    /// `Some(Credential)` if found and not deleted, `None` otherwise
+    pub async fn get_credential_by_account_and_node_id(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<Credential>> {
+        let credential = sqlx::query_as!(
+            Credential,
+            r#"
+               SELECT
+               id as "id!",
+               user_id as "user_id!",
+               account_id as "account_id!",
+               node_id as "node_id!",
+               node_alias as "node_alias!",
+               macaroon as "macaroon!",
+               tls_cert as "tls_cert!",
+               address as "address!",
+               node_type as "node_type?",
+               client_cert as "client_cert?",
+               client_key as "client_key?",
+               ca_cert as "ca_cert?",
+               is_active as "is_active!",
+               created_at as "created_at!: DateTime<Utc>",
+               updated_at as "updated_at!: DateTime<Utc>",
+               is_deleted as "is_deleted!",
+               deleted_at as "deleted_at?: DateTime<Utc>"
+               FROM credentials
+               WHERE account_id = ? AND node_id = ? AND is_deleted = 0
+               "#,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        credential.map(reveal_secrets).transpose()
+    }
+
+    /// Retrieves all active credentials belonging to an account.
+    ///
+    /// # Arguments
+    /// * `account_id` - Account whose node credentials to fetch
+    ///
+    /// # Security
+    /// - Returns complete credentials including sensitive data
+    pub async fn get_credentials_by_account_id(&self, account_id: &str) -> Result<Vec<Credential>> {
+        let credentials = sqlx::query_as!(
+            Credential,
+            r#"
+               SELECT
+               id as "id!",
+               user_id as "user_id!",
+               account_id as "account_id!",
+               node_id as "node_id!",
+               node_alias as "node_alias!",
+               macaroon as "macaroon!",
+               tls_cert as "tls_cert!",
+               address as "address!",
+               node_type as "node_type?",
+               client_cert as "client_cert?",
+               client_key as "client_key?",
+               ca_cert as "ca_cert?",
+               is_active as "is_active!",
+               created_at as "created_at!: DateTime<Utc>",
+               updated_at as "updated_at!: DateTime<Utc>",
+               is_deleted as "is_deleted!",
+               deleted_at as "deleted_at?: DateTime<Utc>"
+               FROM credentials
+               WHERE account_id = ? AND is_active = 1 AND is_deleted = 0
+               "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        credentials.into_iter().map(reveal_secrets).collect()
+    }
+
+    /// Returns the secrets of every credential as stored, including deleted
+    /// ones, as `(id, macaroon, client_key)`.
+    pub async fn get_stored_secrets(&self) -> Result<Vec<(String, String, Option<String>)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id as "id!", macaroon as "macaroon!", client_key as "client_key?"
+            FROM credentials
+            "#
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.id, row.macaroon, row.client_key))
+            .collect())
+    }
+
+    /// Overwrites the stored secrets of a credential with already encrypted
+    /// values, provided the stored macaroon is still `current_macaroon`.
+    ///
+    /// # Returns
+    /// `true` if the credential was updated
+    pub async fn update_stored_secrets(
+        &self,
+        id: &str,
+        current_macaroon: &str,
+        macaroon: &str,
+        client_key: Option<&str>,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE credentials SET macaroon = ?, client_key = ? WHERE id = ? AND macaroon = ?",
+            macaroon,
+            client_key,
+            id,
+            current_macaroon
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
     }
 
     /// Marks a credential as deleted (soft deletion).
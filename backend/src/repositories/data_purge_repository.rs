@@ -0,0 +1,266 @@
+//! Database repository for user data purge requests.
+
+use crate::database::models::DataPurgeRequest;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for purge confirmations and the purge itself.
+pub struct DataPurgeRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> DataPurgeRepository<'a> {
+    /// Creates a new DataPurgeRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a pending purge request awaiting confirmation.
+    pub async fn create_request(
+        &self,
+        id: &str,
+        account_id: &str,
+        user_id: &str,
+        requested_by: &str,
+        confirmation_token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<DataPurgeRequest> {
+        let request = sqlx::query_as!(
+            DataPurgeRequest,
+            r#"
+            INSERT INTO data_purge_requests (id, account_id, user_id, requested_by, confirmation_token, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            requested_by as "requested_by!",
+            confirmation_token as "confirmation_token!",
+            expires_at as "expires_at!: DateTime<Utc>",
+            confirmed_at as "confirmed_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>"
+            "#,
+            id,
+            account_id,
+            user_id,
+            requested_by,
+            confirmation_token,
+            expires_at
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(request)
+    }
+
+    /// Finds an unconfirmed request for a user by its confirmation token.
+    pub async fn get_pending_request(
+        &self,
+        user_id: &str,
+        confirmation_token: &str,
+    ) -> Result<Option<DataPurgeRequest>> {
+        let request = sqlx::query_as!(
+            DataPurgeRequest,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            requested_by as "requested_by!",
+            confirmation_token as "confirmation_token!",
+            expires_at as "expires_at!: DateTime<Utc>",
+            confirmed_at as "confirmed_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM data_purge_requests
+            WHERE user_id = ? AND confirmation_token = ? AND confirmed_at IS NULL
+            "#,
+            user_id,
+            confirmation_token
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(request)
+    }
+
+    /// Anonymizes a user's personal data, removes what they set up or belong
+    /// to, and confirms their pending requests, atomically.
+    ///
+    /// The user row is kept (with placeholder values) so events and other
+    /// account history that reference it stay intact.
+    pub async fn purge_user_data(
+        &self,
+        user_id: &str,
+        placeholder_password_hash: &str,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let now = Utc::now();
+
+        let (username, email) = anonymized_identity(user_id);
+        let original_email = sqlx::query!(
+            r#"SELECT email as "email!" FROM users WHERE id = ?"#,
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .email;
+
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET username = ?, display_name = NULL, email = ?, password_hash = ?, is_active = 0, is_deleted = 1, deleted_at = ?
+            WHERE id = ?
+            "#,
+            username,
+            email,
+            placeholder_password_hash,
+            now,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Linked LNURL-auth keys identify the user's wallet.
+        sqlx::query!("DELETE FROM lnurl_auth_keys WHERE user_id = ?", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // So do addresses they asked to switch to.
+        sqlx::query!(
+            "DELETE FROM email_change_requests WHERE user_id = ?",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Invites addressed to the user carry their email address.
+        sqlx::query!(
+            "UPDATE invites SET invitee_email = ? WHERE invitee_email = ?",
+            email,
+            original_email
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Notification endpoints the user set up point at their own inboxes and URLs.
+        sqlx::query!(
+            r#"
+            UPDATE notifications
+            SET is_deleted = 1, deleted_at = ?
+            WHERE user_id = ? AND is_deleted = 0
+            "#,
+            now,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Node credentials they stored are deleted, and their secrets dropped with them.
+        sqlx::query!(
+            r#"
+            UPDATE credentials
+            SET macaroon = '', client_key = NULL, is_deleted = 1, deleted_at = ?
+            WHERE user_id = ? AND is_deleted = 0
+            "#,
+            now,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM saved_views WHERE user_id = ?", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // The user leaves every account they were a member of.
+        sqlx::query!("DELETE FROM account_memberships WHERE user_id = ?", user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!("DELETE FROM user_node_scopes WHERE user_id = ?", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // Detach the user from audit entries they made, keeping the entries themselves.
+        sqlx::query!(
+            "UPDATE audit_logs SET actor_user_id = NULL WHERE actor_user_id = ?",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Confirms this request along with any other outstanding ones for the user.
+        sqlx::query!(
+            "UPDATE data_purge_requests SET confirmed_at = ? WHERE user_id = ? AND confirmed_at IS NULL",
+            now,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Placeholder username and email for a purged user. Derived from the ID so
+/// they stay unique without revealing anything about the person.
+fn anonymized_identity(user_id: &str) -> (String, String) {
+    (
+        format!("deleted-user-{user_id}"),
+        format!("deleted-{user_id}@invalid.invalid"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn count(pool: &SqlitePool, sql: &str) -> i64 {
+        sqlx::query_scalar(sql)
+            .bind("user")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_purge_user_data_removes_related_rows(pool: SqlitePool) {
+        for statement in [
+            "INSERT INTO accounts (id, name) VALUES ('home', 'Home'), ('other', 'Other')",
+            "INSERT INTO users (id, account_id, username, password_hash, email, role_id) \
+             VALUES ('user', 'home', 'alice', 'hash', 'alice@example.com', \
+             '01932f4e-8b2b-7a3c-9d5f-2a3b4c5d6e7f')",
+            "INSERT INTO account_memberships (id, user_id, account_id, role_id, created_by) \
+             VALUES ('membership', 'user', 'other', '01932f4e-8b2b-7a3c-9d5f-2a3b4c5d6e7f', 'user')",
+            "INSERT INTO user_node_scopes (user_id, account_id, node_ids) \
+             VALUES ('user', 'other', '[\"node\"]')",
+            "INSERT INTO saved_views (id, account_id, user_id, name, endpoint) \
+             VALUES ('view', 'home', 'user', 'Mine', '/events')",
+            "INSERT INTO notifications (id, account_id, user_id, name, notification_type, url) \
+             VALUES ('notification', 'home', 'user', 'Inbox', 'Email', 'alice@example.com')",
+            "INSERT INTO credentials (id, user_id, account_id, node_id, macaroon, tls_cert, address) \
+             VALUES ('credential', 'user', 'home', 'node', 'secret', 'cert', 'localhost:10009')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        DataPurgeRepository::new(&pool)
+            .purge_user_data("user", "placeholder")
+            .await
+            .unwrap();
+
+        for sql in [
+            "SELECT COUNT(*) FROM account_memberships WHERE user_id = ?",
+            "SELECT COUNT(*) FROM user_node_scopes WHERE user_id = ?",
+            "SELECT COUNT(*) FROM saved_views WHERE user_id = ?",
+            "SELECT COUNT(*) FROM notifications WHERE user_id = ? AND is_deleted = 0",
+            "SELECT COUNT(*) FROM credentials WHERE user_id = ? AND is_deleted = 0",
+            "SELECT COUNT(*) FROM credentials WHERE user_id = ? AND macaroon != ''",
+            "SELECT COUNT(*) FROM users WHERE id = ? AND email = 'alice@example.com'",
+        ] {
+            assert_eq!(count(&pool, sql).await, 0, "{sql}");
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Database repository for email addresses awaiting verification, either a
+//! user's first address or one they're changing to.
+
+use crate::database::models::EmailChangeRequest;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for email verification requests.
+pub struct EmailChangeRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> EmailChangeRepository<'a> {
+    /// Creates a new EmailChangeRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a new request, superseding any earlier unconfirmed ones for the user.
+    pub async fn create_request(
+        &self,
+        id: &str,
+        user_id: &str,
+        new_email: &str,
+        token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<EmailChangeRequest> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM email_change_requests WHERE user_id = ? AND confirmed_at IS NULL",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let request = sqlx::query_as!(
+            EmailChangeRequest,
+            r#"
+            INSERT INTO email_change_requests (id, user_id, new_email, token, expires_at)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            user_id as "user_id!",
+            new_email as "new_email!",
+            token as "token!",
+            expires_at as "expires_at!: DateTime<Utc>",
+            confirmed_at as "confirmed_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>"
+            "#,
+            id,
+            user_id,
+            new_email,
+            token,
+            expires_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(request)
+    }
+
+    /// Finds an unconfirmed request by its verification token.
+    pub async fn get_pending_request_by_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<EmailChangeRequest>> {
+        let request = sqlx::query_as!(
+            EmailChangeRequest,
+            r#"
+            SELECT
+            id as "id!",
+            user_id as "user_id!",
+            new_email as "new_email!",
+            token as "token!",
+            expires_at as "expires_at!: DateTime<Utc>",
+            confirmed_at as "confirmed_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM email_change_requests
+            WHERE token = ? AND confirmed_at IS NULL
+            "#,
+            token
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(request)
+    }
+
+    /// Returns the address a user is changing to, if a request is still open.
+    pub async fn get_pending_email(&self, user_id: &str) -> Result<Option<String>> {
+        let now = Utc::now();
+        let row = sqlx::query!(
+            r#"
+            SELECT new_email as "new_email!"
+            FROM email_change_requests
+            WHERE user_id = ? AND confirmed_at IS NULL AND expires_at > ?
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            user_id,
+            now
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.new_email))
+    }
+
+    /// Switches the user to the request's address, marks it verified and the
+    /// request confirmed, atomically.
+    pub async fn apply_request(&self, request: &EmailChangeRequest) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let now = Utc::now();
+
+        sqlx::query!(
+            "UPDATE users SET email = ?, email_verified_at = ? WHERE id = ? AND is_deleted = 0",
+            request.new_email,
+            now,
+            request.user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE email_change_requests SET confirmed_at = ? WHERE id = ?",
+            now,
+            request.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+//! Database repository for alert escalation policies and their escalations.
+
+use crate::database::models::{Escalation, EscalationPolicy, EscalationStatus};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for escalation policies and the escalations they start.
+pub struct EscalationRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> EscalationRepository<'a> {
+    /// Creates a new EscalationRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a policy.
+    pub async fn create_policy(&self, policy: &EscalationPolicy) -> Result<EscalationPolicy> {
+        let created = sqlx::query_as!(
+            EscalationPolicy,
+            r#"
+            INSERT INTO escalation_policies (id, account_id, name, node_id, steps, is_active, created_by)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            name as "name!",
+            node_id,
+            steps as "steps!",
+            is_active as "is_active!",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            policy.id,
+            policy.account_id,
+            policy.name,
+            policy.node_id,
+            policy.steps,
+            policy.is_active,
+            policy.created_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    /// Lists an account's policies by name.
+    pub async fn get_policies(&self, account_id: &str) -> Result<Vec<EscalationPolicy>> {
+        let policies = sqlx::query_as!(
+            EscalationPolicy,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            name as "name!",
+            node_id,
+            steps as "steps!",
+            is_active as "is_active!",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM escalation_policies
+            WHERE account_id = ?
+            ORDER BY name ASC
+            "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(policies)
+    }
+
+    /// Retrieves one of an account's policies.
+    pub async fn get_policy(&self, id: &str, account_id: &str) -> Result<Option<EscalationPolicy>> {
+        let policy = sqlx::query_as!(
+            EscalationPolicy,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            name as "name!",
+            node_id,
+            steps as "steps!",
+            is_active as "is_active!",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM escalation_policies
+            WHERE id = ? AND account_id = ?
+            "#,
+            id,
+            account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Checks whether another of the account's policies has this name.
+    pub async fn name_exists(&self, account_id: &str, name: &str, except_id: &str) -> Result<bool> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!: i64" FROM escalation_policies
+            WHERE account_id = ? AND name = ? AND id != ?
+            "#,
+            account_id,
+            name,
+            except_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(row.count > 0)
+    }
+
+    /// Replaces a policy's settings.
+    pub async fn update_policy(
+        &self,
+        policy: &EscalationPolicy,
+    ) -> Result<Option<EscalationPolicy>> {
+        let updated = sqlx::query_as!(
+            EscalationPolicy,
+            r#"
+            UPDATE escalation_policies
+            SET name = ?, node_id = ?, steps = ?, is_active = ?
+            WHERE id = ? AND account_id = ?
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            name as "name!",
+            node_id,
+            steps as "steps!",
+            is_active as "is_active!",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            policy.name,
+            policy.node_id,
+            policy.steps,
+            policy.is_active,
+            policy.id,
+            policy.account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(updated)
+    }
+
+    /// Deletes a policy, and with it its escalations.
+    pub async fn delete_policy(&self, id: &str, account_id: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM escalation_policies WHERE id = ? AND account_id = ?",
+            id,
+            account_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stores an escalation.
+    pub async fn create_escalation(&self, escalation: &Escalation) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO escalations (
+                id, account_id, policy_id, event_id, node_id, status, steps_sent, next_step_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            escalation.id,
+            escalation.account_id,
+            escalation.policy_id,
+            escalation.event_id,
+            escalation.node_id,
+            escalation.status,
+            escalation.steps_sent,
+            escalation.next_step_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves one of an account's escalations.
+    pub async fn get_escalation(&self, id: &str, account_id: &str) -> Result<Option<Escalation>> {
+        let escalation = sqlx::query_as!(
+            Escalation,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            policy_id as "policy_id!",
+            event_id as "event_id!",
+            node_id as "node_id!",
+            status as "status: EscalationStatus",
+            steps_sent as "steps_sent!",
+            next_step_at as "next_step_at?: DateTime<Utc>",
+            acknowledged_by,
+            acknowledged_at as "acknowledged_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM escalations
+            WHERE id = ? AND account_id = ?
+            "#,
+            id,
+            account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(escalation)
+    }
+
+    /// Lists an account's escalations, newest first.
+    pub async fn get_escalations(
+        &self,
+        account_id: &str,
+        status: Option<EscalationStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Escalation>> {
+        let escalations = sqlx::query_as!(
+            Escalation,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            policy_id as "policy_id!",
+            event_id as "event_id!",
+            node_id as "node_id!",
+            status as "status: EscalationStatus",
+            steps_sent as "steps_sent!",
+            next_step_at as "next_step_at?: DateTime<Utc>",
+            acknowledged_by,
+            acknowledged_at as "acknowledged_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM escalations
+            WHERE account_id = ? AND (? IS NULL OR status = ?)
+            ORDER BY created_at DESC
+            LIMIT ? OFFSET ?
+            "#,
+            account_id,
+            status,
+            status,
+            limit,
+            offset
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(escalations)
+    }
+
+    /// Records a step as notified, scheduling the next one or, without one,
+    /// ending the escalation as exhausted. Does nothing unless the
+    /// escalation is still pending.
+    pub async fn record_step(
+        &self,
+        id: &str,
+        steps_sent: i64,
+        next_step_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let status = match next_step_at {
+            Some(_) => EscalationStatus::Pending,
+            None => EscalationStatus::Exhausted,
+        };
+        let result = sqlx::query!(
+            r#"
+            UPDATE escalations
+            SET steps_sent = ?, next_step_at = ?, status = ?
+            WHERE id = ? AND status = ?
+            "#,
+            steps_sent,
+            next_step_at,
+            status,
+            id,
+            EscalationStatus::Pending
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Cancels a pending escalation.
+    pub async fn cancel_escalation(&self, id: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE escalations SET status = ?, next_step_at = NULL
+            WHERE id = ? AND status = ?
+            "#,
+            EscalationStatus::Cancelled,
+            id,
+            EscalationStatus::Pending
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Acknowledges a pending escalation, stopping its remaining steps.
+    /// Returns whether it was pending.
+    pub async fn acknowledge_escalation(
+        &self,
+        id: &str,
+        account_id: &str,
+        user_id: &str,
+        acknowledged_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE escalations
+            SET status = ?, next_step_at = NULL, acknowledged_by = ?, acknowledged_at = ?
+            WHERE id = ? AND account_id = ? AND status = ?
+            "#,
+            EscalationStatus::Acknowledged,
+            user_id,
+            acknowledged_at,
+            id,
+            account_id,
+            EscalationStatus::Pending
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
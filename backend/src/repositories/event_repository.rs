@@ -1,7 +1,7 @@
 //! Database repository for event management operations.
 
 use crate::database::models::{
-    CreateEvent, Event, EventFilters, EventResponse, EventSeverity, EventType,
+    CreateEvent, Event, EventCountRow, EventFilters, EventResponse, EventSeverity, EventType,
 };
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -63,7 +63,94 @@ impl<'a> EventRepository<'a> {
         Ok(event)
     }
 
-    /// Retrieves events by account ID with basic filtering.
+    /// Creates several events in one transaction, so a burst of node events
+    /// takes the SQLite write lock once instead of once per event.
+    pub async fn create_events(&self, events: Vec<CreateEvent>) -> Result<Vec<Event>> {
+        let mut tx = self.pool.begin().await?;
+        let mut created = Vec::with_capacity(events.len());
+
+        for event in events {
+            let event = sqlx::query_as!(
+                Event,
+                r#"
+                INSERT INTO events (id, account_id, user_id, node_id, node_alias, event_type, severity, title, description, data, notifications_id, timestamp)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                RETURNING
+                id as "id!",
+                account_id as "account_id!",
+                user_id as "user_id!",
+                node_id as "node_id!",
+                node_alias as "node_alias!",
+                event_type as "event_type: EventType",
+                severity as "severity: EventSeverity",
+                title as "title!",
+                description as "description!",
+                data as "data!",
+                notifications_id as "notifications_id!",
+                timestamp as "timestamp!: DateTime<Utc>",
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>",
+                is_deleted as "is_deleted!",
+                deleted_at as "deleted_at?: DateTime<Utc>"
+                "#,
+                event.id,
+                event.account_id,
+                event.user_id,
+                event.node_id,
+                event.node_alias,
+                event.event_type,
+                event.severity,
+                event.title,
+                event.description,
+                event.data,
+                event.notifications_id,
+                event.timestamp
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            created.push(event);
+        }
+
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    /// Retrieves a single event by ID, scoped to the owning account.
+    pub async fn get_event_by_id(&self, id: &str, account_id: &str) -> Result<Option<Event>> {
+        let event = sqlx::query_as!(
+            Event,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            node_id as "node_id!",
+            node_alias as "node_alias!",
+            event_type as "event_type: EventType",
+            severity as "severity: EventSeverity",
+            title as "title!",
+            description as "description!",
+            data as "data!",
+            notifications_id as "notifications_id?",
+            timestamp as "timestamp!: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>"
+            FROM events
+            WHERE id = ? AND account_id = ? AND is_deleted = 0
+            "#,
+            id,
+            account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(event)
+    }
+
+    /// Retrieves events by account ID, limited to `filters.node_ids` when set,
+    /// with limit and offset.
     pub async fn get_events_by_account_id(
         &self,
         account_id: &str,
@@ -82,6 +169,7 @@ impl<'a> EventRepository<'a> {
         // Simple implementation without complex dynamic queries
         let limit = filters.limit.unwrap_or(50).min(1000);
         let offset = filters.offset.unwrap_or(0);
+        let node_ids = node_ids_json(filters.node_ids.as_deref())?;
 
         let events = sqlx::query_as!(
             Event,
@@ -105,10 +193,107 @@ impl<'a> EventRepository<'a> {
             deleted_at as "deleted_at?: DateTime<Utc>"
             FROM events
             WHERE account_id = ? AND is_deleted = 0
+            AND (? IS NULL OR node_id IN (SELECT value FROM json_each(?)))
             ORDER BY timestamp DESC
             LIMIT ? OFFSET ?
             "#,
             account_id,
+            node_ids,
+            node_ids,
+            limit,
+            offset
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Lists an account's events since `since`, oldest first, up to `limit`.
+    pub async fn get_events_since(
+        &self,
+        account_id: &str,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let events = sqlx::query_as!(
+            Event,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            node_id as "node_id!",
+            node_alias as "node_alias!",
+            event_type as "event_type: EventType",
+            severity as "severity: EventSeverity",
+            title as "title!",
+            description as "description!",
+            notifications_id as "notifications_id!",
+            data as "data!",
+            timestamp as "timestamp!: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>"
+            FROM events
+            WHERE account_id = ? AND timestamp >= ? AND is_deleted = 0
+            ORDER BY timestamp ASC
+            LIMIT ?
+            "#,
+            account_id,
+            since,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Full-text searches an account's events, limited to `node_ids` when set.
+    ///
+    /// `match_query` is an FTS5 query over title, description and JSON data values.
+    pub async fn search_events_by_account_id(
+        &self,
+        account_id: &str,
+        match_query: &str,
+        node_ids: Option<&[String]>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Event>> {
+        let node_ids = node_ids_json(node_ids)?;
+        let events = sqlx::query_as!(
+            Event,
+            r#"
+            SELECT
+            e.id as "id!",
+            e.account_id as "account_id!",
+            e.user_id as "user_id!",
+            e.node_id as "node_id!",
+            e.node_alias as "node_alias!",
+            e.event_type as "event_type: EventType",
+            e.severity as "severity: EventSeverity",
+            e.title as "title!",
+            e.description as "description!",
+            e.notifications_id as "notifications_id?",
+            e.data as "data!",
+            e.timestamp as "timestamp!: DateTime<Utc>",
+            e.created_at as "created_at!: DateTime<Utc>",
+            e.updated_at as "updated_at!: DateTime<Utc>",
+            e.is_deleted as "is_deleted!",
+            e.deleted_at as "deleted_at?: DateTime<Utc>"
+            FROM events_fts f
+            JOIN events e ON e.rowid = f.rowid
+            WHERE events_fts MATCH ? AND e.account_id = ? AND e.is_deleted = 0
+            AND (? IS NULL OR e.node_id IN (SELECT value FROM json_each(?)))
+            ORDER BY e.timestamp DESC
+            LIMIT ? OFFSET ?
+            "#,
+            match_query,
+            account_id,
+            node_ids,
+            node_ids,
             limit,
             offset
         )
@@ -118,15 +303,49 @@ impl<'a> EventRepository<'a> {
         Ok(events)
     }
 
-    /// Gets event count by account ID.
+    /// Counts the events matched by [`Self::search_events_by_account_id`].
+    pub async fn count_search_events_by_account_id(
+        &self,
+        account_id: &str,
+        match_query: &str,
+        node_ids: Option<&[String]>,
+    ) -> Result<i64> {
+        let node_ids = node_ids_json(node_ids)?;
+        let result = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as count
+            FROM events_fts f
+            JOIN events e ON e.rowid = f.rowid
+            WHERE events_fts MATCH ? AND e.account_id = ? AND e.is_deleted = 0
+            AND (? IS NULL OR e.node_id IN (SELECT value FROM json_each(?)))
+            "#,
+            match_query,
+            account_id,
+            node_ids,
+            node_ids
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(result.count)
+    }
+
+    /// Counts the events matched by [`Self::get_events_by_account_id`].
     pub async fn count_events_by_account_id(
         &self,
         account_id: &str,
-        _filters: Option<EventFilters>,
+        filters: Option<EventFilters>,
     ) -> Result<i64> {
+        let node_ids = node_ids_json(filters.and_then(|f| f.node_ids).as_deref())?;
         let result = sqlx::query!(
-            "SELECT COUNT(*) as count FROM events WHERE account_id = ? AND is_deleted = 0",
-            account_id
+            r#"
+            SELECT COUNT(*) as count FROM events
+            WHERE account_id = ? AND is_deleted = 0
+            AND (? IS NULL OR node_id IN (SELECT value FROM json_each(?)))
+            "#,
+            account_id,
+            node_ids,
+            node_ids
         )
         .fetch_one(self.pool)
         .await?;
@@ -222,16 +441,70 @@ impl<'a> EventRepository<'a> {
         Ok(events)
     }
 
-    /// Count events by severity.
-    pub async fn count_events_by_account_and_severity(
+    /// Counts the events of an account in `[from, to)` by severity, event
+    /// type and node.
+    pub async fn get_event_counts(
+        &self,
+        account_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<EventCountRow>> {
+        let rows = sqlx::query_as!(
+            EventCountRow,
+            r#"
+            SELECT
+            severity as "severity!: EventSeverity",
+            event_type as "event_type!: EventType",
+            node_id as "node_id!",
+            MAX(node_alias) as "node_alias!: String",
+            COUNT(*) as "count!: i64"
+            FROM events
+            WHERE account_id = ? AND timestamp >= ? AND timestamp < ? AND is_deleted = 0
+            GROUP BY severity, event_type, node_id
+            "#,
+            account_id,
+            from,
+            to
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Counts events of the given severity recorded since `since`.
+    pub async fn count_events_by_account_and_severity_since(
         &self,
         account_id: &str,
         severity: &EventSeverity,
+        since: DateTime<Utc>,
     ) -> Result<i64> {
         let result = sqlx::query!(
-            "SELECT COUNT(*) as count FROM events WHERE account_id = ? AND severity = ? AND is_deleted = 0",
+            "SELECT COUNT(*) as count FROM events WHERE account_id = ? AND severity = ? AND timestamp >= ? AND is_deleted = 0",
             account_id,
-            severity
+            severity,
+            since
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(result.count)
+    }
+
+    /// Counts events of one type a node raised since `since`.
+    pub async fn count_node_events_since(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        event_type: &EventType,
+        since: DateTime<Utc>,
+    ) -> Result<i64> {
+        let result = sqlx::query!(
+            "SELECT COUNT(*) as count FROM events WHERE account_id = ? AND node_id = ? AND event_type = ? AND timestamp >= ? AND is_deleted = 0",
+            account_id,
+            node_id,
+            event_type,
+            since
         )
         .fetch_one(self.pool)
         .await?;
@@ -239,6 +512,167 @@ impl<'a> EventRepository<'a> {
         Ok(result.count)
     }
 
+    /// Totals forwards the HTLC interceptor failed since `since`, per outgoing
+    /// channel, as (chan_id, count, outgoing msat).
+    pub async fn get_failed_forwards_by_channel(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(String, i64, i64)>> {
+        let event_type = EventType::HtlcIntercepted;
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+            CAST(json_extract(data, '$.outgoing_chan_id') AS TEXT) as "chan_id!: String",
+            COUNT(*) as "count!: i64",
+            COALESCE(SUM(json_extract(data, '$.outgoing_amount_msat')), 0) as "amount_msat!: i64"
+            FROM events
+            WHERE account_id = ? AND node_id = ? AND event_type = ? AND timestamp >= ?
+            AND json_extract(data, '$.resumed') = 0 AND is_deleted = 0
+            GROUP BY json_extract(data, '$.outgoing_chan_id')
+            "#,
+            account_id,
+            node_id,
+            event_type,
+            since
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.chan_id, r.count, r.amount_msat))
+            .collect())
+    }
+
+    /// Counts failed forwards since `since` per outgoing channel and UTC hour
+    /// of day, as (chan_id, hour, count).
+    pub async fn get_forward_failures_by_hour(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(String, i64, i64)>> {
+        let event_type = EventType::ForwardFailed;
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+            CAST(json_extract(data, '$.outgoing_chan_id') AS TEXT) as "chan_id!: String",
+            CAST(strftime('%H', timestamp) AS INTEGER) as "hour!: i64",
+            COUNT(*) as "count!: i64"
+            FROM events
+            WHERE account_id = ? AND node_id = ? AND event_type = ? AND timestamp >= ?
+            AND is_deleted = 0
+            GROUP BY 1, 2
+            "#,
+            account_id,
+            node_id,
+            event_type,
+            since
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.chan_id, r.hour, r.count))
+            .collect())
+    }
+
+    /// Gets a node's failed forwards in a time range, newest first, as
+    /// (event id, timestamp, data) rows. With a channel, only failures into
+    /// or out of it.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_forward_failures(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        channel_id: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<(String, DateTime<Utc>, String)>> {
+        let event_type = EventType::ForwardFailed;
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+            id as "id!",
+            timestamp as "timestamp!: DateTime<Utc>",
+            data as "data!"
+            FROM events
+            WHERE account_id = ? AND node_id = ? AND event_type = ?
+            AND timestamp >= ? AND timestamp <= ? AND is_deleted = 0
+            AND (? IS NULL
+                OR CAST(json_extract(data, '$.incoming_chan_id') AS TEXT) = ?
+                OR CAST(json_extract(data, '$.outgoing_chan_id') AS TEXT) = ?)
+            ORDER BY timestamp DESC
+            LIMIT ? OFFSET ?
+            "#,
+            account_id,
+            node_id,
+            event_type,
+            from,
+            to,
+            channel_id,
+            channel_id,
+            channel_id,
+            limit,
+            offset
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.id, r.timestamp, r.data))
+            .collect())
+    }
+
+    /// Counts a node's failed forwards in a time range by where they failed
+    /// and why, as (link_failure, reason, count) rows.
+    pub async fn count_forward_failures_by_reason(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        channel_id: Option<&str>,
+    ) -> Result<Vec<(bool, String, i64)>> {
+        let event_type = EventType::ForwardFailed;
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+            COALESCE(json_extract(data, '$.link_failure'), 0) as "link_failure!: bool",
+            COALESCE(json_extract(data, '$.reason'), '') as "reason!: String",
+            COUNT(*) as "count!: i64"
+            FROM events
+            WHERE account_id = ? AND node_id = ? AND event_type = ?
+            AND timestamp >= ? AND timestamp <= ? AND is_deleted = 0
+            AND (? IS NULL
+                OR CAST(json_extract(data, '$.incoming_chan_id') AS TEXT) = ?
+                OR CAST(json_extract(data, '$.outgoing_chan_id') AS TEXT) = ?)
+            GROUP BY 1, 2
+            "#,
+            account_id,
+            node_id,
+            event_type,
+            from,
+            to,
+            channel_id,
+            channel_id,
+            channel_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.link_failure, r.reason, r.count))
+            .collect())
+    }
+
     /// Gets events by notification ID.
     pub async fn get_events_by_notification_id(
         &self,
@@ -287,6 +721,93 @@ impl<'a> EventRepository<'a> {
         Ok(event_responses)
     }
 
+    /// Lists the events recorded for an endpoint after the event `after`,
+    /// oldest first. Event ids are time-ordered, so they double as a cursor.
+    pub async fn get_events_by_notification_id_after(
+        &self,
+        notifications_id: &str,
+        after: &str,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let events = sqlx::query_as!(
+            Event,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            node_id as "node_id!",
+            node_alias as "node_alias!",
+            event_type as "event_type: EventType",
+            severity as "severity: EventSeverity",
+            title as "title!",
+            description as "description!",
+            data as "data!",
+            timestamp as "timestamp!: DateTime<Utc>",
+            notifications_id as "notifications_id?",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>"
+            FROM events
+            WHERE notifications_id = ? AND id > ? AND is_deleted = 0
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+            notifications_id,
+            after,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Lists a node's events after the event `after`, oldest first.
+    pub async fn get_node_events_after(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        after: &str,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let events = sqlx::query_as!(
+            Event,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            node_id as "node_id!",
+            node_alias as "node_alias!",
+            event_type as "event_type: EventType",
+            severity as "severity: EventSeverity",
+            title as "title!",
+            description as "description!",
+            data as "data!",
+            timestamp as "timestamp!: DateTime<Utc>",
+            notifications_id as "notifications_id?",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>"
+            FROM events
+            WHERE account_id = ? AND node_id = ? AND id > ? AND is_deleted = 0
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+            account_id,
+            node_id,
+            after,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
     /// Gets event count by notification ID.
     pub async fn count_events_by_notification_id(&self, notifications_id: &str) -> Result<i64> {
         let result = sqlx::query!(
@@ -299,3 +820,9 @@ impl<'a> EventRepository<'a> {
         Ok(result.count)
     }
 }
+
+/// Encodes a node filter as a JSON array for `json_each`, leaving `None` to
+/// match every node.
+fn node_ids_json(node_ids: Option<&[String]>) -> Result<Option<String>> {
+    Ok(node_ids.map(serde_json::to_string).transpose()?)
+}
@@ -0,0 +1,85 @@
+//! Database repository for paused node event subscriptions.
+
+use crate::database::models::PausedEventType;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for the event types an account has stopped ingesting per node.
+pub struct EventSubscriptionRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> EventSubscriptionRepository<'a> {
+    /// Creates a new EventSubscriptionRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the paused event types of a node.
+    pub async fn get_paused_types(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Vec<PausedEventType>> {
+        let paused = sqlx::query_as!(
+            PausedEventType,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            event_type as "event_type!",
+            paused_by as "paused_by!",
+            paused_at as "paused_at!: DateTime<Utc>"
+            FROM paused_event_types WHERE account_id = ? AND node_id = ?
+            ORDER BY event_type
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(paused)
+    }
+
+    /// Pauses an event type, keeping the original pause if it already was.
+    pub async fn pause(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        event_type: &str,
+        paused_by: &str,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO paused_event_types (account_id, node_id, event_type, paused_by)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(account_id, node_id, event_type) DO NOTHING
+            "#,
+            account_id,
+            node_id,
+            event_type,
+            paused_by
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Resumes an event type. Returns whether it was paused.
+    pub async fn resume(&self, account_id: &str, node_id: &str, event_type: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM paused_event_types WHERE account_id = ? AND node_id = ? AND event_type = ?",
+            account_id,
+            node_id,
+            event_type
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
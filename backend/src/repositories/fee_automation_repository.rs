@@ -0,0 +1,176 @@
+//! Database repository for fee automation policies and their change history.
+
+use crate::database::models::{FeeAutomationPolicy, FeePolicyChange};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for per-node fee rules and the changes they made.
+pub struct FeeAutomationRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> FeeAutomationRepository<'a> {
+    /// Creates a new FeeAutomationRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves the policy of one of an account's nodes, if one was saved.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<FeeAutomationPolicy>> {
+        let policy = sqlx::query_as!(
+            FeeAutomationPolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            dry_run as "dry_run!",
+            rules as "rules!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM fee_automation_policies WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Lists the enabled policies of an account's nodes.
+    pub async fn get_enabled_policies(&self, account_id: &str) -> Result<Vec<FeeAutomationPolicy>> {
+        let policies = sqlx::query_as!(
+            FeeAutomationPolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            dry_run as "dry_run!",
+            rules as "rules!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM fee_automation_policies WHERE account_id = ? AND is_enabled = 1
+            "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(policies)
+    }
+
+    /// Creates or replaces a node's policy.
+    pub async fn upsert_policy(&self, policy: &FeeAutomationPolicy) -> Result<FeeAutomationPolicy> {
+        let saved = sqlx::query_as!(
+            FeeAutomationPolicy,
+            r#"
+            INSERT INTO fee_automation_policies (
+                account_id, node_id, is_enabled, dry_run, rules, updated_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, node_id) DO UPDATE SET
+                is_enabled = excluded.is_enabled,
+                dry_run = excluded.dry_run,
+                rules = excluded.rules,
+                updated_by = excluded.updated_by
+            RETURNING
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            dry_run as "dry_run!",
+            rules as "rules!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            policy.account_id,
+            policy.node_id,
+            policy.is_enabled,
+            policy.dry_run,
+            policy.rules,
+            policy.updated_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+
+    /// Records a change made or proposed by a run.
+    pub async fn create_change(&self, change: &FeePolicyChange) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO fee_policy_changes (
+                id, account_id, node_id, channel_id, rule_index, local_balance_pct,
+                old_base_fee_msat, old_fee_rate_ppm, new_base_fee_msat, new_fee_rate_ppm,
+                dry_run, error, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            change.id,
+            change.account_id,
+            change.node_id,
+            change.channel_id,
+            change.rule_index,
+            change.local_balance_pct,
+            change.old_base_fee_msat,
+            change.old_fee_rate_ppm,
+            change.new_base_fee_msat,
+            change.new_fee_rate_ppm,
+            change.dry_run,
+            change.error,
+            change.created_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists a node's most recent changes, newest first.
+    pub async fn get_changes(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        limit: i64,
+    ) -> Result<Vec<FeePolicyChange>> {
+        let changes = sqlx::query_as!(
+            FeePolicyChange,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            channel_id as "channel_id!",
+            rule_index as "rule_index!",
+            local_balance_pct as "local_balance_pct!: f64",
+            old_base_fee_msat as "old_base_fee_msat?",
+            old_fee_rate_ppm as "old_fee_rate_ppm?",
+            new_base_fee_msat as "new_base_fee_msat!",
+            new_fee_rate_ppm as "new_fee_rate_ppm!",
+            dry_run as "dry_run!",
+            error as "error?",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM fee_policy_changes
+            WHERE account_id = ? AND node_id = ?
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+            account_id,
+            node_id,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(changes)
+    }
+}
@@ -0,0 +1,232 @@
+//! Database repository for settled forwards.
+
+use crate::database::models::{ChannelPairFlow, ForwardRecord, StoredForward};
+use crate::utils::Forward;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// How far apart the stream's and the history's timestamps of one forward can
+/// be for a history entry without HTLC ids to count as already stored
+const DUPLICATE_WINDOW_SECS: i64 = 60;
+
+/// Repository for the forwards table.
+pub struct ForwardRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ForwardRepository<'a> {
+    /// Creates a new ForwardRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a settled forward unless it was already recorded, either under
+    /// the same circuit or, for forwards without HTLC ids, as a forward over
+    /// the same channels with the same amounts settled around the same time.
+    /// Returns whether the forward was new.
+    pub async fn insert_forward(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        forward: &Forward,
+    ) -> Result<bool> {
+        let id = Uuid::now_v7().to_string();
+        let chan_id_in = forward.chan_id_in.to_string();
+        let chan_id_out = forward.chan_id_out.to_string();
+        let incoming_htlc_id = forward.incoming_htlc_id.map(|id| id as i64);
+        let outgoing_htlc_id = forward.outgoing_htlc_id.map(|id| id as i64);
+        let amt_in_msat = forward.amt_in_msat as i64;
+        let amt_out_msat = forward.amt_out_msat as i64;
+        let fee_msat = forward.fee_msat as i64;
+        let received_at = forward
+            .received_at
+            .and_then(|at| DateTime::<Utc>::from_timestamp(at as i64, 0));
+        let settled_at = DateTime::<Utc>::from_timestamp(forward.timestamp as i64, 0)
+            .ok_or_else(|| anyhow!("Invalid settle time {}", forward.timestamp))?;
+
+        let result = sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO forwards (
+                id, account_id, node_id, chan_id_in, chan_id_out, incoming_htlc_id,
+                outgoing_htlc_id, amt_in_msat, amt_out_msat, fee_msat, received_at, settled_at
+            )
+            SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12
+            WHERE ?6 IS NOT NULL OR NOT EXISTS (
+                SELECT 1 FROM forwards
+                WHERE account_id = ?2 AND node_id = ?3 AND chan_id_in = ?4 AND chan_id_out = ?5
+                AND amt_out_msat = ?9 AND fee_msat = ?10
+                AND ABS(julianday(settled_at) - julianday(?12)) * 86400 <= ?13
+            )
+            "#,
+            id,
+            account_id,
+            node_id,
+            chan_id_in,
+            chan_id_out,
+            incoming_htlc_id,
+            outgoing_htlc_id,
+            amt_in_msat,
+            amt_out_msat,
+            fee_msat,
+            received_at,
+            settled_at,
+            DUPLICATE_WINDOW_SECS
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Lists the forwards that settled since `since` with `chan_id` as either
+    /// leg, oldest first.
+    pub async fn get_channel_forwards_since(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        chan_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ForwardRecord>> {
+        let forwards = sqlx::query_as!(
+            ForwardRecord,
+            r#"
+            SELECT
+            chan_id_in as "chan_id_in!",
+            chan_id_out as "chan_id_out!",
+            amt_in_msat as "amt_in_msat!",
+            amt_out_msat as "amt_out_msat!",
+            fee_msat as "fee_msat!",
+            settled_at as "settled_at!: DateTime<Utc>"
+            FROM forwards
+            WHERE account_id = ?1 AND node_id = ?2 AND settled_at >= ?4
+            AND (chan_id_in = ?3 OR chan_id_out = ?3)
+            ORDER BY settled_at ASC
+            "#,
+            account_id,
+            node_id,
+            chan_id,
+            since
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(forwards)
+    }
+
+    /// Lists a node's stored forwards after the forward `after`, in id order.
+    pub async fn get_forwards_after(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        after: &str,
+        limit: i64,
+    ) -> Result<Vec<StoredForward>> {
+        let forwards = sqlx::query_as!(
+            StoredForward,
+            r#"
+            SELECT
+            id as "id!",
+            chan_id_in as "chan_id_in!",
+            chan_id_out as "chan_id_out!",
+            incoming_htlc_id as "incoming_htlc_id?: i64",
+            outgoing_htlc_id as "outgoing_htlc_id?: i64",
+            amt_in_msat as "amt_in_msat!",
+            amt_out_msat as "amt_out_msat!",
+            fee_msat as "fee_msat!",
+            received_at as "received_at?: DateTime<Utc>",
+            settled_at as "settled_at!: DateTime<Utc>"
+            FROM forwards
+            WHERE account_id = ? AND node_id = ? AND id > ?
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+            account_id,
+            node_id,
+            after,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(forwards)
+    }
+
+    /// Sums a node's forwards that settled in a time range per pair of
+    /// incoming and outgoing channels.
+    pub async fn get_channel_pair_flows(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ChannelPairFlow>> {
+        let flows = sqlx::query_as!(
+            ChannelPairFlow,
+            r#"
+            SELECT
+            chan_id_in as "chan_id_in!",
+            chan_id_out as "chan_id_out!",
+            COUNT(*) as "forwards!: i64",
+            SUM(amt_out_msat) as "amount_msat!: i64",
+            SUM(fee_msat) as "fee_msat!: i64"
+            FROM forwards
+            WHERE account_id = ? AND node_id = ? AND settled_at >= ? AND settled_at <= ?
+            GROUP BY chan_id_in, chan_id_out
+            "#,
+            account_id,
+            node_id,
+            from,
+            to
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(flows)
+    }
+
+    /// Returns up to when a node's forwarding history has been backfilled.
+    pub async fn get_backfilled_until(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let result = sqlx::query!(
+            r#"
+            SELECT backfilled_until as "backfilled_until!: DateTime<Utc>"
+            FROM forward_backfills WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result.map(|row| row.backfilled_until))
+    }
+
+    /// Records that a node's forwarding history has been backfilled up to `until`.
+    pub async fn set_backfilled_until(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        until: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO forward_backfills (account_id, node_id, backfilled_until)
+            VALUES (?, ?, ?)
+            ON CONFLICT(account_id, node_id) DO UPDATE SET backfilled_until = excluded.backfilled_until
+            "#,
+            account_id,
+            node_id,
+            until
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,548 @@
+//! Database repository for the local channel graph mirror.
+
+use crate::database::models::{GraphNodeDetails, GraphSummary, WatchedChannel};
+use crate::utils::{GraphChannel, GraphNode, GraphUpdate, NetworkGraph};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::collections::HashMap;
+
+/// Repository for mirrored graph nodes, channels and policies.
+pub struct GraphRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> GraphRepository<'a> {
+    /// Creates a new GraphRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Replaces everything mirrored from a node with a fresh copy of its graph.
+    pub async fn replace_graph(&self, source_node_id: &str, graph: &NetworkGraph) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM graph_policies WHERE source_node_id = ?",
+            source_node_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM graph_channels WHERE source_node_id = ?",
+            source_node_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM graph_nodes WHERE source_node_id = ?",
+            source_node_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for node in &graph.nodes {
+            upsert_node(&mut tx, source_node_id, node).await?;
+        }
+        for channel in &graph.channels {
+            upsert_channel(&mut tx, source_node_id, channel).await?;
+        }
+
+        let now = Utc::now();
+        sqlx::query!(
+            r#"
+            INSERT INTO graph_sync_state (source_node_id, last_full_sync_at, last_update_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(source_node_id) DO UPDATE SET
+                last_full_sync_at = excluded.last_full_sync_at,
+                last_update_at = excluded.last_update_at
+            "#,
+            source_node_id,
+            now,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Applies a single topology change on top of the mirror.
+    pub async fn apply_update(&self, source_node_id: &str, update: &GraphUpdate) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        match update {
+            GraphUpdate::Node(node) => upsert_node(&mut tx, source_node_id, node).await?,
+            GraphUpdate::Channel(channel) => {
+                upsert_channel(&mut tx, source_node_id, channel).await?
+            }
+            GraphUpdate::ChannelClosed(chan_id) => {
+                let chan_id = chan_id.to_string();
+                sqlx::query!(
+                    "DELETE FROM graph_policies WHERE source_node_id = ? AND chan_id = ?",
+                    source_node_id,
+                    chan_id
+                )
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query!(
+                    "DELETE FROM graph_channels WHERE source_node_id = ? AND chan_id = ?",
+                    source_node_id,
+                    chan_id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        let now = Utc::now();
+        sqlx::query!(
+            r#"
+            INSERT INTO graph_sync_state (source_node_id, last_update_at)
+            VALUES (?, ?)
+            ON CONFLICT(source_node_id) DO UPDATE SET last_update_at = excluded.last_update_at
+            "#,
+            source_node_id,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Counts what is mirrored from a node and when it was last refreshed.
+    pub async fn get_summary(&self, source_node_id: &str) -> Result<GraphSummary> {
+        let counts = sqlx::query!(
+            r#"
+            SELECT
+            (SELECT COUNT(*) FROM graph_nodes WHERE source_node_id = ?1) as "node_count!: i64",
+            (SELECT COUNT(*) FROM graph_channels WHERE source_node_id = ?1) as "channel_count!: i64",
+            (SELECT COALESCE(SUM(capacity_sat), 0) FROM graph_channels WHERE source_node_id = ?1) as "total_capacity_sat!: i64"
+            "#,
+            source_node_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        let state = sqlx::query!(
+            r#"
+            SELECT
+            last_full_sync_at as "last_full_sync_at?: DateTime<Utc>",
+            last_update_at as "last_update_at?: DateTime<Utc>"
+            FROM graph_sync_state WHERE source_node_id = ?
+            "#,
+            source_node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(GraphSummary {
+            node_id: source_node_id.to_string(),
+            node_count: counts.node_count,
+            channel_count: counts.channel_count,
+            total_capacity_sat: counts.total_capacity_sat,
+            last_full_sync_at: state.as_ref().and_then(|s| s.last_full_sync_at),
+            last_update_at: state.and_then(|s| s.last_update_at),
+        })
+    }
+
+    /// Looks up a node in the mirror along with its channel count, capacity and degree rank.
+    pub async fn get_node(
+        &self,
+        source_node_id: &str,
+        pubkey: &str,
+    ) -> Result<Option<GraphNodeDetails>> {
+        let node = sqlx::query_as!(
+            GraphNodeDetails,
+            r#"
+            WITH degrees AS (
+                SELECT pubkey, COUNT(*) as channel_count, SUM(capacity_sat) as capacity_sat
+                FROM (
+                    SELECT node1_pub as pubkey, capacity_sat FROM graph_channels WHERE source_node_id = ?1
+                    UNION ALL
+                    SELECT node2_pub as pubkey, capacity_sat FROM graph_channels WHERE source_node_id = ?1
+                )
+                GROUP BY pubkey
+            )
+            SELECT
+            n.pubkey as "pubkey!",
+            n.alias as "alias!",
+            n.last_update as "last_update?",
+            COALESCE(d.channel_count, 0) as "channel_count!: i64",
+            COALESCE(d.capacity_sat, 0) as "capacity_sat!: i64",
+            1 + (SELECT COUNT(*) FROM degrees other WHERE other.channel_count > COALESCE(d.channel_count, 0)) as "degree_rank!: i64"
+            FROM graph_nodes n
+            LEFT JOIN degrees d ON d.pubkey = n.pubkey
+            WHERE n.source_node_id = ?1 AND n.pubkey = ?2
+            "#,
+            source_node_id,
+            pubkey
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(node)
+    }
+
+    /// Lists every mirrored channel as (node1, node2, capacity).
+    pub async fn get_edges(&self, source_node_id: &str) -> Result<Vec<(String, String, i64)>> {
+        let edges = sqlx::query!(
+            r#"
+            SELECT
+            node1_pub as "node1_pub!",
+            node2_pub as "node2_pub!",
+            capacity_sat as "capacity_sat!: i64"
+            FROM graph_channels WHERE source_node_id = ?
+            "#,
+            source_node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(edges
+            .into_iter()
+            .map(|e| (e.node1_pub, e.node2_pub, e.capacity_sat))
+            .collect())
+    }
+
+    /// Lists every mirrored node.
+    pub async fn get_nodes(&self, source_node_id: &str) -> Result<Vec<GraphNode>> {
+        let nodes = sqlx::query!(
+            r#"
+            SELECT
+            pubkey as "pubkey!",
+            alias as "alias!",
+            last_update as "last_update?",
+            addresses as "addresses?",
+            features as "features?"
+            FROM graph_nodes WHERE source_node_id = ?
+            "#,
+            source_node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(nodes
+            .into_iter()
+            .map(|n| GraphNode {
+                pubkey: n.pubkey,
+                alias: n.alias,
+                last_update: n.last_update.map(|t| t as u64),
+                addresses: parse_json_list(n.addresses.as_deref()),
+                features: parse_json_list(n.features.as_deref()),
+            })
+            .collect())
+    }
+
+    /// Lists the announcements of the node's channel partners, or of the one
+    /// partner `pubkey`. Nodes mirrored before announcements were recorded
+    /// are left out, as there is nothing to compare them with.
+    pub async fn get_peer_announcements(
+        &self,
+        source_node_id: &str,
+        pubkey: Option<&str>,
+    ) -> Result<Vec<GraphNode>> {
+        let nodes = sqlx::query!(
+            r#"
+            SELECT
+            n.pubkey as "pubkey!",
+            n.alias as "alias!",
+            n.last_update as "last_update?",
+            n.addresses as "addresses!",
+            COALESCE(n.features, '[]') as "features!: String"
+            FROM graph_nodes n
+            WHERE n.source_node_id = ?1
+            AND n.addresses IS NOT NULL
+            AND (?2 IS NULL OR n.pubkey = ?2)
+            AND EXISTS (
+                SELECT 1 FROM graph_channels c
+                WHERE c.source_node_id = ?1
+                AND ((c.node1_pub = ?1 AND c.node2_pub = n.pubkey)
+                    OR (c.node2_pub = ?1 AND c.node1_pub = n.pubkey))
+            )
+            "#,
+            source_node_id,
+            pubkey
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(nodes
+            .into_iter()
+            .map(|n| GraphNode {
+                pubkey: n.pubkey,
+                alias: n.alias,
+                last_update: n.last_update.map(|t| t as u64),
+                addresses: parse_json_list(Some(&n.addresses)),
+                features: parse_json_list(Some(&n.features)),
+            })
+            .collect())
+    }
+
+    /// Retrieves a node's announcement, if it is in the mirror.
+    pub async fn get_announcement(
+        &self,
+        source_node_id: &str,
+        pubkey: &str,
+    ) -> Result<Option<GraphNode>> {
+        let node = sqlx::query!(
+            r#"
+            SELECT
+            pubkey as "pubkey!",
+            alias as "alias!",
+            last_update as "last_update?",
+            addresses as "addresses?",
+            features as "features?"
+            FROM graph_nodes WHERE source_node_id = ? AND pubkey = ?
+            "#,
+            source_node_id,
+            pubkey
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(node.map(|n| GraphNode {
+            pubkey: n.pubkey,
+            alias: n.alias,
+            last_update: n.last_update.map(|t| t as u64),
+            addresses: parse_json_list(n.addresses.as_deref()),
+            features: parse_json_list(n.features.as_deref()),
+        }))
+    }
+
+    /// Lists a node's channels with the policy it advertises on each,
+    /// ordered by channel ID.
+    pub async fn get_node_channels(
+        &self,
+        source_node_id: &str,
+        pubkey: &str,
+    ) -> Result<Vec<WatchedChannel>> {
+        let channels = sqlx::query!(
+            r#"
+            SELECT
+            c.chan_id as "chan_id!",
+            CASE WHEN c.node1_pub = ?2 THEN c.node2_pub ELSE c.node1_pub END as "peer_pubkey!: String",
+            c.capacity_sat as "capacity_sat!: i64",
+            p.fee_base_msat as "fee_base_msat?: i64",
+            p.fee_rate_ppm as "fee_rate_ppm?: i64",
+            p.disabled as "disabled?: bool"
+            FROM graph_channels c
+            LEFT JOIN graph_policies p
+                ON p.source_node_id = c.source_node_id AND p.chan_id = c.chan_id
+                AND p.node_pubkey = ?2
+            WHERE c.source_node_id = ?1 AND (c.node1_pub = ?2 OR c.node2_pub = ?2)
+            ORDER BY c.chan_id
+            "#,
+            source_node_id,
+            pubkey
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(channels
+            .into_iter()
+            .map(|c| WatchedChannel {
+                chan_id: c.chan_id,
+                peer_pubkey: c.peer_pubkey,
+                capacity_sat: c.capacity_sat,
+                fee_base_msat: c.fee_base_msat,
+                fee_rate_ppm: c.fee_rate_ppm,
+                disabled: c.disabled,
+            })
+            .collect())
+    }
+
+    /// Lists every mirrored policy as (advertising node, node at the other
+    /// end, fee rate, disabled).
+    pub async fn get_directed_policies(
+        &self,
+        source_node_id: &str,
+    ) -> Result<Vec<(String, String, i64, bool)>> {
+        let policies = sqlx::query!(
+            r#"
+            SELECT
+            p.node_pubkey as "node_pubkey!",
+            CASE WHEN c.node1_pub = p.node_pubkey THEN c.node2_pub ELSE c.node1_pub END as "peer_pubkey!: String",
+            p.fee_rate_ppm as "fee_rate_ppm!: i64",
+            p.disabled as "disabled!: bool"
+            FROM graph_policies p
+            JOIN graph_channels c ON c.source_node_id = p.source_node_id AND c.chan_id = p.chan_id
+            WHERE p.source_node_id = ?
+            "#,
+            source_node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(policies
+            .into_iter()
+            .map(|p| (p.node_pubkey, p.peer_pubkey, p.fee_rate_ppm, p.disabled))
+            .collect())
+    }
+
+    /// Maps the IDs of a node's channels to the peer at the other end.
+    pub async fn get_channel_peers(
+        &self,
+        source_node_id: &str,
+        pubkey: &str,
+    ) -> Result<HashMap<String, String>> {
+        let channels = sqlx::query!(
+            r#"
+            SELECT
+            chan_id as "chan_id!",
+            CASE WHEN node1_pub = ?2 THEN node2_pub ELSE node1_pub END as "peer_pubkey!: String"
+            FROM graph_channels
+            WHERE source_node_id = ?1 AND (node1_pub = ?2 OR node2_pub = ?2)
+            "#,
+            source_node_id,
+            pubkey
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(channels
+            .into_iter()
+            .map(|c| (c.chan_id, c.peer_pubkey))
+            .collect())
+    }
+
+    /// Lists the fee rates of every enabled policy in the mirror, in ascending order.
+    pub async fn get_fee_rates(&self, source_node_id: &str) -> Result<Vec<i64>> {
+        let rates = sqlx::query_scalar!(
+            r#"
+            SELECT fee_rate_ppm as "fee_rate_ppm!: i64"
+            FROM graph_policies
+            WHERE source_node_id = ? AND disabled = 0
+            ORDER BY fee_rate_ppm
+            "#,
+            source_node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rates)
+    }
+
+    /// Lists the fee rates a node advertises on its own channels, in ascending order.
+    pub async fn get_node_fee_rates(&self, source_node_id: &str, pubkey: &str) -> Result<Vec<i64>> {
+        let rates = sqlx::query_scalar!(
+            r#"
+            SELECT fee_rate_ppm as "fee_rate_ppm!: i64"
+            FROM graph_policies
+            WHERE source_node_id = ? AND node_pubkey = ? AND disabled = 0
+            ORDER BY fee_rate_ppm
+            "#,
+            source_node_id,
+            pubkey
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rates)
+    }
+}
+
+fn parse_json_list<T: serde::de::DeserializeOwned>(json: Option<&str>) -> Vec<T> {
+    json.and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default()
+}
+
+async fn upsert_node(
+    tx: &mut Transaction<'_, Sqlite>,
+    source_node_id: &str,
+    node: &GraphNode,
+) -> Result<()> {
+    let last_update = node.last_update.map(|t| t as i64);
+    let addresses = serde_json::to_string(&node.addresses)?;
+    let features = serde_json::to_string(&node.features)?;
+    sqlx::query!(
+        r#"
+        INSERT INTO graph_nodes (source_node_id, pubkey, alias, last_update, addresses, features)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(source_node_id, pubkey) DO UPDATE SET
+            alias = excluded.alias,
+            last_update = excluded.last_update,
+            addresses = excluded.addresses,
+            -- Updates that leave out feature bits keep the known ones
+            features = CASE WHEN excluded.features = '[]'
+                THEN graph_nodes.features ELSE excluded.features END
+        "#,
+        source_node_id,
+        node.pubkey,
+        node.alias,
+        last_update,
+        addresses,
+        features
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn upsert_channel(
+    tx: &mut Transaction<'_, Sqlite>,
+    source_node_id: &str,
+    channel: &GraphChannel,
+) -> Result<()> {
+    let chan_id = channel.chan_id.to_string();
+    let capacity_sat = channel.capacity_sat as i64;
+    sqlx::query!(
+        r#"
+        INSERT INTO graph_channels (source_node_id, chan_id, node1_pub, node2_pub, capacity_sat)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(source_node_id, chan_id) DO UPDATE SET capacity_sat = excluded.capacity_sat
+        "#,
+        source_node_id,
+        chan_id,
+        channel.node1_pub,
+        channel.node2_pub,
+        capacity_sat
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    for policy in &channel.policies {
+        let node_pubkey = policy.pubkey.to_string();
+        let fee_base_msat = policy.fee_base_msat as i64;
+        let fee_rate_ppm = policy.fee_rate_milli_msat as i64;
+        let min_htlc_msat = policy.min_htlc_msat as i64;
+        let max_htlc_msat = policy.max_htlc_msat.map(|max| max as i64);
+        let time_lock_delta = policy.time_lock_delta as i64;
+        let last_update = policy.last_update.map(|t| t as i64);
+        sqlx::query!(
+            r#"
+            INSERT INTO graph_policies (
+                source_node_id, chan_id, node_pubkey, fee_base_msat, fee_rate_ppm,
+                min_htlc_msat, max_htlc_msat, time_lock_delta, disabled, last_update
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(source_node_id, chan_id, node_pubkey) DO UPDATE SET
+                fee_base_msat = excluded.fee_base_msat,
+                fee_rate_ppm = excluded.fee_rate_ppm,
+                min_htlc_msat = excluded.min_htlc_msat,
+                max_htlc_msat = excluded.max_htlc_msat,
+                time_lock_delta = excluded.time_lock_delta,
+                disabled = excluded.disabled,
+                last_update = excluded.last_update
+            "#,
+            source_node_id,
+            chan_id,
+            node_pubkey,
+            fee_base_msat,
+            fee_rate_ppm,
+            min_htlc_msat,
+            max_htlc_msat,
+            time_lock_delta,
+            policy.disabled,
+            last_update
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Database repository for HTLC interceptor policies.
+
+use crate::database::models::HtlcInterceptorPolicy;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for per-node HTLC interceptor rules.
+pub struct HtlcInterceptorRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> HtlcInterceptorRepository<'a> {
+    /// Creates a new HtlcInterceptorRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves the policy of one of an account's nodes, if one was saved.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<HtlcInterceptorPolicy>> {
+        let policy = sqlx::query_as!(
+            HtlcInterceptorPolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            blocked_peers as "blocked_peers!",
+            max_htlc_msat as "max_htlc_msat?",
+            maintenance_windows as "maintenance_windows!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM htlc_interceptor_policies WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Lists every enabled policy, across all accounts.
+    pub async fn get_enabled_policies(&self) -> Result<Vec<HtlcInterceptorPolicy>> {
+        let policies = sqlx::query_as!(
+            HtlcInterceptorPolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            blocked_peers as "blocked_peers!",
+            max_htlc_msat as "max_htlc_msat?",
+            maintenance_windows as "maintenance_windows!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM htlc_interceptor_policies WHERE is_enabled = 1
+            "#
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(policies)
+    }
+
+    /// Creates or replaces a node's policy.
+    pub async fn upsert_policy(
+        &self,
+        policy: &HtlcInterceptorPolicy,
+    ) -> Result<HtlcInterceptorPolicy> {
+        let saved = sqlx::query_as!(
+            HtlcInterceptorPolicy,
+            r#"
+            INSERT INTO htlc_interceptor_policies (
+                account_id, node_id, is_enabled, blocked_peers,
+                max_htlc_msat, maintenance_windows, updated_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, node_id) DO UPDATE SET
+                is_enabled = excluded.is_enabled,
+                blocked_peers = excluded.blocked_peers,
+                max_htlc_msat = excluded.max_htlc_msat,
+                maintenance_windows = excluded.maintenance_windows,
+                updated_by = excluded.updated_by
+            RETURNING
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            blocked_peers as "blocked_peers!",
+            max_htlc_msat as "max_htlc_msat?",
+            maintenance_windows as "maintenance_windows!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            policy.account_id,
+            policy.node_id,
+            policy.is_enabled,
+            policy.blocked_peers,
+            policy.max_htlc_msat,
+            policy.maintenance_windows,
+            policy.updated_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+}
@@ -0,0 +1,104 @@
+//! Database repository for idempotency keys and their cached responses.
+
+use crate::database::models::IdempotencyRecord;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for requests made with an Idempotency-Key.
+pub struct IdempotencyRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> IdempotencyRepository<'a> {
+    /// Creates a new IdempotencyRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Claims a key for a new request. Returns false if the account already
+    /// used the key.
+    pub async fn reserve(
+        &self,
+        account_id: &str,
+        key: &str,
+        method: &str,
+        path: &str,
+        request_hash: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (account_id, idempotency_key, method, path, request_hash)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, idempotency_key) DO NOTHING
+            "#,
+            account_id,
+            key,
+            method,
+            path,
+            request_hash
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Fetches the request stored under a key.
+    pub async fn get(&self, account_id: &str, key: &str) -> Result<Option<IdempotencyRecord>> {
+        let record = sqlx::query_as!(
+            IdempotencyRecord,
+            r#"
+            SELECT
+            request_hash as "request_hash!",
+            status_code as "status_code?",
+            content_type as "content_type?",
+            response_body as "response_body?"
+            FROM idempotency_keys WHERE account_id = ? AND idempotency_key = ?
+            "#,
+            account_id,
+            key
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Stores the response to a reserved key.
+    pub async fn complete(
+        &self,
+        account_id: &str,
+        key: &str,
+        status_code: i64,
+        content_type: Option<&str>,
+        response_body: &[u8],
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE idempotency_keys
+            SET status_code = ?, content_type = ?, response_body = ?
+            WHERE account_id = ? AND idempotency_key = ?
+            "#,
+            status_code,
+            content_type,
+            response_body,
+            account_id,
+            key
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes keys created before `cutoff`. Returns how many were removed.
+    pub async fn delete_expired(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query!("DELETE FROM idempotency_keys WHERE created_at < ?", cutoff)
+            .execute(self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -36,8 +36,8 @@ impl<'a> InviteRepository<'a> {
         let invite = sqlx::query_as!(
             Invite,
             r#"
-            INSERT INTO invites (id, account_id, inviter_id, invitee_email, token, invite_status, expires_at, is_active)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO invites (id, account_id, inviter_id, invitee_email, token, invite_status, role_id, node_ids, expires_at, is_active)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING 
             id as "id!",
             account_id as "account_id!",
@@ -45,6 +45,8 @@ impl<'a> InviteRepository<'a> {
             invitee_email as "invitee_email!",
             token as "token!",
             invite_status as "invite_status: InviteStatus",
+            role_id as "role_id?",
+            node_ids as "node_ids?",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             expires_at as "expires_at!: DateTime<Utc>",
@@ -58,6 +60,8 @@ impl<'a> InviteRepository<'a> {
             invite.invitee_email,
             invite.token,
             invite.invite_status,
+            invite.role_id,
+            invite.node_ids,
             invite.expires_at,
             true
         )
@@ -114,6 +118,8 @@ impl<'a> InviteRepository<'a> {
             invitee_email as "invitee_email!",
             token as "token!",
             invite_status as "invite_status: InviteStatus",
+            role_id as "role_id?",
+            node_ids as "node_ids?",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             expires_at as "expires_at!: DateTime<Utc>",
@@ -148,6 +154,8 @@ impl<'a> InviteRepository<'a> {
             invitee_email as "invitee_email!",
             token as "token!",
             invite_status as "invite_status: InviteStatus",
+            role_id as "role_id?",
+            node_ids as "node_ids?",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             expires_at as "expires_at!: DateTime<Utc>",
@@ -187,6 +195,8 @@ impl<'a> InviteRepository<'a> {
             invitee_email as "invitee_email!",
             token as "token!",
             invite_status as "invite_status: InviteStatus",
+            role_id as "role_id?",
+            node_ids as "node_ids?",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             expires_at as "expires_at!: DateTime<Utc>",
@@ -222,6 +232,8 @@ impl<'a> InviteRepository<'a> {
             invitee_email as "invitee_email!",
             token as "token!",
             invite_status as "invite_status: InviteStatus",
+            role_id as "role_id?",
+            node_ids as "node_ids?",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             expires_at as "expires_at!: DateTime<Utc>",
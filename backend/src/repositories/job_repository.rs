@@ -0,0 +1,259 @@
+//! Database repository for the background job queue.
+
+use crate::database::models::{Job, JobStatus, JobType};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for job queue persistence.
+pub struct JobRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> JobRepository<'a> {
+    /// Creates a new JobRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Enqueues a new job to run at `run_at`.
+    pub async fn create_job(
+        &self,
+        id: &str,
+        job_type: JobType,
+        payload: &str,
+        account_id: Option<&str>,
+        max_attempts: i64,
+        run_at: DateTime<Utc>,
+    ) -> Result<Job> {
+        let job = sqlx::query_as!(
+            Job,
+            r#"
+            INSERT INTO jobs (id, job_type, payload, status, account_id, max_attempts, run_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            job_type as "job_type: JobType",
+            payload as "payload!",
+            status as "status: JobStatus",
+            account_id as "account_id?",
+            attempts as "attempts!",
+            max_attempts as "max_attempts!",
+            run_at as "run_at!: DateTime<Utc>",
+            locked_at as "locked_at?: DateTime<Utc>",
+            last_error as "last_error?",
+            completed_at as "completed_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            id,
+            job_type,
+            payload,
+            JobStatus::Pending,
+            account_id,
+            max_attempts,
+            run_at
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(job)
+    }
+
+    /// Atomically claims the oldest due job and marks it as running.
+    pub async fn claim_next_job(&self, now: DateTime<Utc>) -> Result<Option<Job>> {
+        let job = sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE jobs
+            SET status = ?, attempts = attempts + 1, locked_at = ?
+            WHERE id = (
+                SELECT id FROM jobs
+                WHERE status = ? AND run_at <= ?
+                ORDER BY run_at ASC
+                LIMIT 1
+            )
+            RETURNING
+            id as "id!",
+            job_type as "job_type: JobType",
+            payload as "payload!",
+            status as "status: JobStatus",
+            account_id as "account_id?",
+            attempts as "attempts!",
+            max_attempts as "max_attempts!",
+            run_at as "run_at!: DateTime<Utc>",
+            locked_at as "locked_at?: DateTime<Utc>",
+            last_error as "last_error?",
+            completed_at as "completed_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            JobStatus::Running,
+            now,
+            JobStatus::Pending,
+            now
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(job)
+    }
+
+    /// Marks a running job as completed.
+    pub async fn complete_job(&self, id: &str) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET status = ?, completed_at = ?, locked_at = NULL, last_error = NULL
+            WHERE id = ?
+            "#,
+            JobStatus::Completed,
+            now,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a failed attempt. The job is rescheduled for `retry_at` when given,
+    /// otherwise it is marked as permanently failed.
+    pub async fn fail_job(
+        &self,
+        id: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        match retry_at {
+            Some(retry_at) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE jobs
+                    SET status = ?, run_at = ?, locked_at = NULL, last_error = ?
+                    WHERE id = ?
+                    "#,
+                    JobStatus::Pending,
+                    retry_at,
+                    error,
+                    id
+                )
+                .execute(self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query!(
+                    r#"
+                    UPDATE jobs
+                    SET status = ?, locked_at = NULL, last_error = ?
+                    WHERE id = ?
+                    "#,
+                    JobStatus::Failed,
+                    error,
+                    id
+                )
+                .execute(self.pool)
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns jobs left running by a previous process to the queue.
+    pub async fn requeue_running_jobs(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET status = ?, locked_at = NULL
+            WHERE status = ?
+            "#,
+            JobStatus::Pending,
+            JobStatus::Running
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Lists an account's jobs, together with system-wide jobs if
+    /// `include_system` is set, newest first.
+    pub async fn get_jobs(
+        &self,
+        account_id: &str,
+        include_system: bool,
+        status: Option<JobStatus>,
+        job_type: Option<JobType>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Job>> {
+        let jobs = sqlx::query_as!(
+            Job,
+            r#"
+            SELECT
+            id as "id!",
+            job_type as "job_type: JobType",
+            payload as "payload!",
+            status as "status: JobStatus",
+            account_id as "account_id?",
+            attempts as "attempts!",
+            max_attempts as "max_attempts!",
+            run_at as "run_at!: DateTime<Utc>",
+            locked_at as "locked_at?: DateTime<Utc>",
+            last_error as "last_error?",
+            completed_at as "completed_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM jobs
+            WHERE (account_id = ? OR (? AND account_id IS NULL))
+            AND (? IS NULL OR status = ?)
+            AND (? IS NULL OR job_type = ?)
+            ORDER BY created_at DESC
+            LIMIT ? OFFSET ?
+            "#,
+            account_id,
+            include_system,
+            status,
+            status,
+            job_type,
+            job_type,
+            limit,
+            offset
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    /// Counts the jobs matched by [`Self::get_jobs`].
+    pub async fn count_jobs(
+        &self,
+        account_id: &str,
+        include_system: bool,
+        status: Option<JobStatus>,
+        job_type: Option<JobType>,
+    ) -> Result<i64> {
+        let result = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as count FROM jobs
+            WHERE (account_id = ? OR (? AND account_id IS NULL))
+            AND (? IS NULL OR status = ?)
+            AND (? IS NULL OR job_type = ?)
+            "#,
+            account_id,
+            include_system,
+            status,
+            status,
+            job_type,
+            job_type
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(result.count)
+    }
+}
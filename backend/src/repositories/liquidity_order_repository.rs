@@ -0,0 +1,227 @@
+//! Database repository for LSPS1 liquidity orders.
+
+use crate::database::models::{LiquidityOrder, LiquidityOrderState};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for purchased inbound channels.
+pub struct LiquidityOrderRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> LiquidityOrderRepository<'a> {
+    /// Creates a new LiquidityOrderRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores an order the LSP has accepted.
+    pub async fn create_order(&self, order: &LiquidityOrder) -> Result<LiquidityOrder> {
+        let created = sqlx::query_as!(
+            LiquidityOrder,
+            r#"
+            INSERT INTO liquidity_orders (
+                id, account_id, node_id, lsp_url, lsp_order_id, order_state, payment_state,
+                lsp_balance_sat, client_balance_sat, channel_expiry_blocks, announce_channel,
+                fee_total_sat, order_total_sat, bolt11_invoice, payment_expires_at, created_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            lsp_url as "lsp_url!",
+            lsp_order_id as "lsp_order_id!",
+            order_state as "order_state!: LiquidityOrderState",
+            payment_state as "payment_state?",
+            lsp_balance_sat as "lsp_balance_sat!",
+            client_balance_sat as "client_balance_sat!",
+            channel_expiry_blocks as "channel_expiry_blocks!",
+            announce_channel as "announce_channel!: bool",
+            fee_total_sat as "fee_total_sat!",
+            order_total_sat as "order_total_sat!",
+            bolt11_invoice as "bolt11_invoice!",
+            payment_expires_at as "payment_expires_at?: DateTime<Utc>",
+            funding_outpoint as "funding_outpoint?",
+            channel_funded_at as "channel_funded_at?: DateTime<Utc>",
+            channel_expires_at as "channel_expires_at?: DateTime<Utc>",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            order.id,
+            order.account_id,
+            order.node_id,
+            order.lsp_url,
+            order.lsp_order_id,
+            order.order_state,
+            order.payment_state,
+            order.lsp_balance_sat,
+            order.client_balance_sat,
+            order.channel_expiry_blocks,
+            order.announce_channel,
+            order.fee_total_sat,
+            order.order_total_sat,
+            order.bolt11_invoice,
+            order.payment_expires_at,
+            order.created_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    /// Retrieves one of a node's orders.
+    pub async fn get_order(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        id: &str,
+    ) -> Result<Option<LiquidityOrder>> {
+        let order = sqlx::query_as!(
+            LiquidityOrder,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            lsp_url as "lsp_url!",
+            lsp_order_id as "lsp_order_id!",
+            order_state as "order_state!: LiquidityOrderState",
+            payment_state as "payment_state?",
+            lsp_balance_sat as "lsp_balance_sat!",
+            client_balance_sat as "client_balance_sat!",
+            channel_expiry_blocks as "channel_expiry_blocks!",
+            announce_channel as "announce_channel!: bool",
+            fee_total_sat as "fee_total_sat!",
+            order_total_sat as "order_total_sat!",
+            bolt11_invoice as "bolt11_invoice!",
+            payment_expires_at as "payment_expires_at?: DateTime<Utc>",
+            funding_outpoint as "funding_outpoint?",
+            channel_funded_at as "channel_funded_at?: DateTime<Utc>",
+            channel_expires_at as "channel_expires_at?: DateTime<Utc>",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM liquidity_orders WHERE account_id = ? AND node_id = ? AND id = ?
+            "#,
+            account_id,
+            node_id,
+            id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(order)
+    }
+
+    /// Lists a node's orders, newest first.
+    pub async fn get_orders(&self, account_id: &str, node_id: &str) -> Result<Vec<LiquidityOrder>> {
+        let orders = sqlx::query_as!(
+            LiquidityOrder,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            lsp_url as "lsp_url!",
+            lsp_order_id as "lsp_order_id!",
+            order_state as "order_state!: LiquidityOrderState",
+            payment_state as "payment_state?",
+            lsp_balance_sat as "lsp_balance_sat!",
+            client_balance_sat as "client_balance_sat!",
+            channel_expiry_blocks as "channel_expiry_blocks!",
+            announce_channel as "announce_channel!: bool",
+            fee_total_sat as "fee_total_sat!",
+            order_total_sat as "order_total_sat!",
+            bolt11_invoice as "bolt11_invoice!",
+            payment_expires_at as "payment_expires_at?: DateTime<Utc>",
+            funding_outpoint as "funding_outpoint?",
+            channel_funded_at as "channel_funded_at?: DateTime<Utc>",
+            channel_expires_at as "channel_expires_at?: DateTime<Utc>",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM liquidity_orders WHERE account_id = ? AND node_id = ?
+            ORDER BY created_at DESC
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(orders)
+    }
+
+    /// Records the latest order, payment and channel state reported by the LSP.
+    pub async fn update_status(&self, order: &LiquidityOrder) -> Result<LiquidityOrder> {
+        let updated = sqlx::query_as!(
+            LiquidityOrder,
+            r#"
+            UPDATE liquidity_orders
+            SET order_state = ?, payment_state = ?, funding_outpoint = ?,
+                channel_funded_at = ?, channel_expires_at = ?
+            WHERE id = ?
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            lsp_url as "lsp_url!",
+            lsp_order_id as "lsp_order_id!",
+            order_state as "order_state!: LiquidityOrderState",
+            payment_state as "payment_state?",
+            lsp_balance_sat as "lsp_balance_sat!",
+            client_balance_sat as "client_balance_sat!",
+            channel_expiry_blocks as "channel_expiry_blocks!",
+            announce_channel as "announce_channel!: bool",
+            fee_total_sat as "fee_total_sat!",
+            order_total_sat as "order_total_sat!",
+            bolt11_invoice as "bolt11_invoice!",
+            payment_expires_at as "payment_expires_at?: DateTime<Utc>",
+            funding_outpoint as "funding_outpoint?",
+            channel_funded_at as "channel_funded_at?: DateTime<Utc>",
+            channel_expires_at as "channel_expires_at?: DateTime<Utc>",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            order.order_state,
+            order.payment_state,
+            order.funding_outpoint,
+            order.channel_funded_at,
+            order.channel_expires_at,
+            order.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(updated)
+    }
+
+    /// Looks up what was paid for the channel funded at `funding_outpoint`, if
+    /// it was bought through an order.
+    pub async fn get_cost_by_funding_outpoint(
+        &self,
+        node_id: &str,
+        funding_outpoint: &str,
+    ) -> Result<Option<i64>> {
+        let cost = sqlx::query_scalar!(
+            r#"
+            SELECT fee_total_sat as "fee_total_sat!: i64"
+            FROM liquidity_orders
+            WHERE node_id = ? AND funding_outpoint = ? AND order_state = ?
+            "#,
+            node_id,
+            funding_outpoint,
+            LiquidityOrderState::Completed
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(cost)
+    }
+}
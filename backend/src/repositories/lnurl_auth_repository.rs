@@ -0,0 +1,184 @@
+//! Database repository for LNURL-auth challenges and linked keys.
+
+use crate::database::models::{
+    LnurlAuthAction, LnurlAuthKey, LnurlChallenge, LnurlChallengeStatus,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for LNURL-auth persistence.
+pub struct LnurlAuthRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> LnurlAuthRepository<'a> {
+    /// Creates a new LnurlAuthRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a freshly issued challenge.
+    pub async fn create_challenge(
+        &self,
+        k1: &str,
+        action: LnurlAuthAction,
+        user_id: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<LnurlChallenge> {
+        let challenge = sqlx::query_as!(
+            LnurlChallenge,
+            r#"
+            INSERT INTO lnurl_auth_challenges (k1, action, user_id, status, expires_at)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING
+            k1 as "k1!",
+            action as "action: LnurlAuthAction",
+            user_id as "user_id?",
+            linking_key as "linking_key?",
+            status as "status: LnurlChallengeStatus",
+            expires_at as "expires_at!: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            k1,
+            action,
+            user_id,
+            LnurlChallengeStatus::Pending,
+            expires_at
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(challenge)
+    }
+
+    /// Retrieves a challenge by its k1 value.
+    pub async fn get_challenge(&self, k1: &str) -> Result<Option<LnurlChallenge>> {
+        let challenge = sqlx::query_as!(
+            LnurlChallenge,
+            r#"
+            SELECT
+            k1 as "k1!",
+            action as "action: LnurlAuthAction",
+            user_id as "user_id?",
+            linking_key as "linking_key?",
+            status as "status: LnurlChallengeStatus",
+            expires_at as "expires_at!: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM lnurl_auth_challenges WHERE k1 = ?
+            "#,
+            k1
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(challenge)
+    }
+
+    /// Marks a pending challenge as signed by the given key.
+    ///
+    /// Returns `false` if the challenge was no longer pending, which guards
+    /// against a wallet replaying the callback.
+    pub async fn mark_challenge_verified(
+        &self,
+        k1: &str,
+        user_id: &str,
+        linking_key: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE lnurl_auth_challenges
+            SET status = ?, user_id = ?, linking_key = ?
+            WHERE k1 = ? AND status = ?
+            "#,
+            LnurlChallengeStatus::Verified,
+            user_id,
+            linking_key,
+            k1,
+            LnurlChallengeStatus::Pending
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Consumes a verified challenge so it can only be exchanged for tokens once.
+    pub async fn consume_challenge(&self, k1: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE lnurl_auth_challenges
+            SET status = ?
+            WHERE k1 = ? AND status = ?
+            "#,
+            LnurlChallengeStatus::Consumed,
+            k1,
+            LnurlChallengeStatus::Verified
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Links a wallet's linking key to a user.
+    pub async fn create_key(
+        &self,
+        id: &str,
+        user_id: &str,
+        linking_key: &str,
+    ) -> Result<LnurlAuthKey> {
+        let key = sqlx::query_as!(
+            LnurlAuthKey,
+            r#"
+            INSERT INTO lnurl_auth_keys (id, user_id, linking_key, is_active)
+            VALUES (?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            user_id as "user_id!",
+            linking_key as "linking_key!",
+            is_active as "is_active!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>"
+            "#,
+            id,
+            user_id,
+            linking_key,
+            true
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    /// Retrieves the active link for a linking key, if any.
+    pub async fn get_key_by_linking_key(&self, linking_key: &str) -> Result<Option<LnurlAuthKey>> {
+        let key = sqlx::query_as!(
+            LnurlAuthKey,
+            r#"
+            SELECT
+            id as "id!",
+            user_id as "user_id!",
+            linking_key as "linking_key!",
+            is_active as "is_active!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>"
+            FROM lnurl_auth_keys
+            WHERE linking_key = ? AND is_active = 1 AND is_deleted = 0
+            "#,
+            linking_key
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(key)
+    }
+}
@@ -0,0 +1,96 @@
+//! Database repository for node maintenance windows.
+
+use crate::database::models::MaintenanceWindow;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Repository for planned maintenance of nodes.
+pub struct MaintenanceRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> MaintenanceRepository<'a> {
+    /// Creates a new MaintenanceRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Records a maintenance window of a node.
+    pub async fn create_window(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        reason: Option<&str>,
+        created_by: &str,
+    ) -> Result<MaintenanceWindow> {
+        let id = Uuid::now_v7().to_string();
+
+        let window = sqlx::query_as!(
+            MaintenanceWindow,
+            r#"
+            INSERT INTO node_maintenance_windows (
+                id, account_id, node_id, starts_at, ends_at, reason, created_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            starts_at as "starts_at!: DateTime<Utc>",
+            ends_at as "ends_at!: DateTime<Utc>",
+            reason,
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>"
+            "#,
+            id,
+            account_id,
+            node_id,
+            starts_at,
+            ends_at,
+            reason,
+            created_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(window)
+    }
+
+    /// Lists a node's windows that end after `since`, earliest first.
+    pub async fn get_windows_since(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<MaintenanceWindow>> {
+        let windows = sqlx::query_as!(
+            MaintenanceWindow,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            starts_at as "starts_at!: DateTime<Utc>",
+            ends_at as "ends_at!: DateTime<Utc>",
+            reason,
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM node_maintenance_windows
+            WHERE account_id = ? AND node_id = ? AND ends_at > ?
+            ORDER BY starts_at ASC
+            "#,
+            account_id,
+            node_id,
+            since
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(windows)
+    }
+}
@@ -1,7 +1,43 @@
+pub mod account_membership_repository;
 pub mod account_repository;
+pub mod account_settings_repository;
+pub mod audit_log_repository;
+pub mod billing_invoice_repository;
+pub mod channel_acceptor_repository;
+pub mod channel_confirmation_repository;
+pub mod channel_note_repository;
 pub mod credential_repository;
+pub mod data_purge_repository;
+pub mod email_change_repository;
+pub mod escalation_repository;
 pub mod event_repository;
+pub mod event_subscription_repository;
+pub mod fee_automation_repository;
+pub mod forward_repository;
+pub mod graph_repository;
+pub mod htlc_interceptor_repository;
+pub mod idempotency_repository;
 pub mod invite_repository;
+pub mod job_repository;
+pub mod liquidity_order_repository;
+pub mod lnurl_auth_repository;
+pub mod maintenance_repository;
+pub mod network_position_repository;
+pub mod node_label_repository;
+pub mod node_scope_repository;
+pub mod node_sync_repository;
+pub mod notification_health_repository;
 pub mod notification_repository;
+pub mod payment_latency_repository;
+pub mod peer_metadata_repository;
+pub mod probe_repository;
+pub mod rebalance_repository;
+pub mod retention_repository;
 pub mod role_repository;
+pub mod saved_view_repository;
+pub mod scheduled_task_repository;
+pub mod subscription_repository;
+pub mod swap_repository;
+pub mod usage_repository;
 pub mod user_repository;
+pub mod watched_node_repository;
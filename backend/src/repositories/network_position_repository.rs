@@ -0,0 +1,112 @@
+//! Database repository for network position snapshots.
+
+use crate::database::models::{NetworkPositionPoint, NetworkPositionSnapshot};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for centrality snapshots of a node and its peers.
+pub struct NetworkPositionRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NetworkPositionRepository<'a> {
+    /// Creates a new NetworkPositionRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores the snapshots of one computation run together.
+    pub async fn create_snapshots(&self, snapshots: &[NetworkPositionSnapshot]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for snapshot in snapshots {
+            sqlx::query!(
+                r#"
+                INSERT INTO network_position_snapshots (
+                    id, source_node_id, pubkey, betweenness, closeness,
+                    capacity_rank, channel_count, capacity_sat, captured_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                snapshot.id,
+                snapshot.source_node_id,
+                snapshot.pubkey,
+                snapshot.betweenness,
+                snapshot.closeness,
+                snapshot.capacity_rank,
+                snapshot.channel_count,
+                snapshot.capacity_sat,
+                snapshot.captured_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Retrieves every snapshot from the most recent run for a node.
+    pub async fn get_latest_snapshots(
+        &self,
+        source_node_id: &str,
+    ) -> Result<Vec<NetworkPositionSnapshot>> {
+        let snapshots = sqlx::query_as!(
+            NetworkPositionSnapshot,
+            r#"
+            SELECT
+            s.id as "id!",
+            s.source_node_id as "source_node_id!",
+            s.pubkey as "pubkey!",
+            COALESCE(n.alias, '') as "alias!: String",
+            s.betweenness as "betweenness!: f64",
+            s.closeness as "closeness!: f64",
+            s.capacity_rank as "capacity_rank!: i64",
+            s.channel_count as "channel_count!: i64",
+            s.capacity_sat as "capacity_sat!: i64",
+            s.captured_at as "captured_at!: DateTime<Utc>"
+            FROM network_position_snapshots s
+            LEFT JOIN graph_nodes n ON n.source_node_id = s.source_node_id AND n.pubkey = s.pubkey
+            WHERE s.source_node_id = ?1
+            AND s.captured_at = (
+                SELECT MAX(captured_at) FROM network_position_snapshots WHERE source_node_id = ?1
+            )
+            ORDER BY s.betweenness DESC
+            "#,
+            source_node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(snapshots)
+    }
+
+    /// Lists a node's own snapshots since `since`, oldest first.
+    pub async fn get_history(
+        &self,
+        source_node_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<NetworkPositionPoint>> {
+        let points = sqlx::query_as!(
+            NetworkPositionPoint,
+            r#"
+            SELECT
+            captured_at as "captured_at!: DateTime<Utc>",
+            betweenness as "betweenness!: f64",
+            closeness as "closeness!: f64",
+            capacity_rank as "capacity_rank!: i64"
+            FROM network_position_snapshots
+            WHERE source_node_id = ?1 AND pubkey = ?1 AND captured_at >= ?2
+            ORDER BY captured_at
+            "#,
+            source_node_id,
+            since
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(points)
+    }
+}
@@ -0,0 +1,88 @@
+//! Database repository for node labels.
+
+use crate::database::models::NodeLabel;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for the names and notes given to nodes.
+pub struct NodeLabelRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NodeLabelRepository<'a> {
+    /// Creates a new NodeLabelRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves the label of one of an account's nodes, if it has one.
+    pub async fn get_label(&self, account_id: &str, node_id: &str) -> Result<Option<NodeLabel>> {
+        let label = sqlx::query_as!(
+            NodeLabel,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            display_name,
+            environment,
+            notes,
+            updated_by as "updated_by!",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM node_labels WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(label)
+    }
+
+    /// Creates or replaces a node's label.
+    pub async fn upsert_label(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        display_name: Option<&str>,
+        environment: Option<&str>,
+        notes: Option<&str>,
+        updated_by: &str,
+    ) -> Result<NodeLabel> {
+        let label = sqlx::query_as!(
+            NodeLabel,
+            r#"
+            INSERT INTO node_labels (
+                account_id, node_id, display_name, environment, notes, updated_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, node_id) DO UPDATE SET
+                display_name = excluded.display_name,
+                environment = excluded.environment,
+                notes = excluded.notes,
+                updated_by = excluded.updated_by,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING
+            account_id as "account_id!",
+            node_id as "node_id!",
+            display_name,
+            environment,
+            notes,
+            updated_by as "updated_by!",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            account_id,
+            node_id,
+            display_name,
+            environment,
+            notes,
+            updated_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(label)
+    }
+}
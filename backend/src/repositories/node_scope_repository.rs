@@ -0,0 +1,72 @@
+//! Database repository for the nodes a user is limited to within an account.
+
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+/// Repository for users' node access scopes.
+pub struct NodeScopeRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NodeScopeRepository<'a> {
+    /// Creates a new NodeScopeRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves the nodes a user is limited to within an account, or `None`
+    /// if only their role limits them.
+    pub async fn get_scope(&self, user_id: &str, account_id: &str) -> Result<Option<Vec<String>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT node_ids as "node_ids!" FROM user_node_scopes
+            WHERE user_id = ? AND account_id = ?
+            "#,
+            user_id,
+            account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        row.map(|row| serde_json::from_str(&row.node_ids).map_err(Into::into))
+            .transpose()
+    }
+
+    /// Limits a user to the given nodes within an account.
+    pub async fn set_scope(
+        &self,
+        user_id: &str,
+        account_id: &str,
+        node_ids: &[String],
+    ) -> Result<()> {
+        let node_ids = serde_json::to_string(node_ids)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO user_node_scopes (user_id, account_id, node_ids)
+            VALUES (?, ?, ?)
+            ON CONFLICT(user_id, account_id) DO UPDATE SET node_ids = excluded.node_ids
+            "#,
+            user_id,
+            account_id,
+            node_ids
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lifts a user's own limit within an account.
+    pub async fn delete_scope(&self, user_id: &str, account_id: &str) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM user_node_scopes WHERE user_id = ? AND account_id = ?",
+            user_id,
+            account_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
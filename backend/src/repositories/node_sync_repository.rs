@@ -0,0 +1,715 @@
+//! Database repository for locally synced node data.
+//!
+//! A resync replaces everything stored for a node, so rows that disappeared
+//! from the node (e.g. pruned payments) are dropped locally as well. Payments
+//! imported from another tool's history are the exception: they are kept
+//! until the node reports the same payment itself.
+
+use crate::database::models::{
+    InvoiceTiming, PaymentRouteSample, SyncedChannel, SyncedInvoice, SyncedPayment,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for synced channel, payment and invoice rows.
+pub struct NodeSyncRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NodeSyncRepository<'a> {
+    /// Creates a new NodeSyncRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Replaces the stored channels of a node.
+    pub async fn replace_channels(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        channels: &[SyncedChannel],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM synced_channels WHERE account_id = ? AND node_id = ?",
+            account_id,
+            node_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for channel in channels {
+            sqlx::query!(
+                r#"
+                INSERT INTO synced_channels (
+                    id, account_id, node_id, chan_id, remote_pubkey, alias, channel_state,
+                    private, local_balance_sat, remote_balance_sat, capacity_sat, synced_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                channel.id,
+                channel.account_id,
+                channel.node_id,
+                channel.chan_id,
+                channel.remote_pubkey,
+                channel.alias,
+                channel.channel_state,
+                channel.private,
+                channel.local_balance_sat,
+                channel.remote_balance_sat,
+                channel.capacity_sat,
+                channel.synced_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replaces the stored payments of a node, keeping imported payments the
+    /// node doesn't report.
+    pub async fn replace_payments(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        payments: &[SyncedPayment],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM synced_payments
+            WHERE account_id = ? AND node_id = ? AND imported_from IS NULL
+            "#,
+            account_id,
+            node_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for payment in payments {
+            sqlx::query!(
+                r#"
+                INSERT INTO synced_payments (
+                    id, account_id, node_id, payment_hash, state, payment_type, amount_sat,
+                    routing_fee_sat, created_at_node, completed_at_node, keysend_message,
+                    destination_pubkey, synced_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                payment.id,
+                payment.account_id,
+                payment.node_id,
+                payment.payment_hash,
+                payment.state,
+                payment.payment_type,
+                payment.amount_sat,
+                payment.routing_fee_sat,
+                payment.created_at_node,
+                payment.completed_at_node,
+                payment.keysend_message,
+                payment.destination_pubkey,
+                payment.synced_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            DELETE FROM synced_payments
+            WHERE account_id = ?1 AND node_id = ?2 AND imported_from IS NOT NULL
+            AND payment_hash IN (
+                SELECT payment_hash FROM synced_payments
+                WHERE account_id = ?1 AND node_id = ?2 AND imported_from IS NULL
+            )
+            "#,
+            account_id,
+            node_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stores payments imported from another tool, skipping any whose hash is
+    /// already stored for the node. Returns how many were new.
+    pub async fn insert_imported_payments(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        payments: &[SyncedPayment],
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut inserted = 0;
+        for payment in payments {
+            let result = sqlx::query!(
+                r#"
+                INSERT INTO synced_payments (
+                    id, account_id, node_id, payment_hash, state, payment_type, amount_sat,
+                    routing_fee_sat, created_at_node, completed_at_node, keysend_message,
+                    destination_pubkey, imported_from, synced_at
+                )
+                SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM synced_payments
+                    WHERE account_id = ?2 AND node_id = ?3 AND payment_hash = ?4
+                )
+                "#,
+                payment.id,
+                account_id,
+                node_id,
+                payment.payment_hash,
+                payment.state,
+                payment.payment_type,
+                payment.amount_sat,
+                payment.routing_fee_sat,
+                payment.created_at_node,
+                payment.completed_at_node,
+                payment.keysend_message,
+                payment.destination_pubkey,
+                payment.imported_from,
+                payment.synced_at
+            )
+            .execute(&mut *tx)
+            .await?;
+            inserted += result.rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Replaces the stored invoices of a node.
+    pub async fn replace_invoices(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        invoices: &[SyncedInvoice],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM synced_invoices WHERE account_id = ? AND node_id = ?",
+            account_id,
+            node_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for invoice in invoices {
+            sqlx::query!(
+                r#"
+                INSERT OR REPLACE INTO synced_invoices (
+                    id, account_id, node_id, payment_hash, state, memo, value_msat,
+                    amt_paid_msat, created_at_node, settled_at_node, is_keysend,
+                    keysend_message, synced_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                invoice.id,
+                invoice.account_id,
+                invoice.node_id,
+                invoice.payment_hash,
+                invoice.state,
+                invoice.memo,
+                invoice.value_msat,
+                invoice.amt_paid_msat,
+                invoice.created_at_node,
+                invoice.settled_at_node,
+                invoice.is_keysend,
+                invoice.keysend_message,
+                invoice.synced_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Counts a node's synced invoices created (or, without a creation time,
+    /// settled) between `from` and `to`, with their summed values in msat,
+    /// per stored state.
+    pub async fn get_invoice_totals_by_state(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(String, i64, i64)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+            state as "state!",
+            COUNT(*) as "count!: i64",
+            COALESCE(SUM(value_msat), 0) as "value_msat!: i64"
+            FROM synced_invoices
+            WHERE account_id = ? AND node_id = ?
+            AND COALESCE(created_at_node, settled_at_node) >= ?
+            AND COALESCE(created_at_node, settled_at_node) <= ?
+            GROUP BY state
+            "#,
+            account_id,
+            node_id,
+            from,
+            to
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.state, r.count, r.value_msat))
+            .collect())
+    }
+
+    /// Lists the creation and settlement times of a node's synced invoices
+    /// created between `from` and `to`, leaving out keysend payments.
+    pub async fn get_invoice_timings(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<InvoiceTiming>> {
+        let timings = sqlx::query_as!(
+            InvoiceTiming,
+            r#"
+            SELECT
+            state as "state!",
+            created_at_node as "created_at!: DateTime<Utc>",
+            settled_at_node as "settled_at?: DateTime<Utc>"
+            FROM synced_invoices
+            WHERE account_id = ? AND node_id = ? AND is_keysend = 0
+            AND created_at_node IS NOT NULL
+            AND created_at_node >= ? AND created_at_node <= ?
+            ORDER BY created_at_node ASC
+            "#,
+            account_id,
+            node_id,
+            from,
+            to
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(timings)
+    }
+
+    /// When a node's invoices were last synced.
+    pub async fn get_invoices_synced_at(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT MAX(synced_at) as "synced_at?: DateTime<Utc>"
+            FROM synced_invoices
+            WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(row.synced_at)
+    }
+
+    /// Counts a node's synced payments created (or, without a creation time,
+    /// completed) between `from` and `to` per stored type and state, with
+    /// their summed amounts and routing fees in sats.
+    pub async fn get_payment_totals_by_type_and_state(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(String, String, i64, i64, i64)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+            payment_type as "payment_type!",
+            state as "state!",
+            COUNT(*) as "count!: i64",
+            COALESCE(SUM(amount_sat), 0) as "amount_sat!: i64",
+            COALESCE(SUM(routing_fee_sat), 0) as "routing_fee_sat!: i64"
+            FROM synced_payments
+            WHERE account_id = ? AND node_id = ?
+            AND COALESCE(created_at_node, completed_at_node) >= ?
+            AND COALESCE(created_at_node, completed_at_node) <= ?
+            GROUP BY payment_type, state
+            "#,
+            account_id,
+            node_id,
+            from,
+            to
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                (
+                    r.payment_type,
+                    r.state,
+                    r.count,
+                    r.amount_sat,
+                    r.routing_fee_sat,
+                )
+            })
+            .collect())
+    }
+
+    /// Lists the hashes of a node's synced payments to a destination whose
+    /// pubkey starts with `destination`, or whose alias in the node's graph
+    /// mirror contains it, ignoring case.
+    pub async fn get_payment_hashes_by_destination(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        destination: &str,
+    ) -> Result<Vec<String>> {
+        let destination = destination.to_lowercase();
+        let rows = sqlx::query!(
+            r#"
+            SELECT p.payment_hash as "payment_hash!"
+            FROM synced_payments p
+            LEFT JOIN graph_nodes n
+                ON n.source_node_id = p.node_id AND n.pubkey = p.destination_pubkey
+            WHERE p.account_id = ?1 AND p.node_id = ?2
+            AND p.destination_pubkey IS NOT NULL
+            AND (
+                substr(p.destination_pubkey, 1, length(?3)) = ?3
+                OR (n.alias != '' AND instr(lower(n.alias), ?3) > 0)
+            )
+            "#,
+            account_id,
+            node_id,
+            destination
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.payment_hash).collect())
+    }
+
+    /// Lists the hashes of a node's synced invoices whose memo contains
+    /// `memo_contains`, ignoring case, and whose amount paid lies within the
+    /// inclusive msat bounds. Unset criteria match every invoice.
+    pub async fn get_invoice_hashes_matching(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        memo_contains: Option<&str>,
+        min_paid_msat: Option<i64>,
+        max_paid_msat: Option<i64>,
+    ) -> Result<Vec<String>> {
+        let memo_contains = memo_contains.map(str::to_lowercase);
+        let rows = sqlx::query!(
+            r#"
+            SELECT payment_hash as "payment_hash!"
+            FROM synced_invoices
+            WHERE account_id = ?1 AND node_id = ?2
+            AND (?3 IS NULL OR instr(lower(memo), ?3) > 0)
+            AND (?4 IS NULL OR amt_paid_msat >= ?4)
+            AND (?5 IS NULL OR amt_paid_msat <= ?5)
+            "#,
+            account_id,
+            node_id,
+            memo_contains,
+            min_paid_msat,
+            max_paid_msat
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.payment_hash).collect())
+    }
+
+    /// Lists a node's settled and failed outgoing payments with a known
+    /// destination that completed since `since`, with the first hop recorded
+    /// for them by the latency tracker.
+    pub async fn get_payment_route_samples(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PaymentRouteSample>> {
+        let samples = sqlx::query_as!(
+            PaymentRouteSample,
+            r#"
+            SELECT
+            p.destination_pubkey as "destination!",
+            p.state as "state!",
+            p.amount_sat as "amount_sat!",
+            p.routing_fee_sat as "routing_fee_sat?",
+            l.first_hop_channel_id as "first_hop_channel_id?"
+            FROM synced_payments p
+            LEFT JOIN payment_latencies l
+            ON l.account_id = p.account_id AND l.node_id = p.node_id
+            AND l.payment_hash = p.payment_hash
+            WHERE p.account_id = ? AND p.node_id = ? AND p.payment_type = 'Outgoing'
+            AND p.state IN ('Settled', 'Failed') AND p.destination_pubkey IS NOT NULL
+            AND COALESCE(p.completed_at_node, p.created_at_node) >= ?
+            "#,
+            account_id,
+            node_id,
+            since
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(samples)
+    }
+
+    /// When a node's payments were last synced.
+    pub async fn get_payments_synced_at(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT MAX(synced_at) as "synced_at?: DateTime<Utc>"
+            FROM synced_payments
+            WHERE account_id = ? AND node_id = ? AND imported_from IS NULL
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(row.synced_at)
+    }
+
+    /// Lists the capacity, local and remote balance and privacy of a node's
+    /// synced channels that aren't closed, smallest first.
+    pub async fn get_open_channel_balances(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Vec<(i64, i64, i64, bool)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+            capacity_sat as "capacity_sat!: i64",
+            local_balance_sat as "local_balance_sat!: i64",
+            remote_balance_sat as "remote_balance_sat!: i64",
+            private as "private!: bool"
+            FROM synced_channels
+            WHERE account_id = ? AND node_id = ?
+            AND channel_state NOT IN ('Closed', 'Failed')
+            ORDER BY capacity_sat ASC
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                (
+                    r.capacity_sat,
+                    r.local_balance_sat,
+                    r.remote_balance_sat,
+                    r.private,
+                )
+            })
+            .collect())
+    }
+
+    /// When a node's channels were last synced.
+    pub async fn get_channels_synced_at(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT MAX(synced_at) as "synced_at?: DateTime<Utc>"
+            FROM synced_channels
+            WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(row.synced_at)
+    }
+
+    /// Lists a node's synced channels.
+    pub async fn get_channels(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Vec<SyncedChannel>> {
+        let channels = sqlx::query_as!(
+            SyncedChannel,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            chan_id as "chan_id!",
+            remote_pubkey,
+            alias,
+            channel_state as "channel_state!",
+            private as "private!: bool",
+            local_balance_sat as "local_balance_sat!: i64",
+            remote_balance_sat as "remote_balance_sat!: i64",
+            capacity_sat as "capacity_sat!: i64",
+            synced_at as "synced_at!: DateTime<Utc>"
+            FROM synced_channels
+            WHERE account_id = ? AND node_id = ?
+            ORDER BY id ASC
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(channels)
+    }
+
+    /// Lists a node's synced channels after the row `after`, in id order.
+    pub async fn get_channels_after(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        after: &str,
+        limit: i64,
+    ) -> Result<Vec<SyncedChannel>> {
+        let channels = sqlx::query_as!(
+            SyncedChannel,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            chan_id as "chan_id!",
+            remote_pubkey,
+            alias,
+            channel_state as "channel_state!",
+            private as "private!: bool",
+            local_balance_sat as "local_balance_sat!: i64",
+            remote_balance_sat as "remote_balance_sat!: i64",
+            capacity_sat as "capacity_sat!: i64",
+            synced_at as "synced_at!: DateTime<Utc>"
+            FROM synced_channels
+            WHERE account_id = ? AND node_id = ? AND id > ?
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+            account_id,
+            node_id,
+            after,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(channels)
+    }
+
+    /// Lists a node's synced payments after the row `after`, in id order.
+    pub async fn get_payments_after(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        after: &str,
+        limit: i64,
+    ) -> Result<Vec<SyncedPayment>> {
+        let payments = sqlx::query_as!(
+            SyncedPayment,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            payment_hash as "payment_hash!",
+            state as "state!",
+            payment_type as "payment_type!",
+            amount_sat as "amount_sat!: i64",
+            routing_fee_sat as "routing_fee_sat?: i64",
+            created_at_node as "created_at_node?: DateTime<Utc>",
+            completed_at_node as "completed_at_node?: DateTime<Utc>",
+            keysend_message,
+            destination_pubkey,
+            imported_from,
+            synced_at as "synced_at!: DateTime<Utc>"
+            FROM synced_payments
+            WHERE account_id = ? AND node_id = ? AND id > ?
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+            account_id,
+            node_id,
+            after,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(payments)
+    }
+
+    /// Lists a node's synced invoices after the row `after`, in id order.
+    pub async fn get_invoices_after(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        after: &str,
+        limit: i64,
+    ) -> Result<Vec<SyncedInvoice>> {
+        let invoices = sqlx::query_as!(
+            SyncedInvoice,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            payment_hash as "payment_hash!",
+            state as "state!",
+            memo as "memo!",
+            value_msat as "value_msat!: i64",
+            amt_paid_msat as "amt_paid_msat?: i64",
+            created_at_node as "created_at_node?: DateTime<Utc>",
+            settled_at_node as "settled_at_node?: DateTime<Utc>",
+            is_keysend as "is_keysend!: bool",
+            keysend_message,
+            synced_at as "synced_at!: DateTime<Utc>"
+            FROM synced_invoices
+            WHERE account_id = ? AND node_id = ? AND id > ?
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+            account_id,
+            node_id,
+            after,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(invoices)
+    }
+}
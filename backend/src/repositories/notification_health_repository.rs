@@ -0,0 +1,73 @@
+//! Database repository for notification endpoint health checks.
+
+use crate::database::models::NotificationHealth;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for the latest health check of each notification endpoint.
+pub struct NotificationHealthRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NotificationHealthRepository<'a> {
+    /// Creates a new NotificationHealthRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the latest checks of an account's endpoints.
+    pub async fn get_for_account(&self, account_id: &str) -> Result<Vec<NotificationHealth>> {
+        let health = sqlx::query_as!(
+            NotificationHealth,
+            r#"
+            SELECT
+            notification_id as "notification_id!",
+            account_id as "account_id!",
+            healthy as "healthy!",
+            last_error,
+            last_checked_at as "last_checked_at!: DateTime<Utc>",
+            failing_since as "failing_since?: DateTime<Utc>",
+            alerted_at as "alerted_at?: DateTime<Utc>"
+            FROM notification_health
+            WHERE account_id = ?
+            "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(health)
+    }
+
+    /// Stores the latest check of an endpoint, replacing the previous one.
+    pub async fn upsert(&self, health: &NotificationHealth) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO notification_health (
+                notification_id, account_id, healthy, last_error, last_checked_at,
+                failing_since, alerted_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (notification_id) DO UPDATE SET
+                healthy = excluded.healthy,
+                last_error = excluded.last_error,
+                last_checked_at = excluded.last_checked_at,
+                failing_since = excluded.failing_since,
+                alerted_at = excluded.alerted_at
+            "#,
+            health.notification_id,
+            health.account_id,
+            health.healthy,
+            health.last_error,
+            health.last_checked_at,
+            health.failing_since,
+            health.alerted_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -3,10 +3,24 @@
 //! Provides CRUD operations for webhook and Discord notifications.
 
 use crate::database::models::{CreateNotification, Notification};
+use crate::utils::crypto::StringCrypto;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 
+/// Decrypts the URL and headers of a notification read from the database;
+/// webhook and Discord URLs carry their tokens. Rows from before URLs were
+/// encrypted come back as is.
+fn reveal_secrets(mut notification: Notification) -> Result<Notification> {
+    let crypto = StringCrypto::from_env()?;
+    notification.url = crypto.reveal(&notification.url)?;
+    notification.headers = notification
+        .headers
+        .map(|headers| crypto.reveal(&headers))
+        .transpose()?;
+    Ok(notification)
+}
+
 /// Repository for notification database operations.
 pub struct NotificationRepository<'a> {
     /// Shared SQLite connection pool
@@ -24,11 +38,21 @@ impl<'a> NotificationRepository<'a> {
         &self,
         notification: CreateNotification,
     ) -> Result<Notification> {
+        let crypto = StringCrypto::from_env()?;
+        let url = crypto.encrypt(&notification.url)?;
+        let headers = notification
+            .headers
+            .as_deref()
+            .map(|headers| crypto.encrypt(headers))
+            .transpose()?;
         let notification = sqlx::query_as!(
             Notification,
             r#"
-            INSERT INTO notifications (id, account_id, user_id, name, notification_type, url, is_active)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO notifications (
+                id, account_id, user_id, name, notification_type, url, channel, payload_version,
+                is_active, timeout_secs, headers, verify_tls
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING
             id as "id!",
             account_id as "account_id!",
@@ -36,24 +60,34 @@ impl<'a> NotificationRepository<'a> {
             name as "name!",
             notification_type as "notification_type: crate::database::models::NotificationType",
             url as "url!",
+            channel as "channel?",
+            payload_version as "payload_version!",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             updated_at as "updated_at!: DateTime<Utc>",
             is_deleted as "is_deleted!",
-            deleted_at as "deleted_at?: DateTime<Utc>"
+            deleted_at as "deleted_at?: DateTime<Utc>",
+            timeout_secs as "timeout_secs?",
+            headers as "headers?",
+            verify_tls as "verify_tls!"
             "#,
             notification.id,
             notification.account_id,
             notification.user_id,
             notification.name,
             notification.notification_type,
-            notification.url,
-            true
+            url,
+            notification.channel,
+            notification.payload_version,
+            true,
+            notification.timeout_secs,
+            headers,
+            notification.verify_tls
         )
         .fetch_one(self.pool)
         .await?;
 
-        Ok(notification)
+        reveal_secrets(notification)
     }
 
     /// Retrieves a notification by its ID.
@@ -68,11 +102,16 @@ impl<'a> NotificationRepository<'a> {
             name as "name!",
             notification_type as "notification_type: crate::database::models::NotificationType",
             url as "url!",
+            channel as "channel?",
+            payload_version as "payload_version!",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             updated_at as "updated_at!: DateTime<Utc>",
             is_deleted as "is_deleted!",
-            deleted_at as "deleted_at?: DateTime<Utc>"
+            deleted_at as "deleted_at?: DateTime<Utc>",
+            timeout_secs as "timeout_secs?",
+            headers as "headers?",
+            verify_tls as "verify_tls!"
             FROM notifications WHERE id = ? AND is_deleted = 0
             "#,
             id
@@ -80,7 +119,7 @@ impl<'a> NotificationRepository<'a> {
         .fetch_optional(self.pool)
         .await?;
 
-        Ok(notification)
+        notification.map(reveal_secrets).transpose()
     }
 
     /// Retrieves all notifications for an account.
@@ -98,11 +137,16 @@ impl<'a> NotificationRepository<'a> {
             name as "name!",
             notification_type as "notification_type: crate::database::models::NotificationType",
             url as "url!",
+            channel as "channel?",
+            payload_version as "payload_version!",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             updated_at as "updated_at!: DateTime<Utc>",
             is_deleted as "is_deleted!",
-            deleted_at as "deleted_at?: DateTime<Utc>"
+            deleted_at as "deleted_at?: DateTime<Utc>",
+            timeout_secs as "timeout_secs?",
+            headers as "headers?",
+            verify_tls as "verify_tls!"
             FROM notifications
             WHERE account_id = ? AND is_deleted = 0
             ORDER BY created_at DESC
@@ -112,16 +156,96 @@ impl<'a> NotificationRepository<'a> {
         .fetch_all(self.pool)
         .await?;
 
-        Ok(notifications)
+        notifications.into_iter().map(reveal_secrets).collect()
+    }
+
+    /// Retrieves a notification by its ID, deleted or not.
+    pub async fn get_notification_by_id_with_deleted(
+        &self,
+        id: &str,
+    ) -> Result<Option<Notification>> {
+        let notification = sqlx::query_as!(
+            Notification,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            name as "name!",
+            notification_type as "notification_type: crate::database::models::NotificationType",
+            url as "url!",
+            channel as "channel?",
+            payload_version as "payload_version!",
+            is_active as "is_active!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>",
+            timeout_secs as "timeout_secs?",
+            headers as "headers?",
+            verify_tls as "verify_tls!"
+            FROM notifications WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        notification.map(reveal_secrets).transpose()
+    }
+
+    /// Retrieves the deleted notifications of an account, most recently
+    /// deleted first.
+    pub async fn get_deleted_notifications_by_account_id(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<Notification>> {
+        let notifications = sqlx::query_as!(
+            Notification,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            name as "name!",
+            notification_type as "notification_type: crate::database::models::NotificationType",
+            url as "url!",
+            channel as "channel?",
+            payload_version as "payload_version!",
+            is_active as "is_active!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>",
+            timeout_secs as "timeout_secs?",
+            headers as "headers?",
+            verify_tls as "verify_tls!"
+            FROM notifications
+            WHERE account_id = ? AND is_deleted = 1
+            ORDER BY deleted_at DESC
+            "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        notifications.into_iter().map(reveal_secrets).collect()
     }
 
-    /// Updates a notification.
+    /// Updates a notification. `channel`, `timeout_secs` and `headers` are
+    /// `Some(None)` to clear them.
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_notification(
         &self,
         id: &str,
         name: Option<&str>,
         url: Option<&str>,
+        channel: Option<Option<&str>>,
+        payload_version: Option<i64>,
         is_active: Option<bool>,
+        timeout_secs: Option<Option<i64>>,
+        headers: Option<Option<&str>>,
+        verify_tls: Option<bool>,
     ) -> Result<bool> {
         // Build the query dynamically based on provided fields
         let mut set_clauses = Vec::new();
@@ -135,10 +259,30 @@ impl<'a> NotificationRepository<'a> {
             param_count += 1;
             set_clauses.push(format!("url = ?{param_count}"));
         }
+        if channel.is_some() {
+            param_count += 1;
+            set_clauses.push(format!("channel = ?{param_count}"));
+        }
+        if payload_version.is_some() {
+            param_count += 1;
+            set_clauses.push(format!("payload_version = ?{param_count}"));
+        }
         if is_active.is_some() {
             param_count += 1;
             set_clauses.push(format!("is_active = ?{param_count}"));
         }
+        if timeout_secs.is_some() {
+            param_count += 1;
+            set_clauses.push(format!("timeout_secs = ?{param_count}"));
+        }
+        if headers.is_some() {
+            param_count += 1;
+            set_clauses.push(format!("headers = ?{param_count}"));
+        }
+        if verify_tls.is_some() {
+            param_count += 1;
+            set_clauses.push(format!("verify_tls = ?{param_count}"));
+        }
 
         if set_clauses.is_empty() {
             return Ok(false);
@@ -151,6 +295,12 @@ impl<'a> NotificationRepository<'a> {
             param_count + 1
         );
 
+        let crypto = StringCrypto::from_env()?;
+        let url = url.map(|url| crypto.encrypt(url)).transpose()?;
+        let headers = headers
+            .map(|headers| headers.map(|headers| crypto.encrypt(headers)).transpose())
+            .transpose()?;
+
         // Execute query with proper parameter binding
         let mut query_builder = sqlx::query(&query);
 
@@ -160,9 +310,24 @@ impl<'a> NotificationRepository<'a> {
         if let Some(url) = url {
             query_builder = query_builder.bind(url);
         }
+        if let Some(channel) = channel {
+            query_builder = query_builder.bind(channel);
+        }
+        if let Some(payload_version) = payload_version {
+            query_builder = query_builder.bind(payload_version);
+        }
         if let Some(is_active) = is_active {
             query_builder = query_builder.bind(is_active);
         }
+        if let Some(timeout_secs) = timeout_secs {
+            query_builder = query_builder.bind(timeout_secs);
+        }
+        if let Some(headers) = headers {
+            query_builder = query_builder.bind(headers);
+        }
+        if let Some(verify_tls) = verify_tls {
+            query_builder = query_builder.bind(verify_tls);
+        }
         query_builder = query_builder.bind(id);
 
         let rows_affected = query_builder.execute(self.pool).await?.rows_affected();
@@ -170,7 +335,74 @@ impl<'a> NotificationRepository<'a> {
         Ok(rows_affected > 0)
     }
 
-    /// Soft deletes a notification.
+    /// Returns every notification URL as stored, including deleted ones, as
+    /// `(id, url)`.
+    pub async fn get_stored_urls(&self) -> Result<Vec<(String, String)>> {
+        let rows = sqlx::query!(r#"SELECT id as "id!", url as "url!" FROM notifications"#)
+            .fetch_all(self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| (row.id, row.url)).collect())
+    }
+
+    /// Overwrites the stored URL of a notification with an already encrypted
+    /// value, provided it's still `current_url`.
+    ///
+    /// # Returns
+    /// `true` if the notification was updated
+    pub async fn update_stored_url(&self, id: &str, current_url: &str, url: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE notifications SET url = ? WHERE id = ? AND url = ?",
+            url,
+            id,
+            current_url
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns every notification's headers as stored, including deleted
+    /// ones, as `(id, headers)`.
+    pub async fn get_stored_headers(&self) -> Result<Vec<(String, String)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id as "id!", headers as "headers!"
+            FROM notifications WHERE headers IS NOT NULL
+            "#
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.id, row.headers)).collect())
+    }
+
+    /// Overwrites the stored headers of a notification with an already
+    /// encrypted value, provided they're still `current_headers`.
+    ///
+    /// # Returns
+    /// `true` if the notification was updated
+    pub async fn update_stored_headers(
+        &self,
+        id: &str,
+        current_headers: &str,
+        headers: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE notifications SET headers = ? WHERE id = ? AND headers = ?",
+            headers,
+            id,
+            current_headers
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Soft deletes a notification. Its row stays, so the events and
+    /// deliveries pointing at it stay linked and it can be restored.
     pub async fn delete_notification(&self, id: &str) -> Result<()> {
         sqlx::query!(
             r#"
@@ -185,4 +417,23 @@ impl<'a> NotificationRepository<'a> {
 
         Ok(())
     }
+
+    /// Restores a soft deleted notification.
+    ///
+    /// # Returns
+    /// `true` if the notification was restored
+    pub async fn restore_notification(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE notifications
+            SET is_deleted = 0, deleted_at = NULL, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND is_deleted = 1
+            "#,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
 }
@@ -0,0 +1,116 @@
+//! Database repository for recorded payment latencies.
+
+use crate::database::models::PaymentLatencySample;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for time-to-settle samples of outgoing payments.
+pub struct PaymentLatencyRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> PaymentLatencyRepository<'a> {
+    /// Creates a new PaymentLatencyRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a sample, ignoring payments that were already recorded.
+    /// Returns whether the sample was new.
+    pub async fn insert_sample(&self, sample: &PaymentLatencySample) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO payment_latencies (
+                account_id, node_id, payment_hash, destination, first_hop_channel_id,
+                settle_ms, htlc_ms, settled_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, node_id, payment_hash) DO NOTHING
+            "#,
+            sample.account_id,
+            sample.node_id,
+            sample.payment_hash,
+            sample.destination,
+            sample.first_hop_channel_id,
+            sample.settle_ms,
+            sample.htlc_ms,
+            sample.settled_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns when the latest recorded payment of a node settled.
+    pub async fn get_latest_settled_at(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let result = sqlx::query!(
+            r#"
+            SELECT MAX(settled_at) as "settled_at?: DateTime<Utc>"
+            FROM payment_latencies WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(result.settled_at)
+    }
+
+    /// Lists a node's samples settled since `since`, oldest first.
+    pub async fn get_samples_since(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PaymentLatencySample>> {
+        let samples = sqlx::query_as!(
+            PaymentLatencySample,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            payment_hash as "payment_hash!",
+            destination,
+            first_hop_channel_id,
+            settle_ms as "settle_ms!",
+            htlc_ms,
+            settled_at as "settled_at!: DateTime<Utc>"
+            FROM payment_latencies
+            WHERE account_id = ? AND node_id = ? AND settled_at >= ?
+            ORDER BY settled_at ASC
+            "#,
+            account_id,
+            node_id,
+            since
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(samples)
+    }
+
+    /// Removes samples of an account settled before `cutoff`.
+    pub async fn delete_samples_before(
+        &self,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM payment_latencies WHERE account_id = ? AND settled_at < ?",
+            account_id,
+            cutoff
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
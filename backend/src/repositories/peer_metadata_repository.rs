@@ -0,0 +1,86 @@
+//! Database repository for cached peer metadata.
+
+use crate::database::models::{PeerContact, PeerMetadata};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for metadata fetched from Amboss and 1ML.
+pub struct PeerMetadataRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> PeerMetadataRepository<'a> {
+    /// Creates a new PeerMetadataRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Fetches the cached metadata of a node, however old.
+    pub async fn get(&self, pubkey: &str) -> Result<Option<PeerMetadata>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+            pubkey as "pubkey!",
+            community_tags as "community_tags!",
+            rank as "rank?: i64",
+            email,
+            twitter,
+            telegram,
+            website,
+            fetched_at as "fetched_at!: DateTime<Utc>"
+            FROM peer_metadata WHERE pubkey = ?
+            "#,
+            pubkey
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(row.map(|row| PeerMetadata {
+            pubkey: row.pubkey,
+            community_tags: serde_json::from_str(&row.community_tags).unwrap_or_default(),
+            rank: row.rank,
+            contact: PeerContact {
+                email: row.email,
+                twitter: row.twitter,
+                telegram: row.telegram,
+                website: row.website,
+            },
+            fetched_at: row.fetched_at,
+        }))
+    }
+
+    /// Stores freshly fetched metadata, replacing the cached copy.
+    pub async fn upsert(&self, metadata: &PeerMetadata) -> Result<()> {
+        let community_tags = serde_json::to_string(&metadata.community_tags)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO peer_metadata (
+                pubkey, community_tags, rank, email, twitter, telegram, website, fetched_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(pubkey) DO UPDATE SET
+                community_tags = excluded.community_tags,
+                rank = excluded.rank,
+                email = excluded.email,
+                twitter = excluded.twitter,
+                telegram = excluded.telegram,
+                website = excluded.website,
+                fetched_at = excluded.fetched_at
+            "#,
+            metadata.pubkey,
+            community_tags,
+            metadata.rank,
+            metadata.contact.email,
+            metadata.contact.twitter,
+            metadata.contact.telegram,
+            metadata.contact.website,
+            metadata.fetched_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+//! Database repository for route probing policies and probe results.
+
+use crate::database::models::{ProbePolicy, ProbeResult};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for probing settings and the probe log.
+pub struct ProbeRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ProbeRepository<'a> {
+    /// Creates a new ProbeRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves the policy of one of an account's nodes, if one was saved.
+    pub async fn get_policy(&self, account_id: &str, node_id: &str) -> Result<Option<ProbePolicy>> {
+        let policy = sqlx::query_as!(
+            ProbePolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            targets as "targets!",
+            amount_sat as "amount_sat!",
+            max_fee_sat as "max_fee_sat!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM probe_policies WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Lists the enabled policies of an account's nodes.
+    pub async fn get_enabled_policies(&self, account_id: &str) -> Result<Vec<ProbePolicy>> {
+        let policies = sqlx::query_as!(
+            ProbePolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            targets as "targets!",
+            amount_sat as "amount_sat!",
+            max_fee_sat as "max_fee_sat!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM probe_policies WHERE account_id = ? AND is_enabled = 1
+            "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(policies)
+    }
+
+    /// Creates or replaces a node's policy.
+    pub async fn upsert_policy(&self, policy: &ProbePolicy) -> Result<ProbePolicy> {
+        let saved = sqlx::query_as!(
+            ProbePolicy,
+            r#"
+            INSERT INTO probe_policies (
+                account_id, node_id, is_enabled, targets, amount_sat, max_fee_sat, updated_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, node_id) DO UPDATE SET
+                is_enabled = excluded.is_enabled,
+                targets = excluded.targets,
+                amount_sat = excluded.amount_sat,
+                max_fee_sat = excluded.max_fee_sat,
+                updated_by = excluded.updated_by
+            RETURNING
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            targets as "targets!",
+            amount_sat as "amount_sat!",
+            max_fee_sat as "max_fee_sat!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            policy.account_id,
+            policy.node_id,
+            policy.is_enabled,
+            policy.targets,
+            policy.amount_sat,
+            policy.max_fee_sat,
+            policy.updated_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+
+    /// Records a probe's outcome.
+    pub async fn create_result(&self, result: &ProbeResult) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO probe_results (
+                id, account_id, node_id, destination, outgoing_channel_id,
+                reached, latency_ms, failure_reason, probed_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            result.id,
+            result.account_id,
+            result.node_id,
+            result.destination,
+            result.outgoing_channel_id,
+            result.reached,
+            result.latency_ms,
+            result.failure_reason,
+            result.probed_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves the latest probe of a route, if it was ever probed.
+    pub async fn get_last_result(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        destination: &str,
+        outgoing_channel_id: Option<&str>,
+    ) -> Result<Option<ProbeResult>> {
+        let result = sqlx::query_as!(
+            ProbeResult,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            destination as "destination!",
+            outgoing_channel_id as "outgoing_channel_id?",
+            reached as "reached!",
+            latency_ms as "latency_ms!",
+            failure_reason as "failure_reason?",
+            probed_at as "probed_at!: DateTime<Utc>"
+            FROM probe_results
+            WHERE account_id = ? AND node_id = ? AND destination = ?
+            AND outgoing_channel_id IS ?
+            ORDER BY probed_at DESC
+            LIMIT 1
+            "#,
+            account_id,
+            node_id,
+            destination,
+            outgoing_channel_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Lists a node's probes since `since`, oldest first.
+    pub async fn get_results_since(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ProbeResult>> {
+        let results = sqlx::query_as!(
+            ProbeResult,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            destination as "destination!",
+            outgoing_channel_id as "outgoing_channel_id?",
+            reached as "reached!",
+            latency_ms as "latency_ms!",
+            failure_reason as "failure_reason?",
+            probed_at as "probed_at!: DateTime<Utc>"
+            FROM probe_results
+            WHERE account_id = ? AND node_id = ? AND probed_at >= ?
+            ORDER BY probed_at ASC
+            "#,
+            account_id,
+            node_id,
+            since
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    /// Deletes an account's probes older than `cutoff`.
+    pub async fn delete_results_before(
+        &self,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM probe_results WHERE account_id = ? AND probed_at < ?",
+            account_id,
+            cutoff
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,215 @@
+//! Database repository for auto-rebalancing policies and rebalance attempts.
+
+use crate::database::models::{RebalanceAttempt, RebalancePolicy};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for rebalancing settings and the attempt log.
+pub struct RebalanceRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RebalanceRepository<'a> {
+    /// Creates a new RebalanceRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves the policy of one of an account's nodes, if one was saved.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<RebalancePolicy>> {
+        let policy = sqlx::query_as!(
+            RebalancePolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            targets as "targets!",
+            tolerance_pct as "tolerance_pct!: f64",
+            max_fee_ppm as "max_fee_ppm!",
+            monthly_budget_sat as "monthly_budget_sat!",
+            blackout_windows as "blackout_windows!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM rebalance_policies WHERE account_id = ? AND node_id = ?
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Lists the enabled policies of an account's nodes.
+    pub async fn get_enabled_policies(&self, account_id: &str) -> Result<Vec<RebalancePolicy>> {
+        let policies = sqlx::query_as!(
+            RebalancePolicy,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            targets as "targets!",
+            tolerance_pct as "tolerance_pct!: f64",
+            max_fee_ppm as "max_fee_ppm!",
+            monthly_budget_sat as "monthly_budget_sat!",
+            blackout_windows as "blackout_windows!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM rebalance_policies WHERE account_id = ? AND is_enabled = 1
+            "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(policies)
+    }
+
+    /// Creates or replaces a node's policy.
+    pub async fn upsert_policy(&self, policy: &RebalancePolicy) -> Result<RebalancePolicy> {
+        let saved = sqlx::query_as!(
+            RebalancePolicy,
+            r#"
+            INSERT INTO rebalance_policies (
+                account_id, node_id, is_enabled, targets, tolerance_pct,
+                max_fee_ppm, monthly_budget_sat, blackout_windows, updated_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, node_id) DO UPDATE SET
+                is_enabled = excluded.is_enabled,
+                targets = excluded.targets,
+                tolerance_pct = excluded.tolerance_pct,
+                max_fee_ppm = excluded.max_fee_ppm,
+                monthly_budget_sat = excluded.monthly_budget_sat,
+                blackout_windows = excluded.blackout_windows,
+                updated_by = excluded.updated_by
+            RETURNING
+            account_id as "account_id!",
+            node_id as "node_id!",
+            is_enabled as "is_enabled!",
+            targets as "targets!",
+            tolerance_pct as "tolerance_pct!: f64",
+            max_fee_ppm as "max_fee_ppm!",
+            monthly_budget_sat as "monthly_budget_sat!",
+            blackout_windows as "blackout_windows!",
+            updated_by as "updated_by!",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            policy.account_id,
+            policy.node_id,
+            policy.is_enabled,
+            policy.targets,
+            policy.tolerance_pct,
+            policy.max_fee_ppm,
+            policy.monthly_budget_sat,
+            policy.blackout_windows,
+            policy.updated_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+
+    /// Records a rebalance attempt.
+    pub async fn create_attempt(&self, attempt: &RebalanceAttempt) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO rebalance_attempts (
+                id, account_id, node_id, outgoing_channel_id, incoming_channel_id, amount_sat,
+                fee_msat, route, succeeded, error, is_automatic, created_by, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            attempt.id,
+            attempt.account_id,
+            attempt.node_id,
+            attempt.outgoing_channel_id,
+            attempt.incoming_channel_id,
+            attempt.amount_sat,
+            attempt.fee_msat,
+            attempt.route,
+            attempt.succeeded,
+            attempt.error,
+            attempt.is_automatic,
+            attempt.created_by,
+            attempt.created_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists a node's most recent attempts, newest first.
+    pub async fn get_attempts(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        limit: i64,
+    ) -> Result<Vec<RebalanceAttempt>> {
+        let attempts = sqlx::query_as!(
+            RebalanceAttempt,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            outgoing_channel_id as "outgoing_channel_id!",
+            incoming_channel_id as "incoming_channel_id!",
+            amount_sat as "amount_sat!",
+            fee_msat as "fee_msat!",
+            route as "route!",
+            succeeded as "succeeded!",
+            error as "error?",
+            is_automatic as "is_automatic!",
+            created_by as "created_by?",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM rebalance_attempts
+            WHERE account_id = ? AND node_id = ?
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+            account_id,
+            node_id,
+            limit
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(attempts)
+    }
+
+    /// Sums the fees automatic rebalances on a node paid since `since`, in msat.
+    pub async fn get_automatic_fees_since(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<i64> {
+        let fees = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(fee_msat), 0) as "fees!: i64"
+            FROM rebalance_attempts
+            WHERE account_id = ? AND node_id = ? AND is_automatic = 1 AND succeeded = 1
+            AND created_at >= ?
+            "#,
+            account_id,
+            node_id,
+            since
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(fees)
+    }
+}
@@ -0,0 +1,251 @@
+//! Database repository for per-account retention settings and pruning.
+
+use crate::database::models::{JobStatus, JobType, RetentionSettings};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for retention settings and the data they govern.
+pub struct RetentionRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RetentionRepository<'a> {
+    /// Creates a new RetentionRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves an account's settings, if any were saved.
+    pub async fn get_settings(&self, account_id: &str) -> Result<Option<RetentionSettings>> {
+        let settings = sqlx::query_as!(
+            RetentionSettings,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            events_days as "events_days?",
+            deliveries_days as "deliveries_days?",
+            audit_logs_days as "audit_logs_days?",
+            balance_history_days as "balance_history_days?",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM account_retention_settings WHERE account_id = ?
+            "#,
+            account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    /// Lists the settings of every account that has any.
+    pub async fn get_all_settings(&self) -> Result<Vec<RetentionSettings>> {
+        let settings = sqlx::query_as!(
+            RetentionSettings,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            events_days as "events_days?",
+            deliveries_days as "deliveries_days?",
+            audit_logs_days as "audit_logs_days?",
+            balance_history_days as "balance_history_days?",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM account_retention_settings
+            "#
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    /// Creates or replaces an account's settings.
+    pub async fn upsert_settings(&self, settings: &RetentionSettings) -> Result<RetentionSettings> {
+        let saved = sqlx::query_as!(
+            RetentionSettings,
+            r#"
+            INSERT INTO account_retention_settings (
+                account_id, events_days, deliveries_days, audit_logs_days, balance_history_days
+            )
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(account_id) DO UPDATE SET
+                events_days = excluded.events_days,
+                deliveries_days = excluded.deliveries_days,
+                audit_logs_days = excluded.audit_logs_days,
+                balance_history_days = excluded.balance_history_days
+            RETURNING
+            account_id as "account_id!",
+            events_days as "events_days?",
+            deliveries_days as "deliveries_days?",
+            audit_logs_days as "audit_logs_days?",
+            balance_history_days as "balance_history_days?",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            settings.account_id,
+            settings.events_days,
+            settings.deliveries_days,
+            settings.audit_logs_days,
+            settings.balance_history_days
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+
+    /// Counts events older than `cutoff` and their approximate size.
+    pub async fn estimate_events(
+        &self,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<(i64, i64)> {
+        let result = sqlx::query!(
+            r#"
+            SELECT
+            COUNT(*) as "rows!: i64",
+            COALESCE(SUM(LENGTH(title) + LENGTH(description) + LENGTH(data)), 0) as "bytes!: i64"
+            FROM events WHERE account_id = ? AND timestamp < ?
+            "#,
+            account_id,
+            cutoff
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok((result.rows, result.bytes))
+    }
+
+    /// Counts finished notification deliveries older than `cutoff` and their approximate size.
+    pub async fn estimate_deliveries(
+        &self,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<(i64, i64)> {
+        let result = sqlx::query!(
+            r#"
+            SELECT
+            COUNT(*) as "rows!: i64",
+            COALESCE(SUM(LENGTH(payload) + COALESCE(LENGTH(last_error), 0)), 0) as "bytes!: i64"
+            FROM jobs
+            WHERE account_id = ? AND job_type = ? AND status IN (?, ?) AND updated_at < ?
+            "#,
+            account_id,
+            JobType::NotificationDelivery,
+            JobStatus::Completed,
+            JobStatus::Failed,
+            cutoff
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok((result.rows, result.bytes))
+    }
+
+    /// Counts audit log entries older than `cutoff` and their approximate size.
+    pub async fn estimate_audit_logs(
+        &self,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<(i64, i64)> {
+        let result = sqlx::query!(
+            r#"
+            SELECT
+            COUNT(*) as "rows!: i64",
+            COALESCE(SUM(LENGTH(action) + LENGTH(details)), 0) as "bytes!: i64"
+            FROM audit_logs WHERE account_id = ? AND created_at < ?
+            "#,
+            account_id,
+            cutoff
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok((result.rows, result.bytes))
+    }
+
+    /// Counts balance snapshots older than `cutoff` and their approximate size.
+    pub async fn estimate_balance_history(
+        &self,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<(i64, i64)> {
+        let result = sqlx::query!(
+            r#"
+            SELECT
+            COUNT(*) as "rows!: i64",
+            -- 64 bytes covers the numeric columns of a snapshot
+            COALESCE(SUM(LENGTH(id) + LENGTH(node_id) + 64), 0) as "bytes!: i64"
+            FROM balance_snapshots WHERE account_id = ? AND captured_at < ?
+            "#,
+            account_id,
+            cutoff
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok((result.rows, result.bytes))
+    }
+
+    /// Deletes events older than `cutoff`.
+    pub async fn prune_events(&self, account_id: &str, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM events WHERE account_id = ? AND timestamp < ?",
+            account_id,
+            cutoff
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes finished notification deliveries older than `cutoff`.
+    pub async fn prune_deliveries(&self, account_id: &str, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM jobs
+            WHERE account_id = ? AND job_type = ? AND status IN (?, ?) AND updated_at < ?
+            "#,
+            account_id,
+            JobType::NotificationDelivery,
+            JobStatus::Completed,
+            JobStatus::Failed,
+            cutoff
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes audit log entries older than `cutoff`.
+    pub async fn prune_audit_logs(&self, account_id: &str, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM audit_logs WHERE account_id = ? AND created_at < ?",
+            account_id,
+            cutoff
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes balance snapshots older than `cutoff`.
+    pub async fn prune_balance_history(
+        &self,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM balance_snapshots WHERE account_id = ? AND captured_at < ?",
+            account_id,
+            cutoff
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
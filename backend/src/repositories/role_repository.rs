@@ -1,16 +1,17 @@
 //! Database repository for role management operations.
 //!
-//! Provides read-only access to system roles with:
+//! Provides access to built-in and account-defined roles with:
 //! - Role lookup by ID or name
 //! - Complete role listing
-use crate::database::models::Role;
+//! - Custom role creation
+use crate::database::models::{Permission, Role};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 
 /// Repository for role database operations.
 ///
-/// Handles all read operations for the Role entity,
+/// Handles all operations for the Role entity,
 /// enforcing data consistency and access patterns.
 pub struct RoleRepository<'a> {
     /// Shared SQLite connection pool
@@ -39,7 +40,10 @@ impl<'a> RoleRepository<'a> {
             r#"
             SELECT 
             id as "id!",
+            account_id as "account_id?",
             name as "name!",
+            permissions as "permissions!",
+            node_ids as "node_ids?",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             updated_at as "updated_at!: DateTime<Utc>",
@@ -55,7 +59,7 @@ impl<'a> RoleRepository<'a> {
         Ok(role)
     }
 
-    /// Retrieves a role by its exact name.
+    /// Retrieves a built-in role by its exact name.
     ///
     /// # Arguments
     /// * `name` - Exact role name to search for
@@ -71,13 +75,16 @@ impl<'a> RoleRepository<'a> {
             r#"
             SELECT 
             id as "id!",
+            account_id as "account_id?",
             name as "name!",
+            permissions as "permissions!",
+            node_ids as "node_ids?",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             updated_at as "updated_at!: DateTime<Utc>",
             is_deleted as "is_deleted!",
             deleted_at as "deleted_at?: DateTime<Utc>"
-            FROM roles WHERE name = ? AND is_deleted = 0
+            FROM roles WHERE name = ? AND account_id IS NULL AND is_deleted = 0
             "#,
             name
         )
@@ -97,7 +104,10 @@ impl<'a> RoleRepository<'a> {
             r#"
             SELECT 
             id as "id!",
+            account_id as "account_id?",
             name as "name!",
+            permissions as "permissions!",
+            node_ids as "node_ids?",
             is_active as "is_active!",
             created_at as "created_at!: DateTime<Utc>",
             updated_at as "updated_at!: DateTime<Utc>",
@@ -111,4 +121,84 @@ impl<'a> RoleRepository<'a> {
 
         Ok(roles)
     }
+
+    /// Retrieves the roles usable within an account: the built-in roles plus
+    /// the account's own custom roles.
+    ///
+    /// # Arguments
+    /// * `account_id` - Account whose custom roles to include
+    pub async fn get_roles_for_account(&self, account_id: &str) -> Result<Vec<Role>> {
+        let roles = sqlx::query_as!(
+            Role,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id?",
+            name as "name!",
+            permissions as "permissions!",
+            node_ids as "node_ids?",
+            is_active as "is_active!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>"
+            FROM roles
+            WHERE (account_id IS NULL OR account_id = ?) AND is_deleted = 0
+            ORDER BY account_id IS NOT NULL, name
+            "#,
+            account_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(roles)
+    }
+
+    /// Creates a custom role owned by an account.
+    ///
+    /// # Arguments
+    /// * `id` - New role ID
+    /// * `account_id` - Owning account
+    /// * `name` - Role name, unique within the account
+    /// * `permissions` - Scopes the role grants
+    /// * `node_ids` - Nodes the role is limited to; `None` for every node
+    pub async fn create_role(
+        &self,
+        id: &str,
+        account_id: &str,
+        name: &str,
+        permissions: &[Permission],
+        node_ids: Option<&[String]>,
+    ) -> Result<Role> {
+        let permissions = serde_json::to_string(permissions)?;
+        let node_ids = node_ids.map(serde_json::to_string).transpose()?;
+
+        let role = sqlx::query_as!(
+            Role,
+            r#"
+            INSERT INTO roles (id, account_id, name, permissions, node_ids)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id?",
+            name as "name!",
+            permissions as "permissions!",
+            node_ids as "node_ids?",
+            is_active as "is_active!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>",
+            is_deleted as "is_deleted!",
+            deleted_at as "deleted_at?: DateTime<Utc>"
+            "#,
+            id,
+            account_id,
+            name,
+            permissions,
+            node_ids
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(role)
+    }
 }
@@ -0,0 +1,108 @@
+//! Database repository for saved filter views.
+
+use crate::database::models::SavedView;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for the filter views users save.
+pub struct SavedViewRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SavedViewRepository<'a> {
+    /// Creates a new SavedViewRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a view for a user.
+    pub async fn create_view(&self, view: &SavedView) -> Result<SavedView> {
+        let saved = sqlx::query_as!(
+            SavedView,
+            r#"
+            INSERT INTO saved_views (id, account_id, user_id, name, endpoint, filters, sort)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            name as "name!",
+            endpoint as "endpoint!",
+            filters as "filters!",
+            sort,
+            created_at as "created_at!: DateTime<Utc>"
+            "#,
+            view.id,
+            view.account_id,
+            view.user_id,
+            view.name,
+            view.endpoint,
+            view.filters,
+            view.sort
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(saved)
+    }
+
+    /// Lists a user's views, optionally only those for one endpoint, by name.
+    pub async fn get_views_by_user_id(
+        &self,
+        user_id: &str,
+        endpoint: Option<&str>,
+    ) -> Result<Vec<SavedView>> {
+        let views = sqlx::query_as!(
+            SavedView,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            user_id as "user_id!",
+            name as "name!",
+            endpoint as "endpoint!",
+            filters as "filters!",
+            sort,
+            created_at as "created_at!: DateTime<Utc>"
+            FROM saved_views
+            WHERE user_id = ? AND (? IS NULL OR endpoint = ?)
+            ORDER BY name ASC
+            "#,
+            user_id,
+            endpoint,
+            endpoint
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(views)
+    }
+
+    /// Checks whether the user already has a view with this name.
+    pub async fn name_exists(&self, user_id: &str, name: &str) -> Result<bool> {
+        let row = sqlx::query!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM saved_views WHERE user_id = ? AND name = ?"#,
+            user_id,
+            name
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(row.count > 0)
+    }
+
+    /// Deletes one of a user's views.
+    pub async fn delete_view(&self, id: &str, user_id: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM saved_views WHERE id = ? AND user_id = ?",
+            id,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
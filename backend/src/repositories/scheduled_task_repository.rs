@@ -0,0 +1,283 @@
+//! Database repository for scheduled tasks and the data they collect.
+
+use crate::database::models::{BalanceSnapshot, ScheduledTask, TaskRunStatus, TaskType};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for scheduled task persistence.
+pub struct ScheduledTaskRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ScheduledTaskRepository<'a> {
+    /// Creates a new ScheduledTaskRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates a task unless one of the same type already exists for the account.
+    pub async fn ensure_task(
+        &self,
+        id: &str,
+        task_type: TaskType,
+        account_id: Option<&str>,
+        cron_expression: &str,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO scheduled_tasks (id, task_type, account_id, cron_expression, next_run_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            id,
+            task_type,
+            account_id,
+            cron_expression,
+            next_run_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves a task by ID.
+    pub async fn get_task_by_id(&self, id: &str) -> Result<Option<ScheduledTask>> {
+        let task = sqlx::query_as!(
+            ScheduledTask,
+            r#"
+            SELECT
+            id as "id!",
+            task_type as "task_type: TaskType",
+            account_id as "account_id?",
+            cron_expression as "cron_expression!",
+            is_active as "is_active!",
+            last_run_at as "last_run_at?: DateTime<Utc>",
+            next_run_at as "next_run_at?: DateTime<Utc>",
+            last_status as "last_status?: TaskRunStatus",
+            last_error as "last_error?",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM scheduled_tasks WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(task)
+    }
+
+    /// Lists an account's tasks, together with system-wide tasks if
+    /// `include_system` is set.
+    pub async fn get_tasks(
+        &self,
+        account_id: &str,
+        include_system: bool,
+    ) -> Result<Vec<ScheduledTask>> {
+        let tasks = sqlx::query_as!(
+            ScheduledTask,
+            r#"
+            SELECT
+            id as "id!",
+            task_type as "task_type: TaskType",
+            account_id as "account_id?",
+            cron_expression as "cron_expression!",
+            is_active as "is_active!",
+            last_run_at as "last_run_at?: DateTime<Utc>",
+            next_run_at as "next_run_at?: DateTime<Utc>",
+            last_status as "last_status?: TaskRunStatus",
+            last_error as "last_error?",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM scheduled_tasks
+            WHERE account_id = ? OR (? AND account_id IS NULL)
+            ORDER BY account_id IS NULL DESC, task_type ASC
+            "#,
+            account_id,
+            include_system
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Returns active tasks whose next run is at or before `now`.
+    pub async fn get_due_tasks(&self, now: DateTime<Utc>) -> Result<Vec<ScheduledTask>> {
+        let tasks = sqlx::query_as!(
+            ScheduledTask,
+            r#"
+            SELECT
+            id as "id!",
+            task_type as "task_type: TaskType",
+            account_id as "account_id?",
+            cron_expression as "cron_expression!",
+            is_active as "is_active!",
+            last_run_at as "last_run_at?: DateTime<Utc>",
+            next_run_at as "next_run_at?: DateTime<Utc>",
+            last_status as "last_status?: TaskRunStatus",
+            last_error as "last_error?",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM scheduled_tasks
+            WHERE is_active = 1 AND next_run_at IS NOT NULL AND next_run_at <= ?
+            ORDER BY next_run_at ASC
+            "#,
+            now
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Records that a run was queued and schedules the following one.
+    pub async fn mark_queued(
+        &self,
+        id: &str,
+        run_at: DateTime<Utc>,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE scheduled_tasks
+            SET last_run_at = ?, next_run_at = ?, last_status = ?
+            WHERE id = ?
+            "#,
+            run_at,
+            next_run_at,
+            TaskRunStatus::Queued,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records the outcome of the latest run.
+    pub async fn record_result(
+        &self,
+        id: &str,
+        status: TaskRunStatus,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE scheduled_tasks
+            SET last_status = ?, last_error = ?
+            WHERE id = ?
+            "#,
+            status,
+            error,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Updates a task's schedule and active flag.
+    pub async fn update_task(
+        &self,
+        id: &str,
+        cron_expression: &str,
+        is_active: bool,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<ScheduledTask> {
+        let task = sqlx::query_as!(
+            ScheduledTask,
+            r#"
+            UPDATE scheduled_tasks
+            SET cron_expression = ?, is_active = ?, next_run_at = ?
+            WHERE id = ?
+            RETURNING
+            id as "id!",
+            task_type as "task_type: TaskType",
+            account_id as "account_id?",
+            cron_expression as "cron_expression!",
+            is_active as "is_active!",
+            last_run_at as "last_run_at?: DateTime<Utc>",
+            next_run_at as "next_run_at?: DateTime<Utc>",
+            last_status as "last_status?: TaskRunStatus",
+            last_error as "last_error?",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            cron_expression,
+            is_active,
+            next_run_at,
+            id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(task)
+    }
+
+    /// Stores a node balance snapshot.
+    pub async fn create_balance_snapshot(&self, snapshot: &BalanceSnapshot) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO balance_snapshots (
+                id, account_id, node_id, local_balance_sat, remote_balance_sat,
+                capacity_sat, num_channels, num_active_channels, btc_price_usd, captured_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            snapshot.id,
+            snapshot.account_id,
+            snapshot.node_id,
+            snapshot.local_balance_sat,
+            snapshot.remote_balance_sat,
+            snapshot.capacity_sat,
+            snapshot.num_channels,
+            snapshot.num_active_channels,
+            snapshot.btc_price_usd,
+            snapshot.captured_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the most recent hourly BTC price timestamp, if any.
+    pub async fn get_latest_price_time(&self) -> Result<Option<DateTime<Utc>>> {
+        let result = sqlx::query!(
+            r#"
+            SELECT MAX(recorded_at) as "recorded_at?: DateTime<Utc>" FROM btc_prices
+            "#
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(result.recorded_at)
+    }
+
+    /// Stores an hourly BTC price, ignoring hours that are already recorded.
+    pub async fn insert_price(
+        &self,
+        id: &str,
+        price_usd: f64,
+        recorded_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO btc_prices (id, price_usd, recorded_at)
+            VALUES (?, ?, ?)
+            "#,
+            id,
+            price_usd,
+            recorded_at
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+//! Database repository for accounts' billing subscriptions.
+
+use crate::database::models::{AccountSubscription, BillingProvider, Plan, SubscriptionStatus};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for account subscriptions.
+pub struct SubscriptionRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SubscriptionRepository<'a> {
+    /// Creates a new SubscriptionRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves an account's subscription, if it ever had one.
+    pub async fn get_subscription(&self, account_id: &str) -> Result<Option<AccountSubscription>> {
+        let subscription = sqlx::query_as!(
+            AccountSubscription,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            plan as "plan: Plan",
+            status as "status: SubscriptionStatus",
+            provider as "provider: BillingProvider",
+            stripe_customer_id as "stripe_customer_id?",
+            stripe_subscription_id as "stripe_subscription_id?",
+            current_period_end as "current_period_end?: DateTime<Utc>",
+            suspended_at as "suspended_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM account_subscriptions WHERE account_id = ?
+            "#,
+            account_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    /// Retrieves the subscription with the given Stripe subscription ID.
+    pub async fn get_by_stripe_subscription_id(
+        &self,
+        stripe_subscription_id: &str,
+    ) -> Result<Option<AccountSubscription>> {
+        let subscription = sqlx::query_as!(
+            AccountSubscription,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            plan as "plan: Plan",
+            status as "status: SubscriptionStatus",
+            provider as "provider: BillingProvider",
+            stripe_customer_id as "stripe_customer_id?",
+            stripe_subscription_id as "stripe_subscription_id?",
+            current_period_end as "current_period_end?: DateTime<Utc>",
+            suspended_at as "suspended_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM account_subscriptions WHERE stripe_subscription_id = ?
+            "#,
+            stripe_subscription_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    /// Creates or updates an account's subscription. Stripe IDs and the
+    /// period end that are `None` keep their stored values.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn upsert_subscription(
+        &self,
+        account_id: &str,
+        plan: Plan,
+        status: SubscriptionStatus,
+        provider: BillingProvider,
+        stripe_customer_id: Option<&str>,
+        stripe_subscription_id: Option<&str>,
+        current_period_end: Option<DateTime<Utc>>,
+    ) -> Result<AccountSubscription> {
+        let subscription = sqlx::query_as!(
+            AccountSubscription,
+            r#"
+            INSERT INTO account_subscriptions (
+                account_id, plan, status, provider, stripe_customer_id, stripe_subscription_id,
+                current_period_end
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(account_id) DO UPDATE SET
+                plan = excluded.plan,
+                status = excluded.status,
+                provider = excluded.provider,
+                stripe_customer_id = COALESCE(excluded.stripe_customer_id, stripe_customer_id),
+                stripe_subscription_id = COALESCE(excluded.stripe_subscription_id, stripe_subscription_id),
+                current_period_end = COALESCE(excluded.current_period_end, current_period_end),
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING
+            account_id as "account_id!",
+            plan as "plan: Plan",
+            status as "status: SubscriptionStatus",
+            provider as "provider: BillingProvider",
+            stripe_customer_id as "stripe_customer_id?",
+            stripe_subscription_id as "stripe_subscription_id?",
+            current_period_end as "current_period_end?: DateTime<Utc>",
+            suspended_at as "suspended_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            "#,
+            account_id,
+            plan,
+            status,
+            provider,
+            stripe_customer_id,
+            stripe_subscription_id,
+            current_period_end
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    /// Lists the subscriptions paid through a provider.
+    pub async fn get_by_provider(
+        &self,
+        provider: BillingProvider,
+    ) -> Result<Vec<AccountSubscription>> {
+        let subscriptions = sqlx::query_as!(
+            AccountSubscription,
+            r#"
+            SELECT
+            account_id as "account_id!",
+            plan as "plan: Plan",
+            status as "status: SubscriptionStatus",
+            provider as "provider: BillingProvider",
+            stripe_customer_id as "stripe_customer_id?",
+            stripe_subscription_id as "stripe_subscription_id?",
+            current_period_end as "current_period_end?: DateTime<Utc>",
+            suspended_at as "suspended_at?: DateTime<Utc>",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at!: DateTime<Utc>"
+            FROM account_subscriptions WHERE provider = ?
+            "#,
+            provider
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(subscriptions)
+    }
+
+    /// Updates the status of an account's subscription.
+    pub async fn set_status(&self, account_id: &str, status: SubscriptionStatus) -> Result<()> {
+        sqlx::query!(
+            "UPDATE account_subscriptions SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE account_id = ?",
+            status,
+            account_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records when billing suspended the account, or clears it.
+    pub async fn set_suspended_at(
+        &self,
+        account_id: &str,
+        suspended_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE account_subscriptions SET suspended_at = ?, updated_at = CURRENT_TIMESTAMP WHERE account_id = ?",
+            suspended_at,
+            account_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+//! Database repository for rebalancing swaps.
+
+use crate::database::models::{Swap, SwapType};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for Boltz swaps and their progress.
+pub struct SwapRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SwapRepository<'a> {
+    /// Creates a new SwapRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a swap Boltz has accepted.
+    pub async fn create_swap(&self, swap: &Swap) -> Result<Swap> {
+        let created = sqlx::query_as!(
+            Swap,
+            r#"
+            INSERT INTO swaps (
+                id, account_id, node_id, swap_type, boltz_swap_id, status, is_final,
+                amount_sat, onchain_amount_sat, service_fee_sat, miner_fee_sat, invoice,
+                lockup_address, timeout_block_height, channel_id, rescue_data, created_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            swap_type as "swap_type!: SwapType",
+            boltz_swap_id as "boltz_swap_id!",
+            status as "status!",
+            is_final as "is_final!: bool",
+            amount_sat as "amount_sat!",
+            onchain_amount_sat as "onchain_amount_sat!",
+            service_fee_sat as "service_fee_sat!",
+            miner_fee_sat as "miner_fee_sat!",
+            invoice as "invoice!",
+            lockup_address as "lockup_address!",
+            timeout_block_height as "timeout_block_height!",
+            channel_id as "channel_id?",
+            rescue_data as "rescue_data!",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at?: DateTime<Utc>"
+            "#,
+            swap.id,
+            swap.account_id,
+            swap.node_id,
+            swap.swap_type,
+            swap.boltz_swap_id,
+            swap.status,
+            swap.is_final,
+            swap.amount_sat,
+            swap.onchain_amount_sat,
+            swap.service_fee_sat,
+            swap.miner_fee_sat,
+            swap.invoice,
+            swap.lockup_address,
+            swap.timeout_block_height,
+            swap.channel_id,
+            swap.rescue_data,
+            swap.created_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    /// Retrieves one of a node's swaps.
+    pub async fn get_swap(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        id: &str,
+    ) -> Result<Option<Swap>> {
+        let swap = sqlx::query_as!(
+            Swap,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            swap_type as "swap_type!: SwapType",
+            boltz_swap_id as "boltz_swap_id!",
+            status as "status!",
+            is_final as "is_final!: bool",
+            amount_sat as "amount_sat!",
+            onchain_amount_sat as "onchain_amount_sat!",
+            service_fee_sat as "service_fee_sat!",
+            miner_fee_sat as "miner_fee_sat!",
+            invoice as "invoice!",
+            lockup_address as "lockup_address!",
+            timeout_block_height as "timeout_block_height!",
+            channel_id as "channel_id?",
+            rescue_data as "rescue_data!",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM swaps WHERE account_id = ? AND node_id = ? AND id = ?
+            "#,
+            account_id,
+            node_id,
+            id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(swap)
+    }
+
+    /// Lists a node's swaps, newest first.
+    pub async fn get_swaps(&self, account_id: &str, node_id: &str) -> Result<Vec<Swap>> {
+        let swaps = sqlx::query_as!(
+            Swap,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            swap_type as "swap_type!: SwapType",
+            boltz_swap_id as "boltz_swap_id!",
+            status as "status!",
+            is_final as "is_final!: bool",
+            amount_sat as "amount_sat!",
+            onchain_amount_sat as "onchain_amount_sat!",
+            service_fee_sat as "service_fee_sat!",
+            miner_fee_sat as "miner_fee_sat!",
+            invoice as "invoice!",
+            lockup_address as "lockup_address!",
+            timeout_block_height as "timeout_block_height!",
+            channel_id as "channel_id?",
+            rescue_data as "rescue_data!",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM swaps WHERE account_id = ? AND node_id = ?
+            ORDER BY created_at DESC
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(swaps)
+    }
+
+    /// Lists swaps across all accounts that can still change status.
+    pub async fn get_open_swaps(&self) -> Result<Vec<Swap>> {
+        let swaps = sqlx::query_as!(
+            Swap,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            swap_type as "swap_type!: SwapType",
+            boltz_swap_id as "boltz_swap_id!",
+            status as "status!",
+            is_final as "is_final!: bool",
+            amount_sat as "amount_sat!",
+            onchain_amount_sat as "onchain_amount_sat!",
+            service_fee_sat as "service_fee_sat!",
+            miner_fee_sat as "miner_fee_sat!",
+            invoice as "invoice!",
+            lockup_address as "lockup_address!",
+            timeout_block_height as "timeout_block_height!",
+            channel_id as "channel_id?",
+            rescue_data as "rescue_data!",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>",
+            updated_at as "updated_at?: DateTime<Utc>"
+            FROM swaps WHERE is_final = 0
+            "#
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(swaps)
+    }
+
+    /// Records a new status reported by Boltz.
+    pub async fn update_status(&self, id: &str, status: &str, is_final: bool) -> Result<()> {
+        sqlx::query!(
+            "UPDATE swaps SET status = ?, is_final = ? WHERE id = ?",
+            status,
+            is_final,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the rescue data of every swap as stored, as `(id, rescue_data)`.
+    pub async fn get_stored_rescue_data(&self) -> Result<Vec<(String, String)>> {
+        let rows = sqlx::query!(r#"SELECT id as "id!", rescue_data as "rescue_data!" FROM swaps"#)
+            .fetch_all(self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.id, row.rescue_data))
+            .collect())
+    }
+
+    /// Replaces a swap's encrypted rescue data, provided it's still
+    /// `current_rescue_data`.
+    ///
+    /// # Returns
+    /// `true` if the swap was updated
+    pub async fn update_rescue_data(
+        &self,
+        id: &str,
+        current_rescue_data: &str,
+        rescue_data: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE swaps SET rescue_data = ? WHERE id = ? AND rescue_data = ?",
+            rescue_data,
+            id,
+            current_rescue_data
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Sums the fees of completed swaps pinned to a channel.
+    pub async fn get_channel_swap_fees(&self, node_id: &str, channel_id: &str) -> Result<i64> {
+        let fees = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(service_fee_sat + miner_fee_sat), 0) as "fees!: i64"
+            FROM swaps
+            WHERE node_id = ? AND channel_id = ? AND is_final = 1
+            AND status IN ('transaction.claimed', 'invoice.settled')
+            "#,
+            node_id,
+            channel_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(fees)
+    }
+}
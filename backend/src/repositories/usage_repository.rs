@@ -0,0 +1,155 @@
+//! Database repository for per-account daily usage.
+
+use crate::database::models::{AccountUsageDay, JobStatus, JobType};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for account usage rollups and the counts they are built from.
+pub struct UsageRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> UsageRepository<'a> {
+    /// Creates a new UsageRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Retrieves an account's recorded days between `from` and `to`
+    /// inclusive, oldest first. Days with no usage have no row.
+    pub async fn get_usage(
+        &self,
+        account_id: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<AccountUsageDay>> {
+        let usage = sqlx::query_as!(
+            AccountUsageDay,
+            r#"
+            SELECT
+            day as "day!: NaiveDate",
+            api_requests as "api_requests!",
+            events_stored as "events_stored!",
+            notifications_sent as "notifications_sent!",
+            nodes_connected as "nodes_connected!"
+            FROM account_usage
+            WHERE account_id = ? AND day >= ? AND day <= ?
+            ORDER BY day ASC
+            "#,
+            account_id,
+            from,
+            to
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(usage)
+    }
+
+    /// Counts the events an account stored between `from` and `to`.
+    pub async fn count_events_stored(
+        &self,
+        account_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64" FROM events
+            WHERE account_id = ? AND created_at >= ? AND created_at < ?
+            "#,
+            account_id,
+            from,
+            to
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Counts the notifications an account delivered between `from` and `to`.
+    pub async fn count_notifications_sent(
+        &self,
+        account_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64" FROM jobs
+            WHERE account_id = ? AND job_type = ? AND status = ?
+            AND completed_at >= ? AND completed_at < ?
+            "#,
+            account_id,
+            JobType::NotificationDelivery,
+            JobStatus::Completed,
+            from,
+            to
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Records counts taken from the events and jobs tables. A count never
+    /// goes down, so a recount after retention pruned some rows keeps the
+    /// earlier figure.
+    pub async fn record_counts(
+        &self,
+        account_id: &str,
+        day: NaiveDate,
+        events_stored: i64,
+        notifications_sent: i64,
+        nodes_connected: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO account_usage (account_id, day, events_stored, notifications_sent, nodes_connected)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(account_id, day) DO UPDATE SET
+            events_stored = MAX(events_stored, excluded.events_stored),
+            notifications_sent = MAX(notifications_sent, excluded.notifications_sent),
+            nodes_connected = MAX(nodes_connected, excluded.nodes_connected),
+            updated_at = CURRENT_TIMESTAMP
+            "#,
+            account_id,
+            day,
+            events_stored,
+            notifications_sent,
+            nodes_connected
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Adds API requests to a day's count.
+    pub async fn add_api_requests(
+        &self,
+        account_id: &str,
+        day: NaiveDate,
+        requests: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO account_usage (account_id, day, api_requests)
+            VALUES (?, ?, ?)
+            ON CONFLICT(account_id, day) DO UPDATE SET
+            api_requests = api_requests + excluded.api_requests,
+            updated_at = CURRENT_TIMESTAMP
+            "#,
+            account_id,
+            day,
+            requests
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
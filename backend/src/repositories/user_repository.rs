@@ -47,6 +47,7 @@ impl<'a> UserRepository<'a> {
             role_id as "role_id!",
             role_access_level as "role_access_level: RoleAccessLevel",
             username as "username!",
+            display_name as "display_name?",
             password_hash as "password_hash!",
             email as "email!",
             is_active as "is_active!",
@@ -87,6 +88,7 @@ impl<'a> UserRepository<'a> {
             role_id as "role_id!",
             role_access_level as "role_access_level: RoleAccessLevel",
             username as "username!",
+            display_name as "display_name?",
             password_hash as "password_hash!",
             email as "email!",
             is_active as "is_active!",
@@ -121,6 +123,7 @@ impl<'a> UserRepository<'a> {
             role_id as "role_id!",
             role_access_level as "role_access_level: RoleAccessLevel",
             username as "username!",
+            display_name as "display_name?",
             password_hash as "password_hash!",
             email as "email!",
             is_active as "is_active!",
@@ -155,6 +158,7 @@ impl<'a> UserRepository<'a> {
             role_id as "role_id!",
             role_access_level as "role_access_level: RoleAccessLevel",
             username as "username!",
+            display_name as "display_name?",
             password_hash as "password_hash!",
             email as "email!",
             is_active as "is_active!",
@@ -189,6 +193,7 @@ impl<'a> UserRepository<'a> {
             u.role_id as "role_id!",
             u.role_access_level as "role_access_level: RoleAccessLevel",
             u.username as "username!",
+            u.display_name as "display_name?",
             u.password_hash as "password_hash!",
             u.email as "email!",
             u.is_active as "is_active!",
@@ -312,6 +317,7 @@ impl<'a> UserRepository<'a> {
             role_id as "role_id!",
             role_access_level as "role_access_level: RoleAccessLevel",
             username as "username!",
+            display_name as "display_name?",
             password_hash as "password_hash!",
             email as "email!",
             is_active as "is_active!",
@@ -345,4 +351,121 @@ impl<'a> UserRepository<'a> {
 
         Ok(count as u64)
     }
+
+    /// Assigns a different role to a user.
+    ///
+    /// # Returns
+    /// `true` if the user was updated, `false` if no active user matched
+    pub async fn update_user_role(&self, user_id: &str, role_id: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE users SET role_id = ? WHERE id = ? AND is_deleted = 0",
+            role_id,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Sets or clears a user's display name.
+    pub async fn update_display_name(
+        &self,
+        user_id: &str,
+        display_name: Option<&str>,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE users SET display_name = ? WHERE id = ? AND is_deleted = 0",
+            display_name,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Replaces a user's password hash and revokes every token issued before
+    /// `sessions_invalidated_at`.
+    pub async fn update_password(
+        &self,
+        user_id: &str,
+        password_hash: &str,
+        sessions_invalidated_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE users SET password_hash = ?, sessions_invalidated_at = ?
+            WHERE id = ? AND is_deleted = 0
+            "#,
+            password_hash,
+            sessions_invalidated_at,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Replaces a user's password hash without touching their sessions, for
+    /// upgrading the hash of an unchanged password.
+    pub async fn update_password_hash(&self, user_id: &str, password_hash: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE users SET password_hash = ? WHERE id = ? AND is_deleted = 0",
+            password_hash,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns when the user's sessions were last revoked, if ever.
+    pub async fn get_sessions_invalidated_at(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT sessions_invalidated_at as "sessions_invalidated_at?: DateTime<Utc>"
+            FROM users WHERE id = ?
+            "#,
+            user_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(row.and_then(|r| r.sessions_invalidated_at))
+    }
+
+    /// Returns whether the user has confirmed their current email address.
+    pub async fn is_email_verified(&self, user_id: &str) -> Result<bool> {
+        let row = sqlx::query!(
+            r#"
+            SELECT email_verified_at as "email_verified_at?: DateTime<Utc>"
+            FROM users WHERE id = ? AND is_deleted = 0
+            "#,
+            user_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(row.is_some_and(|r| r.email_verified_at.is_some()))
+    }
+
+    /// Marks the user's current email address as verified.
+    pub async fn mark_email_verified(&self, user_id: &str) -> Result<bool> {
+        let now = Utc::now();
+        let result = sqlx::query!(
+            "UPDATE users SET email_verified_at = ? WHERE id = ? AND is_deleted = 0",
+            now,
+            user_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
 }
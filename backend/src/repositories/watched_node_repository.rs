@@ -0,0 +1,191 @@
+//! Database repository for watch-only external nodes.
+
+use crate::database::models::WatchedNode;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Repository for the external nodes an account watches.
+pub struct WatchedNodeRepository<'a> {
+    /// Shared SQLite connection pool
+    pool: &'a SqlitePool,
+}
+
+impl<'a> WatchedNodeRepository<'a> {
+    /// Creates a new WatchedNodeRepository instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a watched node.
+    pub async fn create(&self, watched: &WatchedNode) -> Result<WatchedNode> {
+        let created = sqlx::query_as!(
+            WatchedNode,
+            r#"
+            INSERT INTO watched_nodes (
+                id, account_id, node_id, pubkey, label, snapshot, last_checked_at, created_by
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            pubkey as "pubkey!",
+            label,
+            snapshot,
+            last_checked_at as "last_checked_at?: DateTime<Utc>",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>"
+            "#,
+            watched.id,
+            watched.account_id,
+            watched.node_id,
+            watched.pubkey,
+            watched.label,
+            watched.snapshot,
+            watched.last_checked_at,
+            watched.created_by
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    /// Lists the nodes watched through one of an account's nodes, oldest first.
+    pub async fn get_for_node(&self, account_id: &str, node_id: &str) -> Result<Vec<WatchedNode>> {
+        let watched = sqlx::query_as!(
+            WatchedNode,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            pubkey as "pubkey!",
+            label,
+            snapshot,
+            last_checked_at as "last_checked_at?: DateTime<Utc>",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM watched_nodes
+            WHERE account_id = ? AND node_id = ?
+            ORDER BY created_at ASC
+            "#,
+            account_id,
+            node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(watched)
+    }
+
+    /// Lists the nodes watched through `node_id` by any account.
+    pub async fn get_by_source(&self, node_id: &str) -> Result<Vec<WatchedNode>> {
+        let watched = sqlx::query_as!(
+            WatchedNode,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            pubkey as "pubkey!",
+            label,
+            snapshot,
+            last_checked_at as "last_checked_at?: DateTime<Utc>",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM watched_nodes
+            WHERE node_id = ?
+            "#,
+            node_id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(watched)
+    }
+
+    /// Retrieves one of the nodes watched through one of an account's nodes.
+    pub async fn get(
+        &self,
+        id: &str,
+        account_id: &str,
+        node_id: &str,
+    ) -> Result<Option<WatchedNode>> {
+        let watched = sqlx::query_as!(
+            WatchedNode,
+            r#"
+            SELECT
+            id as "id!",
+            account_id as "account_id!",
+            node_id as "node_id!",
+            pubkey as "pubkey!",
+            label,
+            snapshot,
+            last_checked_at as "last_checked_at?: DateTime<Utc>",
+            created_by as "created_by!",
+            created_at as "created_at!: DateTime<Utc>"
+            FROM watched_nodes
+            WHERE id = ? AND account_id = ? AND node_id = ?
+            "#,
+            id,
+            account_id,
+            node_id
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(watched)
+    }
+
+    /// Checks whether a pubkey is already watched through one of an account's nodes.
+    pub async fn exists(&self, account_id: &str, node_id: &str, pubkey: &str) -> Result<bool> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!: i64" FROM watched_nodes
+            WHERE account_id = ? AND node_id = ? AND pubkey = ?
+            "#,
+            account_id,
+            node_id,
+            pubkey
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(row.count > 0)
+    }
+
+    /// Stores the snapshot taken at a check.
+    pub async fn record_snapshot(
+        &self,
+        id: &str,
+        snapshot: &str,
+        checked_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE watched_nodes SET snapshot = ?, last_checked_at = ? WHERE id = ?",
+            snapshot,
+            checked_at,
+            id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stops watching a node.
+    pub async fn delete(&self, id: &str, account_id: &str, node_id: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM watched_nodes WHERE id = ? AND account_id = ? AND node_id = ?",
+            id,
+            account_id,
+            node_id
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
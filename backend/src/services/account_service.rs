@@ -2,16 +2,32 @@
 //!
 //! Handles all account-related business operations
 
+use crate::config::Config;
 use crate::database::models::{
-    Account, CreateAccount, CreateNewAccount, RoleAccessLevel, UserWithAccount,
+    Account, AccountMembership, AddAccountMemberRequest, CreateAccount, CreateNewAccount,
+    RoleAccessLevel, UserWithAccount,
 };
 use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::account_membership_repository::AccountMembershipRepository;
 use crate::repositories::account_repository::AccountRepository;
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::node_scope_repository::NodeScopeRepository;
 use crate::repositories::role_repository::RoleRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::role_service::RoleService;
+use crate::services::scheduler::SchedulerService;
+use crate::utils::jwt::Claims;
+use crate::utils::password::{hash_password, validate_password_strength};
+use serde_json::json;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 use validator::Validate;
 
+/// IDs the initial migration gives the built-in roles, reused when an empty
+/// database is bootstrapped without them.
+const ADMIN_ROLE_ID: &str = "01932f4e-8b2a-7a3c-9d5e-1f2a3b4c5d6e";
+const MEMBER_ROLE_ID: &str = "01932f4e-8b2b-7a3c-9d5f-2a3b4c5d6e7f";
+
 /// Service layer for account operations.
 pub struct AccountService<'a> {
     /// Shared database connection pool
@@ -27,6 +43,24 @@ impl<'a> AccountService<'a> {
         Self { pool }
     }
 
+    /// Whether admins of the account may run instance-wide operations; see
+    /// [`Config::is_operator_account`].
+    pub async fn is_operator_account(
+        &self,
+        config: &Config,
+        account_id: &str,
+    ) -> ServiceResult<bool> {
+        let first_account_id = match config.operator_account_id {
+            None if !config.registration_enabled => {
+                AccountRepository::new(self.pool)
+                    .get_first_account_id()
+                    .await?
+            }
+            _ => None,
+        };
+        Ok(config.is_operator_account(account_id, first_account_id.as_deref()))
+    }
+
     /// Creates a new account with full validation and setup.
     ///
     /// # Arguments
@@ -44,6 +78,27 @@ impl<'a> AccountService<'a> {
     pub async fn create_account(
         &self,
         create_account: CreateNewAccount,
+    ) -> ServiceResult<UserWithAccount> {
+        self.insert_account(create_account, true).await
+    }
+
+    /// Signs up a new account: always while there is none yet, afterwards
+    /// only if registration is enabled.
+    pub async fn register_account(
+        &self,
+        create_account: CreateNewAccount,
+        registration_enabled: bool,
+    ) -> ServiceResult<UserWithAccount> {
+        self.insert_account(create_account, registration_enabled)
+            .await
+    }
+
+    /// Creates the account and its admin; unless `allow_existing_accounts`,
+    /// only if no other account exists by the time it is inserted.
+    async fn insert_account(
+        &self,
+        create_account: CreateNewAccount,
+        allow_existing_accounts: bool,
     ) -> ServiceResult<UserWithAccount> {
         // Input validation using validator crate
         if let Err(validation_errors) = create_account.validate() {
@@ -63,6 +118,7 @@ impl<'a> AccountService<'a> {
 
             return Err(ServiceError::validation(error_messages.join(", ")));
         }
+        validate_password_strength(&create_account.password, &create_account.username)?;
 
         // Pre-validation checks
         let account_repo = AccountRepository::new(self.pool);
@@ -98,22 +154,35 @@ impl<'a> AccountService<'a> {
         // Business validation
         self.validate_business_rules(&create_account)?;
 
-        // Check if the Admin role exists
-        let role_repo = RoleRepository::new(self.pool);
-        let role = role_repo.get_role_by_name("Admin").await?;
-        if role.is_none() {
-            return Err(ServiceError::not_found("Role", "Admin"));
-        }
-
-        let role = role.unwrap();
-
-        // Start a transaction for atomic account + user creation
+        // Start a transaction for atomic role + account + user creation
         let mut tx = self
             .pool
             .begin()
             .await
             .map_err(|e| ServiceError::Database { source: e.into() })?;
 
+        // Make sure the built-in roles exist, so the first account can be
+        // bootstrapped on a database they were removed from
+        sqlx::query!(
+            "INSERT OR IGNORE INTO roles (id, name) VALUES (?, 'Admin'), (?, 'Member')",
+            ADMIN_ROLE_ID,
+            MEMBER_ROLE_ID
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServiceError::Database { source: e.into() })?;
+
+        let role = sqlx::query!(
+            r#"
+            SELECT id as "id!" FROM roles
+            WHERE account_id IS NULL AND name = 'Admin' AND is_deleted = 0
+            "#
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| ServiceError::Database { source: e.into() })?
+        .ok_or_else(|| ServiceError::not_found("Role", "Admin"))?;
+
         // Create the account
         let new_account = CreateAccount {
             name: create_account.name.clone(),
@@ -122,12 +191,17 @@ impl<'a> AccountService<'a> {
         };
 
         let account_id = Uuid::now_v7().to_string();
-        // Insert the account into the database
+        // Insert the account into the database. The roles insert above
+        // already holds the write lock, so concurrent sign-ups can't both see
+        // no accounts here.
         let account = sqlx::query_as!(
             crate::database::models::Account,
             r#"
             INSERT INTO accounts (id, name, is_active)
-            VALUES (?, ?, ?)
+            SELECT ?, ?, ?
+            WHERE ? OR NOT EXISTS (
+                SELECT 1 FROM accounts WHERE is_deleted = 0 AND is_active = 1
+            )
             RETURNING
             id as "id!",
             name as "name!",
@@ -139,9 +213,10 @@ impl<'a> AccountService<'a> {
             "#,
             account_id,
             new_account.name,
-            true
+            true,
+            allow_existing_accounts
         )
-        .fetch_one(&mut *tx)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(|e| {
             let error_msg = e.to_string();
@@ -150,11 +225,11 @@ impl<'a> AccountService<'a> {
             } else {
                 ServiceError::Database { source: e.into() }
             }
-        })?;
+        })?
+        .ok_or_else(|| ServiceError::permission_denied("Registration is disabled"))?;
 
         // Create the admin user for the account
-        let password_hash = bcrypt::hash(&create_account.password, bcrypt::DEFAULT_COST)
-            .map_err(|e| ServiceError::validation(format!("Password hashing failed: {e}")))?;
+        let password_hash = hash_password(&create_account.password).await?;
 
         let user_id = Uuid::now_v7().to_string();
         // Insert the user into the database
@@ -169,6 +244,7 @@ impl<'a> AccountService<'a> {
             role_id as "role_id!",
             role_access_level as "role_access_level: RoleAccessLevel",
             username as "username!",
+            display_name as "display_name?",
             password_hash as "password_hash!",
             email as "email!",
             is_active as "is_active!",
@@ -204,6 +280,14 @@ impl<'a> AccountService<'a> {
             .await
             .map_err(|e| ServiceError::Database { source: e.into() })?;
 
+        // Seed the account's recurring tasks; the scheduler also backfills these on startup.
+        if let Err(e) = SchedulerService::new(self.pool)
+            .ensure_account_tasks(&account.id)
+            .await
+        {
+            tracing::warn!("Failed to create scheduled tasks for {}: {}", account.id, e);
+        }
+
         // Return the created account and user
         let user_with_account = UserWithAccount { account, user };
 
@@ -229,6 +313,115 @@ impl<'a> AccountService<'a> {
         Ok(account)
     }
 
+    /// Adds an existing user from another account to the caller's account,
+    /// with Member or one of the account's custom roles. The user reaches
+    /// the account by switching to it.
+    pub async fn add_member(
+        &self,
+        claims: &Claims,
+        request: AddAccountMemberRequest,
+    ) -> ServiceResult<AccountMembership> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let user = UserRepository::new(self.pool)
+            .get_user_by_email(request.email.trim())
+            .await?
+            .ok_or_else(|| ServiceError::not_found("User", &request.email))?;
+        if user.account_id == claims.account_id() {
+            return Err(ServiceError::already_exists(
+                "Account member",
+                &request.email,
+            ));
+        }
+
+        let role_service = RoleService::new(self.pool);
+        let role = match request.role_id.as_deref() {
+            Some(role_id) => {
+                role_service
+                    .get_assignable_role(claims.account_id(), role_id)
+                    .await?
+            }
+            None => RoleRepository::new(self.pool)
+                .get_role_by_name("Member")
+                .await?
+                .ok_or_else(|| ServiceError::not_found("Role", "Member"))?,
+        };
+        let node_ids = match request.node_ids {
+            Some(node_ids) => Some(
+                role_service
+                    .validate_node_scope(claims.account_id(), node_ids)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let membership = AccountMembershipRepository::new(self.pool)
+            .create_membership(
+                &Uuid::now_v7().to_string(),
+                &user.id,
+                claims.account_id(),
+                &role.id,
+                claims.user_id(),
+            )
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("UNIQUE constraint failed") {
+                    ServiceError::already_exists("Account member", &request.email)
+                } else {
+                    ServiceError::Database { source: e }
+                }
+            })?;
+
+        if let Some(node_ids) = &node_ids {
+            NodeScopeRepository::new(self.pool)
+                .set_scope(&user.id, claims.account_id(), node_ids)
+                .await?;
+        }
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                claims.account_id(),
+                Some(claims.user_id()),
+                "member_added",
+                "user",
+                Some(&user.id),
+                &json!({ "role_id": role.id, "node_ids": node_ids }),
+            )
+            .await?;
+
+        Ok(membership)
+    }
+
+    /// Removes a user who joined the caller's account from another account.
+    /// Tokens already issued for the account stay valid until they expire;
+    /// refreshing them falls back to the user's home account.
+    pub async fn remove_member(&self, claims: &Claims, user_id: &str) -> ServiceResult<()> {
+        if !AccountMembershipRepository::new(self.pool)
+            .delete_membership(user_id, claims.account_id())
+            .await?
+        {
+            return Err(ServiceError::not_found("Account member", user_id));
+        }
+        NodeScopeRepository::new(self.pool)
+            .delete_scope(user_id, claims.account_id())
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                claims.account_id(),
+                Some(claims.user_id()),
+                "member_removed",
+                "user",
+                Some(user_id),
+                &json!({}),
+            )
+            .await?;
+
+        Ok(())
+    }
+
     /// Business validation rules.
     fn validate_business_rules(&self, create_account: &CreateNewAccount) -> ServiceResult<()> {
         // Validate name doesn't start with numbers or special characters
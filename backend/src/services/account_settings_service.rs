@@ -0,0 +1,97 @@
+//! Account-wide display settings.
+//!
+//! The time zone drives digest timestamps and the daily buckets of the offer,
+//! invoice latency and channel revenue reports; the display currency picks
+//! the fiat values of payment stats.
+
+use crate::database::models::{AccountSettings, UpdateAccountSettingsRequest};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::account_settings_repository::AccountSettingsRepository;
+use sqlx::SqlitePool;
+use validator::Validate;
+
+/// Fiat currencies the price feed quotes.
+pub const SUPPORTED_CURRENCIES: [&str; 7] = ["USD", "EUR", "GBP", "CAD", "CHF", "AUD", "JPY"];
+
+/// Service layer for account settings.
+pub struct AccountSettingsService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> AccountSettingsService<'a> {
+    /// Creates a new AccountSettingsService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns an account's settings, or the defaults if none were saved.
+    pub async fn get_settings(&self, account_id: &str) -> ServiceResult<AccountSettings> {
+        let settings = AccountSettingsRepository::new(self.pool)
+            .get_settings(account_id)
+            .await?;
+
+        Ok(settings.unwrap_or_else(|| AccountSettings::default_for(account_id)))
+    }
+
+    /// Updates the given fields of an account's settings.
+    pub async fn update_settings(
+        &self,
+        account_id: &str,
+        request: UpdateAccountSettingsRequest,
+    ) -> ServiceResult<AccountSettings> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let mut settings = self.get_settings(account_id).await?;
+
+        if let Some(timezone) = request.timezone {
+            validate_timezone(&timezone)?;
+            settings.timezone = timezone;
+        }
+        if let Some(currency) = request.display_currency {
+            settings.display_currency = normalize_currency(&currency)?;
+        }
+
+        Ok(AccountSettingsRepository::new(self.pool)
+            .upsert_settings(&settings)
+            .await?)
+    }
+}
+
+fn validate_timezone(timezone: &str) -> ServiceResult<()> {
+    timezone
+        .parse::<chrono_tz::Tz>()
+        .map(|_| ())
+        .map_err(|_| ServiceError::validation(format!("Unknown timezone '{timezone}'")))
+}
+
+fn normalize_currency(currency: &str) -> ServiceResult<String> {
+    let currency = currency.to_ascii_uppercase();
+    if SUPPORTED_CURRENCIES.contains(&currency.as_str()) {
+        Ok(currency)
+    } else {
+        Err(ServiceError::validation(format!(
+            "Unsupported currency '{currency}'. Supported: {}",
+            SUPPORTED_CURRENCIES.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_iana_timezones_only() {
+        assert!(validate_timezone("Europe/Berlin").is_ok());
+        assert!(validate_timezone("UTC").is_ok());
+        assert!(validate_timezone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn currency_codes_are_case_insensitive() {
+        assert_eq!(normalize_currency("eur").unwrap(), "EUR");
+        assert!(normalize_currency("XYZ").is_err());
+    }
+}
@@ -0,0 +1,569 @@
+//! Plans and subscription billing for hosted deployments.
+//!
+//! With `BILLING_ENABLED` every account is on a plan that caps its nodes,
+//! notification channels and retention windows. Accounts start on Free and
+//! upgrade through Stripe checkout; Stripe's webhook then keeps the plan and
+//! its status current. Operators with a treasury node can also take payment
+//! in sats (see `lightning_billing`). A subscription that is canceled or
+//! never paid falls back to Free. Self-hosted deployments leave billing off
+//! and have no limits.
+
+use crate::config::Config;
+use crate::database::models::{
+    AccountSubscription, BillingOverview, BillingProvider, BillingUsage, CheckoutSession,
+    CreateCheckoutRequest, Plan, PlanDetails, PlanLimits, SubscriptionStatus,
+    UpdateRetentionRequest,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::notification_repository::NotificationRepository;
+use crate::repositories::subscription_repository::SubscriptionRepository;
+use crate::services::stripe::{CheckoutParams, StripeClient, StripeEvent, verify_signature};
+use crate::services::user_service::UserService;
+use crate::utils::jwt::Claims;
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+
+/// Something a plan puts a cap on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanResource {
+    Nodes,
+    NotificationChannels,
+}
+
+/// What each plan allows.
+pub fn plan_limits(plan: Plan) -> PlanLimits {
+    match plan {
+        Plan::Free => PlanLimits {
+            max_nodes: Some(1),
+            max_notification_channels: Some(2),
+            max_retention_days: Some(30),
+        },
+        Plan::Pro => PlanLimits {
+            max_nodes: Some(5),
+            max_notification_channels: Some(20),
+            max_retention_days: Some(365),
+        },
+        Plan::Business => PlanLimits {
+            max_nodes: None,
+            max_notification_channels: None,
+            max_retention_days: None,
+        },
+    }
+}
+
+/// The plan in force for a subscription. A failed renewal keeps the plan
+/// while Stripe retries; anything else short of active is Free.
+pub fn effective_plan(subscription: Option<&AccountSubscription>) -> Plan {
+    match subscription {
+        Some(subscription)
+            if matches!(
+                subscription.status,
+                SubscriptionStatus::Active
+                    | SubscriptionStatus::Trialing
+                    | SubscriptionStatus::PastDue
+            ) =>
+        {
+            subscription.plan
+        }
+        _ => Plan::Free,
+    }
+}
+
+/// Maps a Stripe subscription status onto ours.
+pub fn status_from_stripe(status: &str) -> SubscriptionStatus {
+    match status {
+        "active" => SubscriptionStatus::Active,
+        "trialing" => SubscriptionStatus::Trialing,
+        "past_due" => SubscriptionStatus::PastDue,
+        "incomplete" => SubscriptionStatus::Incomplete,
+        // canceled, unpaid, paused and incomplete_expired
+        _ => SubscriptionStatus::Canceled,
+    }
+}
+
+/// Status a checkout session event leaves the subscription in. A session
+/// paid with a delayed method such as a bank debit completes unpaid, and is
+/// settled by a later `async_payment_succeeded` or `async_payment_failed`.
+pub fn checkout_status(event_type: &str, payment_status: &str) -> SubscriptionStatus {
+    match (event_type, payment_status) {
+        ("checkout.session.async_payment_failed", _) => SubscriptionStatus::Canceled,
+        ("checkout.session.async_payment_succeeded", _) => SubscriptionStatus::Active,
+        // Nothing is due up front for a trial or a full discount
+        (_, "paid" | "no_payment_required") => SubscriptionStatus::Active,
+        _ => SubscriptionStatus::Incomplete,
+    }
+}
+
+fn parse_plan(name: &str) -> Option<Plan> {
+    match name {
+        "Free" => Some(Plan::Free),
+        "Pro" => Some(Plan::Pro),
+        "Business" => Some(Plan::Business),
+        _ => None,
+    }
+}
+
+/// Fails when adding one more of `resource` to the `current` ones would
+/// exceed the plan.
+pub fn check_capacity(plan: Plan, resource: PlanResource, current: i64) -> ServiceResult<()> {
+    let limits = plan_limits(plan);
+    let (limit, noun) = match resource {
+        PlanResource::Nodes => (limits.max_nodes, "node"),
+        PlanResource::NotificationChannels => {
+            (limits.max_notification_channels, "notification channel")
+        }
+    };
+
+    match limit {
+        Some(limit) if current >= limit => Err(ServiceError::upgrade_required(format!(
+            "The {plan} plan allows {limit} {noun}{}. Upgrade your plan to add more.",
+            if limit == 1 { "" } else { "s" }
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Fails when a retention window is longer than the plan allows, or when a
+/// capped plan would keep data indefinitely.
+pub fn check_retention(plan: Plan, request: &UpdateRetentionRequest) -> ServiceResult<()> {
+    let Some(max_days) = plan_limits(plan).max_retention_days else {
+        return Ok(());
+    };
+
+    for (name, days) in [
+        ("events", request.events_days),
+        ("deliveries", request.deliveries_days),
+        ("audit logs", request.audit_logs_days),
+        ("balance history", request.balance_history_days),
+    ] {
+        if days.is_none_or(|days| days > max_days) {
+            return Err(ServiceError::upgrade_required(format!(
+                "The {plan} plan keeps {name} for at most {max_days} days. \
+                 Upgrade your plan to keep them longer."
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Service layer for plans and subscriptions.
+pub struct BillingService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> BillingService<'a> {
+    /// Creates a new BillingService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists every plan and whether it can be bought.
+    pub fn list_plans(&self) -> ServiceResult<Vec<PlanDetails>> {
+        let config = Config::from_env()?;
+
+        Ok([Plan::Free, Plan::Pro, Plan::Business]
+            .into_iter()
+            .map(|plan| PlanDetails {
+                plan,
+                limits: plan_limits(plan),
+                purchasable: config.billing_enabled && price_id(&config, plan).is_some(),
+                price_sat: lightning_price_sat(&config, plan),
+            })
+            .collect())
+    }
+
+    /// Returns the account's plan, its limits and how much of them is used.
+    pub async fn get_overview(&self, account_id: &str) -> ServiceResult<BillingOverview> {
+        let config = Config::from_env()?;
+        let subscription = SubscriptionRepository::new(self.pool)
+            .get_subscription(account_id)
+            .await?;
+        let plan = effective_plan(subscription.as_ref());
+
+        Ok(BillingOverview {
+            billing_enabled: config.billing_enabled,
+            plan,
+            status: subscription.as_ref().map(|s| s.status),
+            provider: subscription.as_ref().map(|s| s.provider),
+            limits: if config.billing_enabled {
+                plan_limits(plan)
+            } else {
+                PlanLimits {
+                    max_nodes: None,
+                    max_notification_channels: None,
+                    max_retention_days: None,
+                }
+            },
+            usage: BillingUsage {
+                nodes: self.count(account_id, PlanResource::Nodes, None).await?,
+                notification_channels: self
+                    .count(account_id, PlanResource::NotificationChannels, None)
+                    .await?,
+            },
+            current_period_end: subscription.and_then(|s| s.current_period_end),
+        })
+    }
+
+    /// Fails with an upgrade-required error when the account may not add
+    /// another `resource`. A user's own node doesn't count against the limit,
+    /// since connecting a node replaces it.
+    pub async fn check_limit(&self, claims: &Claims, resource: PlanResource) -> ServiceResult<()> {
+        if !Config::from_env()?.billing_enabled {
+            return Ok(());
+        }
+
+        let plan = self.plan_for(claims.account_id()).await?;
+        let current = self
+            .count(claims.account_id(), resource, Some(claims.user_id()))
+            .await?;
+        check_capacity(plan, resource, current)
+    }
+
+    /// Fails with an upgrade-required error when the retention windows go
+    /// beyond the account's plan.
+    pub async fn check_retention(
+        &self,
+        account_id: &str,
+        request: &UpdateRetentionRequest,
+    ) -> ServiceResult<()> {
+        if !Config::from_env()?.billing_enabled {
+            return Ok(());
+        }
+
+        check_retention(self.plan_for(account_id).await?, request)
+    }
+
+    /// Starts a Stripe checkout for a paid plan.
+    pub async fn create_checkout(
+        &self,
+        claims: &Claims,
+        request: CreateCheckoutRequest,
+    ) -> ServiceResult<CheckoutSession> {
+        let config = Config::from_env()?;
+        if !config.billing_enabled {
+            return Err(ServiceError::invalid_operation("Billing is not enabled"));
+        }
+        let secret_key = config
+            .stripe_secret_key
+            .as_deref()
+            .ok_or_else(|| ServiceError::internal_error("STRIPE_SECRET_KEY is not set"))?;
+        let price_id = price_id(&config, request.plan).ok_or_else(|| {
+            ServiceError::validation(format!("The {} plan can't be bought", request.plan))
+        })?;
+
+        let subscription = SubscriptionRepository::new(self.pool)
+            .get_subscription(claims.account_id())
+            .await?;
+        let current = effective_plan(subscription.as_ref());
+        if current != Plan::Free {
+            return Err(ServiceError::invalid_operation(format!(
+                "The account is already subscribed to the {current} plan"
+            )));
+        }
+
+        let user = UserService::new(self.pool)
+            .get_user_for_claims(claims)
+            .await?;
+        let plan = request.plan.to_string();
+        let success_url = format!("{}/settings/billing?checkout=success", config.base_url);
+        let cancel_url = format!("{}/settings/billing?checkout=canceled", config.base_url);
+
+        StripeClient::new(&config.stripe_api_url, secret_key)?
+            .create_checkout_session(CheckoutParams {
+                price_id,
+                account_id: claims.account_id(),
+                plan: &plan,
+                customer_id: subscription
+                    .as_ref()
+                    .and_then(|s| s.stripe_customer_id.as_deref()),
+                customer_email: Some(&user.email),
+                success_url: &success_url,
+                cancel_url: &cancel_url,
+            })
+            .await
+    }
+
+    /// Verifies and applies a Stripe webhook delivery. Events that concern
+    /// no known account are acknowledged and ignored.
+    pub async fn handle_webhook(&self, payload: &[u8], signature: &str) -> ServiceResult<()> {
+        let config = Config::from_env()?;
+        let secret = config
+            .stripe_webhook_secret
+            .as_deref()
+            .ok_or_else(|| ServiceError::internal_error("STRIPE_WEBHOOK_SECRET is not set"))?;
+        verify_signature(payload, signature, secret, Utc::now().timestamp())
+            .map_err(|e| ServiceError::permission_denied(format!("Invalid webhook: {e}")))?;
+
+        let event: StripeEvent = serde_json::from_slice(payload)
+            .map_err(|e| ServiceError::validation(format!("Invalid webhook payload: {e}")))?;
+        let object = &event.data.object;
+
+        match event.event_type.as_str() {
+            "checkout.session.completed"
+            | "checkout.session.async_payment_succeeded"
+            | "checkout.session.async_payment_failed" => {
+                let Some(account_id) = object["client_reference_id"]
+                    .as_str()
+                    .or_else(|| object["metadata"]["account_id"].as_str())
+                else {
+                    tracing::warn!("Checkout session in event {} has no account", event.id);
+                    return Ok(());
+                };
+                let Some(plan) = object["metadata"]["plan"].as_str().and_then(parse_plan) else {
+                    tracing::warn!("Checkout session in event {} has no plan", event.id);
+                    return Ok(());
+                };
+                let payment_status = object["payment_status"].as_str().unwrap_or_default();
+                self.apply(
+                    account_id,
+                    plan,
+                    checkout_status(&event.event_type, payment_status),
+                    object["customer"].as_str(),
+                    object["subscription"].as_str(),
+                    None,
+                )
+                .await
+            }
+            "customer.subscription.created"
+            | "customer.subscription.updated"
+            | "customer.subscription.deleted" => {
+                let repo = SubscriptionRepository::new(self.pool);
+                let stripe_subscription_id = object["id"].as_str().unwrap_or_default();
+                let existing = repo
+                    .get_by_stripe_subscription_id(stripe_subscription_id)
+                    .await?;
+                let Some(account_id) = object["metadata"]["account_id"]
+                    .as_str()
+                    .map(str::to_string)
+                    .or_else(|| existing.as_ref().map(|s| s.account_id.clone()))
+                else {
+                    tracing::warn!(
+                        "Stripe subscription {} belongs to no known account",
+                        stripe_subscription_id
+                    );
+                    return Ok(());
+                };
+
+                let price = object["items"]["data"][0]["price"]["id"].as_str();
+                let Some(plan) = price
+                    .and_then(|price| plan_for_price(&config, price))
+                    .or_else(|| object["metadata"]["plan"].as_str().and_then(parse_plan))
+                    .or_else(|| existing.as_ref().map(|s| s.plan))
+                else {
+                    tracing::warn!(
+                        "Stripe subscription {} is for an unknown price",
+                        stripe_subscription_id
+                    );
+                    return Ok(());
+                };
+                let status = if event.event_type == "customer.subscription.deleted" {
+                    SubscriptionStatus::Canceled
+                } else {
+                    status_from_stripe(object["status"].as_str().unwrap_or_default())
+                };
+
+                self.apply(
+                    &account_id,
+                    plan,
+                    status,
+                    object["customer"].as_str(),
+                    Some(stripe_subscription_id),
+                    period_end(object),
+                )
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn apply(
+        &self,
+        account_id: &str,
+        plan: Plan,
+        status: SubscriptionStatus,
+        stripe_customer_id: Option<&str>,
+        stripe_subscription_id: Option<&str>,
+        current_period_end: Option<DateTime<Utc>>,
+    ) -> ServiceResult<()> {
+        let subscription = SubscriptionRepository::new(self.pool)
+            .upsert_subscription(
+                account_id,
+                plan,
+                status,
+                BillingProvider::Stripe,
+                stripe_customer_id,
+                stripe_subscription_id,
+                current_period_end,
+            )
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                None,
+                "subscription_updated",
+                "account",
+                Some(account_id),
+                &json!({ "plan": subscription.plan, "status": subscription.status }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn plan_for(&self, account_id: &str) -> ServiceResult<Plan> {
+        let subscription = SubscriptionRepository::new(self.pool)
+            .get_subscription(account_id)
+            .await?;
+        Ok(effective_plan(subscription.as_ref()))
+    }
+
+    async fn count(
+        &self,
+        account_id: &str,
+        resource: PlanResource,
+        excluding_user_id: Option<&str>,
+    ) -> ServiceResult<i64> {
+        let count = match resource {
+            PlanResource::Nodes => CredentialRepository::new(self.pool)
+                .get_credentials_by_account_id(account_id)
+                .await?
+                .iter()
+                .filter(|credential| Some(credential.user_id.as_str()) != excluding_user_id)
+                .count(),
+            PlanResource::NotificationChannels => NotificationRepository::new(self.pool)
+                .get_notifications_by_account_id(account_id)
+                .await?
+                .len(),
+        };
+        Ok(count as i64)
+    }
+}
+
+fn price_id(config: &Config, plan: Plan) -> Option<&str> {
+    match plan {
+        Plan::Free => None,
+        Plan::Pro => config.stripe_price_pro.as_deref(),
+        Plan::Business => config.stripe_price_business.as_deref(),
+    }
+}
+
+/// Monthly Lightning price of a plan, when Lightning billing is set up.
+pub fn lightning_price_sat(config: &Config, plan: Plan) -> Option<i64> {
+    if !config.lightning_billing_enabled() {
+        return None;
+    }
+    match plan {
+        Plan::Free => None,
+        Plan::Pro => config.lightning_price_pro_sat,
+        Plan::Business => config.lightning_price_business_sat,
+    }
+}
+
+fn plan_for_price(config: &Config, price: &str) -> Option<Plan> {
+    [Plan::Pro, Plan::Business]
+        .into_iter()
+        .find(|plan| price_id(config, *plan) == Some(price))
+}
+
+/// End of the current billing period. Newer Stripe API versions report it
+/// on the subscription item rather than the subscription.
+fn period_end(subscription: &Value) -> Option<DateTime<Utc>> {
+    subscription["current_period_end"]
+        .as_i64()
+        .or_else(|| subscription["items"]["data"][0]["current_period_end"].as_i64())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(plan: Plan, status: SubscriptionStatus) -> AccountSubscription {
+        let now = Utc::now();
+        AccountSubscription {
+            account_id: "account-1".to_string(),
+            plan,
+            status,
+            provider: BillingProvider::Stripe,
+            stripe_customer_id: Some("cus_1".to_string()),
+            stripe_subscription_id: Some("sub_1".to_string()),
+            current_period_end: None,
+            suspended_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_effective_plan() {
+        assert_eq!(effective_plan(None), Plan::Free);
+        assert_eq!(
+            effective_plan(Some(&subscription(Plan::Pro, SubscriptionStatus::PastDue))),
+            Plan::Pro
+        );
+        assert_eq!(
+            effective_plan(Some(&subscription(Plan::Pro, SubscriptionStatus::Canceled))),
+            Plan::Free
+        );
+        assert_eq!(
+            effective_plan(Some(&subscription(
+                Plan::Business,
+                SubscriptionStatus::Incomplete
+            ))),
+            Plan::Free
+        );
+    }
+
+    #[test]
+    fn test_checkout_status() {
+        let completed = "checkout.session.completed";
+        assert_eq!(
+            checkout_status(completed, "paid"),
+            SubscriptionStatus::Active
+        );
+        assert_eq!(
+            checkout_status(completed, "no_payment_required"),
+            SubscriptionStatus::Active
+        );
+        assert_eq!(
+            checkout_status(completed, "unpaid"),
+            SubscriptionStatus::Incomplete
+        );
+        assert_eq!(
+            checkout_status("checkout.session.async_payment_succeeded", "paid"),
+            SubscriptionStatus::Active
+        );
+        assert_eq!(
+            checkout_status("checkout.session.async_payment_failed", "unpaid"),
+            SubscriptionStatus::Canceled
+        );
+    }
+
+    #[test]
+    fn test_check_capacity() {
+        assert!(check_capacity(Plan::Free, PlanResource::Nodes, 0).is_ok());
+        let error = check_capacity(Plan::Free, PlanResource::Nodes, 1).unwrap_err();
+        assert!(matches!(error, ServiceError::UpgradeRequired { .. }));
+        assert!(check_capacity(Plan::Pro, PlanResource::NotificationChannels, 19).is_ok());
+        assert!(check_capacity(Plan::Pro, PlanResource::NotificationChannels, 20).is_err());
+        assert!(check_capacity(Plan::Business, PlanResource::Nodes, 1000).is_ok());
+    }
+
+    #[test]
+    fn test_check_retention() {
+        let request = |days: Option<i64>| UpdateRetentionRequest {
+            events_days: days,
+            deliveries_days: Some(7),
+            audit_logs_days: Some(7),
+            balance_history_days: Some(7),
+        };
+
+        assert!(check_retention(Plan::Free, &request(Some(30))).is_ok());
+        assert!(check_retention(Plan::Free, &request(Some(31))).is_err());
+        assert!(check_retention(Plan::Free, &request(None)).is_err());
+        assert!(check_retention(Plan::Business, &request(None)).is_ok());
+    }
+}
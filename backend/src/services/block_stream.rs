@@ -0,0 +1,275 @@
+//! New blocks from mempool.space.
+//!
+//! The block stream follows mempool.space's websocket and, for every block it
+//! announces, records a `BlockConnected` event for each stored node on the
+//! same chain. The event carries the node's own height so operators can see
+//! when a node falls behind the chain tip. Each block also advances the
+//! confirmation tracking of the nodes' channel transactions.
+
+use crate::config::Config;
+use crate::database::models::{CreateEvent, EventSeverity, EventType};
+use crate::errors::LightningError;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::services::channel_confirmations::track_node;
+use crate::services::event_service::EventService;
+use crate::services::mempool::MempoolClient;
+use crate::utils::handlers_common::{NodeClientHandle, create_node_client};
+use crate::utils::jwt::NodeCredentials;
+use bitcoin::Network;
+use bitcoin::secp256k1::PublicKey;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use uuid::Uuid;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// mempool.space drops websocket clients that stay silent for too long
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A node this many blocks behind the tip gets a warning instead of info
+const SYNC_LAG_WARNING_BLOCKS: u32 = 3;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Block {
+    id: String,
+    height: u32,
+}
+
+/// The messages of interest: the recent blocks sent on subscribing, and each
+/// new block after that.
+#[derive(Deserialize)]
+struct MempoolMessage {
+    block: Option<Block>,
+    blocks: Option<Vec<Block>>,
+}
+
+/// Starts following mempool.space for new blocks, unless `MEMPOOL_WS_URL`
+/// is empty.
+pub async fn start_block_stream(pool: SqlitePool) {
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("Block stream not started: {}", e);
+            return;
+        }
+    };
+    let Some(url) = config.mempool_ws_url else {
+        tracing::info!("MEMPOOL_WS_URL is empty; block stream disabled");
+        return;
+    };
+    let network = config.mempool_network;
+
+    tokio::spawn(async move {
+        let mut tip: Option<u32> = None;
+        loop {
+            if let Err(e) = follow_blocks(&pool, &url, network, &mut tip).await {
+                tracing::warn!("Block stream from {} interrupted: {}", url, e);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+
+    tracing::info!("Started block stream");
+}
+
+/// Reads blocks until the websocket closes. `tip` survives reconnects so a
+/// block seen before the interruption isn't reported twice.
+async fn follow_blocks(
+    pool: &SqlitePool,
+    url: &str,
+    network: Network,
+    tip: &mut Option<u32>,
+) -> Result<(), String> {
+    let (socket, _) = connect_async(url).await.map_err(|e| e.to_string())?;
+    let (mut sink, mut stream) = socket.split();
+    sink.send(Message::Text(
+        json!({ "action": "want", "data": ["blocks"] }).to_string(),
+    ))
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = ping.tick() => {
+                sink.send(Message::Text(json!({ "action": "ping" }).to_string()))
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e.to_string()),
+                };
+                let Ok(message) = serde_json::from_str::<MempoolMessage>(&text) else {
+                    continue;
+                };
+                if let Some(block) = newest_block(message) {
+                    // The first tip only sets the baseline; blocks mined while
+                    // disconnected are reported as one, the newest.
+                    let is_new = tip.is_some_and(|tip| block.height > tip);
+                    if tip.is_none_or(|tip| block.height > tip) {
+                        *tip = Some(block.height);
+                    }
+                    if is_new {
+                        on_block(pool, network, &block).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn newest_block(message: MempoolMessage) -> Option<Block> {
+    message
+        .block
+        .into_iter()
+        .chain(message.blocks.unwrap_or_default())
+        .max_by_key(|block| block.height)
+}
+
+/// Records `BlockConnected` for every stored node on the stream's chain.
+async fn on_block(pool: &SqlitePool, network: Network, block: &Block) {
+    let credentials = match CredentialRepository::new(pool).get_all_credentials().await {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            tracing::error!("Failed to load nodes for block {}: {}", block.height, e);
+            return;
+        }
+    };
+
+    let mempool = match MempoolClient::new() {
+        Ok(client) => Some(client),
+        Err(e) => {
+            tracing::error!("Confirmation tracking unavailable: {}", e);
+            None
+        }
+    };
+
+    for credential in credentials {
+        let node_credentials = NodeCredentials::from(credential.clone());
+        let (client, node_height) = match node_on_chain(&node_credentials, network).await {
+            Ok(Some(node)) => node,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::debug!(
+                    "Skipping block event for {}: {}",
+                    node_credentials.node_id,
+                    e
+                );
+                continue;
+            }
+        };
+
+        let sync_lag = block.height.saturating_sub(node_height);
+        let (severity, description) = if sync_lag >= SYNC_LAG_WARNING_BLOCKS {
+            (
+                EventSeverity::Warning,
+                format!(
+                    "Block {} connected; node is {sync_lag} blocks behind at {node_height}",
+                    block.height
+                ),
+            )
+        } else {
+            (
+                EventSeverity::Info,
+                format!("Block {} connected; node is in sync", block.height),
+            )
+        };
+        let event = CreateEvent {
+            id: Uuid::now_v7().to_string(),
+            account_id: credential.account_id.clone(),
+            user_id: credential.user_id.clone(),
+            node_id: credential.node_id.clone(),
+            node_alias: credential.node_alias.clone(),
+            event_type: EventType::BlockConnected,
+            severity,
+            title: "Block Connected".to_string(),
+            description,
+            data: json!({
+                "height": block.height,
+                "block_hash": block.id,
+                "node_height": node_height,
+                "sync_lag": sync_lag,
+            })
+            .to_string(),
+            notifications_id: None,
+            timestamp: chrono::Utc::now(),
+        };
+
+        if let Err(e) = EventService::new(pool)
+            .create_and_dispatch_event(event)
+            .await
+        {
+            tracing::error!(
+                "Failed to record block {} for node {}: {}",
+                block.height,
+                credential.node_id,
+                e
+            );
+        }
+
+        if let Some(mempool) = &mempool {
+            if let Err(e) = track_node(pool, mempool, &*client, &credential, block.height).await {
+                tracing::warn!(
+                    "Failed to track channel confirmations for {}: {}",
+                    credential.node_id,
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// A client for the node and its block height, or None if it follows
+/// another chain or can't report one.
+async fn node_on_chain(
+    node_credentials: &NodeCredentials,
+    network: Network,
+) -> Result<Option<(NodeClientHandle, u32)>, String> {
+    let public_key = PublicKey::from_str(&node_credentials.node_id).map_err(|e| e.to_string())?;
+    let client = create_node_client(node_credentials, public_key)
+        .await
+        .map_err(|(_, body)| body)?;
+    if client.get_network().await.map_err(|e| e.to_string())? != network {
+        return Ok(None);
+    }
+
+    match client.get_block_height().await {
+        Ok(height) => Ok(Some((client, height))),
+        Err(LightningError::Unsupported(_)) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newest_block() {
+        let initial: MempoolMessage = serde_json::from_str(
+            r#"{ "blocks": [{ "id": "aa", "height": 100 }, { "id": "bb", "height": 101 }], "mempoolInfo": {} }"#,
+        )
+        .unwrap();
+        assert_eq!(newest_block(initial).map(|b| b.height), Some(101));
+
+        let new_block: MempoolMessage =
+            serde_json::from_str(r#"{ "block": { "id": "cc", "height": 102, "tx_count": 1 } }"#)
+                .unwrap();
+        assert_eq!(
+            newest_block(new_block).map(|b| b.id),
+            Some("cc".to_string())
+        );
+
+        let other: MempoolMessage = serde_json::from_str(r#"{ "pong": true }"#).unwrap();
+        assert!(newest_block(other).is_none());
+    }
+}
@@ -0,0 +1,310 @@
+//! Client for the Boltz v2 swap API.
+//!
+//! Only the BTC/BTC pair is used. Boltz quotes fees as a percentage of the
+//! swap plus fixed miner fees, and reports progress as status strings such
+//! as `transaction.mempool` or `invoice.settled`.
+
+use crate::database::models::{SwapQuote, SwapType};
+use crate::errors::{ServiceError, ServiceResult};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const PAIR: &str = "BTC";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PairLimits {
+    pub minimal: u64,
+    pub maximal: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReverseMinerFees {
+    pub claim: u64,
+    pub lockup: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MinerFees {
+    Submarine(u64),
+    Reverse(ReverseMinerFees),
+}
+
+impl MinerFees {
+    fn total(&self) -> u64 {
+        match self {
+            MinerFees::Submarine(fee) => *fee,
+            MinerFees::Reverse(fees) => fees.claim + fees.lockup,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PairFees {
+    pub percentage: f64,
+    #[serde(rename = "minerFees")]
+    pub miner_fees: MinerFees,
+}
+
+/// Boltz's current terms for one swap direction.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pair {
+    pub hash: String,
+    pub limits: PairLimits,
+    pub fees: PairFees,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmarineSwapResponse {
+    pub id: String,
+    pub address: String,
+    pub expected_amount: u64,
+    pub timeout_block_height: u64,
+    pub claim_public_key: String,
+    pub swap_tree: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReverseSwapResponse {
+    pub id: String,
+    pub invoice: String,
+    pub lockup_address: String,
+    pub onchain_amount: u64,
+    pub timeout_block_height: u64,
+    pub refund_public_key: String,
+    pub swap_tree: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwapStatusResponse {
+    status: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateSubmarineSwap<'a> {
+    from: &'a str,
+    to: &'a str,
+    invoice: &'a str,
+    refund_public_key: &'a str,
+    pair_hash: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateReverseSwap<'a> {
+    from: &'a str,
+    to: &'a str,
+    preimage_hash: &'a str,
+    claim_public_key: &'a str,
+    invoice_amount: u64,
+    pair_hash: &'a str,
+}
+
+/// Whether Boltz will report anything further for a swap in this status.
+pub fn is_final(swap_type: SwapType, status: &str) -> bool {
+    is_successful(swap_type, status)
+        || matches!(
+            status,
+            "swap.expired"
+                | "invoice.expired"
+                | "invoice.failedToPay"
+                | "transaction.failed"
+                | "transaction.lockupFailed"
+                | "transaction.refunded"
+        )
+}
+
+/// Whether a swap in this status moved the funds as intended.
+pub fn is_successful(swap_type: SwapType, status: &str) -> bool {
+    match swap_type {
+        SwapType::Submarine => status == "transaction.claimed",
+        SwapType::Reverse => status == "invoice.settled",
+    }
+}
+
+/// Prices a swap of `amount_sat` (the lightning side) under a pair's terms.
+pub fn quote(swap_type: SwapType, amount_sat: u64, pair: &Pair) -> SwapQuote {
+    let service_fee_sat = (amount_sat as f64 * pair.fees.percentage / 100.0).ceil() as u64;
+    let miner_fee_sat = pair.fees.miner_fees.total();
+    let total_fee_sat = service_fee_sat + miner_fee_sat;
+    let onchain_amount_sat = match swap_type {
+        SwapType::Submarine => amount_sat + total_fee_sat,
+        SwapType::Reverse => amount_sat.saturating_sub(total_fee_sat),
+    };
+
+    SwapQuote {
+        swap_type,
+        amount_sat,
+        onchain_amount_sat,
+        service_fee_sat,
+        miner_fee_sat,
+        total_fee_sat,
+        min_amount_sat: pair.limits.minimal,
+        max_amount_sat: pair.limits.maximal,
+    }
+}
+
+/// Talks to the Boltz API.
+pub struct BoltzClient {
+    http_client: Client,
+    base_url: String,
+}
+
+impl BoltzClient {
+    /// Creates a client for the Boltz API at `base_url`.
+    pub fn new(base_url: &str) -> ServiceResult<Self> {
+        let http_client = Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Fetches the current BTC/BTC terms for a swap direction.
+    pub async fn get_pair(&self, swap_type: SwapType) -> ServiceResult<Pair> {
+        let request =
+            self.http_client
+                .get(format!("{}/v2/swap/{}", self.base_url, path(swap_type)));
+        let mut pairs: HashMap<String, HashMap<String, Pair>> = self.send(request).await?;
+
+        pairs
+            .remove(PAIR)
+            .and_then(|mut to| to.remove(PAIR))
+            .ok_or_else(|| ServiceError::external_service("Boltz does not offer BTC/BTC swaps"))
+    }
+
+    /// Asks Boltz to pay `invoice` once funds are locked at the returned address.
+    pub async fn create_submarine_swap(
+        &self,
+        invoice: &str,
+        refund_public_key: &str,
+        pair_hash: &str,
+    ) -> ServiceResult<SubmarineSwapResponse> {
+        let request = self
+            .http_client
+            .post(format!("{}/v2/swap/submarine", self.base_url))
+            .json(&CreateSubmarineSwap {
+                from: PAIR,
+                to: PAIR,
+                invoice,
+                refund_public_key,
+                pair_hash,
+            });
+        self.send(request).await
+    }
+
+    /// Asks Boltz for a hold invoice it settles once we claim the on-chain lockup.
+    pub async fn create_reverse_swap(
+        &self,
+        preimage_hash: &str,
+        claim_public_key: &str,
+        invoice_amount: u64,
+        pair_hash: &str,
+    ) -> ServiceResult<ReverseSwapResponse> {
+        let request = self
+            .http_client
+            .post(format!("{}/v2/swap/reverse", self.base_url))
+            .json(&CreateReverseSwap {
+                from: PAIR,
+                to: PAIR,
+                preimage_hash,
+                claim_public_key,
+                invoice_amount,
+                pair_hash,
+            });
+        self.send(request).await
+    }
+
+    /// Fetches the current status of a swap.
+    pub async fn get_status(&self, boltz_swap_id: &str) -> ServiceResult<String> {
+        let request = self
+            .http_client
+            .get(format!("{}/v2/swap/{}", self.base_url, boltz_swap_id));
+        let response: SwapStatusResponse = self.send(request).await?;
+        Ok(response.status)
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> ServiceResult<T> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ServiceError::external_service(format!("Boltz unreachable: {e}")))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ServiceError::external_service(format!(
+                "Boltz rejected the request ({status}): {body}"
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ServiceError::external_service(format!("Invalid Boltz response: {e}")))
+    }
+}
+
+fn path(swap_type: SwapType) -> &'static str {
+    match swap_type {
+        SwapType::Submarine => "submarine",
+        SwapType::Reverse => "reverse",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        let reverse: Pair = serde_json::from_str(
+            r#"{
+                "hash": "abc",
+                "rate": 1,
+                "limits": { "minimal": 25000, "maximal": 25000000 },
+                "fees": { "percentage": 0.5, "minerFees": { "claim": 333, "lockup": 462 } }
+            }"#,
+        )
+        .unwrap();
+        let q = quote(SwapType::Reverse, 1_000_000, &reverse);
+        assert_eq!(q.service_fee_sat, 5_000);
+        assert_eq!(q.miner_fee_sat, 795);
+        assert_eq!(q.onchain_amount_sat, 1_000_000 - 5_795);
+
+        let submarine: Pair = serde_json::from_str(
+            r#"{
+                "hash": "def",
+                "rate": 1,
+                "limits": { "minimal": 50000, "maximal": 25000000, "maximalZeroConf": 0 },
+                "fees": { "percentage": 0.1, "minerFees": 255 }
+            }"#,
+        )
+        .unwrap();
+        let q = quote(SwapType::Submarine, 1_000_001, &submarine);
+        assert_eq!(q.service_fee_sat, 1_001);
+        assert_eq!(q.onchain_amount_sat, 1_000_001 + 1_001 + 255);
+    }
+
+    #[test]
+    fn test_final_statuses() {
+        assert!(is_final(SwapType::Reverse, "invoice.settled"));
+        assert!(!is_final(SwapType::Reverse, "transaction.claimed"));
+        assert!(is_final(SwapType::Submarine, "transaction.claimed"));
+        assert!(is_final(SwapType::Submarine, "swap.expired"));
+        assert!(!is_final(SwapType::Submarine, "transaction.mempool"));
+    }
+}
@@ -0,0 +1,413 @@
+//! Channel acceptor for LND nodes.
+//!
+//! An account can set rules for inbound channel opens on each of its LND
+//! nodes: a minimum size, allowed and blocked peers, and whether only private
+//! channels are accepted. While a policy is enabled, a background task holds
+//! LND's ChannelAcceptor stream open, answers every open request against the
+//! rules and records each decision as a `ChannelAcceptorDecision` event.
+
+use crate::database::models::{
+    ChannelAcceptorPolicy, CreateEvent, EventSeverity, EventType, UpdateChannelAcceptorRequest,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::channel_acceptor_repository::ChannelAcceptorRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::services::event_service::EventService;
+use crate::services::node_manager::{ChannelOpenRequest, LndConnection, LndNode};
+use crate::services::subscription_health::{self, SubscriptionKind};
+use crate::utils::NodeId;
+use bitcoin::secp256k1::PublicKey;
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+use validator::Validate;
+
+/// How long to wait before re-registering after the stream drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Running acceptor tasks, keyed by (account_id, node_id)
+static ACCEPTORS: OnceLock<Mutex<HashMap<(String, String), JoinHandle<()>>>> = OnceLock::new();
+
+fn acceptors() -> &'static Mutex<HashMap<(String, String), JoinHandle<()>>> {
+    ACCEPTORS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Checks an inbound open against a policy, returning the rejection reason if it fails.
+pub fn evaluate(
+    policy: &ChannelAcceptorPolicy,
+    request: &ChannelOpenRequest,
+) -> Result<(), String> {
+    if policy.blocked_peers().contains(&request.peer_pubkey) {
+        return Err("peer is blocked".to_string());
+    }
+
+    let allowed_peers = policy.allowed_peers();
+    if !allowed_peers.is_empty() && !allowed_peers.contains(&request.peer_pubkey) {
+        return Err("peer is not on the allow list".to_string());
+    }
+
+    if policy.private_only && !request.private {
+        return Err("only private channels are accepted".to_string());
+    }
+
+    if let Some(min_size) = policy
+        .min_channel_size_sat
+        .filter(|min_size| (request.funding_sat as i64) < *min_size)
+    {
+        return Err(format!(
+            "channel size {} sat is below the minimum of {min_size} sat",
+            request.funding_sat
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses and normalizes a list of peer public keys.
+fn normalize_peers(peers: &[String]) -> ServiceResult<String> {
+    let mut normalized = Vec::with_capacity(peers.len());
+    for peer in peers {
+        let pubkey = PublicKey::from_str(peer.trim())
+            .map_err(|_| ServiceError::validation(format!("Invalid peer public key: {peer}")))?;
+        normalized.push(pubkey.to_string());
+    }
+    normalized.sort();
+    normalized.dedup();
+    Ok(json!(normalized).to_string())
+}
+
+/// Service layer for channel acceptor policies.
+pub struct ChannelAcceptorService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ChannelAcceptorService<'a> {
+    /// Creates a new ChannelAcceptorService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns a node's policy. Nodes without one accept every channel.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<ChannelAcceptorPolicy> {
+        let policy = ChannelAcceptorRepository::new(self.pool)
+            .get_policy(account_id, node_id)
+            .await?;
+
+        Ok(policy.unwrap_or_else(|| ChannelAcceptorPolicy {
+            account_id: account_id.to_string(),
+            node_id: node_id.to_string(),
+            is_enabled: false,
+            min_channel_size_sat: None,
+            allowed_peers: "[]".to_string(),
+            blocked_peers: "[]".to_string(),
+            private_only: false,
+            updated_by: user_id.to_string(),
+            updated_at: None,
+        }))
+    }
+
+    /// Replaces a node's policy and starts or stops its acceptor to match.
+    pub async fn update_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: UpdateChannelAcceptorRequest,
+    ) -> ServiceResult<ChannelAcceptorPolicy> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let credential = CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+        if credential.node_type.as_deref().unwrap_or("lnd") != "lnd" {
+            return Err(ServiceError::invalid_operation(
+                "The channel acceptor is only available for LND nodes",
+            ));
+        }
+
+        let policy = ChannelAcceptorRepository::new(self.pool)
+            .upsert_policy(&ChannelAcceptorPolicy {
+                account_id: account_id.to_string(),
+                node_id: node_id.to_string(),
+                is_enabled: request.is_enabled,
+                min_channel_size_sat: request.min_channel_size_sat,
+                allowed_peers: normalize_peers(&request.allowed_peers)?,
+                blocked_peers: normalize_peers(&request.blocked_peers)?,
+                private_only: request.private_only,
+                updated_by: user_id.to_string(),
+                updated_at: None,
+            })
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "channel_acceptor_updated",
+                "node",
+                Some(node_id),
+                &json!({
+                    "is_enabled": policy.is_enabled,
+                    "min_channel_size_sat": policy.min_channel_size_sat,
+                    "allowed_peers": policy.allowed_peers(),
+                    "blocked_peers": policy.blocked_peers(),
+                    "private_only": policy.private_only,
+                }),
+            )
+            .await?;
+
+        if policy.is_enabled {
+            start_acceptor(self.pool.clone(), account_id, node_id);
+        } else {
+            stop_acceptor(account_id, node_id);
+        }
+
+        Ok(policy)
+    }
+}
+
+/// Starts acceptors for every enabled policy. Called once at startup.
+pub async fn start_channel_acceptors(pool: SqlitePool) {
+    let policies = match ChannelAcceptorRepository::new(&pool)
+        .get_enabled_policies()
+        .await
+    {
+        Ok(policies) => policies,
+        Err(e) => {
+            tracing::error!("Failed to load channel acceptor policies: {}", e);
+            return;
+        }
+    };
+
+    for policy in &policies {
+        start_acceptor(pool.clone(), &policy.account_id, &policy.node_id);
+    }
+
+    tracing::info!("Started {} channel acceptor(s)", policies.len());
+}
+
+/// Starts (or restarts) the acceptor task of a node.
+pub fn start_acceptor(pool: SqlitePool, account_id: &str, node_id: &str) {
+    let key = (account_id.to_string(), node_id.to_string());
+    let task = tokio::spawn(run_acceptor(pool, key.0.clone(), key.1.clone()));
+    if let Some(previous) = acceptors().lock().unwrap().insert(key, task) {
+        previous.abort();
+    }
+}
+
+/// Stops a node's acceptor task. Dropping the stream hands decisions back to LND.
+fn stop_acceptor(account_id: &str, node_id: &str) {
+    let key = (account_id.to_string(), node_id.to_string());
+    if let Some(task) = acceptors().lock().unwrap().remove(&key) {
+        task.abort();
+        tracing::info!("Stopped channel acceptor for node {}", node_id);
+    }
+}
+
+/// Keeps the acceptor registered, reconnecting whenever the stream drops.
+async fn run_acceptor(pool: SqlitePool, account_id: String, node_id: String) {
+    loop {
+        let error = match serve_acceptor(&pool, &account_id, &node_id).await {
+            Ok(()) => {
+                tracing::warn!("LND closed the channel acceptor stream for {}", node_id);
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Channel acceptor for node {} failed: {}", node_id, e);
+                Some(e)
+            }
+        };
+        subscription_health::record_disconnected(
+            &node_id,
+            SubscriptionKind::ChannelAcceptor,
+            error,
+        );
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn serve_acceptor(pool: &SqlitePool, account_id: &str, node_id: &str) -> Result<(), String> {
+    let credential = CredentialRepository::new(pool)
+        .get_credential_by_account_and_node_id(account_id, node_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Node {node_id} no longer has credentials"))?;
+
+    let node = LndNode::new(LndConnection {
+        id: NodeId::PublicKey(PublicKey::from_str(node_id).map_err(|e| e.to_string())?),
+        address: credential.address,
+        macaroon: credential.macaroon,
+        cert: credential.tls_cert,
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut acceptor = node.channel_acceptor().await.map_err(|e| e.to_string())?;
+    subscription_health::record_connected(node_id, SubscriptionKind::ChannelAcceptor);
+    tracing::info!("Channel acceptor registered on node {}", node_id);
+
+    let repo = ChannelAcceptorRepository::new(pool);
+    while let Some(request) = acceptor.next_request().await.map_err(|e| e.to_string())? {
+        let received_at = Instant::now();
+        subscription_health::record_event(node_id, SubscriptionKind::ChannelAcceptor);
+        // Read on every request so rule changes apply without re-registering.
+        // If the policy can't be read the open is accepted, as LND would
+        // without an acceptor.
+        let policy = match repo.get_policy(account_id, node_id).await {
+            Ok(policy) => policy.filter(|p| p.is_enabled),
+            Err(e) => {
+                tracing::warn!("Failed to load channel acceptor policy: {}", e);
+                None
+            }
+        };
+        let verdict = policy
+            .as_ref()
+            .map_or(Ok(()), |policy| evaluate(policy, &request));
+
+        acceptor
+            .respond(
+                request.pending_chan_id.clone(),
+                verdict.is_ok(),
+                verdict.clone().err(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        subscription_health::record_lag(
+            node_id,
+            SubscriptionKind::ChannelAcceptor,
+            received_at.elapsed(),
+        );
+
+        if let Some(policy) = policy {
+            record_decision(pool, &policy, &credential.node_alias, &request, &verdict).await;
+        }
+    }
+
+    Ok(())
+}
+
+async fn record_decision(
+    pool: &SqlitePool,
+    policy: &ChannelAcceptorPolicy,
+    node_alias: &str,
+    request: &ChannelOpenRequest,
+    verdict: &Result<(), String>,
+) {
+    let (severity, title, description) = match verdict {
+        Ok(()) => (
+            EventSeverity::Info,
+            "Channel Open Accepted",
+            format!(
+                "Accepted {} sat channel from {}",
+                request.funding_sat, request.peer_pubkey
+            ),
+        ),
+        Err(reason) => (
+            EventSeverity::Warning,
+            "Channel Open Rejected",
+            format!(
+                "Rejected {} sat channel from {}: {reason}",
+                request.funding_sat, request.peer_pubkey
+            ),
+        ),
+    };
+
+    let event = CreateEvent {
+        id: Uuid::now_v7().to_string(),
+        account_id: policy.account_id.clone(),
+        user_id: policy.updated_by.clone(),
+        node_id: policy.node_id.clone(),
+        node_alias: node_alias.to_string(),
+        event_type: EventType::ChannelAcceptorDecision,
+        severity,
+        title: title.to_string(),
+        description,
+        data: json!({
+            "accepted": verdict.is_ok(),
+            "peer_pubkey": request.peer_pubkey,
+            "funding_sat": request.funding_sat,
+            "push_msat": request.push_msat,
+            "private": request.private,
+            "reason": verdict.as_ref().err(),
+        })
+        .to_string(),
+        notifications_id: None,
+        timestamp: Utc::now(),
+    };
+
+    if let Err(e) = EventService::new(pool)
+        .create_and_dispatch_event(event)
+        .await
+    {
+        tracing::warn!("Failed to record channel acceptor decision: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEER: &str = "02abababababababababababababababababababababababababababababababab";
+
+    fn policy() -> ChannelAcceptorPolicy {
+        ChannelAcceptorPolicy {
+            account_id: "account".to_string(),
+            node_id: "node".to_string(),
+            is_enabled: true,
+            min_channel_size_sat: Some(100_000),
+            allowed_peers: "[]".to_string(),
+            blocked_peers: "[]".to_string(),
+            private_only: false,
+            updated_by: "user".to_string(),
+            updated_at: None,
+        }
+    }
+
+    fn request(funding_sat: u64, private: bool) -> ChannelOpenRequest {
+        ChannelOpenRequest {
+            pending_chan_id: vec![0; 32],
+            peer_pubkey: PEER.to_string(),
+            funding_sat,
+            push_msat: 0,
+            private,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_applies_each_rule() {
+        let mut policy = policy();
+        assert!(evaluate(&policy, &request(100_000, false)).is_ok());
+        assert!(evaluate(&policy, &request(99_999, false)).is_err());
+
+        policy.private_only = true;
+        assert!(evaluate(&policy, &request(200_000, false)).is_err());
+        assert!(evaluate(&policy, &request(200_000, true)).is_ok());
+
+        policy.allowed_peers =
+            json!(["03cdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd"])
+                .to_string();
+        assert!(evaluate(&policy, &request(200_000, true)).is_err());
+
+        policy.allowed_peers = json!([PEER]).to_string();
+        policy.blocked_peers = json!([PEER]).to_string();
+        assert_eq!(
+            evaluate(&policy, &request(200_000, true)),
+            Err("peer is blocked".to_string())
+        );
+    }
+}
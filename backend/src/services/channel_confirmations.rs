@@ -0,0 +1,211 @@
+//! Confirmation tracking for channel funding and closing transactions.
+//!
+//! On every block from the block stream, each node's pending channels are
+//! registered by txid and every unfinished transaction is checked against
+//! mempool.space. A `ChannelConfirmation` event is recorded when one reaches
+//! 1, 3 and 6 confirmations: seen on chain, usable (the usual minimum depth)
+//! and final.
+
+use crate::database::models::{
+    ChannelConfirmation, CreateEvent, Credential, EventSeverity, EventType,
+};
+use crate::errors::ServiceResult;
+use crate::repositories::channel_confirmation_repository::ChannelConfirmationRepository;
+use crate::services::event_service::EventService;
+use crate::services::mempool::MempoolClient;
+use crate::services::node_manager::LightningClient;
+use crate::utils::{PendingChannel, PendingChannelKind};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Confirmations after which a transaction is considered final
+const FINAL_CONFIRMATIONS: u32 = 6;
+
+/// Depths reported as events
+const MILESTONES: [u32; 3] = [1, 3, FINAL_CONFIRMATIONS];
+
+/// Confirmations of a transaction mined at `confirmation_height` with the
+/// chain at `tip`.
+fn confirmations(tip: u32, confirmation_height: u32) -> u32 {
+    if confirmation_height > tip {
+        0
+    } else {
+        tip - confirmation_height + 1
+    }
+}
+
+/// The highest milestone passed at `confirmations` that is above `notified`.
+fn next_milestone(notified: u32, confirmations: u32) -> Option<u32> {
+    MILESTONES
+        .into_iter()
+        .filter(|milestone| *milestone > notified && *milestone <= confirmations)
+        .max()
+}
+
+fn kind_name(kind: PendingChannelKind) -> &'static str {
+    match kind {
+        PendingChannelKind::Opening => "opening",
+        PendingChannelKind::Closing => "closing",
+    }
+}
+
+/// Registers a node's pending channels and reports the milestones its
+/// channel transactions passed at `tip`.
+pub async fn track_node(
+    pool: &SqlitePool,
+    mempool: &MempoolClient,
+    node: &dyn LightningClient,
+    credential: &Credential,
+    tip: u32,
+) -> ServiceResult<()> {
+    let repo = ChannelConfirmationRepository::new(pool);
+
+    // Nodes that can't list pending channels still finish what was tracked.
+    if let Ok(pending) = node.list_pending_channels().await {
+        for channel in pending {
+            let Some(txid) = channel.txid else {
+                continue;
+            };
+            repo.track(&ChannelConfirmation {
+                node_id: credential.node_id.clone(),
+                txid,
+                account_id: credential.account_id.clone(),
+                user_id: credential.user_id.clone(),
+                node_alias: credential.node_alias.clone(),
+                kind: kind_name(channel.kind).to_string(),
+                channel_point: channel.channel_point,
+                remote_pubkey: channel.remote_pubkey,
+                confirmation_height: None,
+                notified_confirmations: 0,
+            })
+            .await?;
+        }
+    }
+
+    for tracked in repo
+        .get_unfinished(&credential.node_id, FINAL_CONFIRMATIONS as i64)
+        .await?
+    {
+        let height = match mempool.confirmation_height(&tracked.txid).await {
+            Ok(height) => height,
+            Err(e) => {
+                tracing::debug!("Failed to check {}: {}", tracked.txid, e);
+                continue;
+            }
+        };
+        let notified = tracked.notified_confirmations as u32;
+        let milestone =
+            height.and_then(|height| next_milestone(notified, confirmations(tip, height)));
+
+        if let (Some(height), Some(milestone)) = (height, milestone) {
+            record_milestone(pool, &tracked, height, milestone).await;
+        }
+        repo.update(
+            &tracked.node_id,
+            &tracked.txid,
+            height.map(i64::from),
+            milestone.unwrap_or(notified) as i64,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn record_milestone(
+    pool: &SqlitePool,
+    tracked: &ChannelConfirmation,
+    confirmation_height: u32,
+    milestone: u32,
+) {
+    let (title, transaction) = if tracked.kind == "opening" {
+        ("Channel Funding Confirmed", "Funding")
+    } else {
+        ("Channel Close Confirmed", "Closing")
+    };
+    let outcome = match (tracked.kind.as_str(), milestone) {
+        (_, FINAL_CONFIRMATIONS) => "; it is final",
+        ("opening", 3) => "; the channel is usable",
+        _ => "",
+    };
+    let plural = if milestone == 1 { "" } else { "s" };
+
+    let event = CreateEvent {
+        id: Uuid::now_v7().to_string(),
+        account_id: tracked.account_id.clone(),
+        user_id: tracked.user_id.clone(),
+        node_id: tracked.node_id.clone(),
+        node_alias: tracked.node_alias.clone(),
+        event_type: EventType::ChannelConfirmation,
+        severity: EventSeverity::Info,
+        title: title.to_string(),
+        description: format!(
+            "{transaction} transaction of the channel with {} has {milestone} confirmation{plural}{outcome}",
+            tracked.remote_pubkey
+        ),
+        data: json!({
+            "kind": tracked.kind,
+            "txid": tracked.txid,
+            "channel_point": tracked.channel_point,
+            "remote_pubkey": tracked.remote_pubkey,
+            "confirmations": milestone,
+            "confirmation_height": confirmation_height,
+        })
+        .to_string(),
+        notifications_id: None,
+        timestamp: chrono::Utc::now(),
+    };
+
+    if let Err(e) = EventService::new(pool)
+        .create_and_dispatch_event(event)
+        .await
+    {
+        tracing::error!("Failed to record confirmation of {}: {}", tracked.txid, e);
+    }
+}
+
+/// Fills in confirmation counts of pending channels from the tracked
+/// transactions, as seen from the node at `node_height`. Transactions not
+/// tracked yet (before the next block) are left without counts.
+pub async fn annotate_pending_channels(
+    pool: &SqlitePool,
+    node_id: &str,
+    node_height: u32,
+    channels: &mut [PendingChannel],
+) -> ServiceResult<()> {
+    let heights: HashMap<String, Option<i64>> = ChannelConfirmationRepository::new(pool)
+        .get_confirmation_heights(node_id)
+        .await?
+        .into_iter()
+        .collect();
+
+    for channel in channels {
+        let Some(height) = channel.txid.as_ref().and_then(|txid| heights.get(txid)) else {
+            continue;
+        };
+        let confirmed = height.map_or(0, |height| confirmations(node_height, height as u32));
+        channel.confirmations = Some(confirmed);
+        channel.confirmations_remaining = Some(FINAL_CONFIRMATIONS.saturating_sub(confirmed));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_milestone() {
+        assert_eq!(confirmations(100, 100), 1);
+        assert_eq!(confirmations(99, 100), 0);
+        assert_eq!(next_milestone(0, 0), None);
+        assert_eq!(next_milestone(0, 1), Some(1));
+        assert_eq!(next_milestone(1, 2), None);
+        // Several blocks at once report only the highest milestone.
+        assert_eq!(next_milestone(1, 7), Some(6));
+        assert_eq!(next_milestone(6, 10), None);
+    }
+}
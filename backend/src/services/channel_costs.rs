@@ -0,0 +1,51 @@
+//! On-chain fees of a channel's funding and closing transactions.
+//!
+//! The opener pays for both transactions, so a channel the peer opened cost
+//! us nothing on chain. Otherwise the fee comes from the node's wallet, or
+//! from mempool.space for transactions the wallet doesn't list (e.g. a
+//! funding transaction paid from an external wallet).
+
+use crate::services::mempool::MempoolClient;
+use crate::services::node_manager::LightningClient;
+use crate::utils::ChannelDetails;
+use bitcoin::Txid;
+
+/// Fills in `funding_fee_sat` and, for closed channels, `closing_fee_sat`.
+pub async fn fill_onchain_fees(node: &dyn LightningClient, channel: &mut ChannelDetails) {
+    match channel.initiator {
+        Some(false) => {
+            channel.funding_fee_sat = Some(0);
+            channel.closing_fee_sat = channel.closing_txid.map(|_| 0);
+        }
+        Some(true) => {
+            let mempool = MempoolClient::new()
+                .inspect_err(|e| tracing::warn!("mempool.space lookups unavailable: {}", e))
+                .ok();
+            if let Some(txid) = channel.txid {
+                channel.funding_fee_sat = transaction_fee(node, mempool.as_ref(), &txid).await;
+            }
+            if let Some(txid) = channel.closing_txid {
+                channel.closing_fee_sat = transaction_fee(node, mempool.as_ref(), &txid).await;
+            }
+        }
+        None => {}
+    }
+}
+
+async fn transaction_fee(
+    node: &dyn LightningClient,
+    mempool: Option<&MempoolClient>,
+    txid: &Txid,
+) -> Option<u64> {
+    if let Ok(Some(fee)) = node.get_transaction_fee(txid).await {
+        return Some(fee);
+    }
+
+    match mempool?.transaction_fee(&txid.to_string()).await {
+        Ok(fee) => fee,
+        Err(e) => {
+            tracing::debug!("Failed to look up the fee of {}: {}", txid, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Notes on channels and peers.
+//!
+//! Operators can keep labelled notes on a node's channels, by short channel
+//! id, and on its peers, by public key. A channel shows the notes of both the
+//! channel and its peer, and channel lists can be filtered by label.
+
+use crate::database::models::{
+    ChannelNote, ChannelNoteQuery, CreateChannelNoteRequest, NoteTarget, UpdateChannelNoteRequest,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::channel_note_repository::ChannelNoteRepository;
+use crate::services::cln_rest::parse_short_channel_id;
+use bitcoin::secp256k1::PublicKey;
+use sqlx::SqlitePool;
+use std::str::FromStr;
+use validator::Validate;
+
+/// The notes kept on a channel or on its peer.
+pub fn notes_for(notes: &[ChannelNote], channel_id: &str, remote_pubkey: &str) -> Vec<ChannelNote> {
+    notes
+        .iter()
+        .filter(|note| match note.target_type {
+            NoteTarget::Channel => note.target_id == channel_id,
+            NoteTarget::Peer => note.target_id == remote_pubkey,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether any of the notes carries `label`, ignoring case.
+pub fn has_label(notes: &[ChannelNote], label: &str) -> bool {
+    notes
+        .iter()
+        .any(|note| note.label.eq_ignore_ascii_case(label.trim()))
+}
+
+/// Brings a target id to the form notes are stored under: the integer short
+/// channel id (CLN's `BLOCKxTXxOUT` is accepted) or the public key in hex.
+fn normalize_target(target_type: NoteTarget, target_id: &str) -> ServiceResult<String> {
+    let target_id = target_id.trim();
+    match target_type {
+        NoteTarget::Channel => parse_short_channel_id(target_id)
+            .filter(|scid| *scid != 0)
+            .map(|scid| scid.to_string())
+            .ok_or_else(|| ServiceError::validation("Target ID must be a short channel id")),
+        NoteTarget::Peer => PublicKey::from_str(target_id)
+            .map(|pubkey| pubkey.to_string())
+            .map_err(|_| ServiceError::validation("Target ID must be a peer public key")),
+    }
+}
+
+/// Trims a note's text, dropping it if blank.
+fn note_text(note: Option<String>) -> Option<String> {
+    note.map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty())
+}
+
+/// Service layer for channel and peer notes.
+pub struct ChannelNoteService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ChannelNoteService<'a> {
+    /// Creates a new ChannelNoteService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists a node's notes matching the query.
+    pub async fn list_notes(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: ChannelNoteQuery,
+    ) -> ServiceResult<Vec<ChannelNote>> {
+        let target_id = match (query.target_type, query.target_id.as_deref()) {
+            (Some(target_type), Some(target_id)) => Some(normalize_target(target_type, target_id)?),
+            (None, Some(_)) => {
+                return Err(ServiceError::validation(
+                    "target_type is required when filtering by target_id",
+                ));
+            }
+            _ => None,
+        };
+        let label = query.label.as_deref().map(str::trim);
+
+        Ok(ChannelNoteRepository::new(self.pool)
+            .get_notes(
+                account_id,
+                node_id,
+                query.target_type,
+                target_id.as_deref(),
+                label,
+            )
+            .await?)
+    }
+
+    /// Adds a note to a channel or peer of the node.
+    pub async fn create_note(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: CreateChannelNoteRequest,
+    ) -> ServiceResult<ChannelNote> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        let label = request.label.trim().to_string();
+        if label.is_empty() {
+            return Err(ServiceError::validation("Label is required"));
+        }
+
+        let request = CreateChannelNoteRequest {
+            target_id: normalize_target(request.target_type, &request.target_id)?,
+            label,
+            note: note_text(request.note),
+            ..request
+        };
+        Ok(ChannelNoteRepository::new(self.pool)
+            .create_note(account_id, node_id, user_id, &request)
+            .await?)
+    }
+
+    /// Changes the label or text of one of the node's notes.
+    pub async fn update_note(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        id: &str,
+        request: UpdateChannelNoteRequest,
+    ) -> ServiceResult<ChannelNote> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let repo = ChannelNoteRepository::new(self.pool);
+        let mut note = repo
+            .get_note(id, account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Channel note", id))?;
+
+        if let Some(label) = request.label {
+            let label = label.trim().to_string();
+            if label.is_empty() {
+                return Err(ServiceError::validation("Label is required"));
+            }
+            note.label = label;
+        }
+        if request.note.is_some() {
+            note.note = note_text(request.note);
+        }
+
+        if !repo
+            .update_note(id, &note.label, note.note.as_deref())
+            .await?
+        {
+            return Err(ServiceError::not_found("Channel note", id));
+        }
+        repo.get_note(id, account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Channel note", id))
+    }
+
+    /// Deletes one of the node's notes.
+    pub async fn delete_note(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        id: &str,
+    ) -> ServiceResult<()> {
+        let repo = ChannelNoteRepository::new(self.pool);
+        repo.get_note(id, account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Channel note", id))?;
+        repo.delete_note(id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn note(target_type: NoteTarget, target_id: &str, label: &str) -> ChannelNote {
+        ChannelNote {
+            id: label.to_string(),
+            account_id: "account".to_string(),
+            node_id: "node".to_string(),
+            target_type,
+            target_id: target_id.to_string(),
+            label: label.to_string(),
+            note: None,
+            created_by: "user".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_notes_for_channel_and_peer() {
+        let notes = vec![
+            note(NoteTarget::Channel, "42", "ring of fire #42"),
+            note(NoteTarget::Peer, "02aa", "do not close"),
+            note(NoteTarget::Channel, "43", "other channel"),
+        ];
+
+        let matched = notes_for(&notes, "42", "02aa");
+        assert_eq!(matched.len(), 2);
+        assert!(has_label(&matched, " Do Not Close"));
+        assert!(!has_label(&matched, "other channel"));
+        // A peer id that happens to look like a channel id doesn't match.
+        assert!(notes_for(&notes, "02aa", "42").is_empty());
+    }
+
+    #[test]
+    fn test_normalize_target() {
+        assert_eq!(
+            normalize_target(NoteTarget::Channel, "1x2x3").unwrap(),
+            ((1u64 << 40) | (2 << 16) | 3).to_string()
+        );
+        assert!(normalize_target(NoteTarget::Channel, "0").is_err());
+        assert!(normalize_target(NoteTarget::Peer, "02aa").is_err());
+    }
+}
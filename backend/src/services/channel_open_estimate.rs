@@ -0,0 +1,183 @@
+//! Cost estimates for opening a channel.
+//!
+//! Before committing to an open, an operator can see what the funding
+//! transaction should cost at the fee rates mempool.space currently
+//! recommends (or one of their own), how soon each rate should confirm, and
+//! how much of the channel is tied up in reserves rather than spendable.
+//! Sizes assume P2WPKH wallet inputs and change, and a P2WSH funding output.
+
+use crate::database::models::{
+    OpenChannelEstimate, OpenChannelEstimateRequest, OpenChannelFeeTier,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::services::mempool::MempoolClient;
+use validator::Validate;
+
+/// Version, locktime, counts and the segwit marker, rounded up
+const TX_OVERHEAD_VBYTES: i64 = 11;
+const P2WPKH_INPUT_VBYTES: i64 = 68;
+const P2WSH_OUTPUT_VBYTES: i64 = 43;
+const P2WPKH_OUTPUT_VBYTES: i64 = 31;
+
+/// Outputs below this are dust, so no reserve is ever smaller
+const DUST_LIMIT_SAT: i64 = 354;
+
+const ANCHOR_OUTPUT_SAT: i64 = 330;
+
+/// Weight of a commitment transaction without HTLCs
+const COMMITMENT_WEIGHT: i64 = 724;
+const ANCHOR_COMMITMENT_WEIGHT: i64 = 1_124;
+
+/// Anchor commitments are fee-bumped when closing, so they only pay this
+const ANCHOR_COMMITMENT_MAX_SAT_PER_VBYTE: f64 = 10.0;
+
+/// On-chain balance LND keeps back per anchor channel to bump its close
+const WALLET_ANCHOR_RESERVE_SAT: i64 = 10_000;
+
+/// Size of a funding transaction spending `inputs` wallet inputs, with change.
+pub fn funding_vbytes(inputs: i64) -> i64 {
+    TX_OVERHEAD_VBYTES + inputs * P2WPKH_INPUT_VBYTES + P2WSH_OUTPUT_VBYTES + P2WPKH_OUTPUT_VBYTES
+}
+
+/// Works out the estimate for `request` at each of `rates`, given as
+/// (label, sat/vB, target blocks).
+pub fn estimate_open(
+    request: &OpenChannelEstimateRequest,
+    rates: &[(&str, f64, Option<u32>)],
+) -> OpenChannelEstimate {
+    let capacity_sat = request.local_funding_sat;
+    let vbytes = funding_vbytes(request.inputs.unwrap_or(1));
+    let wallet_anchor_reserve_sat = if request.anchors {
+        WALLET_ANCHOR_RESERVE_SAT
+    } else {
+        0
+    };
+
+    let tiers = rates
+        .iter()
+        .map(|(label, sat_per_vbyte, target_blocks)| {
+            let funding_fee_sat = (vbytes as f64 * sat_per_vbyte).ceil() as i64;
+            OpenChannelFeeTier {
+                label: label.to_string(),
+                sat_per_vbyte: *sat_per_vbyte,
+                target_blocks: *target_blocks,
+                estimated_minutes: target_blocks.map(|blocks| blocks * 10),
+                funding_fee_sat,
+                total_wallet_spend_sat: capacity_sat + funding_fee_sat + wallet_anchor_reserve_sat,
+            }
+        })
+        .collect();
+
+    // The commitment fee follows the fastest rate, capped for anchor
+    // channels, whose closes are bumped when they happen.
+    let fastest = rates
+        .iter()
+        .map(|(_, sat_per_vbyte, _)| *sat_per_vbyte)
+        .fold(1.0, f64::max);
+    let (commitment_weight, anchor_outputs_sat, commitment_rate) = if request.anchors {
+        (
+            ANCHOR_COMMITMENT_WEIGHT,
+            2 * ANCHOR_OUTPUT_SAT,
+            fastest.min(ANCHOR_COMMITMENT_MAX_SAT_PER_VBYTE),
+        )
+    } else {
+        (COMMITMENT_WEIGHT, 0, fastest)
+    };
+    let commitment_fee_sat = (commitment_weight as f64 / 4.0 * commitment_rate).ceil() as i64;
+    let channel_reserve_sat = (capacity_sat / 100).max(DUST_LIMIT_SAT);
+
+    OpenChannelEstimate {
+        capacity_sat,
+        push_sat: request.push_sat,
+        funding_vbytes: vbytes,
+        tiers,
+        channel_reserve_sat,
+        anchor_outputs_sat,
+        commitment_fee_sat,
+        wallet_anchor_reserve_sat,
+        spendable_local_sat: (capacity_sat
+            - request.push_sat
+            - channel_reserve_sat
+            - anchor_outputs_sat
+            - commitment_fee_sat)
+            .max(0),
+        fee_rates_error: None,
+    }
+}
+
+/// Estimates the cost of opening a channel at the recommended fee rates
+/// and, if given, the caller's own rate.
+pub async fn estimate_channel_open(
+    request: OpenChannelEstimateRequest,
+) -> ServiceResult<OpenChannelEstimate> {
+    if let Err(validation_errors) = request.validate() {
+        return Err(ServiceError::validation(validation_errors.to_string()));
+    }
+    if request.push_sat >= request.local_funding_sat {
+        return Err(ServiceError::validation(
+            "Push amount must be less than the channel size",
+        ));
+    }
+
+    let recommended = match MempoolClient::new() {
+        Ok(client) => client.recommended_fees().await,
+        Err(e) => Err(e),
+    };
+
+    let mut rates = Vec::new();
+    let mut fee_rates_error = None;
+    match recommended {
+        Ok(fees) => rates.extend([
+            ("fastest", fees.fastest_fee, Some(1)),
+            ("half_hour", fees.half_hour_fee, Some(3)),
+            ("hour", fees.hour_fee, Some(6)),
+            ("economy", fees.economy_fee, Some(144)),
+        ]),
+        // A rate of the caller's own is still worth estimating.
+        Err(e) if request.sat_per_vbyte.is_some() => fee_rates_error = Some(e.to_string()),
+        Err(e) => return Err(e),
+    }
+    if let Some(sat_per_vbyte) = request.sat_per_vbyte {
+        rates.push(("custom", sat_per_vbyte, None));
+    }
+
+    let mut estimate = estimate_open(&request, &rates);
+    estimate.fee_rates_error = fee_rates_error;
+    Ok(estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(anchors: bool) -> OpenChannelEstimateRequest {
+        OpenChannelEstimateRequest {
+            local_funding_sat: 1_000_000,
+            push_sat: 10_000,
+            inputs: Some(2),
+            anchors,
+            sat_per_vbyte: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_open() {
+        let rates = [("fastest", 20.0, Some(1)), ("economy", 2.5, Some(144))];
+
+        let estimate = estimate_open(&request(true), &rates);
+        assert_eq!(estimate.funding_vbytes, 221);
+        assert_eq!(estimate.tiers[0].funding_fee_sat, 4_420);
+        assert_eq!(estimate.tiers[0].total_wallet_spend_sat, 1_014_420);
+        assert_eq!(estimate.tiers[1].funding_fee_sat, 553);
+        assert_eq!(estimate.tiers[1].estimated_minutes, Some(1_440));
+        assert_eq!(estimate.channel_reserve_sat, 10_000);
+        // Anchor commitments pay at most 10 sat/vB.
+        assert_eq!(estimate.commitment_fee_sat, 2_810);
+        assert_eq!(estimate.spendable_local_sat, 976_530);
+
+        let legacy = estimate_open(&request(false), &rates);
+        assert_eq!(legacy.anchor_outputs_sat, 0);
+        assert_eq!(legacy.wallet_anchor_reserve_sat, 0);
+        assert_eq!(legacy.commitment_fee_sat, 3_620);
+    }
+}
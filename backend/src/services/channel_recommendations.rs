@@ -0,0 +1,266 @@
+//! Suggestions for new channel partners.
+//!
+//! Candidates are well-connected nodes in the graph mirror that we don't
+//! already have a channel with. Each is scored on four signals:
+//!
+//! - position: closeness centrality, so channels shorten routes to the network
+//! - demand: forwards the HTLC interceptor failed toward peers the candidate
+//!   is also connected to, i.e. traffic a direct channel could have carried
+//! - uptime: how many of the candidate's channels are enabled and how
+//!   recently it announced itself
+//! - fee environment: how much the candidate's peers charge to reach it
+//!   compared to the rest of the network
+
+use crate::database::models::{ChannelRecommendation, ChannelRecommendationQuery};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::graph_repository::GraphRepository;
+use crate::services::graph_sync::percentile;
+use crate::services::network_position::ChannelGraph;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+
+const POSITION_WEIGHT: f64 = 0.35;
+const DEMAND_WEIGHT: f64 = 0.25;
+const UPTIME_WEIGHT: f64 = 0.25;
+const FEE_WEIGHT: f64 = 0.15;
+
+/// Nodes with fewer channels aren't considered
+const MIN_CANDIDATE_CHANNELS: i64 = 5;
+/// Most-connected nodes scored per request
+const MAX_CANDIDATES: usize = 200;
+
+/// Announcements newer than this count as fully fresh
+const FRESH_ANNOUNCEMENT_DAYS: i64 = 14;
+/// Announcements older than this count as stale
+const STALE_ANNOUNCEMENT_DAYS: i64 = 60;
+
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 50;
+const DEFAULT_DEMAND_DAYS: i64 = 30;
+const MAX_DEMAND_DAYS: i64 = 365;
+
+/// Combines component scores (each 0-1) into a 0-100 score.
+pub fn weighted_score(position: f64, demand: f64, uptime: f64, fee: f64) -> f64 {
+    100.0
+        * (POSITION_WEIGHT * position
+            + DEMAND_WEIGHT * demand
+            + UPTIME_WEIGHT * uptime
+            + FEE_WEIGHT * fee)
+}
+
+/// Share of enabled channels, scaled down as the last announcement ages.
+pub fn uptime_score(
+    enabled: usize,
+    total: usize,
+    last_update: Option<u64>,
+    now: DateTime<Utc>,
+) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let freshness = match last_update.and_then(|t| DateTime::from_timestamp(t as i64, 0)) {
+        Some(announced) => {
+            let age_days = (now - announced).num_days();
+            if age_days <= FRESH_ANNOUNCEMENT_DAYS {
+                1.0
+            } else {
+                let span = (STALE_ANNOUNCEMENT_DAYS - FRESH_ANNOUNCEMENT_DAYS) as f64;
+                (1.0 - (age_days - FRESH_ANNOUNCEMENT_DAYS) as f64 / span).max(0.0)
+            }
+        }
+        // Nodes the mirror only knows from channel announcements
+        None => 0.5,
+    };
+    enabled as f64 / total as f64 * freshness
+}
+
+/// Inbound fees at or above twice the network median score 1.
+pub fn fee_score(median_inbound_ppm: Option<i64>, network_median_ppm: Option<i64>) -> f64 {
+    match (median_inbound_ppm, network_median_ppm) {
+        (Some(inbound), Some(network)) if network > 0 => {
+            (inbound as f64 / (2 * network) as f64).min(1.0)
+        }
+        (Some(inbound), _) if inbound > 0 => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Service layer for channel partner recommendations.
+pub struct ChannelRecommendationService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ChannelRecommendationService<'a> {
+    /// Creates a new ChannelRecommendationService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Ranks candidate peers for the node; empty until its graph is mirrored.
+    pub async fn get_recommendations(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: ChannelRecommendationQuery,
+    ) -> ServiceResult<Vec<ChannelRecommendation>> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_DEMAND_DAYS)
+            .clamp(1, MAX_DEMAND_DAYS);
+
+        let graph_repo = GraphRepository::new(self.pool);
+        let edges = graph_repo.get_edges(node_id).await?;
+        if edges.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let nodes: HashMap<String, (String, Option<u64>)> = graph_repo
+            .get_nodes(node_id)
+            .await?
+            .into_iter()
+            .map(|n| (n.pubkey, (n.alias, n.last_update)))
+            .collect();
+
+        // (enabled, total) channels per node, and the fee rates charged toward it
+        let mut availability: HashMap<String, (usize, usize)> = HashMap::new();
+        let mut inbound_fees: HashMap<String, Vec<i64>> = HashMap::new();
+        let mut network_fees = Vec::new();
+        for (advertiser, peer, fee_rate_ppm, disabled) in
+            graph_repo.get_directed_policies(node_id).await?
+        {
+            let entry = availability.entry(advertiser).or_default();
+            entry.1 += 1;
+            if !disabled {
+                entry.0 += 1;
+                inbound_fees.entry(peer).or_default().push(fee_rate_ppm);
+                network_fees.push(fee_rate_ppm);
+            }
+        }
+        network_fees.sort_unstable();
+        let network_median = percentile(&network_fees, 50.0);
+
+        let channel_peers = graph_repo.get_channel_peers(node_id, node_id).await?;
+        let mut failed_by_peer: HashMap<String, i64> = HashMap::new();
+        for (chan_id, count, _) in EventRepository::new(self.pool)
+            .get_failed_forwards_by_channel(account_id, node_id, Utc::now() - Duration::days(days))
+            .await?
+        {
+            if let Some(peer) = channel_peers.get(&chan_id) {
+                *failed_by_peer.entry(peer.clone()).or_default() += count;
+            }
+        }
+
+        let owner = node_id.to_string();
+        let candidates = tokio::task::spawn_blocking(move || {
+            let graph = ChannelGraph::from_edges(&edges);
+            let peers: HashSet<String> = graph.neighbours(&owner).into_iter().collect();
+            let pubkeys: Vec<String> = graph
+                .pubkeys_by_degree()
+                .into_iter()
+                .filter(|pubkey| *pubkey != owner && !peers.contains(pubkey))
+                .take(MAX_CANDIDATES)
+                .collect();
+
+            let scores = graph.score(&pubkeys, 0);
+            pubkeys
+                .into_iter()
+                .filter_map(|pubkey| {
+                    let score = scores.get(&pubkey)?.clone();
+                    if score.channel_count < MIN_CANDIDATE_CHANNELS {
+                        return None;
+                    }
+                    let failed: i64 = graph
+                        .neighbours(&pubkey)
+                        .iter()
+                        .filter_map(|n| failed_by_peer.get(n))
+                        .sum();
+                    Some((pubkey, score, failed))
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        let max_closeness = candidates
+            .iter()
+            .map(|(_, score, _)| score.closeness)
+            .fold(0.0, f64::max);
+        let max_failed = candidates.iter().map(|(_, _, f)| *f).max().unwrap_or(0);
+        let now = Utc::now();
+
+        let mut recommendations: Vec<ChannelRecommendation> = candidates
+            .into_iter()
+            .map(|(pubkey, score, failed_forwards)| {
+                let (alias, last_update) = nodes.get(&pubkey).cloned().unwrap_or_default();
+                let (enabled, total) = availability.get(&pubkey).copied().unwrap_or_default();
+                let median_inbound_fee_ppm = inbound_fees.get_mut(&pubkey).and_then(|fees| {
+                    fees.sort_unstable();
+                    percentile(fees, 50.0)
+                });
+
+                let position_score = if max_closeness > 0.0 {
+                    score.closeness / max_closeness
+                } else {
+                    0.0
+                };
+                let demand_score = if max_failed > 0 {
+                    failed_forwards as f64 / max_failed as f64
+                } else {
+                    0.0
+                };
+                let uptime_score = uptime_score(enabled, total, last_update, now);
+                let fee_score = fee_score(median_inbound_fee_ppm, network_median);
+
+                ChannelRecommendation {
+                    pubkey,
+                    alias,
+                    score: weighted_score(position_score, demand_score, uptime_score, fee_score),
+                    position_score,
+                    demand_score,
+                    uptime_score,
+                    fee_score,
+                    channel_count: score.channel_count,
+                    capacity_sat: score.capacity_sat,
+                    failed_forwards,
+                    median_inbound_fee_ppm,
+                }
+            })
+            .collect();
+
+        recommendations.sort_by(|a, b| b.score.total_cmp(&a.score));
+        recommendations.truncate(limit);
+        Ok(recommendations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uptime_score() {
+        let now = Utc::now();
+        let recent = Some((now - Duration::days(3)).timestamp() as u64);
+        let stale = Some((now - Duration::days(90)).timestamp() as u64);
+        let aging = Some((now - Duration::days(37)).timestamp() as u64);
+
+        assert_eq!(uptime_score(4, 4, recent, now), 1.0);
+        assert_eq!(uptime_score(3, 4, recent, now), 0.75);
+        assert_eq!(uptime_score(4, 4, stale, now), 0.0);
+        assert!((uptime_score(4, 4, aging, now) - 0.5).abs() < 0.05);
+        assert_eq!(uptime_score(2, 4, None, now), 0.25);
+        assert_eq!(uptime_score(0, 0, recent, now), 0.0);
+    }
+
+    #[test]
+    fn test_fee_score() {
+        assert_eq!(fee_score(Some(100), Some(100)), 0.5);
+        assert_eq!(fee_score(Some(500), Some(100)), 1.0);
+        assert_eq!(fee_score(None, Some(100)), 0.0);
+        assert_eq!(fee_score(Some(10), Some(0)), 1.0);
+        assert_eq!(weighted_score(1.0, 1.0, 1.0, 1.0), 100.0);
+    }
+}
@@ -0,0 +1,179 @@
+//! Routing revenue per channel.
+//!
+//! Built from the forwards table: every settled forward earned its fee on
+//! two channels, the one it came in through and the one it went out on, so
+//! a channel's revenue is split by the leg it played.
+
+use crate::database::models::{
+    ChannelRevenue, ChannelRevenuePoint, ChannelRevenueQuery, ForwardRecord,
+};
+use crate::errors::ServiceResult;
+use crate::repositories::forward_repository::ForwardRepository;
+use crate::services::account_settings_service::AccountSettingsService;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+const DEFAULT_REVENUE_DAYS: i64 = 30;
+
+const MAX_REVENUE_DAYS: i64 = 365;
+
+/// Sums what `channel_id` earned from `forwards` over the `days` ending at
+/// `now`, with days counted in `tz`.
+pub fn channel_revenue(
+    channel_id: &str,
+    forwards: &[ForwardRecord],
+    days: i64,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> ChannelRevenue {
+    let first_day = now.with_timezone(&tz).date_naive() - Duration::days(days - 1);
+    let mut by_day: BTreeMap<NaiveDate, ChannelRevenuePoint> = first_day
+        .iter_days()
+        .take(days as usize)
+        .map(|date| {
+            (
+                date,
+                ChannelRevenuePoint {
+                    date,
+                    inbound_fee_msat: 0,
+                    outbound_fee_msat: 0,
+                    volume_msat: 0,
+                },
+            )
+        })
+        .collect();
+
+    let mut revenue = ChannelRevenue {
+        channel_id: channel_id.to_string(),
+        days,
+        inbound_forwards: 0,
+        outbound_forwards: 0,
+        inbound_fee_msat: 0,
+        outbound_fee_msat: 0,
+        volume_msat: 0,
+        series: Vec::new(),
+    };
+    for forward in forwards {
+        let Some(point) = by_day.get_mut(&forward.settled_at.with_timezone(&tz).date_naive())
+        else {
+            continue;
+        };
+        let fee_msat = forward.fee_msat.max(0) as u64;
+        if forward.chan_id_in == channel_id {
+            let amount = forward.amt_in_msat.max(0) as u64;
+            revenue.inbound_forwards += 1;
+            revenue.inbound_fee_msat += fee_msat;
+            revenue.volume_msat += amount;
+            point.inbound_fee_msat += fee_msat;
+            point.volume_msat += amount;
+        }
+        if forward.chan_id_out == channel_id {
+            let amount = forward.amt_out_msat.max(0) as u64;
+            revenue.outbound_forwards += 1;
+            revenue.outbound_fee_msat += fee_msat;
+            revenue.volume_msat += amount;
+            point.outbound_fee_msat += fee_msat;
+            point.volume_msat += amount;
+        }
+    }
+    revenue.series = by_day.into_values().collect();
+    revenue
+}
+
+/// Service layer for channel revenue reports.
+pub struct ChannelRevenueService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ChannelRevenueService<'a> {
+    /// Creates a new ChannelRevenueService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Reports the fees and volume of one of the node's channels over the
+    /// requested number of days.
+    pub async fn get_revenue(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        channel_id: &str,
+        query: ChannelRevenueQuery,
+    ) -> ServiceResult<ChannelRevenue> {
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_REVENUE_DAYS)
+            .clamp(1, MAX_REVENUE_DAYS);
+        let tz = AccountSettingsService::new(self.pool)
+            .get_settings(account_id)
+            .await?
+            .tz();
+        let now = Utc::now();
+        // Start at local midnight so the oldest day of the series is complete.
+        let since = (now.with_timezone(&tz).date_naive() - Duration::days(days - 1))
+            .and_hms_opt(0, 0, 0)
+            .and_then(|start| start.and_local_timezone(tz).earliest())
+            .map_or(now - Duration::days(days), |start| {
+                start.with_timezone(&Utc)
+            });
+        let forwards = ForwardRepository::new(self.pool)
+            .get_channel_forwards_since(account_id, node_id, channel_id, since)
+            .await?;
+
+        Ok(channel_revenue(channel_id, &forwards, days, now, tz))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forward(chan_id_in: &str, chan_id_out: &str, fee_msat: i64, day: i64) -> ForwardRecord {
+        ForwardRecord {
+            chan_id_in: chan_id_in.to_string(),
+            chan_id_out: chan_id_out.to_string(),
+            amt_in_msat: 100_000 + fee_msat,
+            amt_out_msat: 100_000,
+            fee_msat,
+            settled_at: DateTime::from_timestamp(1_700_000_000 + day * 86_400, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_channel_revenue() {
+        let now = DateTime::from_timestamp(1_700_000_000 + 2 * 86_400, 0).unwrap();
+        let forwards = vec![
+            forward("1", "2", 10, 0),
+            forward("2", "1", 20, 1),
+            forward("1", "3", 30, 2),
+            forward("1", "2", 40, -5),
+        ];
+
+        let revenue = channel_revenue("1", &forwards, 3, now, chrono_tz::UTC);
+        assert_eq!(revenue.inbound_forwards, 2);
+        assert_eq!(revenue.outbound_forwards, 1);
+        assert_eq!(revenue.inbound_fee_msat, 40);
+        assert_eq!(revenue.outbound_fee_msat, 20);
+        assert_eq!(revenue.volume_msat, 100_010 + 100_000 + 100_030);
+        assert_eq!(revenue.series.len(), 3);
+        assert_eq!(revenue.series[1].outbound_fee_msat, 20);
+        assert_eq!(revenue.series[2].inbound_fee_msat, 30);
+
+        // 00:13 UTC on the oldest day is still the day before in Tokyo's
+        // window, which runs a day ahead.
+        let early = ForwardRecord {
+            settled_at: forwards[0].settled_at - Duration::hours(22),
+            ..forward("1", "2", 50, 0)
+        };
+        let utc = channel_revenue("1", std::slice::from_ref(&early), 3, now, chrono_tz::UTC);
+        assert_eq!(utc.inbound_fee_msat, 50);
+        let tokyo = channel_revenue("1", &[early], 3, now, chrono_tz::Asia::Tokyo);
+        assert_eq!(tokyo.inbound_fee_msat, 0);
+        assert_eq!(
+            tokyo.series[2].date,
+            NaiveDate::from_ymd_opt(2023, 11, 17).unwrap()
+        );
+    }
+}
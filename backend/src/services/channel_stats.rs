@@ -0,0 +1,122 @@
+//! Channel capacity distribution for the dashboard overview.
+//!
+//! Built from the channels stored by the last node resync. The median needs
+//! every capacity, so rows are read per channel and summarized here rather
+//! than aggregated in SQL.
+
+use crate::database::models::{CapacityBucket, ChannelStats};
+use crate::errors::ServiceResult;
+use crate::repositories::node_sync_repository::NodeSyncRepository;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Lower bounds of the histogram buckets, in sats; the last is open-ended.
+const CAPACITY_BUCKETS_SAT: [i64; 6] = [0, 1_000_000, 2_000_000, 5_000_000, 10_000_000, 50_000_000];
+
+/// Summarizes (capacity, local balance, remote balance, private) rows.
+pub fn channel_stats(
+    rows: Vec<(i64, i64, i64, bool)>,
+    synced_at: Option<DateTime<Utc>>,
+) -> ChannelStats {
+    let mut capacities: Vec<i64> = rows.iter().map(|row| row.0).collect();
+    capacities.sort_unstable();
+    let channels = capacities.len() as i64;
+    let total_capacity_sat: i64 = capacities.iter().sum();
+    let median_capacity_sat = match capacities.len() {
+        0 => 0,
+        n if n % 2 == 1 => capacities[n / 2],
+        n => (capacities[n / 2 - 1] + capacities[n / 2]) / 2,
+    };
+
+    let local_balance_sat: i64 = rows.iter().map(|row| row.1).sum();
+    let remote_balance_sat: i64 = rows.iter().map(|row| row.2).sum();
+    let private = rows.iter().filter(|row| row.3).count() as i64;
+    let balance = local_balance_sat + remote_balance_sat;
+
+    let histogram = CAPACITY_BUCKETS_SAT
+        .iter()
+        .enumerate()
+        .map(|(i, &min_sat)| {
+            let max_sat = CAPACITY_BUCKETS_SAT.get(i + 1).copied();
+            CapacityBucket {
+                min_sat,
+                max_sat,
+                count: capacities
+                    .iter()
+                    .filter(|&&capacity| {
+                        capacity >= min_sat && max_sat.is_none_or(|max| capacity < max)
+                    })
+                    .count() as i64,
+            }
+        })
+        .collect();
+
+    ChannelStats {
+        channels,
+        public: channels - private,
+        private,
+        total_capacity_sat,
+        median_capacity_sat,
+        mean_capacity_sat: if channels > 0 {
+            total_capacity_sat / channels
+        } else {
+            0
+        },
+        local_balance_sat,
+        remote_balance_sat,
+        local_ratio: (balance > 0).then(|| local_balance_sat as f64 / balance as f64),
+        histogram,
+        synced_at,
+    }
+}
+
+/// Service layer for channel statistics.
+pub struct ChannelStatsService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ChannelStatsService<'a> {
+    /// Creates a new ChannelStatsService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the capacity distribution of the node's open channels.
+    pub async fn get_stats(&self, account_id: &str, node_id: &str) -> ServiceResult<ChannelStats> {
+        let repo = NodeSyncRepository::new(self.pool);
+        let rows = repo.get_open_channel_balances(account_id, node_id).await?;
+        let synced_at = repo.get_channels_synced_at(account_id, node_id).await?;
+
+        Ok(channel_stats(rows, synced_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_stats() {
+        let rows = vec![
+            (500_000, 400_000, 90_000, false),
+            (2_000_000, 1_000_000, 990_000, true),
+            (3_000_000, 0, 2_990_000, false),
+            (60_000_000, 30_000_000, 29_990_000, false),
+        ];
+
+        let stats = channel_stats(rows, None);
+        assert_eq!(stats.channels, 4);
+        assert_eq!((stats.public, stats.private), (3, 1));
+        assert_eq!(stats.total_capacity_sat, 65_500_000);
+        assert_eq!(stats.median_capacity_sat, 2_500_000);
+        assert_eq!(stats.mean_capacity_sat, 16_375_000);
+        assert_eq!(stats.local_balance_sat, 31_400_000);
+        let counts: Vec<i64> = stats.histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 0, 2, 0, 0, 1]);
+        assert_eq!(stats.histogram.last().unwrap().max_sat, None);
+
+        let empty = channel_stats(Vec::new(), None);
+        assert_eq!(empty.median_capacity_sat, 0);
+        assert_eq!(empty.local_ratio, None);
+    }
+}
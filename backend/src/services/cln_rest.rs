@@ -0,0 +1,1084 @@
+//! Core Lightning client backed by the `clnrest` plugin.
+//!
+//! `clnrest` exposes CLN's JSON-RPC methods as `POST /v1/<method>` over HTTPS and
+//! authorizes each call with a rune, which is much easier for operators to hand
+//! over than the client certificate bundle required by the gRPC interface.
+
+use crate::{
+    errors::LightningError,
+    services::{event_manager::LightningEvent, node_manager::LightningClient},
+    utils::{
+        self, ChannelDetails, ChannelSummary, CustomInvoice, Forward, MessageVerification, NodeId,
+        NodeInfo, NodePolicy, OfferInvoice, PaymentDetails, PaymentHtlc, PaymentLatency,
+        PaymentMismatch, PaymentState, PaymentSummary, PaymentType, ShortChannelID,
+        sats_to_usd::PriceConverter,
+        states::{cln_channel_state, cln_invoice_payment_state, cln_invoice_status, cln_pay_state},
+    },
+};
+
+use async_trait::async_trait;
+use bitcoin::{Network, Txid, secp256k1::PublicKey};
+use lightning::ln::{PaymentHash, features::NodeFeatures};
+use reqwest::{Certificate, Client};
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use std::{collections::HashSet, pin::Pin, str::FromStr, time::Duration};
+use tokio_stream::Stream;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClnRestConnection {
+    #[serde(with = "utils::serde_node_id")]
+    pub id: NodeId,
+    #[serde(with = "utils::serde_address")]
+    pub address: String,
+    pub rune: String,
+    /// Optional path to the CA that signed clnrest's certificate (`ca.pem` in
+    /// the lightning directory). Without it the system roots are used.
+    #[serde(default, deserialize_with = "utils::deserialize_optional_path")]
+    pub ca_cert: Option<String>,
+}
+
+/// Thin HTTP wrapper that invokes CLN RPC methods through clnrest.
+struct ClnRestClient {
+    http: Client,
+    base_url: String,
+    rune: String,
+}
+
+impl ClnRestClient {
+    /// Invokes a CLN RPC method and decodes its JSON result.
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, String> {
+        let response = self
+            .http
+            .post(format!("{}/v1/{method}", self.base_url))
+            .header("Rune", &self.rune)
+            .json(&params)
+            .send()
+            .await
+            .map_err(|err| format!("clnrest {method} request failed: {err}"))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<RestError>(&body)
+                .map(|err| err.message)
+                .unwrap_or(body);
+            return Err(format!("clnrest {method} returned {status}: {message}"));
+        }
+
+        response
+            .json::<T>()
+            .await
+            .map_err(|err| format!("Invalid clnrest {method} response: {err}"))
+    }
+}
+
+pub struct ClnRestNode {
+    client: ClnRestClient,
+    pub info: NodeInfo,
+    network: Network,
+    price_converter: PriceConverter,
+}
+
+/// Deserializes msat amounts, which CLN reports as plain integers since v23.05
+/// and as `"<n>msat"` strings before that.
+fn deserialize_msat<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Msat {
+        Number(u64),
+        Text(String),
+    }
+
+    match Option::<Msat>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Msat::Number(msat)) => Ok(Some(msat)),
+        Some(Msat::Text(text)) => text
+            .trim_end_matches("msat")
+            .parse()
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// Parses a short channel id in either CLN's `BLOCKxTXxOUT` notation or its
+/// integer encoding.
+pub(crate) fn parse_short_channel_id(scid: &str) -> Option<u64> {
+    let parts: Vec<&str> = scid.split('x').collect();
+    match parts.as_slice() {
+        [block, tx, output] => {
+            let block: u64 = block.parse().ok()?;
+            let tx: u64 = tx.parse().ok()?;
+            let output: u64 = output.parse().ok()?;
+            Some((block << 40) | (tx << 16) | output)
+        }
+        [integer] => integer.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Formats a short channel id in CLN's `BLOCKxTXxOUT` notation.
+pub(crate) fn format_short_channel_id(scid: u64) -> String {
+    format!(
+        "{}x{}x{}",
+        scid >> 40,
+        (scid >> 16) & 0xFF_FFFF,
+        scid & 0xFFFF
+    )
+}
+
+#[derive(Deserialize)]
+struct RestError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GetinfoResponse {
+    id: String,
+    alias: Option<String>,
+    network: String,
+    blockheight: u32,
+    our_features: Option<OurFeatures>,
+}
+
+#[derive(Deserialize)]
+struct OurFeatures {
+    node: String,
+}
+
+#[derive(Deserialize)]
+struct ListnodesResponse {
+    nodes: Vec<ListnodesNode>,
+}
+
+#[derive(Deserialize)]
+struct ListnodesNode {
+    alias: Option<String>,
+    features: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ListpeerchannelsResponse {
+    channels: Vec<PeerChannel>,
+}
+
+#[derive(Deserialize)]
+struct PeerChannel {
+    peer_id: String,
+    short_channel_id: Option<String>,
+    state: String,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    total_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    to_us_msat: Option<u64>,
+    alias: Option<PeerChannelAlias>,
+    opener: Option<String>,
+    private: Option<bool>,
+    funding_txid: Option<String>,
+    funding_outnum: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    last_tx_fee_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    our_reserve_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    their_reserve_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    out_fulfilled_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    in_fulfilled_msat: Option<u64>,
+    updates: Option<PeerChannelUpdates>,
+}
+
+#[derive(Deserialize)]
+struct PeerChannelAlias {
+    remote: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PeerChannelUpdates {
+    local: Option<ChannelUpdatePolicy>,
+    remote: Option<ChannelUpdatePolicy>,
+}
+
+#[derive(Deserialize)]
+struct ChannelUpdatePolicy {
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    htlc_minimum_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    htlc_maximum_msat: Option<u64>,
+    cltv_expiry_delta: u32,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    fee_base_msat: Option<u64>,
+    fee_proportional_millionths: u32,
+}
+
+#[derive(Deserialize)]
+struct ListchannelsResponse {
+    channels: Vec<GossipChannel>,
+}
+
+#[derive(Deserialize)]
+struct GossipChannel {
+    source: String,
+    short_channel_id: String,
+    public: bool,
+    active: bool,
+    last_update: u64,
+}
+
+#[derive(Deserialize)]
+struct ListpaysResponse {
+    pays: Vec<Pay>,
+}
+
+#[derive(Deserialize)]
+struct Pay {
+    payment_hash: String,
+    status: String,
+    destination: Option<String>,
+    #[serde(default)]
+    created_at: u64,
+    completed_at: Option<u64>,
+    bolt11: Option<String>,
+    description: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    amount_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    amount_sent_msat: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ListinvoicesResponse {
+    invoices: Vec<RestInvoice>,
+}
+
+#[derive(Deserialize)]
+struct RestInvoice {
+    payment_hash: String,
+    status: String,
+    description: Option<String>,
+    bolt11: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    amount_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    amount_received_msat: Option<u64>,
+    pay_index: Option<u64>,
+    paid_at: Option<u64>,
+    #[serde(default)]
+    expires_at: u64,
+    payment_preimage: Option<String>,
+    invreq_payer_note: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ListsendpaysResponse {
+    payments: Vec<SendPay>,
+}
+
+#[derive(Deserialize)]
+struct SendPay {
+    id: u64,
+    created_at: u64,
+    completed_at: Option<u64>,
+    erroronion: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ListforwardsResponse {
+    forwards: Vec<RestForward>,
+}
+
+#[derive(Deserialize)]
+struct RestForward {
+    in_channel: String,
+    out_channel: Option<String>,
+    in_htlc_id: Option<u64>,
+    out_htlc_id: Option<u64>,
+    received_time: f64,
+    resolved_time: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    in_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    out_msat: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_msat")]
+    fee_msat: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct SignmessageResponse {
+    zbase: String,
+}
+
+#[derive(Deserialize)]
+struct CheckmessageResponse {
+    verified: bool,
+    pubkey: String,
+}
+
+impl ClnRestNode {
+    pub async fn new(connection: ClnRestConnection) -> Result<Self, LightningError> {
+        let mut builder = Client::builder().timeout(Duration::from_secs(30));
+
+        if let Some(ca_path) = &connection.ca_cert {
+            let pem = tokio::fs::read(ca_path).await.map_err(|err| {
+                LightningError::ConnectionError(format!("Cannot load CA certificate: {err}"))
+            })?;
+            let ca = Certificate::from_pem(&pem).map_err(|err| {
+                LightningError::ConnectionError(format!("Invalid CA certificate: {err}"))
+            })?;
+            // clnrest's generated certificate is issued for "cln"/"localhost", so the
+            // hostname rarely matches the address it is reached on. The chain is
+            // still verified against the pinned CA.
+            builder = builder
+                .add_root_certificate(ca)
+                .danger_accept_invalid_hostnames(true);
+        }
+
+        let client = ClnRestClient {
+            http: builder
+                .build()
+                .map_err(|err| LightningError::ConnectionError(err.to_string()))?,
+            base_url: connection.address.trim_end_matches('/').to_string(),
+            rune: connection.rune,
+        };
+
+        let info: GetinfoResponse = client
+            .call("getinfo", json!({}))
+            .await
+            .map_err(LightningError::GetInfoError)?;
+
+        let pubkey = PublicKey::from_str(&info.id)
+            .map_err(|err| LightningError::GetInfoError(err.to_string()))?;
+        let mut alias = info.alias.unwrap_or_default();
+        connection.id.validate(&pubkey, &mut alias)?;
+
+        let features = info
+            .our_features
+            .and_then(|features| hex::decode(features.node).ok())
+            .map_or(NodeFeatures::empty(), NodeFeatures::from_be_bytes);
+
+        let network = Network::from_str(&info.network)
+            .map_err(|err| LightningError::GetInfoError(err.to_string()))?;
+
+        Ok(Self {
+            client,
+            info: NodeInfo {
+                pubkey,
+                features,
+                alias,
+            },
+            network,
+            price_converter: PriceConverter::new(),
+        })
+    }
+
+    async fn list_peer_channels(&self) -> Result<Vec<PeerChannel>, LightningError> {
+        self.client
+            .call::<ListpeerchannelsResponse>("listpeerchannels", json!({}))
+            .await
+            .map(|response| response.channels)
+            .map_err(LightningError::ChannelError)
+    }
+
+    /// Gossip entries for both directions of our channels. Querying by source and
+    /// destination avoids pulling the whole network graph on mainnet.
+    async fn own_gossip_channels(&self) -> Result<Vec<GossipChannel>, LightningError> {
+        let pubkey = self.info.pubkey.to_string();
+        let mut channels = Vec::new();
+        for filter in [
+            json!({ "source": pubkey }),
+            json!({ "destination": pubkey }),
+        ] {
+            let response: ListchannelsResponse = self
+                .client
+                .call("listchannels", filter)
+                .await
+                .map_err(LightningError::ChannelError)?;
+            channels.extend(response.channels);
+        }
+        Ok(channels)
+    }
+
+    async fn get_htlcs_for_payment(
+        &self,
+        payment_hash: &str,
+    ) -> Result<Vec<PaymentHtlc>, LightningError> {
+        let response: ListsendpaysResponse = self
+            .client
+            .call("listsendpays", json!({ "payment_hash": payment_hash }))
+            .await
+            .map_err(LightningError::PaymentError)?;
+
+        Ok(response
+            .payments
+            .into_iter()
+            .map(|sendpay| PaymentHtlc {
+                routes: vec![],
+                attempt_id: sendpay.id,
+                attempt_time: Some(sendpay.created_at),
+                resolve_time: sendpay.completed_at,
+                failure_reason: sendpay.erroronion.map(|_| "Payment failed".to_string()),
+                failure_code: None,
+            })
+            .collect())
+    }
+
+    async fn process_outgoing_payment(
+        &self,
+        payment: Pay,
+    ) -> Result<PaymentDetails, LightningError> {
+        let amount_sat = payment.amount_msat.unwrap_or(0) / 1000;
+        let routing_fee = (payment.amount_sent_msat.unwrap_or(0) / 1000).checked_sub(amount_sat);
+
+        let destination_pubkey = payment
+            .destination
+            .as_deref()
+            .map(PublicKey::from_str)
+            .transpose()
+            .map_err(|err| LightningError::Parse(format!("Invalid destination pubkey: {err}")))?;
+
+        let amount_usd = self.price_converter.sats_to_usd(amount_sat).await?;
+        let htlcs = self
+            .get_htlcs_for_payment(&payment.payment_hash)
+            .await
+            .unwrap_or_else(|_| vec![]);
+
+        Ok(PaymentDetails {
+            state: cln_pay_state(&payment.status),
+            payment_type: PaymentType::Outgoing,
+            amount_sat,
+            amount_usd,
+            routing_fee,
+            network: Some(self.network.to_string()),
+            description: payment.description,
+            creation_time: (payment.created_at > 0).then_some(payment.created_at),
+            invoice: payment.bolt11,
+            payment_hash: payment.payment_hash,
+            destination_pubkey,
+            completed_at: payment.completed_at,
+            htlcs,
+        })
+    }
+
+    async fn process_incoming_payment(
+        &self,
+        invoice: RestInvoice,
+    ) -> Result<PaymentDetails, LightningError> {
+        let state = cln_invoice_payment_state(&invoice.status).unwrap_or(PaymentState::Inflight);
+
+        let completed_at = match state {
+            PaymentState::Settled | PaymentState::Failed => {
+                invoice.paid_at.filter(|&paid_at| paid_at > 0)
+            }
+            _ => None,
+        };
+
+        let amount_sat = invoice
+            .amount_received_msat
+            .or(invoice.amount_msat)
+            .unwrap_or(0)
+            / 1000;
+        let amount_usd = self.price_converter.sats_to_usd(amount_sat).await?;
+        let htlcs = self
+            .get_htlcs_for_payment(&invoice.payment_hash)
+            .await
+            .unwrap_or_else(|_| vec![]);
+
+        Ok(PaymentDetails {
+            state,
+            payment_type: PaymentType::Incoming,
+            amount_sat,
+            amount_usd,
+            routing_fee: None,
+            network: Some(self.network.to_string()),
+            description: invoice.description,
+            creation_time: (invoice.expires_at > 0).then_some(invoice.expires_at),
+            invoice: invoice.bolt11,
+            payment_hash: invoice.payment_hash,
+            destination_pubkey: Some(self.info.pubkey),
+            completed_at,
+            htlcs,
+        })
+    }
+
+    fn to_custom_invoice(invoice: RestInvoice) -> CustomInvoice {
+        let amount_msat = invoice.amount_msat.unwrap_or(0);
+        let amount_paid_msat = invoice.amount_received_msat;
+
+        CustomInvoice {
+            state: cln_invoice_status(&invoice.status, invoice.expires_at),
+            memo: invoice.description.unwrap_or_default(),
+            payment_hash: invoice.payment_hash,
+            payment_preimage: invoice.payment_preimage.unwrap_or_default(),
+            value: amount_msat / 1000,
+            value_msat: amount_msat,
+            creation_date: None,
+            settle_date: invoice.paid_at.map(|timestamp| timestamp as i64),
+            payment_request: invoice.bolt11.unwrap_or_default(),
+            expiry: Some(invoice.expires_at),
+            amount_paid_msat,
+            payment_mismatch: amount_paid_msat
+                .and_then(|paid| PaymentMismatch::between(amount_msat, paid)),
+            is_keysend: None,
+            keysend_message: None,
+            is_amp: None,
+            payment_addr: None,
+            htlcs: None,
+            features: None,
+        }
+    }
+
+    fn to_node_policy(
+        policy: &ChannelUpdatePolicy,
+        pubkey: PublicKey,
+        disabled: bool,
+        last_update: Option<u64>,
+    ) -> NodePolicy {
+        NodePolicy {
+            pubkey,
+            fee_base_msat: policy.fee_base_msat.unwrap_or(0),
+            fee_rate_milli_msat: policy.fee_proportional_millionths as u64,
+            min_htlc_msat: policy.htlc_minimum_msat.unwrap_or(0),
+            max_htlc_msat: policy.htlc_maximum_msat,
+            time_lock_delta: policy.cltv_expiry_delta as u16,
+            disabled,
+            last_update,
+        }
+    }
+}
+
+#[async_trait]
+impl LightningClient for ClnRestNode {
+    fn get_info(&self) -> &NodeInfo {
+        &self.info
+    }
+
+    async fn get_network(&self) -> Result<Network, LightningError> {
+        let info: GetinfoResponse = self
+            .client
+            .call("getinfo", json!({}))
+            .await
+            .map_err(LightningError::GetInfoError)?;
+
+        Network::from_str(&info.network)
+            .map_err(|err| LightningError::ValidationError(err.to_string()))
+    }
+
+    async fn get_block_height(&self) -> Result<u32, LightningError> {
+        let info: GetinfoResponse = self
+            .client
+            .call("getinfo", json!({}))
+            .await
+            .map_err(LightningError::GetInfoError)?;
+
+        Ok(info.blockheight)
+    }
+
+    async fn get_node_info(&self, node_id: &PublicKey) -> Result<NodeInfo, LightningError> {
+        let mut response: ListnodesResponse = self
+            .client
+            .call("listnodes", json!({ "id": node_id.to_string() }))
+            .await
+            .map_err(LightningError::GetNodeInfoError)?;
+
+        let node = response
+            .nodes
+            .pop()
+            .ok_or_else(|| LightningError::GetNodeInfoError("Node not found".to_string()))?;
+
+        Ok(NodeInfo {
+            pubkey: *node_id,
+            alias: node.alias.unwrap_or_default(),
+            features: node
+                .features
+                .and_then(|features| hex::decode(features).ok())
+                .map_or(NodeFeatures::empty(), NodeFeatures::from_be_bytes),
+        })
+    }
+
+    async fn list_channels(&self) -> Result<Vec<ChannelSummary>, LightningError> {
+        let peer_channels = self.list_peer_channels().await?;
+        let gossip_channels = self.own_gossip_channels().await?;
+
+        let mut routing_info = std::collections::HashMap::new();
+        for channel in gossip_channels {
+            routing_info
+                .entry(channel.short_channel_id)
+                .and_modify(|info: &mut (u64, bool)| {
+                    info.0 = info.0.max(channel.last_update);
+                    info.1 |= channel.public;
+                })
+                .or_insert((channel.last_update, channel.public));
+        }
+
+        let now = chrono::Utc::now().timestamp() as u64;
+
+        Ok(peer_channels
+            .into_iter()
+            .filter_map(|channel| {
+                let scid = channel.short_channel_id.as_ref()?;
+                let chan_id = ShortChannelID(parse_short_channel_id(scid)?);
+
+                let capacity = channel.total_msat.unwrap_or(0) / 1000;
+                let local_balance = channel.to_us_msat.unwrap_or(0) / 1000;
+
+                let (last_update, is_public) =
+                    routing_info.get(scid).copied().unwrap_or((0, false));
+                // Private channels never gossip, so fall back to now like the gRPC client.
+                let last_update = if !is_public && last_update == 0 {
+                    now
+                } else {
+                    last_update
+                };
+
+                Some(ChannelSummary {
+                    chan_id,
+                    remote_pubkey: channel.peer_id,
+                    alias: channel.alias.and_then(|alias| alias.remote),
+                    channel_state: cln_channel_state(&channel.state),
+                    private: !is_public,
+                    remote_balance: capacity.saturating_sub(local_balance),
+                    local_balance,
+                    capacity,
+                    last_update: Some(last_update),
+                    uptime: None,
+                    notes: Vec::new(),
+                })
+            })
+            .collect())
+    }
+
+    async fn get_channel_info(
+        &self,
+        channel_id: &ShortChannelID,
+    ) -> Result<ChannelDetails, LightningError> {
+        let channel = self
+            .list_peer_channels()
+            .await?
+            .into_iter()
+            .find(|channel| {
+                channel
+                    .short_channel_id
+                    .as_deref()
+                    .and_then(parse_short_channel_id)
+                    == Some(channel_id.0)
+            })
+            .ok_or_else(|| {
+                LightningError::ChannelError(format!("Channel {channel_id} not found"))
+            })?;
+
+        let remote_pubkey = PublicKey::from_str(&channel.peer_id).map_err(|err| {
+            LightningError::ChannelError(format!(
+                "Invalid peer pubkey for channel {channel_id}: {err}"
+            ))
+        })?;
+
+        let scid = channel.short_channel_id.clone().unwrap_or_default();
+        let mut local_last_update = None;
+        let mut remote_last_update = None;
+        let mut is_active = false;
+        for gossip in self
+            .own_gossip_channels()
+            .await?
+            .into_iter()
+            .filter(|gossip| gossip.short_channel_id == scid)
+        {
+            if gossip.source == self.info.pubkey.to_string() {
+                local_last_update = Some(gossip.last_update);
+                is_active = gossip.active;
+            } else if gossip.source == channel.peer_id {
+                remote_last_update = Some(gossip.last_update);
+            }
+        }
+
+        let capacity_sat = channel.total_msat.ok_or_else(|| {
+            LightningError::ChannelError(format!("Missing total_msat for channel {channel_id}"))
+        })? / 1000;
+        let local_balance_sat = channel.to_us_msat.ok_or_else(|| {
+            LightningError::ChannelError(format!("Missing to_us_msat for channel {channel_id}"))
+        })? / 1000;
+        let remote_balance_sat = capacity_sat.checked_sub(local_balance_sat).ok_or_else(|| {
+            LightningError::ChannelError(format!(
+                "Invalid balance calculation for channel {channel_id}"
+            ))
+        })?;
+
+        let initiator = match channel.opener.as_deref() {
+            Some("local") => Some(true),
+            Some("remote") => Some(false),
+            _ => None,
+        };
+
+        let (local_policy, remote_policy) = match &channel.updates {
+            Some(updates) => (
+                updates.local.as_ref().map(|policy| {
+                    Self::to_node_policy(policy, self.info.pubkey, !is_active, local_last_update)
+                }),
+                updates.remote.as_ref().map(|policy| {
+                    Self::to_node_policy(policy, remote_pubkey, !is_active, remote_last_update)
+                }),
+            ),
+            None => (None, None),
+        };
+
+        let (node1_policy, node2_policy) = if self.info.pubkey < remote_pubkey {
+            (local_policy, remote_policy)
+        } else {
+            (remote_policy, local_policy)
+        };
+
+        Ok(ChannelDetails {
+            channel_id: *channel_id,
+            local_balance_sat,
+            remote_balance_sat,
+            capacity_sat,
+            active: Some(is_active),
+            private: channel.private.unwrap_or(false),
+            remote_pubkey,
+            commit_fee_sat: channel.last_tx_fee_msat.map(|msat| msat / 1000),
+            local_chan_reserve_sat: channel.our_reserve_msat.map(|msat| msat / 1000),
+            remote_chan_reserve_sat: channel.their_reserve_msat.map(|msat| msat / 1000),
+            num_updates: None,
+            total_satoshis_sent: channel.out_fulfilled_msat.map(|msat| msat / 1000),
+            total_satoshis_received: channel.in_fulfilled_msat.map(|msat| msat / 1000),
+            channel_age_blocks: None,
+            opening_cost_sat: None,
+            funding_fee_sat: None,
+            closing_txid: None,
+            closing_fee_sat: None,
+            rebalance_cost_sat: None,
+            peer_metadata: None,
+            notes: Vec::new(),
+            initiator,
+            txid: channel
+                .funding_txid
+                .as_deref()
+                .and_then(|txid| Txid::from_str(txid).ok()),
+            vout: channel.funding_outnum,
+            node1_policy,
+            node2_policy,
+        })
+    }
+
+    async fn get_payment_details(
+        &self,
+        payment_hash: &PaymentHash,
+    ) -> Result<PaymentDetails, LightningError> {
+        let hex_hash = hex::encode(payment_hash.0);
+
+        let pays: ListpaysResponse = self
+            .client
+            .call("listpays", json!({ "payment_hash": hex_hash }))
+            .await
+            .map_err(LightningError::PaymentError)?;
+        if let Some(payment) = pays.pays.into_iter().last() {
+            return self.process_outgoing_payment(payment).await;
+        }
+
+        let invoices: ListinvoicesResponse = self
+            .client
+            .call("listinvoices", json!({ "payment_hash": hex_hash }))
+            .await
+            .map_err(LightningError::InvoiceError)?;
+        if let Some(invoice) = invoices.invoices.into_iter().next() {
+            return self.process_incoming_payment(invoice).await;
+        }
+
+        Err(LightningError::NotFound(format!(
+            "Payment {hex_hash} not found"
+        )))
+    }
+
+    async fn list_payments(&self) -> Result<Vec<PaymentSummary>, LightningError> {
+        let btc_price = self.price_converter.fetch_btc_price().await?;
+
+        let pays: ListpaysResponse = self
+            .client
+            .call("listpays", json!({}))
+            .await
+            .map_err(LightningError::PaymentError)?;
+        let invoices: ListinvoicesResponse = self
+            .client
+            .call("listinvoices", json!({}))
+            .await
+            .map_err(LightningError::InvoiceError)?;
+
+        let outgoing = pays.pays.into_iter().map(|payment| {
+            let amount_sat = payment.amount_msat.unwrap_or(0) / 1000;
+            let routing_fee = match (payment.amount_sent_msat, payment.amount_msat) {
+                (Some(sent), Some(amount)) => Some(sent.saturating_sub(amount) / 1000),
+                _ => None,
+            };
+
+            PaymentSummary {
+                state: cln_pay_state(&payment.status),
+                payment_type: PaymentType::Outgoing,
+                amount_sat,
+                amount_usd: PriceConverter::sats_to_usd_with_price(amount_sat, btc_price),
+                routing_fee,
+                creation_time: (payment.created_at > 0).then_some(payment.created_at),
+                invoice: payment.bolt11,
+                payment_hash: payment.payment_hash,
+                completed_at: payment.completed_at,
+                channel_id: None,
+                keysend_message: None,
+            }
+        });
+
+        let incoming = invoices
+            .invoices
+            .into_iter()
+            .filter(|invoice| invoice.pay_index.is_some())
+            .filter_map(|invoice| {
+                let state = cln_invoice_payment_state(&invoice.status)?;
+                let amount_sat = invoice
+                    .amount_received_msat
+                    .or(invoice.amount_msat)
+                    .unwrap_or(0)
+                    / 1000;
+                let completed_at = match state {
+                    PaymentState::Settled | PaymentState::Failed => {
+                        invoice.paid_at.filter(|&paid_at| paid_at > 0)
+                    }
+                    _ => None,
+                };
+
+                Some(PaymentSummary {
+                    state,
+                    payment_type: PaymentType::Incoming,
+                    amount_sat,
+                    amount_usd: PriceConverter::sats_to_usd_with_price(amount_sat, btc_price),
+                    routing_fee: None,
+                    creation_time: (invoice.expires_at > 0).then_some(invoice.expires_at),
+                    invoice: invoice.bolt11,
+                    payment_hash: invoice.payment_hash,
+                    completed_at,
+                    channel_id: None,
+                    keysend_message: None,
+                })
+            });
+
+        let mut seen_hashes = HashSet::new();
+        let mut all_payments: Vec<PaymentSummary> = outgoing
+            .chain(incoming)
+            .filter(|payment| seen_hashes.insert(payment.payment_hash.clone()))
+            .collect();
+
+        all_payments.sort_by(|a, b| b.creation_time.cmp(&a.creation_time));
+
+        Ok(all_payments)
+    }
+
+    async fn stream_events(
+        &mut self,
+    ) -> Result<Pin<Box<dyn Stream<Item = LightningEvent> + Send>>, LightningError> {
+        Err(LightningError::StreamingError(
+            "Event streaming is not supported over clnrest".to_string(),
+        ))
+    }
+
+    async fn list_invoices(&self) -> Result<Vec<CustomInvoice>, LightningError> {
+        let response: ListinvoicesResponse = self
+            .client
+            .call("listinvoices", json!({}))
+            .await
+            .map_err(LightningError::InvoiceError)?;
+
+        Ok(response
+            .invoices
+            .into_iter()
+            .map(Self::to_custom_invoice)
+            .collect())
+    }
+
+    async fn get_invoice_details(
+        &self,
+        payment_hash: &PaymentHash,
+    ) -> Result<CustomInvoice, LightningError> {
+        let response: ListinvoicesResponse = self
+            .client
+            .call(
+                "listinvoices",
+                json!({ "payment_hash": hex::encode(payment_hash.0) }),
+            )
+            .await
+            .map_err(|err| {
+                LightningError::InvoiceError(format!("CLN listinvoices error: {err}"))
+            })?;
+
+        response
+            .invoices
+            .into_iter()
+            .next()
+            .map(Self::to_custom_invoice)
+            .ok_or_else(|| LightningError::NotFound("Invoice not found".into()))
+    }
+
+    async fn list_offer_invoices(
+        &self,
+        offer_id: &str,
+    ) -> Result<Vec<OfferInvoice>, LightningError> {
+        let response: ListinvoicesResponse = self
+            .client
+            .call("listinvoices", json!({ "offer_id": offer_id }))
+            .await
+            .map_err(LightningError::InvoiceError)?;
+
+        Ok(response
+            .invoices
+            .into_iter()
+            .map(|invoice| OfferInvoice {
+                state: cln_invoice_status(&invoice.status, invoice.expires_at),
+                amount_received_msat: invoice.amount_received_msat,
+                paid_at: invoice.paid_at,
+                payer_note: invoice.invreq_payer_note,
+            })
+            .collect())
+    }
+
+    async fn sign_message(&self, message: &str) -> Result<String, LightningError> {
+        let response: SignmessageResponse = self
+            .client
+            .call("signmessage", json!({ "message": message }))
+            .await
+            .map_err(LightningError::SigningError)?;
+
+        Ok(response.zbase)
+    }
+
+    async fn verify_message(
+        &self,
+        message: &str,
+        signature: &str,
+    ) -> Result<MessageVerification, LightningError> {
+        let response: CheckmessageResponse = self
+            .client
+            .call(
+                "checkmessage",
+                json!({ "message": message, "zbase": signature }),
+            )
+            .await
+            .map_err(LightningError::SigningError)?;
+
+        Ok(MessageVerification {
+            valid: response.verified,
+            pubkey: response.pubkey,
+        })
+    }
+
+    async fn list_forwards(&self, since: u64) -> Result<Vec<Forward>, LightningError> {
+        let response: ListforwardsResponse = self
+            .client
+            .call("listforwards", json!({ "status": "settled" }))
+            .await
+            .map_err(LightningError::ChannelError)?;
+
+        Ok(response
+            .forwards
+            .into_iter()
+            .filter_map(|forward| {
+                let timestamp = forward.resolved_time.unwrap_or(forward.received_time) as u64;
+                (timestamp >= since).then_some(Forward {
+                    timestamp,
+                    received_at: Some(forward.received_time as u64),
+                    chan_id_in: ShortChannelID(parse_short_channel_id(&forward.in_channel)?),
+                    chan_id_out: ShortChannelID(parse_short_channel_id(
+                        forward.out_channel.as_deref()?,
+                    )?),
+                    incoming_htlc_id: forward.in_htlc_id,
+                    outgoing_htlc_id: forward.out_htlc_id,
+                    amt_in_msat: forward.in_msat.unwrap_or(0),
+                    amt_out_msat: forward.out_msat.unwrap_or(0),
+                    fee_msat: forward.fee_msat.unwrap_or(0),
+                })
+            })
+            .collect())
+    }
+
+    async fn list_payment_latencies(
+        &self,
+        since: u64,
+    ) -> Result<Vec<PaymentLatency>, LightningError> {
+        let pays: ListpaysResponse = self
+            .client
+            .call("listpays", json!({ "status": "complete" }))
+            .await
+            .map_err(LightningError::PaymentError)?;
+
+        Ok(pays
+            .pays
+            .into_iter()
+            .filter_map(|pay| {
+                let completed_at = pay.completed_at.filter(|&at| at >= since)?;
+                Some(PaymentLatency {
+                    payment_hash: pay.payment_hash,
+                    destination: pay.destination,
+                    first_hop: None,
+                    settled_at: completed_at,
+                    settle_ms: completed_at.saturating_sub(pay.created_at) * 1000,
+                    htlc_ms: None,
+                })
+            })
+            .collect())
+    }
+
+    async fn update_channel_fees(
+        &self,
+        channel: &ChannelDetails,
+        base_fee_msat: u64,
+        fee_rate_ppm: u32,
+    ) -> Result<(), LightningError> {
+        self.client
+            .call::<Value>(
+                "setchannel",
+                json!({
+                    "id": format_short_channel_id(channel.channel_id.0),
+                    "feebase": base_fee_msat,
+                    "feeppm": fee_rate_ppm,
+                }),
+            )
+            .await
+            .map_err(LightningError::ChannelError)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Amount {
+        #[serde(default, deserialize_with = "deserialize_msat")]
+        amount_msat: Option<u64>,
+    }
+
+    #[test]
+    fn test_msat_accepts_numbers_and_legacy_strings() {
+        let number: Amount = serde_json::from_str(r#"{"amount_msat": 1500}"#).unwrap();
+        let legacy: Amount = serde_json::from_str(r#"{"amount_msat": "1500msat"}"#).unwrap();
+        let missing: Amount = serde_json::from_str("{}").unwrap();
+
+        assert_eq!(number.amount_msat, Some(1500));
+        assert_eq!(legacy.amount_msat, Some(1500));
+        assert_eq!(missing.amount_msat, None);
+    }
+
+    #[test]
+    fn test_parse_short_channel_id() {
+        assert_eq!(
+            parse_short_channel_id("103x1x0"),
+            Some((103u64 << 40) | (1 << 16))
+        );
+        assert_eq!(parse_short_channel_id("12345"), Some(12345));
+        assert_eq!(parse_short_channel_id("1x2"), None);
+        assert_eq!(
+            format_short_channel_id((103u64 << 40) | (1 << 16)),
+            "103x1x0"
+        );
+    }
+}
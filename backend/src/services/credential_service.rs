@@ -2,14 +2,51 @@
 //!
 //! Handles all credential-related business operations
 
-use crate::database::models::{CreateCredential, Credential};
+use crate::database::models::{CreateCredential, Credential, CredentialSummary};
 use crate::errors::{ServiceError, ServiceResult};
 use crate::repositories::account_repository::AccountRepository;
+use crate::repositories::audit_log_repository::AuditLogRepository;
 use crate::repositories::credential_repository::CredentialRepository;
 use crate::repositories::user_repository::UserRepository;
+use bitcoin::hashes::{Hash, sha256};
+use serde_json::json;
 use sqlx::SqlitePool;
 use validator::Validate;
 
+/// Short SHA-256 fingerprint of a secret, enough to tell two values apart
+/// without revealing either. `None` for an unset secret.
+pub fn fingerprint(secret: &str) -> Option<String> {
+    if secret.is_empty() {
+        return None;
+    }
+    let digest = sha256::Hash::hash(secret.as_bytes()).to_string();
+    Some(digest[..16].to_string())
+}
+
+impl From<&Credential> for CredentialSummary {
+    fn from(credential: &Credential) -> Self {
+        Self {
+            id: credential.id.clone(),
+            user_id: credential.user_id.clone(),
+            node_id: credential.node_id.clone(),
+            node_alias: credential.node_alias.clone(),
+            node_type: credential
+                .node_type
+                .clone()
+                .unwrap_or_else(|| "lnd".to_string()),
+            address: credential.address.clone(),
+            macaroon_fingerprint: fingerprint(&credential.macaroon),
+            tls_cert_fingerprint: fingerprint(&credential.tls_cert),
+            client_cert_fingerprint: credential.client_cert.as_deref().and_then(fingerprint),
+            client_key_fingerprint: credential.client_key.as_deref().and_then(fingerprint),
+            ca_cert_fingerprint: credential.ca_cert.as_deref().and_then(fingerprint),
+            is_active: credential.is_active,
+            created_at: credential.created_at,
+            updated_at: credential.updated_at,
+        }
+    }
+}
+
 pub struct CredentialService<'a> {
     /// Shared database connection pool
     pool: &'a SqlitePool,
@@ -126,4 +163,65 @@ impl<'a> CredentialService<'a> {
         let credential = repo.get_credential_by_user_id(user_id).await?;
         Ok(credential)
     }
+
+    /// Lists the account's stored credentials without their secrets.
+    pub async fn list_credentials(
+        &self,
+        account_id: &str,
+    ) -> ServiceResult<Vec<CredentialSummary>> {
+        let credentials = CredentialRepository::new(self.pool)
+            .get_credentials_by_account_id(account_id)
+            .await?;
+        Ok(credentials.iter().map(CredentialSummary::from).collect())
+    }
+
+    /// Deletes one of the account's stored credentials. Tokens only name the
+    /// credential and node requests load it by id, so those bound to it stop
+    /// reaching the node at once.
+    pub async fn delete_credential(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        id: &str,
+    ) -> ServiceResult<()> {
+        let repo = CredentialRepository::new(self.pool);
+        let credential = repo
+            .get_credential_by_id(id)
+            .await?
+            .filter(|credential| credential.account_id == account_id)
+            .ok_or_else(|| ServiceError::not_found("Credential", id))?;
+        repo.delete_credential(id).await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "credential_deleted",
+                "credential",
+                Some(id),
+                &json!({ "node_id": credential.node_id, "owner_id": credential.user_id }),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_hides_the_secret() {
+        let macaroon = "0201036c6e6402f801030a10";
+        let print = fingerprint(macaroon).unwrap();
+
+        assert_eq!(print.len(), 16);
+        assert_eq!(fingerprint(macaroon), Some(print));
+        assert_ne!(
+            fingerprint("0201036c6e6402f801030a11"),
+            fingerprint(macaroon)
+        );
+        assert_eq!(fingerprint(""), None);
+    }
 }
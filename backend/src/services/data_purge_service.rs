@@ -0,0 +1,138 @@
+//! User data purge (right to erasure).
+//!
+//! Purging is a two-step operation: the first request returns a short-lived
+//! confirmation token, and repeating the request with that token carries out
+//! the purge. Both steps are written to the audit trail.
+
+use crate::database::models::DataPurgeResponse;
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::data_purge_repository::DataPurgeRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::utils::generate_random_string::generate_random_string;
+use crate::utils::jwt::Claims;
+use crate::utils::password::hash_password;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// How long a purge confirmation token stays valid
+const CONFIRMATION_TTL_MINUTES: i64 = 15;
+
+/// Service layer for purging a user's personal data.
+pub struct DataPurgeService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> DataPurgeService<'a> {
+    /// Creates a new DataPurgeService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Starts or confirms a purge of `user_id`'s personal data.
+    ///
+    /// Users may purge their own data; account admins may purge any user in
+    /// their account except the account admin.
+    pub async fn purge_user_data(
+        &self,
+        claims: &Claims,
+        user_id: &str,
+        confirmation_token: Option<&str>,
+    ) -> ServiceResult<DataPurgeResponse> {
+        let user_repo = UserRepository::new(self.pool);
+        let user = user_repo
+            .get_user_by_id(user_id)
+            .await?
+            .filter(|u| u.account_id == claims.account_id())
+            .ok_or_else(|| ServiceError::not_found("User", user_id))?;
+
+        if claims.user_id() != user.id && !claims.is_admin() {
+            return Err(ServiceError::permission_denied(
+                "Only admins can purge another user's data",
+            ));
+        }
+
+        let is_account_admin = user_repo
+            .get_admin_user_by_account_id(&user.account_id)
+            .await?
+            .is_some_and(|admin| admin.id == user.id);
+        if is_account_admin {
+            return Err(ServiceError::invalid_operation(
+                "The account admin's data cannot be purged while the account exists",
+            ));
+        }
+
+        let audit = AuditLogRepository::new(self.pool);
+        let purge_repo = DataPurgeRepository::new(self.pool);
+
+        let Some(token) = confirmation_token else {
+            let request = purge_repo
+                .create_request(
+                    &Uuid::now_v7().to_string(),
+                    &user.account_id,
+                    &user.id,
+                    claims.user_id(),
+                    &generate_random_string(32),
+                    Utc::now() + Duration::minutes(CONFIRMATION_TTL_MINUTES),
+                )
+                .await?;
+
+            audit
+                .create_log(
+                    &user.account_id,
+                    Some(claims.user_id()),
+                    "user_data_purge_requested",
+                    "user",
+                    Some(&user.id),
+                    &json!({ "request_id": request.id }),
+                )
+                .await?;
+
+            return Ok(DataPurgeResponse {
+                user_id: user.id,
+                status: "pending_confirmation".to_string(),
+                confirmation_token: Some(request.confirmation_token),
+                expires_at: Some(request.expires_at),
+            });
+        };
+
+        let request = purge_repo
+            .get_pending_request(&user.id, token)
+            .await?
+            .filter(|r| r.expires_at > Utc::now())
+            .ok_or_else(|| {
+                ServiceError::validation("Confirmation token is invalid or has expired")
+            })?;
+
+        // Nobody knows this password, so the row can never be logged into again.
+        let placeholder_hash = hash_password(&generate_random_string(32)).await?;
+        purge_repo
+            .purge_user_data(&user.id, &placeholder_hash)
+            .await?;
+
+        // Recorded after the purge so this entry keeps the acting user, unless
+        // they purged themselves.
+        let actor = (claims.user_id() != user.id).then(|| claims.user_id());
+        audit
+            .create_log(
+                &user.account_id,
+                actor,
+                "user_data_purged",
+                "user",
+                Some(&user.id),
+                &json!({ "request_id": request.id }),
+            )
+            .await?;
+
+        tracing::info!("Purged personal data of user {}", user.id);
+
+        Ok(DataPurgeResponse {
+            user_id: user.id,
+            status: "purged".to_string(),
+            confirmation_token: None,
+            expires_at: None,
+        })
+    }
+}
@@ -0,0 +1,259 @@
+//! Demo mode: a ready-made account for exploring the dashboard.
+//!
+//! With `DEMO_MODE=true` the server provisions a "NodeGaze Demo" account on
+//! first start, backed by the mock node (so the binary must be built with the
+//! `mock-node` feature), seeds a month of balance snapshots and events, and
+//! streams live synthetic events into it. Visitors sign in through
+//! `POST /api/v1/auth/demo` as a read-only member of that account.
+
+use crate::database::models::{
+    BalanceSnapshot, CreateCredential, CreateEvent, CreateNewAccount, CreateUser, EventSeverity,
+    EventType, RoleAccessLevel, User,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::account_repository::AccountRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::role_repository::RoleRepository;
+use crate::repositories::scheduled_task_repository::ScheduledTaskRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::account_service::AccountService;
+use crate::services::event_manager::start_event_stream;
+use crate::utils::jwt::NodeCredentials;
+use crate::utils::password::hash_password;
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use serde_json::json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+const DEMO_ACCOUNT_NAME: &str = "NodeGaze Demo";
+
+/// Username visitors are signed in as
+pub const DEMO_USERNAME: &str = "demo";
+
+/// Public key the demo node reports; any valid key works with the mock node
+const DEMO_NODE_ID: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+/// Alias the mock node derives from `DEMO_NODE_ID`
+const DEMO_NODE_ALIAS: &str = "mock-0279be66";
+
+/// Live events arrive every 30 seconds
+const DEMO_NODE_ADDRESS: &str = "mock://demo?interval=30";
+
+/// Days of history seeded into a new demo account
+const HISTORY_DAYS: i64 = 30;
+
+/// Provisions the demo account if needed and starts its live event stream.
+pub async fn start_demo(pool: SqlitePool) {
+    if !cfg!(feature = "mock-node") {
+        tracing::error!("DEMO_MODE needs a build with `--features mock-node`; demo disabled");
+        return;
+    }
+
+    let visitor = match provision_demo_account(&pool).await {
+        Ok(visitor) => visitor,
+        Err(e) => {
+            tracing::error!("Failed to provision demo account: {}", e);
+            return;
+        }
+    };
+
+    let credential = match CredentialRepository::new(&pool)
+        .get_credential_by_user_id(&visitor.id)
+        .await
+    {
+        Ok(Some(credential)) => credential,
+        Ok(None) => {
+            tracing::error!("Demo user {} has no node credentials", visitor.id);
+            return;
+        }
+        Err(e) => {
+            tracing::error!("Failed to load demo node credentials: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = start_event_stream(
+        pool,
+        visitor.account_id.clone(),
+        visitor.id.clone(),
+        &NodeCredentials::from(credential),
+    )
+    .await
+    {
+        tracing::error!("Failed to start demo event stream: {}", e);
+        return;
+    }
+
+    tracing::info!("Demo mode enabled for account {}", visitor.account_id);
+}
+
+/// Returns the demo visitor, creating the account, node and history first
+/// if this is the first start in demo mode.
+async fn provision_demo_account(pool: &SqlitePool) -> ServiceResult<User> {
+    let user_repo = UserRepository::new(pool);
+    if let Some(visitor) = user_repo.get_user_by_username(DEMO_USERNAME).await? {
+        return Ok(visitor);
+    }
+    if AccountRepository::new(pool)
+        .account_name_exists(DEMO_ACCOUNT_NAME)
+        .await?
+    {
+        return Err(ServiceError::invalid_operation(format!(
+            "Account '{DEMO_ACCOUNT_NAME}' exists but has no '{DEMO_USERNAME}' user"
+        )));
+    }
+
+    // The owner's password is never handed out; visitors only get the member.
+    let owner = AccountService::new(pool)
+        .create_account(CreateNewAccount {
+            name: DEMO_ACCOUNT_NAME.to_string(),
+            username: "demo-owner".to_string(),
+            email: "owner@demo.nodegaze.local".to_string(),
+            password: random_password(),
+        })
+        .await?;
+    let account_id = owner.account.id;
+
+    let role = RoleRepository::new(pool)
+        .get_role_by_name("Member")
+        .await?
+        .ok_or_else(|| ServiceError::not_found("Role", "Member"))?;
+    let password_hash = hash_password(&random_password()).await?;
+    let visitor = user_repo
+        .create_user(CreateUser {
+            id: Uuid::now_v7().to_string(),
+            account_id: account_id.clone(),
+            role_id: role.id,
+            username: DEMO_USERNAME.to_string(),
+            email: "visitor@demo.nodegaze.local".to_string(),
+            password_hash,
+            role_access_level: RoleAccessLevel::Read,
+        })
+        .await?;
+
+    CredentialRepository::new(pool)
+        .create_credential(CreateCredential {
+            id: Uuid::now_v7().to_string(),
+            user_id: visitor.id.clone(),
+            account_id: account_id.clone(),
+            node_id: DEMO_NODE_ID.to_string(),
+            node_alias: DEMO_NODE_ALIAS.to_string(),
+            macaroon: "demo".to_string(),
+            tls_cert: "demo".to_string(),
+            address: DEMO_NODE_ADDRESS.to_string(),
+            node_type: Some("lnd".to_string()),
+            client_cert: None,
+            client_key: None,
+            ca_cert: None,
+        })
+        .await?;
+
+    seed_history(pool, &account_id, &visitor.id).await?;
+
+    tracing::info!("Provisioned demo account {}", account_id);
+    Ok(visitor)
+}
+
+/// Nobody signs in with a password on the demo account; this just keeps the
+/// generated users from having a guessable one.
+fn random_password() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Backfills daily balance snapshots and a few events per day so the charts
+/// and event log have something to show from the first visit.
+async fn seed_history(pool: &SqlitePool, account_id: &str, user_id: &str) -> ServiceResult<()> {
+    let now = Utc::now();
+    let task_repo = ScheduledTaskRepository::new(pool);
+    let mut events = Vec::new();
+
+    for day in (1..=HISTORY_DAYS).rev() {
+        let captured_at = now - Duration::days(day);
+        // Local balance drifts between roughly 40% and 60% of capacity.
+        let capacity_sat = 8_000_000;
+        let local_balance_sat = 3_200_000 + (day * 7_919 % 30) * 53_000;
+        task_repo
+            .create_balance_snapshot(&BalanceSnapshot {
+                id: Uuid::now_v7().to_string(),
+                account_id: account_id.to_string(),
+                node_id: DEMO_NODE_ID.to_string(),
+                local_balance_sat,
+                remote_balance_sat: capacity_sat - local_balance_sat,
+                capacity_sat,
+                num_channels: 3,
+                num_active_channels: 2,
+                btc_price_usd: Some(60_000.0 + (day * 131 % 40) as f64 * 250.0),
+                captured_at,
+                created_at: now,
+            })
+            .await?;
+
+        let amount_sat = 1_000 + day * 2_357 % 90_000;
+        events.push(demo_event(
+            account_id,
+            user_id,
+            EventType::InvoiceSettled,
+            EventSeverity::Info,
+            "Invoice Settled",
+            format!("Received {amount_sat} sats"),
+            json!({ "amount_sat": amount_sat }),
+            captured_at + Duration::hours(9),
+        ));
+        events.push(demo_event(
+            account_id,
+            user_id,
+            EventType::PaymentSent,
+            EventSeverity::Info,
+            "Payment Sent",
+            format!("Sent {} sats", amount_sat / 3),
+            json!({ "amount_sat": amount_sat / 3 }),
+            captured_at + Duration::hours(14),
+        ));
+        if day % 4 == 0 {
+            events.push(demo_event(
+                account_id,
+                user_id,
+                EventType::ForwardFailed,
+                EventSeverity::Warning,
+                "Forward Failed",
+                "A forward failed for lack of outbound liquidity".to_string(),
+                json!({ "failure_reason": "insufficient_balance" }),
+                captured_at + Duration::hours(18),
+            ));
+        }
+    }
+
+    EventRepository::new(pool).create_events(events).await?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn demo_event(
+    account_id: &str,
+    user_id: &str,
+    event_type: EventType,
+    severity: EventSeverity,
+    title: &str,
+    description: String,
+    data: serde_json::Value,
+    timestamp: chrono::DateTime<Utc>,
+) -> CreateEvent {
+    CreateEvent {
+        id: Uuid::now_v7().to_string(),
+        account_id: account_id.to_string(),
+        user_id: user_id.to_string(),
+        node_id: DEMO_NODE_ID.to_string(),
+        node_alias: DEMO_NODE_ALIAS.to_string(),
+        event_type,
+        severity,
+        title: title.to_string(),
+        description,
+        data: data.to_string(),
+        notifications_id: None,
+        timestamp,
+    }
+}
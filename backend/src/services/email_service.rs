@@ -59,6 +59,36 @@ impl EmailService {
             .await
     }
 
+    /// Sends a link that confirms a change of email address
+    pub async fn send_email_verification(
+        &self,
+        recipient_email: &str,
+        recipient_name: &str,
+        token: &str,
+    ) -> ServiceResult<()> {
+        let verify_url = format!("{}/verify-email?token={}", self.config.base_url, token);
+
+        let html_content = format!(
+            r#"
+            <p>Hi {recipient_name},</p>
+            <p>Confirm that you want to use this address for your NodeGaze account:</p>
+            <p><a href="{verify_url}">Verify email address</a></p>
+            <p>This link expires in 24 hours. If you didn't request this, you can ignore this email.</p>
+            "#
+        );
+        let text_content = format!(
+            "Hi {recipient_name},\n\nConfirm that you want to use this address for your NodeGaze account:\n{verify_url}\n\nThis link expires in 24 hours. If you didn't request this, you can ignore this email.\n"
+        );
+
+        self.send_email(
+            recipient_email,
+            "Verify your email address",
+            &html_content,
+            &text_content,
+        )
+        .await
+    }
+
     /// Sends a generic email
     pub async fn send_email(
         &self,
@@ -0,0 +1,510 @@
+//! Escalation of unacknowledged critical events.
+//!
+//! A policy is a chain of notification endpoints, e.g. Slack, then
+//! PagerDuty, then an SMS gateway's webhook. Every critical event of a node
+//! the policy covers starts an escalation: each time a step's delay passes
+//! without an acknowledgement, the event is sent to that step's endpoint,
+//! until someone acknowledges it or the chain runs out. Steps run as
+//! `AlertEscalation` jobs, so pending escalations survive restarts and a
+//! failed delivery is retried before the chain moves on.
+
+use crate::database::models::{
+    Escalation, EscalationPolicy, EscalationPolicyRequest, EscalationPolicyResponse,
+    EscalationQuery, EscalationStatus, EscalationStep, Event, EventSeverity, JobType,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::escalation_repository::EscalationRepository;
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::notification_repository::NotificationRepository;
+use crate::services::job_queue::JobQueue;
+use crate::services::notification_dispatcher::NotificationDispatcher;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Longest wait before a step, in minutes
+const MAX_STEP_DELAY_MINUTES: i64 = 24 * 60;
+
+const DEFAULT_ESCALATION_LIMIT: i64 = 50;
+
+const MAX_ESCALATION_LIMIT: i64 = 500;
+
+/// Payload of an `AlertEscalation` job, which notifies an escalation's
+/// next step.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EscalationJob {
+    pub escalation_id: String,
+    pub account_id: String,
+}
+
+/// When the step after `steps_sent` steps is due, or `None` once the chain
+/// has run out.
+pub fn next_step_at(
+    steps: &[EscalationStep],
+    steps_sent: usize,
+    from: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    steps
+        .get(steps_sent)
+        .map(|step| from + Duration::minutes(step.delay_minutes))
+}
+
+/// Whether a policy escalates an event.
+pub fn covers(policy: &EscalationPolicy, event: &Event) -> bool {
+    policy.is_active
+        && policy.account_id == event.account_id
+        && event.severity == EventSeverity::Critical
+        && policy
+            .node_id
+            .as_deref()
+            .is_none_or(|node_id| node_id == event.node_id)
+}
+
+/// Service layer for escalation policies and escalations.
+pub struct EscalationService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> EscalationService<'a> {
+    /// Creates a new EscalationService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the account's policies.
+    pub async fn list_policies(
+        &self,
+        account_id: &str,
+    ) -> ServiceResult<Vec<EscalationPolicyResponse>> {
+        let policies = EscalationRepository::new(self.pool)
+            .get_policies(account_id)
+            .await?;
+        Ok(policies
+            .into_iter()
+            .map(EscalationPolicyResponse::from)
+            .collect())
+    }
+
+    /// Creates a policy.
+    pub async fn create_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        request: EscalationPolicyRequest,
+    ) -> ServiceResult<EscalationPolicyResponse> {
+        let id = Uuid::now_v7().to_string();
+        let policy = self
+            .validate_policy(account_id, &id, user_id, request)
+            .await?;
+        let policy = EscalationRepository::new(self.pool)
+            .create_policy(&policy)
+            .await?;
+        Ok(EscalationPolicyResponse::from(policy))
+    }
+
+    /// Replaces a policy's settings. Escalations already under way follow
+    /// the new steps from the step they are at.
+    pub async fn update_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        id: &str,
+        request: EscalationPolicyRequest,
+    ) -> ServiceResult<EscalationPolicyResponse> {
+        let policy = self
+            .validate_policy(account_id, id, user_id, request)
+            .await?;
+        EscalationRepository::new(self.pool)
+            .update_policy(&policy)
+            .await?
+            .map(EscalationPolicyResponse::from)
+            .ok_or_else(|| ServiceError::not_found("Escalation policy", id))
+    }
+
+    /// Deletes a policy, cancelling its escalations.
+    pub async fn delete_policy(&self, account_id: &str, id: &str) -> ServiceResult<()> {
+        if !EscalationRepository::new(self.pool)
+            .delete_policy(id, account_id)
+            .await?
+        {
+            return Err(ServiceError::not_found("Escalation policy", id));
+        }
+        Ok(())
+    }
+
+    /// Checks a policy request and builds the policy it describes.
+    async fn validate_policy(
+        &self,
+        account_id: &str,
+        id: &str,
+        user_id: &str,
+        request: EscalationPolicyRequest,
+    ) -> ServiceResult<EscalationPolicy> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        let name = request.name.trim().to_string();
+        if name.is_empty() {
+            return Err(ServiceError::validation("Name is required"));
+        }
+
+        let node_id = request
+            .node_id
+            .map(|node_id| node_id.trim().to_string())
+            .filter(|node_id| !node_id.is_empty());
+        if let Some(node_id) = &node_id {
+            CredentialRepository::new(self.pool)
+                .get_credential_by_account_and_node_id(account_id, node_id)
+                .await?
+                .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+        }
+
+        let notification_repo = NotificationRepository::new(self.pool);
+        for step in &request.steps {
+            if !(1..=MAX_STEP_DELAY_MINUTES).contains(&step.delay_minutes) {
+                return Err(ServiceError::validation(format!(
+                    "Step delays must be between 1 and {MAX_STEP_DELAY_MINUTES} minutes"
+                )));
+            }
+            notification_repo
+                .get_notification_by_id(&step.notification_id)
+                .await?
+                .filter(|notification| notification.account_id == account_id)
+                .ok_or_else(|| ServiceError::not_found("Notification", &step.notification_id))?;
+        }
+
+        let repo = EscalationRepository::new(self.pool);
+        if repo.name_exists(account_id, &name, id).await? {
+            return Err(ServiceError::already_exists(
+                "Escalation policy with name",
+                &name,
+            ));
+        }
+
+        let now = Utc::now();
+        Ok(EscalationPolicy {
+            id: id.to_string(),
+            account_id: account_id.to_string(),
+            name,
+            node_id,
+            steps: serde_json::to_string(&request.steps)
+                .map_err(|e| ServiceError::internal_error(e.to_string()))?,
+            is_active: request.is_active,
+            created_by: user_id.to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Lists the account's escalations, newest first.
+    pub async fn get_escalations(
+        &self,
+        account_id: &str,
+        query: EscalationQuery,
+    ) -> ServiceResult<Vec<Escalation>> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_ESCALATION_LIMIT)
+            .clamp(1, MAX_ESCALATION_LIMIT);
+        let escalations = EscalationRepository::new(self.pool)
+            .get_escalations(
+                account_id,
+                query.status,
+                limit,
+                query.offset.unwrap_or(0).max(0),
+            )
+            .await?;
+        Ok(escalations)
+    }
+
+    /// Acknowledges a pending escalation, so none of its remaining steps
+    /// are notified.
+    pub async fn acknowledge(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        id: &str,
+    ) -> ServiceResult<Escalation> {
+        let repo = EscalationRepository::new(self.pool);
+        let acknowledged = repo
+            .acknowledge_escalation(id, account_id, user_id, Utc::now())
+            .await?;
+        let escalation = repo
+            .get_escalation(id, account_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Escalation", id))?;
+        if !acknowledged {
+            return Err(ServiceError::invalid_operation(format!(
+                "Escalation is no longer pending ({:?})",
+                escalation.status
+            )));
+        }
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "escalation_acknowledged",
+                "event",
+                Some(&escalation.event_id),
+                &json!({
+                    "escalation_id": escalation.id,
+                    "policy_id": escalation.policy_id,
+                    "steps_sent": escalation.steps_sent,
+                }),
+            )
+            .await?;
+
+        Ok(escalation)
+    }
+}
+
+/// Starts an escalation for each critical event and each policy covering it.
+///
+/// Events are stored once per endpoint they go to, so rows with the same
+/// node, type, time and content are escalated once, as the one event they
+/// are.
+pub async fn start_escalations(pool: &SqlitePool, events: &[Event]) {
+    let repo = EscalationRepository::new(pool);
+    let mut seen = HashSet::new();
+    let mut policies: Option<(String, Vec<EscalationPolicy>)> = None;
+
+    for event in events {
+        if event.severity != EventSeverity::Critical
+            || !seen.insert((
+                &event.account_id,
+                &event.node_id,
+                event.event_type.to_string(),
+                event.timestamp,
+                &event.title,
+                &event.data,
+            ))
+        {
+            continue;
+        }
+
+        if policies
+            .as_ref()
+            .is_none_or(|(account_id, _)| account_id != &event.account_id)
+        {
+            match repo.get_policies(&event.account_id).await {
+                Ok(account_policies) => {
+                    policies = Some((event.account_id.clone(), account_policies))
+                }
+                Err(e) => {
+                    tracing::error!("Failed to load escalation policies: {}", e);
+                    continue;
+                }
+            }
+        }
+        let Some((_, account_policies)) = &policies else {
+            continue;
+        };
+
+        for policy in account_policies
+            .iter()
+            .filter(|policy| covers(policy, event))
+        {
+            if let Err(e) = start_escalation(pool, policy, event).await {
+                tracing::error!(
+                    "Failed to start escalation of event {} under policy {}: {}",
+                    event.id,
+                    policy.id,
+                    e
+                );
+            }
+        }
+    }
+}
+
+async fn start_escalation(
+    pool: &SqlitePool,
+    policy: &EscalationPolicy,
+    event: &Event,
+) -> ServiceResult<()> {
+    let Some(run_at) = next_step_at(&policy.steps(), 0, Utc::now()) else {
+        return Ok(());
+    };
+
+    let escalation = Escalation {
+        id: Uuid::now_v7().to_string(),
+        account_id: event.account_id.clone(),
+        policy_id: policy.id.clone(),
+        event_id: event.id.clone(),
+        node_id: event.node_id.clone(),
+        status: EscalationStatus::Pending,
+        steps_sent: 0,
+        next_step_at: Some(run_at),
+        acknowledged_by: None,
+        acknowledged_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    EscalationRepository::new(pool)
+        .create_escalation(&escalation)
+        .await?;
+    queue_step(pool, &escalation, run_at).await
+}
+
+async fn queue_step(
+    pool: &SqlitePool,
+    escalation: &Escalation,
+    run_at: DateTime<Utc>,
+) -> ServiceResult<()> {
+    JobQueue::new(pool)
+        .enqueue(
+            JobType::AlertEscalation,
+            &EscalationJob {
+                escalation_id: escalation.id.clone(),
+                account_id: escalation.account_id.clone(),
+            },
+            Some(&escalation.account_id),
+            Some(run_at),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Runs an escalation job: notifies the escalation's next step unless it
+/// has been acknowledged, and queues the step after.
+pub async fn run_escalation_job(pool: &SqlitePool, payload: &str) -> Result<(), String> {
+    let job: EscalationJob = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+    let repo = EscalationRepository::new(pool);
+
+    let Some(escalation) = repo
+        .get_escalation(&job.escalation_id, &job.account_id)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(());
+    };
+    if escalation.status != EscalationStatus::Pending {
+        return Ok(());
+    }
+
+    let policy = repo
+        .get_policy(&escalation.policy_id, &escalation.account_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .filter(|policy| policy.is_active);
+    let event = EventRepository::new(pool)
+        .get_event_by_id(&escalation.event_id, &escalation.account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (Some(policy), Some(event)) = (policy, event) else {
+        tracing::info!(
+            "Cancelling escalation {}: its policy is disabled or its event is gone",
+            escalation.id
+        );
+        return repo
+            .cancel_escalation(&escalation.id)
+            .await
+            .map_err(|e| e.to_string());
+    };
+
+    let steps = policy.steps();
+    let sent = escalation.steps_sent as usize;
+    // The policy may have lost steps since the escalation started.
+    if let Some(step) = steps.get(sent) {
+        let notification = NotificationRepository::new(pool)
+            .get_notification_by_id(&step.notification_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .filter(|n| n.account_id == escalation.account_id && n.is_active);
+        match notification {
+            Some(notification) => {
+                tracing::info!(
+                    "Escalating event {} to endpoint {} (step {} of {})",
+                    event.id,
+                    notification.id,
+                    sent + 1,
+                    steps.len()
+                );
+                NotificationDispatcher::new()
+                    .send_to_endpoint(&event, notification)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            // A missing endpoint shouldn't hold up the rest of the chain.
+            None => tracing::warn!(
+                "Skipping step {} of escalation {}: endpoint {} is disabled or deleted",
+                sent + 1,
+                escalation.id,
+                step.notification_id
+            ),
+        }
+    }
+
+    let next_at = next_step_at(&steps, sent + 1, Utc::now());
+    let recorded = repo
+        .record_step(&escalation.id, (sent + 1) as i64, next_at)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let (true, Some(run_at)) = (recorded, next_at) {
+        queue_step(pool, &escalation, run_at)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::EventType;
+    use crate::services::event_service::EventService;
+
+    fn step(delay_minutes: i64) -> EscalationStep {
+        EscalationStep {
+            notification_id: "endpoint".to_string(),
+            delay_minutes,
+        }
+    }
+
+    #[test]
+    fn test_next_step_at() {
+        let steps = vec![step(5), step(15)];
+        let now = Utc::now();
+        assert_eq!(
+            next_step_at(&steps, 0, now),
+            Some(now + Duration::minutes(5))
+        );
+        assert_eq!(
+            next_step_at(&steps, 1, now),
+            Some(now + Duration::minutes(15))
+        );
+        assert_eq!(next_step_at(&steps, 2, now), None);
+    }
+
+    #[test]
+    fn test_covers_only_critical_events_of_the_policy_node() {
+        let mut event = EventService::sample_event(EventType::ChannelClosed, "account", "user");
+        event.severity = EventSeverity::Critical;
+        let now = Utc::now();
+        let mut policy = EscalationPolicy {
+            id: "policy".to_string(),
+            account_id: "account".to_string(),
+            name: "on-call".to_string(),
+            node_id: None,
+            steps: serde_json::to_string(&vec![step(5)]).unwrap(),
+            is_active: true,
+            created_by: "user".to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(covers(&policy, &event));
+
+        policy.node_id = Some("another-node".to_string());
+        assert!(!covers(&policy, &event));
+
+        policy.node_id = Some(event.node_id.clone());
+        event.severity = EventSeverity::Warning;
+        assert!(!covers(&policy, &event));
+    }
+}
@@ -1,20 +1,56 @@
 //! Manages Events occuring on a lightning node.
 //!
 //! This module collects, aggregates and dispatches events occuring on a lightning node
-//! in order to provide timely notifications for critical events.
+//! in order to provide timely notifications for critical events. Events are
+//! buffered briefly and written in batches so busy nodes don't fight over the
+//! SQLite write lock.
+//!
+//! Each node's stream feeds its handler through a bounded buffer. When the
+//! handler falls behind, the configured overflow policy either blocks the
+//! stream or drops the oldest buffered events, counting them and raising a
+//! `SubscriptionDegraded` event.
+//!
+//! An account can pause individual event types of a node; the writer drops
+//! events of a paused type instead of storing them. Settled forwards come
+//! through the same stream but go to the forwards table rather than events.
+//!
+//! Balance changes are reported per settled HTLC. The collector emits the
+//! first one of a channel right away and folds the ones after it into a
+//! single event per `BALANCE_CHANGE_INTERVAL`.
 
-use crate::services::node_manager::LightningClient;
+use crate::config::{Config, EventOverflowPolicy};
+use crate::database::models::{BalanceChangeCause, CreateEvent, EventSeverity, EventType};
+use crate::repositories::event_subscription_repository::EventSubscriptionRepository;
+use crate::repositories::forward_repository::ForwardRepository;
+use crate::services::event_service::EventService;
+use crate::services::lightning_billing::record_settled_invoices;
+use crate::services::node_manager::{
+    ClnConnection, ClnNode, LightningClient, LndConnection, connect_lnd,
+};
+use crate::services::subscription_health::{self, SubscriptionKind};
+use crate::utils::handlers_common::extract_cln_tls_components;
+use crate::utils::jwt::NodeCredentials;
+use crate::utils::{Forward, NodeId, PaymentMismatch};
 use bitcoin::secp256k1::PublicKey;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use tokio;
-use tokio::sync::{Mutex, mpsc};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{Mutex, broadcast, mpsc};
+use tokio::time::Instant;
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
+use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum LNDEvent {
+/// A node event in the shape shared by every node implementation. Each client
+/// normalizes what its node reports into one of these before it is streamed.
+#[derive(Debug, Clone)]
+pub enum LightningEvent {
     ChannelOpened {
         active: bool,
         remote_pubkey: String,
@@ -40,61 +76,288 @@ pub enum LNDEvent {
         open_initiator: i32,
         close_initiator: i32,
     },
-    InvoiceCreated {
-        preimage: Vec<u8>,
-        hash: Vec<u8>,
-        value_msat: i64,
-        state: i32,
-        memo: String,
-        creation_date: i64,
-        payment_request: String,
+    InvoiceCreated(InvoiceEvent),
+    InvoiceSettled(InvoiceEvent),
+    InvoiceCancelled(InvoiceEvent),
+    InvoiceAccepted(InvoiceEvent),
+    /// An invoice paid for more or less than its value.
+    InvoicePaymentMismatch {
+        hash: String,
+        value_msat: u64,
+        amount_paid_msat: u64,
+        mismatch: PaymentMismatch,
+    },
+    /// A payment pushed to us without an invoice.
+    KeysendReceived {
+        payment_hash: String,
+        amount_msat: u64,
+        message: Option<String>,
     },
-    InvoiceSettled {
-        preimage: Vec<u8>,
-        hash: Vec<u8>,
-        value_msat: i64,
-        state: i32,
-        memo: String,
-        creation_date: i64,
-        payment_request: String,
+    /// An outgoing payment part that completed.
+    PaymentSent {
+        payment_hash: String,
+        part_id: u64,
+        group_id: u64,
+        amount_msat: u64,
+        amount_sent_msat: u64,
+        destination: Option<String>,
     },
-    InvoiceCancelled {
-        preimage: Vec<u8>,
-        hash: Vec<u8>,
-        value_msat: i64,
-        state: i32,
-        memo: String,
-        creation_date: i64,
-        payment_request: String,
+    /// An outgoing payment part that failed.
+    PaymentFailed {
+        payment_hash: String,
+        part_id: u64,
+        group_id: u64,
+        amount_msat: u64,
+        destination: Option<String>,
+        reason: String,
     },
-    InvoiceAccepted {
-        preimage: Vec<u8>,
-        hash: Vec<u8>,
-        value_msat: i64,
-        state: i32,
-        memo: String,
-        creation_date: i64,
-        payment_request: String,
+    /// A forward that failed past our node or was refused by our outgoing link.
+    ForwardFailed {
+        incoming_chan_id: u64,
+        outgoing_chan_id: u64,
+        timestamp_ns: u64,
+        outgoing_amt_msat: Option<u64>,
+        link_failure: bool,
+        reason: String,
+    },
+    /// A forward that settled, stored in the forwards table instead of as an event
+    ForwardSettled(Forward),
+    /// Local balance of a channel moved by settled HTLCs. Clients report one
+    /// per HTLC; the collector folds them per channel before they're stored.
+    ChannelBalanceChanged {
+        chan_id: u64,
+        delta_msat: i64,
+        causes: Vec<BalanceChangeCause>,
+        htlcs: u64,
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum CLNEvent {
-    ChannelOpened {},
+/// The invoice carried by the invoice lifecycle events.
+#[derive(Debug, Clone)]
+pub struct InvoiceEvent {
+    pub preimage: Vec<u8>,
+    pub hash: Vec<u8>,
+    pub value_msat: i64,
+    pub state: i32,
+    pub memo: String,
+    pub creation_date: i64,
+    pub payment_request: String,
 }
 
-#[derive(Debug, Clone)]
-pub enum NodeSpecificEvent {
-    LND(LNDEvent),
-    CLN(CLNEvent),
+/// Event types produced by node event streams, the ones that can be paused
+pub const STREAMED_EVENT_TYPES: [EventType; 12] = [
+    EventType::ChannelOpened,
+    EventType::ChannelClosed,
+    EventType::InvoiceCreated,
+    EventType::InvoiceSettled,
+    EventType::InvoiceCancelled,
+    EventType::InvoiceAccepted,
+    EventType::ForwardFailed,
+    EventType::PaymentSent,
+    EventType::PaymentFailed,
+    EventType::ChannelBalanceChanged,
+    EventType::InvoicePaymentMismatch,
+    EventType::KeysendReceived,
+];
+
+const DEFAULT_EVENT_CHANNEL_CAPACITY: usize = 32;
+
+/// Shortest gap between two `SubscriptionDegraded` events of one node
+const DEGRADED_EVENT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Shortest gap between two `ChannelBalanceChanged` events of one channel
+const BALANCE_CHANGE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Balance changes of a channel held back since its last event.
+#[derive(Default)]
+struct PendingBalanceChange {
+    delta_msat: i64,
+    causes: BTreeSet<BalanceChangeCause>,
+    htlcs: u64,
+}
+
+/// Limits `ChannelBalanceChanged` events to one per channel per
+/// `BALANCE_CHANGE_INTERVAL`, folding the changes in between.
+#[derive(Default)]
+struct BalanceChangeThrottle {
+    last_emitted: HashMap<u64, Instant>,
+    pending: HashMap<u64, PendingBalanceChange>,
+}
+
+impl BalanceChangeThrottle {
+    /// Passes `event` on unless it is a balance change of a channel that had
+    /// an event within the interval, in which case it is held back.
+    fn admit(&mut self, event: LightningEvent, now: Instant) -> Option<LightningEvent> {
+        let LightningEvent::ChannelBalanceChanged {
+            chan_id,
+            delta_msat,
+            causes,
+            htlcs,
+        } = event
+        else {
+            return Some(event);
+        };
+
+        let recent = self
+            .last_emitted
+            .get(&chan_id)
+            .is_some_and(|at| now.duration_since(*at) < BALANCE_CHANGE_INTERVAL);
+        if !recent {
+            self.last_emitted.insert(chan_id, now);
+            return Some(LightningEvent::ChannelBalanceChanged {
+                chan_id,
+                delta_msat,
+                causes,
+                htlcs,
+            });
+        }
+
+        let pending = self.pending.entry(chan_id).or_default();
+        pending.delta_msat += delta_msat;
+        pending.causes.extend(causes);
+        pending.htlcs += htlcs;
+        None
+    }
+
+    /// Emits the held back changes of channels whose interval has passed.
+    fn flush_due(&mut self, now: Instant) -> Vec<LightningEvent> {
+        let due: Vec<u64> = self
+            .pending
+            .keys()
+            .filter(|chan_id| {
+                self.last_emitted
+                    .get(chan_id)
+                    .is_none_or(|at| now.duration_since(*at) >= BALANCE_CHANGE_INTERVAL)
+            })
+            .copied()
+            .collect();
+
+        due.into_iter()
+            .filter_map(|chan_id| {
+                let pending = self.pending.remove(&chan_id)?;
+                self.last_emitted.insert(chan_id, now);
+                // HTLCs that cancel out leave nothing worth reporting.
+                (pending.delta_msat != 0).then(|| LightningEvent::ChannelBalanceChanged {
+                    chan_id,
+                    delta_msat: pending.delta_msat,
+                    causes: pending.causes.into_iter().collect(),
+                    htlcs: pending.htlcs,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Buffer size and overflow policy, read from the environment on first use
+static CHANNEL_SETTINGS: OnceLock<(usize, EventOverflowPolicy)> = OnceLock::new();
+
+fn channel_settings() -> (usize, EventOverflowPolicy) {
+    *CHANNEL_SETTINGS.get_or_init(|| match Config::from_env() {
+        Ok(config) => (
+            config.event_channel_capacity.max(1),
+            config.event_overflow_policy,
+        ),
+        Err(_) => (DEFAULT_EVENT_CHANNEL_CAPACITY, EventOverflowPolicy::Block),
+    })
+}
+
+/// Creates the buffer between a node's event stream and its handler.
+pub fn event_channel() -> (EventSender, EventReceiver) {
+    let (capacity, policy) = channel_settings();
+    bounded_event_channel(capacity, policy)
+}
+
+fn bounded_event_channel(
+    capacity: usize,
+    policy: EventOverflowPolicy,
+) -> (EventSender, EventReceiver) {
+    match policy {
+        EventOverflowPolicy::Block => {
+            let (sender, receiver) = mpsc::channel(capacity);
+            (
+                EventSender::Block(sender),
+                EventReceiver {
+                    inner: ReceiverKind::Block(receiver),
+                    dropped: 0,
+                },
+            )
+        }
+        // A broadcast channel with a single receiver is a ring buffer that
+        // overwrites its oldest entries and tells the receiver how many it lost.
+        EventOverflowPolicy::DropOldest => {
+            let (sender, receiver) = broadcast::channel(capacity);
+            (
+                EventSender::DropOldest(sender),
+                EventReceiver {
+                    inner: ReceiverKind::DropOldest(receiver),
+                    dropped: 0,
+                },
+            )
+        }
+    }
+}
+
+/// Sending half of a node's event buffer.
+#[derive(Clone)]
+pub enum EventSender {
+    Block(mpsc::Sender<LightningEvent>),
+    DropOldest(broadcast::Sender<LightningEvent>),
+}
+
+impl EventSender {
+    /// Buffers an event. Fails only once the handler is gone.
+    async fn send(&self, event: LightningEvent) -> Result<(), ()> {
+        match self {
+            EventSender::Block(sender) => sender.send(event).await.map_err(|_| ()),
+            EventSender::DropOldest(sender) => sender.send(event).map(|_| ()).map_err(|_| ()),
+        }
+    }
+}
+
+enum ReceiverKind {
+    Block(mpsc::Receiver<LightningEvent>),
+    DropOldest(broadcast::Receiver<LightningEvent>),
+}
+
+/// Receiving half of a node's event buffer.
+pub struct EventReceiver {
+    inner: ReceiverKind,
+    /// Events overwritten since the last `take_dropped`
+    dropped: u64,
+}
+
+impl EventReceiver {
+    /// Waits for the next event, or None once the node stream has ended.
+    async fn recv(&mut self) -> Option<LightningEvent> {
+        match &mut self.inner {
+            ReceiverKind::Block(receiver) => receiver.recv().await,
+            ReceiverKind::DropOldest(receiver) => loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some(event),
+                    Err(RecvError::Lagged(count)) => {
+                        WRITER_STATS
+                            .dropped_events
+                            .fetch_add(count, Ordering::Relaxed);
+                        self.dropped += count;
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            },
+        }
+    }
+
+    /// Returns how many events were dropped since the last call.
+    fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
 }
 
 pub struct EventCollector {
-    raw_event_sender: mpsc::Sender<NodeSpecificEvent>,
+    raw_event_sender: EventSender,
 }
 
 impl EventCollector {
-    pub fn new(sender: mpsc::Sender<NodeSpecificEvent>) -> Self {
+    pub fn new(sender: EventSender) -> Self {
         EventCollector {
             raw_event_sender: sender,
         }
@@ -109,10 +372,11 @@ impl EventCollector {
         let node_id_for_task = node_id.clone();
 
         tokio::spawn(async move {
+            let node_key = node_id_for_task.to_string();
             let mut lnd_node_guard = lnd_node_.lock().await;
             let event_stream_result = lnd_node_guard.stream_events().await;
 
-            let mut event_stream: Pin<Box<dyn Stream<Item = NodeSpecificEvent> + Send>> =
+            let mut event_stream: Pin<Box<dyn Stream<Item = LightningEvent> + Send>> =
                 match event_stream_result {
                     Ok(stream) => stream,
                     Err(e) => {
@@ -124,21 +388,153 @@ impl EventCollector {
                         return;
                     }
                 };
+            subscription_health::record_connected(&node_key, SubscriptionKind::Events);
 
-            while let Some(event) = event_stream.next().await {
-                if sender.send(event).await.is_err() {
-                    tracing::error!(
-                        "Failed to send event for node {}. Receiver likely dropped.",
-                        node_id_for_task
-                    );
-                    break;
+            let mut balance_changes = BalanceChangeThrottle::default();
+            let mut flush = tokio::time::interval(BALANCE_CHANGE_INTERVAL);
+            'stream: loop {
+                let events = tokio::select! {
+                    event = event_stream.next() => match event {
+                        Some(event) => {
+                            subscription_health::record_event(&node_key, SubscriptionKind::Events);
+                            balance_changes.admit(event, Instant::now()).into_iter().collect()
+                        }
+                        None => break,
+                    },
+                    _ = flush.tick() => balance_changes.flush_due(Instant::now()),
+                };
+                for event in events {
+                    if sender.send(event).await.is_err() {
+                        tracing::error!(
+                            "Failed to send event for node {}. Receiver likely dropped.",
+                            node_id_for_task
+                        );
+                        break 'stream;
+                    }
                 }
             }
+            subscription_health::record_disconnected(&node_key, SubscriptionKind::Events, None);
             tracing::info!("Event stream for node {} ended.", node_id_for_task);
         });
     }
 }
 
+/// Connects to a stored node and streams its events into the account's
+/// event log, as authenticating the node does.
+pub async fn start_event_stream(
+    pool: sqlx::SqlitePool,
+    account_id: String,
+    user_id: String,
+    node_credentials: &NodeCredentials,
+) -> Result<(), String> {
+    let public_key = PublicKey::from_str(&node_credentials.node_id).map_err(|e| e.to_string())?;
+    let node: Box<dyn LightningClient + Send + Sync + 'static> =
+        match node_credentials.node_type.as_str() {
+            "lnd" => connect_lnd(LndConnection {
+                id: NodeId::PublicKey(public_key),
+                address: node_credentials.address.clone(),
+                macaroon: node_credentials.macaroon.clone(),
+                cert: node_credentials.tls_cert.clone(),
+            })
+            .await
+            .map_err(|e| e.to_string())?,
+            "cln" => {
+                let (client_cert, client_key, ca_cert) =
+                    extract_cln_tls_components(node_credentials).map_err(|(_, body)| body)?;
+                Box::new(
+                    ClnNode::new(ClnConnection {
+                        id: NodeId::PublicKey(public_key),
+                        address: node_credentials.address.clone(),
+                        ca_cert,
+                        client_cert,
+                        client_key,
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?,
+                )
+            }
+            other => return Err(format!("{other} nodes have no event stream")),
+        };
+
+    let (sender, receiver) = event_channel();
+    EventCollector::new(sender)
+        .start_sending(public_key, Arc::new(Mutex::new(node)))
+        .await;
+    EventHandler::with_context(
+        pool,
+        account_id,
+        user_id,
+        node_credentials.node_id.clone(),
+        node_credentials.node_alias.clone(),
+    )
+    .start_receiving(receiver);
+
+    Ok(())
+}
+
+/// Longest an event waits in the write buffer before its batch is stored
+const EVENT_BATCH_WINDOW: Duration = Duration::from_millis(100);
+
+/// Most events stored per transaction; a full buffer is flushed early
+const EVENT_BATCH_MAX: usize = 200;
+
+/// Counters of the batched event writer, shared by every node's handler.
+struct WriterStats {
+    batches: AtomicU64,
+    events: AtomicU64,
+    rows: AtomicU64,
+    largest_batch: AtomicU64,
+    overflows: AtomicU64,
+    failed_events: AtomicU64,
+    dropped_events: AtomicU64,
+    paused_events: AtomicU64,
+}
+
+static WRITER_STATS: WriterStats = WriterStats {
+    batches: AtomicU64::new(0),
+    events: AtomicU64::new(0),
+    rows: AtomicU64::new(0),
+    largest_batch: AtomicU64::new(0),
+    overflows: AtomicU64::new(0),
+    failed_events: AtomicU64::new(0),
+    dropped_events: AtomicU64::new(0),
+    paused_events: AtomicU64::new(0),
+};
+
+/// Snapshot of the event writer's counters since the server started.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventWriterMetrics {
+    /// Transactions written
+    pub batches: u64,
+    /// Node events stored
+    pub events: u64,
+    /// Rows written, one per notification endpoint an event fans out to
+    pub rows: u64,
+    pub largest_batch: u64,
+    /// Batches flushed early because the buffer filled up
+    pub overflows: u64,
+    /// Node events lost because their batch could not be written
+    pub failed_events: u64,
+    /// Node events discarded by the drop_oldest overflow policy
+    pub dropped_events: u64,
+    /// Node events skipped because their type is paused
+    pub paused_events: u64,
+}
+
+/// Reads the event writer's counters.
+pub fn event_writer_metrics() -> EventWriterMetrics {
+    EventWriterMetrics {
+        batches: WRITER_STATS.batches.load(Ordering::Relaxed),
+        events: WRITER_STATS.events.load(Ordering::Relaxed),
+        rows: WRITER_STATS.rows.load(Ordering::Relaxed),
+        largest_batch: WRITER_STATS.largest_batch.load(Ordering::Relaxed),
+        overflows: WRITER_STATS.overflows.load(Ordering::Relaxed),
+        failed_events: WRITER_STATS.failed_events.load(Ordering::Relaxed),
+        dropped_events: WRITER_STATS.dropped_events.load(Ordering::Relaxed),
+        paused_events: WRITER_STATS.paused_events.load(Ordering::Relaxed),
+    }
+}
+
 #[derive(Clone)]
 pub struct EventHandler {
     pool: Option<sqlx::SqlitePool>,
@@ -159,15 +555,109 @@ impl EventHandler {
         }
     }
 
-    pub fn start_receiving(self, mut receiver: mpsc::Receiver<NodeSpecificEvent>) {
+    /// Buffers incoming events and writes them in batches: the first event
+    /// opens a window of `EVENT_BATCH_WINDOW`, and everything that arrives
+    /// within it (up to `EVENT_BATCH_MAX`) is stored in one transaction.
+    pub fn start_receiving(self, mut receiver: EventReceiver) {
         let handler = self.clone();
         tokio::spawn(async move {
-            while let Some(raw_event) = receiver.recv().await {
-                handler.dispatch_event(raw_event).await;
+            let mut unreported_drops = 0;
+            let mut last_degraded_at: Option<Instant> = None;
+
+            while let Some(first_event) = receiver.recv().await {
+                let received_at = Instant::now();
+                let mut batch = vec![first_event];
+                let deadline = received_at + EVENT_BATCH_WINDOW;
+                while batch.len() < EVENT_BATCH_MAX {
+                    match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                        Ok(Some(event)) => batch.push(event),
+                        Ok(None) | Err(_) => break,
+                    }
+                }
+                if batch.len() == EVENT_BATCH_MAX {
+                    WRITER_STATS.overflows.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        "Event buffer filled before its {}ms window closed; flushing early",
+                        EVENT_BATCH_WINDOW.as_millis()
+                    );
+                }
+                handler.dispatch_batch(batch).await;
+                if let Some(node_id) = &handler.node_id {
+                    // Lag of the oldest event in the batch, from leaving the
+                    // buffer to being stored.
+                    subscription_health::record_lag(
+                        node_id,
+                        SubscriptionKind::Events,
+                        received_at.elapsed(),
+                    );
+                }
+
+                unreported_drops += receiver.take_dropped();
+                if unreported_drops > 0
+                    && last_degraded_at.is_none_or(|at| at.elapsed() >= DEGRADED_EVENT_INTERVAL)
+                {
+                    handler.report_degraded(unreported_drops).await;
+                    unreported_drops = 0;
+                    last_degraded_at = Some(Instant::now());
+                }
             }
         });
     }
 
+    /// Records that `dropped` events of this node were discarded because its
+    /// buffer was full.
+    async fn report_degraded(&self, dropped: u64) {
+        let (capacity, policy) = channel_settings();
+        tracing::warn!(
+            "Dropped {} event(s) from node {}: buffer of {} is full",
+            dropped,
+            self.node_id.as_deref().unwrap_or("unknown"),
+            capacity
+        );
+
+        let (Some(pool), Some(account_id), Some(user_id), Some(node_id), Some(node_alias)) = (
+            &self.pool,
+            &self.account_id,
+            &self.user_id,
+            &self.node_id,
+            &self.node_alias,
+        ) else {
+            return;
+        };
+        let event = CreateEvent {
+            id: Uuid::now_v7().to_string(),
+            account_id: account_id.clone(),
+            user_id: user_id.clone(),
+            node_id: node_id.clone(),
+            node_alias: node_alias.clone(),
+            event_type: EventType::SubscriptionDegraded,
+            severity: EventSeverity::Warning,
+            title: "Event Subscription Degraded".to_string(),
+            description: format!(
+                "Dropped {dropped} event(s) because the buffer of {capacity} events was full"
+            ),
+            data: serde_json::json!({
+                "dropped_events": dropped,
+                "buffer_capacity": capacity,
+                "overflow_policy": policy.to_string(),
+            })
+            .to_string(),
+            notifications_id: None,
+            timestamp: chrono::Utc::now(),
+        };
+
+        if let Err(e) = EventService::new(pool)
+            .create_and_dispatch_event(event)
+            .await
+        {
+            tracing::error!(
+                "Failed to record degraded subscription for node {}: {}",
+                node_id,
+                e
+            );
+        }
+    }
+
     pub fn with_context(
         pool: sqlx::SqlitePool,
         account_id: String,
@@ -184,7 +674,7 @@ impl EventHandler {
         }
     }
 
-    pub async fn dispatch_event(&self, raw_event: NodeSpecificEvent) {
+    pub async fn dispatch_batch(&self, raw_events: Vec<LightningEvent>) {
         // Only process if we have database context
         if let (Some(pool), Some(account_id), Some(user_id), Some(node_id), Some(node_alias)) = (
             &self.pool,
@@ -193,28 +683,216 @@ impl EventHandler {
             &self.node_id,
             &self.node_alias,
         ) {
-            let event_service = crate::services::event_service::EventService::new(pool);
+            // Read per batch so pausing takes effect without restarting the stream.
+            let paused: HashSet<String> = match EventSubscriptionRepository::new(pool)
+                .get_paused_types(account_id, node_id)
+                .await
+            {
+                Ok(paused) => paused.into_iter().map(|p| p.event_type).collect(),
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to load paused event types for node {}: {}",
+                        node_id,
+                        e
+                    );
+                    HashSet::new()
+                }
+            };
 
-            if let Err(e) = event_service
-                .process_lightning_event(
-                    pool,
-                    account_id.clone(),
-                    user_id.clone(),
-                    node_id.clone(),
-                    node_alias.clone(),
-                    &raw_event,
-                )
+            // Billing invoices are settled even when invoice events are paused.
+            let settled: Vec<String> = raw_events
+                .iter()
+                .filter_map(|raw_event| match raw_event {
+                    LightningEvent::InvoiceSettled(invoice) => Some(hex::encode(&invoice.hash)),
+                    _ => None,
+                })
+                .collect();
+            if !settled.is_empty() {
+                record_settled_invoices(pool, account_id, node_id, &settled).await;
+            }
+
+            let mut forwards = Vec::new();
+            let raw_events: Vec<LightningEvent> = raw_events
+                .into_iter()
+                .filter_map(|raw_event| match raw_event {
+                    LightningEvent::ForwardSettled(forward) => {
+                        forwards.push(forward);
+                        None
+                    }
+                    raw_event => Some(raw_event),
+                })
+                .collect();
+            let forward_repo = ForwardRepository::new(pool);
+            for forward in &forwards {
+                if let Err(e) = forward_repo
+                    .insert_forward(account_id, node_id, forward)
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to store settled forward for node {}: {}. Forward: {:?}",
+                        node_id,
+                        e,
+                        forward
+                    );
+                }
+            }
+
+            let event_service = crate::services::event_service::EventService::new(pool);
+            let built: Vec<_> = raw_events
+                .iter()
+                .filter_map(|raw_event| {
+                    event_service.build_lightning_event(
+                        account_id.clone(),
+                        user_id.clone(),
+                        node_id.clone(),
+                        node_alias.clone(),
+                        raw_event,
+                    )
+                })
+                .collect();
+            let built_count = built.len();
+            let events: Vec<_> = built
+                .into_iter()
+                .filter(|event| !paused.contains(&event.event_type.to_string()))
+                .collect();
+            let skipped = (built_count - events.len()) as u64;
+            WRITER_STATS
+                .paused_events
+                .fetch_add(skipped, Ordering::Relaxed);
+            if events.is_empty() {
+                return;
+            }
+            WRITER_STATS.batches.fetch_add(1, Ordering::Relaxed);
+            WRITER_STATS
+                .largest_batch
+                .fetch_max(events.len() as u64, Ordering::Relaxed);
+            let results = match event_service
+                .create_and_dispatch_events(events.clone())
                 .await
             {
-                tracing::error!(
-                    "Failed to process lightning event for node {}: {}. Event: {:?}",
-                    node_id,
-                    e,
-                    raw_event
-                );
+                // The batch is one transaction, so a single bad event fails
+                // it; storing the events one at a time loses only that one.
+                Err(e) if events.len() > 1 => {
+                    tracing::warn!(
+                        "Failed to store batch of {} lightning events for node {}: {}. Retrying one at a time",
+                        events.len(),
+                        node_id,
+                        e
+                    );
+                    let mut results = Vec::with_capacity(events.len());
+                    for event in events {
+                        let result = event_service
+                            .create_and_dispatch_events(vec![event.clone()])
+                            .await;
+                        results.push((result, vec![event]));
+                    }
+                    results
+                }
+                result => vec![(result, events)],
+            };
+            for (result, events) in results {
+                let event_count = events.len() as u64;
+                match result {
+                    Ok(rows) => {
+                        WRITER_STATS
+                            .events
+                            .fetch_add(event_count, Ordering::Relaxed);
+                        WRITER_STATS.rows.fetch_add(rows as u64, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        WRITER_STATS
+                            .failed_events
+                            .fetch_add(event_count, Ordering::Relaxed);
+                        tracing::error!(
+                            "Failed to store {} lightning event(s) for node {}: {}. Events: {:?}",
+                            event_count,
+                            node_id,
+                            e,
+                            events
+                        );
+                    }
+                }
             }
         } else {
             tracing::debug!("Skipping event dispatch - no database context available");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forward_failed(outgoing_chan_id: u64) -> LightningEvent {
+        LightningEvent::ForwardFailed {
+            incoming_chan_id: 1,
+            outgoing_chan_id,
+            timestamp_ns: 0,
+            outgoing_amt_msat: None,
+            link_failure: false,
+            reason: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_keeps_newest_events() {
+        let (sender, mut receiver) = bounded_event_channel(2, EventOverflowPolicy::DropOldest);
+        for chan_id in 1..=5 {
+            sender.send(forward_failed(chan_id)).await.unwrap();
+        }
+        drop(sender);
+
+        let mut received = Vec::new();
+        while let Some(LightningEvent::ForwardFailed {
+            outgoing_chan_id, ..
+        }) = receiver.recv().await
+        {
+            received.push(outgoing_chan_id);
+        }
+
+        assert_eq!(received, vec![4, 5]);
+        assert_eq!(receiver.take_dropped(), 3);
+        assert_eq!(receiver.take_dropped(), 0);
+    }
+
+    #[test]
+    fn test_balance_changes_are_folded_per_channel() {
+        let change = |chan_id, delta_msat, cause| LightningEvent::ChannelBalanceChanged {
+            chan_id,
+            delta_msat,
+            causes: vec![cause],
+            htlcs: 1,
+        };
+        let mut throttle = BalanceChangeThrottle::default();
+        let start = Instant::now();
+
+        assert!(
+            throttle
+                .admit(change(1, -500, BalanceChangeCause::Payment), start)
+                .is_some()
+        );
+        assert!(
+            throttle
+                .admit(change(1, 200, BalanceChangeCause::Forward), start)
+                .is_none()
+        );
+        assert!(
+            throttle
+                .admit(change(1, 100, BalanceChangeCause::Invoice), start)
+                .is_none()
+        );
+        assert!(
+            throttle
+                .admit(change(2, 100, BalanceChangeCause::Invoice), start)
+                .is_some()
+        );
+        assert!(throttle.flush_due(start).is_empty());
+
+        let flushed = throttle.flush_due(start + BALANCE_CHANGE_INTERVAL);
+        assert!(matches!(
+            flushed.as_slice(),
+            [LightningEvent::ChannelBalanceChanged { chan_id: 1, delta_msat: 300, causes, htlcs: 2 }]
+                if causes == &[BalanceChangeCause::Forward, BalanceChangeCause::Invoice]
+        ));
+    }
+}
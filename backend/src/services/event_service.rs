@@ -1,19 +1,84 @@
 //! Event business logic service.
 
 use crate::database::models::{
-    CreateEvent, Event, EventFilters, EventResponse, EventSeverity, EventType,
+    ChannelBalanceChangedData, ChannelClosedData, ChannelOpenedData, CreateEvent, Event,
+    EventCountRow, EventFilters, EventPayload, EventResponse, EventSeverity, EventStats,
+    EventStatsQuery, EventType, EventTypeCount, ForwardFailedData, InvoiceEventData,
+    InvoicePaymentMismatchData, KeysendReceivedData, MaintenanceWindow, NodeEventCount, NodeLabel,
+    Notification, NotificationType, PaymentEventData, SeverityEventCount,
 };
 use crate::errors::{ServiceError, ServiceResult};
 use crate::repositories::event_repository::EventRepository;
+use crate::repositories::maintenance_repository::MaintenanceRepository;
+use crate::repositories::node_label_repository::NodeLabelRepository;
 use crate::repositories::notification_repository::NotificationRepository;
-use crate::services::notification_dispatcher::NotificationDispatcher;
-use chrono::Utc;
+use crate::services::escalations::start_escalations;
+use crate::services::event_manager::LightningEvent;
+use crate::services::maintenance::{covers, label_event_data};
+use crate::services::node_labels::tag_event_data;
+use crate::services::notification_dispatcher::{NotificationDispatcher, touches_channel};
+use crate::utils::PaymentMismatch;
+use chrono::{DateTime, Duration, Utc};
 use serde_json;
-use serde_json::Value;
 use sqlx::SqlitePool;
 use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Days of events counted by [`EventService::get_event_stats`] when no start is given.
+const DEFAULT_STATS_DAYS: i64 = 30;
+
+/// Folds per-(severity, event type, node) counts into event statistics.
+fn event_stats(rows: Vec<EventCountRow>, from: DateTime<Utc>, to: DateTime<Utc>) -> EventStats {
+    let severities = [
+        EventSeverity::Critical,
+        EventSeverity::Warning,
+        EventSeverity::Info,
+    ];
+    let mut by_severity: Vec<SeverityEventCount> = severities
+        .into_iter()
+        .map(|severity| SeverityEventCount { severity, count: 0 })
+        .collect();
+    let mut by_event_type: Vec<EventTypeCount> = Vec::new();
+    let mut by_node: Vec<NodeEventCount> = Vec::new();
+    let mut total = 0;
+
+    for row in rows {
+        total += row.count;
+        if let Some(entry) = by_severity.iter_mut().find(|e| e.severity == row.severity) {
+            entry.count += row.count;
+        }
+        match by_event_type
+            .iter_mut()
+            .find(|e| e.event_type == row.event_type)
+        {
+            Some(entry) => entry.count += row.count,
+            None => by_event_type.push(EventTypeCount {
+                event_type: row.event_type,
+                count: row.count,
+            }),
+        }
+        match by_node.iter_mut().find(|e| e.node_id == row.node_id) {
+            Some(entry) => entry.count += row.count,
+            None => by_node.push(NodeEventCount {
+                node_id: row.node_id,
+                node_alias: row.node_alias,
+                count: row.count,
+            }),
+        }
+    }
+    by_event_type.sort_by(|a, b| b.count.cmp(&a.count));
+    by_node.sort_by(|a, b| b.count.cmp(&a.count));
+
+    EventStats {
+        from,
+        to,
+        total,
+        by_severity,
+        by_event_type,
+        by_node,
+    }
+}
+
 /// Service layer for event operations.
 pub struct EventService<'a> {
     pool: &'a SqlitePool,
@@ -32,50 +97,118 @@ impl<'a> EventService<'a> {
     /// Creates and dispatches a new event.
     pub async fn create_and_dispatch_event(
         &self,
-        mut create_event: CreateEvent,
+        create_event: CreateEvent,
     ) -> ServiceResult<Event> {
-        let event_repo = EventRepository::new(self.pool);
-        let notification_repo = NotificationRepository::new(self.pool);
+        let created_events = self.store_events(vec![create_event]).await?;
 
-        // Get all active notifications for this account
-        let notifications = notification_repo
-            .get_notifications_by_account_id(&create_event.account_id)
-            .await?;
+        dispatch_events(self.pool, &self.dispatcher, &created_events).await;
 
-        let active_notifications: Vec<_> = notifications.iter().filter(|n| n.is_active).collect();
+        // Return the first event, or an error if none were created
+        created_events
+            .into_iter()
+            .next()
+            .ok_or_else(|| ServiceError::InternalError {
+                message: "No events were created".to_string(),
+            })
+    }
 
-        let mut created_events = Vec::new();
+    /// Creates a batch of events in a single transaction and dispatches their
+    /// notifications in the background. Returns how many rows were written.
+    pub async fn create_and_dispatch_events(
+        &self,
+        create_events: Vec<CreateEvent>,
+    ) -> ServiceResult<usize> {
+        let created_events = self.store_events(create_events).await?;
+        let count = created_events.len();
 
-        // Create one event per notification endpoint
-        for notification in &active_notifications {
-            create_event.notifications_id = Some(notification.id.clone());
-            create_event.id = Uuid::now_v7().to_string(); // Generate new ID for each event
+        let pool = self.pool.clone();
+        let dispatcher = self.dispatcher.clone();
+        tokio::spawn(async move {
+            dispatch_events(&pool, &dispatcher, &created_events).await;
+        });
 
-            let event = event_repo.create_event(create_event.clone()).await?;
-            created_events.push(event);
-        }
+        Ok(count)
+    }
 
-        // If no notifications, create event without notification_id
-        if active_notifications.is_empty() {
-            create_event.notifications_id = None;
-            let event = event_repo.create_event(create_event).await?;
-            created_events.push(event);
-        }
+    /// Writes events with one row per active notification endpoint of their
+    /// account that isn't limited to a channel the event doesn't touch, or a
+    /// single row when there is none.
+    /// Events of a node under maintenance are labelled as such, and only get
+    /// endpoint rows when critical. Quiet events only get firehose rows, which
+    /// every event gets. Events of a labelled node carry its label.
+    async fn store_events(&self, create_events: Vec<CreateEvent>) -> ServiceResult<Vec<Event>> {
+        let notification_repo = NotificationRepository::new(self.pool);
+        let maintenance_repo = MaintenanceRepository::new(self.pool);
+        let label_repo = NodeLabelRepository::new(self.pool);
+        let mut endpoints: HashMap<String, Vec<Notification>> = HashMap::new();
+        let mut maintenance: HashMap<(String, String), Vec<MaintenanceWindow>> = HashMap::new();
+        let mut labels: HashMap<(String, String), Option<NodeLabel>> = HashMap::new();
+        let mut rows = Vec::new();
 
-        // Dispatch notifications for all created events
-        for event in &created_events {
-            if let Err(e) = self.dispatcher.dispatch_event(self.pool, event).await {
-                tracing::error!("Failed to dispatch event notifications: {}", e);
+        for mut create_event in create_events {
+            let node = (
+                create_event.account_id.clone(),
+                create_event.node_id.clone(),
+            );
+            if !maintenance.contains_key(&node) {
+                let windows = maintenance_repo
+                    .get_windows_since(&node.0, &node.1, create_event.timestamp)
+                    .await?;
+                maintenance.insert(node.clone(), windows);
+            }
+            let held = covers(&maintenance[&node], create_event.timestamp);
+            if held {
+                create_event.data = label_event_data(&create_event.data);
+            }
+            if !labels.contains_key(&node) {
+                let label = label_repo.get_label(&node.0, &node.1).await?;
+                labels.insert(node.clone(), label);
+            }
+            if let Some(label) = &labels[&node] {
+                create_event.data = tag_event_data(&create_event.data, label);
+            }
+
+            let quiet = is_quiet(&create_event.event_type)
+                || (held && create_event.severity != EventSeverity::Critical);
+
+            if !endpoints.contains_key(&create_event.account_id) {
+                let active = notification_repo
+                    .get_notifications_by_account_id(&create_event.account_id)
+                    .await?
+                    .into_iter()
+                    .filter(|n| n.is_active)
+                    .collect();
+                endpoints.insert(create_event.account_id.clone(), active);
+            }
+            let notification_ids: Vec<&String> = endpoints[&create_event.account_id]
+                .iter()
+                .filter(|n| {
+                    n.notification_type == NotificationType::Firehose
+                        || (!quiet
+                            && n.channel
+                                .as_deref()
+                                .is_none_or(|channel| touches_channel(&create_event.data, channel)))
+                })
+                .map(|n| &n.id)
+                .collect();
+
+            if notification_ids.is_empty() {
+                rows.push(CreateEvent {
+                    notifications_id: None,
+                    ..create_event
+                });
+                continue;
+            }
+            for notification_id in notification_ids {
+                rows.push(CreateEvent {
+                    id: Uuid::now_v7().to_string(),
+                    notifications_id: Some(notification_id.to_string()),
+                    ..create_event.clone()
+                });
             }
         }
 
-        // Return the first event, or an error if none were created
-        created_events
-            .into_iter()
-            .next()
-            .ok_or_else(|| ServiceError::InternalError {
-                message: "No events were created".to_string(),
-            })
+        Ok(EventRepository::new(self.pool).create_events(rows).await?)
     }
 
     /// Retrieves events for an account with optional filters.
@@ -88,37 +221,63 @@ impl<'a> EventService<'a> {
         let repo = EventRepository::new(pool);
         let events = repo.get_events_by_account_id(account_id, filters).await?;
 
-        let event_responses: Vec<EventResponse> = events
-            .into_iter()
-            .filter_map(|event| {
-                // Parse JSON data
-                let data = match serde_json::from_str::<Value>(&event.data) {
-                    Ok(data) => data,
-                    Err(e) => {
-                        tracing::warn!("Failed to parse event data for {}: {}", event.id, e);
-                        serde_json::json!({})
-                    }
-                };
+        Ok(events.into_iter().map(EventResponse::from).collect())
+    }
 
-                Some(EventResponse {
-                    id: event.id,
-                    account_id: event.account_id,
-                    user_id: event.user_id,
-                    node_id: event.node_id,
-                    node_alias: event.node_alias,
-                    event_type: event.event_type,
-                    severity: event.severity,
-                    title: event.title,
-                    description: event.description,
-                    notifications_id: event.notifications_id,
-                    data,
-                    timestamp: event.timestamp,
-                    created_at: event.created_at,
-                })
+    /// Retrieves one of the account's events.
+    pub async fn get_event_for_account(
+        &self,
+        account_id: &str,
+        id: &str,
+    ) -> ServiceResult<Option<EventResponse>> {
+        let event = EventRepository::new(self.pool)
+            .get_event_by_id(id, account_id)
+            .await?;
+        Ok(event.map(EventResponse::from))
+    }
+
+    /// Full-text searches an account's events, newest first, returning a page
+    /// of them with the number of matches.
+    ///
+    /// Each whitespace-separated term must match (as a prefix) somewhere in the
+    /// title, description or data values, so `03ab` finds events mentioning any
+    /// pubkey starting with those characters. Only the limit, offset and node
+    /// filters apply.
+    pub async fn search_events_for_account(
+        &self,
+        account_id: &str,
+        query: &str,
+        filters: Option<EventFilters>,
+    ) -> ServiceResult<(Vec<EventResponse>, i64)> {
+        let Some(match_query) = fts_match_query(query) else {
+            return Err(ServiceError::validation("Search query must not be empty"));
+        };
+
+        let (limit, offset, node_ids) = filters
+            .map(|f| {
+                (
+                    f.limit.unwrap_or(50).min(1000),
+                    f.offset.unwrap_or(0),
+                    f.node_ids,
+                )
             })
-            .collect();
+            .unwrap_or((50, 0, None));
 
-        Ok(event_responses)
+        let repo = EventRepository::new(self.pool);
+        let events = repo
+            .search_events_by_account_id(
+                account_id,
+                &match_query,
+                node_ids.as_deref(),
+                limit,
+                offset,
+            )
+            .await?;
+        let total = repo
+            .count_search_events_by_account_id(account_id, &match_query, node_ids.as_deref())
+            .await?;
+
+        Ok((events.into_iter().map(EventResponse::from).collect(), total))
     }
 
     /// Gets event count for an account.
@@ -133,49 +292,423 @@ impl<'a> EventService<'a> {
         Ok(count)
     }
 
-    /// Gets event statistics by severity.
-    pub async fn get_event_stats_by_severity(
+    /// Counts the account's events in a date range by severity, event type
+    /// and node, with a single grouped query.
+    pub async fn get_event_stats(
         &self,
-        pool: &SqlitePool,
         account_id: &str,
-    ) -> ServiceResult<(i64, i64, i64)> {
-        let repo = EventRepository::new(pool);
+        node_scope: Option<&[String]>,
+        query: EventStatsQuery,
+    ) -> ServiceResult<EventStats> {
+        let to = query.to.unwrap_or_else(Utc::now);
+        let from = query
+            .from
+            .unwrap_or_else(|| to - Duration::days(DEFAULT_STATS_DAYS));
+        if from > to {
+            return Err(ServiceError::validation("'from' must not be after 'to'"));
+        }
 
-        let info_count = repo
-            .count_events_by_account_and_severity(account_id, &EventSeverity::Info)
+        let mut rows = EventRepository::new(self.pool)
+            .get_event_counts(account_id, from, to)
             .await?;
+        rows.retain(|row| node_scope.is_none_or(|node_ids| node_ids.contains(&row.node_id)));
+        Ok(event_stats(rows, from, to))
+    }
 
-        let warning_count = repo
-            .count_events_by_account_and_severity(account_id, &EventSeverity::Warning)
-            .await?;
+    /// Builds a representative, never-persisted event for the given type.
+    ///
+    /// Used to preview notification payloads when no historical event of that
+    /// type exists yet. Data fields mirror what the live processors emit.
+    pub fn sample_event(event_type: EventType, account_id: &str, user_id: &str) -> Event {
+        let sample_pubkey = "02".to_string() + &"ab".repeat(32);
+        let sample_hash = "cd".repeat(32);
 
-        let critical_count = repo
-            .count_events_by_account_and_severity(account_id, &EventSeverity::Critical)
-            .await?;
+        let (severity, title, description, data) = match event_type {
+            EventType::ChannelOpened => (
+                EventSeverity::Info,
+                "Channel Opened",
+                format!("New channel opened with {sample_pubkey}"),
+                serde_json::json!({
+                    "active": true,
+                    "channel_id": 834_567_890_123_456_u64,
+                    "counterparty_node_id": sample_pubkey,
+                    "channel_point": format!("{sample_hash}:0"),
+                    "capacity": 1_000_000,
+                    "local_balance": 500_000,
+                    "remote_balance": 500_000,
+                }),
+            ),
+            EventType::ChannelClosed => (
+                EventSeverity::Warning,
+                "Channel Closed",
+                format!("Channel closed with {sample_pubkey}"),
+                serde_json::json!({
+                    "chan_id": 834_567_890_123_456_u64,
+                    "remote_pubkey": sample_pubkey,
+                    "channel_point": format!("{sample_hash}:0"),
+                    "closing_tx_hash": sample_hash,
+                    "capacity": 1_000_000,
+                    "settled_balance": 499_000,
+                }),
+            ),
+            EventType::InvoiceCreated
+            | EventType::InvoiceSettled
+            | EventType::InvoiceCancelled
+            | EventType::InvoiceAccepted => {
+                let (severity, title, verb) = match event_type {
+                    EventType::InvoiceCreated => {
+                        (EventSeverity::Info, "Invoice Created", "created")
+                    }
+                    EventType::InvoiceSettled => {
+                        (EventSeverity::Info, "Invoice Settled", "settled")
+                    }
+                    EventType::InvoiceCancelled => {
+                        (EventSeverity::Warning, "Invoice Cancelled", "cancelled")
+                    }
+                    _ => (EventSeverity::Info, "Invoice Accepted", "accepted"),
+                };
+                (
+                    severity,
+                    title,
+                    format!("Invoice {verb} for 21000000 msat"),
+                    serde_json::json!({
+                        "hash": sample_hash,
+                        "value_msat": 21_000_000,
+                        "memo": "Sample invoice",
+                        "creation_date": Utc::now().timestamp(),
+                    }),
+                )
+            }
+            EventType::PaymentSent | EventType::PaymentReceived | EventType::PaymentFailed => {
+                let (severity, title) = match event_type {
+                    EventType::PaymentSent => (EventSeverity::Info, "Payment Sent"),
+                    EventType::PaymentReceived => (EventSeverity::Info, "Payment Received"),
+                    _ => (EventSeverity::Warning, "Payment Failed"),
+                };
+                (
+                    severity,
+                    title,
+                    format!("{title} for 21000 sat"),
+                    serde_json::json!({
+                        "payment_hash": sample_hash,
+                        "part_id": 0,
+                        "group_id": 1,
+                        "amount_msat": 21_000_000,
+                        "fee_msat": 3_000,
+                        "destination": sample_pubkey,
+                    }),
+                )
+            }
+            EventType::NodeConnected => (
+                EventSeverity::Info,
+                "Node Connected",
+                "Node connection established".to_string(),
+                serde_json::json!({}),
+            ),
+            EventType::NodeDisconnected => (
+                EventSeverity::Critical,
+                "Node Disconnected",
+                "Node connection lost".to_string(),
+                serde_json::json!({}),
+            ),
+            EventType::NodeResync => (
+                EventSeverity::Info,
+                "Node Resync Completed",
+                "Resync completed: 12 channels, 340 payments, 95 invoices".to_string(),
+                serde_json::json!({
+                    "stage": "completed",
+                    "channels": 12,
+                    "payments": 340,
+                    "invoices": 95,
+                }),
+            ),
+            EventType::ChannelAcceptorDecision => (
+                EventSeverity::Warning,
+                "Channel Open Rejected",
+                format!(
+                    "Rejected 50000 sat channel from {sample_pubkey}: channel size 50000 sat is below the minimum of 100000 sat"
+                ),
+                serde_json::json!({
+                    "accepted": false,
+                    "peer_pubkey": sample_pubkey,
+                    "funding_sat": 50_000,
+                    "push_msat": 0,
+                    "private": false,
+                    "reason": "channel size 50000 sat is below the minimum of 100000 sat",
+                }),
+            ),
+            EventType::HtlcIntercepted => (
+                EventSeverity::Warning,
+                "Forward Failed",
+                format!(
+                    "Failed 2500000 msat forward from {sample_pubkey}: forwards from this peer are blocked"
+                ),
+                serde_json::json!({
+                    "resumed": false,
+                    "payment_hash": sample_hash,
+                    "incoming_peer": sample_pubkey,
+                    "incoming_chan_id": 834_567_890_123_456_u64,
+                    "outgoing_chan_id": 834_567_890_654_321_u64,
+                    "incoming_amount_msat": 2_501_000,
+                    "outgoing_amount_msat": 2_500_000,
+                    "reason": "forwards from this peer are blocked",
+                }),
+            ),
+            EventType::SwapUpdated => (
+                EventSeverity::Info,
+                "Swap Completed",
+                "Reverse swap of 500000 sats completed: invoice.settled".to_string(),
+                serde_json::json!({
+                    "swap_id": Uuid::now_v7().to_string(),
+                    "boltz_swap_id": "Xg9s2vTz8QkM",
+                    "swap_type": "reverse",
+                    "status": "invoice.settled",
+                    "amount_sat": 500_000,
+                    "total_fee_sat": 2_610,
+                }),
+            ),
+            EventType::ForwardFailed => (
+                EventSeverity::Info,
+                "Forward Failed",
+                "Forward out through channel 834567890654321 failed: TemporaryChannelFailure"
+                    .to_string(),
+                serde_json::json!({
+                    "incoming_chan_id": 834_567_890_123_456_u64,
+                    "outgoing_chan_id": 834_567_890_654_321_u64,
+                    "timestamp_ns": Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+                    "outgoing_amt_msat": 2_500_000,
+                    "link_failure": false,
+                    "reason": "TemporaryChannelFailure",
+                }),
+            ),
+            EventType::SubscriptionDegraded => (
+                EventSeverity::Warning,
+                "Event Subscription Degraded",
+                "Dropped 57 event(s) because the buffer of 32 events was full".to_string(),
+                serde_json::json!({
+                    "dropped_events": 57,
+                    "buffer_capacity": 32,
+                    "overflow_policy": "drop_oldest",
+                }),
+            ),
+            EventType::BlockConnected => (
+                EventSeverity::Info,
+                "Block Connected",
+                "Block 905123 connected; node is in sync".to_string(),
+                serde_json::json!({
+                    "height": 905_123,
+                    "block_hash": "00000000000000000001a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f7",
+                    "node_height": 905_123,
+                    "sync_lag": 0,
+                }),
+            ),
+            EventType::ChannelConfirmation => (
+                EventSeverity::Info,
+                "Channel Funding Confirmed",
+                "Funding transaction of the channel with sample-peer has 3 confirmations; the channel is usable"
+                    .to_string(),
+                serde_json::json!({
+                    "kind": "opening",
+                    "txid": sample_hash,
+                    "channel_point": format!("{sample_hash}:0"),
+                    "remote_pubkey": sample_pubkey,
+                    "confirmations": 3,
+                    "confirmation_height": 905_121,
+                }),
+            ),
+            EventType::PeerAnnouncementChanged => (
+                EventSeverity::Warning,
+                "Peer Moved to Tor Only",
+                "sample-peer now announces only Tor addresses".to_string(),
+                serde_json::json!({
+                    "peer_pubkey": sample_pubkey,
+                    "previous_alias": "sample-peer",
+                    "alias": "sample-peer",
+                    "added_addresses": ["sampleaddressxyz.onion:9735"],
+                    "removed_addresses": ["203.0.113.7:9735"],
+                    "added_features": [],
+                    "removed_features": [],
+                    "tor_only": true,
+                }),
+            ),
+            EventType::PaymentLatencyDegraded => (
+                EventSeverity::Warning,
+                "Payment Latency Degraded",
+                "Payments took 9400 ms to settle at p95 over the last day, up from 3100 ms"
+                    .to_string(),
+                serde_json::json!({
+                    "recent_p95_ms": 9_400,
+                    "baseline_p95_ms": 3_100,
+                    "recent_samples": 42,
+                    "baseline_samples": 310,
+                }),
+            ),
+            EventType::ChannelBalanceChanged => (
+                EventSeverity::Info,
+                "Channel Balance Changed",
+                "Local balance of channel 834567890123456 fell by 2501000 msat".to_string(),
+                serde_json::json!({
+                    "chan_id": 834_567_890_123_456_u64,
+                    "delta_msat": -2_501_000,
+                    "causes": ["forward", "payment"],
+                    "htlcs": 2,
+                }),
+            ),
+            EventType::InvoicePaymentMismatch => (
+                EventSeverity::Warning,
+                "Invoice Overpaid",
+                "Invoice for 21000000 msat was paid 42000000 msat".to_string(),
+                serde_json::json!({
+                    "hash": sample_hash,
+                    "value_msat": 21_000_000,
+                    "amount_paid_msat": 42_000_000,
+                    "mismatch": "overpaid",
+                }),
+            ),
+            EventType::KeysendReceived => (
+                EventSeverity::Info,
+                "Keysend Received",
+                "Received 21000000 msat via keysend: thanks for the routing".to_string(),
+                serde_json::json!({
+                    "payment_hash": sample_hash,
+                    "amount_msat": 21_000_000,
+                    "message": "thanks for the routing",
+                }),
+            ),
+            EventType::RouteProbeFailed => (
+                EventSeverity::Warning,
+                "Route Probe Failed",
+                format!("Probes of 50000 sats no longer reach {sample_pubkey}: FailureReasonNoRoute"),
+                serde_json::json!({
+                    "destination": sample_pubkey,
+                    "outgoing_channel_id": "834567890123456",
+                    "amount_sat": 50_000,
+                    "failure_reason": "FailureReasonNoRoute",
+                }),
+            ),
+            EventType::WatchedNodeChannelOpened => (
+                EventSeverity::Info,
+                "Watched Node Opened a Channel",
+                format!("rival opened channel 834567890123456 with {sample_pubkey} (5000000 sats)"),
+                serde_json::json!({
+                    "watched_pubkey": sample_pubkey,
+                    "channel": {
+                        "chan_id": "834567890123456",
+                        "peer_pubkey": sample_pubkey,
+                        "capacity_sat": 5_000_000,
+                        "fee_base_msat": 1_000,
+                        "fee_rate_ppm": 250,
+                        "disabled": false,
+                    },
+                }),
+            ),
+            EventType::WatchedNodeChannelClosed => (
+                EventSeverity::Info,
+                "Watched Node Closed a Channel",
+                format!("rival closed channel 834567890123456 with {sample_pubkey} (5000000 sats)"),
+                serde_json::json!({
+                    "watched_pubkey": sample_pubkey,
+                    "channel": {
+                        "chan_id": "834567890123456",
+                        "peer_pubkey": sample_pubkey,
+                        "capacity_sat": 5_000_000,
+                        "fee_base_msat": 1_000,
+                        "fee_rate_ppm": 250,
+                        "disabled": false,
+                    },
+                }),
+            ),
+            EventType::WatchedNodeFeeChanged => (
+                EventSeverity::Info,
+                "Watched Node Changed Fees",
+                format!(
+                    "rival changed its fees towards {sample_pubkey} on channel 834567890123456 from 1000 msat + 250 ppm to 1000 msat + 100 ppm"
+                ),
+                serde_json::json!({
+                    "watched_pubkey": sample_pubkey,
+                    "previous": {
+                        "chan_id": "834567890123456",
+                        "peer_pubkey": sample_pubkey,
+                        "capacity_sat": 5_000_000,
+                        "fee_base_msat": 1_000,
+                        "fee_rate_ppm": 250,
+                        "disabled": false,
+                    },
+                    "channel": {
+                        "chan_id": "834567890123456",
+                        "peer_pubkey": sample_pubkey,
+                        "capacity_sat": 5_000_000,
+                        "fee_base_msat": 1_000,
+                        "fee_rate_ppm": 100,
+                        "disabled": false,
+                    },
+                }),
+            ),
+            EventType::WatchedNodeAnnouncementChanged => (
+                EventSeverity::Info,
+                "Watched Node Announcement Changed",
+                "rival v2: alias changed from 'rival' to 'rival v2'".to_string(),
+                serde_json::json!({
+                    "watched_pubkey": sample_pubkey,
+                    "previous_alias": "rival",
+                    "alias": "rival v2",
+                    "added_addresses": [],
+                    "removed_addresses": [],
+                    "added_features": [],
+                    "removed_features": [],
+                    "tor_only": false,
+                }),
+            ),
+            EventType::NotificationEndpointUnhealthy => (
+                EventSeverity::Warning,
+                "Notification Endpoint Unhealthy",
+                "Notification endpoint 'ops webhook' has been failing since 2025-09-09 08:00 UTC: Could not connect to endpoint".to_string(),
+                serde_json::json!({
+                    "notification_id": "0198d2a4-5f3e-7c21-9a4b-6f1e2d3c4b5a",
+                    "notification_name": "ops webhook",
+                    "notification_type": "Webhook",
+                    "failing_since": "2025-09-09T08:00:00Z",
+                    "last_error": "Could not connect to endpoint",
+                }),
+            ),
+        };
 
-        Ok((info_count, warning_count, critical_count))
+        let now = Utc::now();
+        Event {
+            id: Uuid::now_v7().to_string(),
+            account_id: account_id.to_string(),
+            user_id: user_id.to_string(),
+            node_id: sample_pubkey,
+            node_alias: "sample-node".to_string(),
+            event_type,
+            severity,
+            title: title.to_string(),
+            description,
+            data: data.to_string(),
+            notifications_id: None,
+            timestamp: now,
+            created_at: now,
+            updated_at: now,
+            is_deleted: false,
+            deleted_at: None,
+        }
     }
 
-    /// Processes a Lightning node event and creates a standardized event.
-    pub async fn process_lightning_event(
+    /// Converts a Lightning node event into a standardized event, ready to be
+    /// written with [`Self::create_and_dispatch_events`]. Settled forwards
+    /// aren't events and give None.
+    pub fn build_lightning_event(
         &self,
-        pool: &SqlitePool,
         account_id: String,
         user_id: String,
         node_id: String,
         node_alias: String,
-        lightning_event: &crate::services::event_manager::NodeSpecificEvent,
-    ) -> ServiceResult<Event> {
-        let (event_type, severity, title, description, data) = match lightning_event {
-            crate::services::event_manager::NodeSpecificEvent::LND(lnd_event) => {
-                self.process_lnd_event(lnd_event)
-            }
-            crate::services::event_manager::NodeSpecificEvent::CLN(cln_event) => {
-                self.process_cln_event(cln_event)
-            }
-        };
+        lightning_event: &LightningEvent,
+    ) -> Option<CreateEvent> {
+        let (event_type, severity, title, description, data) =
+            Self::process_lightning_event(lightning_event)?;
 
-        self.create_and_dispatch_event(CreateEvent {
+        Some(CreateEvent {
             id: Uuid::now_v7().to_string(),
             account_id,
             user_id,
@@ -189,22 +722,15 @@ impl<'a> EventService<'a> {
             notifications_id: None,
             timestamp: Utc::now(),
         })
-        .await
     }
 
-    /// Processes LND-specific events.v4
-    fn process_lnd_event(
-        &self,
-        lnd_event: &crate::services::event_manager::LNDEvent,
-    ) -> (
-        EventType,
-        EventSeverity,
-        String,
-        String,
-        HashMap<String, Value>,
-    ) {
-        match lnd_event {
-            crate::services::event_manager::LNDEvent::ChannelOpened {
+    /// Maps a node event to the type, severity, title, description and data
+    /// it is stored with. Settled forwards aren't events and give None.
+    fn process_lightning_event(
+        event: &LightningEvent,
+    ) -> Option<(EventType, EventSeverity, String, String, EventPayload)> {
+        Some(match event {
+            LightningEvent::ChannelOpened {
                 active,
                 remote_pubkey,
                 channel_point,
@@ -219,37 +745,19 @@ impl<'a> EventService<'a> {
                 EventSeverity::Info,
                 "Channel Opened".to_string(),
                 format!("New channel opened with {remote_pubkey}"),
-                HashMap::from([
-                    ("active".to_string(), Value::Bool(*active)),
-                    ("channel_id".to_string(), Value::Number((*chan_id).into())),
-                    (
-                        "counterparty_node_id".to_string(),
-                        Value::String(remote_pubkey.clone()),
-                    ),
-                    (
-                        "channel_point".to_string(),
-                        Value::String((channel_point).clone()),
-                    ),
-                    ("capacity".to_string(), Value::Number((*capacity).into())),
-                    (
-                        "local_balance".to_string(),
-                        Value::Number((*local_balance).into()),
-                    ),
-                    (
-                        "remote_balance".to_string(),
-                        Value::Number((*remote_balance).into()),
-                    ),
-                    (
-                        "total_satoshis_sent".to_string(),
-                        Value::Number((*total_satoshis_sent).into()),
-                    ),
-                    (
-                        "total_satoshis_received".to_string(),
-                        Value::Number((*total_satoshis_received).into()),
-                    ),
-                ]),
+                EventPayload::ChannelOpened(ChannelOpenedData {
+                    active: *active,
+                    channel_id: *chan_id,
+                    counterparty_node_id: remote_pubkey.clone(),
+                    channel_point: channel_point.clone(),
+                    capacity: *capacity,
+                    local_balance: *local_balance,
+                    remote_balance: *remote_balance,
+                    total_satoshis_sent: *total_satoshis_sent,
+                    total_satoshis_received: *total_satoshis_received,
+                }),
             ),
-            crate::services::event_manager::LNDEvent::ChannelClosed {
+            LightningEvent::ChannelClosed {
                 channel_point,
                 chan_id,
                 chain_hash,
@@ -267,233 +775,315 @@ impl<'a> EventService<'a> {
                 EventSeverity::Warning,
                 "Channel Closed".to_string(),
                 format!("Channel closed with {remote_pubkey}"),
-                HashMap::from([
-                    ("chan_id".to_string(), Value::Number((*chan_id).into())),
-                    (
-                        "remote_pubkey".to_string(),
-                        Value::String(remote_pubkey.clone()),
-                    ),
-                    (
-                        "channel_point".to_string(),
-                        Value::String((channel_point).clone()),
-                    ),
-                    ("chain_hash".to_string(), Value::String(chain_hash.clone())),
-                    (
-                        "closing_tx_hash".to_string(),
-                        Value::String(closing_tx_hash.clone()),
-                    ),
-                    ("capacity".to_string(), Value::Number((*capacity).into())),
-                    (
-                        "close_height".to_string(),
-                        Value::Number((*close_height).into()),
-                    ),
-                    (
-                        "settled_balance".to_string(),
-                        Value::Number((*settled_balance).into()),
-                    ),
-                    (
-                        "time_locked_balance".to_string(),
-                        Value::Number((*time_locked_balance).into()),
+                EventPayload::ChannelClosed(ChannelClosedData {
+                    chan_id: *chan_id,
+                    remote_pubkey: remote_pubkey.clone(),
+                    channel_point: channel_point.clone(),
+                    chain_hash: chain_hash.clone(),
+                    closing_tx_hash: closing_tx_hash.clone(),
+                    capacity: *capacity,
+                    close_height: *close_height,
+                    settled_balance: *settled_balance,
+                    time_locked_balance: *time_locked_balance,
+                    close_type: *close_type,
+                    open_initiator: *open_initiator,
+                    close_initiator: *close_initiator,
+                }),
+            ),
+            LightningEvent::InvoiceCreated(invoice)
+            | LightningEvent::InvoiceSettled(invoice)
+            | LightningEvent::InvoiceCancelled(invoice)
+            | LightningEvent::InvoiceAccepted(invoice) => {
+                let (event_type, severity, title, description) = match event {
+                    LightningEvent::InvoiceCreated(_) => (
+                        EventType::InvoiceCreated,
+                        EventSeverity::Info,
+                        "Invoice Created",
+                        "New invoice created",
                     ),
-                    (
-                        "close_type".to_string(),
-                        Value::Number((*close_type).into()),
+                    LightningEvent::InvoiceSettled(_) => (
+                        EventType::InvoiceSettled,
+                        EventSeverity::Info,
+                        "Invoice Settled",
+                        "Invoice settled",
                     ),
-                    (
-                        "open_initiator".to_string(),
-                        Value::Number((*open_initiator).into()),
+                    LightningEvent::InvoiceCancelled(_) => (
+                        EventType::InvoiceCancelled,
+                        EventSeverity::Warning,
+                        "Invoice Cancelled",
+                        "Invoice cancelled",
                     ),
-                    (
-                        "close_initiator".to_string(),
-                        Value::Number((*close_initiator).into()),
+                    _ => (
+                        EventType::InvoiceAccepted,
+                        EventSeverity::Info,
+                        "Invoice Accepted",
+                        "Invoice accepted",
                     ),
-                ]),
+                };
+                (
+                    event_type,
+                    severity,
+                    title.to_string(),
+                    format!("{description} for {} msat", invoice.value_msat),
+                    EventPayload::Invoice(InvoiceEventData {
+                        preimage: hex::encode(&invoice.preimage),
+                        hash: hex::encode(&invoice.hash),
+                        value_msat: invoice.value_msat,
+                        state: invoice.state,
+                        memo: invoice.memo.clone(),
+                        creation_date: invoice.creation_date,
+                        payment_request: invoice.payment_request.clone(),
+                    }),
+                )
+            }
+            LightningEvent::PaymentSent {
+                payment_hash,
+                part_id,
+                group_id,
+                amount_msat,
+                amount_sent_msat,
+                destination,
+            } => (
+                EventType::PaymentSent,
+                EventSeverity::Info,
+                "Payment Sent".to_string(),
+                format!("Payment sent for {amount_msat} msat"),
+                EventPayload::Payment(PaymentEventData {
+                    payment_hash: payment_hash.clone(),
+                    part_id: *part_id,
+                    group_id: *group_id,
+                    amount_msat: *amount_msat,
+                    fee_msat: Some(amount_sent_msat.saturating_sub(*amount_msat)),
+                    destination: destination.clone(),
+                    reason: None,
+                }),
             ),
-            crate::services::event_manager::LNDEvent::InvoiceCreated {
-                preimage,
-                hash,
-                value_msat,
-                state,
-                memo,
-                creation_date,
-                payment_request,
+            LightningEvent::PaymentFailed {
+                payment_hash,
+                part_id,
+                group_id,
+                amount_msat,
+                destination,
+                reason,
+            } => (
+                EventType::PaymentFailed,
+                EventSeverity::Warning,
+                "Payment Failed".to_string(),
+                format!("Payment of {amount_msat} msat failed: {reason}"),
+                EventPayload::Payment(PaymentEventData {
+                    payment_hash: payment_hash.clone(),
+                    part_id: *part_id,
+                    group_id: *group_id,
+                    amount_msat: *amount_msat,
+                    fee_msat: None,
+                    destination: destination.clone(),
+                    reason: Some(reason.clone()),
+                }),
+            ),
+            LightningEvent::ForwardFailed {
+                incoming_chan_id,
+                outgoing_chan_id,
+                timestamp_ns,
+                outgoing_amt_msat,
+                link_failure,
+                reason,
             } => (
-                EventType::InvoiceCreated,
+                EventType::ForwardFailed,
                 EventSeverity::Info,
-                "Invoice Created".to_string(),
-                format!("New invoice created for {value_msat} msat"),
-                HashMap::from([
-                    ("preimage".to_string(), Value::String(hex::encode(preimage))),
-                    ("hash".to_string(), Value::String(hex::encode(hash))),
-                    (
-                        "value_msat".to_string(),
-                        Value::Number((*value_msat).into()),
-                    ),
-                    ("state".to_string(), Value::Number((*state).into())),
-                    ("memo".to_string(), Value::String(memo.clone())),
-                    (
-                        "creation_date".to_string(),
-                        Value::Number((*creation_date).into()),
-                    ),
-                    (
-                        "payment_request".to_string(),
-                        Value::String(payment_request.clone()),
-                    ),
-                ]),
+                "Forward Failed".to_string(),
+                format!("Forward out through channel {outgoing_chan_id} failed: {reason}"),
+                EventPayload::ForwardFailed(ForwardFailedData {
+                    incoming_chan_id: *incoming_chan_id,
+                    outgoing_chan_id: *outgoing_chan_id,
+                    timestamp_ns: *timestamp_ns,
+                    outgoing_amt_msat: *outgoing_amt_msat,
+                    link_failure: *link_failure,
+                    reason: reason.clone(),
+                }),
             ),
-            crate::services::event_manager::LNDEvent::InvoiceSettled {
-                preimage,
-                hash,
-                value_msat,
-                state,
-                memo,
-                creation_date,
-                payment_request,
+            LightningEvent::ChannelBalanceChanged {
+                chan_id,
+                delta_msat,
+                causes,
+                htlcs,
             } => (
-                EventType::InvoiceSettled,
+                EventType::ChannelBalanceChanged,
                 EventSeverity::Info,
-                "Invoice Settled".to_string(),
-                format!("Invoice settled for {value_msat} msat"),
-                HashMap::from([
-                    ("preimage".to_string(), Value::String(hex::encode(preimage))),
-                    ("hash".to_string(), Value::String(hex::encode(hash))),
-                    (
-                        "value_msat".to_string(),
-                        Value::Number((*value_msat).into()),
-                    ),
-                    ("state".to_string(), Value::Number((*state).into())),
-                    ("memo".to_string(), Value::String(memo.clone())),
-                    (
-                        "creation_date".to_string(),
-                        Value::Number((*creation_date).into()),
-                    ),
-                    (
-                        "payment_request".to_string(),
-                        Value::String(payment_request.clone()),
-                    ),
-                ]),
+                "Channel Balance Changed".to_string(),
+                format!(
+                    "Local balance of channel {chan_id} {} by {} msat",
+                    if *delta_msat < 0 { "fell" } else { "rose" },
+                    delta_msat.unsigned_abs()
+                ),
+                EventPayload::ChannelBalanceChanged(ChannelBalanceChangedData {
+                    chan_id: *chan_id,
+                    delta_msat: *delta_msat,
+                    causes: causes.clone(),
+                    htlcs: *htlcs,
+                }),
             ),
-            crate::services::event_manager::LNDEvent::InvoiceCancelled {
-                preimage,
+            LightningEvent::InvoicePaymentMismatch {
                 hash,
                 value_msat,
-                state,
-                memo,
-                creation_date,
-                payment_request,
+                amount_paid_msat,
+                mismatch,
             } => (
-                EventType::InvoiceCancelled,
+                EventType::InvoicePaymentMismatch,
                 EventSeverity::Warning,
-                "Invoice Cancelled".to_string(),
-                format!("Invoice cancelled for {value_msat} msat"),
-                HashMap::from([
-                    ("preimage".to_string(), Value::String(hex::encode(preimage))),
-                    ("hash".to_string(), Value::String(hex::encode(hash))),
-                    (
-                        "value_msat".to_string(),
-                        Value::Number((*value_msat).into()),
-                    ),
-                    ("state".to_string(), Value::Number((*state).into())),
-                    ("memo".to_string(), Value::String(memo.clone())),
-                    (
-                        "creation_date".to_string(),
-                        Value::Number((*creation_date).into()),
-                    ),
-                    (
-                        "payment_request".to_string(),
-                        Value::String(payment_request.clone()),
-                    ),
-                ]),
+                match mismatch {
+                    PaymentMismatch::Overpaid => "Invoice Overpaid",
+                    PaymentMismatch::Underpaid => "Invoice Underpaid",
+                }
+                .to_string(),
+                format!("Invoice for {value_msat} msat was paid {amount_paid_msat} msat"),
+                EventPayload::InvoicePaymentMismatch(InvoicePaymentMismatchData {
+                    hash: hash.clone(),
+                    value_msat: *value_msat,
+                    amount_paid_msat: *amount_paid_msat,
+                    mismatch: *mismatch,
+                }),
             ),
-            crate::services::event_manager::LNDEvent::InvoiceAccepted {
-                preimage,
-                hash,
-                value_msat,
-                state,
-                memo,
-                creation_date,
-                payment_request,
+            LightningEvent::KeysendReceived {
+                payment_hash,
+                amount_msat,
+                message,
             } => (
-                EventType::InvoiceAccepted,
+                EventType::KeysendReceived,
                 EventSeverity::Info,
-                "Invoice Accepted".to_string(),
-                format!("Invoice accepted for {value_msat} msat"),
-                HashMap::from([
-                    ("preimage".to_string(), Value::String(hex::encode(preimage))),
-                    ("hash".to_string(), Value::String(hex::encode(hash))),
-                    (
-                        "value_msat".to_string(),
-                        Value::Number((*value_msat).into()),
-                    ),
-                    ("state".to_string(), Value::Number((*state).into())),
-                    ("memo".to_string(), Value::String(memo.clone())),
-                    (
-                        "creation_date".to_string(),
-                        Value::Number((*creation_date).into()),
-                    ),
-                    (
-                        "payment_request".to_string(),
-                        Value::String(payment_request.clone()),
-                    ),
-                ]),
+                "Keysend Received".to_string(),
+                match message {
+                    Some(message) => format!("Received {amount_msat} msat via keysend: {message}"),
+                    None => format!("Received {amount_msat} msat via keysend"),
+                },
+                EventPayload::KeysendReceived(KeysendReceivedData {
+                    payment_hash: payment_hash.clone(),
+                    amount_msat: *amount_msat,
+                    message: message.clone(),
+                }),
             ),
-        }
+            LightningEvent::ForwardSettled(_) => return None,
+        })
     }
+}
 
-    /// Processes CLN-specific events.
-    fn process_cln_event(
-        &self,
-        cln_event: &crate::services::event_manager::CLNEvent,
-    ) -> (
-        EventType,
-        EventSeverity,
-        String,
-        String,
-        HashMap<String, Value>,
-    ) {
-        match cln_event {
-            crate::services::event_manager::CLNEvent::ChannelOpened {} => (
-                EventType::ChannelOpened,
-                EventSeverity::Info,
-                "Channel Opened".to_string(),
-                "New channel opened".to_string(),
-                HashMap::new(),
-            ),
-            // crate::services::event_manager::CLNEvent::ChannelClosed {} => (
-            //     EventType::ChannelClosed,
-            //     EventSeverity::Warning,
-            //     "Channel Closed".to_string(),
-            //     "Channel closed".to_string(),
-            //     HashMap::new(),
-            // ),
-            // crate::services::event_manager::CLNEvent::InvoiceSettled {} => (
-            //     EventType::InvoiceSettled,
-            //     EventSeverity::Info,
-            //     "Invoice Settled".to_string(),
-            //     "Invoice has been settled".to_string(),
-            //     HashMap::new(),
-            // ),
-            // crate::services::event_manager::CLNEvent::InvoiceCreated {} => (
-            //     EventType::InvoiceCreated,
-            //     EventSeverity::Info,
-            //     "Invoice Created".to_string(),
-            //     "New invoice created".to_string(),
-            //     HashMap::new(),
-            // ),
-            // crate::services::event_manager::CLNEvent::InvoiceCancelled {} => (
-            //     EventType::InvoiceCancelled,
-            //     EventSeverity::Warning,
-            //     "Invoice Cancelled".to_string(),
-            //     "Invoice has been cancelled".to_string(),
-            //     HashMap::new(),
-            // ),
-            // crate::services::event_manager::CLNEvent::InvoiceAccepted {} => (
-            //     EventType::InvoiceAccepted,
-            //     EventSeverity::Info,
-            //     "Invoice Accepted".to_string(),
-            //     "Invoice has been accepted".to_string(),
-            //     HashMap::new(),
-            // ),
+/// Failed forwards are routine on a routing node; they are kept for the
+/// failure heatmap without notifying anyone.
+fn is_quiet(event_type: &EventType) -> bool {
+    *event_type == EventType::ForwardFailed
+}
+
+/// Sends every stored event to its notification endpoints, and starts the
+/// escalations of critical ones. Quiet events only have endpoint rows for
+/// firehoses.
+async fn dispatch_events(pool: &SqlitePool, dispatcher: &NotificationDispatcher, events: &[Event]) {
+    for event in events
+        .iter()
+        .filter(|event| !is_quiet(&event.event_type) || event.notifications_id.is_some())
+    {
+        if let Err(e) = dispatcher.dispatch_event(pool, event).await {
+            tracing::error!("Failed to dispatch event notifications: {}", e);
         }
     }
+    start_escalations(pool, events).await;
+}
+
+/// Builds an FTS5 query from free text: every term is quoted (so operators and
+/// punctuation in user input are taken literally) and matched as a prefix.
+fn fts_match_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{term}\"*"))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fts_match_query_quotes_and_prefixes_terms() {
+        assert_eq!(fts_match_query("03ab").as_deref(), Some("\"03ab\"*"));
+        assert_eq!(
+            fts_match_query("  channel  OR \"closed\" ").as_deref(),
+            Some("\"channel\"* \"OR\"* \"closed\"*")
+        );
+        assert_eq!(fts_match_query("  \"\" "), None);
+    }
+
+    #[test]
+    fn test_process_lightning_event_invoice() {
+        let invoice = crate::services::event_manager::InvoiceEvent {
+            preimage: Vec::new(),
+            hash: vec![0xab],
+            value_msat: 1_000,
+            state: 3,
+            memo: "coffee".to_string(),
+            creation_date: 0,
+            payment_request: String::new(),
+        };
+        let (event_type, severity, _, description, data) =
+            EventService::process_lightning_event(&LightningEvent::InvoiceAccepted(invoice))
+                .unwrap();
+
+        assert_eq!(event_type, EventType::InvoiceAccepted);
+        assert_eq!(severity, EventSeverity::Info);
+        assert_eq!(description, "Invoice accepted for 1000 msat");
+        let EventPayload::Invoice(data) = data else {
+            panic!("expected an invoice payload");
+        };
+        assert_eq!(data.hash, "ab");
+    }
+
+    #[test]
+    fn test_event_data_keeps_added_keys() {
+        let stored = r#"{"incoming_chan_id":1,"outgoing_chan_id":2,"timestamp_ns":0,"outgoing_amt_msat":null,"link_failure":true,"reason":"x","node_label":{"display_name":"alice"}}"#;
+        let data = crate::database::models::EventData::parse(&EventType::ForwardFailed, stored);
+
+        assert!(matches!(data.payload, EventPayload::ForwardFailed(_)));
+        assert_eq!(data.extra.keys().collect::<Vec<_>>(), ["node_label"]);
+        let round_trip = serde_json::to_value(&data).unwrap();
+        assert_eq!(
+            round_trip,
+            serde_json::from_str::<serde_json::Value>(stored).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_event_stats_folds_grouped_counts() {
+        let row = |severity, event_type, node_id: &str, count| EventCountRow {
+            severity,
+            event_type,
+            node_id: node_id.to_string(),
+            node_alias: format!("{node_id}-alias"),
+            count,
+        };
+        let to = Utc::now();
+        let from = to - Duration::days(30);
+        let stats = event_stats(
+            vec![
+                row(EventSeverity::Info, EventType::InvoiceSettled, "node-a", 5),
+                row(
+                    EventSeverity::Warning,
+                    EventType::ChannelClosed,
+                    "node-a",
+                    1,
+                ),
+                row(EventSeverity::Info, EventType::InvoiceSettled, "node-b", 7),
+            ],
+            from,
+            to,
+        );
+
+        assert_eq!(stats.total, 13);
+        let severities: Vec<i64> = stats.by_severity.iter().map(|e| e.count).collect();
+        assert_eq!(severities, vec![0, 1, 12]);
+        assert_eq!(stats.by_event_type[0].event_type, EventType::InvoiceSettled);
+        assert_eq!(stats.by_event_type[0].count, 12);
+        assert_eq!(stats.by_node[0].node_id, "node-b");
+        assert_eq!(stats.by_node[1].count, 6);
+    }
 }
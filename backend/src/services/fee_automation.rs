@@ -0,0 +1,320 @@
+//! Balance-driven fee automation, in the spirit of charge-lnd.
+//!
+//! Operators give each node an ordered list of rules such as "below 20% local
+//! balance charge 2500 ppm, above 80% charge 50 ppm". The `FeeAutomation`
+//! scheduled task walks the node's active channels, picks the first rule that
+//! matches each one and updates the channel's fees when they differ. Every
+//! change lands in `fee_policy_changes`; in dry-run mode changes are only
+//! recorded, never applied.
+
+use crate::database::models::{
+    FeeAutomationPolicy, FeePolicyChange, FeeRule, UpdateFeeAutomationRequest,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::fee_automation_repository::FeeAutomationRepository;
+use crate::utils::ChannelState;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use bitcoin::secp256k1::PublicKey;
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::str::FromStr;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Base fee set when a rule has none and the channel's current one is unknown
+const DEFAULT_BASE_FEE_MSAT: u64 = 1_000;
+
+/// Change history entries returned when no limit is given
+const DEFAULT_HISTORY_LIMIT: i64 = 100;
+const MAX_HISTORY_LIMIT: i64 = 1_000;
+
+/// Returns the first rule matching a channel with `local_pct` percent of its
+/// capacity on our side, along with its position.
+pub fn match_rule(rules: &[FeeRule], local_pct: f64) -> Option<(usize, &FeeRule)> {
+    rules.iter().enumerate().find(|(_, rule)| {
+        rule.min_local_pct.is_none_or(|min| local_pct >= min)
+            && rule.max_local_pct.is_none_or(|max| local_pct < max)
+    })
+}
+
+/// Service layer for fee automation policies.
+pub struct FeeAutomationService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> FeeAutomationService<'a> {
+    /// Creates a new FeeAutomationService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns a node's policy. Nodes without one keep whatever fees they have.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<FeeAutomationPolicy> {
+        let policy = FeeAutomationRepository::new(self.pool)
+            .get_policy(account_id, node_id)
+            .await?;
+
+        Ok(policy.unwrap_or_else(|| FeeAutomationPolicy {
+            account_id: account_id.to_string(),
+            node_id: node_id.to_string(),
+            is_enabled: false,
+            dry_run: false,
+            rules: "[]".to_string(),
+            updated_by: user_id.to_string(),
+            updated_at: None,
+        }))
+    }
+
+    /// Replaces a node's policy. It takes effect on the next scheduled run.
+    pub async fn update_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: UpdateFeeAutomationRequest,
+    ) -> ServiceResult<FeeAutomationPolicy> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        if request.rules.iter().any(|rule| {
+            matches!((rule.min_local_pct, rule.max_local_pct), (Some(min), Some(max)) if min >= max)
+        }) {
+            return Err(ServiceError::validation(
+                "A rule's minimum local balance must be below its maximum",
+            ));
+        }
+
+        CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+
+        let policy = FeeAutomationRepository::new(self.pool)
+            .upsert_policy(&FeeAutomationPolicy {
+                account_id: account_id.to_string(),
+                node_id: node_id.to_string(),
+                is_enabled: request.is_enabled,
+                dry_run: request.dry_run,
+                rules: json!(request.rules).to_string(),
+                updated_by: user_id.to_string(),
+                updated_at: None,
+            })
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "fee_automation_updated",
+                "node",
+                Some(node_id),
+                &json!({
+                    "is_enabled": policy.is_enabled,
+                    "dry_run": policy.dry_run,
+                    "rules": policy.rules(),
+                }),
+            )
+            .await?;
+
+        Ok(policy)
+    }
+
+    /// Runs a node's rules immediately, honouring its dry-run setting.
+    pub async fn run_now(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_credentials: &NodeCredentials,
+    ) -> ServiceResult<Vec<FeePolicyChange>> {
+        let policy = self
+            .get_policy(account_id, user_id, &node_credentials.node_id)
+            .await?;
+        if policy.rules().is_empty() {
+            return Err(ServiceError::invalid_operation(
+                "The node has no fee automation rules",
+            ));
+        }
+
+        run_policy(self.pool, &policy, node_credentials)
+            .await
+            .map_err(ServiceError::external_service)
+    }
+
+    /// Lists a node's fee changes, newest first.
+    pub async fn get_history(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        limit: Option<i64>,
+    ) -> ServiceResult<Vec<FeePolicyChange>> {
+        let limit = limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .clamp(1, MAX_HISTORY_LIMIT);
+
+        Ok(FeeAutomationRepository::new(self.pool)
+            .get_changes(account_id, node_id, limit)
+            .await?)
+    }
+}
+
+/// Runs the enabled policies of every node in the account.
+pub async fn run_account_fee_automation(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let policies = FeeAutomationRepository::new(pool)
+        .get_enabled_policies(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let credential_repo = CredentialRepository::new(pool);
+
+    let mut failures = Vec::new();
+    for policy in policies {
+        let credential = match credential_repo
+            .get_credential_by_account_and_node_id(account_id, &policy.node_id)
+            .await
+        {
+            Ok(Some(credential)) => credential,
+            Ok(None) => continue,
+            Err(e) => {
+                failures.push(format!("{}: {e}", policy.node_id));
+                continue;
+            }
+        };
+
+        match run_policy(pool, &policy, &NodeCredentials::from(credential)).await {
+            Ok(changes) if !changes.is_empty() => tracing::info!(
+                "Fee automation {} {} channel(s) on {}",
+                if policy.dry_run {
+                    "proposed changes to"
+                } else {
+                    "updated"
+                },
+                changes.len(),
+                policy.node_id
+            ),
+            Ok(_) => {}
+            Err(e) => failures.push(format!("{}: {e}", policy.node_id)),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Fee automation failed for {}", failures.join("; ")))
+    }
+}
+
+/// Applies a policy to every active channel of its node and records each change.
+async fn run_policy(
+    pool: &SqlitePool,
+    policy: &FeeAutomationPolicy,
+    node_credentials: &NodeCredentials,
+) -> Result<Vec<FeePolicyChange>, String> {
+    let rules = policy.rules();
+    let public_key = PublicKey::from_str(&node_credentials.node_id).map_err(|e| e.to_string())?;
+    let client = create_node_client(node_credentials, public_key)
+        .await
+        .map_err(|(_, body)| body)?;
+    let own_pubkey = client.get_info().pubkey;
+    let repo = FeeAutomationRepository::new(pool);
+
+    let mut changes = Vec::new();
+    for channel in client.list_channels().await.map_err(|e| e.to_string())? {
+        if !matches!(channel.channel_state, ChannelState::Active) || channel.capacity == 0 {
+            continue;
+        }
+        let local_pct = channel.local_balance as f64 * 100.0 / channel.capacity as f64;
+        let Some((rule_index, rule)) = match_rule(&rules, local_pct) else {
+            continue;
+        };
+
+        let details = client
+            .get_channel_info(&channel.chan_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let current = [&details.node1_policy, &details.node2_policy]
+            .into_iter()
+            .flatten()
+            .find(|node_policy| node_policy.pubkey == own_pubkey);
+        let old_base_fee_msat = current.map(|p| p.fee_base_msat);
+        let old_fee_rate_ppm = current.map(|p| p.fee_rate_milli_msat);
+
+        let new_base_fee_msat = rule
+            .base_fee_msat
+            .or(old_base_fee_msat)
+            .unwrap_or(DEFAULT_BASE_FEE_MSAT);
+        if old_base_fee_msat == Some(new_base_fee_msat)
+            && old_fee_rate_ppm == Some(rule.fee_rate_ppm as u64)
+        {
+            continue;
+        }
+
+        let error = if policy.dry_run {
+            None
+        } else {
+            client
+                .update_channel_fees(&details, new_base_fee_msat, rule.fee_rate_ppm)
+                .await
+                .err()
+                .map(|e| e.to_string())
+        };
+
+        let change = FeePolicyChange {
+            id: Uuid::now_v7().to_string(),
+            account_id: policy.account_id.clone(),
+            node_id: policy.node_id.clone(),
+            channel_id: channel.chan_id.to_string(),
+            rule_index: rule_index as i64,
+            local_balance_pct: local_pct,
+            old_base_fee_msat: old_base_fee_msat.map(|fee| fee as i64),
+            old_fee_rate_ppm: old_fee_rate_ppm.map(|rate| rate as i64),
+            new_base_fee_msat: new_base_fee_msat as i64,
+            new_fee_rate_ppm: rule.fee_rate_ppm as i64,
+            dry_run: policy.dry_run,
+            error,
+            created_at: Utc::now(),
+        };
+        repo.create_change(&change)
+            .await
+            .map_err(|e| e.to_string())?;
+        changes.push(change);
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(min: Option<f64>, max: Option<f64>, fee_rate_ppm: u32) -> FeeRule {
+        FeeRule {
+            min_local_pct: min,
+            max_local_pct: max,
+            fee_rate_ppm,
+            base_fee_msat: None,
+        }
+    }
+
+    #[test]
+    fn test_match_rule_picks_first_match() {
+        let rules = vec![
+            rule(None, Some(20.0), 2500),
+            rule(Some(80.0), None, 50),
+            rule(None, None, 500),
+        ];
+
+        assert_eq!(match_rule(&rules, 10.0).map(|(i, _)| i), Some(0));
+        assert_eq!(match_rule(&rules, 20.0).map(|(i, _)| i), Some(2));
+        assert_eq!(match_rule(&rules, 80.0).map(|(i, _)| i), Some(1));
+        assert_eq!(match_rule(&rules, 50.0).map(|(i, _)| i), Some(2));
+        assert!(match_rule(&rules[..2], 50.0).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+//! Fee position of a node's channels.
+//!
+//! Reads the graph mirror to place the fee rate of each of the node's
+//! channels within the whole network and within its corridor: the other
+//! nodes that also have a channel to the same peer, and so compete to
+//! forward into it. Channels priced well outside their corridor get a
+//! suggested rate, pulled towards the competitors' interquartile range.
+
+use crate::database::models::{ChannelFeePosition, FeePositionReport};
+use crate::errors::ServiceResult;
+use crate::repositories::graph_repository::GraphRepository;
+use crate::services::graph_sync::{GraphService, percentile};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// Fewer competitors than this say too little about a corridor's price
+const MIN_COMPETITORS: usize = 3;
+
+/// Share of an ascending list below `value`, 0-100.
+fn share_below(sorted: &[i64], value: i64) -> Option<f64> {
+    (!sorted.is_empty())
+        .then(|| sorted.partition_point(|rate| *rate < value) as f64 * 100.0 / sorted.len() as f64)
+}
+
+/// Suggests a fee rate for a channel charging `fee_rate_ppm`, given the
+/// ascending rates of its corridor competitors and of the whole network.
+/// Returns the rate and why, or None when the channel is priced in line.
+pub fn suggest_fee_rate(
+    fee_rate_ppm: i64,
+    competitors: &[i64],
+    network: &[i64],
+) -> Option<(i64, String)> {
+    if competitors.len() >= MIN_COMPETITORS {
+        let p25 = percentile(competitors, 25.0)?;
+        let p75 = percentile(competitors, 75.0)?;
+        if fee_rate_ppm > p75 {
+            return Some((
+                p75,
+                format!(
+                    "Charges more than 75% of the {} competitors into this peer",
+                    competitors.len()
+                ),
+            ));
+        }
+        if fee_rate_ppm < p25 {
+            return Some((
+                p25,
+                format!(
+                    "Charges less than 75% of the {} competitors into this peer",
+                    competitors.len()
+                ),
+            ));
+        }
+        return None;
+    }
+
+    let p75 = percentile(network, 75.0)?;
+    let p90 = percentile(network, 90.0)?;
+    (fee_rate_ppm > p90).then(|| {
+        (
+            p75,
+            "Charges more than 90% of the network, with too few competitors to compare".to_string(),
+        )
+    })
+}
+
+/// Service layer for the fee position analytics.
+pub struct FeePositionService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> FeePositionService<'a> {
+    /// Creates a new FeePositionService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Places each enabled channel of the node within the network and its
+    /// corridor, as seen in the node's graph mirror.
+    pub async fn get_fee_position(&self, node_id: &str) -> ServiceResult<FeePositionReport> {
+        let repo = GraphRepository::new(self.pool);
+        let network_rates = repo.get_fee_rates(node_id).await?;
+        let network = GraphService::new(self.pool)
+            .get_fee_percentiles(node_id)
+            .await?;
+
+        // Rates competitors charge to forward into each peer
+        let mut corridors: HashMap<String, Vec<i64>> = HashMap::new();
+        for (advertiser, peer, fee_rate_ppm, disabled) in
+            repo.get_directed_policies(node_id).await?
+        {
+            if !disabled && advertiser != node_id {
+                corridors.entry(peer).or_default().push(fee_rate_ppm);
+            }
+        }
+        for rates in corridors.values_mut() {
+            rates.sort_unstable();
+        }
+
+        let aliases: HashMap<String, String> = repo
+            .get_nodes(node_id)
+            .await?
+            .into_iter()
+            .map(|node| (node.pubkey, node.alias))
+            .collect();
+
+        let mut channels: Vec<ChannelFeePosition> = repo
+            .get_node_channels(node_id, node_id)
+            .await?
+            .into_iter()
+            .filter(|channel| channel.disabled == Some(false))
+            .filter_map(|channel| {
+                let fee_rate_ppm = channel.fee_rate_ppm?;
+                let competitors = corridors
+                    .get(&channel.peer_pubkey)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let suggestion = suggest_fee_rate(fee_rate_ppm, competitors, &network_rates);
+
+                Some(ChannelFeePosition {
+                    peer_alias: aliases.get(&channel.peer_pubkey).cloned(),
+                    chan_id: channel.chan_id,
+                    peer_pubkey: channel.peer_pubkey,
+                    fee_rate_ppm,
+                    network_percentile: share_below(&network_rates, fee_rate_ppm),
+                    competitor_count: competitors.len(),
+                    competitor_p25: percentile(competitors, 25.0),
+                    competitor_p50: percentile(competitors, 50.0),
+                    competitor_p75: percentile(competitors, 75.0),
+                    corridor_percentile: share_below(competitors, fee_rate_ppm),
+                    suggested_fee_rate_ppm: suggestion.as_ref().map(|(rate, _)| *rate),
+                    suggestion: suggestion.map(|(_, reason)| reason),
+                })
+            })
+            .collect();
+        channels.sort_by(|a, b| {
+            b.suggestion
+                .is_some()
+                .cmp(&a.suggestion.is_some())
+                .then_with(|| a.chan_id.cmp(&b.chan_id))
+        });
+
+        Ok(FeePositionReport { network, channels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_fee_rate() {
+        let competitors = [100, 200, 300, 400];
+        let network: Vec<i64> = (1..=10).map(|i| i * 100).collect();
+
+        assert_eq!(
+            suggest_fee_rate(900, &competitors, &network).unwrap().0,
+            300
+        );
+        assert_eq!(suggest_fee_rate(50, &competitors, &network).unwrap().0, 100);
+        assert!(suggest_fee_rate(250, &competitors, &network).is_none());
+
+        // Too few competitors falls back to the network as a whole.
+        assert_eq!(suggest_fee_rate(5000, &[100], &network).unwrap().0, 800);
+        assert!(suggest_fee_rate(500, &[100], &network).is_none());
+        assert!(suggest_fee_rate(500, &[], &[]).is_none());
+    }
+
+    #[test]
+    fn test_share_below() {
+        assert_eq!(share_below(&[100, 200, 300, 400], 300), Some(50.0));
+        assert_eq!(share_below(&[], 300), None);
+    }
+}
@@ -0,0 +1,290 @@
+//! Failed forwards: a per-peer heatmap and a drill-down list.
+//!
+//! LND nodes report every forward that fails downstream or on our outgoing
+//! link as a `ForwardFailed` event. The heatmap counts them per outgoing peer
+//! and UTC hour of day, which makes peers that fail at the same time every
+//! day (during their backups, say) stand out. The list shows each failure
+//! with its cause: failures for lack of outgoing liquidity call for a
+//! rebalance, while a flood of downstream failures may call for higher fees
+//! on the channel.
+
+use crate::database::models::{
+    FailureHeatmap, FailureHeatmapQuery, ForwardFailedData, ForwardFailure, ForwardFailureCause,
+    ForwardFailureCauseCounts, ForwardFailureList, ForwardFailureQuery, PeerFailureHeatmap,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::graph_repository::GraphRepository;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+const DEFAULT_HEATMAP_DAYS: i64 = 30;
+const MAX_HEATMAP_DAYS: i64 = 90;
+const DEFAULT_FAILURE_DAYS: i64 = 7;
+const DEFAULT_FAILURE_LIMIT: i64 = 100;
+const MAX_FAILURE_LIMIT: i64 = 1_000;
+
+/// Reasons LND gives for a link failure when the outgoing channel couldn't
+/// carry the HTLC, compared without case, spaces or underscores.
+const LIQUIDITY_REASONS: [&str; 3] = [
+    "insufficientbalance",
+    "insufficientbandwidth",
+    "temporarychannelfailure",
+];
+
+/// Tells whether a failure was ours to fix, and how.
+pub fn classify_failure(link_failure: bool, reason: &str) -> ForwardFailureCause {
+    if !link_failure {
+        return ForwardFailureCause::Downstream;
+    }
+    let reason: String = reason
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if LIQUIDITY_REASONS.iter().any(|r| reason.contains(r)) {
+        ForwardFailureCause::LocalLiquidity
+    } else {
+        ForwardFailureCause::LocalPolicy
+    }
+}
+
+/// Folds (chan_id, hour, count) rows into one row per peer, most failures
+/// first. Channels whose peer is unknown are kept as rows of their own.
+pub fn build_heatmap(
+    rows: Vec<(String, i64, i64)>,
+    channel_peers: &HashMap<String, String>,
+) -> Vec<PeerFailureHeatmap> {
+    let mut peers: HashMap<String, PeerFailureHeatmap> = HashMap::new();
+    for (chan_id, hour, count) in rows {
+        let Ok(hour) = usize::try_from(hour) else {
+            continue;
+        };
+        if hour >= 24 {
+            continue;
+        }
+        let peer_pubkey = channel_peers.get(&chan_id).cloned();
+        let entry = peers
+            .entry(peer_pubkey.clone().unwrap_or_else(|| chan_id.clone()))
+            .or_insert_with(|| PeerFailureHeatmap {
+                peer_pubkey,
+                peer_alias: None,
+                channel_ids: Vec::new(),
+                total_failures: 0,
+                hourly: vec![0; 24],
+                peak_hour: 0,
+            });
+        if !entry.channel_ids.contains(&chan_id) {
+            entry.channel_ids.push(chan_id);
+        }
+        entry.hourly[hour] += count;
+        entry.total_failures += count;
+    }
+
+    let mut heatmap: Vec<PeerFailureHeatmap> = peers
+        .into_values()
+        .map(|mut peer| {
+            peer.channel_ids.sort();
+            // The first of equally bad hours wins.
+            peer.peak_hour = (0..24)
+                .rev()
+                .max_by_key(|&hour| peer.hourly[hour])
+                .unwrap_or(0);
+            peer
+        })
+        .collect();
+    heatmap.sort_by(|a, b| {
+        b.total_failures
+            .cmp(&a.total_failures)
+            .then_with(|| a.channel_ids.cmp(&b.channel_ids))
+    });
+    heatmap
+}
+
+/// Service layer for forward failure analytics.
+pub struct ForwardFailureService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ForwardFailureService<'a> {
+    /// Creates a new ForwardFailureService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Builds the failure heatmap of a node over the requested number of days.
+    pub async fn get_heatmap(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: FailureHeatmapQuery,
+    ) -> ServiceResult<FailureHeatmap> {
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_HEATMAP_DAYS)
+            .clamp(1, MAX_HEATMAP_DAYS);
+        let rows = EventRepository::new(self.pool)
+            .get_forward_failures_by_hour(account_id, node_id, Utc::now() - Duration::days(days))
+            .await?;
+
+        let graph_repo = GraphRepository::new(self.pool);
+        let channel_peers = graph_repo.get_channel_peers(node_id, node_id).await?;
+        let aliases: HashMap<String, String> = graph_repo
+            .get_nodes(node_id)
+            .await?
+            .into_iter()
+            .map(|node| (node.pubkey, node.alias))
+            .collect();
+
+        let mut peers = build_heatmap(rows, &channel_peers);
+        for peer in &mut peers {
+            peer.peer_alias = peer
+                .peer_pubkey
+                .as_ref()
+                .and_then(|pubkey| aliases.get(pubkey))
+                .cloned();
+        }
+
+        Ok(FailureHeatmap {
+            days,
+            total_failures: peers.iter().map(|peer| peer.total_failures).sum(),
+            peers,
+        })
+    }
+
+    /// Lists a node's failed forwards in a date range, newest first, with
+    /// counts by cause over the whole range.
+    pub async fn get_failures(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: ForwardFailureQuery,
+    ) -> ServiceResult<ForwardFailureList> {
+        let to = query.to.unwrap_or_else(Utc::now);
+        let from = query
+            .from
+            .unwrap_or_else(|| to - Duration::days(DEFAULT_FAILURE_DAYS));
+        if from > to {
+            return Err(ServiceError::validation("'from' must not be after 'to'"));
+        }
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_FAILURE_LIMIT)
+            .clamp(1, MAX_FAILURE_LIMIT);
+        let offset = query.offset.unwrap_or(0).max(0);
+        let channel_id = query.channel_id.as_deref();
+
+        let event_repo = EventRepository::new(self.pool);
+        let mut by_cause = ForwardFailureCauseCounts::default();
+        for (link_failure, reason, count) in event_repo
+            .count_forward_failures_by_reason(account_id, node_id, from, to, channel_id)
+            .await?
+        {
+            match classify_failure(link_failure, &reason) {
+                ForwardFailureCause::LocalLiquidity => by_cause.local_liquidity += count,
+                ForwardFailureCause::LocalPolicy => by_cause.local_policy += count,
+                ForwardFailureCause::Downstream => by_cause.downstream += count,
+            }
+        }
+
+        let channel_peers = GraphRepository::new(self.pool)
+            .get_channel_peers(node_id, node_id)
+            .await?;
+        let failures = event_repo
+            .get_forward_failures(account_id, node_id, from, to, channel_id, limit, offset)
+            .await?
+            .into_iter()
+            .filter_map(|(event_id, timestamp, data)| {
+                let data: ForwardFailedData = match serde_json::from_str(&data) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        tracing::warn!("Skipping forward failure {event_id} with bad data: {e}");
+                        return None;
+                    }
+                };
+                let incoming_channel_id = data.incoming_chan_id.to_string();
+                let outgoing_channel_id = data.outgoing_chan_id.to_string();
+                Some(ForwardFailure {
+                    event_id,
+                    timestamp,
+                    incoming_peer_pubkey: channel_peers.get(&incoming_channel_id).cloned(),
+                    outgoing_peer_pubkey: channel_peers.get(&outgoing_channel_id).cloned(),
+                    incoming_channel_id,
+                    outgoing_channel_id,
+                    amount_sat: data.outgoing_amt_msat.map(|msat| msat / 1000),
+                    cause: classify_failure(data.link_failure, &data.reason),
+                    reason: data.reason,
+                })
+            })
+            .collect();
+
+        Ok(ForwardFailureList {
+            from,
+            to,
+            total: by_cause.local_liquidity + by_cause.local_policy + by_cause.downstream,
+            by_cause,
+            failures,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_heatmap() {
+        let channel_peers = HashMap::from([
+            ("1".to_string(), "peer_a".to_string()),
+            ("2".to_string(), "peer_a".to_string()),
+        ]);
+        let rows = vec![
+            ("1".to_string(), 3, 4),
+            ("2".to_string(), 3, 2),
+            ("2".to_string(), 14, 1),
+            ("9".to_string(), 22, 2),
+            ("9".to_string(), 5, 2),
+        ];
+
+        let heatmap = build_heatmap(rows, &channel_peers);
+
+        assert_eq!(heatmap.len(), 2);
+        assert_eq!(heatmap[0].peer_pubkey.as_deref(), Some("peer_a"));
+        assert_eq!(heatmap[0].channel_ids, vec!["1", "2"]);
+        assert_eq!(heatmap[0].total_failures, 7);
+        assert_eq!(heatmap[0].hourly[3], 6);
+        assert_eq!(heatmap[0].peak_hour, 3);
+        assert_eq!(heatmap[1].peer_pubkey, None);
+        assert_eq!(heatmap[1].channel_ids, vec!["9"]);
+        assert_eq!(heatmap[1].peak_hour, 5);
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        assert_eq!(
+            classify_failure(false, "failed downstream"),
+            ForwardFailureCause::Downstream
+        );
+        assert_eq!(
+            classify_failure(true, "InsufficientBalance"),
+            ForwardFailureCause::LocalLiquidity
+        );
+        assert_eq!(
+            classify_failure(true, "insufficient balance"),
+            ForwardFailureCause::LocalLiquidity
+        );
+        assert_eq!(
+            classify_failure(true, "TEMPORARY_CHANNEL_FAILURE"),
+            ForwardFailureCause::LocalLiquidity
+        );
+        assert_eq!(
+            classify_failure(true, "FeeInsufficient"),
+            ForwardFailureCause::LocalPolicy
+        );
+        assert_eq!(
+            classify_failure(true, "HtlcExceedsMax"),
+            ForwardFailureCause::LocalPolicy
+        );
+    }
+}
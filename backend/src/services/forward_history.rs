@@ -0,0 +1,96 @@
+//! Persistence of settled forwards.
+//!
+//! LND nodes write each forward to the `forwards` table as their HTLC event
+//! stream reports it settling. The `ForwardHistory` scheduled task fills in
+//! the rest from the node's forwarding history: forwards from before the node
+//! was added, ones that settled while the stream was down, and all forwards
+//! of CLN nodes, whose stream doesn't report them. Together they are the
+//! record fee reports and per-channel revenue are built on.
+
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::forward_repository::ForwardRepository;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use bitcoin::secp256k1::PublicKey;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use std::str::FromStr;
+
+/// How far back the first backfill of a node reaches
+const INITIAL_HISTORY_DAYS: i64 = 365;
+
+/// Each backfill re-reads this much before where the previous one ended, for
+/// forwards the node logged late
+const BACKFILL_OVERLAP_HOURS: i64 = 1;
+
+/// Backfills the forwarding history of every node of an account.
+pub async fn backfill_account_forwards(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let credentials = CredentialRepository::new(pool)
+        .get_credentials_by_account_id(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut failures = Vec::new();
+    for credential in credentials {
+        let node_credentials = NodeCredentials::from(credential);
+        if let Err(e) = backfill_node_forwards(pool, account_id, &node_credentials).await {
+            failures.push(format!("{}: {e}", node_credentials.node_id));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Forward backfill failed for {}",
+            failures.join("; ")
+        ))
+    }
+}
+
+/// Stores the forwards a node settled since its last backfill.
+async fn backfill_node_forwards(
+    pool: &SqlitePool,
+    account_id: &str,
+    node_credentials: &NodeCredentials,
+) -> Result<(), String> {
+    let node_id = &node_credentials.node_id;
+    let repo = ForwardRepository::new(pool);
+    let until = Utc::now();
+    let since = match repo
+        .get_backfilled_until(account_id, node_id)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        Some(backfilled_until) => backfilled_until - Duration::hours(BACKFILL_OVERLAP_HOURS),
+        None => until - Duration::days(INITIAL_HISTORY_DAYS),
+    };
+
+    let public_key = PublicKey::from_str(node_id).map_err(|e| e.to_string())?;
+    let client = create_node_client(node_credentials, public_key)
+        .await
+        .map_err(|(_, body)| body)?;
+    let forwards = client
+        .list_forwards(since.timestamp() as u64)
+        .await
+        .map_err(|e| e.to_string())?;
+    drop(client);
+
+    let mut recorded = 0;
+    for forward in &forwards {
+        if repo
+            .insert_forward(account_id, node_id, forward)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            recorded += 1;
+        }
+    }
+    if recorded > 0 {
+        tracing::info!("Backfilled {} forward(s) of node {}", recorded, node_id);
+    }
+
+    repo.set_backfilled_until(account_id, node_id, until)
+        .await
+        .map_err(|e| e.to_string())
+}
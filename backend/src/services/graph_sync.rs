@@ -0,0 +1,291 @@
+//! Local mirror of the channel graph.
+//!
+//! The `GraphSync` scheduled task copies each node's view of the network into
+//! SQLite, and a per-node subscription applies topology updates between full
+//! syncs. Graph analytics (node lookups, fee percentiles, network position)
+//! read the mirror instead of asking the node. Both paths report changed
+//! announcements of the node's channel partners; full syncs also check the
+//! external nodes watched through the mirror.
+
+use crate::database::models::{FeePercentiles, GraphNodeDetails, GraphSummary};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::graph_repository::GraphRepository;
+use crate::services::network_position::record_network_position;
+use crate::services::node_manager::LightningError;
+use crate::services::peer_announcements::record_peer_announcement_changes;
+use crate::services::subscription_health::{self, SubscriptionKind};
+use crate::services::watched_nodes::check_watched_nodes;
+use crate::utils::GraphUpdate;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use bitcoin::secp256k1::PublicKey;
+use futures::StreamExt;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(60);
+
+/// Running graph subscriptions, keyed by node_id
+static SUBSCRIPTIONS: OnceLock<Mutex<HashMap<String, JoinHandle<()>>>> = OnceLock::new();
+
+fn subscriptions() -> &'static Mutex<HashMap<String, JoinHandle<()>>> {
+    SUBSCRIPTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Nearest-rank percentile of an ascending list.
+pub fn percentile(sorted: &[i64], pct: f64) -> Option<i64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Service layer for reading the graph mirror.
+pub struct GraphService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> GraphService<'a> {
+    /// Creates a new GraphService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Reports how much of the graph is mirrored for a node and how fresh it is.
+    pub async fn get_summary(&self, node_id: &str) -> ServiceResult<GraphSummary> {
+        Ok(GraphRepository::new(self.pool).get_summary(node_id).await?)
+    }
+
+    /// Looks up a node in the graph as seen by `node_id`.
+    pub async fn get_node(&self, node_id: &str, pubkey: &str) -> ServiceResult<GraphNodeDetails> {
+        GraphRepository::new(self.pool)
+            .get_node(node_id, pubkey)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Graph node", pubkey))
+    }
+
+    /// Compares the node's own fee rates with the rest of the graph.
+    pub async fn get_fee_percentiles(&self, node_id: &str) -> ServiceResult<FeePercentiles> {
+        let repo = GraphRepository::new(self.pool);
+        let rates = repo.get_fee_rates(node_id).await?;
+        let own_rates = repo.get_node_fee_rates(node_id, node_id).await?;
+
+        let own_median_ppm = percentile(&own_rates, 50.0);
+        let own_percentile = own_median_ppm.filter(|_| !rates.is_empty()).map(|median| {
+            let below = rates.partition_point(|rate| *rate < median);
+            below as f64 * 100.0 / rates.len() as f64
+        });
+
+        Ok(FeePercentiles {
+            sample_size: rates.len(),
+            p25: percentile(&rates, 25.0),
+            p50: percentile(&rates, 50.0),
+            p75: percentile(&rates, 75.0),
+            p90: percentile(&rates, 90.0),
+            own_median_ppm,
+            own_percentile,
+        })
+    }
+}
+
+/// Copies the graph of every node connected to the account into the mirror.
+pub async fn sync_account_graphs(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let credentials = CredentialRepository::new(pool)
+        .get_credentials_by_account_id(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let repo = GraphRepository::new(pool);
+
+    let mut failures = Vec::new();
+    for credential in credentials {
+        let node_credentials = NodeCredentials::from(credential);
+        let node_id = node_credentials.node_id.clone();
+
+        let synced = async {
+            let public_key = PublicKey::from_str(&node_id).map_err(|e| e.to_string())?;
+            let client = create_node_client(&node_credentials, public_key)
+                .await
+                .map_err(|(_, body)| body)?;
+            let graph = match client.describe_graph().await {
+                Ok(graph) => graph,
+                Err(LightningError::Unsupported(_)) => {
+                    return Ok(false);
+                }
+                Err(e) => return Err(e.to_string()),
+            };
+            let previous_peers = repo
+                .get_peer_announcements(&node_id, None)
+                .await
+                .map_err(|e| e.to_string())?;
+            repo.replace_graph(&node_id, &graph)
+                .await
+                .map_err(|e| e.to_string())?;
+            record_peer_announcement_changes(pool, &node_id, previous_peers, &graph.nodes).await;
+            check_watched_nodes(pool, &node_id).await;
+            tracing::info!(
+                "Mirrored {} node(s) and {} channel(s) from {}",
+                graph.nodes.len(),
+                graph.channels.len(),
+                node_id
+            );
+            if let Err(e) = record_network_position(pool, &node_id).await {
+                tracing::warn!("Failed to score network position of {}: {}", node_id, e);
+            }
+            Ok::<_, String>(true)
+        }
+        .await;
+
+        match synced {
+            Ok(true) => ensure_subscription(pool.clone(), node_credentials),
+            Ok(false) => {}
+            Err(e) => failures.push(format!("{node_id}: {e}")),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Graph sync failed for {}", failures.join("; ")))
+    }
+}
+
+/// Subscribes to graph updates for every node that has been mirrored before.
+pub async fn start_graph_subscriptions(pool: SqlitePool) {
+    let credentials = match CredentialRepository::new(&pool).get_all_credentials().await {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            tracing::error!("Failed to load credentials for graph subscriptions: {}", e);
+            return;
+        }
+    };
+
+    let repo = GraphRepository::new(&pool);
+    let mut started = 0;
+    for credential in credentials {
+        let mirrored = repo
+            .get_summary(&credential.node_id)
+            .await
+            .is_ok_and(|summary| summary.last_full_sync_at.is_some());
+        if mirrored {
+            ensure_subscription(pool.clone(), NodeCredentials::from(credential));
+            started += 1;
+        }
+    }
+
+    tracing::info!("Started {} graph subscription(s)", started);
+}
+
+/// Starts a node's subscription unless one is already running.
+fn ensure_subscription(pool: SqlitePool, node_credentials: NodeCredentials) {
+    let mut subscriptions = subscriptions().lock().unwrap();
+    if subscriptions
+        .get(&node_credentials.node_id)
+        .is_some_and(|task| !task.is_finished())
+    {
+        return;
+    }
+    let node_id = node_credentials.node_id.clone();
+    let task = tokio::spawn(run_subscription(pool, node_credentials));
+    subscriptions.insert(node_id, task);
+}
+
+/// Replaces a node's subscription with a fresh one.
+pub fn restart_subscription(pool: SqlitePool, node_credentials: NodeCredentials) {
+    let node_id = node_credentials.node_id.clone();
+    let task = tokio::spawn(run_subscription(pool, node_credentials));
+    if let Some(previous) = subscriptions().lock().unwrap().insert(node_id, task) {
+        previous.abort();
+    }
+}
+
+/// Applies graph updates as they arrive, resubscribing whenever the stream ends.
+///
+/// Nodes that can't stream updates are left to the periodic full sync.
+async fn run_subscription(pool: SqlitePool, node_credentials: NodeCredentials) {
+    let node_id = &node_credentials.node_id;
+    loop {
+        match apply_updates(&pool, &node_credentials).await {
+            Ok(false) => return,
+            Ok(true) => {
+                tracing::warn!("Graph subscription for {} ended", node_id);
+                subscription_health::record_disconnected(node_id, SubscriptionKind::Graph, None);
+            }
+            Err(e) => {
+                tracing::warn!("Graph subscription for {} failed: {}", node_id, e);
+                subscription_health::record_disconnected(node_id, SubscriptionKind::Graph, Some(e));
+            }
+        }
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
+
+async fn apply_updates(
+    pool: &SqlitePool,
+    node_credentials: &NodeCredentials,
+) -> Result<bool, String> {
+    let public_key = PublicKey::from_str(&node_credentials.node_id).map_err(|e| e.to_string())?;
+    let mut updates = {
+        let client = create_node_client(node_credentials, public_key)
+            .await
+            .map_err(|(_, body)| body)?;
+        // The stream outlives the handle, so it doesn't hold a concurrency slot.
+        match client.subscribe_graph().await {
+            Ok(updates) => updates,
+            Err(LightningError::Unsupported(_)) => return Ok(false),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+
+    let node_id = &node_credentials.node_id;
+    subscription_health::record_connected(node_id, SubscriptionKind::Graph);
+
+    let repo = GraphRepository::new(pool);
+    while let Some(update) = updates.next().await {
+        let update = update.map_err(|e| e.to_string())?;
+        let received_at = Instant::now();
+        subscription_health::record_event(node_id, SubscriptionKind::Graph);
+        let previous_peer = match &update {
+            GraphUpdate::Node(node) => repo
+                .get_peer_announcements(node_id, Some(&node.pubkey))
+                .await
+                .map_err(|e| e.to_string())?,
+            _ => Vec::new(),
+        };
+        repo.apply_update(node_id, &update)
+            .await
+            .map_err(|e| e.to_string())?;
+        if let GraphUpdate::Node(node) = &update {
+            record_peer_announcement_changes(
+                pool,
+                node_id,
+                previous_peer,
+                std::slice::from_ref(node),
+            )
+            .await;
+        }
+        subscription_health::record_lag(node_id, SubscriptionKind::Graph, received_at.elapsed());
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let rates = [1, 10, 100, 200, 500, 1000, 2000, 5000];
+        assert_eq!(percentile(&rates, 25.0), Some(10));
+        assert_eq!(percentile(&rates, 50.0), Some(200));
+        assert_eq!(percentile(&rates, 90.0), Some(5000));
+        assert_eq!(percentile(&rates, 0.0), Some(1));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+}
@@ -0,0 +1,523 @@
+//! Import of forwarding and payment history exported by other node tools.
+//!
+//! Balance of Satoshis, LNDg and ThunderHub all export history as CSV or
+//! JSON, each with its own column names. Records are read into lower-case
+//! column maps, and the columns below are tried in order, so an export from
+//! any of the three (or a hand-made one using the same names) is understood:
+//!
+//! | Field          | Columns                                                  |
+//! |----------------|----------------------------------------------------------|
+//! | forward time   | `forward_date`, `created_at`, `date & time`, `date`, `timestamp` |
+//! | channels       | `chan_id_in`/`chan_id_out`, `incoming_channel`/`outgoing_channel` |
+//! | forward amount | `amt_out_msat`, `mtokens`, or sats in `amt_out`, `tokens` |
+//! | payment hash   | `payment_hash`, `id`, `transaction hash`, `hash`          |
+//! | payment amount | `value_msat`, `mtokens`, or sats in `value`, `tokens`, `amount` |
+//! | fee            | `fee_msat`, `fee_mtokens`, or sats in `fee`, `fees`      |
+//! | payment state  | `status` (LNDg's numeric codes too), `state`, `is_confirmed` |
+//!
+//! BoS accounting exports list a payment's routing fee as a separate `fee`
+//! row, which is folded into the payment. Forwards go to the `forwards`
+//! table, where a forward already recorded from the node is recognized and
+//! skipped; payments go to `synced_payments`, tagged with the tool they came
+//! from so resyncs keep them.
+
+use crate::database::models::{
+    ImportFormat, ImportHistoryRequest, ImportKind, ImportSummary, SkippedImportRow, SyncedPayment,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::forward_repository::ForwardRepository;
+use crate::repositories::node_sync_repository::NodeSyncRepository;
+use crate::services::cln_rest::parse_short_channel_id;
+use crate::utils::{Forward, PaymentState, ShortChannelID};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Skipped rows listed in an import summary; the rest are only counted
+const MAX_LISTED_SKIPS: usize = 20;
+
+const FORWARD_TIME_COLUMNS: &[&str] = &[
+    "forward_date",
+    "created_at",
+    "date & time",
+    "date",
+    "timestamp",
+];
+const CHAN_IN_COLUMNS: &[&str] = &["chan_id_in", "incoming_channel"];
+const CHAN_OUT_COLUMNS: &[&str] = &["chan_id_out", "outgoing_channel"];
+const PAYMENT_HASH_COLUMNS: &[&str] = &["payment_hash", "id", "transaction hash", "hash"];
+const PAYMENT_TIME_COLUMNS: &[&str] = &["creation_date", "created_at", "date & time", "date"];
+const FEE_MSAT_COLUMNS: &[&str] = &["fee_msat", "fees_msat", "fee_mtokens"];
+const FEE_SAT_COLUMNS: &[&str] = &["fee", "fees"];
+
+/// One record of an export, keyed by lower-case column name.
+type Record = HashMap<String, String>;
+
+/// A payment read from an export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedPayment {
+    pub payment_hash: String,
+    pub state: PaymentState,
+    pub amount_msat: u64,
+    pub fee_msat: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Reads an export into records.
+pub fn parse_records(
+    format: ImportFormat,
+    kind: ImportKind,
+    content: &str,
+) -> Result<Vec<Record>, String> {
+    match format {
+        ImportFormat::Csv => {
+            let mut rows = parse_csv(content.trim_start_matches('\u{feff}'))?.into_iter();
+            let header: Vec<String> = rows
+                .next()
+                .ok_or("The export is empty")?
+                .iter()
+                .map(|column| column.trim().to_lowercase())
+                .collect();
+            Ok(rows
+                .map(|row| {
+                    header
+                        .iter()
+                        .cloned()
+                        .zip(row)
+                        .filter(|(_, value)| !value.trim().is_empty())
+                        .collect()
+                })
+                .collect())
+        }
+        ImportFormat::Json => {
+            let key = match kind {
+                ImportKind::Forwards => "forwards",
+                ImportKind::Payments => "payments",
+            };
+            let items =
+                match serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {e}"))? {
+                    Value::Array(items) => items,
+                    Value::Object(mut object) => match object.remove(key) {
+                        Some(Value::Array(items)) => items,
+                        _ => return Err(format!("Expected an array or a \"{key}\" array")),
+                    },
+                    _ => return Err(format!("Expected an array or a \"{key}\" array")),
+                };
+            Ok(items
+                .into_iter()
+                .map(|item| match item {
+                    Value::Object(fields) => fields
+                        .into_iter()
+                        .filter_map(|(column, value)| {
+                            let value = match value {
+                                Value::String(value) => value,
+                                Value::Number(value) => value.to_string(),
+                                Value::Bool(value) => value.to_string(),
+                                _ => return None,
+                            };
+                            Some((column.to_lowercase(), value))
+                        })
+                        .collect(),
+                    // Left empty, so it is reported as missing its fields.
+                    _ => Record::new(),
+                })
+                .collect())
+        }
+    }
+}
+
+/// Splits CSV text into rows of fields, honouring quoted fields with
+/// embedded commas, quotes (`""`) and line breaks. Blank lines are dropped.
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("The export ends inside a quoted field".to_string());
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    Ok(rows)
+}
+
+/// The value of the first of `columns` the record has.
+fn first<'r>(record: &'r Record, columns: &[&str]) -> Option<&'r str> {
+    columns
+        .iter()
+        .find_map(|column| record.get(*column))
+        .map(|value| value.trim())
+}
+
+/// Reads an amount from the first msat column present, or else the first
+/// sat column, where fractional sats are allowed. Signs are dropped, as BoS
+/// writes spent amounts as negative.
+fn amount_msat(
+    record: &Record,
+    msat_columns: &[&str],
+    sat_columns: &[&str],
+) -> Result<Option<u64>, String> {
+    if let Some(value) = first(record, msat_columns) {
+        return value
+            .trim_start_matches('-')
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid msat amount '{value}'"));
+    }
+    match first(record, sat_columns) {
+        Some(value) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|sats| sats.is_finite())
+            .map(|sats| Some((sats.abs() * 1000.0).round() as u64))
+            .ok_or_else(|| format!("Invalid amount '{value}'")),
+        None => Ok(None),
+    }
+}
+
+/// Parses Unix seconds (or milliseconds), RFC 3339, or the
+/// `YYYY-MM-DD HH:MM:SS` form LNDg writes, with or without an offset.
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return match secs {
+            // Too large for seconds, so milliseconds
+            s if s > 100_000_000_000 => DateTime::from_timestamp_millis(s),
+            s => DateTime::from_timestamp(s, 0),
+        };
+    }
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%:z"))
+        .map(|time| time.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .map(|time| time.and_utc())
+        })
+}
+
+fn channel(record: &Record, columns: &[&str], leg: &str) -> Result<ShortChannelID, String> {
+    let value = first(record, columns).ok_or(format!("Missing {leg} channel"))?;
+    parse_short_channel_id(value)
+        .map(ShortChannelID)
+        .ok_or_else(|| format!("Invalid {leg} channel '{value}'"))
+}
+
+/// Reads a forward record.
+pub fn to_forward(record: &Record) -> Result<Forward, String> {
+    let time = first(record, FORWARD_TIME_COLUMNS).ok_or("Missing forward time")?;
+    let settled_at = parse_time(time).ok_or_else(|| format!("Invalid forward time '{time}'"))?;
+    let chan_id_in = channel(record, CHAN_IN_COLUMNS, "incoming")?;
+    let chan_id_out = channel(record, CHAN_OUT_COLUMNS, "outgoing")?;
+
+    let amt_out_msat = amount_msat(record, &["amt_out_msat", "mtokens"], &["amt_out", "tokens"])?
+        .ok_or("Missing forwarded amount")?;
+    let amt_in_msat = amount_msat(record, &["amt_in_msat"], &["amt_in"])?;
+    let fee_msat = amount_msat(record, FEE_MSAT_COLUMNS, FEE_SAT_COLUMNS)?;
+    let (amt_in_msat, fee_msat) = match (amt_in_msat, fee_msat) {
+        (Some(amt_in), _) if amt_in < amt_out_msat => {
+            return Err("Incoming amount is below the outgoing amount".to_string());
+        }
+        (Some(amt_in), _) => (amt_in, amt_in - amt_out_msat),
+        (None, Some(fee)) => (amt_out_msat + fee, fee),
+        (None, None) => return Err("Missing incoming amount or fee".to_string()),
+    };
+
+    Ok(Forward {
+        timestamp: settled_at.timestamp().max(0) as u64,
+        received_at: None,
+        chan_id_in,
+        chan_id_out,
+        incoming_htlc_id: None,
+        outgoing_htlc_id: None,
+        amt_in_msat,
+        amt_out_msat,
+        fee_msat,
+    })
+}
+
+fn payment_state(record: &Record) -> Result<PaymentState, String> {
+    let Some(value) = first(record, &["status", "state", "is_confirmed"]) else {
+        // History exports list finished payments unless they say otherwise.
+        return Ok(PaymentState::Settled);
+    };
+    match value.to_lowercase().as_str() {
+        // LNDg's status codes, and ThunderHub's is_confirmed
+        "1" => Ok(PaymentState::Inflight),
+        "2" | "true" => Ok(PaymentState::Settled),
+        "3" | "false" => Ok(PaymentState::Failed),
+        other => PaymentState::from_str(other),
+    }
+}
+
+/// Reads payment records, folding BoS fee rows into their payments. Returns
+/// the payments with the position of their first record, and the records
+/// that couldn't be read.
+pub fn to_payments(records: &[Record]) -> (Vec<(usize, ImportedPayment)>, Vec<SkippedImportRow>) {
+    let mut payments: Vec<(usize, ImportedPayment)> = Vec::new();
+    let mut by_hash: HashMap<String, usize> = HashMap::new();
+    let mut skipped = Vec::new();
+
+    for (i, record) in records.iter().enumerate() {
+        let row = i + 1;
+        let read = || -> Result<(ImportedPayment, bool), String> {
+            let hash = first(record, PAYMENT_HASH_COLUMNS).ok_or("Missing payment hash")?;
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Invalid payment hash '{hash}'"));
+            }
+            let is_fee_row =
+                first(record, &["type"]).is_some_and(|t| t.eq_ignore_ascii_case("fee"));
+            let time = first(record, PAYMENT_TIME_COLUMNS).ok_or("Missing payment time")?;
+            let created_at =
+                parse_time(time).ok_or_else(|| format!("Invalid payment time '{time}'"))?;
+
+            let (amount_msat, fee_msat) = if is_fee_row {
+                let fee = amount_msat(record, &["mtokens"], &["amount", "fee"])?
+                    .ok_or("Missing fee amount")?;
+                (0, fee)
+            } else {
+                let amount = amount_msat(
+                    record,
+                    &["value_msat", "mtokens"],
+                    &["value", "value_sat", "tokens", "amount"],
+                )?
+                .ok_or("Missing payment amount")?;
+                let fee = amount_msat(record, FEE_MSAT_COLUMNS, FEE_SAT_COLUMNS)?.unwrap_or(0);
+                (amount, fee)
+            };
+
+            let payment = ImportedPayment {
+                payment_hash: hash.to_lowercase(),
+                state: payment_state(record)?,
+                amount_msat,
+                fee_msat,
+                created_at,
+            };
+            Ok((payment, is_fee_row))
+        };
+
+        match read() {
+            Ok((payment, is_fee_row)) => match by_hash.get(&payment.payment_hash) {
+                Some(&index) => {
+                    let existing = &mut payments[index].1;
+                    existing.amount_msat += payment.amount_msat;
+                    existing.fee_msat += payment.fee_msat;
+                    if !is_fee_row {
+                        existing.state = payment.state;
+                    }
+                }
+                None => {
+                    by_hash.insert(payment.payment_hash.clone(), payments.len());
+                    payments.push((row, payment));
+                }
+            },
+            Err(reason) => skipped.push(SkippedImportRow { row, reason }),
+        }
+    }
+
+    (payments, skipped)
+}
+
+/// Service layer for history imports.
+pub struct HistoryImportService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> HistoryImportService<'a> {
+    /// Creates a new HistoryImportService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Imports a history export into one of the account's nodes.
+    pub async fn import(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        request: ImportHistoryRequest,
+    ) -> ServiceResult<ImportSummary> {
+        CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+
+        let records = parse_records(request.format, request.kind, &request.content)
+            .map_err(ServiceError::validation)?;
+        let mut summary = ImportSummary {
+            source: request.source,
+            kind: request.kind,
+            rows: records.len(),
+            imported: 0,
+            duplicates: 0,
+            skipped: 0,
+            skipped_rows: Vec::new(),
+        };
+        let mut skipped = Vec::new();
+
+        match request.kind {
+            ImportKind::Forwards => {
+                let repo = ForwardRepository::new(self.pool);
+                for (i, record) in records.iter().enumerate() {
+                    match to_forward(record) {
+                        Ok(forward) => {
+                            if repo.insert_forward(account_id, node_id, &forward).await? {
+                                summary.imported += 1;
+                            } else {
+                                summary.duplicates += 1;
+                            }
+                        }
+                        Err(reason) => skipped.push(SkippedImportRow { row: i + 1, reason }),
+                    }
+                }
+            }
+            ImportKind::Payments => {
+                let (payments, unreadable) = to_payments(&records);
+                skipped = unreadable;
+
+                let imported_at = Utc::now();
+                let rows: Vec<SyncedPayment> = payments
+                    .into_iter()
+                    .map(|(_, payment)| SyncedPayment {
+                        id: Uuid::now_v7().to_string(),
+                        account_id: account_id.to_string(),
+                        node_id: node_id.to_string(),
+                        payment_hash: payment.payment_hash,
+                        state: format!("{:?}", payment.state),
+                        payment_type: "Outgoing".to_string(),
+                        amount_sat: (payment.amount_msat / 1000) as i64,
+                        routing_fee_sat: Some((payment.fee_msat / 1000) as i64),
+                        created_at_node: Some(payment.created_at),
+                        completed_at_node: None,
+                        keysend_message: None,
+                        destination_pubkey: None,
+                        imported_from: Some(request.source.to_string()),
+                        synced_at: imported_at,
+                    })
+                    .collect();
+                summary.imported = NodeSyncRepository::new(self.pool)
+                    .insert_imported_payments(account_id, node_id, &rows)
+                    .await?;
+                summary.duplicates = rows.len() as u64 - summary.imported;
+            }
+        }
+
+        summary.skipped = skipped.len();
+        skipped.truncate(MAX_LISTED_SKIPS);
+        summary.skipped_rows = skipped;
+
+        tracing::info!(
+            "Imported {} {} of node {} from {} ({} duplicate, {} skipped)",
+            summary.imported,
+            match summary.kind {
+                ImportKind::Forwards => "forwards",
+                ImportKind::Payments => "payments",
+            },
+            node_id,
+            summary.source,
+            summary.duplicates,
+            summary.skipped
+        );
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_quoting() {
+        let rows = parse_csv("a,b,c\r\n\"x, y\",\"say \"\"hi\"\"\",\n\n1,2,3").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["a", "b", "c"],
+                vec!["x, y", "say \"hi\"", ""],
+                vec!["1", "2", "3"],
+            ]
+        );
+        assert!(parse_csv("a,\"b").is_err());
+    }
+
+    #[test]
+    fn test_lndg_forward() {
+        let csv = "forward_date,chan_id_in,chan_id_out,amt_in_msat,amt_out_msat,fee\n\
+                   2024-03-01 12:30:00.123456,870000x1x0,871234567890,1001500,1000000,1.5";
+        let records = parse_records(ImportFormat::Csv, ImportKind::Forwards, csv).unwrap();
+        let forward = to_forward(&records[0]).unwrap();
+
+        assert_eq!(forward.chan_id_in.0, (870_000 << 40) | (1 << 16));
+        assert_eq!(forward.chan_id_out.0, 871_234_567_890);
+        assert_eq!(forward.amt_in_msat, 1_001_500);
+        assert_eq!(forward.fee_msat, 1_500);
+        assert_eq!(forward.timestamp, 1_709_296_200);
+    }
+
+    #[test]
+    fn test_payments_fold_bos_fee_rows() {
+        let hash = "ab".repeat(32);
+        let csv = format!(
+            "Amount,Date & Time,Type,Transaction Hash\n\
+             -5000,2024-03-01T12:00:00Z,spend,{hash}\n\
+             -2,2024-03-01T12:00:00Z,fee,{hash}\n\
+             -1,2024-03-01T12:00:00Z,spend,nothex"
+        );
+        let records = parse_records(ImportFormat::Csv, ImportKind::Payments, &csv).unwrap();
+        let (payments, skipped) = to_payments(&records);
+
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].1.amount_msat, 5_000_000);
+        assert_eq!(payments[0].1.fee_msat, 2_000);
+        assert_eq!(payments[0].1.state, PaymentState::Settled);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].row, 3);
+    }
+
+    #[test]
+    fn test_thunderhub_json_payments() {
+        let hash = "cd".repeat(32);
+        let json = format!(
+            r#"{{"payments": [{{"id": "{hash}", "created_at": "2024-03-01T12:00:00.000Z",
+                "tokens": 2100, "fee_mtokens": "3500", "is_confirmed": false}}]}}"#
+        );
+        let records = parse_records(ImportFormat::Json, ImportKind::Payments, &json).unwrap();
+        let (payments, _) = to_payments(&records);
+
+        assert_eq!(payments[0].1.amount_msat, 2_100_000);
+        assert_eq!(payments[0].1.fee_msat, 3_500);
+        assert_eq!(payments[0].1.state, PaymentState::Failed);
+    }
+}
@@ -0,0 +1,467 @@
+//! HTLC interceptor for LND nodes.
+//!
+//! With an enabled policy, nodegaze registers as the node's HTLC interceptor
+//! and decides every forward: it is failed if the node is in a maintenance
+//! window, the incoming peer is blocked or the outgoing amount exceeds the
+//! cap, and resumed otherwise. Each decision is recorded as an
+//! `HtlcIntercepted` event; only failures are sent to notification endpoints.
+//!
+//! LND holds intercepted forwards while the stream is down, so the task
+//! reconnects with backoff and fails open whenever the rules can't be read.
+
+use crate::database::models::{
+    CreateEvent, EventSeverity, EventType, HtlcInterceptorPolicy, UpdateHtlcInterceptorRequest,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::htlc_interceptor_repository::HtlcInterceptorRepository;
+use crate::services::event_service::EventService;
+use crate::services::node_manager::{InterceptedHtlc, LndConnection, LndNode};
+use crate::services::subscription_health::{self, SubscriptionKind};
+use crate::utils::NodeId;
+use bitcoin::secp256k1::PublicKey;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+use validator::Validate;
+
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(300);
+
+/// Running interceptor tasks, keyed by (account_id, node_id)
+static INTERCEPTORS: OnceLock<Mutex<HashMap<(String, String), JoinHandle<()>>>> = OnceLock::new();
+
+fn interceptors() -> &'static Mutex<HashMap<(String, String), JoinHandle<()>>> {
+    INTERCEPTORS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Checks a held forward against a policy, returning the reason to fail it.
+pub fn evaluate(
+    policy: &HtlcInterceptorPolicy,
+    htlc: &InterceptedHtlc,
+    incoming_peer: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    if policy
+        .maintenance_windows()
+        .iter()
+        .any(|window| window.contains(now))
+    {
+        return Err("node is in a maintenance window".to_string());
+    }
+
+    if incoming_peer.is_some_and(|peer| policy.blocked_peers().iter().any(|p| p == peer)) {
+        return Err("forwards from this peer are blocked".to_string());
+    }
+
+    if let Some(max_msat) = policy
+        .max_htlc_msat
+        .filter(|max_msat| htlc.outgoing_amount_msat as i64 > *max_msat)
+    {
+        return Err(format!(
+            "HTLC of {} msat exceeds the maximum of {max_msat} msat",
+            htlc.outgoing_amount_msat
+        ));
+    }
+
+    Ok(())
+}
+
+/// Service layer for HTLC interceptor policies.
+pub struct HtlcInterceptorService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> HtlcInterceptorService<'a> {
+    /// Creates a new HtlcInterceptorService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns a node's policy. Nodes without one forward without interception.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<HtlcInterceptorPolicy> {
+        let policy = HtlcInterceptorRepository::new(self.pool)
+            .get_policy(account_id, node_id)
+            .await?;
+
+        Ok(policy.unwrap_or_else(|| HtlcInterceptorPolicy {
+            account_id: account_id.to_string(),
+            node_id: node_id.to_string(),
+            is_enabled: false,
+            blocked_peers: "[]".to_string(),
+            max_htlc_msat: None,
+            maintenance_windows: "[]".to_string(),
+            updated_by: user_id.to_string(),
+            updated_at: None,
+        }))
+    }
+
+    /// Replaces a node's policy and starts or stops its interceptor to match.
+    pub async fn update_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: UpdateHtlcInterceptorRequest,
+    ) -> ServiceResult<HtlcInterceptorPolicy> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        if request
+            .maintenance_windows
+            .iter()
+            .any(|window| window.ends_at <= window.starts_at)
+        {
+            return Err(ServiceError::validation(
+                "Maintenance windows must end after they start",
+            ));
+        }
+
+        let mut blocked_peers = Vec::with_capacity(request.blocked_peers.len());
+        for peer in &request.blocked_peers {
+            let pubkey = PublicKey::from_str(peer.trim()).map_err(|_| {
+                ServiceError::validation(format!("Invalid peer public key: {peer}"))
+            })?;
+            blocked_peers.push(pubkey.to_string());
+        }
+        blocked_peers.sort();
+        blocked_peers.dedup();
+
+        let credential = CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+        if credential.node_type.as_deref().unwrap_or("lnd") != "lnd" {
+            return Err(ServiceError::invalid_operation(
+                "The HTLC interceptor is only available for LND nodes",
+            ));
+        }
+
+        let policy = HtlcInterceptorRepository::new(self.pool)
+            .upsert_policy(&HtlcInterceptorPolicy {
+                account_id: account_id.to_string(),
+                node_id: node_id.to_string(),
+                is_enabled: request.is_enabled,
+                blocked_peers: json!(blocked_peers).to_string(),
+                max_htlc_msat: request.max_htlc_msat,
+                maintenance_windows: json!(request.maintenance_windows).to_string(),
+                updated_by: user_id.to_string(),
+                updated_at: None,
+            })
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "htlc_interceptor_updated",
+                "node",
+                Some(node_id),
+                &json!({
+                    "is_enabled": policy.is_enabled,
+                    "blocked_peers": policy.blocked_peers(),
+                    "max_htlc_msat": policy.max_htlc_msat,
+                    "maintenance_windows": policy.maintenance_windows(),
+                }),
+            )
+            .await?;
+
+        if policy.is_enabled {
+            start_interceptor(self.pool.clone(), account_id, node_id);
+        } else {
+            stop_interceptor(account_id, node_id);
+        }
+
+        Ok(policy)
+    }
+}
+
+/// Starts interceptors for every enabled policy. Called once at startup.
+pub async fn start_htlc_interceptors(pool: SqlitePool) {
+    let policies = match HtlcInterceptorRepository::new(&pool)
+        .get_enabled_policies()
+        .await
+    {
+        Ok(policies) => policies,
+        Err(e) => {
+            tracing::error!("Failed to load HTLC interceptor policies: {}", e);
+            return;
+        }
+    };
+
+    for policy in &policies {
+        start_interceptor(pool.clone(), &policy.account_id, &policy.node_id);
+    }
+
+    tracing::info!("Started {} HTLC interceptor(s)", policies.len());
+}
+
+/// Starts (or restarts) the interceptor task of a node.
+pub fn start_interceptor(pool: SqlitePool, account_id: &str, node_id: &str) {
+    let key = (account_id.to_string(), node_id.to_string());
+    let task = tokio::spawn(run_interceptor(pool, key.0.clone(), key.1.clone()));
+    if let Some(previous) = interceptors().lock().unwrap().insert(key, task) {
+        previous.abort();
+    }
+}
+
+/// Stops a node's interceptor task, letting LND forward on its own again.
+fn stop_interceptor(account_id: &str, node_id: &str) {
+    let key = (account_id.to_string(), node_id.to_string());
+    if let Some(task) = interceptors().lock().unwrap().remove(&key) {
+        task.abort();
+        tracing::info!("Stopped HTLC interceptor for node {}", node_id);
+    }
+}
+
+/// Keeps the interceptor registered, backing off between failed attempts.
+async fn run_interceptor(pool: SqlitePool, account_id: String, node_id: String) {
+    let mut delay = MIN_RECONNECT_DELAY;
+    loop {
+        let mut registered = false;
+        let error = match serve_interceptor(&pool, &account_id, &node_id, &mut registered).await {
+            Ok(()) => {
+                tracing::warn!("LND closed the HTLC interceptor stream for {}", node_id);
+                None
+            }
+            Err(e) => {
+                tracing::warn!("HTLC interceptor for node {} failed: {}", node_id, e);
+                Some(e)
+            }
+        };
+        subscription_health::record_disconnected(
+            &node_id,
+            SubscriptionKind::HtlcInterceptor,
+            error,
+        );
+
+        // A stream that was up resets the backoff; repeated failures stretch it.
+        delay = if registered {
+            MIN_RECONNECT_DELAY
+        } else {
+            (delay * 2).min(MAX_RECONNECT_DELAY)
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn serve_interceptor(
+    pool: &SqlitePool,
+    account_id: &str,
+    node_id: &str,
+    registered: &mut bool,
+) -> Result<(), String> {
+    let credential = CredentialRepository::new(pool)
+        .get_credential_by_account_and_node_id(account_id, node_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Node {node_id} no longer has credentials"))?;
+
+    let node = LndNode::new(LndConnection {
+        id: NodeId::PublicKey(PublicKey::from_str(node_id).map_err(|e| e.to_string())?),
+        address: credential.address,
+        macaroon: credential.macaroon,
+        cert: credential.tls_cert,
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut interceptor = node.htlc_interceptor().await.map_err(|e| e.to_string())?;
+    *registered = true;
+    subscription_health::record_connected(node_id, SubscriptionKind::HtlcInterceptor);
+    tracing::info!("HTLC interceptor registered on node {}", node_id);
+
+    let repo = HtlcInterceptorRepository::new(pool);
+    let mut channel_peers = node.channel_peers().await.unwrap_or_default();
+
+    while let Some(htlc) = interceptor.next_htlc().await.map_err(|e| e.to_string())? {
+        let received_at = Instant::now();
+        subscription_health::record_event(node_id, SubscriptionKind::HtlcInterceptor);
+        if !channel_peers.contains_key(&htlc.incoming_chan_id) {
+            // A channel opened since the last lookup.
+            match node.channel_peers().await {
+                Ok(peers) => channel_peers = peers,
+                Err(e) => tracing::warn!("Failed to refresh channel peers: {}", e),
+            }
+        }
+        let incoming_peer = channel_peers.get(&htlc.incoming_chan_id).cloned();
+
+        let policy = match repo.get_policy(account_id, node_id).await {
+            Ok(policy) => policy.filter(|p| p.is_enabled),
+            Err(e) => {
+                tracing::warn!("Failed to load HTLC interceptor policy: {}", e);
+                None
+            }
+        };
+        let verdict = policy.as_ref().map_or(Ok(()), |policy| {
+            evaluate(policy, &htlc, incoming_peer.as_deref(), Utc::now())
+        });
+
+        let resolved = match verdict {
+            Ok(()) => interceptor.resume(&htlc).await,
+            Err(_) => interceptor.fail(&htlc).await,
+        };
+        resolved.map_err(|e| e.to_string())?;
+        // The HTLC is held until resolved, so this is the delay added to it.
+        subscription_health::record_lag(
+            node_id,
+            SubscriptionKind::HtlcInterceptor,
+            received_at.elapsed(),
+        );
+
+        if let Some(policy) = policy {
+            record_decision(
+                pool,
+                &policy,
+                &credential.node_alias,
+                &htlc,
+                incoming_peer.as_deref(),
+                &verdict,
+            )
+            .await;
+        }
+    }
+
+    Ok(())
+}
+
+async fn record_decision(
+    pool: &SqlitePool,
+    policy: &HtlcInterceptorPolicy,
+    node_alias: &str,
+    htlc: &InterceptedHtlc,
+    incoming_peer: Option<&str>,
+    verdict: &Result<(), String>,
+) {
+    let peer = incoming_peer.unwrap_or("unknown peer");
+    let (severity, title, description) = match verdict {
+        Ok(()) => (
+            EventSeverity::Info,
+            "Forward Resumed",
+            format!(
+                "Resumed {} msat forward from {peer}",
+                htlc.outgoing_amount_msat
+            ),
+        ),
+        Err(reason) => (
+            EventSeverity::Warning,
+            "Forward Failed",
+            format!(
+                "Failed {} msat forward from {peer}: {reason}",
+                htlc.outgoing_amount_msat
+            ),
+        ),
+    };
+
+    let event = CreateEvent {
+        id: Uuid::now_v7().to_string(),
+        account_id: policy.account_id.clone(),
+        user_id: policy.updated_by.clone(),
+        node_id: policy.node_id.clone(),
+        node_alias: node_alias.to_string(),
+        event_type: EventType::HtlcIntercepted,
+        severity,
+        title: title.to_string(),
+        description,
+        data: json!({
+            "resumed": verdict.is_ok(),
+            "payment_hash": htlc.payment_hash,
+            "incoming_peer": incoming_peer,
+            "incoming_chan_id": htlc.incoming_chan_id,
+            "outgoing_chan_id": htlc.outgoing_chan_id,
+            "incoming_amount_msat": htlc.incoming_amount_msat,
+            "outgoing_amount_msat": htlc.outgoing_amount_msat,
+            "reason": verdict.as_ref().err(),
+        })
+        .to_string(),
+        notifications_id: None,
+        timestamp: Utc::now(),
+    };
+
+    // Every forward passes through here, so only failures notify.
+    let result = if verdict.is_ok() {
+        EventRepository::new(pool)
+            .create_event(event)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    } else {
+        EventService::new(pool)
+            .create_and_dispatch_event(event)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to record HTLC interception: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::MaintenanceWindow;
+    use chrono::Duration as ChronoDuration;
+
+    const PEER: &str = "02abababababababababababababababababababababababababababababababab";
+
+    fn policy() -> HtlcInterceptorPolicy {
+        HtlcInterceptorPolicy {
+            account_id: "account".to_string(),
+            node_id: "node".to_string(),
+            is_enabled: true,
+            blocked_peers: "[]".to_string(),
+            max_htlc_msat: Some(1_000_000),
+            maintenance_windows: "[]".to_string(),
+            updated_by: "user".to_string(),
+            updated_at: None,
+        }
+    }
+
+    fn htlc(outgoing_amount_msat: u64) -> InterceptedHtlc {
+        InterceptedHtlc {
+            incoming_chan_id: 1,
+            htlc_id: 0,
+            payment_hash: "cd".repeat(32),
+            incoming_amount_msat: outgoing_amount_msat + 1_000,
+            outgoing_amount_msat,
+            outgoing_chan_id: 2,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_applies_each_rule() {
+        let now = Utc::now();
+        let mut policy = policy();
+        assert!(evaluate(&policy, &htlc(1_000_000), Some(PEER), now).is_ok());
+        assert!(evaluate(&policy, &htlc(1_000_001), Some(PEER), now).is_err());
+
+        policy.blocked_peers = json!([PEER]).to_string();
+        assert!(evaluate(&policy, &htlc(500), Some(PEER), now).is_err());
+        assert!(evaluate(&policy, &htlc(500), None, now).is_ok());
+
+        policy.maintenance_windows = json!([MaintenanceWindow {
+            starts_at: now - ChronoDuration::minutes(5),
+            ends_at: now + ChronoDuration::minutes(5),
+        }])
+        .to_string();
+        assert_eq!(
+            evaluate(&policy, &htlc(500), None, now),
+            Err("node is in a maintenance window".to_string())
+        );
+        assert!(evaluate(&policy, &htlc(500), None, now + ChronoDuration::minutes(10)).is_ok());
+    }
+}
@@ -0,0 +1,325 @@
+//! Incident timelines.
+//!
+//! An incident is a run of one node's events that involve the same peer,
+//! channel or the node's own connection, each following the one before
+//! within a quiet gap. A peer going offline, the forwards failing on its
+//! channel and the force close that follows end up in one timeline.
+//! Incidents are grouped from the event log on each call rather than stored,
+//! and only those with a warning or critical event are reported.
+
+use crate::database::models::{EventResponse, EventSeverity, EventType, Incident, IncidentQuery};
+use crate::errors::ServiceResult;
+use crate::repositories::event_repository::EventRepository;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::collections::{BTreeSet, HashMap};
+
+const DEFAULT_INCIDENT_DAYS: i64 = 7;
+
+const MAX_INCIDENT_DAYS: i64 = 30;
+
+const DEFAULT_GAP_MINUTES: i64 = 30;
+
+const MAX_GAP_MINUTES: i64 = 24 * 60;
+
+/// Most events read per request; busy nodes log thousands of forwards a day
+const MAX_INCIDENT_EVENTS: i64 = 20_000;
+
+/// Event data fields naming the peer an event is about
+const PEER_KEYS: [&str; 2] = ["remote_pubkey", "peer_pubkey"];
+
+/// Event data fields naming the channel an event is about. A failed forward
+/// also names the channel it came in on, which is left out: it didn't fail,
+/// and would tie unrelated incidents together.
+const CHANNEL_KEYS: [&str; 4] = ["chan_id", "channel_id", "outgoing_chan_id", "channel_point"];
+
+/// Something events can have in common.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Entity {
+    /// The node's own connection to nodegaze
+    Node,
+    Peer(String),
+    Channel(String),
+}
+
+fn entities(event: &EventResponse) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    if matches!(
+        event.event_type,
+        EventType::NodeConnected
+            | EventType::NodeDisconnected
+            | EventType::NodeResync
+            | EventType::SubscriptionDegraded
+    ) {
+        entities.push(Entity::Node);
+    }
+
+    let data = serde_json::to_value(&event.data).unwrap_or_default();
+    let value_of = |key: &&str| match data.get(*key)? {
+        Value::String(value) if !value.is_empty() => Some(value.clone()),
+        Value::Number(value) if value.as_u64() != Some(0) => Some(value.to_string()),
+        _ => None,
+    };
+    entities.extend(PEER_KEYS.iter().filter_map(value_of).map(Entity::Peer));
+    entities.extend(
+        CHANNEL_KEYS
+            .iter()
+            .filter_map(value_of)
+            .map(Entity::Channel),
+    );
+    entities
+}
+
+fn severity_rank(severity: &EventSeverity) -> u8 {
+    match severity {
+        EventSeverity::Info => 0,
+        EventSeverity::Warning => 1,
+        EventSeverity::Critical => 2,
+    }
+}
+
+/// Events grouped so far.
+struct Group {
+    entities: BTreeSet<Entity>,
+    events: Vec<EventResponse>,
+    last_at: DateTime<Utc>,
+}
+
+/// Groups `events`, oldest first, into incidents as of `now`, newest
+/// incident first.
+pub fn group_incidents(
+    events: Vec<EventResponse>,
+    gap: Duration,
+    now: DateTime<Utc>,
+) -> Vec<Incident> {
+    let mut groups: Vec<Option<Group>> = Vec::new();
+    // Group each entity of a node last appeared in
+    let mut latest: HashMap<(String, Entity), usize> = HashMap::new();
+
+    for event in events {
+        let event_entities = entities(&event);
+        if event_entities.is_empty() {
+            continue;
+        }
+
+        let mut related: Vec<usize> = event_entities
+            .iter()
+            .filter_map(|entity| latest.get(&(event.node_id.clone(), entity.clone())))
+            .copied()
+            .filter(|&index| {
+                groups[index]
+                    .as_ref()
+                    .is_some_and(|group| event.timestamp - group.last_at <= gap)
+            })
+            .collect();
+        related.sort_unstable();
+        related.dedup();
+
+        // An event related to several groups joins them into the oldest.
+        let index = match related.split_first() {
+            Some((&first, others)) => {
+                for &other in others {
+                    let Some(merged) = groups[other].take() else {
+                        continue;
+                    };
+                    for entity in &merged.entities {
+                        latest.insert((event.node_id.clone(), entity.clone()), first);
+                    }
+                    if let Some(group) = groups[first].as_mut() {
+                        group.entities.extend(merged.entities);
+                        group.events.extend(merged.events);
+                        group.events.sort_by_key(|event| event.timestamp);
+                        group.last_at = group.last_at.max(merged.last_at);
+                    }
+                }
+                first
+            }
+            None => {
+                groups.push(Some(Group {
+                    entities: BTreeSet::new(),
+                    events: Vec::new(),
+                    last_at: event.timestamp,
+                }));
+                groups.len() - 1
+            }
+        };
+
+        if let Some(group) = groups[index].as_mut() {
+            for entity in event_entities {
+                latest.insert((event.node_id.clone(), entity.clone()), index);
+                group.entities.insert(entity);
+            }
+            group.last_at = group.last_at.max(event.timestamp);
+            group.events.push(event);
+        }
+    }
+
+    let mut incidents: Vec<Incident> = groups
+        .into_iter()
+        .flatten()
+        .filter_map(|group| {
+            let first = group.events.first()?;
+            let last = group.events.last()?;
+            let severity = group
+                .events
+                .iter()
+                .map(|event| &event.severity)
+                .max_by_key(|severity| severity_rank(severity))?
+                .clone();
+            if severity == EventSeverity::Info {
+                return None;
+            }
+
+            let mut peers = Vec::new();
+            let mut channels = Vec::new();
+            for entity in group.entities {
+                match entity {
+                    Entity::Node => {}
+                    Entity::Peer(pubkey) => peers.push(pubkey),
+                    Entity::Channel(channel) => channels.push(channel),
+                }
+            }
+
+            Some(Incident {
+                id: first.id.clone(),
+                node_id: first.node_id.clone(),
+                node_alias: first.node_alias.clone(),
+                severity,
+                started_at: first.timestamp,
+                ended_at: last.timestamp,
+                resolved: last.severity == EventSeverity::Info || now - last.timestamp > gap,
+                peers,
+                channels,
+                events: group.events,
+            })
+        })
+        .collect();
+    incidents.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    incidents
+}
+
+/// Service layer for incident timelines.
+pub struct IncidentService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> IncidentService<'a> {
+    /// Creates a new IncidentService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Groups the account's events of the requested number of days into
+    /// incidents, newest first.
+    pub async fn get_incidents(
+        &self,
+        account_id: &str,
+        query: IncidentQuery,
+    ) -> ServiceResult<Vec<Incident>> {
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_INCIDENT_DAYS)
+            .clamp(1, MAX_INCIDENT_DAYS);
+        let gap = Duration::minutes(
+            query
+                .gap_minutes
+                .unwrap_or(DEFAULT_GAP_MINUTES)
+                .clamp(1, MAX_GAP_MINUTES),
+        );
+
+        let now = Utc::now();
+        let events = EventRepository::new(self.pool)
+            .get_events_since(account_id, now - Duration::days(days), MAX_INCIDENT_EVENTS)
+            .await?;
+
+        Ok(group_incidents(
+            events.into_iter().map(EventResponse::from).collect(),
+            gap,
+            now,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::EventData;
+    use serde_json::json;
+
+    fn event(
+        minute: i64,
+        event_type: EventType,
+        severity: EventSeverity,
+        data: Value,
+    ) -> EventResponse {
+        let timestamp = DateTime::from_timestamp(1_700_000_000 + minute * 60, 0).unwrap();
+        EventResponse {
+            data: EventData::parse(&event_type, &data.to_string()),
+            id: format!("event-{minute}"),
+            account_id: "account".to_string(),
+            user_id: "user".to_string(),
+            node_id: "node".to_string(),
+            node_alias: "alias".to_string(),
+            event_type,
+            severity,
+            title: String::new(),
+            description: String::new(),
+            notifications_id: None,
+            timestamp,
+            created_at: timestamp,
+        }
+    }
+
+    #[test]
+    fn test_group_incidents() {
+        let events = vec![
+            event(
+                0,
+                EventType::ForwardFailed,
+                EventSeverity::Info,
+                json!({ "incoming_chan_id": 9, "outgoing_chan_id": 1 }),
+            ),
+            event(
+                10,
+                EventType::ChannelClosed,
+                EventSeverity::Critical,
+                json!({ "chan_id": 1, "remote_pubkey": "02aa" }),
+            ),
+            // Unrelated forward failures never make an incident on their own.
+            event(
+                12,
+                EventType::ForwardFailed,
+                EventSeverity::Info,
+                json!({ "incoming_chan_id": 1, "outgoing_chan_id": 2 }),
+            ),
+            event(
+                30,
+                EventType::PeerAnnouncementChanged,
+                EventSeverity::Warning,
+                json!({ "peer_pubkey": "02aa" }),
+            ),
+            // Too long after the last event of the peer to be part of it.
+            event(
+                120,
+                EventType::PeerAnnouncementChanged,
+                EventSeverity::Warning,
+                json!({ "peer_pubkey": "02aa" }),
+            ),
+        ];
+        let now = DateTime::from_timestamp(1_700_000_000 + 130 * 60, 0).unwrap();
+
+        let incidents = group_incidents(events, Duration::minutes(30), now);
+        assert_eq!(incidents.len(), 2);
+        assert_eq!(incidents[0].id, "event-120");
+        assert!(!incidents[0].resolved);
+
+        let incident = &incidents[1];
+        assert_eq!(incident.id, "event-0");
+        assert_eq!(incident.events.len(), 3);
+        assert_eq!(incident.severity, EventSeverity::Critical);
+        assert_eq!(incident.peers, vec!["02aa"]);
+        assert_eq!(incident.channels, vec!["1"]);
+        assert!(incident.resolved);
+    }
+}
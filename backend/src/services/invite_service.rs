@@ -13,7 +13,9 @@ use crate::repositories::invite_repository::InviteRepository;
 use crate::repositories::role_repository::RoleRepository;
 use crate::repositories::user_repository::UserRepository;
 use crate::services::email_service::EmailService;
+use crate::services::role_service::RoleService;
 use crate::utils::generate_random_string::generate_random_string;
+use crate::utils::password::{hash_password, validate_password_strength};
 use chrono::{Duration, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
@@ -77,12 +79,36 @@ impl<'a> InviteService<'a> {
         create_invite: CreateInviteRequest,
         user: User,
     ) -> ServiceResult<Invite> {
+        let role_service = RoleService::new(self.pool);
+        let role_id = match create_invite.role_id {
+            Some(role_id) => Some(
+                role_service
+                    .get_assignable_role(&user.account_id, &role_id)
+                    .await?
+                    .id,
+            ),
+            None => None,
+        };
+        let node_ids = match create_invite.node_ids {
+            Some(node_ids) => Some(
+                serde_json::to_string(
+                    &role_service
+                        .validate_node_scope(&user.account_id, node_ids)
+                        .await?,
+                )
+                .map_err(|e| ServiceError::internal_error(e.to_string()))?,
+            ),
+            None => None,
+        };
+
         let create_invite = CreateInvite {
             id: Uuid::now_v7().to_string(),
             account_id: user.account_id.clone(),
             invitee_email: create_invite.email,
             inviter_id: user.id.clone(),
             invite_status: InviteStatus::Pending,
+            role_id,
+            node_ids,
             token: generate_random_string(20),
             expires_at: Utc::now() + Duration::days(7),
         };
@@ -319,6 +345,7 @@ impl<'a> InviteService<'a> {
         if invite.expires_at <= Utc::now() {
             return Err(ServiceError::validation("Invite has expired"));
         }
+        validate_password_strength(&accept_invite.password, &accept_invite.username)?;
 
         // Start a transaction for invite acceptance + user creation
         let mut tx = self
@@ -357,18 +384,21 @@ impl<'a> InviteService<'a> {
             return Err(ServiceError::validation("Invitation not resent"));
         }
 
-        // Create a new user
-        // Check if the Member role exists
+        // Create a new user with the invite's role, falling back to Member
         let role_repo = RoleRepository::new(self.pool);
-        let role = role_repo.get_role_by_name("Member").await?;
-        if role.is_none() {
-            return Err(ServiceError::not_found("Role", "Member"));
-        }
-
-        let role = role.unwrap();
+        let invited_role = match invite.role_id.as_deref() {
+            Some(role_id) => role_repo.get_role_by_id(role_id).await?,
+            None => None,
+        };
+        let role = match invited_role {
+            Some(role) => role,
+            None => role_repo
+                .get_role_by_name("Member")
+                .await?
+                .ok_or_else(|| ServiceError::not_found("Role", "Member"))?,
+        };
 
-        let password_hash = bcrypt::hash(&accept_invite.password, bcrypt::DEFAULT_COST)
-            .map_err(|e| ServiceError::validation(format!("Password hashing failed: {e}")))?;
+        let password_hash = hash_password(&accept_invite.password).await?;
 
         let user_id = Uuid::now_v7().to_string();
         // Create the user in the database
@@ -383,6 +413,7 @@ impl<'a> InviteService<'a> {
             role_id as "role_id!",
             role_access_level as "role_access_level: RoleAccessLevel",
             username as "username!",
+            display_name as "display_name?",
             password_hash as "password_hash!",
             email as "email!",
             is_active as "is_active!",
@@ -413,6 +444,22 @@ impl<'a> InviteService<'a> {
             }
         })?;
 
+        // Limit the new user to the nodes the invite granted
+        if let Some(node_ids) = &invite.node_ids {
+            sqlx::query!(
+                r#"
+                INSERT INTO user_node_scopes (user_id, account_id, node_ids)
+                VALUES (?, ?, ?)
+                "#,
+                user.id,
+                invite.account_id,
+                node_ids
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServiceError::Database { source: e.into() })?;
+        }
+
         // Commit the transaction
         tx.commit()
             .await
@@ -0,0 +1,241 @@
+//! How long invoices wait to be paid.
+//!
+//! Computed from the creation and settlement times of the invoices stored by
+//! the last node resync. Merchants can compare the p90 wait with the expiry
+//! they give invoices: an expiry well above it only keeps stale invoices
+//! open, while a high expiry rate says buyers need more time.
+
+use crate::database::models::{
+    InvoiceLatencyPoint, InvoiceLatencyQuery, InvoiceLatencyReport, InvoiceLatencyStats,
+    InvoiceTiming, LatencyPeriod,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::node_sync_repository::NodeSyncRepository;
+use crate::services::account_settings_service::AccountSettingsService;
+use crate::services::graph_sync::percentile;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+const DEFAULT_LATENCY_DAYS: i64 = 30;
+
+/// Longest range a report covers, which bounds the number of periods
+const MAX_LATENCY_DAYS: i64 = 366;
+
+/// The first day of the period `date` falls in.
+fn period_start(date: NaiveDate, period: LatencyPeriod) -> NaiveDate {
+    match period {
+        LatencyPeriod::Day => date,
+        LatencyPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+    }
+}
+
+/// Latency percentiles and expiry rate of a set of invoices.
+pub fn latency_stats(timings: &[&InvoiceTiming]) -> InvoiceLatencyStats {
+    let mut waits: Vec<i64> = Vec::new();
+    let mut stats = InvoiceLatencyStats {
+        invoices: timings.len() as i64,
+        ..Default::default()
+    };
+    let mut resolved = 0;
+    for timing in timings {
+        match timing.state.as_str() {
+            "Settled" => {
+                stats.settled += 1;
+                resolved += 1;
+                if let Some(settled_at) = timing.settled_at {
+                    waits.push((settled_at - timing.created_at).num_seconds().max(0));
+                }
+            }
+            "Expired" => {
+                stats.expired += 1;
+                resolved += 1;
+            }
+            // Canceled invoices are stored as Failed.
+            "Failed" => resolved += 1,
+            _ => {}
+        }
+    }
+    waits.sort_unstable();
+    stats.p50_secs = percentile(&waits, 50.0);
+    stats.p90_secs = percentile(&waits, 90.0);
+    stats.expiry_rate = (resolved > 0).then(|| stats.expired as f64 / resolved as f64);
+    stats
+}
+
+/// Builds the latency report of invoices created between `from` and `to`,
+/// with a point for every period of the range. Periods are calendar days or
+/// weeks in `tz`.
+pub fn invoice_latency(
+    timings: &[InvoiceTiming],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    period: LatencyPeriod,
+    tz: Tz,
+    synced_at: Option<DateTime<Utc>>,
+) -> InvoiceLatencyReport {
+    let local_date = |time: DateTime<Utc>| time.with_timezone(&tz).date_naive();
+    let mut by_period: BTreeMap<NaiveDate, Vec<&InvoiceTiming>> = BTreeMap::new();
+    let mut start = period_start(local_date(from), period);
+    while start <= local_date(to) {
+        by_period.insert(start, Vec::new());
+        start += match period {
+            LatencyPeriod::Day => Duration::days(1),
+            LatencyPeriod::Week => Duration::weeks(1),
+        };
+    }
+    for timing in timings {
+        if let Some(group) = by_period.get_mut(&period_start(local_date(timing.created_at), period))
+        {
+            group.push(timing);
+        }
+    }
+
+    InvoiceLatencyReport {
+        from,
+        to,
+        period,
+        overall: latency_stats(&timings.iter().collect::<Vec<_>>()),
+        periods: by_period
+            .into_iter()
+            .map(|(period_start, group)| InvoiceLatencyPoint {
+                period_start,
+                stats: latency_stats(&group),
+            })
+            .collect(),
+        synced_at,
+    }
+}
+
+/// Service layer for invoice latency analytics.
+pub struct InvoiceLatencyService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> InvoiceLatencyService<'a> {
+    /// Creates a new InvoiceLatencyService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Reports how long the node's invoices created in the queried range
+    /// took to settle, and how many expired.
+    pub async fn get_latency(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: InvoiceLatencyQuery,
+    ) -> ServiceResult<InvoiceLatencyReport> {
+        let to = query.to.unwrap_or_else(Utc::now);
+        let from = query
+            .from
+            .unwrap_or_else(|| to - Duration::days(DEFAULT_LATENCY_DAYS));
+        if from > to {
+            return Err(ServiceError::validation("'from' must not be after 'to'"));
+        }
+        if to - from > Duration::days(MAX_LATENCY_DAYS) {
+            return Err(ServiceError::validation(format!(
+                "Range must not exceed {MAX_LATENCY_DAYS} days"
+            )));
+        }
+
+        let repo = NodeSyncRepository::new(self.pool);
+        let timings = repo
+            .get_invoice_timings(account_id, node_id, from, to)
+            .await?;
+        let synced_at = repo.get_invoices_synced_at(account_id, node_id).await?;
+        let tz = AccountSettingsService::new(self.pool)
+            .get_settings(account_id)
+            .await?
+            .tz();
+
+        Ok(invoice_latency(
+            &timings,
+            from,
+            to,
+            query.period,
+            tz,
+            synced_at,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(state: &str, day: i64, wait_secs: Option<i64>) -> InvoiceTiming {
+        let created_at = DateTime::from_timestamp(1_700_000_000 + day * 86_400, 0).unwrap();
+        InvoiceTiming {
+            state: state.to_string(),
+            created_at,
+            settled_at: wait_secs.map(|secs| created_at + Duration::seconds(secs)),
+        }
+    }
+
+    #[test]
+    fn test_invoice_latency() {
+        let from = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let to = from + Duration::days(2);
+        let timings = vec![
+            timing("Settled", 0, Some(10)),
+            timing("Settled", 0, Some(30)),
+            timing("Settled", 0, Some(600)),
+            timing("Expired", 0, None),
+            timing("Settled", 2, Some(20)),
+            timing("Open", 2, None),
+        ];
+
+        let report = invoice_latency(&timings, from, to, LatencyPeriod::Day, chrono_tz::UTC, None);
+
+        assert_eq!(report.overall.invoices, 6);
+        assert_eq!(report.overall.settled, 4);
+        assert_eq!(report.overall.p50_secs, Some(20));
+        assert_eq!(report.overall.p90_secs, Some(600));
+        assert_eq!(report.overall.expiry_rate, Some(0.2));
+        assert_eq!(report.periods.len(), 3);
+        assert_eq!(report.periods[0].stats.p50_secs, Some(30));
+        assert_eq!(report.periods[0].stats.expiry_rate, Some(0.25));
+        assert_eq!(report.periods[1].stats.invoices, 0);
+        assert_eq!(report.periods[1].stats.expiry_rate, None);
+
+        let weekly = invoice_latency(
+            &timings,
+            from,
+            to,
+            LatencyPeriod::Week,
+            chrono_tz::UTC,
+            None,
+        );
+        assert!(
+            weekly
+                .periods
+                .iter()
+                .all(|point| point.period_start.weekday() == chrono::Weekday::Mon)
+        );
+        assert_eq!(
+            weekly
+                .periods
+                .iter()
+                .map(|point| point.stats.invoices)
+                .sum::<i64>(),
+            6
+        );
+
+        // 22:13 UTC is already the next day in Tokyo.
+        let tokyo = invoice_latency(
+            &timings,
+            from,
+            to,
+            LatencyPeriod::Day,
+            chrono_tz::Asia::Tokyo,
+            None,
+        );
+        assert_eq!(
+            tokyo.periods[0].period_start,
+            NaiveDate::from_ymd_opt(2023, 11, 15).unwrap()
+        );
+        assert_eq!(tokyo.periods[0].stats.invoices, 4);
+    }
+}
@@ -0,0 +1,120 @@
+//! Invoice totals for the dashboard.
+//!
+//! Computed in SQL over the invoices stored by the last node resync, so the
+//! dashboard doesn't have to page through every invoice on the node. Canceled
+//! invoices are stored in the `Failed` state, as the invoice listing reports
+//! them.
+
+use crate::database::models::{InvoiceStateTotals, InvoiceStats, InvoiceStatsQuery};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::node_sync_repository::NodeSyncRepository;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+
+const DEFAULT_STATS_DAYS: i64 = 30;
+
+/// Folds per-state (state, count, value_msat) rows into invoice stats.
+pub fn invoice_stats(
+    rows: Vec<(String, i64, i64)>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    synced_at: Option<DateTime<Utc>>,
+) -> InvoiceStats {
+    let mut stats = InvoiceStats {
+        from,
+        to,
+        open: InvoiceStateTotals::default(),
+        settled: InvoiceStateTotals::default(),
+        expired: InvoiceStateTotals::default(),
+        canceled: InvoiceStateTotals::default(),
+        total: InvoiceStateTotals::default(),
+        settlement_rate: None,
+        synced_at,
+    };
+
+    for (state, count, value_msat) in rows {
+        let value_sat = value_msat / 1000;
+        let totals = match state.as_str() {
+            "Open" => Some(&mut stats.open),
+            "Settled" => Some(&mut stats.settled),
+            "Expired" => Some(&mut stats.expired),
+            "Failed" => Some(&mut stats.canceled),
+            _ => None,
+        };
+        if let Some(totals) = totals {
+            totals.count += count;
+            totals.value_sat += value_sat;
+        }
+        stats.total.count += count;
+        stats.total.value_sat += value_sat;
+    }
+
+    let resolved = stats.settled.count + stats.expired.count + stats.canceled.count;
+    stats.settlement_rate = (resolved > 0).then(|| stats.settled.count as f64 / resolved as f64);
+    stats
+}
+
+/// Service layer for invoice statistics.
+pub struct InvoiceStatsService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> InvoiceStatsService<'a> {
+    /// Creates a new InvoiceStatsService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the node's invoice totals by state over the queried range.
+    pub async fn get_stats(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: InvoiceStatsQuery,
+    ) -> ServiceResult<InvoiceStats> {
+        let to = query.to.unwrap_or_else(Utc::now);
+        let from = query
+            .from
+            .unwrap_or_else(|| to - Duration::days(DEFAULT_STATS_DAYS));
+        if from > to {
+            return Err(ServiceError::validation("'from' must not be after 'to'"));
+        }
+
+        let repo = NodeSyncRepository::new(self.pool);
+        let rows = repo
+            .get_invoice_totals_by_state(account_id, node_id, from, to)
+            .await?;
+        let synced_at = repo.get_invoices_synced_at(account_id, node_id).await?;
+
+        Ok(invoice_stats(rows, from, to, synced_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invoice_stats() {
+        let to = Utc::now();
+        let from = to - Duration::days(1);
+        let rows = vec![
+            ("Open".to_string(), 2, 3_000_000),
+            ("Settled".to_string(), 6, 12_000_500),
+            ("Expired".to_string(), 1, 1_000_000),
+            ("Failed".to_string(), 1, 0),
+        ];
+
+        let stats = invoice_stats(rows, from, to, None);
+        assert_eq!(stats.open.count, 2);
+        assert_eq!(stats.settled.value_sat, 12_000);
+        assert_eq!(stats.canceled.count, 1);
+        assert_eq!(stats.total.count, 10);
+        assert_eq!(stats.total.value_sat, 16_000);
+        assert_eq!(stats.settlement_rate, Some(0.75));
+
+        let empty = invoice_stats(Vec::new(), from, to, None);
+        assert_eq!(empty.total.count, 0);
+        assert_eq!(empty.settlement_rate, None);
+    }
+}
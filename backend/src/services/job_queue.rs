@@ -0,0 +1,197 @@
+//! SQLite-backed background job queue.
+//!
+//! Jobs are persisted in the `jobs` table so they survive restarts. A small pool
+//! of workers polls for due jobs, runs them, and reschedules failures with
+//! exponential backoff until `max_attempts` is reached.
+
+use crate::database::models::{Job, JobFilters, JobResponse, JobType};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::job_repository::JobRepository;
+use crate::services::escalations::run_escalation_job;
+use crate::services::node_export::run_export_job;
+use crate::services::node_sync::run_resync_job;
+use crate::services::notification_dispatcher::NotificationDispatcher;
+use crate::services::scheduler::run_scheduled_task;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Attempts made before a job is marked as failed.
+pub const DEFAULT_MAX_ATTEMPTS: i64 = 5;
+
+/// How long an idle worker sleeps before polling for due jobs again.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const BASE_RETRY_DELAY_SECS: i64 = 30;
+const MAX_RETRY_DELAY_SECS: i64 = 3600;
+
+/// Delay before retrying a job that has failed `attempts` times.
+pub fn retry_delay(attempts: i64) -> chrono::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    let secs = BASE_RETRY_DELAY_SECS.saturating_mul(2_i64.pow(exponent));
+    chrono::Duration::seconds(secs.min(MAX_RETRY_DELAY_SECS))
+}
+
+/// Service layer for enqueuing and inspecting jobs.
+pub struct JobQueue<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> JobQueue<'a> {
+    /// Creates a new JobQueue instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Enqueues a job. It runs as soon as a worker is free, or at `run_at` if given.
+    pub async fn enqueue<T: Serialize>(
+        &self,
+        job_type: JobType,
+        payload: &T,
+        account_id: Option<&str>,
+        run_at: Option<DateTime<Utc>>,
+    ) -> ServiceResult<Job> {
+        let payload = serde_json::to_string(payload)
+            .map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        let job = JobRepository::new(self.pool)
+            .create_job(
+                &Uuid::now_v7().to_string(),
+                job_type,
+                &payload,
+                account_id,
+                DEFAULT_MAX_ATTEMPTS,
+                run_at.unwrap_or_else(Utc::now),
+            )
+            .await?;
+
+        tracing::debug!("Enqueued {} job {}", job.job_type, job.id);
+        Ok(job)
+    }
+
+    /// Lists an account's jobs, together with system-wide jobs if
+    /// `include_system` is set.
+    pub async fn get_jobs(
+        &self,
+        account_id: &str,
+        include_system: bool,
+        filters: JobFilters,
+        limit: i64,
+        offset: i64,
+    ) -> ServiceResult<(Vec<JobResponse>, i64)> {
+        let repo = JobRepository::new(self.pool);
+        let jobs = repo
+            .get_jobs(
+                account_id,
+                include_system,
+                filters.status.clone(),
+                filters.job_type.clone(),
+                limit,
+                offset,
+            )
+            .await?;
+        let total = repo
+            .count_jobs(account_id, include_system, filters.status, filters.job_type)
+            .await?;
+
+        Ok((jobs.into_iter().map(JobResponse::from).collect(), total))
+    }
+}
+
+/// Starts the worker pool. Jobs left running by a previous process are requeued first.
+pub async fn start_workers(pool: SqlitePool, workers: usize) {
+    match JobRepository::new(&pool).requeue_running_jobs().await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Requeued {} interrupted job(s)", count),
+        Err(e) => tracing::error!("Failed to requeue interrupted jobs: {}", e),
+    }
+
+    for worker_id in 0..workers.max(1) {
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            loop {
+                match JobRepository::new(&pool).claim_next_job(Utc::now()).await {
+                    Ok(Some(job)) => process_job(&pool, job, worker_id).await,
+                    Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                    Err(e) => {
+                        tracing::error!("Job worker {} failed to claim a job: {}", worker_id, e);
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                    }
+                }
+            }
+        });
+    }
+
+    tracing::info!("Started {} job worker(s)", workers.max(1));
+}
+
+async fn process_job(pool: &SqlitePool, job: Job, worker_id: usize) {
+    tracing::debug!(
+        "Worker {} running {} job {} (attempt {}/{})",
+        worker_id,
+        job.job_type,
+        job.id,
+        job.attempts,
+        job.max_attempts
+    );
+
+    let repo = JobRepository::new(pool);
+    let result = match run_job(pool, &job).await {
+        Ok(()) => repo.complete_job(&job.id).await,
+        Err(error) => {
+            let retry_at =
+                (job.attempts < job.max_attempts).then(|| Utc::now() + retry_delay(job.attempts));
+            match retry_at {
+                Some(at) => tracing::warn!(
+                    "{} job {} failed, retrying at {}: {}",
+                    job.job_type,
+                    job.id,
+                    at,
+                    error
+                ),
+                None => tracing::error!(
+                    "{} job {} failed permanently after {} attempt(s): {}",
+                    job.job_type,
+                    job.id,
+                    job.attempts,
+                    error
+                ),
+            }
+            repo.fail_job(&job.id, &error, retry_at).await
+        }
+    };
+
+    if let Err(e) = result {
+        tracing::error!("Failed to record outcome of job {}: {}", job.id, e);
+    }
+}
+
+/// Runs a single job by dispatching on its type.
+async fn run_job(pool: &SqlitePool, job: &Job) -> Result<(), String> {
+    match job.job_type {
+        JobType::NotificationDelivery => {
+            NotificationDispatcher::new()
+                .run_delivery_job(pool, &job.payload)
+                .await
+        }
+        JobType::ScheduledTask => run_scheduled_task(pool, &job.payload).await,
+        JobType::NodeResync => run_resync_job(pool, &job.payload).await,
+        JobType::NodeExport => run_export_job(pool, &job.payload).await,
+        JobType::AlertEscalation => run_escalation_job(pool, &job.payload).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off_and_caps() {
+        assert_eq!(retry_delay(1), chrono::Duration::seconds(30));
+        assert_eq!(retry_delay(2), chrono::Duration::seconds(60));
+        assert_eq!(retry_delay(4), chrono::Duration::seconds(240));
+        assert_eq!(retry_delay(20), chrono::Duration::seconds(3600));
+    }
+}
@@ -0,0 +1,223 @@
+//! Rotation of the key secrets are encrypted with at rest.
+//!
+//! Node credential secrets, notification URLs and headers, and swap rescue
+//! data are sealed with a per-value data key, wrapped by the configured
+//! `ENCRYPTION_KEY` and tagged with its version. After a new key is
+//! configured, rekeying rewraps every data key still under an older version,
+//! and seals values stored before they were encrypted at all. Once the status
+//! reports nothing left under a retired version, that key can be removed.
+
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::notification_repository::NotificationRepository;
+use crate::repositories::swap_repository::SwapRepository;
+use crate::utils::crypto::{CryptoError, StringCrypto};
+use serde::Serialize;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+/// How the values of one kind of secret are stored.
+#[derive(Debug, Serialize)]
+pub struct SecretKeyVersions {
+    pub kind: &'static str,
+    /// Values not sealed yet: plaintext, or swap rescue data encrypted
+    /// directly with the key before envelope encryption
+    pub unsealed: u64,
+    /// Number of sealed values per key version
+    pub by_key_version: BTreeMap<u32, u64>,
+}
+
+impl SecretKeyVersions {
+    fn count<'v>(kind: &'static str, values: impl Iterator<Item = &'v str>) -> Self {
+        let mut versions = Self {
+            kind,
+            unsealed: 0,
+            by_key_version: BTreeMap::new(),
+        };
+        for value in values {
+            match StringCrypto::key_version(value) {
+                Some(version) => *versions.by_key_version.entry(version).or_default() += 1,
+                None => versions.unsealed += 1,
+            }
+        }
+        versions
+    }
+}
+
+/// Which key versions stored secrets are encrypted with.
+#[derive(Debug, Serialize)]
+pub struct EncryptionStatus {
+    pub current_key_version: u32,
+    pub secrets: Vec<SecretKeyVersions>,
+}
+
+/// Values moved to the current key by a rekey.
+#[derive(Debug, Serialize)]
+pub struct RekeyReport {
+    pub key_version: u32,
+    pub credentials: u64,
+    pub notifications: u64,
+    pub swaps: u64,
+}
+
+/// Brings a stored value under the current key, or returns `None` if it
+/// already is. Unsealed values are either plaintext or legacy ciphertext.
+fn rekey_value(
+    crypto: &StringCrypto,
+    value: &str,
+    unsealed_is_plaintext: bool,
+) -> Result<Option<String>, CryptoError> {
+    match StringCrypto::key_version(value) {
+        Some(_) => crypto.rewrap(value),
+        None if unsealed_is_plaintext => crypto.encrypt(value).map(Some),
+        None => crypto.encrypt(&crypto.decrypt(value)?).map(Some),
+    }
+}
+
+fn rekey_error(kind: &str, id: &str, error: CryptoError) -> ServiceError {
+    ServiceError::internal_error(format!("Failed to rekey {kind} {id}: {error}"))
+}
+
+/// Service layer for encryption key rotation.
+pub struct KeyRotationService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> KeyRotationService<'a> {
+    /// Creates a new KeyRotationService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    fn crypto() -> ServiceResult<StringCrypto> {
+        StringCrypto::from_env()
+            .map_err(|e| ServiceError::internal_error(format!("Encryption keys unavailable: {e}")))
+    }
+
+    /// Counts stored secrets by the key version they're encrypted with.
+    pub async fn status(&self) -> ServiceResult<EncryptionStatus> {
+        let credentials = CredentialRepository::new(self.pool)
+            .get_stored_secrets()
+            .await?;
+        let notification_repo = NotificationRepository::new(self.pool);
+        let notifications = notification_repo.get_stored_urls().await?;
+        let notification_headers = notification_repo.get_stored_headers().await?;
+        let swaps = SwapRepository::new(self.pool)
+            .get_stored_rescue_data()
+            .await?;
+
+        Ok(EncryptionStatus {
+            current_key_version: Self::crypto()?.current_version(),
+            secrets: vec![
+                SecretKeyVersions::count(
+                    "credentials",
+                    credentials.iter().flat_map(|(_, macaroon, client_key)| {
+                        std::iter::once(macaroon.as_str()).chain(client_key.as_deref())
+                    }),
+                ),
+                SecretKeyVersions::count(
+                    "notifications",
+                    notifications.iter().map(|(_, url)| url.as_str()).chain(
+                        notification_headers
+                            .iter()
+                            .map(|(_, headers)| headers.as_str()),
+                    ),
+                ),
+                SecretKeyVersions::count("swaps", swaps.iter().map(|(_, data)| data.as_str())),
+            ],
+        })
+    }
+
+    /// Re-encrypts every stored secret not yet under the current key. Rows
+    /// changed while the rekey runs are left for the next one.
+    pub async fn rekey(&self, account_id: &str, user_id: &str) -> ServiceResult<RekeyReport> {
+        let crypto = Self::crypto()?;
+        let mut report = RekeyReport {
+            key_version: crypto.current_version(),
+            credentials: 0,
+            notifications: 0,
+            swaps: 0,
+        };
+
+        let credential_repo = CredentialRepository::new(self.pool);
+        for (id, macaroon, client_key) in credential_repo.get_stored_secrets().await? {
+            let new_macaroon = rekey_value(&crypto, &macaroon, true)
+                .map_err(|e| rekey_error("credential", &id, e))?;
+            let new_client_key = client_key
+                .as_deref()
+                .map(|key| rekey_value(&crypto, key, true))
+                .transpose()
+                .map_err(|e| rekey_error("credential", &id, e))?
+                .flatten();
+            if new_macaroon.is_none() && new_client_key.is_none() {
+                continue;
+            }
+            let updated = credential_repo
+                .update_stored_secrets(
+                    &id,
+                    &macaroon,
+                    new_macaroon.as_deref().unwrap_or(&macaroon),
+                    new_client_key.as_deref().or(client_key.as_deref()),
+                )
+                .await?;
+            report.credentials += u64::from(updated);
+        }
+
+        let notification_repo = NotificationRepository::new(self.pool);
+        for (id, url) in notification_repo.get_stored_urls().await? {
+            let Some(new_url) = rekey_value(&crypto, &url, true)
+                .map_err(|e| rekey_error("notification", &id, e))?
+            else {
+                continue;
+            };
+            let updated = notification_repo
+                .update_stored_url(&id, &url, &new_url)
+                .await?;
+            report.notifications += u64::from(updated);
+        }
+        for (id, headers) in notification_repo.get_stored_headers().await? {
+            let Some(new_headers) = rekey_value(&crypto, &headers, true)
+                .map_err(|e| rekey_error("notification", &id, e))?
+            else {
+                continue;
+            };
+            let updated = notification_repo
+                .update_stored_headers(&id, &headers, &new_headers)
+                .await?;
+            report.notifications += u64::from(updated);
+        }
+
+        let swap_repo = SwapRepository::new(self.pool);
+        for (id, rescue_data) in swap_repo.get_stored_rescue_data().await? {
+            let Some(new_rescue_data) = rekey_value(&crypto, &rescue_data, false)
+                .map_err(|e| rekey_error("swap", &id, e))?
+            else {
+                continue;
+            };
+            let updated = swap_repo
+                .update_rescue_data(&id, &rescue_data, &new_rescue_data)
+                .await?;
+            report.swaps += u64::from(updated);
+        }
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "encryption_rekeyed",
+                "system",
+                None,
+                &json!({
+                    "key_version": report.key_version,
+                    "credentials": report.credentials,
+                    "notifications": report.notifications,
+                    "swaps": report.swaps,
+                }),
+            )
+            .await?;
+
+        Ok(report)
+    }
+}
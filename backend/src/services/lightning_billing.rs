@@ -0,0 +1,701 @@
+//! Plans paid in sats with invoices from the operator's treasury node.
+//!
+//! When `TREASURY_ACCOUNT_ID` and `TREASURY_NODE_ID` are set, an account on
+//! Free can subscribe by paying a BOLT11 invoice for one month of a plan.
+//! Invoices are BOLT11 only: the node clients can't create BOLT12 offers.
+//! Payments are picked up from the treasury node's invoice stream, and the
+//! `LightningBilling` task checks any invoice still open against the node in
+//! case an event was missed.
+//!
+//! The same task issues each renewal invoice a few days before the period
+//! ends and emails it to the account's admin. A renewal still unpaid when
+//! the period ends leaves the subscription past due for `BILLING_GRACE_DAYS`,
+//! after which the account is suspended: its members can no longer log in
+//! and its admin is sent an invoice that reinstates it when paid.
+
+use crate::config::Config;
+use crate::database::models::{
+    AccountSubscription, BillingInvoice, BillingInvoiceStatus, BillingProvider,
+    LightningSubscribeRequest, Plan, SubscriptionStatus,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::account_repository::AccountRepository;
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::billing_invoice_repository::BillingInvoiceRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::subscription_repository::SubscriptionRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::billing::{effective_plan, lightning_price_sat};
+use crate::services::email_service::EmailService;
+use crate::utils::InvoiceStatus;
+use crate::utils::handlers_common::{NodeClientHandle, create_node_client};
+use crate::utils::jwt::{Claims, NodeCredentials};
+use bitcoin::secp256k1::PublicKey;
+use chrono::{DateTime, Duration, Months, Utc};
+use lightning::ln::PaymentHash;
+use lightning_invoice::Bolt11Invoice;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// How long before the period ends the renewal invoice is issued.
+const RENEWAL_LEAD_DAYS: i64 = 3;
+
+/// Expiry of an invoice that starts a subscription from the billing page.
+const SUBSCRIBE_INVOICE_EXPIRY_SECS: i64 = 3600;
+
+/// Expiry of the invoice sent when an account is suspended.
+const REINSTATEMENT_INVOICE_EXPIRY_DAYS: i64 = 30;
+
+/// Where a Lightning subscription stands relative to its period end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Standing {
+    Current,
+    /// The period ends soon; the renewal invoice should be out
+    RenewalDue,
+    /// The period has ended unpaid but the grace period hasn't
+    PastDue,
+    Suspend,
+}
+
+/// Classifies a subscription paid through `period_end` at `now`.
+pub fn standing(period_end: DateTime<Utc>, now: DateTime<Utc>, grace_days: i64) -> Standing {
+    if now >= period_end + Duration::days(grace_days) {
+        Standing::Suspend
+    } else if now >= period_end {
+        Standing::PastDue
+    } else if now >= period_end - Duration::days(RENEWAL_LEAD_DAYS) {
+        Standing::RenewalDue
+    } else {
+        Standing::Current
+    }
+}
+
+/// The end of the period a paid invoice covers. A renewal extends the
+/// current period, so paying during the grace period doesn't add the days
+/// already used; anything else starts a month from payment.
+pub fn paid_through(
+    invoice: &BillingInvoice,
+    subscription: Option<&AccountSubscription>,
+    paid_at: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let renews = subscription
+        .filter(|s| s.provider == BillingProvider::Lightning && s.suspended_at.is_none())
+        .and_then(|s| s.current_period_end)
+        == Some(invoice.period_start);
+    if renews {
+        invoice.period_end
+    } else {
+        add_month(paid_at)
+    }
+}
+
+fn add_month(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.checked_add_months(Months::new(1))
+        .unwrap_or(time + Duration::days(30))
+}
+
+/// Service layer for paying plans over Lightning.
+pub struct LightningBillingService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> LightningBillingService<'a> {
+    /// Creates a new LightningBillingService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns an invoice that subscribes the account to a plan for a month
+    /// once paid. An open invoice for the same plan is reused.
+    pub async fn subscribe(
+        &self,
+        claims: &Claims,
+        request: LightningSubscribeRequest,
+    ) -> ServiceResult<BillingInvoice> {
+        let config = Config::from_env()?;
+        if !config.lightning_billing_enabled() {
+            return Err(ServiceError::invalid_operation(
+                "Lightning billing is not enabled",
+            ));
+        }
+        let amount_sat = lightning_price_sat(&config, request.plan).ok_or_else(|| {
+            ServiceError::validation(format!(
+                "The {} plan can't be paid over Lightning",
+                request.plan
+            ))
+        })?;
+
+        let account_id = claims.account_id();
+        let subscription = SubscriptionRepository::new(self.pool)
+            .get_subscription(account_id)
+            .await?;
+        let current = effective_plan(subscription.as_ref());
+        if current != Plan::Free {
+            return Err(ServiceError::invalid_operation(format!(
+                "The account is already subscribed to the {current} plan"
+            )));
+        }
+
+        let now = Utc::now();
+        let open = BillingInvoiceRepository::new(self.pool)
+            .list_for_account(account_id)
+            .await?
+            .into_iter()
+            .find(|invoice| {
+                invoice.status == BillingInvoiceStatus::Open
+                    && invoice.plan == request.plan
+                    && invoice.amount_sat == amount_sat
+                    && invoice.expires_at > now
+            });
+        if let Some(invoice) = open {
+            return Ok(invoice);
+        }
+
+        let invoice = issue_invoice(
+            self.pool,
+            &config,
+            account_id,
+            request.plan,
+            amount_sat,
+            now,
+            now + Duration::seconds(SUBSCRIBE_INVOICE_EXPIRY_SECS),
+        )
+        .await?
+        .ok_or_else(|| ServiceError::internal_error("An invoice for this period already exists"))?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(claims.user_id()),
+                "billing_invoice_created",
+                "billing_invoice",
+                Some(&invoice.id),
+                &json!({ "plan": invoice.plan, "amount_sat": invoice.amount_sat }),
+            )
+            .await?;
+        Ok(invoice)
+    }
+
+    /// Lists the account's Lightning invoices, newest first.
+    pub async fn list_invoices(&self, account_id: &str) -> ServiceResult<Vec<BillingInvoice>> {
+        Ok(BillingInvoiceRepository::new(self.pool)
+            .list_for_account(account_id)
+            .await?)
+    }
+}
+
+/// Applies payments seen on a node's invoice stream. Does nothing unless
+/// the node is the treasury node.
+pub async fn record_settled_invoices(
+    pool: &SqlitePool,
+    account_id: &str,
+    node_id: &str,
+    payment_hashes: &[String],
+) {
+    let Ok(config) = Config::from_env() else {
+        return;
+    };
+    if !config.lightning_billing_enabled()
+        || config.treasury_account_id.as_deref() != Some(account_id)
+        || config.treasury_node_id.as_deref() != Some(node_id)
+    {
+        return;
+    }
+
+    let repo = BillingInvoiceRepository::new(pool);
+    for payment_hash in payment_hashes {
+        let invoice = match repo.get_by_payment_hash(payment_hash).await {
+            Ok(Some(invoice)) => invoice,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::error!("Failed to look up billing invoice {}: {}", payment_hash, e);
+                continue;
+            }
+        };
+        if let Err(e) = settle(pool, &invoice, Utc::now()).await {
+            tracing::error!("Failed to apply payment of invoice {}: {}", invoice.id, e);
+        }
+    }
+}
+
+/// Reconciles open invoices with the treasury node, issues renewal
+/// invoices, and marks overdue subscriptions past due or suspends them.
+pub async fn run_lightning_billing(pool: &SqlitePool) -> Result<(), String> {
+    let config = Config::from_env().map_err(|e| e.to_string())?;
+    if !config.lightning_billing_enabled() {
+        return Ok(());
+    }
+    let now = Utc::now();
+    let invoice_repo = BillingInvoiceRepository::new(pool);
+    let subscription_repo = SubscriptionRepository::new(pool);
+
+    let open = invoice_repo.list_open().await.map_err(|e| e.to_string())?;
+    if !open.is_empty() {
+        let client = treasury_client(pool, &config)
+            .await
+            .map_err(|e| e.to_string())?;
+        for invoice in &open {
+            let Some(payment_hash) = payment_hash(&invoice.payment_hash) else {
+                continue;
+            };
+            let state = match client.get_invoice_details(&payment_hash).await {
+                Ok(details) => Some((details.state, details.settle_date)),
+                Err(e) => {
+                    tracing::warn!("Failed to check billing invoice {}: {}", invoice.id, e);
+                    None
+                }
+            };
+            match state {
+                Some((InvoiceStatus::Settled, settle_date)) => {
+                    let paid_at = settle_date
+                        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                        .unwrap_or(now);
+                    settle(pool, invoice, paid_at)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+                Some((InvoiceStatus::Expired | InvoiceStatus::Failed, _)) => {
+                    invoice_repo
+                        .mark_expired(&invoice.id)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+                _ if invoice.expires_at <= now => {
+                    invoice_repo
+                        .mark_expired(&invoice.id)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let open = invoice_repo.list_open().await.map_err(|e| e.to_string())?;
+    let subscriptions = subscription_repo
+        .get_by_provider(BillingProvider::Lightning)
+        .await
+        .map_err(|e| e.to_string())?;
+    for subscription in subscriptions {
+        if subscription.suspended_at.is_some()
+            || subscription.status == SubscriptionStatus::Canceled
+        {
+            continue;
+        }
+        let Some(period_end) = subscription.current_period_end else {
+            continue;
+        };
+        let account_id = &subscription.account_id;
+
+        let result = match standing(period_end, now, config.billing_grace_days) {
+            Standing::Current => Ok(()),
+            Standing::RenewalDue | Standing::PastDue => {
+                renew(pool, &config, &subscription, period_end, &open, now).await
+            }
+            Standing::Suspend => suspend(pool, &config, &subscription, now).await,
+        };
+        if let Err(e) = result {
+            tracing::error!("Lightning billing failed for account {}: {}", account_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Issues the renewal invoice if it isn't out yet, and marks the
+/// subscription past due once the period has ended.
+async fn renew(
+    pool: &SqlitePool,
+    config: &Config,
+    subscription: &AccountSubscription,
+    period_end: DateTime<Utc>,
+    open: &[BillingInvoice],
+    now: DateTime<Utc>,
+) -> ServiceResult<()> {
+    let account_id = &subscription.account_id;
+    if now >= period_end && subscription.status != SubscriptionStatus::PastDue {
+        SubscriptionRepository::new(pool)
+            .set_status(account_id, SubscriptionStatus::PastDue)
+            .await?;
+    }
+
+    let issued = open
+        .iter()
+        .any(|invoice| invoice.account_id == *account_id && invoice.period_start == period_end);
+    if issued {
+        return Ok(());
+    }
+    let Some(amount_sat) = lightning_price_sat(config, subscription.plan) else {
+        tracing::warn!(
+            "The {} plan has no Lightning price, not renewing account {}",
+            subscription.plan,
+            account_id
+        );
+        return Ok(());
+    };
+
+    let invoice = issue_invoice(
+        pool,
+        config,
+        account_id,
+        subscription.plan,
+        amount_sat,
+        period_end,
+        period_end + Duration::days(config.billing_grace_days),
+    )
+    .await?;
+    let Some(invoice) = invoice else {
+        return Ok(());
+    };
+
+    let deadline = invoice.expires_at.format("%Y-%m-%d");
+    email_admin(
+        pool,
+        config,
+        account_id,
+        "Your NodeGaze renewal invoice",
+        &format!(
+            "Your {} plan renews on {}. Pay {} sats by {} to keep your account active:\n\n{}",
+            invoice.plan,
+            period_end.format("%Y-%m-%d"),
+            invoice.amount_sat,
+            deadline,
+            invoice.payment_request
+        ),
+    )
+    .await;
+    Ok(())
+}
+
+/// Deactivates an account whose renewal went unpaid through the grace
+/// period and sends its admin an invoice that reinstates it.
+async fn suspend(
+    pool: &SqlitePool,
+    config: &Config,
+    subscription: &AccountSubscription,
+    now: DateTime<Utc>,
+) -> ServiceResult<()> {
+    let account_id = &subscription.account_id;
+    let subscription_repo = SubscriptionRepository::new(pool);
+    AccountRepository::new(pool)
+        .set_active(account_id, false)
+        .await?;
+    subscription_repo
+        .set_status(account_id, SubscriptionStatus::Canceled)
+        .await?;
+    subscription_repo
+        .set_suspended_at(account_id, Some(now))
+        .await?;
+
+    AuditLogRepository::new(pool)
+        .create_log(
+            account_id,
+            None,
+            "account_suspended",
+            "account",
+            Some(account_id),
+            &json!({ "reason": "unpaid_invoice", "plan": subscription.plan }),
+        )
+        .await?;
+
+    let Some(amount_sat) = lightning_price_sat(config, subscription.plan) else {
+        return Ok(());
+    };
+    let invoice = issue_invoice(
+        pool,
+        config,
+        account_id,
+        subscription.plan,
+        amount_sat,
+        now,
+        now + Duration::days(REINSTATEMENT_INVOICE_EXPIRY_DAYS),
+    )
+    .await?;
+    let Some(invoice) = invoice else {
+        return Ok(());
+    };
+
+    email_admin(
+        pool,
+        config,
+        account_id,
+        "Your NodeGaze account is suspended",
+        &format!(
+            "Your {} plan renewal was not paid, so your account has been suspended. \
+             Pay {} sats to reinstate it for another month:\n\n{}",
+            invoice.plan, invoice.amount_sat, invoice.payment_request
+        ),
+    )
+    .await;
+    Ok(())
+}
+
+/// Marks an invoice paid and extends the subscription it pays for,
+/// reactivating the account if billing had suspended it.
+async fn settle(
+    pool: &SqlitePool,
+    invoice: &BillingInvoice,
+    paid_at: DateTime<Utc>,
+) -> ServiceResult<()> {
+    if !BillingInvoiceRepository::new(pool)
+        .mark_paid(&invoice.id, paid_at)
+        .await?
+    {
+        return Ok(());
+    }
+
+    let account_id = &invoice.account_id;
+    let subscription_repo = SubscriptionRepository::new(pool);
+    let existing = subscription_repo.get_subscription(account_id).await?;
+    let period_end = paid_through(invoice, existing.as_ref(), paid_at);
+    let subscription = subscription_repo
+        .upsert_subscription(
+            account_id,
+            invoice.plan,
+            SubscriptionStatus::Active,
+            BillingProvider::Lightning,
+            None,
+            None,
+            Some(period_end),
+        )
+        .await?;
+
+    let audit = AuditLogRepository::new(pool);
+    if subscription.suspended_at.is_some() {
+        AccountRepository::new(pool)
+            .set_active(account_id, true)
+            .await?;
+        subscription_repo.set_suspended_at(account_id, None).await?;
+        audit
+            .create_log(
+                account_id,
+                None,
+                "account_reactivated",
+                "account",
+                Some(account_id),
+                &json!({ "invoice_id": invoice.id }),
+            )
+            .await?;
+    }
+    audit
+        .create_log(
+            account_id,
+            None,
+            "subscription_updated",
+            "account",
+            Some(account_id),
+            &json!({
+                "plan": subscription.plan,
+                "status": subscription.status,
+                "provider": subscription.provider,
+                "invoice_id": invoice.id,
+            }),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Creates an invoice on the treasury node and records it. Returns `None`
+/// when the account already has an invoice for the period, which happens
+/// when two billing runs renew the same subscription at once.
+async fn issue_invoice(
+    pool: &SqlitePool,
+    config: &Config,
+    account_id: &str,
+    plan: Plan,
+    amount_sat: i64,
+    period_start: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> ServiceResult<Option<BillingInvoice>> {
+    let period_end = add_month(period_start);
+    let memo = format!(
+        "NodeGaze {plan} plan, {} to {}",
+        period_start.format("%Y-%m-%d"),
+        period_end.format("%Y-%m-%d")
+    );
+    let expiry_secs = (expires_at - Utc::now()).num_seconds().max(60) as u64;
+
+    let client = treasury_client(pool, config).await?;
+    let payment_request = client
+        .create_invoice(amount_sat as u64 * 1000, &memo, expiry_secs)
+        .await
+        .map_err(|e| ServiceError::external_service(e.to_string()))?;
+    let payment_hash = Bolt11Invoice::from_str(&payment_request)
+        .map(|invoice| invoice.payment_hash().to_string())
+        .map_err(|e| {
+            ServiceError::external_service(format!("Treasury node returned a bad invoice: {e}"))
+        })?;
+
+    let invoice = BillingInvoiceRepository::new(pool)
+        .create_invoice(&BillingInvoice {
+            id: Uuid::now_v7().to_string(),
+            account_id: account_id.to_string(),
+            plan,
+            amount_sat,
+            payment_request,
+            payment_hash,
+            period_start,
+            period_end,
+            status: BillingInvoiceStatus::Open,
+            expires_at,
+            paid_at: None,
+            created_at: Utc::now(),
+        })
+        .await?;
+    Ok(invoice)
+}
+
+async fn treasury_client(pool: &SqlitePool, config: &Config) -> ServiceResult<NodeClientHandle> {
+    let (Some(account_id), Some(node_id)) = (
+        config.treasury_account_id.as_deref(),
+        config.treasury_node_id.as_deref(),
+    ) else {
+        return Err(ServiceError::internal_error(
+            "TREASURY_ACCOUNT_ID and TREASURY_NODE_ID must be set",
+        ));
+    };
+    let credential = CredentialRepository::new(pool)
+        .get_credential_by_account_and_node_id(account_id, node_id)
+        .await?
+        .ok_or_else(|| ServiceError::internal_error("The treasury node is not connected"))?;
+
+    let public_key = PublicKey::from_str(node_id)
+        .map_err(|e| ServiceError::internal_error(format!("Invalid treasury node ID: {e}")))?;
+    create_node_client(&NodeCredentials::from(credential), public_key)
+        .await
+        .map_err(|(_, body)| ServiceError::external_service(body))
+}
+
+fn payment_hash(hex_hash: &str) -> Option<PaymentHash> {
+    let bytes: [u8; 32] = hex::decode(hex_hash).ok()?.try_into().ok()?;
+    Some(PaymentHash(bytes))
+}
+
+/// Emails the account's admin, logging rather than failing when it can't.
+async fn email_admin(
+    pool: &SqlitePool,
+    config: &Config,
+    account_id: &str,
+    subject: &str,
+    text: &str,
+) {
+    let Some(email_config) = config.email_config() else {
+        tracing::warn!(
+            "Email is not configured, not sending \"{}\" to account {}",
+            subject,
+            account_id
+        );
+        return;
+    };
+    let admin = match UserRepository::new(pool)
+        .get_admin_user_by_account_id(account_id)
+        .await
+    {
+        Ok(Some(admin)) => admin,
+        Ok(None) => {
+            tracing::warn!("Account {} has no admin to bill", account_id);
+            return;
+        }
+        Err(e) => {
+            tracing::error!("Failed to load admin of account {}: {}", account_id, e);
+            return;
+        }
+    };
+
+    let html = format!(
+        "<p>{}</p>",
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace("\n\n", "</p><p>")
+    );
+    let sent = match EmailService::new(email_config) {
+        Ok(service) => service.send_email(&admin.email, subject, &html, text).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = sent {
+        tracing::error!("Failed to email account {}: {}", account_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice(period_start: DateTime<Utc>) -> BillingInvoice {
+        BillingInvoice {
+            id: "invoice-1".to_string(),
+            account_id: "account-1".to_string(),
+            plan: Plan::Pro,
+            amount_sat: 21_000,
+            payment_request: "lnbc1".to_string(),
+            payment_hash: "00".repeat(32),
+            period_start,
+            period_end: add_month(period_start),
+            status: BillingInvoiceStatus::Open,
+            expires_at: period_start,
+            paid_at: None,
+            created_at: period_start,
+        }
+    }
+
+    fn subscription(
+        period_end: DateTime<Utc>,
+        suspended_at: Option<DateTime<Utc>>,
+    ) -> AccountSubscription {
+        AccountSubscription {
+            account_id: "account-1".to_string(),
+            plan: Plan::Pro,
+            status: SubscriptionStatus::PastDue,
+            provider: BillingProvider::Lightning,
+            stripe_customer_id: None,
+            stripe_subscription_id: None,
+            current_period_end: Some(period_end),
+            suspended_at,
+            created_at: period_end,
+            updated_at: period_end,
+        }
+    }
+
+    #[test]
+    fn test_standing() {
+        let end = DateTime::parse_from_rfc3339("2025-10-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            standing(end, end - Duration::days(10), 7),
+            Standing::Current
+        );
+        assert_eq!(
+            standing(end, end - Duration::days(2), 7),
+            Standing::RenewalDue
+        );
+        assert_eq!(standing(end, end, 7), Standing::PastDue);
+        assert_eq!(standing(end, end + Duration::days(6), 7), Standing::PastDue);
+        assert_eq!(standing(end, end + Duration::days(7), 7), Standing::Suspend);
+        assert_eq!(standing(end, end, 0), Standing::Suspend);
+    }
+
+    #[test]
+    fn test_paid_through() {
+        let end = DateTime::parse_from_rfc3339("2025-10-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let paid_at = end + Duration::days(2);
+        let renewal = invoice(end);
+
+        // A renewal paid during the grace period extends the old period.
+        assert_eq!(
+            paid_through(&renewal, Some(&subscription(end, None)), paid_at),
+            renewal.period_end
+        );
+        // Paying after suspension, or a first invoice, starts from payment.
+        assert_eq!(
+            paid_through(&renewal, Some(&subscription(end, Some(end))), paid_at),
+            add_month(paid_at)
+        );
+        assert_eq!(paid_through(&renewal, None, paid_at), add_month(paid_at));
+    }
+}
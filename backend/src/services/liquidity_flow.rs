@@ -0,0 +1,185 @@
+//! Where liquidity flows through a node.
+//!
+//! Settled forwards from the forwards table are summed per pair of incoming
+//! and outgoing channels and laid out for a Sankey diagram: each channel is
+//! a node on the incoming side, the outgoing side or both, and each pair of
+//! channels a link weighted by the amount forwarded.
+
+use crate::database::models::{
+    ChannelPairFlow, FlowSide, LiquidityFlow, LiquidityFlowLink, LiquidityFlowNode,
+    LiquidityFlowQuery,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::forward_repository::ForwardRepository;
+use crate::repositories::graph_repository::GraphRepository;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+const DEFAULT_FLOW_DAYS: i64 = 30;
+
+/// Lays channel pair sums out as Sankey nodes and links. Incoming channels
+/// come first, then outgoing ones, each by amount, largest first; links are
+/// ordered the same way.
+pub fn build_flow(
+    pairs: Vec<ChannelPairFlow>,
+    channel_peers: &HashMap<String, String>,
+    aliases: &HashMap<String, String>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> LiquidityFlow {
+    let mut totals: HashMap<(FlowSide, &str), u64> = HashMap::new();
+    for pair in &pairs {
+        let amount = pair.amount_msat.max(0) as u64;
+        *totals
+            .entry((FlowSide::Incoming, &pair.chan_id_in))
+            .or_default() += amount;
+        *totals
+            .entry((FlowSide::Outgoing, &pair.chan_id_out))
+            .or_default() += amount;
+    }
+
+    let mut sides: Vec<((FlowSide, &str), u64)> = totals.into_iter().collect();
+    sides.sort_by(
+        |((side_a, chan_a), amount_a), ((side_b, chan_b), amount_b)| {
+            side_a
+                .cmp(side_b)
+                .then(amount_b.cmp(amount_a))
+                .then(chan_a.cmp(chan_b))
+        },
+    );
+    let index: HashMap<(FlowSide, &str), usize> = sides
+        .iter()
+        .enumerate()
+        .map(|(i, (key, _))| (*key, i))
+        .collect();
+    let nodes = sides
+        .iter()
+        .map(|((side, chan_id), amount)| {
+            let peer_pubkey = channel_peers.get(*chan_id).cloned();
+            LiquidityFlowNode {
+                id: match side {
+                    FlowSide::Incoming => format!("in:{chan_id}"),
+                    FlowSide::Outgoing => format!("out:{chan_id}"),
+                },
+                channel_id: chan_id.to_string(),
+                side: *side,
+                peer_alias: peer_pubkey
+                    .as_ref()
+                    .and_then(|pubkey| aliases.get(pubkey))
+                    .cloned(),
+                peer_pubkey,
+                amount_msat: *amount,
+            }
+        })
+        .collect();
+
+    let mut links: Vec<LiquidityFlowLink> = pairs
+        .iter()
+        .map(|pair| LiquidityFlowLink {
+            source: index[&(FlowSide::Incoming, pair.chan_id_in.as_str())],
+            target: index[&(FlowSide::Outgoing, pair.chan_id_out.as_str())],
+            forwards: pair.forwards.max(0) as u64,
+            amount_msat: pair.amount_msat.max(0) as u64,
+            fee_msat: pair.fee_msat.max(0) as u64,
+        })
+        .collect();
+    links.sort_by(|a, b| {
+        b.amount_msat
+            .cmp(&a.amount_msat)
+            .then(a.source.cmp(&b.source))
+            .then(a.target.cmp(&b.target))
+    });
+
+    LiquidityFlow {
+        from,
+        to,
+        total_forwards: links.iter().map(|link| link.forwards).sum(),
+        total_amount_msat: links.iter().map(|link| link.amount_msat).sum(),
+        total_fee_msat: links.iter().map(|link| link.fee_msat).sum(),
+        nodes,
+        links,
+    }
+}
+
+/// Service layer for liquidity flow analytics.
+pub struct LiquidityFlowService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> LiquidityFlowService<'a> {
+    /// Creates a new LiquidityFlowService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Builds the liquidity flow of a node over a date range.
+    pub async fn get_flow(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: LiquidityFlowQuery,
+    ) -> ServiceResult<LiquidityFlow> {
+        let to = query.to.unwrap_or_else(Utc::now);
+        let from = query
+            .from
+            .unwrap_or_else(|| to - Duration::days(DEFAULT_FLOW_DAYS));
+        if from > to {
+            return Err(ServiceError::validation("'from' must not be after 'to'"));
+        }
+
+        let pairs = ForwardRepository::new(self.pool)
+            .get_channel_pair_flows(account_id, node_id, from, to)
+            .await?;
+        let graph_repo = GraphRepository::new(self.pool);
+        let channel_peers = graph_repo.get_channel_peers(node_id, node_id).await?;
+        let aliases: HashMap<String, String> = graph_repo
+            .get_nodes(node_id)
+            .await?
+            .into_iter()
+            .map(|node| (node.pubkey, node.alias))
+            .collect();
+
+        Ok(build_flow(pairs, &channel_peers, &aliases, from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(chan_id_in: &str, chan_id_out: &str, amount_msat: i64) -> ChannelPairFlow {
+        ChannelPairFlow {
+            chan_id_in: chan_id_in.to_string(),
+            chan_id_out: chan_id_out.to_string(),
+            forwards: 2,
+            amount_msat,
+            fee_msat: amount_msat / 1000,
+        }
+    }
+
+    #[test]
+    fn test_build_flow() {
+        let channel_peers = HashMap::from([("1".to_string(), "peer_a".to_string())]);
+        let aliases = HashMap::from([("peer_a".to_string(), "alice".to_string())]);
+        let now = Utc::now();
+        let pairs = vec![
+            pair("1", "2", 5_000_000),
+            pair("2", "1", 1_000_000),
+            pair("1", "3", 2_000_000),
+        ];
+
+        let flow = build_flow(pairs, &channel_peers, &aliases, now, now);
+
+        let ids: Vec<&str> = flow.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(ids, vec!["in:1", "in:2", "out:2", "out:3", "out:1"]);
+        assert_eq!(flow.nodes[0].amount_msat, 7_000_000);
+        assert_eq!(flow.nodes[0].peer_alias.as_deref(), Some("alice"));
+        assert_eq!(flow.nodes[4].peer_pubkey.as_deref(), Some("peer_a"));
+        assert_eq!((flow.links[0].source, flow.links[0].target), (0, 2));
+        assert_eq!((flow.links[2].source, flow.links[2].target), (1, 4));
+        assert_eq!(flow.total_forwards, 6);
+        assert_eq!(flow.total_amount_msat, 8_000_000);
+        assert_eq!(flow.total_fee_msat, 8_000);
+    }
+}
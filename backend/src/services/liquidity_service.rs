@@ -0,0 +1,186 @@
+//! Inbound liquidity purchases from LSPS1 providers.
+//!
+//! Placing an order returns the LSP's invoice for the operator to pay. Orders
+//! are refreshed from the LSP when read until they complete or fail; a
+//! completed order records the funding outpoint so the channel can be shown
+//! with what it cost.
+
+use crate::database::models::{CreateLiquidityOrderRequest, LiquidityOrder, LiquidityOrderState};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::liquidity_order_repository::LiquidityOrderRepository;
+use crate::services::lsps1::{Lsps1Client, Lsps1CreateOrder, Lsps1Order};
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Maps the LSP's order state onto ours.
+fn order_state(lsp_state: &str) -> LiquidityOrderState {
+    match lsp_state {
+        "COMPLETED" => LiquidityOrderState::Completed,
+        "FAILED" => LiquidityOrderState::Failed,
+        _ => LiquidityOrderState::Created,
+    }
+}
+
+/// Copies the LSP's view of an order onto the stored one.
+fn apply_lsp_order(order: &mut LiquidityOrder, lsp_order: &Lsps1Order) {
+    order.order_state = order_state(&lsp_order.order_state);
+    order.payment_state = Some(lsp_order.payment.bolt11.state.clone());
+    if let Some(channel) = &lsp_order.channel {
+        order.funding_outpoint = Some(channel.funding_outpoint.clone());
+        order.channel_funded_at = Some(channel.funded_at);
+        order.channel_expires_at = Some(channel.expires_at);
+    }
+}
+
+/// Service layer for LSPS1 liquidity orders.
+pub struct LiquidityService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> LiquidityService<'a> {
+    /// Creates a new LiquidityService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Orders an inbound channel to the node, checking the LSP's limits first.
+    pub async fn create_order(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: CreateLiquidityOrderRequest,
+    ) -> ServiceResult<LiquidityOrder> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let client = Lsps1Client::new(&request.lsp_url)?;
+        let options = client.get_info().await?;
+        if request.lsp_balance_sat < options.min_initial_lsp_balance_sat
+            || request.lsp_balance_sat > options.max_initial_lsp_balance_sat
+        {
+            return Err(ServiceError::validation(format!(
+                "The LSP sells between {} and {} sats of inbound liquidity",
+                options.min_initial_lsp_balance_sat, options.max_initial_lsp_balance_sat
+            )));
+        }
+        if request.channel_expiry_blocks > options.max_channel_expiry_blocks {
+            return Err(ServiceError::validation(format!(
+                "The LSP keeps channels open for at most {} blocks",
+                options.max_channel_expiry_blocks
+            )));
+        }
+
+        let lsp_order = client
+            .create_order(&Lsps1CreateOrder::inbound(
+                node_id.to_string(),
+                request.lsp_balance_sat,
+                request.channel_expiry_blocks,
+                request.announce_channel,
+                request.token,
+            ))
+            .await?;
+
+        let bolt11 = &lsp_order.payment.bolt11;
+        let mut order = LiquidityOrder {
+            id: Uuid::now_v7().to_string(),
+            account_id: account_id.to_string(),
+            node_id: node_id.to_string(),
+            lsp_url: request.lsp_url,
+            lsp_order_id: lsp_order.order_id.clone(),
+            order_state: LiquidityOrderState::Created,
+            payment_state: None,
+            lsp_balance_sat: lsp_order.lsp_balance_sat as i64,
+            client_balance_sat: lsp_order.client_balance_sat as i64,
+            channel_expiry_blocks: lsp_order.channel_expiry_blocks as i64,
+            announce_channel: lsp_order.announce_channel,
+            fee_total_sat: bolt11.fee_total_sat as i64,
+            order_total_sat: bolt11.order_total_sat as i64,
+            bolt11_invoice: bolt11.invoice.clone(),
+            payment_expires_at: Some(bolt11.expires_at),
+            funding_outpoint: None,
+            channel_funded_at: None,
+            channel_expires_at: None,
+            created_by: user_id.to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        };
+        apply_lsp_order(&mut order, &lsp_order);
+
+        let order = LiquidityOrderRepository::new(self.pool)
+            .create_order(&order)
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "liquidity_order_created",
+                "node",
+                Some(node_id),
+                &json!({
+                    "order_id": order.id,
+                    "lsp_url": order.lsp_url,
+                    "lsp_order_id": order.lsp_order_id,
+                    "lsp_balance_sat": order.lsp_balance_sat,
+                    "fee_total_sat": order.fee_total_sat,
+                }),
+            )
+            .await?;
+
+        Ok(order)
+    }
+
+    /// Lists the node's orders as last seen, newest first.
+    pub async fn list_orders(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<Vec<LiquidityOrder>> {
+        Ok(LiquidityOrderRepository::new(self.pool)
+            .get_orders(account_id, node_id)
+            .await?)
+    }
+
+    /// Returns an order, first refreshing it from the LSP if it is still open.
+    pub async fn get_order(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        id: &str,
+    ) -> ServiceResult<LiquidityOrder> {
+        let repo = LiquidityOrderRepository::new(self.pool);
+        let mut order = repo
+            .get_order(account_id, node_id, id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Liquidity order", id))?;
+
+        if order.order_state != LiquidityOrderState::Created {
+            return Ok(order);
+        }
+
+        let lsp_order = Lsps1Client::new(&order.lsp_url)?
+            .get_order(&order.lsp_order_id)
+            .await?;
+        apply_lsp_order(&mut order, &lsp_order);
+
+        Ok(repo.update_status(&order).await?)
+    }
+
+    /// What was paid for the channel funded at `funding_outpoint`, if bought here.
+    pub async fn get_channel_cost(
+        &self,
+        node_id: &str,
+        funding_outpoint: &str,
+    ) -> ServiceResult<Option<u64>> {
+        let cost = LiquidityOrderRepository::new(self.pool)
+            .get_cost_by_funding_outpoint(node_id, funding_outpoint)
+            .await?;
+        Ok(cost.map(|sat| sat as u64))
+    }
+}
@@ -0,0 +1,243 @@
+//! Client for the HTTP binding of LSPS1 (channel requests from an LSP).
+//!
+//! An order asks the LSP to open a channel to our node with `lsp_balance_sat`
+//! of inbound liquidity on its side. The LSP answers with an invoice covering
+//! its fee; after it is paid the order moves to `COMPLETED` and carries the
+//! funding outpoint of the new channel. Amounts are sent as strings, as the
+//! spec requires.
+
+use crate::errors::{ServiceError, ServiceResult};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::Duration;
+
+/// Blocks within which we ask the LSP to get the funding transaction confirmed
+const FUNDING_CONFIRMS_WITHIN_BLOCKS: u32 = 6;
+
+/// Channel limits the LSP advertises.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lsps1Options {
+    #[serde(deserialize_with = "sat_from_str")]
+    pub min_initial_lsp_balance_sat: u64,
+    #[serde(deserialize_with = "sat_from_str")]
+    pub max_initial_lsp_balance_sat: u64,
+    pub max_channel_expiry_blocks: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Lsps1GetInfo {
+    options: Lsps1Options,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Lsps1CreateOrder {
+    /// Our node, so the LSP knows where to open the channel
+    pub public_key: String,
+    #[serde(serialize_with = "sat_to_str")]
+    pub lsp_balance_sat: u64,
+    #[serde(serialize_with = "sat_to_str")]
+    pub client_balance_sat: u64,
+    pub required_channel_confirmations: u32,
+    pub funding_confirms_within_blocks: u32,
+    pub channel_expiry_blocks: u32,
+    pub token: Option<String>,
+    pub announce_channel: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lsps1Order {
+    pub order_id: String,
+    #[serde(deserialize_with = "sat_from_str")]
+    pub lsp_balance_sat: u64,
+    #[serde(deserialize_with = "sat_from_str")]
+    pub client_balance_sat: u64,
+    pub channel_expiry_blocks: u32,
+    pub announce_channel: bool,
+    /// `CREATED`, `COMPLETED` or `FAILED`
+    pub order_state: String,
+    pub payment: Lsps1Payment,
+    pub channel: Option<Lsps1Channel>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lsps1Payment {
+    pub bolt11: Lsps1Bolt11Payment,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lsps1Bolt11Payment {
+    /// `EXPECT_PAYMENT`, `HOLD`, `PAID` or `REFUNDED`
+    pub state: String,
+    pub expires_at: DateTime<Utc>,
+    #[serde(deserialize_with = "sat_from_str")]
+    pub fee_total_sat: u64,
+    #[serde(deserialize_with = "sat_from_str")]
+    pub order_total_sat: u64,
+    pub invoice: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lsps1Channel {
+    pub funded_at: DateTime<Utc>,
+    /// `txid:vout`
+    pub funding_outpoint: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+fn sat_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+fn sat_to_str<S: Serializer>(sat: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&sat.to_string())
+}
+
+/// Talks to one LSP's LSPS1 endpoints.
+pub struct Lsps1Client {
+    http_client: Client,
+    base_url: String,
+}
+
+impl Lsps1Client {
+    /// Creates a client for the LSP at `base_url`.
+    pub fn new(base_url: &str) -> ServiceResult<Self> {
+        let http_client = Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Fetches the channel limits the LSP accepts orders within.
+    pub async fn get_info(&self) -> ServiceResult<Lsps1Options> {
+        let request = self.http_client.get(format!("{}/get_info", self.base_url));
+        let info: Lsps1GetInfo = self.send(request).await?;
+        Ok(info.options)
+    }
+
+    /// Places an order for a channel to `request.public_key`.
+    pub async fn create_order(&self, request: &Lsps1CreateOrder) -> ServiceResult<Lsps1Order> {
+        let request = self
+            .http_client
+            .post(format!("{}/create_order", self.base_url))
+            .json(request);
+        self.send(request).await
+    }
+
+    /// Fetches the current state of an order.
+    pub async fn get_order(&self, order_id: &str) -> ServiceResult<Lsps1Order> {
+        let request = self
+            .http_client
+            .get(format!("{}/get_order", self.base_url))
+            .query(&[("order_id", order_id)]);
+        self.send(request).await
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> ServiceResult<T> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ServiceError::external_service(format!("LSP unreachable: {e}")))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            // LSPs return the JSON-RPC error object as the body.
+            let body = response.text().await.unwrap_or_default();
+            return Err(ServiceError::external_service(format!(
+                "LSP rejected the request ({status}): {body}"
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ServiceError::external_service(format!("Invalid LSP response: {e}")))
+    }
+}
+
+impl Lsps1CreateOrder {
+    /// Builds an order for inbound liquidity only, with no balance pushed to us.
+    pub fn inbound(
+        public_key: String,
+        lsp_balance_sat: u64,
+        channel_expiry_blocks: u32,
+        announce_channel: bool,
+        token: Option<String>,
+    ) -> Self {
+        Self {
+            public_key,
+            lsp_balance_sat,
+            client_balance_sat: 0,
+            required_channel_confirmations: 0,
+            funding_confirms_within_blocks: FUNDING_CONFIRMS_WITHIN_BLOCKS,
+            channel_expiry_blocks,
+            token,
+            announce_channel,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_order() {
+        let order: Lsps1Order = serde_json::from_str(
+            r#"{
+                "order_id": "bb4b5d0a-8334-49d8-9463-90a6d413af7c",
+                "lsp_balance_sat": "5000000",
+                "client_balance_sat": "0",
+                "required_channel_confirmations": 0,
+                "funding_confirms_within_blocks": 6,
+                "channel_expiry_blocks": 144,
+                "token": "",
+                "created_at": "2025-01-01T00:00:00.000Z",
+                "announce_channel": true,
+                "order_state": "COMPLETED",
+                "payment": {
+                    "bolt11": {
+                        "state": "PAID",
+                        "expires_at": "2025-01-01T00:10:00.000Z",
+                        "fee_total_sat": "8888",
+                        "order_total_sat": "8888",
+                        "invoice": "lnbc888880n1pj..."
+                    },
+                    "onchain": null
+                },
+                "channel": {
+                    "funded_at": "2025-01-01T00:20:00.000Z",
+                    "funding_outpoint": "0301e0480b374b32851a9462db29dc19fe830a7f7d7a88b81612b9d42099c0ae:0",
+                    "expires_at": "2025-01-02T00:20:00.000Z"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(order.lsp_balance_sat, 5_000_000);
+        assert_eq!(order.payment.bolt11.fee_total_sat, 8888);
+        assert_eq!(order.order_state, "COMPLETED");
+        assert!(order.channel.unwrap().funding_outpoint.ends_with(":0"));
+
+        let request = serde_json::to_value(Lsps1CreateOrder::inbound(
+            "02abc".to_string(),
+            1_000_000,
+            144,
+            false,
+            None,
+        ))
+        .unwrap();
+        assert_eq!(request["lsp_balance_sat"], "1000000");
+        assert_eq!(request["client_balance_sat"], "0");
+    }
+}
@@ -0,0 +1,104 @@
+//! Client for the mempool.space REST API.
+//!
+//! Used where the node itself can't answer: where a transaction confirmed,
+//! what fee a transaction the node's wallet didn't fund paid, and what fee
+//! rates currently get a transaction confirmed.
+
+use crate::config::Config;
+use crate::errors::{ServiceError, ServiceResult};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Deserialize)]
+struct TxStatus {
+    confirmed: bool,
+    block_height: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct Tx {
+    fee: u64,
+}
+
+/// Fee rates, in sat/vB, mempool.space recommends for each confirmation target.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecommendedFees {
+    /// Next block
+    pub fastest_fee: f64,
+    /// Within about three blocks
+    pub half_hour_fee: f64,
+    /// Within about six blocks
+    pub hour_fee: f64,
+    /// Without priority, eventually
+    pub economy_fee: f64,
+}
+
+/// Talks to the mempool.space API at `MEMPOOL_API_URL`.
+pub struct MempoolClient {
+    http_client: Client,
+    base_url: String,
+}
+
+impl MempoolClient {
+    pub fn new() -> ServiceResult<Self> {
+        let config = Config::from_env().map_err(|e| ServiceError::internal_error(e.to_string()))?;
+        let http_client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        Ok(Self {
+            http_client,
+            base_url: config.mempool_api_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Height of the block that confirmed `txid`, or None while unconfirmed.
+    pub async fn confirmation_height(&self, txid: &str) -> ServiceResult<Option<u32>> {
+        let status: Option<TxStatus> = self.get(&format!("tx/{txid}/status")).await?;
+        Ok(status
+            .filter(|status| status.confirmed)
+            .and_then(|status| status.block_height))
+    }
+
+    /// Fee paid by `txid`, or None if mempool.space doesn't know it.
+    pub async fn transaction_fee(&self, txid: &str) -> ServiceResult<Option<u64>> {
+        let tx: Option<Tx> = self.get(&format!("tx/{txid}")).await?;
+        Ok(tx.map(|tx| tx.fee))
+    }
+
+    /// Fee rates currently recommended per confirmation target.
+    pub async fn recommended_fees(&self) -> ServiceResult<RecommendedFees> {
+        self.get("v1/fees/recommended").await?.ok_or_else(|| {
+            ServiceError::external_service("mempool.space has no fee recommendations")
+        })
+    }
+
+    /// GETs a resource; None when it doesn't exist (not broadcast yet, or
+    /// dropped from the mempool).
+    async fn get<T: for<'de> Deserialize<'de>>(&self, path: &str) -> ServiceResult<Option<T>> {
+        let response = self
+            .http_client
+            .get(format!("{}/{}", self.base_url, path))
+            .send()
+            .await
+            .map_err(|e| {
+                ServiceError::external_service(format!("mempool.space unreachable: {e}"))
+            })?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(ServiceError::external_service(format!(
+                "mempool.space returned {}",
+                response.status()
+            )));
+        }
+
+        response.json().await.map(Some).map_err(|e| {
+            ServiceError::external_service(format!("Invalid mempool.space response: {e}"))
+        })
+    }
+}
@@ -0,0 +1,507 @@
+//! In-memory Lightning node for development and tests.
+//!
+//! Built only with the `mock-node` feature. Authenticating an LND connection
+//! whose address starts with `mock://` yields a `MockNode` instead of dialing
+//! a real node, so the frontend and integration tests can run without one.
+//! Everything it serves is derived from its alias and fixed constants, so the
+//! same alias always sees the same channels, payments and invoices. Its event
+//! stream loops over a short script of channel, invoice and forward events,
+//! spaced by `?interval=<secs>` on the address (10s by default, 0 for none).
+
+use crate::{
+    errors::LightningError,
+    services::{
+        event_manager::{InvoiceEvent, LightningEvent},
+        node_manager::LightningClient,
+    },
+    utils::{
+        ChannelDetails, ChannelState, ChannelSummary, CustomInvoice, Forward, InvoiceStatus,
+        MessageVerification, NodeId, NodeInfo, PaymentDetails, PaymentState, PaymentSummary,
+        PaymentType, ShortChannelID,
+    },
+};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use bitcoin::Network;
+use bitcoin::hashes::{Hash, sha256};
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use lightning::ln::{PaymentHash, features::NodeFeatures};
+use std::{pin::Pin, time::Duration};
+use tokio_stream::Stream;
+
+/// Address scheme that selects the mock node
+const MOCK_SCHEME: &str = "mock://";
+
+/// Unix time every mock timestamp is offset from
+const MOCK_EPOCH: u64 = 1_750_000_000;
+
+/// Gap between the events of the default script
+const DEFAULT_EVENT_DELAY: Duration = Duration::from_secs(10);
+
+/// Whether an address points at the mock node.
+pub fn is_mock_address(address: &str) -> bool {
+    address.starts_with(MOCK_SCHEME)
+}
+
+/// Reads the gap between scripted events from the address. A gap of zero
+/// turns the script off.
+fn script_interval(address: &str) -> Duration {
+    address
+        .split_once('?')
+        .into_iter()
+        .flat_map(|(_, query)| query.split('&'))
+        .find_map(|param| param.strip_prefix("interval="))
+        .and_then(|secs| secs.parse().ok())
+        .map_or(DEFAULT_EVENT_DELAY, Duration::from_secs)
+}
+
+/// An event the mock node emits `delay` after the previous one.
+#[derive(Debug, Clone)]
+struct ScriptedEvent {
+    delay: Duration,
+    event: LightningEvent,
+}
+
+struct MockChannel {
+    chan_id: u64,
+    peer: PublicKey,
+    peer_alias: String,
+    capacity: u64,
+    local_balance: u64,
+    private: bool,
+    state: ChannelState,
+}
+
+pub struct MockNode {
+    info: NodeInfo,
+    channels: Vec<MockChannel>,
+    script: Vec<ScriptedEvent>,
+}
+
+/// Derives a stable key pair from a seed string.
+fn mock_key(seed: &str) -> PublicKey {
+    let digest = sha256::Hash::hash(seed.as_bytes());
+    let secret_key =
+        SecretKey::from_slice(digest.as_byte_array()).expect("a sha256 digest is a valid key");
+    PublicKey::from_secret_key(&Secp256k1::new(), &secret_key)
+}
+
+fn mock_hash(seed: &str) -> String {
+    sha256::Hash::hash(seed.as_bytes()).to_string()
+}
+
+/// Short channel id of output `vout` of the first transaction in `block`.
+fn scid(block: u64, vout: u64) -> u64 {
+    (block << 40) | (1 << 16) | vout
+}
+
+impl MockNode {
+    /// Creates a mock node for a connection id and `mock://` address. A
+    /// public key is used as is; an alias seeds the node's key.
+    pub fn new(id: NodeId, address: &str) -> Self {
+        let (pubkey, alias) = match id {
+            NodeId::PublicKey(pubkey) => {
+                let alias = format!("mock-{}", &pubkey.to_string()[..8]);
+                (pubkey, alias)
+            }
+            NodeId::Alias(alias) => (mock_key(&alias), alias),
+        };
+
+        let channels = [
+            (2_000_000, 1_200_000, false, ChannelState::Active),
+            (5_000_000, 500_000, false, ChannelState::Active),
+            (1_000_000, 900_000, true, ChannelState::Disabled),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (capacity, local_balance, private, state))| {
+            let peer_alias = format!("{alias}-peer-{i}");
+            MockChannel {
+                chan_id: scid(800_000 + i as u64 * 1_000, i as u64),
+                peer: mock_key(&peer_alias),
+                peer_alias,
+                capacity,
+                local_balance,
+                private,
+                state,
+            }
+        })
+        .collect();
+
+        let mut node = Self {
+            info: NodeInfo {
+                pubkey,
+                alias,
+                features: NodeFeatures::empty(),
+            },
+            channels,
+            script: Vec::new(),
+        };
+        let interval = script_interval(address);
+        if !interval.is_zero() {
+            node.script = node.default_script(interval);
+        }
+        node
+    }
+
+    fn default_script(&self, interval: Duration) -> Vec<ScriptedEvent> {
+        let channel = &self.channels[0];
+        let invoice = self.invoice(0, InvoiceStatus::Settled);
+        let events = vec![
+            LightningEvent::ChannelOpened {
+                active: true,
+                remote_pubkey: channel.peer.to_string(),
+                channel_point: format!("{}:0", mock_hash(&channel.peer_alias)),
+                chan_id: channel.chan_id,
+                capacity: channel.capacity as i64,
+                local_balance: channel.local_balance as i64,
+                remote_balance: (channel.capacity - channel.local_balance) as i64,
+                total_satoshis_sent: 0,
+                total_satoshis_received: 0,
+            },
+            LightningEvent::InvoiceCreated(InvoiceEvent {
+                preimage: Vec::new(),
+                hash: hex::decode(&invoice.payment_hash).unwrap_or_default(),
+                value_msat: invoice.value_msat as i64,
+                state: 0,
+                memo: invoice.memo.clone(),
+                creation_date: invoice.creation_date.unwrap_or_default(),
+                payment_request: invoice.payment_request.clone(),
+            }),
+            LightningEvent::InvoiceSettled(InvoiceEvent {
+                preimage: hex::decode(&invoice.payment_preimage).unwrap_or_default(),
+                hash: hex::decode(&invoice.payment_hash).unwrap_or_default(),
+                value_msat: invoice.value_msat as i64,
+                state: 1,
+                memo: invoice.memo,
+                creation_date: invoice.creation_date.unwrap_or_default(),
+                payment_request: invoice.payment_request,
+            }),
+            LightningEvent::ForwardFailed {
+                incoming_chan_id: self.channels[1].chan_id,
+                outgoing_chan_id: channel.chan_id,
+                timestamp_ns: MOCK_EPOCH * 1_000_000_000,
+                outgoing_amt_msat: Some(250_000_000),
+                link_failure: true,
+                reason: "insufficient balance".to_string(),
+            },
+        ];
+
+        events
+            .into_iter()
+            .map(|event| ScriptedEvent {
+                delay: interval,
+                event,
+            })
+            .collect()
+    }
+
+    fn channel(&self, chan_id: u64) -> Option<&MockChannel> {
+        self.channels.iter().find(|c| c.chan_id == chan_id)
+    }
+
+    fn invoice(&self, index: u64, state: InvoiceStatus) -> CustomInvoice {
+        let preimage = mock_hash(&format!("{}-preimage-{index}", self.info.alias));
+        let created = MOCK_EPOCH + index * 3_600;
+        let value = 10_000 * (index + 1);
+        CustomInvoice {
+            memo: format!("Mock invoice {index}"),
+            payment_hash: mock_hash(&preimage),
+            payment_preimage: preimage,
+            value,
+            value_msat: value * 1_000,
+            creation_date: Some(created as i64),
+            settle_date: matches!(state, InvoiceStatus::Settled).then_some(created as i64 + 60),
+            payment_request: format!("lnbcrt{value}0n1mock{index}"),
+            expiry: Some(3_600),
+            amount_paid_msat: matches!(state, InvoiceStatus::Settled).then_some(value * 1_000),
+            state,
+            payment_mismatch: None,
+            is_keysend: Some(false),
+            keysend_message: None,
+            is_amp: Some(false),
+            payment_addr: None,
+            htlcs: None,
+            features: None,
+        }
+    }
+
+    fn payments(&self) -> Vec<PaymentSummary> {
+        [
+            PaymentState::Settled,
+            PaymentState::Settled,
+            PaymentState::Failed,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, state)| {
+            let i = i as u64;
+            let created = MOCK_EPOCH + i * 7_200;
+            PaymentSummary {
+                state,
+                payment_type: PaymentType::Outgoing,
+                amount_sat: 25_000 * (i + 1),
+                amount_usd: 0.0,
+                routing_fee: (state == PaymentState::Settled).then_some(3 + i),
+                creation_time: Some(created),
+                invoice: None,
+                payment_hash: mock_hash(&format!("{}-payment-{i}", self.info.alias)),
+                completed_at: Some(created + 2),
+                channel_id: Some(ShortChannelID(self.channels[i as usize].chan_id)),
+                keysend_message: None,
+            }
+        })
+        .collect()
+    }
+
+    fn signature(&self, message: &str) -> String {
+        mock_hash(&format!("{}:{message}", self.info.pubkey))
+    }
+}
+
+#[async_trait]
+impl LightningClient for MockNode {
+    fn get_info(&self) -> &NodeInfo {
+        &self.info
+    }
+
+    async fn get_network(&self) -> Result<Network, LightningError> {
+        Ok(Network::Regtest)
+    }
+
+    async fn get_node_info(&self, node_id: &PublicKey) -> Result<NodeInfo, LightningError> {
+        if *node_id == self.info.pubkey {
+            return Ok(self.info.clone());
+        }
+        let alias = self
+            .channels
+            .iter()
+            .find(|c| c.peer == *node_id)
+            .map(|c| c.peer_alias.clone())
+            .ok_or_else(|| LightningError::GetNodeInfoError("Node not found".to_string()))?;
+
+        Ok(NodeInfo {
+            pubkey: *node_id,
+            alias,
+            features: NodeFeatures::empty(),
+        })
+    }
+
+    async fn list_channels(&self) -> Result<Vec<ChannelSummary>, LightningError> {
+        Ok(self
+            .channels
+            .iter()
+            .map(|c| ChannelSummary {
+                chan_id: ShortChannelID(c.chan_id),
+                remote_pubkey: c.peer.to_string(),
+                alias: Some(c.peer_alias.clone()),
+                channel_state: c.state.clone(),
+                private: c.private,
+                remote_balance: c.capacity - c.local_balance,
+                local_balance: c.local_balance,
+                capacity: c.capacity,
+                last_update: Some(MOCK_EPOCH),
+                uptime: Some(86_400),
+                notes: Vec::new(),
+            })
+            .collect())
+    }
+
+    async fn get_channel_info(
+        &self,
+        channel_id: &ShortChannelID,
+    ) -> Result<ChannelDetails, LightningError> {
+        let c = self
+            .channel(channel_id.0)
+            .ok_or_else(|| LightningError::ChannelError("Channel not found".to_string()))?;
+
+        Ok(ChannelDetails {
+            channel_id: ShortChannelID(c.chan_id),
+            local_balance_sat: c.local_balance,
+            remote_balance_sat: c.capacity - c.local_balance,
+            capacity_sat: c.capacity,
+            active: Some(matches!(c.state, ChannelState::Active)),
+            private: c.private,
+            remote_pubkey: c.peer,
+            commit_fee_sat: Some(2_810),
+            local_chan_reserve_sat: Some(c.capacity / 100),
+            remote_chan_reserve_sat: Some(c.capacity / 100),
+            num_updates: Some(42),
+            total_satoshis_sent: Some(c.capacity / 10),
+            total_satoshis_received: Some(c.capacity / 20),
+            channel_age_blocks: Some(4_320),
+            opening_cost_sat: None,
+            funding_fee_sat: None,
+            closing_txid: None,
+            closing_fee_sat: None,
+            rebalance_cost_sat: None,
+            peer_metadata: None,
+            notes: Vec::new(),
+            initiator: Some(true),
+            txid: None,
+            vout: None,
+            node1_policy: None,
+            node2_policy: None,
+        })
+    }
+
+    async fn get_payment_details(
+        &self,
+        payment_hash: &PaymentHash,
+    ) -> Result<PaymentDetails, LightningError> {
+        let hash = hex::encode(payment_hash.0);
+        let payment = self
+            .payments()
+            .into_iter()
+            .find(|p| p.payment_hash == hash)
+            .ok_or_else(|| LightningError::PaymentError("Payment not found".to_string()))?;
+
+        Ok(PaymentDetails {
+            state: payment.state,
+            payment_type: payment.payment_type,
+            amount_sat: payment.amount_sat,
+            amount_usd: payment.amount_usd,
+            routing_fee: payment.routing_fee,
+            network: Some(Network::Regtest.to_string()),
+            description: None,
+            creation_time: payment.creation_time,
+            invoice: payment.invoice,
+            payment_hash: payment.payment_hash,
+            destination_pubkey: None,
+            completed_at: payment.completed_at,
+            htlcs: Vec::new(),
+        })
+    }
+
+    async fn list_payments(&self) -> Result<Vec<PaymentSummary>, LightningError> {
+        Ok(self.payments())
+    }
+
+    async fn stream_events(
+        &mut self,
+    ) -> Result<Pin<Box<dyn Stream<Item = LightningEvent> + Send>>, LightningError> {
+        if self.script.is_empty() {
+            // Stay subscribed without emitting anything, like an idle node.
+            return Ok(Box::pin(tokio_stream::pending()));
+        }
+
+        let script = self.script.clone();
+        Ok(Box::pin(stream! {
+            loop {
+                for step in &script {
+                    tokio::time::sleep(step.delay).await;
+                    yield step.event.clone();
+                }
+            }
+        }))
+    }
+
+    async fn list_invoices(&self) -> Result<Vec<CustomInvoice>, LightningError> {
+        Ok(vec![
+            self.invoice(0, InvoiceStatus::Settled),
+            self.invoice(1, InvoiceStatus::Settled),
+            self.invoice(2, InvoiceStatus::Open),
+        ])
+    }
+
+    async fn get_invoice_details(
+        &self,
+        payment_hash: &PaymentHash,
+    ) -> Result<CustomInvoice, LightningError> {
+        let hash = hex::encode(payment_hash.0);
+        self.list_invoices()
+            .await?
+            .into_iter()
+            .find(|invoice| invoice.payment_hash == hash)
+            .ok_or_else(|| LightningError::InvoiceError("Invoice not found".to_string()))
+    }
+
+    async fn sign_message(&self, message: &str) -> Result<String, LightningError> {
+        Ok(self.signature(message))
+    }
+
+    async fn verify_message(
+        &self,
+        message: &str,
+        signature: &str,
+    ) -> Result<MessageVerification, LightningError> {
+        Ok(MessageVerification {
+            valid: signature == self.signature(message),
+            pubkey: self.info.pubkey.to_string(),
+        })
+    }
+
+    async fn list_forwards(&self, since: u64) -> Result<Vec<Forward>, LightningError> {
+        Ok((0..6u64)
+            .map(|i| Forward {
+                timestamp: MOCK_EPOCH + i * 1_800,
+                received_at: Some(MOCK_EPOCH + i * 1_800 - 2),
+                chan_id_in: ShortChannelID(self.channels[(i % 2) as usize].chan_id),
+                chan_id_out: ShortChannelID(self.channels[((i + 1) % 2) as usize].chan_id),
+                incoming_htlc_id: Some(i),
+                outgoing_htlc_id: Some(i),
+                amt_in_msat: 100_000_000 * (i + 1) + 1_000 + 100 * i,
+                amt_out_msat: 100_000_000 * (i + 1),
+                fee_msat: 1_000 + 100 * i,
+            })
+            .filter(|forward| forward.timestamp >= since)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_same_alias_same_node() {
+        let a = MockNode::new(NodeId::Alias("alice".to_string()), "mock://a");
+        let b = MockNode::new(NodeId::Alias("alice".to_string()), "mock://b");
+        assert_eq!(a.get_info().pubkey, b.get_info().pubkey);
+
+        let payment = &a.list_payments().await.unwrap()[0];
+        let hash = PaymentHash(
+            hex::decode(&payment.payment_hash)
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        );
+        let details = b.get_payment_details(&hash).await.unwrap();
+        assert_eq!(details.amount_sat, payment.amount_sat);
+    }
+
+    #[test]
+    fn test_script_interval() {
+        assert_eq!(script_interval("mock://alice"), DEFAULT_EVENT_DELAY);
+        assert_eq!(
+            script_interval("mock://alice?x=1&interval=2"),
+            Duration::from_secs(2)
+        );
+        assert_eq!(script_interval("mock://alice?interval=0"), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_script_loops() {
+        let mut node = MockNode::new(NodeId::Alias("bob".to_string()), "mock://bob?interval=0");
+        assert!(node.script.is_empty());
+        node.script = node.default_script(Duration::ZERO);
+
+        let events: Vec<_> = node.stream_events().await.unwrap().take(6).collect().await;
+        assert!(matches!(events[4], LightningEvent::ChannelOpened { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_verify_own_signature() {
+        let node = MockNode::new(NodeId::Alias("carol".to_string()), "mock://carol");
+        let signature = node.sign_message("hello").await.unwrap();
+        assert!(
+            node.verify_message("hello", &signature)
+                .await
+                .unwrap()
+                .valid
+        );
+        assert!(!node.verify_message("bye", &signature).await.unwrap().valid);
+    }
+}
@@ -5,13 +5,77 @@
 //! such as managing node connections or aggregating data.
 
 pub mod account_service;
+pub mod account_settings_service;
+pub mod block_stream;
+pub mod billing;
+pub mod boltz;
+pub mod channel_acceptor;
+pub mod channel_confirmations;
+pub mod channel_costs;
+pub mod channel_notes;
+pub mod channel_open_estimate;
+pub mod channel_recommendations;
+pub mod channel_revenue;
+pub mod channel_stats;
+pub mod cln_rest;
 pub mod credential_service;
 pub mod data_aggregator;
+pub mod data_purge_service;
+pub mod demo;
 pub mod email_service;
+pub mod escalations;
 pub mod event_manager;
 pub mod event_service;
+pub mod fee_automation;
+pub mod fee_position;
+pub mod forward_failures;
+pub mod forward_history;
+pub mod graph_sync;
+pub mod history_import;
+pub mod htlc_interceptor;
+pub mod incidents;
 pub mod invite_service;
+pub mod invoice_latency;
+pub mod invoice_stats;
+pub mod job_queue;
+pub mod key_rotation;
+pub mod lightning_billing;
+pub mod liquidity_flow;
+pub mod liquidity_service;
+pub mod lsps1;
+pub mod maintenance;
+pub mod mempool;
+#[cfg(feature = "mock-node")]
+pub mod mock_node;
+pub mod network_position;
+pub mod node_export;
+pub mod node_labels;
+pub mod node_breaker;
+pub mod node_limiter;
 pub mod node_manager;
+pub mod node_sync;
 pub mod notification_dispatcher;
+pub mod notification_health;
 pub mod notification_service;
+pub mod offer_analytics;
+pub mod payment_latency;
+pub mod payment_routes;
+pub mod payment_stats;
+pub mod peer_announcements;
+pub mod peer_channels;
+pub mod peer_enrichment;
+pub mod polar_bootstrap;
+pub mod profile_service;
+pub mod rebalance_service;
+pub mod report_service;
+pub mod retention_service;
+pub mod role_service;
+pub mod route_probes;
+pub mod saved_views;
+pub mod scheduler;
+pub mod stripe;
+pub mod subscription_health;
+pub mod swap_service;
+pub mod usage_meter;
 pub mod user_service;
+pub mod watched_nodes;
@@ -0,0 +1,344 @@
+//! Centrality and capacity ranking over the graph mirror.
+//!
+//! After each full graph sync the node and its direct peers are scored on
+//! betweenness centrality (how many shortest paths run through them),
+//! closeness centrality (how few hops they are from everyone else) and total
+//! capacity. Scores are stored as snapshots so the position can be charted
+//! over time.
+//!
+//! Exact betweenness needs a search from every node in the graph, so it is
+//! estimated from an evenly spread sample of source nodes once the graph is
+//! larger than `BETWEENNESS_SAMPLES`.
+
+use crate::database::models::{NetworkPositionResponse, NetworkPositionSnapshot};
+use crate::errors::ServiceResult;
+use crate::repositories::graph_repository::GraphRepository;
+use crate::repositories::network_position_repository::NetworkPositionRepository;
+use crate::services::peer_enrichment::PeerEnrichmentService;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+/// Number of source nodes betweenness is estimated from
+const BETWEENNESS_SAMPLES: usize = 500;
+
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+const MAX_HISTORY_DAYS: i64 = 365;
+
+/// Undirected channel graph with nodes numbered by index.
+pub struct ChannelGraph {
+    pubkeys: Vec<String>,
+    index: HashMap<String, usize>,
+    /// Distinct neighbours of each node; parallel channels count once
+    adjacency: Vec<Vec<usize>>,
+    channel_count: Vec<i64>,
+    capacity_sat: Vec<i64>,
+}
+
+/// Scores of a single node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeScore {
+    pub betweenness: f64,
+    pub closeness: f64,
+    pub capacity_rank: i64,
+    pub channel_count: i64,
+    pub capacity_sat: i64,
+}
+
+impl ChannelGraph {
+    /// Builds the graph from (node1, node2, capacity) channel rows.
+    pub fn from_edges(edges: &[(String, String, i64)]) -> Self {
+        let mut graph = ChannelGraph {
+            pubkeys: Vec::new(),
+            index: HashMap::new(),
+            adjacency: Vec::new(),
+            channel_count: Vec::new(),
+            capacity_sat: Vec::new(),
+        };
+
+        for (node1, node2, capacity) in edges {
+            let a = graph.node_index(node1);
+            let b = graph.node_index(node2);
+            for n in [a, b] {
+                graph.channel_count[n] += 1;
+                graph.capacity_sat[n] += capacity;
+            }
+            if a != b && !graph.adjacency[a].contains(&b) {
+                graph.adjacency[a].push(b);
+                graph.adjacency[b].push(a);
+            }
+        }
+
+        graph
+    }
+
+    fn node_index(&mut self, pubkey: &str) -> usize {
+        if let Some(&i) = self.index.get(pubkey) {
+            return i;
+        }
+        let i = self.pubkeys.len();
+        self.pubkeys.push(pubkey.to_string());
+        self.index.insert(pubkey.to_string(), i);
+        self.adjacency.push(Vec::new());
+        self.channel_count.push(0);
+        self.capacity_sat.push(0);
+        i
+    }
+
+    /// Every node's public key, most channels first.
+    pub fn pubkeys_by_degree(&self) -> Vec<String> {
+        let mut order: Vec<usize> = (0..self.pubkeys.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.channel_count[i]));
+        order.into_iter().map(|i| self.pubkeys[i].clone()).collect()
+    }
+
+    /// Public keys of a node's channel peers.
+    pub fn neighbours(&self, pubkey: &str) -> Vec<String> {
+        self.index
+            .get(pubkey)
+            .map(|&i| {
+                self.adjacency[i]
+                    .iter()
+                    .map(|&n| self.pubkeys[n].clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Scores the given nodes; keys not in the graph are left out. With no
+    /// samples, betweenness is skipped and reported as zero.
+    pub fn score(&self, pubkeys: &[String], samples: usize) -> HashMap<String, NodeScore> {
+        let betweenness = self.betweenness(samples);
+
+        pubkeys
+            .iter()
+            .filter_map(|pubkey| {
+                let i = *self.index.get(pubkey)?;
+                let capacity_rank = 1 + self
+                    .capacity_sat
+                    .iter()
+                    .filter(|c| **c > self.capacity_sat[i])
+                    .count() as i64;
+                Some((
+                    pubkey.clone(),
+                    NodeScore {
+                        betweenness: betweenness[i],
+                        closeness: self.closeness(i),
+                        capacity_rank,
+                        channel_count: self.channel_count[i],
+                        capacity_sat: self.capacity_sat[i],
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Hop distances from `source`, `None` for unreachable nodes.
+    fn distances(&self, source: usize) -> Vec<Option<usize>> {
+        let mut distance = vec![None; self.pubkeys.len()];
+        distance[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(v) = queue.pop_front() {
+            let next = distance[v].unwrap_or_default() + 1;
+            for &w in &self.adjacency[v] {
+                if distance[w].is_none() {
+                    distance[w] = Some(next);
+                    queue.push_back(w);
+                }
+            }
+        }
+        distance
+    }
+
+    /// Wasserman-Faust closeness, which scales down nodes in small components.
+    fn closeness(&self, node: usize) -> f64 {
+        let n = self.pubkeys.len();
+        let reached: Vec<usize> = self.distances(node).into_iter().flatten().collect();
+        let total: usize = reached.iter().sum();
+        let others = reached.len() - 1;
+        if n < 2 || total == 0 {
+            return 0.0;
+        }
+        (others as f64 / total as f64) * (others as f64 / (n - 1) as f64)
+    }
+
+    /// Brandes' algorithm over up to `samples` evenly spaced sources,
+    /// normalized to 0-1.
+    fn betweenness(&self, samples: usize) -> Vec<f64> {
+        let n = self.pubkeys.len();
+        let mut centrality = vec![0.0; n];
+        if n < 3 || samples == 0 {
+            return centrality;
+        }
+
+        let step = n.div_ceil(samples);
+        let mut sources = 0;
+        for source in (0..n).step_by(step) {
+            sources += 1;
+            let mut order = Vec::with_capacity(n);
+            let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+            let mut paths = vec![0.0_f64; n];
+            let mut distance: Vec<Option<usize>> = vec![None; n];
+            paths[source] = 1.0;
+            distance[source] = Some(0);
+
+            let mut queue = VecDeque::from([source]);
+            while let Some(v) = queue.pop_front() {
+                order.push(v);
+                let next = distance[v].unwrap_or_default() + 1;
+                for &w in &self.adjacency[v] {
+                    if distance[w].is_none() {
+                        distance[w] = Some(next);
+                        queue.push_back(w);
+                    }
+                    if distance[w] == Some(next) {
+                        paths[w] += paths[v];
+                        predecessors[w].push(v);
+                    }
+                }
+            }
+
+            let mut dependency = vec![0.0_f64; n];
+            while let Some(w) = order.pop() {
+                for &v in &predecessors[w] {
+                    dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+                }
+                if w != source {
+                    centrality[w] += dependency[w];
+                }
+            }
+        }
+
+        // Each pair is counted from both ends, which the undirected
+        // normalization factor of 2 cancels out.
+        let scale = n as f64 / sources as f64 / ((n - 1) * (n - 2)) as f64;
+        centrality.iter_mut().for_each(|c| *c *= scale);
+        centrality
+    }
+}
+
+/// Scores a node and its peers on the mirrored graph and stores the snapshots.
+pub async fn record_network_position(pool: &SqlitePool, node_id: &str) -> Result<(), String> {
+    let edges = GraphRepository::new(pool)
+        .get_edges(node_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let owner = node_id.to_string();
+    let scores = tokio::task::spawn_blocking(move || {
+        let graph = ChannelGraph::from_edges(&edges);
+        let mut pubkeys = graph.neighbours(&owner);
+        pubkeys.push(owner);
+        graph.score(&pubkeys, BETWEENNESS_SAMPLES)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if scores.is_empty() {
+        return Ok(());
+    }
+
+    let captured_at = Utc::now();
+    let snapshots: Vec<NetworkPositionSnapshot> = scores
+        .into_iter()
+        .map(|(pubkey, score)| NetworkPositionSnapshot {
+            id: Uuid::now_v7().to_string(),
+            source_node_id: node_id.to_string(),
+            pubkey,
+            alias: String::new(),
+            betweenness: score.betweenness,
+            closeness: score.closeness,
+            capacity_rank: score.capacity_rank,
+            channel_count: score.channel_count,
+            capacity_sat: score.capacity_sat,
+            captured_at,
+        })
+        .collect();
+
+    NetworkPositionRepository::new(pool)
+        .create_snapshots(&snapshots)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Service layer for network position analytics.
+pub struct NetworkPositionService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NetworkPositionService<'a> {
+    /// Creates a new NetworkPositionService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the latest scores of the node and its peers, and the node's trend.
+    pub async fn get_position(
+        &self,
+        node_id: &str,
+        days: Option<i64>,
+    ) -> ServiceResult<NetworkPositionResponse> {
+        let repo = NetworkPositionRepository::new(self.pool);
+        let days = days
+            .unwrap_or(DEFAULT_HISTORY_DAYS)
+            .clamp(1, MAX_HISTORY_DAYS);
+
+        let (node, peers) = repo
+            .get_latest_snapshots(node_id)
+            .await?
+            .into_iter()
+            .partition::<Vec<_>, _>(|s| s.pubkey == node_id);
+        let history = repo
+            .get_history(node_id, Utc::now() - Duration::days(days))
+            .await?;
+
+        let peer_pubkeys: Vec<String> = peers.iter().map(|peer| peer.pubkey.clone()).collect();
+        let peer_metadata = PeerEnrichmentService::new(self.pool)
+            .get_metadata(&peer_pubkeys)
+            .await;
+
+        Ok(NetworkPositionResponse {
+            node: node.into_iter().next(),
+            peers,
+            history,
+            peer_metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(a: &str, b: &str, capacity: i64) -> (String, String, i64) {
+        (a.to_string(), b.to_string(), capacity)
+    }
+
+    #[test]
+    fn test_path_graph_scores() {
+        // a - b - c, with a second a-b channel
+        let graph = ChannelGraph::from_edges(&[
+            edge("a", "b", 100),
+            edge("a", "b", 50),
+            edge("b", "c", 200),
+        ]);
+        let keys = ["a", "b", "c"].map(String::from);
+        let scores = graph.score(&keys, BETWEENNESS_SAMPLES);
+
+        assert_eq!(scores["b"].betweenness, 1.0);
+        assert_eq!(scores["a"].betweenness, 0.0);
+        assert_eq!(scores["b"].closeness, 1.0);
+        assert!((scores["a"].closeness - 2.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(scores["b"].capacity_rank, 1);
+        assert_eq!(scores["b"].capacity_sat, 350);
+        assert_eq!(scores["b"].channel_count, 3);
+        assert_eq!(scores["c"].capacity_rank, 2);
+        assert_eq!(scores["a"].capacity_rank, 3);
+
+        let mut peers = graph.neighbours("b");
+        peers.sort();
+        assert_eq!(peers, ["a", "c"]);
+    }
+}
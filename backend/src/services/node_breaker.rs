@@ -0,0 +1,293 @@
+//! Per-node circuit breaking for node RPC connections.
+//!
+//! Connecting to an unreachable node only fails once the connect timeout runs
+//! out, so every request to it would wait that long. After
+//! `failure_threshold` consecutive failed connections a node's circuit opens
+//! and `create_node_client` fails fast with a 503 for `cooldown`. Once the
+//! cooldown has passed the circuit is half-open: one request is let through
+//! as a probe, closing the circuit if it connects and reopening it if not.
+//! A probe that never reaches the node (the concurrency limit turns it away,
+//! or its credentials are rejected) hands the probe to the next request.
+//! Calls made on a client that did connect are not counted.
+
+use crate::config::Config;
+use crate::errors::ServiceResult;
+use crate::repositories::credential_repository::CredentialRepository;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_COOLDOWN_SECS: u64 = 30;
+
+static NODE_BREAKER: OnceLock<NodeBreaker> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    /// Requests fail fast until the cooldown passes
+    Open,
+    /// A probe request is allowed through
+    HalfOpen,
+}
+
+/// Returned when a node's circuit is open.
+#[derive(Debug)]
+pub struct CircuitOpen {
+    /// How long until a request may be let through again
+    pub retry_after: Duration,
+}
+
+impl CircuitOpen {
+    /// Whole seconds to put in `Retry-After`.
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after.as_secs_f64().ceil().max(1.0) as u64
+    }
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the probe let through while half-open started
+    probe_started_at: Option<Instant>,
+    trips: u64,
+    rejected: u64,
+}
+
+/// Breaker state of one node since the server started.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeBreakerMetrics {
+    pub node_id: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Times the circuit opened
+    pub trips: u64,
+    /// Requests failed fast while the circuit was open
+    pub rejected: u64,
+    /// Seconds until a probe is let through, while open
+    pub retry_after_secs: Option<u64>,
+}
+
+/// Registry of per-node circuits.
+#[derive(Debug)]
+pub struct NodeBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl NodeBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the process-wide breaker, configured from the environment on first use.
+    pub fn global() -> &'static NodeBreaker {
+        NODE_BREAKER.get_or_init(|| match Config::from_env() {
+            Ok(config) => Self::new(
+                config.node_breaker_threshold,
+                Duration::from_secs(config.node_breaker_cooldown_secs),
+            ),
+            Err(_) => Self::new(
+                DEFAULT_FAILURE_THRESHOLD,
+                Duration::from_secs(DEFAULT_COOLDOWN_SECS),
+            ),
+        })
+    }
+
+    /// Checks whether a connection to the node may be attempted. While the
+    /// circuit is half-open only one probe at a time is let through; a probe
+    /// that never reports back is replaced after another cooldown.
+    pub fn admit(&self, node_id: &str, now: Instant) -> Result<(), CircuitOpen> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let Some(circuit) = circuits.get_mut(node_id) else {
+            return Ok(());
+        };
+        let Some(opened_at) = circuit.opened_at else {
+            return Ok(());
+        };
+
+        let reopens_at = opened_at + self.cooldown;
+        if now < reopens_at {
+            circuit.rejected += 1;
+            return Err(CircuitOpen {
+                retry_after: reopens_at - now,
+            });
+        }
+        match circuit.probe_started_at {
+            Some(started_at) if now < started_at + self.cooldown => {
+                circuit.rejected += 1;
+                Err(CircuitOpen {
+                    retry_after: Duration::from_secs(1),
+                })
+            }
+            _ => {
+                circuit.probe_started_at = Some(now);
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether the node's circuit is open or half-open.
+    pub fn is_open(&self, node_id: &str) -> bool {
+        let circuits = self.circuits.lock().unwrap();
+        circuits
+            .get(node_id)
+            .is_some_and(|circuit| circuit.opened_at.is_some())
+    }
+
+    /// Records a connection that succeeded, closing the node's circuit.
+    pub fn record_success(&self, node_id: &str) {
+        let mut circuits = self.circuits.lock().unwrap();
+        if let Some(circuit) = circuits.get_mut(node_id) {
+            circuit.consecutive_failures = 0;
+            circuit.opened_at = None;
+            circuit.probe_started_at = None;
+        }
+    }
+
+    /// Lets the next request probe again when an admitted one ended without
+    /// reaching the node, e.g. turned away by the concurrency limiter or
+    /// failing on its credentials. Its outcome says nothing about the node.
+    pub fn release_probe(&self, node_id: &str) {
+        let mut circuits = self.circuits.lock().unwrap();
+        if let Some(circuit) = circuits.get_mut(node_id) {
+            circuit.probe_started_at = None;
+        }
+    }
+
+    /// Records a connection that failed, opening the node's circuit once the
+    /// threshold is reached or when a probe fails.
+    pub fn record_failure(&self, node_id: &str, now: Instant) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(node_id.to_string()).or_default();
+        circuit.consecutive_failures += 1;
+
+        let probe_failed = circuit.probe_started_at.take().is_some();
+        if probe_failed
+            || (circuit.opened_at.is_none()
+                && circuit.consecutive_failures >= self.failure_threshold)
+        {
+            if circuit.opened_at.is_none() {
+                circuit.trips += 1;
+            }
+            circuit.opened_at = Some(now);
+        }
+    }
+
+    /// Reads the breaker state of each node.
+    pub fn metrics(&self, node_ids: &[String], now: Instant) -> Vec<NodeBreakerMetrics> {
+        let circuits = self.circuits.lock().unwrap();
+        node_ids
+            .iter()
+            .map(|node_id| {
+                let circuit = circuits.get(node_id);
+                let reopens_at = circuit
+                    .and_then(|circuit| circuit.opened_at)
+                    .map(|at| at + self.cooldown);
+                let (state, retry_after_secs) = match reopens_at {
+                    None => (CircuitState::Closed, None),
+                    Some(at) if now < at => (
+                        CircuitState::Open,
+                        Some(
+                            CircuitOpen {
+                                retry_after: at - now,
+                            }
+                            .retry_after_secs(),
+                        ),
+                    ),
+                    Some(_) => (CircuitState::HalfOpen, None),
+                };
+                NodeBreakerMetrics {
+                    node_id: node_id.clone(),
+                    state,
+                    consecutive_failures: circuit.map_or(0, |c| c.consecutive_failures),
+                    trips: circuit.map_or(0, |c| c.trips),
+                    rejected: circuit.map_or(0, |c| c.rejected),
+                    retry_after_secs,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Reads the breaker state of every node connected to an account.
+pub async fn account_breaker_metrics(
+    pool: &SqlitePool,
+    account_id: &str,
+) -> ServiceResult<Vec<NodeBreakerMetrics>> {
+    let mut node_ids: Vec<String> = CredentialRepository::new(pool)
+        .get_credentials_by_account_id(account_id)
+        .await?
+        .into_iter()
+        .map(|credential| credential.node_id)
+        .collect();
+    node_ids.sort();
+    node_ids.dedup();
+
+    Ok(NodeBreaker::global().metrics(&node_ids, Instant::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_opens_probes_and_closes() {
+        let breaker = NodeBreaker::new(2, Duration::from_secs(30));
+        let start = Instant::now();
+
+        breaker.record_failure("node-a", start);
+        assert!(breaker.admit("node-a", start).is_ok());
+        breaker.record_failure("node-a", start);
+
+        let open = breaker.admit("node-a", start + Duration::from_secs(10));
+        assert_eq!(open.unwrap_err().retry_after_secs(), 20);
+        // Other nodes keep their own circuit.
+        assert!(breaker.admit("node-b", start).is_ok());
+
+        // After the cooldown one probe goes through; a failed probe reopens.
+        let half_open = start + Duration::from_secs(30);
+        assert!(breaker.admit("node-a", half_open).is_ok());
+        assert!(breaker.admit("node-a", half_open).is_err());
+        breaker.record_failure("node-a", half_open);
+        assert!(
+            breaker
+                .admit("node-a", half_open + Duration::from_secs(1))
+                .is_err()
+        );
+
+        let retry = half_open + Duration::from_secs(30);
+        assert!(breaker.admit("node-a", retry).is_ok());
+        breaker.record_success("node-a");
+        assert!(breaker.admit("node-a", retry).is_ok());
+
+        let metrics = breaker.metrics(&["node-a".to_string(), "node-b".to_string()], retry);
+        assert_eq!(metrics[0].state, CircuitState::Closed);
+        assert_eq!(metrics[0].trips, 1);
+        assert_eq!(metrics[0].rejected, 3);
+        assert_eq!(metrics[1].trips, 0);
+    }
+
+    #[test]
+    fn test_released_probe_lets_the_next_request_probe() {
+        let breaker = NodeBreaker::new(1, Duration::from_secs(30));
+        let start = Instant::now();
+        breaker.record_failure("node-a", start);
+
+        let half_open = start + Duration::from_secs(30);
+        assert!(breaker.admit("node-a", half_open).is_ok());
+        assert!(breaker.admit("node-a", half_open).is_err());
+        breaker.release_probe("node-a");
+        assert!(breaker.admit("node-a", half_open).is_ok());
+        assert!(breaker.is_open("node-a"));
+    }
+}
@@ -0,0 +1,289 @@
+//! Export bundles of everything stored about one node.
+//!
+//! A bundle is a single NDJSON file holding the node's synced channels,
+//! payments and invoices, its forwards and its events, for moving the node to
+//! another nodegaze instance or for offline analysis. The first line
+//! describes the bundle and the last one counts its records; every line in
+//! between is one record tagged with its kind:
+//!
+//! ```text
+//! {"type":"export","version":1,"node_id":"02ab...","node_alias":"...","exported_at":"..."}
+//! {"type":"channel","data":{...}}
+//! {"type":"end","counts":{"channel":12,"payment":340,...}}
+//! ```
+//!
+//! Bundles are written by a `NodeExport` job to
+//! `EXPORT_DIR/<account>/<node>/<export id>.ndjson`, and only appear there
+//! once complete, so a download never sees a partial file.
+
+use crate::config::Config;
+use crate::database::models::{JobResponse, JobType, NodeExport};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::forward_repository::ForwardRepository;
+use crate::repositories::node_sync_repository::NodeSyncRepository;
+use crate::services::job_queue::JobQueue;
+use bitcoin::secp256k1::PublicKey;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use sqlx::SqlitePool;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use uuid::Uuid;
+
+/// Version of the bundle layout, bumped on incompatible changes
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Rows read from the database at a time while writing a bundle
+const EXPORT_PAGE_SIZE: i64 = 1000;
+
+/// Payload of a `NodeExport` job.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeExportJob {
+    pub export_id: String,
+    pub account_id: String,
+    pub node_id: String,
+}
+
+/// Service layer for node export bundles.
+pub struct NodeExportService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NodeExportService<'a> {
+    /// Creates a new NodeExportService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Queues an export of one of the account's nodes.
+    pub async fn request_export(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<NodeExport> {
+        CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+        let config = Config::from_env().map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        let export_id = Uuid::now_v7().to_string();
+        let job = JobQueue::new(self.pool)
+            .enqueue(
+                JobType::NodeExport,
+                &NodeExportJob {
+                    export_id: export_id.clone(),
+                    account_id: account_id.to_string(),
+                    node_id: node_id.to_string(),
+                },
+                Some(account_id),
+                None,
+            )
+            .await?;
+
+        Ok(NodeExport {
+            download_url: format!(
+                "{}/api/v1/node/{}/export/{}",
+                config.api_base_url.trim_end_matches('/'),
+                node_id,
+                export_id
+            ),
+            id: export_id,
+            node_id: node_id.to_string(),
+            job: JobResponse::from(job),
+        })
+    }
+
+    /// Opens a finished bundle of one of the account's nodes.
+    pub async fn open_export(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        export_id: &str,
+    ) -> ServiceResult<File> {
+        // Both end up in the file path, so only well-formed ids are accepted.
+        PublicKey::from_str(node_id).map_err(|_| ServiceError::validation("Invalid node ID"))?;
+        Uuid::parse_str(export_id).map_err(|_| ServiceError::validation("Invalid export ID"))?;
+        let config = Config::from_env().map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        let path = bundle_path(&config.export_dir, account_id, node_id, export_id);
+        match File::open(&path).await {
+            Ok(file) => Ok(file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(ServiceError::not_found("Export", export_id))
+            }
+            Err(e) => Err(ServiceError::internal_error(e.to_string())),
+        }
+    }
+}
+
+fn bundle_path(export_dir: &str, account_id: &str, node_id: &str, export_id: &str) -> PathBuf {
+    Path::new(export_dir)
+        .join(account_id)
+        .join(node_id)
+        .join(format!("{export_id}.ndjson"))
+}
+
+/// Runs an export job, writing the bundle under a temporary name and moving
+/// it into place once complete.
+pub async fn run_export_job(pool: &SqlitePool, payload: &str) -> Result<(), String> {
+    let job: NodeExportJob = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+    let credential = CredentialRepository::new(pool)
+        .get_credential_by_account_and_node_id(&job.account_id, &job.node_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Node {} no longer has credentials", job.node_id))?;
+    let config = Config::from_env().map_err(|e| e.to_string())?;
+
+    let path = bundle_path(
+        &config.export_dir,
+        &job.account_id,
+        &job.node_id,
+        &job.export_id,
+    );
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Failed to create export directory: {e}"))?;
+    }
+    let partial = path.with_extension("ndjson.part");
+    let file = File::create(&partial)
+        .await
+        .map_err(|e| format!("Failed to create {}: {e}", partial.display()))?;
+    let mut writer = BufWriter::new(file);
+
+    let header = json!({
+        "type": "export",
+        "version": EXPORT_FORMAT_VERSION,
+        "node_id": job.node_id,
+        "node_alias": credential.node_alias,
+        "exported_at": Utc::now(),
+    });
+    let written = async {
+        write_line(&mut writer, &header).await?;
+        let counts = write_bundle(pool, &job, &mut writer).await?;
+        writer.flush().await.map_err(|e| e.to_string())?;
+        Ok::<_, String>(counts)
+    }
+    .await;
+    let counts = match written {
+        Ok(counts) => counts,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(format!("Export {} failed: {e}", job.export_id));
+        }
+    };
+
+    tokio::fs::rename(&partial, &path)
+        .await
+        .map_err(|e| format!("Failed to move export into place: {e}"))?;
+    tracing::info!(
+        "Wrote export {} of node {} ({})",
+        job.export_id,
+        job.node_id,
+        Value::Object(counts)
+    );
+    Ok(())
+}
+
+/// Writes every record of the node, then the closing line with the counts.
+async fn write_bundle(
+    pool: &SqlitePool,
+    job: &NodeExportJob,
+    writer: &mut BufWriter<File>,
+) -> Result<Map<String, Value>, String> {
+    let (account_id, node_id) = (job.account_id.as_str(), job.node_id.as_str());
+    let sync_repo = &NodeSyncRepository::new(pool);
+    let forward_repo = &ForwardRepository::new(pool);
+    let event_repo = &EventRepository::new(pool);
+
+    let mut counts = Map::new();
+    let channels = write_records(writer, "channel", |after| async move {
+        sync_repo
+            .get_channels_after(account_id, node_id, &after, EXPORT_PAGE_SIZE)
+            .await
+    })
+    .await?;
+    counts.insert("channel".to_string(), channels.into());
+
+    let payments = write_records(writer, "payment", |after| async move {
+        sync_repo
+            .get_payments_after(account_id, node_id, &after, EXPORT_PAGE_SIZE)
+            .await
+    })
+    .await?;
+    counts.insert("payment".to_string(), payments.into());
+
+    let invoices = write_records(writer, "invoice", |after| async move {
+        sync_repo
+            .get_invoices_after(account_id, node_id, &after, EXPORT_PAGE_SIZE)
+            .await
+    })
+    .await?;
+    counts.insert("invoice".to_string(), invoices.into());
+
+    let forwards = write_records(writer, "forward", |after| async move {
+        forward_repo
+            .get_forwards_after(account_id, node_id, &after, EXPORT_PAGE_SIZE)
+            .await
+    })
+    .await?;
+    counts.insert("forward".to_string(), forwards.into());
+
+    let events = write_records(writer, "event", |after| async move {
+        event_repo
+            .get_node_events_after(account_id, node_id, &after, EXPORT_PAGE_SIZE)
+            .await
+    })
+    .await?;
+    counts.insert("event".to_string(), events.into());
+
+    write_line(writer, &json!({ "type": "end", "counts": counts })).await?;
+    Ok(counts)
+}
+
+/// Pages through one kind of record, writing a line per record. Every
+/// exported table has a time-ordered `id`, which serves as the cursor.
+/// Returns how many records were written.
+async fn write_records<T, F, Fut>(
+    writer: &mut BufWriter<File>,
+    kind: &str,
+    mut fetch_after: F,
+) -> Result<u64, String>
+where
+    T: Serialize,
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<T>>>,
+{
+    let mut after = String::new();
+    let mut written = 0;
+    loop {
+        let page = fetch_after(after.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        for record in &page {
+            let data = serde_json::to_value(record).map_err(|e| e.to_string())?;
+            if let Some(id) = data.get("id").and_then(Value::as_str) {
+                after = id.to_string();
+            }
+            write_line(writer, &json!({ "type": kind, "data": data })).await?;
+        }
+        written += page.len() as u64;
+
+        if (page.len() as i64) < EXPORT_PAGE_SIZE {
+            return Ok(written);
+        }
+    }
+}
+
+async fn write_line(writer: &mut BufWriter<File>, value: &Value) -> Result<(), String> {
+    let mut line = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+    line.push(b'\n');
+    writer.write_all(&line).await.map_err(|e| e.to_string())
+}
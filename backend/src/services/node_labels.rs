@@ -0,0 +1,165 @@
+//! Operator labels for nodes.
+//!
+//! A node can be given a display name, an environment and free-form notes.
+//! The display name and environment are stamped into the data of every event
+//! raised for the node under `node_label`, so stored events, webhooks and
+//! Discord messages name the node the way its operators do rather than by its
+//! public key or alias.
+
+use crate::database::models::{NodeLabel, UpdateNodeLabelRequest};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::node_label_repository::NodeLabelRepository;
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use validator::Validate;
+
+/// Key of the label in event data
+pub const NODE_LABEL_KEY: &str = "node_label";
+
+/// Adds the parts of a label that identify the node to JSON event data.
+/// Labels with neither a display name nor an environment leave it as is.
+pub fn tag_event_data(data: &str, label: &NodeLabel) -> String {
+    if label.display_name.is_none() && label.environment.is_none() {
+        return data.to_string();
+    }
+    let tag = json!({
+        "display_name": label.display_name,
+        "environment": label.environment,
+    });
+    match serde_json::from_str::<Value>(data) {
+        Ok(Value::Object(mut fields)) => {
+            fields.insert(NODE_LABEL_KEY.to_string(), tag);
+            Value::Object(fields).to_string()
+        }
+        _ => json!({ NODE_LABEL_KEY: tag }).to_string(),
+    }
+}
+
+/// The display name event data was tagged with, if any.
+pub fn display_name(data: &str) -> Option<String> {
+    serde_json::from_str::<Value>(data)
+        .ok()?
+        .get(NODE_LABEL_KEY)?
+        .get("display_name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Reads an optional label field from a request: omitted keeps `current`,
+/// blank clears it.
+fn merge_field(requested: Option<String>, current: Option<String>) -> Option<String> {
+    match requested {
+        Some(value) => Some(value.trim().to_string()).filter(|value| !value.is_empty()),
+        None => current,
+    }
+}
+
+/// Service layer for node labels.
+pub struct NodeLabelService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NodeLabelService<'a> {
+    /// Creates a new NodeLabelService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn ensure_node(&self, account_id: &str, node_id: &str) -> ServiceResult<()> {
+        CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+        Ok(())
+    }
+
+    /// Retrieves a node's label, or `None` if it was never labelled.
+    pub async fn get_label(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<Option<NodeLabel>> {
+        self.ensure_node(account_id, node_id).await?;
+        Ok(NodeLabelRepository::new(self.pool)
+            .get_label(account_id, node_id)
+            .await?)
+    }
+
+    /// Changes the fields of a node's label given in the request.
+    pub async fn update_label(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: UpdateNodeLabelRequest,
+    ) -> ServiceResult<NodeLabel> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        self.ensure_node(account_id, node_id).await?;
+
+        let repo = NodeLabelRepository::new(self.pool);
+        let current = repo.get_label(account_id, node_id).await?;
+        let (display_name, environment, notes) = match current {
+            Some(label) => (label.display_name, label.environment, label.notes),
+            None => (None, None, None),
+        };
+        let label = repo
+            .upsert_label(
+                account_id,
+                node_id,
+                merge_field(request.display_name, display_name).as_deref(),
+                merge_field(request.environment, environment).as_deref(),
+                merge_field(request.notes, notes).as_deref(),
+                user_id,
+            )
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "node_label_updated",
+                "node",
+                Some(node_id),
+                &json!({
+                    "display_name": label.display_name,
+                    "environment": label.environment,
+                }),
+            )
+            .await?;
+
+        Ok(label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_tag_event_data() {
+        let mut label = NodeLabel {
+            account_id: "account".to_string(),
+            node_id: "03fe9a".to_string(),
+            display_name: Some("prod-router-1".to_string()),
+            environment: Some("prod".to_string()),
+            notes: Some("Behind the office NAT".to_string()),
+            updated_by: "user".to_string(),
+            updated_at: Utc::now(),
+        };
+
+        let tagged = tag_event_data(r#"{"chan_id":1}"#, &label);
+        assert_eq!(display_name(&tagged).as_deref(), Some("prod-router-1"));
+        assert!(tagged.contains("chan_id"));
+        assert!(!tagged.contains("office"));
+        assert_eq!(display_name(r#"{"chan_id":1}"#), None);
+
+        label.display_name = None;
+        label.environment = None;
+        assert_eq!(tag_event_data("{}", &label), "{}");
+    }
+}
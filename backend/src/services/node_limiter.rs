@@ -0,0 +1,94 @@
+//! Per-node concurrency limiting for node RPC calls.
+//!
+//! Small nodes (e.g. a Raspberry Pi) struggle when a dashboard fires many RPCs
+//! in parallel. Every client handed out by `create_node_client` holds a permit
+//! from its node's semaphore, so at most `max_in_flight` requests run against a
+//! node at once and the rest wait up to `queue_timeout` before giving up.
+
+use crate::config::Config;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+const DEFAULT_QUEUE_TIMEOUT_MS: u64 = 5000;
+
+static NODE_LIMITER: OnceLock<NodeLimiter> = OnceLock::new();
+
+/// Returned when a node already has the maximum number of requests in flight
+/// and no slot became free within the queue timeout.
+#[derive(Debug)]
+pub struct NodeBusy;
+
+/// Registry of per-node semaphores.
+#[derive(Debug)]
+pub struct NodeLimiter {
+    max_in_flight: usize,
+    queue_timeout: Duration,
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl NodeLimiter {
+    pub fn new(max_in_flight: usize, queue_timeout: Duration) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+            queue_timeout,
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the process-wide limiter, configured from the environment on first use.
+    pub fn global() -> &'static NodeLimiter {
+        NODE_LIMITER.get_or_init(|| match Config::from_env() {
+            Ok(config) => Self::new(
+                config.node_max_inflight_requests,
+                Duration::from_millis(config.node_queue_timeout_ms),
+            ),
+            Err(_) => Self::new(
+                DEFAULT_MAX_IN_FLIGHT,
+                Duration::from_millis(DEFAULT_QUEUE_TIMEOUT_MS),
+            ),
+        })
+    }
+
+    /// Waits for a free slot on the given node.
+    pub async fn acquire(&self, node_id: &str) -> Result<OwnedSemaphorePermit, NodeBusy> {
+        let semaphore = {
+            let mut semaphores = self.semaphores.lock().unwrap();
+            semaphores
+                .entry(node_id.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(self.max_in_flight)))
+                .clone()
+        };
+
+        match tokio::time::timeout(self.queue_timeout, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            // The semaphore is never closed, and a timeout means the node is saturated.
+            _ => Err(NodeBusy),
+        }
+    }
+
+    /// Seconds a client should wait before retrying a rejected request.
+    pub fn retry_after_secs(&self) -> u64 {
+        self.queue_timeout.as_secs().max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_saturated_node_is_rejected() {
+        let limiter = NodeLimiter::new(1, Duration::from_millis(10));
+
+        let permit = limiter.acquire("node-a").await.unwrap();
+        assert!(limiter.acquire("node-a").await.is_err());
+        // Other nodes have their own budget.
+        assert!(limiter.acquire("node-b").await.is_ok());
+
+        drop(permit);
+        assert!(limiter.acquire("node-a").await.is_ok());
+    }
+}
@@ -2,16 +2,31 @@
 //!
 //! This module defines connection structures (`LndConnection`, `ClnConnection`),
 //! manages authenticated node instances (`LndNode`, `ClnNode`), handles their lifecycle,
-//! and provides methods for interacting with the Lightning node RPCs.
+//! and provides methods for interacting with the Lightning node RPCs. The rune-based
+//! CLN REST client lives in [`crate::services::cln_rest`]. With the `mock-node`
+//! feature, LND connections to a `mock://` address are served by
+//! [`crate::services::mock_node`] instead.
 
 use crate::{
+    database::models::BalanceChangeCause,
     errors::LightningError,
-    services::event_manager::{CLNEvent, LNDEvent, NodeSpecificEvent},
+    services::{
+        cln_rest::{ClnRestConnection, format_short_channel_id, parse_short_channel_id},
+        event_manager::{InvoiceEvent, LightningEvent},
+    },
     utils::{
-        self, ChannelDetails, ChannelState, ChannelSummary, CustomInvoice, Feature, Hop,
-        InvoiceHtlc, InvoiceStatus, NodeId, NodeInfo, NodePolicy, PaymentDetails, PaymentHtlc,
-        PaymentState, PaymentSummary, PaymentType, Route, ShortChannelID,
+        self, ChannelDetails, ChannelSummary, CustomInvoice, Feature, Forward, GraphChannel,
+        GraphNode, GraphUpdate, Hop, InvoiceHtlc, MessageVerification, NetworkGraph, NodeId,
+        NodeInfo, NodePolicy, OfferInvoice, PaymentAttempt, PaymentDetails, PaymentHtlc,
+        PaymentLatency, PaymentMismatch, PaymentProgress, PaymentState, PaymentSummary,
+        PaymentType, PendingChannel, PendingChannelKind, ProbeOutcome, RebalanceOutcome, Route,
+        ShortChannelID,
         sats_to_usd::PriceConverter,
+        states::{
+            cln_channel_state_code, cln_invoice_payment_state_code, cln_invoice_status_code,
+            cln_pay_state_code, lnd_channel_state, lnd_htlc_state, lnd_invoice_payment_state,
+            lnd_invoice_status, lnd_payment_state,
+        },
     },
 };
 
@@ -20,12 +35,13 @@ use async_trait::async_trait;
 use bitcoin::{Network, OutPoint, Txid, secp256k1::PublicKey};
 use cln_grpc::pb::{
     GetinfoRequest, ListchannelsRequest, ListnodesRequest, ListpeerchannelsRequest,
-    node_client::NodeClient,
+    listpays_pays::ListpaysPaysStatus, node_client::NodeClient,
 };
 use futures::stream::{SelectAll, StreamExt};
 use hex;
 use lightning::ln::{PaymentHash, features::NodeFeatures};
 use lightning_invoice::{Bolt11Invoice, Bolt11InvoiceDescription};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
@@ -37,29 +53,45 @@ use tokio::time::Duration;
 use tokio::{
     fs::File,
     io::{AsyncReadExt, Error},
-    sync::Mutex,
+    sync::{Mutex, mpsc},
     time::sleep,
 };
-use tokio_stream::Stream;
+use tokio_stream::{Stream, wrappers::ReceiverStream};
 use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
 use tonic_lnd::{
     Client,
     lnrpc::{
-        ChannelEventSubscription, ChannelEventUpdate, ChannelGraphRequest, GetInfoRequest, Invoice,
-        InvoiceSubscription, ListChannelsRequest, ListInvoiceRequest, ListPaymentsRequest,
-        NodeInfoRequest,
+        ChannelAcceptRequest, ChannelAcceptResponse, ChannelEdge, ChannelEventSubscription,
+        ChannelEventUpdate, ChannelGraphRequest, ChannelPoint, ClosedChannelsRequest,
+        ForwardingHistoryRequest, GetInfoRequest, GetTransactionsRequest,
+        GraphTopologySubscription, Invoice, InvoiceHtlcState, InvoiceSubscription,
+        ListChannelsRequest, ListInvoiceRequest, ListPaymentsRequest, NodeInfoRequest,
+        PaymentFailureReason, PendingChannelsRequest, PolicyUpdateRequest, RoutingPolicy,
+        SignMessageRequest, VerifyMessageRequest,
         channel_event_update::{Channel as EventChannel, UpdateType as LndChannelUpdateType},
+        channel_point,
+        failure::FailureCode,
+        htlc_attempt::HtlcStatus,
         invoice::InvoiceState,
         payment::PaymentStatus,
+        pending_channels_response::PendingChannel as LndPendingChannel,
+        policy_update_request,
+    },
+    routerrpc::{
+        CircuitKey, ForwardHtlcInterceptRequest, ForwardHtlcInterceptResponse, HtlcEvent,
+        ResolveHoldForwardAction, SendPaymentRequest, SubscribeHtlcEventsRequest,
+        TrackPaymentRequest,
+        htlc_event::{self, EventType as HtlcEventType},
     },
     tonic::Streaming,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum ConnectionRequest {
     Lnd(LndConnection),
     Cln(ClnConnection),
+    ClnRest(ClnRestConnection),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -81,6 +113,30 @@ pub struct LndNode {
     price_converter: PriceConverter,
 }
 
+/// LND feature bits (the keys of its feature map), ascending.
+fn sorted_keys<V>(features: HashMap<u32, V>) -> Vec<u32> {
+    let mut bits: Vec<u32> = features.into_keys().collect();
+    bits.sort_unstable();
+    bits
+}
+
+/// Bits set in a BOLT 9 feature vector (big-endian, bit 0 in the last
+/// byte), ascending.
+fn feature_bits(bytes: &[u8]) -> Vec<u32> {
+    let mut bits: Vec<u32> = bytes
+        .iter()
+        .rev()
+        .enumerate()
+        .flat_map(|(i, byte)| {
+            (0..8)
+                .filter(move |bit| byte & (1 << bit) != 0)
+                .map(move |bit| (i * 8 + bit) as u32)
+        })
+        .collect();
+    bits.sort_unstable();
+    bits
+}
+
 /// Parses the node features from the format returned by LND gRPC to LDK NodeFeatures
 fn parse_node_features(features: HashSet<u32>) -> NodeFeatures {
     let mut flags = vec![0; 256];
@@ -98,6 +154,22 @@ fn parse_node_features(features: HashSet<u32>) -> NodeFeatures {
     NodeFeatures::from_le_bytes(flags)
 }
 
+/// Connects to an LND node, or builds a mock node for a `mock://` address
+/// when the `mock-node` feature is enabled.
+pub async fn connect_lnd(
+    connection: LndConnection,
+) -> Result<Box<dyn LightningClient + Send + Sync + 'static>, LightningError> {
+    #[cfg(feature = "mock-node")]
+    if crate::services::mock_node::is_mock_address(&connection.address) {
+        return Ok(Box::new(crate::services::mock_node::MockNode::new(
+            connection.id,
+            &connection.address,
+        )));
+    }
+
+    Ok(Box::new(LndNode::new(connection).await?))
+}
+
 impl LndNode {
     pub async fn new(connection: LndConnection) -> Result<Self, LightningError> {
         let mut client =
@@ -194,21 +266,91 @@ impl LndNode {
         Ok(invoice_event_stream)
     }
 
+    async fn stream_htlc_events(&self) -> Result<Streaming<HtlcEvent>, LightningError> {
+        let mut router = {
+            let mut client = self.client.lock().await;
+            client.router().clone()
+        };
+        router
+            .subscribe_htlc_events(SubscribeHtlcEventsRequest {})
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|e| LightningError::StreamingError(format!("{e}")))
+    }
+
     async fn get_lightning_stub(&self) -> tonic_lnd::LightningClient {
         let mut client = self.client.lock().await;
         client.lightning().clone()
     }
 
+    /// Details of a channel that is no longer open, from its close summary.
+    async fn get_closed_channel_info(
+        &self,
+        channel_id: &ShortChannelID,
+    ) -> Result<ChannelDetails, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+        let summary = lightning_stub
+            .closed_channels(ClosedChannelsRequest::default())
+            .await
+            .map_err(|err| {
+                LightningError::ChannelError(format!("LND closed_channels error: {err}"))
+            })?
+            .into_inner()
+            .channels
+            .into_iter()
+            .find(|channel| channel.chan_id == channel_id.0)
+            .ok_or_else(|| LightningError::ChannelError("Channel not found".to_string()))?;
+
+        let channel_point = parse_channel_point(&summary.channel_point)?;
+        let remote_pubkey = PublicKey::from_str(&summary.remote_pubkey)
+            .map_err(|err| LightningError::ChannelError(format!("Invalid remote pubkey: {err}")))?;
+        let capacity_sat: u64 = summary.capacity.try_into().unwrap_or(0);
+        let local_balance_sat: u64 = (summary.settled_balance + summary.time_locked_balance)
+            .try_into()
+            .unwrap_or(0);
+
+        Ok(ChannelDetails {
+            channel_id: ShortChannelID(summary.chan_id),
+            local_balance_sat,
+            remote_balance_sat: capacity_sat.saturating_sub(local_balance_sat),
+            capacity_sat,
+            active: Some(false),
+            private: false,
+            remote_pubkey,
+            commit_fee_sat: None,
+            local_chan_reserve_sat: None,
+            remote_chan_reserve_sat: None,
+            num_updates: None,
+            total_satoshis_sent: None,
+            total_satoshis_received: None,
+            channel_age_blocks: None,
+            opening_cost_sat: None,
+            funding_fee_sat: None,
+            closing_txid: Txid::from_str(&summary.closing_tx_hash).ok(),
+            closing_fee_sat: None,
+            rebalance_cost_sat: None,
+            peer_metadata: None,
+            notes: Vec::new(),
+            // Initiator::Local = 1, Initiator::Remote = 2
+            initiator: match summary.open_initiator {
+                1 => Some(true),
+                2 => Some(false),
+                _ => None,
+            },
+            txid: Some(channel_point.txid),
+            vout: Some(channel_point.vout),
+            node1_policy: None,
+            node2_policy: None,
+        })
+    }
+
     async fn process_outgoing_payment(
         &self,
         payment: tonic_lnd::lnrpc::Payment,
     ) -> Result<PaymentDetails, LightningError> {
-        let state = match PaymentStatus::try_from(payment.status).unwrap_or(PaymentStatus::Unknown)
-        {
-            PaymentStatus::Unknown | PaymentStatus::InFlight => PaymentState::Inflight,
-            PaymentStatus::Succeeded => PaymentState::Settled,
-            PaymentStatus::Failed => PaymentState::Failed,
-        };
+        let state = lnd_payment_state(
+            PaymentStatus::try_from(payment.status).unwrap_or(PaymentStatus::Unknown),
+        );
 
         let creation_time = payment
             .creation_time_ns
@@ -326,16 +468,8 @@ impl LndNode {
         &self,
         invoice: tonic_lnd::lnrpc::Invoice,
     ) -> Result<PaymentDetails, LightningError> {
-        let state = match invoice.state {
-            0 => {
-                // OPEN - check if payment is in progress
-                PaymentState::Inflight
-            }
-            1 => PaymentState::Settled,  // SETTLED
-            2 => PaymentState::Failed,   // CANCELED
-            3 => PaymentState::Inflight, // ACCEPTED (payment in progress)
-            _ => PaymentState::Inflight, // Default to inflight for unknown states
-        };
+        let state = InvoiceState::try_from(invoice.state)
+            .map_or(PaymentState::Inflight, lnd_invoice_payment_state);
 
         let creation_time = Some(invoice.creation_date as u64);
 
@@ -403,7 +537,11 @@ impl LndNode {
 
         Ok(PaymentDetails {
             state,
-            payment_type: PaymentType::Incoming,
+            payment_type: if invoice.is_keysend {
+                PaymentType::Keysend
+            } else {
+                PaymentType::Incoming
+            },
             amount_sat,
             amount_usd,
             routing_fee: None,
@@ -417,6 +555,205 @@ impl LndNode {
             htlcs,
         })
     }
+
+    /// Registers as a channel acceptor, taking over the accept/reject decision
+    /// for inbound channel opens until the returned acceptor is dropped.
+    pub async fn channel_acceptor(&self) -> Result<LndChannelAcceptor, LightningError> {
+        let mut lightning = {
+            let mut client = self.client.lock().await;
+            client.lightning().clone()
+        };
+
+        let (responses, receiver) = mpsc::channel(16);
+        let requests = lightning
+            .channel_acceptor(ReceiverStream::new(receiver))
+            .await
+            .map_err(|err| {
+                LightningError::StreamingError(format!("LND channel_acceptor error: {err}"))
+            })?
+            .into_inner();
+
+        Ok(LndChannelAcceptor {
+            requests,
+            responses,
+        })
+    }
+
+    /// Registers as the node's HTLC interceptor. Forwards are held until
+    /// resolved through the returned interceptor.
+    pub async fn htlc_interceptor(&self) -> Result<LndHtlcInterceptor, LightningError> {
+        let mut router = {
+            let mut client = self.client.lock().await;
+            client.router().clone()
+        };
+
+        let (responses, receiver) = mpsc::channel(64);
+        let requests = router
+            .htlc_interceptor(ReceiverStream::new(receiver))
+            .await
+            .map_err(|err| {
+                LightningError::StreamingError(format!("LND htlc_interceptor error: {err}"))
+            })?
+            .into_inner();
+
+        Ok(LndHtlcInterceptor {
+            requests,
+            responses,
+        })
+    }
+
+    /// Maps the node's channel IDs to the public key of the peer on the other end.
+    pub async fn channel_peers(&self) -> Result<HashMap<u64, String>, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+
+        let response = lightning_stub
+            .list_channels(ListChannelsRequest {
+                active_only: false,
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| {
+                LightningError::ChannelError(format!("LND list_channels error: {err}"))
+            })?;
+
+        Ok(response
+            .into_inner()
+            .channels
+            .into_iter()
+            .map(|channel| (channel.chan_id, channel.remote_pubkey))
+            .collect())
+    }
+}
+
+/// An inbound channel open waiting on a channel acceptor decision.
+#[derive(Debug, Clone)]
+pub struct ChannelOpenRequest {
+    pub pending_chan_id: Vec<u8>,
+    pub peer_pubkey: String,
+    pub funding_sat: u64,
+    pub push_msat: u64,
+    /// The opener asked for the channel not to be announced
+    pub private: bool,
+}
+
+/// An open LND ChannelAcceptor stream. LND holds every inbound open until it
+/// gets a response, so each request must be answered with `respond`.
+pub struct LndChannelAcceptor {
+    requests: Streaming<ChannelAcceptRequest>,
+    responses: mpsc::Sender<ChannelAcceptResponse>,
+}
+
+impl LndChannelAcceptor {
+    /// Waits for the next inbound open. `None` means LND closed the stream.
+    pub async fn next_request(&mut self) -> Result<Option<ChannelOpenRequest>, LightningError> {
+        let request = self
+            .requests
+            .message()
+            .await
+            .map_err(|err| LightningError::StreamingError(err.to_string()))?;
+
+        Ok(request.map(|request| ChannelOpenRequest {
+            peer_pubkey: hex::encode(&request.node_pubkey),
+            pending_chan_id: request.pending_chan_id,
+            funding_sat: request.funding_amt,
+            push_msat: request.push_amt,
+            // Bit 0 of channel_flags is announce_channel.
+            private: request.channel_flags & 1 == 0,
+        }))
+    }
+
+    /// Accepts or rejects a pending open. `error` is relayed to the peer on rejection.
+    pub async fn respond(
+        &self,
+        pending_chan_id: Vec<u8>,
+        accept: bool,
+        error: Option<String>,
+    ) -> Result<(), LightningError> {
+        self.responses
+            .send(ChannelAcceptResponse {
+                accept,
+                pending_chan_id,
+                error: error.unwrap_or_default(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|_| LightningError::StreamingError("channel acceptor stream closed".into()))
+    }
+}
+
+/// A forward held by the HTLC interceptor.
+#[derive(Debug, Clone)]
+pub struct InterceptedHtlc {
+    pub incoming_chan_id: u64,
+    pub htlc_id: u64,
+    pub payment_hash: String,
+    pub incoming_amount_msat: u64,
+    pub outgoing_amount_msat: u64,
+    pub outgoing_chan_id: u64,
+}
+
+/// An open LND HtlcInterceptor stream. Each intercepted forward stays held
+/// until it is resumed or failed.
+pub struct LndHtlcInterceptor {
+    requests: Streaming<ForwardHtlcInterceptRequest>,
+    responses: mpsc::Sender<ForwardHtlcInterceptResponse>,
+}
+
+impl LndHtlcInterceptor {
+    /// Waits for the next intercepted forward. `None` means LND closed the stream.
+    pub async fn next_htlc(&mut self) -> Result<Option<InterceptedHtlc>, LightningError> {
+        let request = self
+            .requests
+            .message()
+            .await
+            .map_err(|err| LightningError::StreamingError(err.to_string()))?;
+
+        Ok(request.map(|request| {
+            let circuit_key = request.incoming_circuit_key.unwrap_or_default();
+            InterceptedHtlc {
+                incoming_chan_id: circuit_key.chan_id,
+                htlc_id: circuit_key.htlc_id,
+                payment_hash: hex::encode(&request.payment_hash),
+                incoming_amount_msat: request.incoming_amount_msat,
+                outgoing_amount_msat: request.outgoing_amount_msat,
+                outgoing_chan_id: request.outgoing_requested_chan_id,
+            }
+        }))
+    }
+
+    /// Lets a held forward continue as LND would have without an interceptor.
+    pub async fn resume(&self, htlc: &InterceptedHtlc) -> Result<(), LightningError> {
+        self.resolve(htlc, ResolveHoldForwardAction::Resume).await
+    }
+
+    /// Fails a held forward back to the sender with a temporary channel failure.
+    pub async fn fail(&self, htlc: &InterceptedHtlc) -> Result<(), LightningError> {
+        self.resolve(htlc, ResolveHoldForwardAction::Fail).await
+    }
+
+    async fn resolve(
+        &self,
+        htlc: &InterceptedHtlc,
+        action: ResolveHoldForwardAction,
+    ) -> Result<(), LightningError> {
+        let failure_code = match action {
+            ResolveHoldForwardAction::Fail => FailureCode::TemporaryChannelFailure as i32,
+            _ => 0,
+        };
+
+        self.responses
+            .send(ForwardHtlcInterceptResponse {
+                incoming_circuit_key: Some(CircuitKey {
+                    chan_id: htlc.incoming_chan_id,
+                    htlc_id: htlc.htlc_id,
+                }),
+                action: action as i32,
+                failure_code,
+                ..Default::default()
+            })
+            .await
+            .map_err(|_| LightningError::StreamingError("HTLC interceptor stream closed".into()))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -578,16 +915,42 @@ impl ClnNode {
         Ok(htlcs)
     }
 
+    /// Streams a `PaymentSent` or `PaymentFailed` event for every sendpay part
+    /// that resolves from now on, the same parts CLN reports through its
+    /// `sendpay_success` and `sendpay_failure` notifications. Parts that were
+    /// already resolved when the stream starts are not replayed.
+    async fn stream_payment_events_only(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = LightningEvent> + Send>>, LightningError> {
+        let mut client = self.get_client_stub().await;
+        let mut watcher = ClnSendpayWatcher::default();
+        let parts = list_cln_sendpays(&mut client)
+            .await
+            .map_err(LightningError::StreamingError)?;
+        watcher.resolve(parts);
+
+        let event_stream = stream! {
+            loop {
+                sleep(Duration::from_secs(CLN_SENDPAY_POLL_SECS)).await;
+                match list_cln_sendpays(&mut client).await {
+                    Ok(parts) => {
+                        for event in watcher.resolve(parts) {
+                            yield event;
+                        }
+                    }
+                    Err(e) => eprintln!("Error reading CLN sendpays: {e}"),
+                }
+            }
+        };
+
+        Ok(Box::pin(event_stream))
+    }
+
     async fn process_outgoing_payment(
         &self,
         payment: cln_grpc::pb::ListpaysPays,
     ) -> Result<PaymentDetails, LightningError> {
-        let state = match payment.status {
-            0 => PaymentState::Inflight, // pending
-            1 => PaymentState::Settled,  // complete
-            2 => PaymentState::Failed,   // failed
-            _ => PaymentState::Failed,
-        };
+        let state = cln_pay_state_code(payment.status);
 
         // Calculate amounts
         let amount = payment
@@ -659,12 +1022,8 @@ impl ClnNode {
         &self,
         invoice: cln_grpc::pb::ListinvoicesInvoices,
     ) -> Result<PaymentDetails, LightningError> {
-        let state = match invoice.status {
-            0 => PaymentState::Inflight, // unpaid
-            1 => PaymentState::Settled,  // paid
-            2 => PaymentState::Failed,   // expired
-            _ => PaymentState::Inflight,
-        };
+        let state =
+            cln_invoice_payment_state_code(invoice.status).unwrap_or(PaymentState::Inflight);
 
         let creation_time = (invoice.expires_at > 0).then_some(invoice.expires_at);
 
@@ -725,6 +1084,46 @@ async fn reader(filename: &str) -> Result<Vec<u8>, Error> {
     Ok(contents)
 }
 
+/// Stream of progress snapshots for a tracked payment; ends once it settles or fails.
+pub type PaymentProgressStream =
+    Pin<Box<dyn Stream<Item = Result<PaymentProgress, LightningError>> + Send>>;
+
+/// Stream of incremental changes to the channel graph.
+pub type GraphUpdateStream =
+    Pin<Box<dyn Stream<Item = Result<GraphUpdate, LightningError>> + Send>>;
+
+/// How long LND keeps looking for a route before giving up on a payment
+const LND_PAYMENT_TIMEOUT_SECS: i32 = 60;
+
+/// Forwarding events fetched from LND per request
+const LND_FORWARDS_PAGE_SIZE: u32 = 10_000;
+
+/// In-flight forwards and sends LND's HTLC stream keeps track of before
+/// evicting the oldest; forwards dropped this way are still picked up by the
+/// history backfill
+const LND_MAX_OPEN_CIRCUITS: usize = 10_000;
+
+/// Payments fetched from LND per request when walking back through history
+const LND_PAYMENTS_PAGE_SIZE: u64 = 1_000;
+
+/// Expiry of the invoice a circular rebalance pays to ourselves
+const REBALANCE_INVOICE_EXPIRY_SECS: u64 = 10 * 60;
+
+/// How long LND keeps looking for a route before giving up on a probe
+const LND_PROBE_TIMEOUT_SECS: i32 = 30;
+
+/// CLTV delta LND uses when a channel's own policy is not in its graph yet
+const LND_DEFAULT_TIME_LOCK_DELTA: u32 = 80;
+
+/// How long CLN's `waitsendpay` blocks before the tracker re-reads the attempts
+const CLN_TRACK_WAIT_SECS: u32 = 5;
+
+/// Gap between two reads of CLN's sendpays when watching for resolved parts
+const CLN_SENDPAY_POLL_SECS: u64 = 5;
+
+/// Custom record keysend payers put a UTF-8 text message in
+const KEYSEND_MESSAGE_TLV: u64 = 34_349_334;
+
 /// Unified interface for Lightning Network node operations across different implementations.
 #[async_trait]
 pub trait LightningClient: Send {
@@ -732,6 +1131,34 @@ pub trait LightningClient: Send {
     fn get_info(&self) -> &NodeInfo;
     /// Retrieves the Bitcoin network the node is connected to.
     async fn get_network(&self) -> Result<Network, LightningError>;
+    /// Returns the height of the best block the node has processed.
+    async fn get_block_height(&self) -> Result<u32, LightningError> {
+        Err(LightningError::Unsupported(
+            "block height is not available for this node type".to_string(),
+        ))
+    }
+    /// Lists channels waiting on their funding or closing transaction.
+    async fn list_pending_channels(&self) -> Result<Vec<PendingChannel>, LightningError> {
+        Err(LightningError::Unsupported(
+            "pending channels are not available for this node type".to_string(),
+        ))
+    }
+    /// Returns the fee the node's wallet paid for a transaction, or None if
+    /// the wallet doesn't know it.
+    async fn get_transaction_fee(&self, _txid: &Txid) -> Result<Option<u64>, LightningError> {
+        Err(LightningError::Unsupported(
+            "wallet transactions are not available for this node type".to_string(),
+        ))
+    }
+    /// Lists the invoices issued for a BOLT12 offer.
+    async fn list_offer_invoices(
+        &self,
+        _offer_id: &str,
+    ) -> Result<Vec<OfferInvoice>, LightningError> {
+        Err(LightningError::Unsupported(
+            "BOLT12 offers are not available for this node type".to_string(),
+        ))
+    }
     /// Fetches public information about a Lightning node by its public key.
     async fn get_node_info(&self, node_id: &PublicKey) -> Result<NodeInfo, LightningError>;
     /// Lists all channels, returning only their capacities in millisatoshis.
@@ -750,7 +1177,7 @@ pub trait LightningClient: Send {
     /// Returns a stream of raw events from the lightning node.
     async fn stream_events(
         &mut self,
-    ) -> Result<Pin<Box<dyn Stream<Item = NodeSpecificEvent> + Send>>, LightningError>;
+    ) -> Result<Pin<Box<dyn Stream<Item = LightningEvent> + Send>>, LightningError>;
     /// Lists all invoices.
     async fn list_invoices(&self) -> Result<Vec<CustomInvoice>, LightningError>;
     /// Gets detailed information about a specific invoice by its payment hash.
@@ -758,6 +1185,112 @@ pub trait LightningClient: Send {
         &self,
         payment_hash: &PaymentHash,
     ) -> Result<CustomInvoice, LightningError>;
+    /// Signs a message with the node's identity key, returning a zbase32 signature.
+    async fn sign_message(&self, message: &str) -> Result<String, LightningError>;
+    /// Checks a zbase32 signature over a message and recovers the signer's key.
+    async fn verify_message(
+        &self,
+        message: &str,
+        signature: &str,
+    ) -> Result<MessageVerification, LightningError>;
+    /// Follows an outgoing payment attempt by attempt until it settles or fails.
+    async fn track_payment(
+        &self,
+        _payment_hash: &PaymentHash,
+    ) -> Result<PaymentProgressStream, LightningError> {
+        Err(LightningError::Unsupported(
+            "payment tracking is not available for this node type".to_string(),
+        ))
+    }
+    /// Fetches the node's view of the public channel graph.
+    async fn describe_graph(&self) -> Result<NetworkGraph, LightningError> {
+        Err(LightningError::Unsupported(
+            "graph export is not available for this node type".to_string(),
+        ))
+    }
+    /// Streams changes to the public channel graph as the node learns of them.
+    async fn subscribe_graph(&self) -> Result<GraphUpdateStream, LightningError> {
+        Err(LightningError::Unsupported(
+            "graph subscriptions are not available for this node type".to_string(),
+        ))
+    }
+    /// Creates an invoice for `amount_msat` and returns it as a BOLT 11 string.
+    async fn create_invoice(
+        &self,
+        _amount_msat: u64,
+        _memo: &str,
+        _expiry_secs: u64,
+    ) -> Result<String, LightningError> {
+        Err(LightningError::Unsupported(
+            "creating invoices is not available for this node type".to_string(),
+        ))
+    }
+    /// Starts paying an invoice, returning once the payment is in flight
+    /// rather than when it settles (hold invoices may stay pending for hours).
+    async fn send_payment(
+        &self,
+        _bolt11: &str,
+        _max_fee_sat: u64,
+        _outgoing_chan_id: Option<u64>,
+    ) -> Result<(), LightningError> {
+        Err(LightningError::Unsupported(
+            "sending payments is not available for this node type".to_string(),
+        ))
+    }
+    /// Lists the forwards the node settled since `since` (Unix seconds).
+    async fn list_forwards(&self, _since: u64) -> Result<Vec<Forward>, LightningError> {
+        Err(LightningError::Unsupported(
+            "forwarding history is not available for this node type".to_string(),
+        ))
+    }
+    /// Lists how long each outgoing payment settled since `since` (unix
+    /// seconds) took.
+    async fn list_payment_latencies(
+        &self,
+        _since: u64,
+    ) -> Result<Vec<PaymentLatency>, LightningError> {
+        Err(LightningError::Unsupported(
+            "payment timings are not available for this node type".to_string(),
+        ))
+    }
+    /// Pays an invoice of our own out through `outgoing_chan_id` and back in
+    /// over a channel with `last_hop_pubkey`, waiting for the payment to resolve.
+    async fn rebalance(
+        &self,
+        _outgoing_chan_id: u64,
+        _last_hop_pubkey: &PublicKey,
+        _amount_msat: u64,
+        _max_fee_msat: u64,
+    ) -> Result<RebalanceOutcome, LightningError> {
+        Err(LightningError::Unsupported(
+            "circular rebalancing is not available for this node type".to_string(),
+        ))
+    }
+    /// Sends `amount_msat` towards `destination` under a random payment hash,
+    /// out through `outgoing_chan_id` if given, to check a route can carry it
+    /// without paying anything.
+    async fn probe(
+        &self,
+        _destination: &PublicKey,
+        _amount_msat: u64,
+        _outgoing_chan_id: Option<u64>,
+        _max_fee_msat: u64,
+    ) -> Result<ProbeOutcome, LightningError> {
+        Err(LightningError::Unsupported(
+            "probing is not available for this node type".to_string(),
+        ))
+    }
+    /// Sets the base fee and fee rate our side of `channel` charges for forwards.
+    async fn update_channel_fees(
+        &self,
+        _channel: &ChannelDetails,
+        _base_fee_msat: u64,
+        _fee_rate_ppm: u32,
+    ) -> Result<(), LightningError> {
+        Err(LightningError::Unsupported(
+            "updating channel fees is not available for this node type".to_string(),
+        ))
+    }
 }
 
 #[async_trait]
@@ -797,6 +1330,93 @@ impl LightningClient for LndNode {
         .map_err(|err| LightningError::ValidationError(err.to_string()))?)
     }
 
+    async fn get_block_height(&self) -> Result<u32, LightningError> {
+        let mut client = self.client.lock().await;
+        let info = client
+            .lightning()
+            .get_info(GetInfoRequest {})
+            .await
+            .map_err(|err| LightningError::GetInfoError(err.to_string()))?
+            .into_inner();
+
+        Ok(info.block_height)
+    }
+
+    async fn list_pending_channels(&self) -> Result<Vec<PendingChannel>, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+        let response = lightning_stub
+            .pending_channels(PendingChannelsRequest::default())
+            .await
+            .map_err(|err| LightningError::ChannelError(err.to_string()))?
+            .into_inner();
+
+        let pending = |kind, channel: Option<LndPendingChannel>, txid: Option<String>| {
+            channel.map(|channel| {
+                let txid = match kind {
+                    PendingChannelKind::Opening => {
+                        channel.channel_point.split(':').next().map(str::to_string)
+                    }
+                    PendingChannelKind::Closing => txid.filter(|txid| !txid.is_empty()),
+                };
+                PendingChannel {
+                    kind,
+                    channel_point: channel.channel_point,
+                    remote_pubkey: channel.remote_node_pub,
+                    capacity_sat: channel.capacity.max(0) as u64,
+                    txid,
+                    confirmations: None,
+                    confirmations_remaining: None,
+                }
+            })
+        };
+
+        let opening = response
+            .pending_open_channels
+            .into_iter()
+            .filter_map(|open| pending(PendingChannelKind::Opening, open.channel, None));
+        let waiting_close = response
+            .waiting_close_channels
+            .into_iter()
+            .filter_map(|close| {
+                pending(
+                    PendingChannelKind::Closing,
+                    close.channel,
+                    Some(close.closing_txid),
+                )
+            });
+        let force_closing = response
+            .pending_force_closing_channels
+            .into_iter()
+            .filter_map(|close| {
+                pending(
+                    PendingChannelKind::Closing,
+                    close.channel,
+                    Some(close.closing_txid),
+                )
+            });
+
+        Ok(opening.chain(waiting_close).chain(force_closing).collect())
+    }
+
+    async fn get_transaction_fee(&self, txid: &Txid) -> Result<Option<u64>, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+        let transactions = lightning_stub
+            .get_transactions(GetTransactionsRequest {
+                end_height: -1,
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| LightningError::ChannelError(err.to_string()))?
+            .into_inner()
+            .transactions;
+
+        let txid = txid.to_string();
+        Ok(transactions
+            .into_iter()
+            .find(|transaction| transaction.tx_hash == txid)
+            .map(|transaction| transaction.total_fees.max(0) as u64))
+    }
+
     async fn get_node_info(&self, node_id: &PublicKey) -> Result<NodeInfo, LightningError> {
         let mut client = self.client.lock().await;
         let node_info = client
@@ -866,16 +1486,13 @@ impl LightningClient for LndNode {
             .channels
             .into_iter()
             .map(|channel| {
-                let channel_state = if channel.active {
-                    ChannelState::Active
-                } else {
-                    ChannelState::Disabled
-                };
+                let channel_state = lnd_channel_state(channel.active);
 
                 let last_update = last_updates.get(&channel.chan_id).copied();
 
                 ChannelSummary {
                     chan_id: ShortChannelID(channel.chan_id),
+                    remote_pubkey: channel.remote_pubkey,
                     alias: None,
                     channel_state,
                     private: channel.private,
@@ -884,6 +1501,7 @@ impl LightningClient for LndNode {
                     capacity: channel.capacity.try_into().unwrap_or(0),
                     last_update,
                     uptime: Some(channel.uptime as u64),
+                    notes: Vec::new(),
                 }
             })
             .collect();
@@ -1013,6 +1631,12 @@ impl LightningClient for LndNode {
                     total_satoshis_received: Some(channel.total_satoshis_received as u64),
                     channel_age_blocks: channel.lifetime.try_into().ok(),
                     opening_cost_sat: None,
+                    funding_fee_sat: None,
+                    closing_txid: None,
+                    closing_fee_sat: None,
+                    rebalance_cost_sat: None,
+                    peer_metadata: None,
+                    notes: Vec::new(),
                     initiator: Some(channel.initiator),
                     txid: Some(channel_point.txid),
                     vout: Some(channel_point.vout),
@@ -1020,9 +1644,7 @@ impl LightningClient for LndNode {
                     node2_policy,
                 })
             }
-            None => Err(LightningError::ChannelError(
-                "Channel not found".to_string(),
-            )),
+            None => self.get_closed_channel_info(channel_id).await,
         }
     }
 
@@ -1102,15 +1724,18 @@ impl LightningClient for LndNode {
             .filter_map(|payment| {
                 let status =
                     PaymentStatus::try_from(payment.status).unwrap_or(PaymentStatus::Unknown);
-                let state = match status {
-                    PaymentStatus::Unknown | PaymentStatus::InFlight => PaymentState::Inflight,
-                    PaymentStatus::Succeeded => PaymentState::Settled,
-                    PaymentStatus::Failed => PaymentState::Failed,
-                };
+                let state = lnd_payment_state(status);
 
                 let amount_sat: u64 = payment.value_sat.try_into().unwrap_or(0);
                 let amount_usd = PriceConverter::sats_to_usd_with_price(amount_sat, btc_price);
 
+                let channel_id = payment
+                    .htlcs
+                    .iter()
+                    .rfind(|htlc| htlc.status() == HtlcStatus::Succeeded)
+                    .and_then(|htlc| htlc.route.as_ref()?.hops.first())
+                    .map(|hop| ShortChannelID(hop.chan_id));
+
                 // Only set completed_at if payment succeeded
                 let completed_at = match state {
                     PaymentState::Settled => payment
@@ -1140,6 +1765,8 @@ impl LightningClient for LndNode {
                     invoice: Some(payment.payment_request),
                     payment_hash: payment.payment_hash,
                     completed_at,
+                    channel_id,
+                    keysend_message: None,
                 })
             })
             .collect();
@@ -1153,13 +1780,9 @@ impl LightningClient for LndNode {
                 !invoice.htlcs.is_empty()
             })
             .filter_map(|invoice| {
-                let state = match invoice.state {
-                    0 => PaymentState::Inflight,
-                    1 => PaymentState::Settled,
-                    2 => PaymentState::Failed,
-                    3 => PaymentState::Inflight,
-                    _ => return None,
-                };
+                let state = InvoiceState::try_from(invoice.state)
+                    .ok()
+                    .map(lnd_invoice_payment_state)?;
 
                 // Use amt_paid_sat if available, fallback to invoice.value for failed attempts
                 let amount_sat = if invoice.amt_paid_sat > 0 {
@@ -1180,16 +1803,27 @@ impl LightningClient for LndNode {
                     _ => None,
                 };
 
+                let keysend_message = lnd_keysend_message(&invoice);
+
                 Some(PaymentSummary {
                     state,
-                    payment_type: PaymentType::Incoming,
+                    payment_type: if invoice.is_keysend {
+                        PaymentType::Keysend
+                    } else {
+                        PaymentType::Incoming
+                    },
                     amount_sat,
                     amount_usd,
                     routing_fee: None,
                     creation_time,
+                    channel_id: invoice
+                        .htlcs
+                        .first()
+                        .map(|htlc| ShortChannelID(htlc.chan_id)),
                     invoice: Some(invoice.payment_request),
                     payment_hash: hex::encode(invoice.r_hash),
                     completed_at,
+                    keysend_message,
                 })
             })
             .collect();
@@ -1216,9 +1850,10 @@ impl LightningClient for LndNode {
 
     async fn stream_events(
         &mut self,
-    ) -> Result<Pin<Box<dyn Stream<Item = NodeSpecificEvent> + Send>>, LightningError> {
+    ) -> Result<Pin<Box<dyn Stream<Item = LightningEvent> + Send>>, LightningError> {
         let channel_events_stream = self.stream_channel_events().await?;
         let invoice_events_stream = self.stream_invoice_events().await?;
+        let htlc_events_stream = self.stream_htlc_events().await?;
 
         let event_stream = stream! {
             let channel_events_filtered = channel_events_stream.filter_map(|result| {
@@ -1229,7 +1864,7 @@ impl LightningClient for LndNode {
                                 if let Some(event_channel) = update.channel {
                                     match event_channel {
                                         EventChannel::OpenChannel(chan) => {
-                                            Some(NodeSpecificEvent::LND(LNDEvent::ChannelOpened {
+                                            Some(LightningEvent::ChannelOpened {
                                                 active: chan.active,
                                                 remote_pubkey: chan.remote_pubkey,
                                                 channel_point: chan.channel_point,
@@ -1239,7 +1874,7 @@ impl LightningClient for LndNode {
                                                 remote_balance: chan.remote_balance,
                                                 total_satoshis_sent: chan.total_satoshis_sent,
                                                 total_satoshis_received: chan.total_satoshis_received,
-                                            }))
+                                            })
                                         }
                                         _ => {
                                             eprintln!("Unexpected channel variant for OpenChannel event");
@@ -1254,7 +1889,7 @@ impl LightningClient for LndNode {
                                 if let Some(event_channel) = update.channel {
                                     match event_channel {
                                         EventChannel::ClosedChannel(chan_close_sum) => {
-                                            Some(NodeSpecificEvent::LND(LNDEvent::ChannelClosed {
+                                            Some(LightningEvent::ChannelClosed {
                                                 channel_point: chan_close_sum.channel_point,
                                                 chan_id:  chan_close_sum.chan_id,
                                                 chain_hash:  chan_close_sum.chain_hash,
@@ -1267,7 +1902,7 @@ impl LightningClient for LndNode {
                                                 close_type:  chan_close_sum.close_type,
                                                 open_initiator:  chan_close_sum.open_initiator,
                                                 close_initiator:  chan_close_sum.close_initiator,
-                                            }))
+                                            })
                                         }
                                         _ => {
                                             eprintln!("Unexpected channel variant for ClosedChannel event");
@@ -1289,67 +1924,80 @@ impl LightningClient for LndNode {
                 futures::future::ready(event_opt)
             });
 
-            let invoice_events_filtered = invoice_events_stream.filter_map(|result| {
-                let event_opt = match result {
+            let invoice_events_filtered = invoice_events_stream.flat_map(|result| {
+                let events = match result {
                     Ok(invoice) => {
-                        match invoice.state() {
-                            InvoiceState::Open => {
-                                Some(NodeSpecificEvent::LND(LNDEvent::InvoiceCreated {
-                                        preimage: invoice.r_preimage,
-                                        hash: invoice.r_hash,
-                                        value_msat: invoice.value_msat,
-                                        state: invoice.state,
-                                        memo: invoice.memo,
-                                        creation_date: invoice.creation_date,
-                                        payment_request: invoice.payment_request,
-                                }))
-                            },
-                            InvoiceState::Settled => {
-                                  Some(NodeSpecificEvent::LND(LNDEvent::InvoiceSettled {
-                                        preimage: invoice.r_preimage,
-                                        hash: invoice.r_hash,
-                                        value_msat: invoice.value_msat,
-                                        state: invoice.state,
-                                        memo: invoice.memo,
-                                        creation_date: invoice.creation_date,
-                                        payment_request: invoice.payment_request,
-                                }))
-                            },
-                            InvoiceState::Canceled => {
-                                  Some(NodeSpecificEvent::LND(LNDEvent::InvoiceCancelled {
-                                        preimage: invoice.r_preimage,
-                                        hash: invoice.r_hash,
-                                        value_msat: invoice.value_msat,
-                                        state: invoice.state,
-                                        memo: invoice.memo,
-                                        creation_date: invoice.creation_date,
-                                        payment_request: invoice.payment_request,
-                                }))
-                            },
-                            InvoiceState::Accepted => {
-                                  Some(NodeSpecificEvent::LND(LNDEvent::InvoiceAccepted {
-                                        preimage: invoice.r_preimage,
-                                        hash: invoice.r_hash,
-                                        value_msat: invoice.value_msat,
-                                        state: invoice.state,
-                                        memo: invoice.memo,
-                                        creation_date: invoice.creation_date,
-                                        payment_request: invoice.payment_request,
-                                }))
+                        let state = invoice.state();
+                        // A settled invoice credits each channel one of its HTLCs came in on.
+                        let mut events: Vec<LightningEvent> = invoice
+                            .htlcs
+                            .iter()
+                            .filter(|htlc| {
+                                state == InvoiceState::Settled
+                                    && htlc.state == InvoiceHtlcState::Settled as i32
+                            })
+                            .map(|htlc| {
+                                htlc_balance_change(
+                                    htlc.chan_id,
+                                    htlc.amt_msat as i64,
+                                    BalanceChangeCause::Invoice,
+                                )
+                            })
+                            .collect();
+                        events.extend(lnd_payment_mismatch(&invoice));
+                        // Keysend invoices are made up by the node on receipt, so
+                        // only the settlement is worth reporting.
+                        if invoice.is_keysend {
+                            if state == InvoiceState::Settled {
+                                events.push(LightningEvent::KeysendReceived {
+                                    payment_hash: hex::encode(&invoice.r_hash),
+                                    amount_msat: lnd_invoice_paid_msat(&invoice),
+                                    message: lnd_keysend_message(&invoice),
+                                });
                             }
+                            return futures::stream::iter(events);
                         }
+                        let invoice_event = InvoiceEvent {
+                            preimage: invoice.r_preimage,
+                            hash: invoice.r_hash,
+                            value_msat: invoice.value_msat,
+                            state: invoice.state,
+                            memo: invoice.memo,
+                            creation_date: invoice.creation_date,
+                            payment_request: invoice.payment_request,
+                        };
+                        events.push(match state {
+                            InvoiceState::Open => LightningEvent::InvoiceCreated(invoice_event),
+                            InvoiceState::Settled => LightningEvent::InvoiceSettled(invoice_event),
+                            InvoiceState::Canceled => LightningEvent::InvoiceCancelled(invoice_event),
+                            InvoiceState::Accepted => LightningEvent::InvoiceAccepted(invoice_event),
+                        });
+                        events
                     },
                     Err(e) => {
                         eprintln!("Error subscribing to LND channel events: {e:?}");
-                        None
+                        Vec::new()
                     }
                 };
-                futures::future::ready(event_opt)
+                futures::stream::iter(events)
+            });
+
+            let mut htlc_tracker = LndHtlcTracker::default();
+            let htlc_events_filtered = htlc_events_stream.flat_map(move |result| {
+                let events = match result {
+                    Ok(event) => htlc_tracker.handle(event),
+                    Err(e) => {
+                        eprintln!("Error receiving LND HTLC event: {e:?}");
+                        Vec::new()
+                    }
+                };
+                futures::stream::iter(events)
             });
 
             let mut merged_stream = SelectAll::new();
             merged_stream.push(channel_events_filtered.boxed());
             merged_stream.push(invoice_events_filtered.boxed());
+            merged_stream.push(htlc_events_filtered.boxed());
 
             while let Some(event) = merged_stream.next().await {
                 yield event;
@@ -1377,14 +2025,11 @@ impl LightningClient for LndNode {
             .invoices
             .into_iter()
             .map(|invoice| {
-                // Map tonic's InvoiceState to your InvoiceStatus enum
-                let state =
-                    match InvoiceState::try_from(invoice.state).unwrap_or(InvoiceState::Open) {
-                        InvoiceState::Open => InvoiceStatus::Open,
-                        InvoiceState::Settled => InvoiceStatus::Settled,
-                        InvoiceState::Canceled => InvoiceStatus::Failed,
-                        InvoiceState::Accepted => InvoiceStatus::Open,
-                    };
+                let state = lnd_invoice_status(
+                    InvoiceState::try_from(invoice.state).unwrap_or(InvoiceState::Open),
+                );
+                let amount_paid_msat = lnd_invoice_paid_msat(&invoice);
+                let keysend_message = lnd_keysend_message(&invoice);
                 let htlcs = Some(
                     invoice
                         .htlcs
@@ -1431,7 +2076,13 @@ impl LightningClient for LndNode {
                     payment_request: invoice.payment_request,
                     expiry: Some(invoice.expiry as u64),
                     state,
+                    amount_paid_msat: Some(amount_paid_msat),
+                    payment_mismatch: PaymentMismatch::between(
+                        invoice.value_msat as u64,
+                        amount_paid_msat,
+                    ),
                     is_keysend: Some(invoice.is_keysend),
+                    keysend_message,
                     is_amp: Some(invoice.is_amp),
                     payment_addr: Some(hex::encode(invoice.payment_addr))
                         .filter(|addr_hex| !addr_hex.is_empty()),
@@ -1461,12 +2112,11 @@ impl LightningClient for LndNode {
             .map_err(|e| LightningError::InvoiceError(e.to_string()))?
             .into_inner();
 
-        let state = match InvoiceState::try_from(response.state).unwrap_or(InvoiceState::Open) {
-            InvoiceState::Open => InvoiceStatus::Open,
-            InvoiceState::Settled => InvoiceStatus::Settled,
-            InvoiceState::Canceled => InvoiceStatus::Failed,
-            InvoiceState::Accepted => InvoiceStatus::Open,
-        };
+        let state = lnd_invoice_status(
+            InvoiceState::try_from(response.state).unwrap_or(InvoiceState::Open),
+        );
+        let amount_paid_msat = lnd_invoice_paid_msat(&response);
+        let keysend_message = lnd_keysend_message(&response);
 
         Ok(CustomInvoice {
             memo: response.memo,
@@ -1481,7 +2131,13 @@ impl LightningClient for LndNode {
             payment_request: response.payment_request,
             expiry: Some(response.expiry as u64),
             state,
+            amount_paid_msat: Some(amount_paid_msat),
+            payment_mismatch: PaymentMismatch::between(
+                response.value_msat as u64,
+                amount_paid_msat,
+            ),
             is_keysend: Some(response.is_keysend),
+            keysend_message,
             is_amp: Some(response.is_amp),
             payment_addr: Some(hex::encode(response.payment_addr))
                 .filter(|addr_hex| !addr_hex.is_empty()),
@@ -1489,30 +2145,900 @@ impl LightningClient for LndNode {
             features: None,
         })
     }
-}
 
-#[async_trait]
-impl LightningClient for ClnNode {
-    fn get_info(&self) -> &NodeInfo {
-        &self.info
-    }
+    async fn sign_message(&self, message: &str) -> Result<String, LightningError> {
+        let mut lightning = {
+            let mut client = self.client.lock().await;
+            client.lightning().clone()
+        };
 
-    async fn get_network(&self) -> Result<Network, LightningError> {
-        let mut client = self.client.lock().await;
-        let info = client
-            .getinfo(GetinfoRequest {})
+        let response = lightning
+            .sign_message(SignMessageRequest {
+                msg: message.as_bytes().to_vec(),
+                ..Default::default()
+            })
             .await
-            .map_err(|err| LightningError::GetInfoError(err.to_string()))?
+            .map_err(|err| LightningError::SigningError(format!("LND sign_message error: {err}")))?
             .into_inner();
 
-        Ok(Network::from_core_arg(&info.network)
-            .map_err(|err| LightningError::ValidationError(err.to_string()))?)
+        Ok(response.signature)
     }
 
-    async fn get_node_info(&self, node_id: &PublicKey) -> Result<NodeInfo, LightningError> {
-        let mut client = self.client.lock().await;
-        let mut nodes: Vec<cln_grpc::pb::ListnodesNodes> = client
-            .list_nodes(ListnodesRequest {
+    async fn verify_message(
+        &self,
+        message: &str,
+        signature: &str,
+    ) -> Result<MessageVerification, LightningError> {
+        let mut lightning = {
+            let mut client = self.client.lock().await;
+            client.lightning().clone()
+        };
+
+        let response = lightning
+            .verify_message(VerifyMessageRequest {
+                msg: message.as_bytes().to_vec(),
+                signature: signature.to_string(),
+            })
+            .await
+            .map_err(|err| {
+                LightningError::SigningError(format!("LND verify_message error: {err}"))
+            })?
+            .into_inner();
+
+        Ok(MessageVerification {
+            valid: response.valid,
+            pubkey: response.pubkey,
+        })
+    }
+
+    async fn track_payment(
+        &self,
+        payment_hash: &PaymentHash,
+    ) -> Result<PaymentProgressStream, LightningError> {
+        let mut router = {
+            let mut client = self.client.lock().await;
+            client.router().clone()
+        };
+
+        let mut updates = router
+            .track_payment_v2(TrackPaymentRequest {
+                payment_hash: payment_hash.0.to_vec(),
+                no_inflight_updates: false,
+            })
+            .await
+            .map_err(|err| match err.code() {
+                tonic_lnd::tonic::Code::NotFound => LightningError::NotFound(format!(
+                    "Payment {} not found",
+                    hex::encode(payment_hash.0)
+                )),
+                _ => LightningError::PaymentError(format!("LND track_payment_v2 error: {err}")),
+            })?
+            .into_inner();
+
+        let progress_stream = stream! {
+            while let Some(result) = updates.next().await {
+                match result {
+                    Ok(payment) => {
+                        let progress = lnd_payment_progress(payment);
+                        let finished = progress.state != PaymentState::Inflight;
+                        yield Ok(progress);
+                        if finished {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        yield Err(LightningError::StreamingError(err.to_string()));
+                        break;
+                    }
+                }
+            }
+        };
+
+        Ok(Box::pin(progress_stream))
+    }
+
+    async fn describe_graph(&self) -> Result<NetworkGraph, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+
+        let graph = lightning_stub
+            .describe_graph(ChannelGraphRequest {
+                include_unannounced: false,
+            })
+            .await
+            .map_err(|err| LightningError::GetGraphError(err.to_string()))?
+            .into_inner();
+
+        Ok(NetworkGraph {
+            nodes: graph
+                .nodes
+                .into_iter()
+                .map(|node| GraphNode {
+                    pubkey: node.pub_key,
+                    alias: node.alias,
+                    last_update: Some(node.last_update as u64).filter(|t| *t > 0),
+                    addresses: node.addresses.into_iter().map(|a| a.addr).collect(),
+                    features: sorted_keys(node.features),
+                })
+                .collect(),
+            channels: graph
+                .edges
+                .into_iter()
+                .filter_map(lnd_graph_channel)
+                .collect(),
+        })
+    }
+
+    async fn subscribe_graph(&self) -> Result<GraphUpdateStream, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+
+        let mut updates = lightning_stub
+            .subscribe_channel_graph(GraphTopologySubscription {})
+            .await
+            .map_err(|err| LightningError::StreamingError(format!("LND graph error: {err}")))?
+            .into_inner();
+
+        let update_stream = stream! {
+            loop {
+                let topology = match updates.message().await {
+                    Ok(Some(topology)) => topology,
+                    Ok(None) => break,
+                    Err(err) => {
+                        yield Err(LightningError::StreamingError(err.to_string()));
+                        break;
+                    }
+                };
+
+                for node in topology.node_updates {
+                    yield Ok(GraphUpdate::Node(GraphNode {
+                        pubkey: node.identity_key,
+                        alias: node.alias,
+                        last_update: Some(chrono::Utc::now().timestamp() as u64),
+                        addresses: node.node_addresses.into_iter().map(|a| a.addr).collect(),
+                        features: sorted_keys(node.features),
+                    }));
+                }
+
+                for edge in topology.channel_updates {
+                    let Ok(advertising) = PublicKey::from_str(&edge.advertising_node) else {
+                        continue;
+                    };
+                    let (node1_pub, node2_pub) =
+                        ordered_pair(edge.advertising_node, edge.connecting_node);
+                    yield Ok(GraphUpdate::Channel(GraphChannel {
+                        chan_id: edge.chan_id,
+                        node1_pub,
+                        node2_pub,
+                        capacity_sat: edge.capacity as u64,
+                        policies: edge
+                            .routing_policy
+                            .iter()
+                            .map(|policy| lnd_node_policy(advertising, policy))
+                            .collect(),
+                    }));
+                }
+
+                for closed in topology.closed_chans {
+                    yield Ok(GraphUpdate::ChannelClosed(closed.chan_id));
+                }
+            }
+        };
+
+        Ok(Box::pin(update_stream))
+    }
+
+    async fn create_invoice(
+        &self,
+        amount_msat: u64,
+        memo: &str,
+        expiry_secs: u64,
+    ) -> Result<String, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+
+        let response = lightning_stub
+            .add_invoice(Invoice {
+                memo: memo.to_string(),
+                value_msat: amount_msat as i64,
+                expiry: expiry_secs as i64,
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| LightningError::InvoiceError(format!("LND add_invoice error: {err}")))?
+            .into_inner();
+
+        Ok(response.payment_request)
+    }
+
+    async fn send_payment(
+        &self,
+        bolt11: &str,
+        max_fee_sat: u64,
+        outgoing_chan_id: Option<u64>,
+    ) -> Result<(), LightningError> {
+        let mut router = {
+            let mut client = self.client.lock().await;
+            client.router().clone()
+        };
+
+        let mut updates = router
+            .send_payment_v2(SendPaymentRequest {
+                payment_request: bolt11.to_string(),
+                fee_limit_sat: max_fee_sat as i64,
+                timeout_seconds: LND_PAYMENT_TIMEOUT_SECS,
+                outgoing_chan_ids: outgoing_chan_id.into_iter().collect(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| {
+                LightningError::PaymentError(format!("LND send_payment_v2 error: {err}"))
+            })?
+            .into_inner();
+
+        // LND keeps paying after the stream is dropped, so only the first
+        // update is needed to know the payment started.
+        match updates.message().await {
+            Ok(Some(payment)) if payment.status == PaymentStatus::Failed as i32 => {
+                Err(LightningError::PaymentError(format!(
+                    "Payment failed: {:?}",
+                    payment.failure_reason()
+                )))
+            }
+            Ok(_) => Ok(()),
+            Err(err) => Err(LightningError::PaymentError(err.to_string())),
+        }
+    }
+
+    async fn list_forwards(&self, since: u64) -> Result<Vec<Forward>, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+        let end_time = chrono::Utc::now().timestamp() as u64;
+
+        let mut forwards = Vec::new();
+        let mut index_offset = 0;
+        loop {
+            let response = lightning_stub
+                .forwarding_history(ForwardingHistoryRequest {
+                    start_time: since,
+                    end_time,
+                    index_offset,
+                    num_max_events: LND_FORWARDS_PAGE_SIZE,
+                    ..Default::default()
+                })
+                .await
+                .map_err(|err| {
+                    LightningError::ChannelError(format!("LND forwarding_history error: {err}"))
+                })?
+                .into_inner();
+
+            let page_len = response.forwarding_events.len();
+            forwards.extend(response.forwarding_events.into_iter().map(|event| Forward {
+                timestamp: event.timestamp_ns / 1_000_000_000,
+                received_at: None,
+                chan_id_in: ShortChannelID(event.chan_id_in),
+                chan_id_out: ShortChannelID(event.chan_id_out),
+                incoming_htlc_id: None,
+                outgoing_htlc_id: None,
+                amt_in_msat: event.amt_in_msat,
+                amt_out_msat: event.amt_out_msat,
+                fee_msat: event.fee_msat,
+            }));
+
+            if page_len < LND_FORWARDS_PAGE_SIZE as usize {
+                break;
+            }
+            index_offset = response.last_offset_index;
+        }
+
+        Ok(forwards)
+    }
+
+    async fn list_payment_latencies(
+        &self,
+        since: u64,
+    ) -> Result<Vec<PaymentLatency>, LightningError> {
+        let mut lightning_stub = self.get_lightning_stub().await;
+        let since_ns = since as i64 * 1_000_000_000;
+
+        // Walk back from the newest payment until we pass `since`.
+        let mut latencies = Vec::new();
+        let mut index_offset = 0;
+        loop {
+            let response = lightning_stub
+                .list_payments(ListPaymentsRequest {
+                    index_offset,
+                    max_payments: LND_PAYMENTS_PAGE_SIZE,
+                    reversed: true,
+                    ..Default::default()
+                })
+                .await
+                .map_err(|err| {
+                    LightningError::PaymentError(format!("LND list_payments error: {err}"))
+                })?
+                .into_inner();
+
+            let page_len = response.payments.len();
+            let reached_since = response
+                .payments
+                .iter()
+                .any(|payment| payment.creation_time_ns < since_ns);
+            latencies.extend(
+                response
+                    .payments
+                    .into_iter()
+                    .filter_map(lnd_payment_latency)
+                    .filter(|latency| latency.settled_at >= since),
+            );
+
+            if reached_since || page_len < LND_PAYMENTS_PAGE_SIZE as usize {
+                break;
+            }
+            index_offset = response.first_index_offset;
+        }
+
+        Ok(latencies)
+    }
+
+    async fn rebalance(
+        &self,
+        outgoing_chan_id: u64,
+        last_hop_pubkey: &PublicKey,
+        amount_msat: u64,
+        max_fee_msat: u64,
+    ) -> Result<RebalanceOutcome, LightningError> {
+        let payment_request = self
+            .create_invoice(
+                amount_msat,
+                "nodegaze rebalance",
+                REBALANCE_INVOICE_EXPIRY_SECS,
+            )
+            .await?;
+
+        let mut router = {
+            let mut client = self.client.lock().await;
+            client.router().clone()
+        };
+
+        let mut updates = router
+            .send_payment_v2(SendPaymentRequest {
+                payment_request,
+                fee_limit_msat: max_fee_msat as i64,
+                timeout_seconds: LND_PAYMENT_TIMEOUT_SECS,
+                outgoing_chan_ids: vec![outgoing_chan_id],
+                last_hop_pubkey: last_hop_pubkey.serialize().to_vec(),
+                allow_self_payment: true,
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| {
+                LightningError::PaymentError(format!("LND send_payment_v2 error: {err}"))
+            })?
+            .into_inner();
+
+        while let Some(payment) = updates
+            .message()
+            .await
+            .map_err(|err| LightningError::PaymentError(err.to_string()))?
+        {
+            let succeeded = match payment.status() {
+                PaymentStatus::Succeeded => true,
+                PaymentStatus::Failed => false,
+                _ => continue,
+            };
+
+            let attempt = payment
+                .htlcs
+                .iter()
+                .rfind(|htlc| !succeeded || htlc.status() == HtlcStatus::Succeeded);
+            let route = attempt
+                .and_then(|htlc| htlc.route.as_ref())
+                .map(|route| {
+                    route
+                        .hops
+                        .iter()
+                        .map(|hop| Hop {
+                            pubkey: PublicKey::from_str(&hop.pub_key).unwrap_or(self.info.pubkey),
+                            chan_id: ShortChannelID(hop.chan_id),
+                            amount_to_forward: (hop.amt_to_forward_msat / 1000) as u64,
+                            fee: Some((hop.fee_msat / 1000) as u64),
+                            expiry: Some(hop.expiry.into()),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            return Ok(RebalanceOutcome {
+                succeeded,
+                fee_msat: if succeeded {
+                    payment.fee_msat as u64
+                } else {
+                    0
+                },
+                route,
+                failure_reason: (!succeeded).then(|| format!("{:?}", payment.failure_reason())),
+            });
+        }
+
+        Err(LightningError::PaymentError(
+            "Payment updates ended before the rebalance resolved".to_string(),
+        ))
+    }
+
+    async fn probe(
+        &self,
+        destination: &PublicKey,
+        amount_msat: u64,
+        outgoing_chan_id: Option<u64>,
+        max_fee_msat: u64,
+    ) -> Result<ProbeOutcome, LightningError> {
+        let mut payment_hash = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut payment_hash);
+
+        let mut router = {
+            let mut client = self.client.lock().await;
+            client.router().clone()
+        };
+
+        let started = std::time::Instant::now();
+        let mut updates = router
+            .send_payment_v2(SendPaymentRequest {
+                dest: destination.serialize().to_vec(),
+                amt_msat: amount_msat as i64,
+                payment_hash: payment_hash.to_vec(),
+                fee_limit_msat: max_fee_msat as i64,
+                timeout_seconds: LND_PROBE_TIMEOUT_SECS,
+                outgoing_chan_ids: outgoing_chan_id.into_iter().collect(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| {
+                LightningError::PaymentError(format!("LND send_payment_v2 error: {err}"))
+            })?
+            .into_inner();
+
+        while let Some(payment) = updates
+            .message()
+            .await
+            .map_err(|err| LightningError::PaymentError(err.to_string()))?
+        {
+            if payment.status() != PaymentStatus::Failed {
+                continue;
+            }
+            let reached = payment.failure_reason()
+                == PaymentFailureReason::FailureReasonIncorrectPaymentDetails;
+            return Ok(ProbeOutcome {
+                reached,
+                latency_ms: started.elapsed().as_millis() as u64,
+                failure_reason: (!reached).then(|| format!("{:?}", payment.failure_reason())),
+            });
+        }
+
+        Err(LightningError::PaymentError(
+            "Payment updates ended before the probe resolved".to_string(),
+        ))
+    }
+
+    async fn update_channel_fees(
+        &self,
+        channel: &ChannelDetails,
+        base_fee_msat: u64,
+        fee_rate_ppm: u32,
+    ) -> Result<(), LightningError> {
+        let (Some(txid), Some(vout)) = (channel.txid, channel.vout) else {
+            return Err(LightningError::ChannelError(format!(
+                "Channel {} has no funding outpoint",
+                channel.channel_id
+            )));
+        };
+
+        // LND requires the CLTV delta on every update, so keep the current one.
+        let own_pubkey = self.get_info().pubkey;
+        let time_lock_delta = [&channel.node1_policy, &channel.node2_policy]
+            .into_iter()
+            .flatten()
+            .find(|policy| policy.pubkey == own_pubkey)
+            .map_or(LND_DEFAULT_TIME_LOCK_DELTA, |policy| {
+                policy.time_lock_delta as u32
+            });
+
+        let mut lightning_stub = self.get_lightning_stub().await;
+
+        let response = lightning_stub
+            .update_channel_policy(PolicyUpdateRequest {
+                scope: Some(policy_update_request::Scope::ChanPoint(ChannelPoint {
+                    funding_txid: Some(channel_point::FundingTxid::FundingTxidStr(
+                        txid.to_string(),
+                    )),
+                    output_index: vout,
+                })),
+                base_fee_msat: base_fee_msat as i64,
+                fee_rate_ppm,
+                time_lock_delta,
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| {
+                LightningError::ChannelError(format!("LND update_channel_policy error: {err}"))
+            })?
+            .into_inner();
+
+        match response.failed_updates.first() {
+            Some(failed) => Err(LightningError::ChannelError(format!(
+                "LND rejected the policy update: {}",
+                failed.update_error
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Converts the policy one end of an LND channel edge advertises.
+fn lnd_node_policy(pubkey: PublicKey, policy: &RoutingPolicy) -> NodePolicy {
+    NodePolicy {
+        pubkey,
+        fee_base_msat: policy.fee_base_msat as u64,
+        fee_rate_milli_msat: policy.fee_rate_milli_msat as u64,
+        min_htlc_msat: policy.min_htlc as u64,
+        max_htlc_msat: Some(policy.max_htlc_msat).filter(|max| *max > 0),
+        time_lock_delta: policy.time_lock_delta as u16,
+        disabled: policy.disabled,
+        last_update: Some(policy.last_update as u64).filter(|t| *t > 0),
+    }
+}
+
+/// Converts an LND graph edge, skipping edges with malformed node keys.
+fn lnd_graph_channel(edge: ChannelEdge) -> Option<GraphChannel> {
+    let node1 = PublicKey::from_str(&edge.node1_pub).ok()?;
+    let node2 = PublicKey::from_str(&edge.node2_pub).ok()?;
+    let policies = [(node1, &edge.node1_policy), (node2, &edge.node2_policy)]
+        .into_iter()
+        .filter_map(|(pubkey, policy)| policy.as_ref().map(|p| lnd_node_policy(pubkey, p)))
+        .collect();
+
+    Some(GraphChannel {
+        chan_id: edge.channel_id,
+        node1_pub: edge.node1_pub,
+        node2_pub: edge.node2_pub,
+        capacity_sat: edge.capacity as u64,
+        policies,
+    })
+}
+
+/// Orders two node keys as BOLT 7 does for a channel's node_1 and node_2.
+fn ordered_pair(a: String, b: String) -> (String, String) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+/// Converts an LND payment update into a progress snapshot.
+fn lnd_payment_progress(payment: tonic_lnd::lnrpc::Payment) -> PaymentProgress {
+    let state = lnd_payment_state(
+        PaymentStatus::try_from(payment.status).unwrap_or(PaymentStatus::Unknown),
+    );
+    let failure_reason =
+        (state == PaymentState::Failed).then(|| format!("{:?}", payment.failure_reason()));
+
+    let attempts = payment
+        .htlcs
+        .iter()
+        .map(|htlc| {
+            let (amount_msat, fee_msat, hop_count) = htlc
+                .route
+                .as_ref()
+                .map(|route| {
+                    (
+                        route.total_amt_msat.max(0) as u64,
+                        route.total_fees_msat.max(0) as u64,
+                        route.hops.len(),
+                    )
+                })
+                .unwrap_or_default();
+
+            PaymentAttempt {
+                attempt_id: htlc.attempt_id,
+                state: lnd_htlc_state(htlc.status()),
+                amount_msat,
+                fee_msat,
+                hop_count,
+                attempt_time: (htlc.attempt_time_ns > 0)
+                    .then_some(htlc.attempt_time_ns as u64 / 1_000_000_000),
+                resolve_time: (htlc.resolve_time_ns > 0)
+                    .then_some(htlc.resolve_time_ns as u64 / 1_000_000_000),
+                failure_reason: htlc
+                    .failure
+                    .as_ref()
+                    .map(|failure| format!("{:?}", failure.code())),
+            }
+        })
+        .collect();
+
+    PaymentProgress {
+        payment_hash: payment.payment_hash,
+        state,
+        amount_msat: payment.value_msat.max(0) as u64,
+        fee_msat: payment.fee_msat.max(0) as u64,
+        failure_reason,
+        attempts,
+    }
+}
+
+/// A forward or payment HTLC LND offered out that hasn't resolved yet.
+struct OpenCircuit {
+    outgoing_htlc_id: u64,
+    amt_in_msat: u64,
+    amt_out_msat: u64,
+    forwarded_at_ns: u64,
+}
+
+/// Remembers `circuit` under `key`, evicting the oldest circuit when full.
+fn open_circuit(
+    circuits: &mut HashMap<(u64, u64), OpenCircuit>,
+    key: (u64, u64),
+    circuit: OpenCircuit,
+) {
+    if circuits.len() >= LND_MAX_OPEN_CIRCUITS {
+        let oldest = circuits
+            .iter()
+            .min_by_key(|(_, circuit)| circuit.forwarded_at_ns)
+            .map(|(key, _)| *key);
+        if let Some(oldest) = oldest {
+            circuits.remove(&oldest);
+        }
+    }
+    circuits.insert(key, circuit);
+}
+
+/// The balance change of one settled HTLC.
+fn htlc_balance_change(chan_id: u64, delta_msat: i64, cause: BalanceChangeCause) -> LightningEvent {
+    LightningEvent::ChannelBalanceChanged {
+        chan_id,
+        delta_msat,
+        causes: vec![cause],
+        htlcs: 1,
+    }
+}
+
+/// Follows forwards and payments through LND's HTLC event stream. Only the
+/// event that offers an HTLC out carries its amounts, so each circuit is
+/// remembered until it settles or fails: forwards keyed by their incoming
+/// channel and HTLC id, payments by their outgoing ones.
+#[derive(Default)]
+struct LndHtlcTracker {
+    circuits: HashMap<(u64, u64), OpenCircuit>,
+    sends: HashMap<(u64, u64), OpenCircuit>,
+}
+
+impl LndHtlcTracker {
+    fn handle(&mut self, event: HtlcEvent) -> Vec<LightningEvent> {
+        match event.event_type() {
+            HtlcEventType::Forward if event.outgoing_channel_id != 0 => self.handle_forward(event),
+            HtlcEventType::Send => self.handle_send(event).into_iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn handle_forward(&mut self, event: HtlcEvent) -> Vec<LightningEvent> {
+        let key = (event.incoming_channel_id, event.incoming_htlc_id);
+        match &event.event {
+            Some(htlc_event::Event::ForwardEvent(forward)) => {
+                if let Some(info) = forward.info.as_ref() {
+                    open_circuit(
+                        &mut self.circuits,
+                        key,
+                        OpenCircuit {
+                            outgoing_htlc_id: event.outgoing_htlc_id,
+                            amt_in_msat: info.incoming_amt_msat,
+                            amt_out_msat: info.outgoing_amt_msat,
+                            forwarded_at_ns: event.timestamp_ns,
+                        },
+                    );
+                }
+                Vec::new()
+            }
+            Some(htlc_event::Event::SettleEvent(_)) => {
+                // Forwards offered out before the stream (re)connected are
+                // left to the history backfill.
+                let Some(circuit) = self.circuits.remove(&key) else {
+                    return Vec::new();
+                };
+                vec![
+                    htlc_balance_change(
+                        event.incoming_channel_id,
+                        circuit.amt_in_msat as i64,
+                        BalanceChangeCause::Forward,
+                    ),
+                    htlc_balance_change(
+                        event.outgoing_channel_id,
+                        -(circuit.amt_out_msat as i64),
+                        BalanceChangeCause::Forward,
+                    ),
+                    LightningEvent::ForwardSettled(Forward {
+                        timestamp: event.timestamp_ns / 1_000_000_000,
+                        received_at: Some(circuit.forwarded_at_ns / 1_000_000_000),
+                        chan_id_in: ShortChannelID(event.incoming_channel_id),
+                        chan_id_out: ShortChannelID(event.outgoing_channel_id),
+                        incoming_htlc_id: Some(event.incoming_htlc_id),
+                        outgoing_htlc_id: Some(circuit.outgoing_htlc_id),
+                        amt_in_msat: circuit.amt_in_msat,
+                        amt_out_msat: circuit.amt_out_msat,
+                        fee_msat: circuit.amt_in_msat.saturating_sub(circuit.amt_out_msat),
+                    }),
+                ]
+            }
+            _ => {
+                self.circuits.remove(&key);
+                lnd_forward_failure(event).into_iter().collect()
+            }
+        }
+    }
+
+    /// Our own payments only debit the channel the HTLC left through.
+    fn handle_send(&mut self, event: HtlcEvent) -> Option<LightningEvent> {
+        let key = (event.outgoing_channel_id, event.outgoing_htlc_id);
+        match &event.event {
+            Some(htlc_event::Event::ForwardEvent(forward)) => {
+                let info = forward.info.as_ref()?;
+                open_circuit(
+                    &mut self.sends,
+                    key,
+                    OpenCircuit {
+                        outgoing_htlc_id: event.outgoing_htlc_id,
+                        amt_in_msat: 0,
+                        amt_out_msat: info.outgoing_amt_msat,
+                        forwarded_at_ns: event.timestamp_ns,
+                    },
+                );
+                None
+            }
+            Some(htlc_event::Event::SettleEvent(_)) => {
+                let circuit = self.sends.remove(&key)?;
+                Some(htlc_balance_change(
+                    event.outgoing_channel_id,
+                    -(circuit.amt_out_msat as i64),
+                    BalanceChangeCause::Payment,
+                ))
+            }
+            _ => {
+                self.sends.remove(&key);
+                None
+            }
+        }
+    }
+}
+
+/// What was paid towards an LND invoice. LND only fills in `amt_paid_msat`
+/// once the invoice settles, so HTLCs held on an open or accepted invoice
+/// are counted too.
+fn lnd_invoice_paid_msat(invoice: &Invoice) -> u64 {
+    let held_msat: u64 = invoice
+        .htlcs
+        .iter()
+        .filter(|htlc| {
+            htlc.state == InvoiceHtlcState::Accepted as i32
+                || htlc.state == InvoiceHtlcState::Settled as i32
+        })
+        .map(|htlc| htlc.amt_msat)
+        .sum();
+    held_msat.max(invoice.amt_paid_msat.max(0) as u64)
+}
+
+/// Reads the text message out of the first HTLC of an invoice that carries one.
+fn lnd_keysend_message(invoice: &Invoice) -> Option<String> {
+    invoice
+        .htlcs
+        .iter()
+        .find_map(|htlc| htlc.custom_records.get(&KEYSEND_MESSAGE_TLV))
+        .map(|message| String::from_utf8_lossy(message).into_owned())
+}
+
+/// Reports a settled invoice or an accepted hold invoice whose HTLCs don't add
+/// up to its value.
+fn lnd_payment_mismatch(invoice: &Invoice) -> Option<LightningEvent> {
+    if !matches!(
+        invoice.state(),
+        InvoiceState::Settled | InvoiceState::Accepted
+    ) {
+        return None;
+    }
+    let value_msat = invoice.value_msat.max(0) as u64;
+    let amount_paid_msat = lnd_invoice_paid_msat(invoice);
+    let mismatch = PaymentMismatch::between(value_msat, amount_paid_msat)?;
+    Some(LightningEvent::InvoicePaymentMismatch {
+        hash: hex::encode(&invoice.r_hash),
+        value_msat,
+        amount_paid_msat,
+        mismatch,
+    })
+}
+
+/// Picks failed forwards out of LND's HTLC event stream.
+fn lnd_forward_failure(event: HtlcEvent) -> Option<LightningEvent> {
+    if event.event_type() != HtlcEventType::Forward || event.outgoing_channel_id == 0 {
+        return None;
+    }
+    let (outgoing_amt_msat, link_failure, reason) = match event.event? {
+        htlc_event::Event::ForwardFailEvent(_) => (None, false, "failed downstream".to_string()),
+        htlc_event::Event::LinkFailEvent(link_fail) => {
+            let reason = if link_fail.failure_string.is_empty() {
+                format!("{:?}", link_fail.failure_detail())
+            } else {
+                link_fail.failure_string
+            };
+            (
+                link_fail.info.map(|info| info.outgoing_amt_msat),
+                true,
+                reason,
+            )
+        }
+        _ => return None,
+    };
+
+    Some(LightningEvent::ForwardFailed {
+        incoming_chan_id: event.incoming_channel_id,
+        outgoing_chan_id: event.outgoing_channel_id,
+        timestamp_ns: event.timestamp_ns,
+        outgoing_amt_msat,
+        link_failure,
+        reason,
+    })
+}
+
+/// Times a succeeded LND payment from its creation to the resolution of the
+/// HTLC that settled it.
+fn lnd_payment_latency(payment: tonic_lnd::lnrpc::Payment) -> Option<PaymentLatency> {
+    if payment.status() != PaymentStatus::Succeeded || payment.creation_time_ns <= 0 {
+        return None;
+    }
+    let htlc = payment
+        .htlcs
+        .iter()
+        .rfind(|htlc| htlc.status() == HtlcStatus::Succeeded && htlc.resolve_time_ns > 0)?;
+    let hops = htlc
+        .route
+        .as_ref()
+        .map(|route| route.hops.as_slice())
+        .unwrap_or_default();
+
+    Some(PaymentLatency {
+        payment_hash: payment.payment_hash.clone(),
+        destination: hops.last().map(|hop| hop.pub_key.clone()),
+        first_hop: hops.first().map(|hop| ShortChannelID(hop.chan_id)),
+        settled_at: htlc.resolve_time_ns as u64 / 1_000_000_000,
+        settle_ms: (htlc.resolve_time_ns - payment.creation_time_ns).max(0) as u64 / 1_000_000,
+        htlc_ms: (htlc.attempt_time_ns > 0)
+            .then(|| (htlc.resolve_time_ns - htlc.attempt_time_ns).max(0) as u64 / 1_000_000),
+    })
+}
+
+#[async_trait]
+impl LightningClient for ClnNode {
+    fn get_info(&self) -> &NodeInfo {
+        &self.info
+    }
+
+    async fn get_network(&self) -> Result<Network, LightningError> {
+        let mut client = self.client.lock().await;
+        let info = client
+            .getinfo(GetinfoRequest {})
+            .await
+            .map_err(|err| LightningError::GetInfoError(err.to_string()))?
+            .into_inner();
+
+        Ok(Network::from_core_arg(&info.network)
+            .map_err(|err| LightningError::ValidationError(err.to_string()))?)
+    }
+
+    async fn get_block_height(&self) -> Result<u32, LightningError> {
+        let mut client = self.client.lock().await;
+        let info = client
+            .getinfo(GetinfoRequest {})
+            .await
+            .map_err(|err| LightningError::GetInfoError(err.to_string()))?
+            .into_inner();
+
+        Ok(info.blockheight)
+    }
+
+    async fn get_node_info(&self, node_id: &PublicKey) -> Result<NodeInfo, LightningError> {
+        let mut client = self.client.lock().await;
+        let mut nodes: Vec<cln_grpc::pb::ListnodesNodes> = client
+            .list_nodes(ListnodesRequest {
                 id: Some(node_id.serialize().to_vec()),
             })
             .await
@@ -1591,13 +3117,7 @@ impl LightningClient for ClnNode {
                 let remote_balance_satoshis =
                     capacity_satoshis.saturating_sub(local_balance_satoshis);
 
-                let channel_state = match peer_channel.state {
-                    0 | 1 | 9 | 10 => ChannelState::Opening,
-                    2 => ChannelState::Active,
-                    3..=5 => ChannelState::Closing,
-                    8 => ChannelState::Closed,
-                    _ => ChannelState::Disabled,
-                };
+                let channel_state = cln_channel_state_code(peer_channel.state);
 
                 let alias = peer_channel.alias.as_ref().and_then(|a| a.remote.clone());
 
@@ -1616,6 +3136,7 @@ impl LightningClient for ClnNode {
 
                 Some(ChannelSummary {
                     chan_id: channel_id,
+                    remote_pubkey: hex::encode(&peer_channel.peer_id),
                     alias,
                     channel_state,
                     private: !is_public,
@@ -1624,6 +3145,7 @@ impl LightningClient for ClnNode {
                     capacity: capacity_satoshis,
                     last_update: Some(last_update_timestamp),
                     uptime: None,
+                    notes: Vec::new(),
                 })
             })
             .collect();
@@ -1830,6 +3352,12 @@ impl LightningClient for ClnNode {
                 .map(|amt| amt.msat / 1000),
             channel_age_blocks: None,
             opening_cost_sat: None,
+            funding_fee_sat: None,
+            closing_txid: None,
+            closing_fee_sat: None,
+            rebalance_cost_sat: None,
+            peer_metadata: None,
+            notes: Vec::new(),
             initiator,
             txid,
             vout: channel.funding_outnum,
@@ -1939,6 +3467,8 @@ impl LightningClient for ClnNode {
                     invoice: payment.bolt11,
                     payment_hash: hex::encode(&payment.payment_hash),
                     completed_at: payment.completed_at,
+                    channel_id: None,
+                    keysend_message: None,
                 })
             })
             .collect();
@@ -1952,12 +3482,7 @@ impl LightningClient for ClnNode {
                 invoice.pay_index.is_some()
             })
             .filter_map(|invoice| {
-                let state = match invoice.status {
-                    0 => PaymentState::Inflight, // unpaid
-                    1 => PaymentState::Settled,  // paid
-                    2 => PaymentState::Failed,   // expired
-                    _ => return None,
-                };
+                let state = cln_invoice_payment_state_code(invoice.status)?;
 
                 // Use amount_received_msat if available (actual payment), fallback to amount_msat (invoice amount)
                 let amount_sat = invoice
@@ -1988,6 +3513,8 @@ impl LightningClient for ClnNode {
                     invoice: invoice.bolt11,
                     payment_hash: hex::encode(&invoice.payment_hash),
                     completed_at,
+                    channel_id: None,
+                    keysend_message: None,
                 })
             })
             .collect();
@@ -2014,17 +3541,8 @@ impl LightningClient for ClnNode {
 
     async fn stream_events(
         &mut self,
-    ) -> Result<Pin<Box<dyn Stream<Item = NodeSpecificEvent> + Send>>, LightningError> {
-        let event_stream = async_stream::stream! {
-            let mut counter = 0;
-            loop {
-                sleep(Duration::from_millis(60)).await;
-                yield NodeSpecificEvent::CLN(CLNEvent::ChannelOpened {  });
-                counter += 1;
-            }
-        };
-
-        Ok(Box::pin(event_stream))
+    ) -> Result<Pin<Box<dyn Stream<Item = LightningEvent> + Send>>, LightningError> {
+        self.stream_payment_events_only().await
     }
 
     async fn list_invoices(&self) -> Result<Vec<CustomInvoice>, LightningError> {
@@ -2035,8 +3553,6 @@ impl LightningClient for ClnNode {
             .map_err(|err| LightningError::InvoiceError(err.to_string()))?
             .into_inner();
 
-        let now = chrono::Utc::now().timestamp() as u64;
-
         let invoices = response
             .invoices
             .into_iter()
@@ -2050,18 +3566,8 @@ impl LightningClient for ClnNode {
 
                 let expires_at = invoice.expires_at;
 
-                let state = match invoice.status {
-                    1 => InvoiceStatus::Settled, // paid
-                    2 => InvoiceStatus::Expired, // expired
-                    3 => InvoiceStatus::Failed,  // failed
-                    _ => {
-                        if invoice.expires_at <= now {
-                            InvoiceStatus::Expired
-                        } else {
-                            InvoiceStatus::Open
-                        }
-                    }
-                };
+                let state = cln_invoice_status_code(invoice.status, expires_at);
+                let amount_paid_msat = invoice.amount_received_msat.map(|amt| amt.msat);
 
                 CustomInvoice {
                     memo: invoice.description.unwrap_or_default(),
@@ -2077,7 +3583,11 @@ impl LightningClient for ClnNode {
                     payment_request: invoice.bolt11.unwrap_or_default(),
                     expiry: Some(expires_at),
                     state,
+                    amount_paid_msat,
+                    payment_mismatch: amount_paid_msat
+                        .and_then(|paid| PaymentMismatch::between(amount_msat, paid)),
                     is_keysend: None,
+                    keysend_message: None,
                     is_amp: None,
                     payment_addr: None,
                     htlcs: None,
@@ -2089,6 +3599,34 @@ impl LightningClient for ClnNode {
         Ok(invoices)
     }
 
+    async fn list_offer_invoices(
+        &self,
+        offer_id: &str,
+    ) -> Result<Vec<OfferInvoice>, LightningError> {
+        let mut client = self.get_client_stub().await;
+        let request = cln_grpc::pb::ListinvoicesRequest {
+            offer_id: Some(offer_id.to_string()),
+            ..Default::default()
+        };
+
+        let response = client
+            .list_invoices(request)
+            .await
+            .map_err(|e| LightningError::InvoiceError(format!("CLN listinvoices error: {e}")))?
+            .into_inner();
+
+        Ok(response
+            .invoices
+            .into_iter()
+            .map(|invoice| OfferInvoice {
+                state: cln_invoice_status_code(invoice.status, invoice.expires_at),
+                amount_received_msat: invoice.amount_received_msat.map(|amt| amt.msat),
+                paid_at: invoice.paid_at,
+                payer_note: invoice.invreq_payer_note,
+            })
+            .collect())
+    }
+
     async fn get_invoice_details(
         &self,
         payment_hash: &PaymentHash,
@@ -2112,20 +3650,7 @@ impl LightningClient for ClnNode {
             .next()
             .ok_or_else(|| LightningError::NotFound("Invoice not found".into()))?;
 
-        let state = match invoice.status {
-            1 => InvoiceStatus::Settled, // paid
-            2 => InvoiceStatus::Expired, // expired
-            3 => InvoiceStatus::Failed,  // failed
-            _ => {
-                let now = chrono::Utc::now().timestamp() as u64;
-
-                if invoice.expires_at <= now {
-                    InvoiceStatus::Expired
-                } else {
-                    InvoiceStatus::Open
-                }
-            }
-        };
+        let state = cln_invoice_status_code(invoice.status, invoice.expires_at);
 
         let amount_msat = invoice
             .amount_msat
@@ -2133,6 +3658,7 @@ impl LightningClient for ClnNode {
             .map(|amt_msat| amt_msat.msat)
             .unwrap_or(0);
         let amount_sats = amount_msat / 1000;
+        let amount_paid_msat = invoice.amount_received_msat.map(|amt| amt.msat);
 
         Ok(CustomInvoice {
             memo: invoice.description.unwrap_or_default(),
@@ -2148,14 +3674,504 @@ impl LightningClient for ClnNode {
             payment_request: invoice.bolt11.unwrap_or_default(),
             expiry: Some(invoice.expires_at),
             state,
+            amount_paid_msat,
+            payment_mismatch: amount_paid_msat
+                .and_then(|paid| PaymentMismatch::between(amount_msat, paid)),
             is_keysend: None,
+            keysend_message: None,
             is_amp: None,
             payment_addr: None,
             htlcs: None,
             features: None,
         })
     }
+
+    async fn sign_message(&self, message: &str) -> Result<String, LightningError> {
+        let mut client = self.get_client_stub().await;
+
+        let response = client
+            .sign_message(cln_grpc::pb::SignmessageRequest {
+                message: message.to_string(),
+            })
+            .await
+            .map_err(|err| LightningError::SigningError(format!("CLN signmessage error: {err}")))?
+            .into_inner();
+
+        Ok(response.zbase)
+    }
+
+    async fn verify_message(
+        &self,
+        message: &str,
+        signature: &str,
+    ) -> Result<MessageVerification, LightningError> {
+        let mut client = self.get_client_stub().await;
+
+        let response = client
+            .check_message(cln_grpc::pb::CheckmessageRequest {
+                message: message.to_string(),
+                zbase: signature.to_string(),
+                pubkey: None,
+            })
+            .await
+            .map_err(|err| LightningError::SigningError(format!("CLN checkmessage error: {err}")))?
+            .into_inner();
+
+        Ok(MessageVerification {
+            valid: response.verified,
+            pubkey: hex::encode(response.pubkey),
+        })
+    }
+
+    async fn track_payment(
+        &self,
+        payment_hash: &PaymentHash,
+    ) -> Result<PaymentProgressStream, LightningError> {
+        let client = self.get_client_stub().await;
+        let hash = payment_hash.0.to_vec();
+
+        // Fail fast for unknown payments rather than opening an empty stream.
+        if cln_payment_progress(&mut client.clone(), &hash)
+            .await?
+            .is_none()
+        {
+            return Err(LightningError::NotFound(format!(
+                "Payment {} not found",
+                hex::encode(&hash)
+            )));
+        }
+
+        let progress_stream = stream! {
+            let mut client = client;
+            let mut last_sent: Option<PaymentProgress> = None;
+            loop {
+                let progress = match cln_payment_progress(&mut client, &hash).await {
+                    Ok(Some(progress)) => progress,
+                    Ok(None) => break,
+                    Err(err) => {
+                        yield Err(err);
+                        break;
+                    }
+                };
+
+                let finished = progress.state != PaymentState::Inflight;
+                if last_sent.as_ref() != Some(&progress) {
+                    last_sent = Some(progress.clone());
+                    yield Ok(progress);
+                }
+                if finished {
+                    break;
+                }
+
+                // Blocks until a pending part resolves or the wait times out;
+                // either way the attempts are re-read on the next iteration.
+                // With no part pending, `pay` is between retries.
+                let pending = last_sent
+                    .as_ref()
+                    .and_then(|p| p.attempts.iter().find(|a| a.state == PaymentState::Inflight))
+                    .map(|a| a.attempt_id);
+                if pending.is_none() {
+                    sleep(Duration::from_secs(CLN_TRACK_WAIT_SECS.into())).await;
+                    continue;
+                }
+                let _ = client
+                    .wait_send_pay(cln_grpc::pb::WaitsendpayRequest {
+                        payment_hash: hash.clone(),
+                        timeout: Some(CLN_TRACK_WAIT_SECS),
+                        partid: pending,
+                        groupid: None,
+                    })
+                    .await;
+            }
+        };
+
+        Ok(Box::pin(progress_stream))
+    }
+
+    async fn describe_graph(&self) -> Result<NetworkGraph, LightningError> {
+        let mut client = self.get_client_stub().await;
+
+        let nodes = client
+            .list_nodes(ListnodesRequest { id: None })
+            .await
+            .map_err(|err| LightningError::GetGraphError(err.to_string()))?
+            .into_inner()
+            .nodes;
+
+        let halves = client
+            .list_channels(ListchannelsRequest::default())
+            .await
+            .map_err(|err| LightningError::GetGraphError(err.to_string()))?
+            .into_inner()
+            .channels;
+
+        // CLN lists each direction of a channel separately.
+        let mut channels: HashMap<u64, GraphChannel> = HashMap::new();
+        for half in halves.into_iter().filter(|half| half.public) {
+            let Some(chan_id) = parse_short_channel_id(&half.short_channel_id) else {
+                continue;
+            };
+            let Ok(source) = PublicKey::from_slice(&half.source) else {
+                continue;
+            };
+
+            let channel = channels.entry(chan_id).or_insert_with(|| {
+                let (node1_pub, node2_pub) =
+                    ordered_pair(source.to_string(), hex::encode(&half.destination));
+                GraphChannel {
+                    chan_id,
+                    node1_pub,
+                    node2_pub,
+                    capacity_sat: half.amount_msat.as_ref().map_or(0, |a| a.msat / 1000),
+                    policies: Vec::new(),
+                }
+            });
+            channel.policies.push(NodePolicy {
+                pubkey: source,
+                fee_base_msat: half.base_fee_millisatoshi as u64,
+                fee_rate_milli_msat: half.fee_per_millionth as u64,
+                min_htlc_msat: half.htlc_minimum_msat.as_ref().map_or(0, |a| a.msat),
+                max_htlc_msat: half.htlc_maximum_msat.as_ref().map(|a| a.msat),
+                time_lock_delta: half.delay as u16,
+                disabled: !half.active,
+                last_update: Some(half.last_update as u64),
+            });
+        }
+
+        Ok(NetworkGraph {
+            nodes: nodes
+                .into_iter()
+                .map(|node| GraphNode {
+                    pubkey: hex::encode(&node.nodeid),
+                    alias: node.alias.unwrap_or_default(),
+                    last_update: node.last_timestamp.map(u64::from),
+                    addresses: node
+                        .addresses
+                        .iter()
+                        .filter_map(|a| {
+                            let host = a.address.as_deref()?;
+                            // Type 2 is IPv6
+                            Some(if a.item_type == 2 {
+                                format!("[{host}]:{}", a.port)
+                            } else {
+                                format!("{host}:{}", a.port)
+                            })
+                        })
+                        .collect(),
+                    features: node
+                        .features
+                        .as_deref()
+                        .map(feature_bits)
+                        .unwrap_or_default(),
+                })
+                .collect(),
+            channels: channels.into_values().collect(),
+        })
+    }
+
+    async fn create_invoice(
+        &self,
+        amount_msat: u64,
+        memo: &str,
+        expiry_secs: u64,
+    ) -> Result<String, LightningError> {
+        let mut client = self.get_client_stub().await;
+
+        let response = client
+            .invoice(cln_grpc::pb::InvoiceRequest {
+                amount_msat: Some(cln_grpc::pb::AmountOrAny {
+                    value: Some(cln_grpc::pb::amount_or_any::Value::Amount(
+                        cln_grpc::pb::Amount { msat: amount_msat },
+                    )),
+                }),
+                label: format!("nodegaze-{}", uuid::Uuid::now_v7()),
+                description: memo.to_string(),
+                expiry: Some(expiry_secs),
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| LightningError::InvoiceError(format!("CLN invoice error: {err}")))?
+            .into_inner();
+
+        Ok(response.bolt11)
+    }
+
+    async fn list_forwards(&self, since: u64) -> Result<Vec<Forward>, LightningError> {
+        let mut client = self.get_client_stub().await;
+
+        let response = client
+            .list_forwards(cln_grpc::pb::ListforwardsRequest {
+                status: Some(
+                    cln_grpc::pb::listforwards_request::ListforwardsStatus::Settled as i32,
+                ),
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| LightningError::ChannelError(format!("CLN listforwards error: {err}")))?
+            .into_inner();
+
+        Ok(response
+            .forwards
+            .into_iter()
+            .filter_map(|forward| {
+                let timestamp = forward.resolved_time.unwrap_or(forward.received_time) as u64;
+                (timestamp >= since).then_some(Forward {
+                    timestamp,
+                    received_at: Some(forward.received_time as u64),
+                    chan_id_in: ShortChannelID(parse_short_channel_id(&forward.in_channel)?),
+                    chan_id_out: ShortChannelID(parse_short_channel_id(
+                        forward.out_channel.as_deref()?,
+                    )?),
+                    incoming_htlc_id: forward.in_htlc_id,
+                    outgoing_htlc_id: forward.out_htlc_id,
+                    amt_in_msat: forward.in_msat.map_or(0, |amount| amount.msat),
+                    amt_out_msat: forward.out_msat.map_or(0, |amount| amount.msat),
+                    fee_msat: forward.fee_msat.map_or(0, |amount| amount.msat),
+                })
+            })
+            .collect())
+    }
+
+    async fn list_payment_latencies(
+        &self,
+        since: u64,
+    ) -> Result<Vec<PaymentLatency>, LightningError> {
+        let mut client = self.get_client_stub().await;
+
+        let response = client
+            .list_pays(cln_grpc::pb::ListpaysRequest {
+                status: Some(cln_grpc::pb::listpays_request::ListpaysStatus::Complete as i32),
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| LightningError::PaymentError(format!("CLN listpays error: {err}")))?
+            .into_inner();
+
+        // listpays only records whole seconds and nothing per attempt.
+        Ok(response
+            .pays
+            .into_iter()
+            .filter_map(|pay| {
+                let completed_at = pay.completed_at.filter(|&at| at >= since)?;
+                Some(PaymentLatency {
+                    payment_hash: hex::encode(&pay.payment_hash),
+                    destination: pay.destination.map(hex::encode),
+                    first_hop: None,
+                    settled_at: completed_at,
+                    settle_ms: completed_at.saturating_sub(pay.created_at) * 1000,
+                    htlc_ms: None,
+                })
+            })
+            .collect())
+    }
+
+    async fn update_channel_fees(
+        &self,
+        channel: &ChannelDetails,
+        base_fee_msat: u64,
+        fee_rate_ppm: u32,
+    ) -> Result<(), LightningError> {
+        let mut client = self.get_client_stub().await;
+
+        client
+            .set_channel(cln_grpc::pb::SetchannelRequest {
+                id: format_short_channel_id(channel.channel_id.0),
+                feebase: Some(cln_grpc::pb::Amount {
+                    msat: base_fee_msat,
+                }),
+                feeppm: Some(fee_rate_ppm),
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| LightningError::ChannelError(format!("CLN setchannel error: {err}")))?;
+
+        Ok(())
+    }
+}
+
+/// Builds a progress snapshot from CLN's `listsendpays` parts for a payment,
+/// using the most recent payment group. Returns `None` if nothing was sent.
+async fn list_cln_sendpays(
+    client: &mut NodeClient<Channel>,
+) -> Result<Vec<cln_grpc::pb::ListsendpaysPayments>, String> {
+    client
+        .list_send_pays(cln_grpc::pb::ListsendpaysRequest::default())
+        .await
+        .map(|response| response.into_inner().payments)
+        .map_err(|err| err.to_string())
+}
+
+/// Remembers which sendpay parts were still pending so that each part is
+/// reported once, when it leaves the pending state.
+#[derive(Default)]
+struct ClnSendpayWatcher {
+    /// Highest sendpay id seen so far
+    last_id: Option<u64>,
+    /// Ids of the parts that were pending on the last read
+    pending: HashSet<u64>,
 }
+
+impl ClnSendpayWatcher {
+    /// Returns the events for parts that resolved since the previous read.
+    /// The first read only records state.
+    fn resolve(&mut self, parts: Vec<cln_grpc::pb::ListsendpaysPayments>) -> Vec<LightningEvent> {
+        let first_read = self.last_id.is_none();
+        let last_id = self.last_id.unwrap_or(0);
+        let mut pending = HashSet::new();
+        let mut events = Vec::new();
+
+        for part in parts {
+            self.last_id = Some(self.last_id.map_or(part.id, |id| id.max(part.id)));
+            if part.status == 0 {
+                pending.insert(part.id);
+                continue;
+            }
+            let is_new = self.pending.contains(&part.id) || (!first_read && part.id > last_id);
+            if is_new {
+                events.push(cln_sendpay_event(part));
+            }
+        }
+
+        self.pending = pending;
+        events
+    }
+}
+
+/// Normalizes a resolved sendpay part into the event CLN would notify for it.
+fn cln_sendpay_event(part: cln_grpc::pb::ListsendpaysPayments) -> LightningEvent {
+    let amount_msat = part.amount_msat.map(|a| a.msat).unwrap_or(0);
+    let payment_hash = hex::encode(&part.payment_hash);
+    let destination = part.destination.as_deref().map(hex::encode);
+    let part_id = part.partid.unwrap_or(0);
+
+    if part.status == 2 {
+        LightningEvent::PaymentSent {
+            payment_hash,
+            part_id,
+            group_id: part.groupid,
+            amount_msat,
+            amount_sent_msat: part.amount_sent_msat.map(|a| a.msat).unwrap_or(amount_msat),
+            destination,
+        }
+    } else {
+        LightningEvent::PaymentFailed {
+            payment_hash,
+            part_id,
+            group_id: part.groupid,
+            amount_msat,
+            destination,
+            reason: if part.erroronion.is_some() {
+                "Failed at a remote hop".to_string()
+            } else {
+                "Failed".to_string()
+            },
+        }
+    }
+}
+
+async fn cln_payment_progress(
+    client: &mut NodeClient<Channel>,
+    payment_hash: &[u8],
+) -> Result<Option<PaymentProgress>, LightningError> {
+    let parts = client
+        .list_send_pays(cln_grpc::pb::ListsendpaysRequest {
+            payment_hash: Some(payment_hash.to_vec()),
+            ..Default::default()
+        })
+        .await
+        .map_err(|err| LightningError::PaymentError(format!("CLN list_send_pays error: {err}")))?
+        .into_inner()
+        .payments;
+
+    let Some(groupid) = parts.iter().map(|part| part.groupid).max() else {
+        return Ok(None);
+    };
+
+    let attempts: Vec<PaymentAttempt> = parts
+        .into_iter()
+        .filter(|part| part.groupid == groupid)
+        .map(|part| {
+            let amount_msat = part.amount_msat.map(|a| a.msat).unwrap_or(0);
+            let sent_msat = part.amount_sent_msat.map(|a| a.msat).unwrap_or(amount_msat);
+            let state = match part.status {
+                0 => PaymentState::Inflight, // pending
+                2 => PaymentState::Settled,  // complete
+                _ => PaymentState::Failed,   // failed
+            };
+            PaymentAttempt {
+                attempt_id: part.partid.unwrap_or(0),
+                failure_reason: (state == PaymentState::Failed).then(|| {
+                    if part.erroronion.is_some() {
+                        "Failed at a remote hop".to_string()
+                    } else {
+                        "Failed".to_string()
+                    }
+                }),
+                state,
+                amount_msat,
+                fee_msat: sent_msat.saturating_sub(amount_msat),
+                hop_count: 0,
+                attempt_time: Some(part.created_at),
+                resolve_time: part.completed_at,
+            }
+        })
+        .collect();
+
+    let pay_status = client
+        .list_pays(cln_grpc::pb::ListpaysRequest {
+            payment_hash: Some(payment_hash.to_vec()),
+            ..Default::default()
+        })
+        .await
+        .map_err(|err| LightningError::PaymentError(format!("CLN listpays error: {err}")))?
+        .into_inner()
+        .pays
+        .last()
+        .map(|pay| pay.status());
+    let state = cln_payment_state(pay_status, &attempts);
+
+    Ok(Some(PaymentProgress {
+        payment_hash: hex::encode(payment_hash),
+        failure_reason: (state == PaymentState::Failed)
+            .then(|| "All payment attempts failed".to_string()),
+        state,
+        amount_msat: attempts
+            .iter()
+            .filter(|a| a.state != PaymentState::Failed)
+            .map(|a| a.amount_msat)
+            .sum(),
+        fee_msat: attempts
+            .iter()
+            .filter(|a| a.state != PaymentState::Failed)
+            .map(|a| a.fee_msat)
+            .sum(),
+        attempts,
+    }))
+}
+
+/// Overall state of a CLN payment from its `listpays` status, if it has one.
+///
+/// Only `listpays` knows whether the payment is over: `pay` keeps retrying
+/// within the same group after every part sent so far has failed. Payments
+/// without a `listpays` entry are judged by their parts.
+fn cln_payment_state(
+    pay_status: Option<ListpaysPaysStatus>,
+    attempts: &[PaymentAttempt],
+) -> PaymentState {
+    match pay_status {
+        Some(ListpaysPaysStatus::Complete) => PaymentState::Settled,
+        Some(ListpaysPaysStatus::Failed) => PaymentState::Failed,
+        Some(ListpaysPaysStatus::Pending) => PaymentState::Inflight,
+        None if attempts.iter().any(|a| a.state == PaymentState::Settled)
+            && !attempts.iter().any(|a| a.state == PaymentState::Inflight) =>
+        {
+            PaymentState::Settled
+        }
+        None if attempts.iter().all(|a| a.state == PaymentState::Failed) => PaymentState::Failed,
+        None => PaymentState::Inflight,
+    }
+}
+
 pub fn parse_channel_point(channel_point_str: &str) -> Result<OutPoint, LightningError> {
     let mut parts = channel_point_str.split(':');
     let txid_str = parts
@@ -2173,3 +4189,38 @@ pub fn parse_channel_point(channel_point_str: &str) -> Result<OutPoint, Lightnin
 
     Ok(OutPoint { txid, vout })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cln_payment_ends_only_when_listpays_does() {
+        let failed = PaymentAttempt {
+            attempt_id: 1,
+            state: PaymentState::Failed,
+            amount_msat: 1_000,
+            fee_msat: 0,
+            hop_count: 0,
+            attempt_time: None,
+            resolve_time: None,
+            failure_reason: Some("Failed".to_string()),
+        };
+        let attempts = [failed];
+
+        // Every part failed so far, but pay is still retrying.
+        assert_eq!(
+            cln_payment_state(Some(ListpaysPaysStatus::Pending), &attempts),
+            PaymentState::Inflight
+        );
+        assert_eq!(
+            cln_payment_state(Some(ListpaysPaysStatus::Failed), &attempts),
+            PaymentState::Failed
+        );
+        assert_eq!(
+            cln_payment_state(Some(ListpaysPaysStatus::Complete), &attempts),
+            PaymentState::Settled
+        );
+        assert_eq!(cln_payment_state(None, &attempts), PaymentState::Failed);
+    }
+}
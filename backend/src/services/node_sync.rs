@@ -0,0 +1,415 @@
+//! Full reconciliation of node data into the local database.
+//!
+//! A resync runs as a background job: it pulls every channel, payment and
+//! invoice from the node and replaces the locally stored copies. Progress is
+//! recorded as `NodeResync` events so the operator can follow it on the events
+//! page; only the final outcome is sent to notification endpoints.
+
+use crate::database::models::{
+    CreateEvent, EventSeverity, EventType, Job, JobType, SyncedChannel, SyncedInvoice,
+    SyncedPayment,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::node_sync_repository::NodeSyncRepository;
+use crate::services::event_service::EventService;
+use crate::services::job_queue::JobQueue;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use crate::utils::{ChannelState, ChannelSummary, PaymentType, ShortChannelID};
+use bitcoin::secp256k1::PublicKey;
+use chrono::{DateTime, Utc};
+use lightning_invoice::Bolt11Invoice;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Payload of a `NodeResync` job.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeResyncJob {
+    pub account_id: String,
+    pub user_id: String,
+    pub node_id: String,
+}
+
+/// Service layer for requesting node resyncs.
+pub struct NodeSyncService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NodeSyncService<'a> {
+    /// Creates a new NodeSyncService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Hashes of the node's synced payments to a destination, matched by
+    /// pubkey prefix or alias substring.
+    pub async fn payment_hashes_to(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        destination: &str,
+    ) -> ServiceResult<HashSet<String>> {
+        let hashes = NodeSyncRepository::new(self.pool)
+            .get_payment_hashes_by_destination(account_id, node_id, destination)
+            .await?;
+        Ok(hashes.into_iter().collect())
+    }
+
+    /// Hashes of the node's synced invoices matching a memo search and an
+    /// amount-paid range in msat.
+    pub async fn invoice_hashes_matching(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        memo_contains: Option<&str>,
+        min_paid_msat: Option<i64>,
+        max_paid_msat: Option<i64>,
+    ) -> ServiceResult<HashSet<String>> {
+        let hashes = NodeSyncRepository::new(self.pool)
+            .get_invoice_hashes_matching(
+                account_id,
+                node_id,
+                memo_contains,
+                min_paid_msat,
+                max_paid_msat,
+            )
+            .await?;
+        Ok(hashes.into_iter().collect())
+    }
+
+    /// The node's channels as of its last sync, for when the node can't be
+    /// reached. `None` if the node has never been synced.
+    pub async fn channel_snapshot(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<Option<(Vec<ChannelSummary>, DateTime<Utc>)>> {
+        let synced = NodeSyncRepository::new(self.pool)
+            .get_channels(account_id, node_id)
+            .await?;
+        let Some(as_of) = synced.iter().map(|channel| channel.synced_at).max() else {
+            return Ok(None);
+        };
+        Ok(Some((
+            synced.into_iter().filter_map(channel_summary).collect(),
+            as_of,
+        )))
+    }
+
+    /// Queues a full resync of one of the account's nodes.
+    pub async fn request_resync(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<Job> {
+        CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+
+        JobQueue::new(self.pool)
+            .enqueue(
+                JobType::NodeResync,
+                &NodeResyncJob {
+                    account_id: account_id.to_string(),
+                    user_id: user_id.to_string(),
+                    node_id: node_id.to_string(),
+                },
+                Some(account_id),
+                None,
+            )
+            .await
+    }
+}
+
+/// Rebuilds a channel's summary from its synced row. Uptime and the last
+/// update time aren't synced, and rows synced before peers were recorded
+/// have an empty peer.
+fn channel_summary(channel: SyncedChannel) -> Option<ChannelSummary> {
+    Some(ChannelSummary {
+        chan_id: ShortChannelID::from_str(&channel.chan_id).ok()?,
+        remote_pubkey: channel.remote_pubkey.unwrap_or_default(),
+        alias: channel.alias,
+        channel_state: ChannelState::from_str(&channel.channel_state).ok()?,
+        private: channel.private,
+        remote_balance: channel.remote_balance_sat.max(0) as u64,
+        local_balance: channel.local_balance_sat.max(0) as u64,
+        capacity: channel.capacity_sat.max(0) as u64,
+        last_update: None,
+        uptime: None,
+        notes: Vec::new(),
+    })
+}
+
+/// Reads the payee's pubkey out of a BOLT11 invoice.
+fn invoice_payee(bolt11: &str) -> Option<String> {
+    Bolt11Invoice::from_str(bolt11)
+        .ok()
+        .map(|invoice| invoice.get_payee_pub_key().to_string())
+}
+
+fn from_unix(secs: Option<i64>) -> Option<DateTime<Utc>> {
+    secs.filter(|s| *s > 0)
+        .and_then(|s| DateTime::from_timestamp(s, 0))
+}
+
+struct ResyncContext<'a> {
+    pool: &'a SqlitePool,
+    job: NodeResyncJob,
+    node_alias: String,
+}
+
+impl ResyncContext<'_> {
+    fn event(
+        &self,
+        severity: EventSeverity,
+        title: &str,
+        description: String,
+        data: Value,
+    ) -> CreateEvent {
+        CreateEvent {
+            id: Uuid::now_v7().to_string(),
+            account_id: self.job.account_id.clone(),
+            user_id: self.job.user_id.clone(),
+            node_id: self.job.node_id.clone(),
+            node_alias: self.node_alias.clone(),
+            event_type: EventType::NodeResync,
+            severity,
+            title: title.to_string(),
+            description,
+            data: data.to_string(),
+            notifications_id: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Records an intermediate progress event without notifying endpoints.
+    async fn progress(&self, stage: &str, description: String, data: Value) {
+        let event = self.event(
+            EventSeverity::Info,
+            "Node Resync In Progress",
+            description,
+            data,
+        );
+        if let Err(e) = EventRepository::new(self.pool).create_event(event).await {
+            tracing::warn!("Failed to record resync progress ({}): {}", stage, e);
+        }
+    }
+
+    /// Records and dispatches the final outcome of the resync.
+    async fn finish(&self, severity: EventSeverity, title: &str, description: String, data: Value) {
+        let event = self.event(severity, title, description, data);
+        if let Err(e) = EventService::new(self.pool)
+            .create_and_dispatch_event(event)
+            .await
+        {
+            tracing::warn!("Failed to record resync outcome: {}", e);
+        }
+    }
+}
+
+/// Runs a resync job, reporting progress and the outcome as events.
+pub async fn run_resync_job(pool: &SqlitePool, payload: &str) -> Result<(), String> {
+    let job: NodeResyncJob = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+    let credential = CredentialRepository::new(pool)
+        .get_credential_by_account_and_node_id(&job.account_id, &job.node_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Node {} no longer has credentials", job.node_id))?;
+
+    let context = ResyncContext {
+        pool,
+        node_alias: credential.node_alias.clone(),
+        job,
+    };
+
+    context
+        .progress(
+            "started",
+            "Resync started".to_string(),
+            json!({ "stage": "started" }),
+        )
+        .await;
+
+    match resync(&context, NodeCredentials::from(credential)).await {
+        Ok(counts) => {
+            context
+                .finish(
+                    EventSeverity::Info,
+                    "Node Resync Completed",
+                    format!(
+                        "Resync completed: {} channels, {} payments, {} invoices",
+                        counts["channels"], counts["payments"], counts["invoices"]
+                    ),
+                    counts,
+                )
+                .await;
+            Ok(())
+        }
+        Err(e) => {
+            context
+                .finish(
+                    EventSeverity::Warning,
+                    "Node Resync Failed",
+                    format!("Resync failed: {e}"),
+                    json!({ "stage": "failed", "error": e }),
+                )
+                .await;
+            Err(e)
+        }
+    }
+}
+
+async fn resync(
+    context: &ResyncContext<'_>,
+    credentials: NodeCredentials,
+) -> Result<Value, String> {
+    let job = &context.job;
+    let repo = NodeSyncRepository::new(context.pool);
+    let public_key = PublicKey::from_str(&job.node_id).map_err(|e| e.to_string())?;
+    let client = create_node_client(&credentials, public_key)
+        .await
+        .map_err(|(_, body)| body)?;
+    let synced_at = Utc::now();
+
+    let channels: Vec<SyncedChannel> = client
+        .list_channels()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|channel| SyncedChannel {
+            id: Uuid::now_v7().to_string(),
+            account_id: job.account_id.clone(),
+            node_id: job.node_id.clone(),
+            chan_id: channel.chan_id.to_string(),
+            remote_pubkey: Some(channel.remote_pubkey),
+            alias: channel.alias,
+            channel_state: format!("{:?}", channel.channel_state),
+            private: channel.private,
+            local_balance_sat: channel.local_balance as i64,
+            remote_balance_sat: channel.remote_balance as i64,
+            capacity_sat: channel.capacity as i64,
+            synced_at,
+        })
+        .collect();
+    repo.replace_channels(&job.account_id, &job.node_id, &channels)
+        .await
+        .map_err(|e| e.to_string())?;
+    context
+        .progress(
+            "channels",
+            format!("Synced {} channels", channels.len()),
+            json!({ "stage": "channels", "channels": channels.len() }),
+        )
+        .await;
+
+    let payments: Vec<SyncedPayment> = client
+        .list_payments()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|payment| SyncedPayment {
+            id: Uuid::now_v7().to_string(),
+            account_id: job.account_id.clone(),
+            node_id: job.node_id.clone(),
+            payment_hash: payment.payment_hash,
+            state: format!("{:?}", payment.state),
+            payment_type: format!("{:?}", payment.payment_type),
+            amount_sat: payment.amount_sat as i64,
+            routing_fee_sat: payment.routing_fee.map(|fee| fee as i64),
+            created_at_node: from_unix(payment.creation_time.map(|t| t as i64)),
+            completed_at_node: from_unix(payment.completed_at.map(|t| t as i64)),
+            keysend_message: payment.keysend_message,
+            destination_pubkey: match payment.payment_type {
+                PaymentType::Outgoing => payment.invoice.as_deref().and_then(invoice_payee),
+                _ => None,
+            },
+            imported_from: None,
+            synced_at,
+        })
+        .collect();
+    repo.replace_payments(&job.account_id, &job.node_id, &payments)
+        .await
+        .map_err(|e| e.to_string())?;
+    context
+        .progress(
+            "payments",
+            format!("Synced {} payments", payments.len()),
+            json!({ "stage": "payments", "payments": payments.len() }),
+        )
+        .await;
+
+    let invoices: Vec<SyncedInvoice> = client
+        .list_invoices()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|invoice| SyncedInvoice {
+            id: Uuid::now_v7().to_string(),
+            account_id: job.account_id.clone(),
+            node_id: job.node_id.clone(),
+            payment_hash: invoice.payment_hash,
+            state: format!("{:?}", invoice.state),
+            memo: invoice.memo,
+            value_msat: invoice.value_msat as i64,
+            amt_paid_msat: invoice.amount_paid_msat.map(|msat| msat as i64),
+            created_at_node: from_unix(invoice.creation_date),
+            settled_at_node: from_unix(invoice.settle_date),
+            is_keysend: invoice.is_keysend.unwrap_or(false),
+            keysend_message: invoice.keysend_message,
+            synced_at,
+        })
+        .collect();
+    repo.replace_invoices(&job.account_id, &job.node_id, &invoices)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(json!({
+        "stage": "completed",
+        "channels": channels.len(),
+        "payments": payments.len(),
+        "invoices": invoices.len(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synced(chan_id: &str, channel_state: &str) -> SyncedChannel {
+        SyncedChannel {
+            id: "row-1".to_string(),
+            account_id: "account-1".to_string(),
+            node_id: "node-1".to_string(),
+            chan_id: chan_id.to_string(),
+            remote_pubkey: None,
+            alias: Some("peer".to_string()),
+            channel_state: channel_state.to_string(),
+            private: true,
+            local_balance_sat: 600_000,
+            remote_balance_sat: 400_000,
+            capacity_sat: 1_000_000,
+            synced_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_channel_summary_from_synced_row() {
+        let summary = channel_summary(synced("825123456789", "Disabled")).unwrap();
+        assert_eq!(u64::from(summary.chan_id), 825_123_456_789);
+        assert!(matches!(summary.channel_state, ChannelState::Disabled));
+        assert_eq!(summary.remote_pubkey, "");
+        assert_eq!(summary.local_balance, 600_000);
+        assert!(summary.private);
+
+        assert!(channel_summary(synced("not-a-channel", "Active")).is_none());
+        assert!(channel_summary(synced("825123456789", "Unknown")).is_none());
+    }
+}
@@ -1,31 +1,222 @@
 //! Service for dispatching events to notification endpoints.
 
-use crate::database::models::{Event, Notification, NotificationType};
+use crate::database::models::{
+    Event, EventSeverity, EventType, JobType, Notification, NotificationType,
+};
+use crate::repositories::event_repository::EventRepository;
 use crate::repositories::notification_repository::NotificationRepository;
-use reqwest::Client;
-use serde_json::json;
+use crate::services::cln_rest::parse_short_channel_id;
+use crate::services::job_queue::{JobQueue, retry_delay};
+use crate::services::maintenance::{MAINTENANCE_LABEL, is_maintenance_event};
+use crate::services::node_labels::{NODE_LABEL_KEY, display_name};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::time::Duration;
 use tracing::{error, info, warn};
 
+/// Webhook body schema version new endpoints receive
+pub const LATEST_WEBHOOK_PAYLOAD_VERSION: i64 = 2;
+
+/// Request timeout of endpoints that don't set their own
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Headers the dispatcher sets itself, which endpoints can't override
+const RESERVED_HEADERS: [&str; 4] = ["content-type", "content-length", "host", "user-agent"];
+
+/// Event data fields naming a channel the event touches
+const CHANNEL_DATA_KEYS: [&str; 5] = [
+    "chan_id",
+    "channel_id",
+    "incoming_chan_id",
+    "outgoing_chan_id",
+    "channel_point",
+];
+
+/// Puts a channel reference in the form endpoints store and events are
+/// compared in: channel points as lowercase `txid:vout`, short channel ids
+/// as their integer. Returns `None` if it's neither.
+pub fn normalize_channel(channel: &str) -> Option<String> {
+    let channel = channel.trim();
+    match channel.split_once(':') {
+        Some((txid, vout)) => {
+            if txid.len() != 64 || !txid.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            let vout = vout.parse::<u32>().ok()?;
+            Some(format!("{}:{vout}", txid.to_lowercase()))
+        }
+        None => parse_short_channel_id(channel)
+            .filter(|scid| *scid != 0)
+            .map(|scid| scid.to_string()),
+    }
+}
+
+/// Whether an event's data names `channel`, as normalized by
+/// `normalize_channel`. Forwards and HTLCs only name short channel ids, so
+/// an endpoint watching a channel point misses them.
+pub fn touches_channel(event_data: &str, channel: &str) -> bool {
+    let Ok(data) = serde_json::from_str::<Value>(event_data) else {
+        return false;
+    };
+    CHANNEL_DATA_KEYS.iter().any(|key| {
+        let value = match data.get(key) {
+            Some(Value::String(value)) => normalize_channel(value),
+            Some(Value::Number(value)) => value
+                .as_u64()
+                .and_then(|scid| normalize_channel(&scid.to_string())),
+            _ => None,
+        };
+        value.as_deref() == Some(channel)
+    })
+}
+
+/// Checks the extra request headers of an endpoint: each must be a valid
+/// HTTP header and none one the dispatcher sets itself.
+pub fn validate_headers(headers: &HashMap<String, String>) -> Result<(), String> {
+    for (name, value) in headers {
+        let header = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("'{name}' is not a valid header name"))?;
+        if RESERVED_HEADERS.contains(&header.as_str()) {
+            return Err(format!("Header '{name}' is set by NodeGaze"));
+        }
+        HeaderValue::from_str(value)
+            .map_err(|_| format!("Header '{name}' has an invalid value"))?;
+    }
+    Ok(())
+}
+
+/// The extra request headers of an endpoint.
+pub fn endpoint_headers(notification: &Notification) -> HashMap<String, String> {
+    notification
+        .headers
+        .as_deref()
+        .and_then(|headers| match serde_json::from_str(headers) {
+            Ok(headers) => Some(headers),
+            Err(e) => {
+                warn!(
+                    "Ignoring unreadable headers of endpoint {}: {}",
+                    notification.id, e
+                );
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// The request timeout of an endpoint, in seconds.
+pub fn endpoint_timeout_secs(notification: &Notification) -> u64 {
+    notification
+        .timeout_secs
+        .and_then(|secs| u64::try_from(secs).ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS)
+}
+
+/// Outcome of evaluating the routing rules for an event against an endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingDecision {
+    /// Whether the endpoint would receive the event
+    pub would_deliver: bool,
+    /// Human-readable reasons the event would be held back
+    pub reasons: Vec<String>,
+}
+
+/// Payload of a queued delivery: a retry of a failed one, or a redispatch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationDeliveryJob {
+    pub event_id: String,
+    pub account_id: String,
+    pub notification_id: String,
+    /// User who asked for the event to be sent again; `None` for retries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redispatched_by: Option<String>,
+}
+
 /// Service for dispatching events to notification endpoints.
 #[derive(Debug, Clone)]
 pub struct NotificationDispatcher {
     http_client: Client,
+    /// For endpoints that opted out of TLS certificate verification
+    unverified_http_client: Client,
 }
 
 impl NotificationDispatcher {
     /// Creates a new NotificationDispatcher instance.
     pub fn new() -> Self {
         let http_client = Client::builder()
-            .timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .build()
+            .expect("Failed to create HTTP client");
+        let unverified_http_client = Client::builder()
+            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .danger_accept_invalid_certs(true)
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { http_client }
+        Self {
+            http_client,
+            unverified_http_client,
+        }
+    }
+
+    /// Builds a request to an endpoint URL with the endpoint's delivery
+    /// settings: its timeout, extra headers and TLS verification.
+    pub fn request(
+        &self,
+        method: Method,
+        url: &str,
+        timeout_secs: u64,
+        headers: &HashMap<String, String>,
+        verify_tls: bool,
+    ) -> RequestBuilder {
+        let client = if verify_tls {
+            &self.http_client
+        } else {
+            &self.unverified_http_client
+        };
+        headers.iter().fold(
+            client
+                .request(method, url)
+                .timeout(Duration::from_secs(timeout_secs))
+                .header("User-Agent", "NodeGaze/1.0"),
+            |request, (name, value)| request.header(name.as_str(), value.as_str()),
+        )
+    }
+
+    /// Builds a request to a notification endpoint with its delivery settings.
+    pub fn endpoint_request(&self, method: Method, notification: &Notification) -> RequestBuilder {
+        self.request(
+            method,
+            &notification.url,
+            endpoint_timeout_secs(notification),
+            &endpoint_headers(notification),
+            notification.verify_tls,
+        )
+    }
+
+    /// The endpoints an event is delivered to out of the account's
+    /// `notifications`.
+    ///
+    /// Events are stored with a row per endpoint they fan out to, pinned by
+    /// `notifications_id`, so each row only reaches its own endpoint; sending
+    /// every row to every endpoint would deliver an event once per endpoint
+    /// the account has. Rows without an endpoint go to all that route it.
+    pub fn endpoints_for_event(
+        event: &Event,
+        notifications: Vec<Notification>,
+    ) -> Vec<Notification> {
+        notifications
+            .into_iter()
+            .filter(|n| Self::evaluate_routing(event, n).would_deliver)
+            .collect()
     }
 
-    /// Dispatches an event to all active notifications for the account.
+    /// Dispatches an event to the active notifications it is routed to.
     pub async fn dispatch_event(
         &self,
         pool: &SqlitePool,
@@ -36,8 +227,7 @@ impl NotificationDispatcher {
             .get_notifications_by_account_id(&event.account_id)
             .await?;
 
-        let active_notifications: Vec<_> =
-            notifications.into_iter().filter(|n| n.is_active).collect();
+        let active_notifications = Self::endpoints_for_event(event, notifications);
 
         if active_notifications.is_empty() {
             info!(
@@ -53,6 +243,18 @@ impl NotificationDispatcher {
             active_notifications.len()
         );
 
+        // Firehose deliveries always go through the job queue, so each one is
+        // persisted before it is attempted.
+        let (firehoses, active_notifications): (Vec<_>, Vec<_>) = active_notifications
+            .into_iter()
+            .partition(|n| n.notification_type == NotificationType::Firehose);
+        for firehose in firehoses {
+            self.queue_delivery(pool, event, firehose.id, None).await;
+        }
+
+        let notification_ids: Vec<String> =
+            active_notifications.iter().map(|n| n.id.clone()).collect();
+
         // Dispatch to all active notifications concurrently
         let dispatch_futures: Vec<_> = active_notifications
             .into_iter()
@@ -62,88 +264,206 @@ impl NotificationDispatcher {
         // Wait for all dispatches to complete
         let results = futures::future::join_all(dispatch_futures).await;
 
-        // Log results
-        for (i, result) in results.into_iter().enumerate() {
+        // Log results and queue retries for failed deliveries
+        for (notification_id, result) in notification_ids.into_iter().zip(results) {
             match result {
                 Ok(_) => info!(
                     "Successfully dispatched event {} to endpoint {}",
-                    event.id, i
-                ),
-                Err(e) => error!(
-                    "Failed to dispatch event {} to endpoint {}: {}",
-                    event.id, i, e
+                    event.id, notification_id
                 ),
+                Err(e) => {
+                    error!(
+                        "Failed to dispatch event {} to endpoint {}: {}",
+                        event.id, notification_id, e
+                    );
+                    let run_at = Utc::now() + retry_delay(1);
+                    self.queue_delivery(pool, event, notification_id, Some(run_at))
+                        .await;
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Sends an event to a specific notification endpoint.
-    async fn send_to_endpoint(
+    /// Queues a delivery on the persistent job queue, to run at `run_at` or
+    /// as soon as a worker is free.
+    async fn queue_delivery(
         &self,
+        pool: &SqlitePool,
         event: &Event,
-        notification: Notification,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        match notification.notification_type {
-            NotificationType::Webhook => self.send_webhook(event, &notification).await,
-            NotificationType::Discord => self.send_discord(event, &notification).await,
+        notification_id: String,
+        run_at: Option<DateTime<Utc>>,
+    ) {
+        let payload = NotificationDeliveryJob {
+            event_id: event.id.clone(),
+            account_id: event.account_id.clone(),
+            notification_id,
+            redispatched_by: None,
+        };
+
+        if let Err(e) = JobQueue::new(pool)
+            .enqueue(
+                JobType::NotificationDelivery,
+                &payload,
+                Some(&event.account_id),
+                run_at,
+            )
+            .await
+        {
+            error!(
+                "Failed to queue delivery of event {} to endpoint {}: {}",
+                event.id, payload.notification_id, e
+            );
         }
     }
 
-    /// Sends event to a webhook endpoint.
-    async fn send_webhook(
-        &self,
-        event: &Event,
-        notification: &Notification,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let payload = json!({
-            "event_id": event.id,
-            "timestamp": event.timestamp,
-            "event_type": event.event_type,
-            "severity": event.severity,
-            "title": event.title,
-            "description": event.description,
-            "node_id": event.node_id,
-            "node_alias": event.node_alias,
-            "data": serde_json::from_str::<serde_json::Value>(&event.data).unwrap_or(json!({}))
-        });
+    /// Runs a queued delivery: a retry of a failed attempt or a redispatch.
+    ///
+    /// Routing is re-evaluated first, so endpoints disabled or deleted since the
+    /// original attempt are skipped rather than retried.
+    pub async fn run_delivery_job(&self, pool: &SqlitePool, payload: &str) -> Result<(), String> {
+        let job: NotificationDeliveryJob =
+            serde_json::from_str(payload).map_err(|e| format!("Invalid job payload: {e}"))?;
 
-        let response = self
-            .http_client
-            .post(&notification.url)
-            .header("Content-Type", "application/json")
-            .header("User-Agent", "NodeGaze/1.0")
-            .json(&payload)
-            .send()
-            .await?;
+        let Some(event) = EventRepository::new(pool)
+            .get_event_by_id(&job.event_id, &job.account_id)
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            warn!("Dropping retry for missing event {}", job.event_id);
+            return Ok(());
+        };
 
-        if response.status().is_success() {
+        let Some(notification) = NotificationRepository::new(pool)
+            .get_notification_by_id(&job.notification_id)
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            warn!(
+                "Dropping retry for missing endpoint {}",
+                job.notification_id
+            );
+            return Ok(());
+        };
+
+        let decision = Self::evaluate_routing(&event, &notification);
+        if !decision.would_deliver {
             info!(
-                "Webhook notification sent successfully to {}",
-                notification.url
+                "Skipping retry of event {} to endpoint {}: {}",
+                event.id,
+                notification.id,
+                decision.reasons.join(", ")
             );
-        } else {
-            warn!(
-                "Webhook notification failed with status {}: {}",
-                response.status(),
-                notification.url
+            return Ok(());
+        }
+
+        if let Some(ref user_id) = job.redispatched_by {
+            info!(
+                "Redispatching event {} to endpoint {} for user {}",
+                event.id, notification.id, user_id
             );
         }
+        self.send_to_endpoint(&event, notification)
+            .await
+            .map_err(|e| e.to_string())
+    }
 
-        Ok(())
+    /// Evaluates the routing rules that decide whether an event reaches an endpoint.
+    ///
+    /// This is the single source of truth for delivery, shared by live dispatch
+    /// and the dry-run preview so both always agree.
+    pub fn evaluate_routing(event: &Event, notification: &Notification) -> RoutingDecision {
+        let mut reasons = Vec::new();
+
+        if notification.is_deleted {
+            reasons.push("Notification endpoint has been deleted".to_string());
+        }
+        if !notification.is_active {
+            reasons.push("Notification endpoint is disabled".to_string());
+        }
+        if notification.account_id != event.account_id {
+            reasons.push("Event belongs to a different account".to_string());
+        }
+        if let Some(ref target) = event.notifications_id {
+            if target != &notification.id {
+                reasons.push("Event was recorded for a different endpoint".to_string());
+            }
+        }
+        // A firehose takes every event, whatever the node was doing.
+        if notification.notification_type != NotificationType::Firehose {
+            if let Some(ref channel) = notification.channel {
+                if !touches_channel(&event.data, channel) {
+                    reasons.push("Event doesn't touch the endpoint's channel".to_string());
+                }
+            }
+            if event.severity != EventSeverity::Critical && is_maintenance_event(&event.data) {
+                reasons.push("Node was under maintenance and the event isn't critical".to_string());
+            }
+        }
+
+        RoutingDecision {
+            would_deliver: reasons.is_empty(),
+            reasons,
+        }
     }
 
-    /// Sends event to a Discord webhook.
-    async fn send_discord(
-        &self,
-        event: &Event,
-        notification: &Notification,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// Renders the exact body that would be POSTed to an endpoint of the given type.
+    pub fn render_payload(event: &Event, notification: &Notification) -> Value {
+        match notification.notification_type {
+            NotificationType::Webhook | NotificationType::Firehose => {
+                Self::render_webhook_payload(event, notification.payload_version)
+            }
+            NotificationType::Discord => Self::render_discord_payload(event),
+        }
+    }
+
+    /// Renders the latest webhook body and converts it down to the version
+    /// the endpoint was set up with.
+    fn render_webhook_payload(event: &Event, version: i64) -> Value {
+        let payload = Self::render_latest_webhook_payload(event);
+        match version {
+            1 => webhook_payload_v1(&payload),
+            _ => payload,
+        }
+    }
+
+    /// Version 2 keeps the node's label and maintenance state next to the
+    /// event instead of inside its data.
+    fn render_latest_webhook_payload(event: &Event) -> Value {
+        let mut data: Map<String, Value> = serde_json::from_str(&event.data).unwrap_or_default();
+        let label = data.remove(NODE_LABEL_KEY).unwrap_or(Value::Null);
+        let maintenance = data
+            .remove(MAINTENANCE_LABEL)
+            .and_then(|maintenance| maintenance.as_bool())
+            .unwrap_or(false);
+
+        json!({
+            "schema_version": LATEST_WEBHOOK_PAYLOAD_VERSION,
+            "event": {
+                "id": event.id,
+                "type": event.event_type.to_string(),
+                "severity": event.severity.to_string(),
+                "title": event.title,
+                "description": event.description,
+                "timestamp": event.timestamp,
+            },
+            "node": {
+                "id": event.node_id,
+                "alias": event.node_alias,
+                "display_name": label["display_name"],
+                "environment": label["environment"],
+            },
+            "maintenance": maintenance,
+            "data": data,
+        })
+    }
+
+    fn render_discord_payload(event: &Event) -> Value {
         let color = match event.severity {
-            crate::database::models::EventSeverity::Info => 0x00ff00, // Green
-            crate::database::models::EventSeverity::Warning => 0xffff00, // Yellow
-            crate::database::models::EventSeverity::Critical => 0xff0000, // Red
+            EventSeverity::Info => 0x00ff00,     // Green
+            EventSeverity::Warning => 0xffff00,  // Yellow
+            EventSeverity::Critical => 0xff0000, // Red
         };
 
         let embed = json!({
@@ -164,10 +484,20 @@ impl NotificationDispatcher {
                 },
                 {
                     "name": "Node",
-                    "value": if event.node_alias.is_empty() {
+                    "value": if let Some(display_name) = display_name(&event.data) {
+                        format!(
+                            "{} ({})",
+                            display_name,
+                            event.node_id.get(..8).unwrap_or(&event.node_id)
+                        )
+                    } else if event.node_alias.is_empty() {
                         event.node_id.clone()
                     } else {
-                        format!("{} ({})", event.node_alias, &event.node_id[..8])
+                        format!(
+                            "{} ({})",
+                            event.node_alias,
+                            event.node_id.get(..8).unwrap_or(&event.node_id)
+                        )
                     },
                     "inline": true
                 }
@@ -177,15 +507,68 @@ impl NotificationDispatcher {
             }
         });
 
-        let payload = json!({
+        json!({
             "embeds": [embed]
-        });
+        })
+    }
+
+    /// Sends an event to a specific notification endpoint.
+    pub async fn send_to_endpoint(
+        &self,
+        event: &Event,
+        notification: Notification,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match notification.notification_type {
+            NotificationType::Webhook | NotificationType::Firehose => {
+                self.send_webhook(event, &notification).await
+            }
+            NotificationType::Discord => self.send_discord(event, &notification).await,
+        }
+    }
+
+    /// Sends event to a webhook endpoint.
+    async fn send_webhook(
+        &self,
+        event: &Event,
+        notification: &Notification,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let payload = Self::render_webhook_payload(event, notification.payload_version);
+
+        let response = self
+            .endpoint_request(Method::POST, notification)
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            info!(
+                "Webhook notification sent successfully to {}",
+                notification.url
+            );
+        } else {
+            warn!(
+                "Webhook notification failed with status {}: {}",
+                response.status(),
+                notification.url
+            );
+            return Err(format!("Webhook endpoint returned {}", response.status()).into());
+        }
+
+        Ok(())
+    }
+
+    /// Sends event to a Discord webhook.
+    async fn send_discord(
+        &self,
+        event: &Event,
+        notification: &Notification,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let payload = Self::render_discord_payload(event);
 
         let response = self
-            .http_client
-            .post(&notification.url)
+            .endpoint_request(Method::POST, notification)
             .header("Content-Type", "application/json")
-            .header("User-Agent", "NodeGaze/1.0")
             .json(&payload)
             .send()
             .await?;
@@ -201,8 +584,195 @@ impl NotificationDispatcher {
                 response.status(),
                 notification.url
             );
+            return Err(format!("Discord endpoint returned {}", response.status()).into());
         }
 
         Ok(())
     }
 }
+
+/// Converts a version 2 webhook body to version 1, the flat body endpoints
+/// received before bodies were versioned.
+fn webhook_payload_v1(payload: &Value) -> Value {
+    let event = &payload["event"];
+    let node = &payload["node"];
+    let mut data = payload["data"].as_object().cloned().unwrap_or_default();
+    if !node["display_name"].is_null() || !node["environment"].is_null() {
+        data.insert(
+            NODE_LABEL_KEY.to_string(),
+            json!({
+                "display_name": node["display_name"],
+                "environment": node["environment"],
+            }),
+        );
+    }
+    if payload["maintenance"].as_bool() == Some(true) {
+        data.insert(MAINTENANCE_LABEL.to_string(), Value::Bool(true));
+    }
+
+    json!({
+        "schema_version": 1,
+        "event_id": event["id"],
+        "timestamp": event["timestamp"],
+        "event_type": event["type"].as_str().and_then(|t| t.parse::<EventType>().ok()),
+        "severity": event["severity"].as_str().and_then(|s| s.parse::<EventSeverity>().ok()),
+        "title": event["title"],
+        "description": event["description"],
+        "node_id": node["id"],
+        "node_alias": node["alias"],
+        "data": data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_touches_channel() {
+        let scid_int = (834_567u64 << 40) | (890 << 16) | 1;
+        let scid = normalize_channel("834567x890x1").unwrap();
+        assert_eq!(scid, scid_int.to_string());
+        let point = normalize_channel(&format!("{}:1", "AB".repeat(32))).unwrap();
+        assert_eq!(point, format!("{}:1", "ab".repeat(32)));
+        assert!(normalize_channel("not-a-channel").is_none());
+
+        let forward = json!({ "incoming_chan_id": 1, "outgoing_chan_id": scid_int }).to_string();
+        assert!(touches_channel(&forward, &scid));
+        assert!(!touches_channel(&forward, &point));
+
+        let closed = json!({ "chan_id": 7, "channel_point": point }).to_string();
+        assert!(touches_channel(&closed, &point));
+        assert!(!touches_channel(&closed, &scid));
+    }
+
+    #[test]
+    fn test_firehose_ignores_channel_and_maintenance() {
+        let mut event = crate::services::event_service::EventService::sample_event(
+            EventType::ForwardFailed,
+            "account",
+            "user",
+        );
+        event.data = crate::services::maintenance::label_event_data(&event.data);
+        let now = chrono::Utc::now();
+        let mut notification = Notification {
+            id: "endpoint".to_string(),
+            account_id: "account".to_string(),
+            user_id: "user".to_string(),
+            name: "warehouse".to_string(),
+            notification_type: NotificationType::Webhook,
+            url: "https://example.com/hook".to_string(),
+            channel: Some("1".to_string()),
+            payload_version: LATEST_WEBHOOK_PAYLOAD_VERSION,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+            is_deleted: false,
+            deleted_at: None,
+            timeout_secs: None,
+            headers: None,
+            verify_tls: true,
+        };
+
+        let decision = NotificationDispatcher::evaluate_routing(&event, &notification);
+        assert_eq!(decision.reasons.len(), 2);
+
+        notification.notification_type = NotificationType::Firehose;
+        assert!(NotificationDispatcher::evaluate_routing(&event, &notification).would_deliver);
+
+        notification.is_active = false;
+        assert!(!NotificationDispatcher::evaluate_routing(&event, &notification).would_deliver);
+    }
+
+    #[test]
+    fn test_fanned_out_event_reaches_only_its_endpoint() {
+        let mut event = crate::services::event_service::EventService::sample_event(
+            EventType::InvoiceSettled,
+            "account",
+            "user",
+        );
+        let now = chrono::Utc::now();
+        let endpoint = |id: &str| Notification {
+            id: id.to_string(),
+            account_id: "account".to_string(),
+            user_id: "user".to_string(),
+            name: id.to_string(),
+            notification_type: NotificationType::Webhook,
+            url: "https://example.com/hook".to_string(),
+            channel: None,
+            payload_version: LATEST_WEBHOOK_PAYLOAD_VERSION,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+            is_deleted: false,
+            deleted_at: None,
+            timeout_secs: None,
+            headers: None,
+            verify_tls: true,
+        };
+        let ids = |event: &Event| {
+            NotificationDispatcher::endpoints_for_event(event, vec![endpoint("a"), endpoint("b")])
+                .into_iter()
+                .map(|n| n.id)
+                .collect::<Vec<_>>()
+        };
+
+        event.notifications_id = Some("b".to_string());
+        assert_eq!(ids(&event), vec!["b"]);
+        event.notifications_id = None;
+        assert_eq!(ids(&event), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_validate_headers() {
+        let headers = HashMap::from([("Authorization".to_string(), "Bearer abc".to_string())]);
+        assert!(validate_headers(&headers).is_ok());
+
+        let reserved = HashMap::from([("Content-Type".to_string(), "text/plain".to_string())]);
+        assert!(validate_headers(&reserved).is_err());
+
+        let bad_name = HashMap::from([("X Token".to_string(), "abc".to_string())]);
+        assert!(validate_headers(&bad_name).is_err());
+
+        let bad_value = HashMap::from([("X-Token".to_string(), "a\nb".to_string())]);
+        assert!(validate_headers(&bad_value).is_err());
+    }
+
+    #[test]
+    fn test_webhook_payload_v1_keeps_flat_body() {
+        let mut event = crate::services::event_service::EventService::sample_event(
+            EventType::ChannelClosed,
+            "account",
+            "user",
+        );
+        let mut data: Map<String, Value> = serde_json::from_str(&event.data).unwrap();
+        data.insert(
+            NODE_LABEL_KEY.to_string(),
+            json!({ "display_name": "alice", "environment": null }),
+        );
+        data.insert(MAINTENANCE_LABEL.to_string(), Value::Bool(true));
+        event.data = Value::Object(data.clone()).to_string();
+
+        let latest = NotificationDispatcher::render_webhook_payload(&event, 2);
+        assert_eq!(latest["node"]["display_name"], "alice");
+        assert_eq!(latest["maintenance"], true);
+        assert!(latest["data"].get(NODE_LABEL_KEY).is_none());
+
+        let v1 = NotificationDispatcher::render_webhook_payload(&event, 1);
+        assert_eq!(
+            v1,
+            json!({
+                "schema_version": 1,
+                "event_id": event.id,
+                "timestamp": event.timestamp,
+                "event_type": event.event_type,
+                "severity": event.severity,
+                "title": event.title,
+                "description": event.description,
+                "node_id": event.node_id,
+                "node_alias": event.node_alias,
+                "data": data,
+            })
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Periodic health checks of notification endpoints.
+//!
+//! Each check asks the endpoint whether it is still there without delivering
+//! anything: a `HEAD` request for webhooks and a `GET` of the webhook for
+//! Discord, which answers with the webhook's details, sent with the
+//! endpoint's own timeout, headers and TLS settings. Endpoints that keep
+//! failing for longer than `NOTIFICATION_UNHEALTHY_AFTER_MINUTES` raise one
+//! event per outage, delivered through the account's other endpoints.
+
+use crate::config::Config;
+use crate::database::models::{
+    CreateEvent, EventSeverity, EventType, Notification, NotificationHealth, NotificationType,
+};
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::notification_health_repository::NotificationHealthRepository;
+use crate::repositories::notification_repository::NotificationRepository;
+use crate::services::event_service::EventService;
+use crate::services::notification_dispatcher::{NotificationDispatcher, endpoint_timeout_secs};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{Method, StatusCode};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Whether an endpoint answering with `status` is still usable. Webhooks
+/// needn't support `HEAD`, so any answer short of a server error will do; a
+/// Discord webhook that was deleted answers 404.
+pub fn responds(notification_type: &NotificationType, status: StatusCode) -> bool {
+    match notification_type {
+        NotificationType::Discord => status.is_success(),
+        NotificationType::Webhook | NotificationType::Firehose => !status.is_server_error(),
+    }
+}
+
+/// The health of an endpoint after a check, carrying the start of a failing
+/// run and whether it was reported over from the previous check.
+pub fn next_health(
+    previous: Option<&NotificationHealth>,
+    notification: &Notification,
+    outcome: Result<(), String>,
+    now: DateTime<Utc>,
+) -> NotificationHealth {
+    let failing = previous.filter(|health| !health.healthy);
+    let (healthy, last_error, failing_since, alerted_at) = match outcome {
+        Ok(()) => (true, None, None, None),
+        Err(error) => (
+            false,
+            Some(error),
+            Some(
+                failing
+                    .and_then(|health| health.failing_since)
+                    .unwrap_or(now),
+            ),
+            failing.and_then(|health| health.alerted_at),
+        ),
+    };
+
+    NotificationHealth {
+        notification_id: notification.id.clone(),
+        account_id: notification.account_id.clone(),
+        healthy,
+        last_error,
+        last_checked_at: now,
+        failing_since,
+        alerted_at,
+    }
+}
+
+/// Whether an endpoint has been failing for `threshold` without being
+/// reported yet.
+pub fn should_alert(health: &NotificationHealth, threshold: Duration, now: DateTime<Utc>) -> bool {
+    match (health.failing_since, health.alerted_at) {
+        (Some(since), None) => now - since >= threshold,
+        _ => false,
+    }
+}
+
+/// Checks every active endpoint of an account and reports those failing for
+/// longer than the configured period.
+pub async fn check_account_endpoints(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let threshold = Duration::minutes(
+        Config::from_env()
+            .map_err(|e| e.to_string())?
+            .notification_unhealthy_after_minutes,
+    );
+    let notifications = NotificationRepository::new(pool)
+        .get_notifications_by_account_id(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let repo = NotificationHealthRepository::new(pool);
+    let previous: HashMap<String, NotificationHealth> = repo
+        .get_for_account(account_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|health| (health.notification_id.clone(), health))
+        .collect();
+    let dispatcher = NotificationDispatcher::new();
+
+    let mut failures = Vec::new();
+    for notification in notifications.iter().filter(|n| n.is_active) {
+        let outcome = check_endpoint(&dispatcher, notification).await;
+        let now = Utc::now();
+        let mut health = next_health(previous.get(&notification.id), notification, outcome, now);
+
+        if should_alert(&health, threshold, now) {
+            match raise_unhealthy(pool, notification, &health).await {
+                Ok(()) => health.alerted_at = Some(now),
+                Err(e) => failures.push(format!("{}: {e}", notification.id)),
+            }
+        }
+        if let Err(e) = repo.upsert(&health).await {
+            failures.push(format!("{}: {e}", notification.id));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Notification health checks failed for {}",
+            failures.join("; ")
+        ))
+    }
+}
+
+/// Asks an endpoint whether it is still there.
+async fn check_endpoint(
+    dispatcher: &NotificationDispatcher,
+    notification: &Notification,
+) -> Result<(), String> {
+    let method = match notification.notification_type {
+        NotificationType::Discord => Method::GET,
+        NotificationType::Webhook | NotificationType::Firehose => Method::HEAD,
+    };
+    let response = dispatcher
+        .endpoint_request(method, notification)
+        .send()
+        .await
+        .map_err(|err| match err {
+            err if err.is_timeout() => {
+                format!(
+                    "Endpoint timeout after {} seconds",
+                    endpoint_timeout_secs(notification)
+                )
+            }
+            err if err.is_connect() => "Could not connect to endpoint".to_string(),
+            err => format!("Endpoint check failed: {err}"),
+        })?;
+
+    if responds(&notification.notification_type, response.status()) {
+        Ok(())
+    } else {
+        Err(format!("Endpoint returned {}", response.status()))
+    }
+}
+
+/// Raises a Warning event for an endpoint that has been failing too long. It
+/// is filed under the node of the endpoint's owner, or any of the account's
+/// nodes if the owner has none.
+async fn raise_unhealthy(
+    pool: &SqlitePool,
+    notification: &Notification,
+    health: &NotificationHealth,
+) -> Result<(), String> {
+    let credential_repo = CredentialRepository::new(pool);
+    let credential = match credential_repo
+        .get_credential_by_user_id(&notification.user_id)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        Some(credential) => Some(credential),
+        None => credential_repo
+            .get_credentials_by_account_id(&notification.account_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .next(),
+    };
+    let Some(credential) = credential else {
+        tracing::warn!(
+            "Notification endpoint {} is unhealthy, but account {} has no node to report it under",
+            notification.id,
+            notification.account_id
+        );
+        return Ok(());
+    };
+
+    let failing_since = health.failing_since.unwrap_or(health.last_checked_at);
+    let last_error = health.last_error.as_deref().unwrap_or("unknown error");
+    let event = CreateEvent {
+        id: Uuid::now_v7().to_string(),
+        account_id: notification.account_id.clone(),
+        user_id: notification.user_id.clone(),
+        node_id: credential.node_id,
+        node_alias: credential.node_alias,
+        event_type: EventType::NotificationEndpointUnhealthy,
+        severity: EventSeverity::Warning,
+        title: "Notification Endpoint Unhealthy".to_string(),
+        description: format!(
+            "Notification endpoint '{}' has been failing since {}: {last_error}",
+            notification.name,
+            failing_since.format("%Y-%m-%d %H:%M UTC")
+        ),
+        data: json!({
+            "notification_id": notification.id,
+            "notification_name": notification.name,
+            "notification_type": notification.notification_type,
+            "failing_since": failing_since,
+            "last_error": health.last_error,
+        })
+        .to_string(),
+        notifications_id: None,
+        timestamp: Utc::now(),
+    };
+
+    EventService::new(pool)
+        .create_and_dispatch_event(event)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(notification_type: NotificationType) -> Notification {
+        let now = Utc::now();
+        Notification {
+            id: "notification-1".to_string(),
+            account_id: "account-1".to_string(),
+            user_id: "user-1".to_string(),
+            name: "ops webhook".to_string(),
+            notification_type,
+            url: "https://example.com/hook".to_string(),
+            channel: None,
+            payload_version: 2,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+            is_deleted: false,
+            deleted_at: None,
+            timeout_secs: None,
+            headers: None,
+            verify_tls: true,
+        }
+    }
+
+    #[test]
+    fn test_responds_by_endpoint_type() {
+        assert!(responds(
+            &NotificationType::Webhook,
+            StatusCode::METHOD_NOT_ALLOWED
+        ));
+        assert!(!responds(
+            &NotificationType::Webhook,
+            StatusCode::BAD_GATEWAY
+        ));
+        assert!(responds(&NotificationType::Discord, StatusCode::OK));
+        assert!(!responds(&NotificationType::Discord, StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_failing_run_is_alerted_once() {
+        let endpoint = notification(NotificationType::Webhook);
+        let threshold = Duration::minutes(60);
+        let start = Utc::now();
+
+        let first = next_health(None, &endpoint, Err("down".to_string()), start);
+        assert_eq!(first.failing_since, Some(start));
+        assert!(!should_alert(&first, threshold, start));
+
+        let later = start + Duration::minutes(65);
+        let mut second = next_health(Some(&first), &endpoint, Err("down".to_string()), later);
+        assert_eq!(second.failing_since, Some(start));
+        assert!(should_alert(&second, threshold, later));
+
+        second.alerted_at = Some(later);
+        let third = next_health(
+            Some(&second),
+            &endpoint,
+            Err("down".to_string()),
+            later + Duration::minutes(5),
+        );
+        assert!(!should_alert(
+            &third,
+            threshold,
+            later + Duration::minutes(5)
+        ));
+
+        let recovered = next_health(
+            Some(&third),
+            &endpoint,
+            Ok(()),
+            later + Duration::minutes(10),
+        );
+        assert!(recovered.healthy);
+        assert_eq!(recovered.failing_since, None);
+        assert_eq!(recovered.alerted_at, None);
+    }
+}
@@ -3,20 +3,45 @@
 //! Handles all notification-related business operations
 
 use crate::database::models::{
-    CreateNotification, CreateNotificationRequest, EventResponse, Notification,
-    UpdateNotificationRequest, User,
+    CreateNotification, CreateNotificationRequest, EventResponse, EventType, FirehoseBatch,
+    FirehoseQuery, JobResponse, JobType, Notification, NotificationHealth, NotificationResponse,
+    NotificationTestResult, NotificationType, TestNotificationRequest, UpdateNotificationRequest,
+    User,
 };
 use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
 use crate::repositories::event_repository::EventRepository;
+use crate::repositories::notification_health_repository::NotificationHealthRepository;
 use crate::repositories::notification_repository::NotificationRepository;
-use chrono::Utc;
-use reqwest::Client;
+use crate::services::event_service::EventService;
+use crate::services::job_queue::JobQueue;
+use crate::services::notification_dispatcher::{
+    LATEST_WEBHOOK_PAYLOAD_VERSION, NotificationDeliveryJob, NotificationDispatcher,
+    endpoint_headers, normalize_channel, validate_headers,
+};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
 use serde_json::json;
 use sqlx::SqlitePool;
-use std::time::Duration;
+use std::collections::HashMap;
 use uuid::Uuid;
 use validator::Validate;
 
+/// How long the test request to a new webhook waits, unless the endpoint
+/// sets its own timeout.
+const TEST_TIMEOUT_SECS: u64 = 5;
+
+/// How long a deleted notification can be restored.
+pub const RESTORE_WINDOW_DAYS: i64 = 30;
+
+/// Until when a deleted notification can be restored; `None` unless deleted.
+pub fn restorable_until(notification: &Notification) -> Option<DateTime<Utc>> {
+    notification
+        .deleted_at
+        .filter(|_| notification.is_deleted)
+        .map(|deleted_at| deleted_at + chrono::Duration::days(RESTORE_WINDOW_DAYS))
+}
+
 pub struct NotificationService<'a> {
     /// Shared database connection pool
     pool: &'a SqlitePool,
@@ -53,9 +78,33 @@ impl<'a> NotificationService<'a> {
             return Err(ServiceError::validation(error_messages.join(", ")));
         }
 
+        let headers = create_request.headers.unwrap_or_default();
+        validate_headers(&headers).map_err(ServiceError::validation)?;
+        let timeout_secs = create_request.timeout_secs;
+        let verify_tls = create_request.verify_tls.unwrap_or(true);
+
         // Validate URL based on notification type
-        self.validate_url(&create_request.url, &create_request.notification_type)
-            .await?;
+        self.validate_url(
+            &create_request.url,
+            &create_request.notification_type,
+            timeout_secs,
+            &headers,
+            verify_tls,
+        )
+        .await?;
+        let channel = create_request
+            .channel
+            .as_deref()
+            .map(Self::parse_channel)
+            .transpose()?;
+        if channel.is_some() {
+            Self::check_channel_allowed(&create_request.notification_type)?;
+        }
+        let payload_version = Self::parse_payload_version(
+            create_request
+                .payload_version
+                .unwrap_or(LATEST_WEBHOOK_PAYLOAD_VERSION),
+        )?;
 
         let create_notification = CreateNotification {
             id: Uuid::now_v7().to_string(),
@@ -64,6 +113,11 @@ impl<'a> NotificationService<'a> {
             name: create_request.name,
             notification_type: create_request.notification_type,
             url: create_request.url,
+            channel,
+            payload_version,
+            timeout_secs,
+            headers: Self::stored_headers(&headers)?,
+            verify_tls,
         };
 
         let repo = NotificationRepository::new(self.pool);
@@ -72,14 +126,37 @@ impl<'a> NotificationService<'a> {
         Ok(notification)
     }
 
-    /// Retrieves all notifications for a user's account.
+    /// Retrieves all notifications for a user's account, each with its latest
+    /// health check. Deleted ones follow when `include_deleted` is set.
     pub async fn get_notifications_for_account(
         &self,
         account_id: &str,
-    ) -> ServiceResult<Vec<Notification>> {
+        include_deleted: bool,
+    ) -> ServiceResult<Vec<NotificationResponse>> {
         let repo = NotificationRepository::new(self.pool);
-        let notifications = repo.get_notifications_by_account_id(account_id).await?;
-        Ok(notifications)
+        let mut notifications = repo.get_notifications_by_account_id(account_id).await?;
+        if include_deleted {
+            notifications.extend(
+                repo.get_deleted_notifications_by_account_id(account_id)
+                    .await?,
+            );
+        }
+        let mut health: HashMap<String, NotificationHealth> =
+            NotificationHealthRepository::new(self.pool)
+                .get_for_account(account_id)
+                .await?
+                .into_iter()
+                .map(|health| (health.notification_id.clone(), health))
+                .collect();
+
+        Ok(notifications
+            .into_iter()
+            .map(|notification| NotificationResponse {
+                health: health.remove(&notification.id),
+                restorable_until: restorable_until(&notification),
+                notification,
+            })
+            .collect())
     }
 
     /// Retrieves a notification by ID with account verification.
@@ -131,10 +208,46 @@ impl<'a> NotificationService<'a> {
         // First verify the notification exists and belongs to the account
         let existing = self.get_notification_required(id, account_id).await?;
 
-        // Validate URL if provided
-        if let Some(ref url) = update_request.url {
-            self.validate_url(url, &existing.notification_type).await?;
+        if let Some(ref headers) = update_request.headers {
+            validate_headers(headers).map_err(ServiceError::validation)?;
         }
+        let timeout_secs = update_request
+            .timeout_secs
+            .map(|secs| Some(secs).filter(|secs| *secs > 0));
+
+        // Validate URL if provided, or the endpoint again if how it's reached changed
+        if update_request.url.is_some()
+            || update_request.headers.is_some()
+            || update_request.timeout_secs.is_some()
+            || update_request.verify_tls.is_some()
+        {
+            let existing_headers = endpoint_headers(&existing);
+            self.validate_url(
+                update_request.url.as_deref().unwrap_or(&existing.url),
+                &existing.notification_type,
+                timeout_secs.unwrap_or(existing.timeout_secs),
+                update_request.headers.as_ref().unwrap_or(&existing_headers),
+                update_request.verify_tls.unwrap_or(existing.verify_tls),
+            )
+            .await?;
+        }
+        let headers = update_request
+            .headers
+            .as_ref()
+            .map(Self::stored_headers)
+            .transpose()?;
+        let channel = match update_request.channel.as_deref().map(str::trim) {
+            Some("") => Some(None),
+            Some(channel) => {
+                Self::check_channel_allowed(&existing.notification_type)?;
+                Some(Some(Self::parse_channel(channel)?))
+            }
+            None => None,
+        };
+        let payload_version = update_request
+            .payload_version
+            .map(Self::parse_payload_version)
+            .transpose()?;
 
         let repo = NotificationRepository::new(self.pool);
         let updated = repo
@@ -142,7 +255,12 @@ impl<'a> NotificationService<'a> {
                 id,
                 update_request.name.as_deref(),
                 update_request.url.as_deref(),
+                channel.as_ref().map(Option::as_deref),
+                payload_version,
                 update_request.is_active,
+                timeout_secs,
+                headers.as_ref().map(Option::as_deref),
+                update_request.verify_tls,
             )
             .await?;
 
@@ -154,17 +272,85 @@ impl<'a> NotificationService<'a> {
         self.get_notification_required(id, account_id).await
     }
 
-    /// Deletes a notification.
-    pub async fn delete_notification(&self, id: &str, account_id: &str) -> ServiceResult<()> {
+    /// Soft deletes a notification. It can be restored for
+    /// [`RESTORE_WINDOW_DAYS`], and its delivery history stays readable.
+    pub async fn delete_notification(
+        &self,
+        id: &str,
+        account_id: &str,
+        user_id: &str,
+    ) -> ServiceResult<()> {
         // Verify the notification exists and belongs to the account
-        self.get_notification_required(id, account_id).await?;
+        let notification = self.get_notification_required(id, account_id).await?;
 
         let repo = NotificationRepository::new(self.pool);
         repo.delete_notification(id).await?;
 
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "notification_deleted",
+                "notification",
+                Some(id),
+                &json!({ "name": notification.name }),
+            )
+            .await?;
+
         Ok(())
     }
 
+    /// Restores a notification deleted within the last [`RESTORE_WINDOW_DAYS`].
+    pub async fn restore_notification(
+        &self,
+        id: &str,
+        account_id: &str,
+        user_id: &str,
+    ) -> ServiceResult<Notification> {
+        let notification = self.get_notification_with_deleted(id, account_id).await?;
+        if !notification.is_deleted {
+            return Err(ServiceError::invalid_operation(
+                "Notification is not deleted",
+            ));
+        }
+        if restorable_until(&notification).is_none_or(|until| until < Utc::now()) {
+            return Err(ServiceError::invalid_operation(format!(
+                "Notifications can only be restored within {RESTORE_WINDOW_DAYS} days of being deleted"
+            )));
+        }
+
+        let repo = NotificationRepository::new(self.pool);
+        if !repo.restore_notification(id).await? {
+            return Err(ServiceError::not_found("Notification", id));
+        }
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "notification_restored",
+                "notification",
+                Some(id),
+                &json!({ "name": notification.name }),
+            )
+            .await?;
+
+        self.get_notification_required(id, account_id).await
+    }
+
+    /// Retrieves a notification of the account, deleted or not.
+    async fn get_notification_with_deleted(
+        &self,
+        id: &str,
+        account_id: &str,
+    ) -> ServiceResult<Notification> {
+        NotificationRepository::new(self.pool)
+            .get_notification_by_id_with_deleted(id)
+            .await?
+            .filter(|notification| notification.account_id == account_id)
+            .ok_or_else(|| ServiceError::not_found("Notification", id))
+    }
+
     /// Gets events dispatched to a specific notification endpoint.
     pub async fn get_events_for_notification(
         &self,
@@ -173,8 +359,9 @@ impl<'a> NotificationService<'a> {
         limit: Option<i64>,
         offset: Option<i64>,
     ) -> ServiceResult<Vec<EventResponse>> {
-        // First verify the notification belongs to the account
-        self.get_notification_required(notifications_id, account_id)
+        // First verify the notification belongs to the account; deleted
+        // endpoints keep their history
+        self.get_notification_with_deleted(notifications_id, account_id)
             .await?;
 
         let limit = limit.unwrap_or(50).min(1000);
@@ -194,8 +381,9 @@ impl<'a> NotificationService<'a> {
         notifications_id: &str,
         account_id: &str,
     ) -> ServiceResult<i64> {
-        // First verify the notification belongs to the account
-        self.get_notification_required(notifications_id, account_id)
+        // First verify the notification belongs to the account; deleted
+        // endpoints keep their history
+        self.get_notification_with_deleted(notifications_id, account_id)
             .await?;
 
         let event_repo = EventRepository::new(self.pool);
@@ -206,11 +394,220 @@ impl<'a> NotificationService<'a> {
         Ok(count)
     }
 
-    /// Validates URL based on notification type.
+    /// Lists what a firehose endpoint was sent after the `after` cursor, so a
+    /// consumer can fill gaps left by deliveries that ran out of retries.
+    pub async fn firehose_catch_up(
+        &self,
+        id: &str,
+        account_id: &str,
+        query: FirehoseQuery,
+    ) -> ServiceResult<FirehoseBatch> {
+        let notification = self.get_notification_required(id, account_id).await?;
+        if notification.notification_type != NotificationType::Firehose {
+            return Err(ServiceError::validation(
+                "Only firehose endpoints can be caught up on",
+            ));
+        }
+
+        let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+        let events = EventRepository::new(self.pool)
+            .get_events_by_notification_id_after(
+                id,
+                query.after.as_deref().unwrap_or_default(),
+                limit,
+            )
+            .await?;
+
+        Ok(FirehoseBatch {
+            has_more: events.len() as i64 == limit,
+            next_cursor: events.last().map(|event| event.id.clone()).or(query.after),
+            events: events
+                .iter()
+                .map(|event| NotificationDispatcher::render_payload(event, &notification))
+                .collect(),
+        })
+    }
+
+    /// Previews what a notification endpoint would receive for an event.
+    ///
+    /// Renders the payload for a historical event (or a synthetic sample) and
+    /// evaluates the live routing rules against it. Nothing is sent unless the
+    /// request explicitly opts out of `dry_run`.
+    pub async fn test_notification(
+        &self,
+        id: &str,
+        request: TestNotificationRequest,
+        user: &User,
+    ) -> ServiceResult<NotificationTestResult> {
+        let notification = self.get_notification_required(id, &user.account_id).await?;
+
+        let (mut event, event_source) = match request.event_id {
+            Some(ref event_id) => {
+                let event_repo = EventRepository::new(self.pool);
+                let event = event_repo
+                    .get_event_by_id(event_id, &user.account_id)
+                    .await?
+                    .ok_or_else(|| ServiceError::not_found("Event", event_id))?;
+                (event, "historical")
+            }
+            None => {
+                let event_type = request.event_type.unwrap_or(EventType::InvoiceSettled);
+                (
+                    EventService::sample_event(event_type, &user.account_id, &user.id),
+                    "sample",
+                )
+            }
+        };
+
+        // Stored events are pinned to the endpoint they were fanned out to; a
+        // preview asks whether *this* endpoint would receive it today.
+        event.notifications_id = Some(notification.id.clone());
+
+        let decision = NotificationDispatcher::evaluate_routing(&event, &notification);
+        let payload = NotificationDispatcher::render_payload(&event, &notification);
+
+        let (delivered, delivery_error) = if request.dry_run {
+            (None, None)
+        } else {
+            let dispatcher = NotificationDispatcher::new();
+            match dispatcher
+                .send_to_endpoint(&event, notification.clone())
+                .await
+            {
+                Ok(_) => (Some(true), None),
+                Err(e) => (Some(false), Some(e.to_string())),
+            }
+        };
+
+        Ok(NotificationTestResult {
+            notification_id: notification.id,
+            notification_type: notification.notification_type,
+            dry_run: request.dry_run,
+            event_source: event_source.to_string(),
+            event: EventResponse::from(event),
+            payload,
+            would_deliver: decision.would_deliver,
+            reasons: decision.reasons,
+            delivered,
+            delivery_error,
+        })
+    }
+
+    /// Queues an event to be delivered again, to one endpoint or to every
+    /// endpoint the routing rules would send it to today.
+    ///
+    /// The deliveries go through the job queue like retries, so each one is
+    /// recorded in the delivery log and retried if it fails.
+    pub async fn redispatch_event(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        event_id: &str,
+        notification_id: Option<&str>,
+    ) -> ServiceResult<Vec<JobResponse>> {
+        let event = EventRepository::new(self.pool)
+            .get_event_by_id(event_id, account_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Event", event_id))?;
+
+        let notifications = match notification_id {
+            Some(id) => vec![self.get_notification_required(id, account_id).await?],
+            None => {
+                NotificationRepository::new(self.pool)
+                    .get_notifications_by_account_id(account_id)
+                    .await?
+            }
+        };
+
+        let mut targets = Vec::new();
+        for notification in notifications {
+            let decision = NotificationDispatcher::evaluate_routing(&event, &notification);
+            if decision.would_deliver {
+                targets.push(notification.id);
+            } else if notification_id.is_some() {
+                return Err(ServiceError::validation(format!(
+                    "Notification endpoint would not receive this event: {}",
+                    decision.reasons.join(", ")
+                )));
+            }
+        }
+        if targets.is_empty() {
+            return Err(ServiceError::validation(
+                "No notification endpoint would receive this event",
+            ));
+        }
+
+        let queue = JobQueue::new(self.pool);
+        let mut jobs = Vec::with_capacity(targets.len());
+        for notification_id in targets {
+            let job = queue
+                .enqueue(
+                    JobType::NotificationDelivery,
+                    &NotificationDeliveryJob {
+                        event_id: event.id.clone(),
+                        account_id: account_id.to_string(),
+                        notification_id,
+                        redispatched_by: Some(user_id.to_string()),
+                    },
+                    Some(account_id),
+                    None,
+                )
+                .await?;
+            jobs.push(JobResponse::from(job));
+        }
+
+        Ok(jobs)
+    }
+
+    /// Parses the channel an endpoint is limited to.
+    fn parse_channel(channel: &str) -> ServiceResult<String> {
+        normalize_channel(channel).ok_or_else(|| {
+            ServiceError::validation(
+                "Channel must be a short channel id or a channel point (txid:output)",
+            )
+        })
+    }
+
+    /// Firehose endpoints receive every event, so they can't be limited to a channel.
+    fn check_channel_allowed(notification_type: &NotificationType) -> ServiceResult<()> {
+        if *notification_type == NotificationType::Firehose {
+            return Err(ServiceError::validation(
+                "Firehose endpoints can't be limited to a channel",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that webhook bodies of `version` can still be rendered.
+    fn parse_payload_version(version: i64) -> ServiceResult<i64> {
+        if (1..=LATEST_WEBHOOK_PAYLOAD_VERSION).contains(&version) {
+            Ok(version)
+        } else {
+            Err(ServiceError::validation(format!(
+                "Payload version must be between 1 and {LATEST_WEBHOOK_PAYLOAD_VERSION}"
+            )))
+        }
+    }
+
+    /// Serializes an endpoint's extra headers for storage; `None` without any.
+    fn stored_headers(headers: &HashMap<String, String>) -> ServiceResult<Option<String>> {
+        if headers.is_empty() {
+            return Ok(None);
+        }
+        serde_json::to_string(headers)
+            .map(Some)
+            .map_err(|e| ServiceError::internal_error(format!("Failed to store headers: {e}")))
+    }
+
+    /// Validates URL based on notification type, reaching webhooks with the
+    /// endpoint's delivery settings.
     async fn validate_url(
         &self,
         url: &str,
         notification_type: &crate::database::models::NotificationType,
+        timeout_secs: Option<i64>,
+        headers: &HashMap<String, String>,
+        verify_tls: bool,
     ) -> ServiceResult<()> {
         match notification_type {
             crate::database::models::NotificationType::Discord => {
@@ -220,23 +617,28 @@ impl<'a> NotificationService<'a> {
                     ));
                 }
             }
-            crate::database::models::NotificationType::Webhook => {
-                self.test_webhook_connection(url).await?;
+            crate::database::models::NotificationType::Webhook
+            | crate::database::models::NotificationType::Firehose => {
+                self.test_webhook_connection(url, timeout_secs, headers, verify_tls)
+                    .await?;
             }
         }
         Ok(())
     }
 
-    async fn test_webhook_connection(&self, url: &str) -> ServiceResult<()> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(5))
-            .build()
-            .map_err(|err| ServiceError::InternalError {
-                message: format!("HTTP client setup failed: {err}"),
-            })?;
-
-        let response = client
-            .post(url)
+    async fn test_webhook_connection(
+        &self,
+        url: &str,
+        timeout_secs: Option<i64>,
+        headers: &HashMap<String, String>,
+        verify_tls: bool,
+    ) -> ServiceResult<()> {
+        let timeout_secs = timeout_secs
+            .and_then(|secs| u64::try_from(secs).ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(TEST_TIMEOUT_SECS);
+        let response = NotificationDispatcher::new()
+            .request(Method::POST, url, timeout_secs, headers, verify_tls)
             .json(&json!({
                 "event": "Ping",
                 "timestamp": Utc::now().to_rfc3339()
@@ -245,7 +647,9 @@ impl<'a> NotificationService<'a> {
             .await
             .map_err(|err| ServiceError::ExternalService {
                 message: match err {
-                    err if err.is_timeout() => "Webhook timeout after 5 seconds".into(),
+                    err if err.is_timeout() => {
+                        format!("Webhook timeout after {timeout_secs} seconds")
+                    }
                     err if err.is_connect() => "Could not connect to webhook server".into(),
                     _ => format!("Webhook communication failed: {err}"),
                 },
@@ -260,3 +664,39 @@ impl<'a> NotificationService<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restorable_until_deleted_only() {
+        let now = Utc::now();
+        let mut notification = Notification {
+            id: "notification-1".to_string(),
+            account_id: "account-1".to_string(),
+            user_id: "user-1".to_string(),
+            name: "ops webhook".to_string(),
+            notification_type: NotificationType::Webhook,
+            url: "https://example.com/hook".to_string(),
+            channel: None,
+            payload_version: LATEST_WEBHOOK_PAYLOAD_VERSION,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+            is_deleted: false,
+            deleted_at: None,
+            timeout_secs: None,
+            headers: None,
+            verify_tls: true,
+        };
+        assert_eq!(restorable_until(&notification), None);
+
+        notification.is_deleted = true;
+        notification.deleted_at = Some(now);
+        assert_eq!(
+            restorable_until(&notification),
+            Some(now + chrono::Duration::days(RESTORE_WINDOW_DAYS))
+        );
+    }
+}
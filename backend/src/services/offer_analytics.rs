@@ -0,0 +1,189 @@
+//! Performance of BOLT12 offers.
+//!
+//! CLN answers every invoice request for an offer with an invoice tagged with
+//! the offer id, so the node's invoices are the whole record: one per
+//! request, paid ones being the payments. Stats are computed from them on
+//! each call rather than stored.
+
+use crate::database::models::{OfferStats, OfferStatsPoint, OfferStatsQuery};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::utils::{InvoiceStatus, OfferInvoice};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, HashSet};
+
+const DEFAULT_OFFER_DAYS: i64 = 30;
+
+const MAX_OFFER_DAYS: i64 = 365;
+
+/// Checks that `offer_id` looks like an offer id (a 32-byte hex hash).
+pub fn validate_offer_id(offer_id: &str) -> ServiceResult<()> {
+    if offer_id.len() == 64 && offer_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(ServiceError::validation(
+            "Offer ID must be 64 hexadecimal characters",
+        ))
+    }
+}
+
+/// Summarizes the invoices issued for an offer as of `now`, with payments
+/// bucketed by their day in `tz`.
+pub fn offer_stats(
+    offer_id: &str,
+    invoices: &[OfferInvoice],
+    query: OfferStatsQuery,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> OfferStats {
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_OFFER_DAYS)
+        .clamp(1, MAX_OFFER_DAYS);
+
+    let paid: Vec<&OfferInvoice> = invoices
+        .iter()
+        .filter(|invoice| matches!(invoice.state, InvoiceStatus::Settled))
+        .collect();
+
+    let notes: HashSet<&str> = paid
+        .iter()
+        .filter_map(|invoice| invoice.payer_note.as_deref())
+        .filter(|note| !note.trim().is_empty())
+        .collect();
+    let without_note = paid
+        .iter()
+        .filter(|invoice| {
+            invoice
+                .payer_note
+                .as_deref()
+                .is_none_or(|note| note.trim().is_empty())
+        })
+        .count();
+
+    let today = now.with_timezone(&tz).date_naive();
+    let first_day = today - Duration::days(days - 1);
+    let mut by_day: BTreeMap<NaiveDate, OfferStatsPoint> = first_day
+        .iter_days()
+        .take(days as usize)
+        .map(|date| {
+            (
+                date,
+                OfferStatsPoint {
+                    date,
+                    payments: 0,
+                    volume_sat: 0,
+                },
+            )
+        })
+        .collect();
+    for invoice in &paid {
+        let Some(date) = invoice
+            .paid_at
+            .and_then(|paid_at| DateTime::from_timestamp(paid_at as i64, 0))
+            .map(|paid_at| paid_at.with_timezone(&tz).date_naive())
+        else {
+            continue;
+        };
+        if let Some(point) = by_day.get_mut(&date) {
+            point.payments += 1;
+            point.volume_sat += received_sat(invoice);
+        }
+    }
+
+    OfferStats {
+        offer_id: offer_id.to_string(),
+        days,
+        invoice_requests: invoices.len() as u64,
+        payments: paid.len() as u64,
+        volume_sat: paid.iter().map(|invoice| received_sat(invoice)).sum(),
+        unique_payers: (notes.len() + without_note) as u64,
+        series: by_day.into_values().collect(),
+    }
+}
+
+fn received_sat(invoice: &OfferInvoice) -> u64 {
+    invoice.amount_received_msat.unwrap_or(0) / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice(state: InvoiceStatus, paid_at: Option<u64>, note: Option<&str>) -> OfferInvoice {
+        OfferInvoice {
+            state,
+            amount_received_msat: paid_at.map(|_| 10_000_000),
+            paid_at,
+            payer_note: note.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_offer_stats() {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let today = now.timestamp() as u64;
+        let yesterday = today - 86_400;
+        let invoices = vec![
+            invoice(InvoiceStatus::Settled, Some(today), Some("alice")),
+            invoice(InvoiceStatus::Settled, Some(yesterday), Some("alice")),
+            invoice(InvoiceStatus::Settled, Some(today), None),
+            invoice(InvoiceStatus::Open, None, Some("bob")),
+            invoice(InvoiceStatus::Expired, None, None),
+        ];
+
+        let stats = offer_stats(
+            &"ab".repeat(32),
+            &invoices,
+            OfferStatsQuery { days: Some(3) },
+            now,
+            chrono_tz::UTC,
+        );
+        assert_eq!(stats.invoice_requests, 5);
+        assert_eq!(stats.payments, 3);
+        assert_eq!(stats.volume_sat, 30_000);
+        assert_eq!(stats.unique_payers, 2);
+        let payments: Vec<u64> = stats.series.iter().map(|point| point.payments).collect();
+        assert_eq!(payments, vec![0, 1, 2]);
+        assert_eq!(stats.series[2].date, now.date_naive());
+    }
+
+    #[test]
+    fn test_offer_stats_buckets_by_account_day() {
+        // 2023-11-14 22:13 UTC, already the 15th in Tokyo
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        // 2023-11-14 00:30 UTC, 09:30 in Tokyo
+        let invoices = vec![invoice(
+            InvoiceStatus::Settled,
+            Some(1_700_000_000 - 78_200),
+            None,
+        )];
+        let series = |tz| {
+            offer_stats(
+                &"ab".repeat(32),
+                &invoices,
+                OfferStatsQuery { days: Some(2) },
+                now,
+                tz,
+            )
+            .series
+        };
+
+        let utc = series(chrono_tz::UTC);
+        assert_eq!(utc[1].date, NaiveDate::from_ymd_opt(2023, 11, 14).unwrap());
+        assert_eq!(utc[1].payments, 1);
+        let tokyo = series(chrono_tz::Asia::Tokyo);
+        assert_eq!(
+            tokyo[1].date,
+            NaiveDate::from_ymd_opt(2023, 11, 15).unwrap()
+        );
+        assert_eq!(tokyo[0].payments, 1);
+        assert_eq!(tokyo[1].payments, 0);
+    }
+
+    #[test]
+    fn test_validate_offer_id() {
+        assert!(validate_offer_id(&"0f".repeat(32)).is_ok());
+        assert!(validate_offer_id("lno1qcp4256ypq").is_err());
+    }
+}
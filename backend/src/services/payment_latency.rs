@@ -0,0 +1,400 @@
+//! Latency tracking for outgoing payments.
+//!
+//! The `PaymentLatency` scheduled task copies the settle time of every new
+//! outgoing payment into SQLite, then compares the last day's p95 with the
+//! week before it and raises a Warning event when payments got markedly
+//! slower. The analytics endpoint groups the recorded samples by destination
+//! and by the channel they left through.
+
+use crate::database::models::{
+    CreateEvent, EventSeverity, EventType, LatencyStats, PaymentLatencyQuery, PaymentLatencyReport,
+    PaymentLatencySample,
+};
+use crate::errors::ServiceResult;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::graph_repository::GraphRepository;
+use crate::repositories::maintenance_repository::MaintenanceRepository;
+use crate::repositories::payment_latency_repository::PaymentLatencyRepository;
+use crate::services::event_service::EventService;
+use crate::services::graph_sync::percentile;
+use crate::services::maintenance::covers;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use bitcoin::secp256k1::PublicKey;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+const DEFAULT_LATENCY_DAYS: i64 = 7;
+
+/// Samples are kept this long, which also bounds the report window
+const MAX_LATENCY_DAYS: i64 = 90;
+
+/// Recent window whose p95 is compared with the baseline
+const RECENT_WINDOW_HOURS: i64 = 24;
+
+/// Days before the recent window that make up the baseline
+const BASELINE_DAYS: i64 = 7;
+
+/// Fewer samples than this in either window say nothing about a trend
+const MIN_SAMPLES: usize = 10;
+
+/// How many times slower the recent p95 must be to count as degraded
+const DEGRADATION_FACTOR: f64 = 2.0;
+
+/// Returns the recent and baseline p95 when the recent one is at least
+/// `DEGRADATION_FACTOR` times slower. Both lists must be sorted.
+pub fn detect_degradation(recent: &[i64], baseline: &[i64]) -> Option<(i64, i64)> {
+    if recent.len() < MIN_SAMPLES || baseline.len() < MIN_SAMPLES {
+        return None;
+    }
+    let recent_p95 = percentile(recent, 95.0)?;
+    let baseline_p95 = percentile(baseline, 95.0)?;
+    (recent_p95 as f64 >= baseline_p95.max(1) as f64 * DEGRADATION_FACTOR)
+        .then_some((recent_p95, baseline_p95))
+}
+
+/// Percentiles of a group of samples. Returns None for an empty group.
+pub fn latency_stats(
+    key: String,
+    alias: Option<String>,
+    samples: &[&PaymentLatencySample],
+) -> Option<LatencyStats> {
+    let mut settle: Vec<i64> = samples.iter().map(|s| s.settle_ms).collect();
+    let mut htlc: Vec<i64> = samples.iter().filter_map(|s| s.htlc_ms).collect();
+    settle.sort_unstable();
+    htlc.sort_unstable();
+
+    Some(LatencyStats {
+        key,
+        alias,
+        sample_count: settle.len(),
+        p50_ms: percentile(&settle, 50.0)?,
+        p95_ms: percentile(&settle, 95.0)?,
+        htlc_p50_ms: percentile(&htlc, 50.0),
+        htlc_p95_ms: percentile(&htlc, 95.0),
+    })
+}
+
+/// Groups samples by `key` and returns their stats, busiest group first.
+fn group_stats<'s>(
+    samples: &'s [PaymentLatencySample],
+    key: impl Fn(&'s PaymentLatencySample) -> Option<&'s String>,
+    alias: impl Fn(&str) -> Option<String>,
+) -> Vec<LatencyStats> {
+    let mut groups: HashMap<&String, Vec<&PaymentLatencySample>> = HashMap::new();
+    for sample in samples {
+        if let Some(key) = key(sample) {
+            groups.entry(key).or_default().push(sample);
+        }
+    }
+
+    let mut stats: Vec<LatencyStats> = groups
+        .into_iter()
+        .filter_map(|(key, group)| latency_stats(key.clone(), alias(key.as_str()), &group))
+        .collect();
+    stats.sort_by(|a, b| {
+        b.sample_count
+            .cmp(&a.sample_count)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    stats
+}
+
+/// Service layer for reading recorded payment latencies.
+pub struct PaymentLatencyService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> PaymentLatencyService<'a> {
+    /// Creates a new PaymentLatencyService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Reports p50/p95 settle times of the node's payments over the requested
+    /// number of days, overall, per destination and per first hop.
+    pub async fn get_latency(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: PaymentLatencyQuery,
+    ) -> ServiceResult<PaymentLatencyReport> {
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_LATENCY_DAYS)
+            .clamp(1, MAX_LATENCY_DAYS);
+        let samples = PaymentLatencyRepository::new(self.pool)
+            .get_samples_since(account_id, node_id, Utc::now() - Duration::days(days))
+            .await?;
+
+        let graph_repo = GraphRepository::new(self.pool);
+        let aliases: HashMap<String, String> = graph_repo
+            .get_nodes(node_id)
+            .await?
+            .into_iter()
+            .map(|node| (node.pubkey, node.alias))
+            .collect();
+        let channel_peers = graph_repo.get_channel_peers(node_id, node_id).await?;
+
+        let mut settle: Vec<i64> = samples.iter().map(|s| s.settle_ms).collect();
+        settle.sort_unstable();
+
+        Ok(PaymentLatencyReport {
+            days,
+            sample_count: samples.len(),
+            p50_ms: percentile(&settle, 50.0),
+            p95_ms: percentile(&settle, 95.0),
+            by_destination: group_stats(
+                &samples,
+                |s| s.destination.as_ref(),
+                |pubkey| aliases.get(pubkey).cloned(),
+            ),
+            by_first_hop: group_stats(
+                &samples,
+                |s| s.first_hop_channel_id.as_ref(),
+                |channel_id| {
+                    channel_peers
+                        .get(channel_id)
+                        .and_then(|peer| aliases.get(peer))
+                        .cloned()
+                },
+            ),
+        })
+    }
+}
+
+/// Records new payment latencies for every node of an account and warns
+/// about nodes whose payments slowed down.
+pub async fn record_account_latencies(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let credentials = CredentialRepository::new(pool)
+        .get_credentials_by_account_id(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut failures = Vec::new();
+    for credential in credentials {
+        let user_id = credential.user_id.clone();
+        let node_alias = credential.node_alias.clone();
+        let node_credentials = NodeCredentials::from(credential);
+
+        if let Err(e) =
+            record_node_latencies(pool, account_id, &user_id, &node_alias, &node_credentials).await
+        {
+            failures.push(format!("{}: {e}", node_credentials.node_id));
+        }
+    }
+
+    let cutoff = Utc::now() - Duration::days(MAX_LATENCY_DAYS);
+    if let Err(e) = PaymentLatencyRepository::new(pool)
+        .delete_samples_before(account_id, cutoff)
+        .await
+    {
+        failures.push(format!("pruning: {e}"));
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Payment latency recording failed for {}",
+            failures.join("; ")
+        ))
+    }
+}
+
+/// Stores the payments a node settled since its last recorded one, then
+/// checks its latency trend if anything new came in.
+async fn record_node_latencies(
+    pool: &SqlitePool,
+    account_id: &str,
+    user_id: &str,
+    node_alias: &str,
+    node_credentials: &NodeCredentials,
+) -> Result<(), String> {
+    let node_id = &node_credentials.node_id;
+    let repo = PaymentLatencyRepository::new(pool);
+    let since = repo
+        .get_latest_settled_at(account_id, node_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| Utc::now() - Duration::days(BASELINE_DAYS + 1));
+
+    let public_key = PublicKey::from_str(node_id).map_err(|e| e.to_string())?;
+    let client = create_node_client(node_credentials, public_key)
+        .await
+        .map_err(|(_, body)| body)?;
+    let latencies = client
+        .list_payment_latencies(since.timestamp() as u64)
+        .await
+        .map_err(|e| e.to_string())?;
+    drop(client);
+
+    let mut recorded = 0;
+    for latency in latencies {
+        let Some(settled_at) = DateTime::from_timestamp(latency.settled_at as i64, 0) else {
+            continue;
+        };
+        let sample = PaymentLatencySample {
+            account_id: account_id.to_string(),
+            node_id: node_id.clone(),
+            payment_hash: latency.payment_hash,
+            destination: latency.destination,
+            first_hop_channel_id: latency.first_hop.map(|channel| channel.to_string()),
+            settle_ms: latency.settle_ms as i64,
+            htlc_ms: latency.htlc_ms.map(|ms| ms as i64),
+            settled_at,
+        };
+        if repo
+            .insert_sample(&sample)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            recorded += 1;
+        }
+    }
+
+    if recorded == 0 {
+        return Ok(());
+    }
+    check_degradation(pool, account_id, user_id, node_id, node_alias).await
+}
+
+/// Raises a Warning event when the node's recent p95 settle time degraded
+/// against its baseline, at most once per recent window.
+async fn check_degradation(
+    pool: &SqlitePool,
+    account_id: &str,
+    user_id: &str,
+    node_id: &str,
+    node_alias: &str,
+) -> Result<(), String> {
+    let now = Utc::now();
+    let recent_start = now - Duration::hours(RECENT_WINDOW_HOURS);
+    let baseline_start = recent_start - Duration::days(BASELINE_DAYS);
+    let samples = PaymentLatencyRepository::new(pool)
+        .get_samples_since(account_id, node_id, baseline_start)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Payments made during maintenance say nothing about the node's usual
+    // latency, and slow ones are expected while it's being worked on.
+    let maintenance = MaintenanceRepository::new(pool)
+        .get_windows_since(account_id, node_id, baseline_start)
+        .await
+        .map_err(|e| e.to_string())?;
+    if covers(&maintenance, now) {
+        return Ok(());
+    }
+
+    let (mut recent, mut baseline): (Vec<i64>, Vec<i64>) = (Vec::new(), Vec::new());
+    for sample in samples
+        .iter()
+        .filter(|sample| !covers(&maintenance, sample.settled_at))
+    {
+        if sample.settled_at >= recent_start {
+            recent.push(sample.settle_ms);
+        } else {
+            baseline.push(sample.settle_ms);
+        }
+    }
+    recent.sort_unstable();
+    baseline.sort_unstable();
+
+    let Some((recent_p95, baseline_p95)) = detect_degradation(&recent, &baseline) else {
+        return Ok(());
+    };
+
+    let already_warned = EventRepository::new(pool)
+        .count_node_events_since(
+            account_id,
+            node_id,
+            &EventType::PaymentLatencyDegraded,
+            recent_start,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    if already_warned > 0 {
+        return Ok(());
+    }
+
+    let event = CreateEvent {
+        id: Uuid::now_v7().to_string(),
+        account_id: account_id.to_string(),
+        user_id: user_id.to_string(),
+        node_id: node_id.to_string(),
+        node_alias: node_alias.to_string(),
+        event_type: EventType::PaymentLatencyDegraded,
+        severity: EventSeverity::Warning,
+        title: "Payment Latency Degraded".to_string(),
+        description: format!(
+            "Payments took {recent_p95} ms to settle at p95 over the last day, up from {baseline_p95} ms"
+        ),
+        data: json!({
+            "recent_p95_ms": recent_p95,
+            "baseline_p95_ms": baseline_p95,
+            "recent_samples": recent.len(),
+            "baseline_samples": baseline.len(),
+        })
+        .to_string(),
+        notifications_id: None,
+        timestamp: Utc::now(),
+    };
+
+    EventService::new(pool)
+        .create_and_dispatch_event(event)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(settle_ms: i64, htlc_ms: Option<i64>) -> PaymentLatencySample {
+        PaymentLatencySample {
+            account_id: "account".to_string(),
+            node_id: "node".to_string(),
+            payment_hash: Uuid::now_v7().to_string(),
+            destination: None,
+            first_hop_channel_id: None,
+            settle_ms,
+            htlc_ms,
+            settled_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_detect_degradation() {
+        let baseline: Vec<i64> = (1..=20).map(|i| i * 100).collect();
+        let slow: Vec<i64> = (1..=20).map(|i| i * 250).collect();
+        let steady: Vec<i64> = (1..=20).map(|i| i * 120).collect();
+
+        assert_eq!(detect_degradation(&slow, &baseline), Some((4750, 1900)));
+        assert_eq!(detect_degradation(&steady, &baseline), None);
+        assert_eq!(detect_degradation(&slow[..5], &baseline), None);
+    }
+
+    #[test]
+    fn test_latency_stats() {
+        let samples = [
+            sample(800, Some(300)),
+            sample(1200, None),
+            sample(3000, Some(900)),
+        ];
+        let refs: Vec<&PaymentLatencySample> = samples.iter().collect();
+        let stats = latency_stats("dest".to_string(), None, &refs).unwrap();
+
+        assert_eq!(stats.sample_count, 3);
+        assert_eq!(stats.p50_ms, 1200);
+        assert_eq!(stats.p95_ms, 3000);
+        assert_eq!(stats.htlc_p50_ms, Some(300));
+        assert_eq!(stats.htlc_p95_ms, Some(900));
+        assert!(latency_stats("dest".to_string(), None, &[]).is_none());
+    }
+}
@@ -0,0 +1,278 @@
+//! Fee advice for the destinations the node pays most often.
+//!
+//! Built from the outgoing payments stored by the last node resync, joined
+//! with the first hops the latency tracker recorded for LND nodes. For each
+//! frequent destination the fee rates of settled payments give a fee limit
+//! that would have let most of them through, and the cheapest of them show
+//! whether the node usually pays far more than it has to.
+
+use crate::database::models::{
+    FirstHopFees, PaymentRouteAdvice, PaymentRouteQuery, PaymentRouteReport, PaymentRouteSample,
+};
+use crate::errors::ServiceResult;
+use crate::repositories::graph_repository::GraphRepository;
+use crate::repositories::node_sync_repository::NodeSyncRepository;
+use crate::services::graph_sync::percentile;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+const DEFAULT_ROUTE_DAYS: i64 = 30;
+const MAX_ROUTE_DAYS: i64 = 365;
+const DEFAULT_MIN_PAYMENTS: i64 = 3;
+
+/// Fewest settled payments through a first hop for it to be preferred
+const MIN_FIRST_HOP_PAYMENTS: i64 = 2;
+
+/// How many times the cheapest successful rate the typical rate must be to
+/// count as overpaying
+const OVERPAY_FACTOR: i64 = 2;
+
+/// Smallest gap between the typical and the cheapest rate worth flagging,
+/// so destinations paid at a few ppm aren't
+const MIN_OVERPAY_PPM: i64 = 100;
+
+fn fee_ppm(sample: &PaymentRouteSample) -> i64 {
+    let fee_sat = sample.routing_fee_sat.unwrap_or(0).max(0);
+    if sample.amount_sat <= 0 {
+        return 0;
+    }
+    fee_sat * 1_000_000 / sample.amount_sat
+}
+
+/// Advice for the payments to one destination. Returns `None` if fewer than
+/// `min_payments` of them settled.
+pub fn route_advice(
+    destination: String,
+    samples: &[&PaymentRouteSample],
+    min_payments: i64,
+) -> Option<PaymentRouteAdvice> {
+    let settled: Vec<&PaymentRouteSample> = samples
+        .iter()
+        .copied()
+        .filter(|sample| sample.state == "Settled")
+        .collect();
+    if (settled.len() as i64) < min_payments.max(1) {
+        return None;
+    }
+
+    let mut rates: Vec<i64> = settled.iter().copied().map(fee_ppm).collect();
+    rates.sort_unstable();
+    let p10 = percentile(&rates, 10.0)?;
+    let p50 = percentile(&rates, 50.0)?;
+    let p90 = percentile(&rates, 90.0)?;
+
+    let mut by_hop: HashMap<&String, Vec<i64>> = HashMap::new();
+    for sample in &settled {
+        if let Some(channel_id) = &sample.first_hop_channel_id {
+            by_hop.entry(channel_id).or_default().push(fee_ppm(sample));
+        }
+    }
+    let mut first_hops: Vec<FirstHopFees> = by_hop
+        .into_iter()
+        .filter_map(|(channel_id, mut rates)| {
+            rates.sort_unstable();
+            Some(FirstHopFees {
+                channel_id: channel_id.clone(),
+                peer_pubkey: None,
+                peer_alias: None,
+                payments: rates.len() as i64,
+                p50_fee_ppm: percentile(&rates, 50.0)?,
+            })
+        })
+        .collect();
+    first_hops.sort_by(|a, b| {
+        a.p50_fee_ppm
+            .cmp(&b.p50_fee_ppm)
+            .then_with(|| b.payments.cmp(&a.payments))
+            .then_with(|| a.channel_id.cmp(&b.channel_id))
+    });
+    let preferred_first_hop = first_hops
+        .iter()
+        .find(|hop| hop.payments >= MIN_FIRST_HOP_PAYMENTS)
+        .cloned();
+
+    let amount_sat: i64 = settled.iter().map(|sample| sample.amount_sat).sum();
+    let fees_sat: i64 = settled
+        .iter()
+        .map(|sample| sample.routing_fee_sat.unwrap_or(0).max(0))
+        .sum();
+    let overpaid_sat = settled
+        .iter()
+        .map(|sample| {
+            let fee_sat = sample.routing_fee_sat.unwrap_or(0).max(0);
+            (fee_sat - sample.amount_sat * p10 / 1_000_000).max(0)
+        })
+        .sum();
+
+    Some(PaymentRouteAdvice {
+        destination,
+        alias: None,
+        settled: settled.len() as i64,
+        failed: samples.len() as i64 - settled.len() as i64,
+        amount_sat,
+        fees_sat,
+        p10_fee_ppm: p10,
+        p50_fee_ppm: p50,
+        p90_fee_ppm: p90,
+        suggested_fee_limit_ppm: p90,
+        preferred_first_hop,
+        first_hops,
+        overpaying: p50 >= p10 * OVERPAY_FACTOR && p50 - p10 >= MIN_OVERPAY_PPM,
+        overpaid_sat,
+    })
+}
+
+/// Advice for every destination paid often enough, overpaid ones first.
+pub fn route_advice_by_destination(
+    samples: &[PaymentRouteSample],
+    min_payments: i64,
+) -> Vec<PaymentRouteAdvice> {
+    let mut by_destination: HashMap<&String, Vec<&PaymentRouteSample>> = HashMap::new();
+    for sample in samples {
+        by_destination
+            .entry(&sample.destination)
+            .or_default()
+            .push(sample);
+    }
+
+    let mut advice: Vec<PaymentRouteAdvice> = by_destination
+        .into_iter()
+        .filter_map(|(destination, group)| route_advice(destination.clone(), &group, min_payments))
+        .collect();
+    advice.sort_by(|a, b| {
+        b.overpaying
+            .cmp(&a.overpaying)
+            .then_with(|| b.fees_sat.cmp(&a.fees_sat))
+            .then_with(|| a.destination.cmp(&b.destination))
+    });
+    advice
+}
+
+/// Service layer for payment route analytics.
+pub struct PaymentRouteService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> PaymentRouteService<'a> {
+    /// Creates a new PaymentRouteService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Suggests fee limits and first hops for the destinations the node paid
+    /// at least `min_payments` times over the requested number of days.
+    pub async fn get_route_advice(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: PaymentRouteQuery,
+    ) -> ServiceResult<PaymentRouteReport> {
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_ROUTE_DAYS)
+            .clamp(1, MAX_ROUTE_DAYS);
+        let min_payments = query.min_payments.unwrap_or(DEFAULT_MIN_PAYMENTS).max(1);
+
+        let sync_repo = NodeSyncRepository::new(self.pool);
+        let samples = sync_repo
+            .get_payment_route_samples(account_id, node_id, Utc::now() - Duration::days(days))
+            .await?;
+        let synced_at = sync_repo
+            .get_payments_synced_at(account_id, node_id)
+            .await?;
+
+        let graph_repo = GraphRepository::new(self.pool);
+        let aliases: HashMap<String, String> = graph_repo
+            .get_nodes(node_id)
+            .await?
+            .into_iter()
+            .map(|node| (node.pubkey, node.alias))
+            .collect();
+        let channel_peers = graph_repo.get_channel_peers(node_id, node_id).await?;
+
+        let mut destinations = route_advice_by_destination(&samples, min_payments);
+        for advice in &mut destinations {
+            advice.alias = aliases.get(&advice.destination).cloned();
+            for hop in advice
+                .first_hops
+                .iter_mut()
+                .chain(advice.preferred_first_hop.as_mut())
+            {
+                hop.peer_pubkey = channel_peers.get(&hop.channel_id).cloned();
+                hop.peer_alias = hop
+                    .peer_pubkey
+                    .as_ref()
+                    .and_then(|pubkey| aliases.get(pubkey))
+                    .cloned();
+            }
+        }
+
+        Ok(PaymentRouteReport {
+            days,
+            destinations,
+            synced_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(
+        destination: &str,
+        state: &str,
+        fee_sat: i64,
+        first_hop: Option<&str>,
+    ) -> PaymentRouteSample {
+        PaymentRouteSample {
+            destination: destination.to_string(),
+            state: state.to_string(),
+            amount_sat: 100_000,
+            routing_fee_sat: Some(fee_sat),
+            first_hop_channel_id: first_hop.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_route_advice_by_destination() {
+        let samples = vec![
+            // 100 ppm through channel 1, 1000 ppm through channel 2
+            sample("shop", "Settled", 10, Some("1")),
+            sample("shop", "Settled", 10, Some("1")),
+            sample("shop", "Settled", 100, Some("2")),
+            sample("shop", "Settled", 100, Some("2")),
+            sample("shop", "Settled", 100, Some("2")),
+            sample("shop", "Failed", 0, None),
+            sample("cafe", "Settled", 5, None),
+            sample("cafe", "Settled", 6, None),
+            sample("cafe", "Settled", 7, None),
+            sample("rare", "Settled", 50, None),
+        ];
+
+        let advice = route_advice_by_destination(&samples, 3);
+
+        assert_eq!(advice.len(), 2);
+        let shop = &advice[0];
+        assert_eq!(shop.destination, "shop");
+        assert!(shop.overpaying);
+        assert_eq!(shop.settled, 5);
+        assert_eq!(shop.failed, 1);
+        assert_eq!(shop.p10_fee_ppm, 100);
+        assert_eq!(shop.p50_fee_ppm, 1000);
+        assert_eq!(shop.suggested_fee_limit_ppm, 1000);
+        assert_eq!(shop.overpaid_sat, 270);
+        assert_eq!(
+            shop.preferred_first_hop
+                .as_ref()
+                .map(|hop| hop.channel_id.as_str()),
+            Some("1")
+        );
+        assert_eq!(shop.first_hops.len(), 2);
+
+        let cafe = &advice[1];
+        assert!(!cafe.overpaying);
+        assert!(cafe.preferred_first_hop.is_none());
+    }
+}
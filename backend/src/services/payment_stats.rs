@@ -0,0 +1,163 @@
+//! Payment totals for the dashboard.
+//!
+//! Like the invoice stats, these are computed in SQL over the payments stored
+//! by the last node resync. Fiat amounts use the current BTC price in the
+//! account's display currency, not the price at the time of each payment.
+
+use crate::database::models::{PaymentDirectionStats, PaymentStats, PaymentStatsQuery};
+use crate::errors::ServiceResult;
+use crate::repositories::node_sync_repository::NodeSyncRepository;
+use crate::services::account_settings_service::AccountSettingsService;
+use crate::utils::sats_to_usd::PriceConverter;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+
+const DEFAULT_STATS_DAYS: i64 = 30;
+const MAX_STATS_DAYS: i64 = 365;
+
+/// Folds (payment_type, state, count, amount_sat, routing_fee_sat) rows into
+/// directional totals, fees and rates. Fiat fields are left for the caller.
+pub fn payment_stats(
+    rows: Vec<(String, String, i64, i64, i64)>,
+    days: i64,
+    to: DateTime<Utc>,
+    currency: String,
+    synced_at: Option<DateTime<Utc>>,
+) -> PaymentStats {
+    let mut outgoing = PaymentDirectionStats::default();
+    let mut incoming = PaymentDirectionStats::default();
+    let mut total_fees_sat = 0;
+
+    for (payment_type, state, count, amount_sat, routing_fee_sat) in rows {
+        let direction = match payment_type.as_str() {
+            "Outgoing" => &mut outgoing,
+            "Incoming" | "Keysend" => &mut incoming,
+            _ => continue,
+        };
+        direction.count += count;
+        match state.as_str() {
+            "Settled" => {
+                direction.settled += count;
+                direction.volume_sat += amount_sat;
+                if payment_type == "Outgoing" {
+                    total_fees_sat += routing_fee_sat;
+                }
+            }
+            "Failed" => direction.failed += count,
+            _ => {}
+        }
+    }
+
+    let resolved = outgoing.settled + outgoing.failed;
+    PaymentStats {
+        days,
+        from: to - Duration::days(days),
+        to,
+        total_volume_sat: outgoing.volume_sat + incoming.volume_sat,
+        total_fees_sat,
+        average_fee_ppm: (outgoing.volume_sat > 0)
+            .then(|| total_fees_sat as f64 * 1_000_000.0 / outgoing.volume_sat as f64),
+        failure_rate: (resolved > 0).then(|| outgoing.failed as f64 / resolved as f64),
+        outgoing,
+        incoming,
+        currency,
+        btc_price: None,
+        total_volume_fiat: None,
+        total_fees_fiat: None,
+        synced_at,
+    }
+}
+
+/// Service layer for payment statistics.
+pub struct PaymentStatsService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> PaymentStatsService<'a> {
+    /// Creates a new PaymentStatsService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the node's payment totals over the last `days` days.
+    pub async fn get_stats(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: PaymentStatsQuery,
+    ) -> ServiceResult<PaymentStats> {
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_STATS_DAYS)
+            .clamp(1, MAX_STATS_DAYS);
+        let to = Utc::now();
+
+        let repo = NodeSyncRepository::new(self.pool);
+        let rows = repo
+            .get_payment_totals_by_type_and_state(
+                account_id,
+                node_id,
+                to - Duration::days(days),
+                to,
+            )
+            .await?;
+        let synced_at = repo.get_payments_synced_at(account_id, node_id).await?;
+        let currency = AccountSettingsService::new(self.pool)
+            .get_settings(account_id)
+            .await?
+            .display_currency;
+
+        let mut stats = payment_stats(rows, days, to, currency, synced_at);
+        match PriceConverter::new()
+            .fetch_btc_price_in(&stats.currency)
+            .await
+        {
+            Ok(btc_price) => {
+                let to_fiat = |sats: i64| {
+                    PriceConverter::sats_to_usd_with_price(sats.max(0) as u64, btc_price)
+                };
+                stats.btc_price = Some(btc_price);
+                stats.total_volume_fiat = Some(to_fiat(stats.total_volume_sat));
+                stats.total_fees_fiat = Some(to_fiat(stats.total_fees_sat));
+            }
+            Err(e) => tracing::warn!("No BTC price for payment stats: {}", e),
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payment_stats() {
+        let rows = vec![
+            (
+                "Outgoing".to_string(),
+                "Settled".to_string(),
+                3,
+                200_000,
+                50,
+            ),
+            ("Outgoing".to_string(), "Failed".to_string(), 1, 10_000, 0),
+            ("Outgoing".to_string(), "Inflight".to_string(), 1, 5_000, 0),
+            ("Incoming".to_string(), "Settled".to_string(), 2, 30_000, 0),
+            ("Keysend".to_string(), "Settled".to_string(), 1, 1_000, 0),
+        ];
+
+        let stats = payment_stats(rows, 7, Utc::now(), "EUR".to_string(), None);
+        assert_eq!(stats.outgoing.count, 5);
+        assert_eq!(stats.outgoing.volume_sat, 200_000);
+        assert_eq!(stats.incoming.settled, 3);
+        assert_eq!(stats.total_volume_sat, 231_000);
+        assert_eq!(stats.total_fees_sat, 50);
+        assert_eq!(stats.average_fee_ppm, Some(250.0));
+        assert_eq!(stats.failure_rate, Some(0.25));
+
+        let empty = payment_stats(Vec::new(), 7, Utc::now(), "USD".to_string(), None);
+        assert_eq!(empty.average_fee_ppm, None);
+        assert_eq!(empty.failure_rate, None);
+    }
+}
@@ -0,0 +1,271 @@
+//! Changes in how channel partners announce themselves.
+//!
+//! Whenever the graph mirror takes in announcements, those of the node's
+//! channel partners are compared with what was mirrored before. A changed
+//! alias, address set or feature bits is recorded as a
+//! `PeerAnnouncementChanged` event. A peer that drops its clearnet addresses
+//! for Tor, or stops announcing addresses at all, gets a warning: both tend
+//! to precede trouble reconnecting to it.
+
+use crate::database::models::{CreateEvent, EventSeverity, EventType};
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::services::event_service::EventService;
+use crate::utils::GraphNode;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+/// What differs between two announcements of the same node.
+#[derive(Debug, PartialEq)]
+pub struct AnnouncementChange {
+    pub previous_alias: String,
+    pub alias: String,
+    pub added_addresses: Vec<String>,
+    pub removed_addresses: Vec<String>,
+    pub added_features: Vec<u32>,
+    pub removed_features: Vec<u32>,
+    /// The node announced clearnet addresses before and only Tor ones now
+    pub tor_only: bool,
+    /// The node announced addresses before and none now
+    pub no_addresses: bool,
+}
+
+fn is_tor(address: &str) -> bool {
+    address
+        .rsplit_once(':')
+        .map_or(address, |(host, _)| host)
+        .ends_with(".onion")
+}
+
+fn is_tor_only(addresses: &[String]) -> bool {
+    !addresses.is_empty() && addresses.iter().all(|address| is_tor(address))
+}
+
+/// Compares a node's previous announcement with its current one; None when
+/// nothing of interest changed.
+pub fn announcement_change(
+    previous: &GraphNode,
+    current: &GraphNode,
+) -> Option<AnnouncementChange> {
+    let old_addresses: BTreeSet<&String> = previous.addresses.iter().collect();
+    let new_addresses: BTreeSet<&String> = current.addresses.iter().collect();
+    // A real announcement always sets some feature bits, so an empty set
+    // means the source didn't include them rather than that all were dropped.
+    let (old_features, new_features): (BTreeSet<u32>, BTreeSet<u32>) =
+        if current.features.is_empty() {
+            Default::default()
+        } else {
+            (
+                previous.features.iter().copied().collect(),
+                current.features.iter().copied().collect(),
+            )
+        };
+
+    let change = AnnouncementChange {
+        previous_alias: previous.alias.clone(),
+        alias: current.alias.clone(),
+        added_addresses: new_addresses
+            .difference(&old_addresses)
+            .map(|a| a.to_string())
+            .collect(),
+        removed_addresses: old_addresses
+            .difference(&new_addresses)
+            .map(|a| a.to_string())
+            .collect(),
+        added_features: new_features.difference(&old_features).copied().collect(),
+        removed_features: old_features.difference(&new_features).copied().collect(),
+        tor_only: is_tor_only(&current.addresses) && !is_tor_only(&previous.addresses),
+        no_addresses: current.addresses.is_empty() && !previous.addresses.is_empty(),
+    };
+
+    let unchanged = change.previous_alias == change.alias
+        && change.added_addresses.is_empty()
+        && change.removed_addresses.is_empty()
+        && change.added_features.is_empty()
+        && change.removed_features.is_empty();
+    (!unchanged).then_some(change)
+}
+
+fn join<T: ToString>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Spells out a change as `alias: what changed`.
+pub fn summarize(change: &AnnouncementChange) -> String {
+    let mut parts = Vec::new();
+    if change.previous_alias != change.alias {
+        parts.push(format!(
+            "alias changed from '{}' to '{}'",
+            change.previous_alias, change.alias
+        ));
+    }
+    if change.tor_only {
+        parts.push("now announces only Tor addresses".to_string());
+    }
+    if change.no_addresses {
+        parts.push("no longer announces any address".to_string());
+    }
+    if !change.added_addresses.is_empty() {
+        parts.push(format!("added {}", join(&change.added_addresses)));
+    }
+    if !change.removed_addresses.is_empty() {
+        parts.push(format!("removed {}", join(&change.removed_addresses)));
+    }
+    if !change.added_features.is_empty() {
+        parts.push(format!("set feature bits {}", join(&change.added_features)));
+    }
+    if !change.removed_features.is_empty() {
+        parts.push(format!(
+            "cleared feature bits {}",
+            join(&change.removed_features)
+        ));
+    }
+    format!("{}: {}", change.alias, parts.join("; "))
+}
+
+fn describe(change: &AnnouncementChange) -> (EventSeverity, &'static str, String) {
+    let description = summarize(change);
+    if change.tor_only {
+        (
+            EventSeverity::Warning,
+            "Peer Moved to Tor Only",
+            description,
+        )
+    } else if change.no_addresses {
+        (
+            EventSeverity::Warning,
+            "Peer Stopped Announcing Addresses",
+            description,
+        )
+    } else {
+        (
+            EventSeverity::Info,
+            "Peer Announcement Changed",
+            description,
+        )
+    }
+}
+
+/// Records an event for every channel partner of `node_id` whose
+/// announcement in `current` differs from its entry in `previous`, the
+/// partners' announcements as mirrored before `current` was applied.
+pub async fn record_peer_announcement_changes(
+    pool: &SqlitePool,
+    node_id: &str,
+    previous: Vec<GraphNode>,
+    current: &[GraphNode],
+) {
+    let previous: HashMap<String, GraphNode> = previous
+        .into_iter()
+        .map(|node| (node.pubkey.clone(), node))
+        .collect();
+    let changes: Vec<(&GraphNode, AnnouncementChange)> = current
+        .iter()
+        .filter_map(|node| {
+            let change = announcement_change(previous.get(&node.pubkey)?, node)?;
+            Some((node, change))
+        })
+        .collect();
+    if changes.is_empty() {
+        return;
+    }
+
+    let credentials = match CredentialRepository::new(pool).get_all_credentials().await {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            tracing::error!("Failed to load credentials of {}: {}", node_id, e);
+            return;
+        }
+    };
+
+    for credential in credentials.iter().filter(|c| c.node_id == node_id) {
+        for (node, change) in &changes {
+            let (severity, title, description) = describe(change);
+            let event = CreateEvent {
+                id: Uuid::now_v7().to_string(),
+                account_id: credential.account_id.clone(),
+                user_id: credential.user_id.clone(),
+                node_id: credential.node_id.clone(),
+                node_alias: credential.node_alias.clone(),
+                event_type: EventType::PeerAnnouncementChanged,
+                severity,
+                title: title.to_string(),
+                description,
+                data: json!({
+                    "peer_pubkey": node.pubkey,
+                    "previous_alias": change.previous_alias,
+                    "alias": change.alias,
+                    "added_addresses": change.added_addresses,
+                    "removed_addresses": change.removed_addresses,
+                    "added_features": change.added_features,
+                    "removed_features": change.removed_features,
+                    "tor_only": change.tor_only,
+                })
+                .to_string(),
+                notifications_id: None,
+                timestamp: chrono::Utc::now(),
+            };
+
+            if let Err(e) = EventService::new(pool)
+                .create_and_dispatch_event(event)
+                .await
+            {
+                tracing::error!(
+                    "Failed to record announcement change of {}: {}",
+                    node.pubkey,
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(alias: &str, addresses: &[&str], features: &[u32]) -> GraphNode {
+        GraphNode {
+            pubkey: "02aa".to_string(),
+            alias: alias.to_string(),
+            last_update: None,
+            addresses: addresses.iter().map(|a| a.to_string()).collect(),
+            features: features.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_announcement_change() {
+        let before = node("peer", &["203.0.113.7:9735", "abc.onion:9735"], &[7, 13]);
+
+        let reordered = node("peer", &["abc.onion:9735", "203.0.113.7:9735"], &[7, 13]);
+        assert_eq!(announcement_change(&before, &reordered), None);
+        // Updates without feature bits don't read as every bit being cleared.
+        let no_features = node("peer", &["203.0.113.7:9735", "abc.onion:9735"], &[]);
+        assert_eq!(announcement_change(&before, &no_features), None);
+
+        let tor = node("peer", &["abc.onion:9735"], &[7, 13, 17]);
+        let change = announcement_change(&before, &tor).unwrap();
+        assert!(change.tor_only);
+        assert_eq!(change.removed_addresses, vec!["203.0.113.7:9735"]);
+        assert_eq!(change.added_features, vec![17]);
+        assert_eq!(describe(&change).0, EventSeverity::Warning);
+
+        let renamed = node(
+            "new-name",
+            &["203.0.113.7:9735", "abc.onion:9735"],
+            &[7, 13],
+        );
+        let change = announcement_change(&before, &renamed).unwrap();
+        assert!(!change.tor_only && !change.no_addresses);
+        assert_eq!(describe(&change).0, EventSeverity::Info);
+
+        let gone = node("peer", &[], &[7, 13]);
+        assert!(announcement_change(&before, &gone).unwrap().no_addresses);
+    }
+}
@@ -0,0 +1,121 @@
+//! Channels with a given remote peer.
+//!
+//! Channels are listed live from each of the account's nodes, so a node that
+//! can't be reached is reported instead of failing the whole lookup.
+
+use crate::database::models::{PeerChannel, PeerChannels};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::utils::ChannelSummary;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::{Claims, NodeCredentials};
+use bitcoin::secp256k1::PublicKey;
+use sqlx::SqlitePool;
+use std::str::FromStr;
+
+/// Whether the channel's peer matches a `peer=` filter: a prefix of its
+/// pubkey, or part of its alias, ignoring case.
+pub fn matches_peer(channel: &ChannelSummary, peer: &str) -> bool {
+    let peer = peer.to_lowercase();
+    channel.remote_pubkey.to_lowercase().starts_with(&peer)
+        || channel
+            .alias
+            .as_ref()
+            .is_some_and(|alias| alias.to_lowercase().contains(&peer))
+}
+
+/// Service layer for looking up channels by peer.
+pub struct PeerChannelService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> PeerChannelService<'a> {
+    /// Creates a new PeerChannelService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the channels every node of the account the caller may access
+    /// has with the peer.
+    pub async fn channels_with_peer(
+        &self,
+        claims: &Claims,
+        pubkey: &str,
+    ) -> ServiceResult<PeerChannels> {
+        let peer = PublicKey::from_str(pubkey)
+            .map_err(|_| ServiceError::validation("Invalid peer public key"))?
+            .to_string();
+
+        let credentials = CredentialRepository::new(self.pool)
+            .get_credentials_by_account_id(claims.account_id())
+            .await?
+            .into_iter()
+            .filter(|credential| claims.can_access_node(&credential.node_id));
+
+        let mut channels = Vec::new();
+        let mut unreachable_nodes = Vec::new();
+        for credential in credentials {
+            let node_credentials = NodeCredentials::from(credential);
+            let node_id = node_credentials.node_id.clone();
+
+            let listed = async {
+                let public_key = PublicKey::from_str(&node_id).map_err(|e| e.to_string())?;
+                let client = create_node_client(&node_credentials, public_key)
+                    .await
+                    .map_err(|(_, body)| body)?;
+                client.list_channels().await.map_err(|e| e.to_string())
+            }
+            .await;
+
+            match listed {
+                Ok(node_channels) => channels.extend(
+                    node_channels
+                        .into_iter()
+                        .filter(|channel| channel.remote_pubkey == peer)
+                        .map(|channel| PeerChannel {
+                            node_id: node_id.clone(),
+                            node_alias: node_credentials.node_alias.clone(),
+                            channel,
+                        }),
+                ),
+                Err(e) => {
+                    tracing::warn!("Failed to list channels of node {}: {}", node_id, e);
+                    unreachable_nodes.push(node_id);
+                }
+            }
+        }
+
+        Ok(PeerChannels {
+            pubkey: peer,
+            channels,
+            unreachable_nodes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{ChannelState, ShortChannelID};
+
+    #[test]
+    fn test_matches_peer() {
+        let channel = ChannelSummary {
+            chan_id: ShortChannelID(1),
+            remote_pubkey: "03abcdef".to_string(),
+            alias: Some("ACINQ".to_string()),
+            channel_state: ChannelState::Active,
+            private: false,
+            remote_balance: 0,
+            local_balance: 0,
+            capacity: 0,
+            last_update: None,
+            uptime: None,
+            notes: Vec::new(),
+        };
+
+        assert!(matches_peer(&channel, "03ABC"));
+        assert!(matches_peer(&channel, "cin"));
+        assert!(!matches_peer(&channel, "abcdef"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Peer metadata from Amboss and 1ML.
+//!
+//! With `PEER_ENRICHMENT=true`, channel details and network position
+//! responses carry each peer's Amboss community tags and contact info and its
+//! 1ML capacity rank. Lookups are cached in `peer_metadata` for a day. If a
+//! refresh fails the stale copy is served, and a peer neither service knows
+//! is left out rather than failing the response.
+
+use crate::config::Config;
+use crate::database::models::{PeerContact, PeerMetadata};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::peer_metadata_repository::PeerMetadataRepository;
+use chrono::{Duration, Utc};
+use futures::StreamExt;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// Cached metadata younger than this is served without asking again
+const CACHE_TTL_HOURS: i64 = 24;
+
+/// Peers looked up at once when a response needs many of them
+const MAX_CONCURRENT_LOOKUPS: usize = 4;
+
+const AMBOSS_NODE_QUERY: &str = r#"
+query GetNode($pubkey: String!) {
+  getNode(pubkey: $pubkey) {
+    socials { info { email twitter telegram website } }
+    amboss { communities { name } }
+  }
+}
+"#;
+
+#[derive(Deserialize)]
+struct AmbossResponse {
+    data: Option<AmbossData>,
+}
+
+#[derive(Deserialize)]
+struct AmbossData {
+    #[serde(rename = "getNode")]
+    node: Option<AmbossNode>,
+}
+
+#[derive(Default, Deserialize)]
+struct AmbossNode {
+    socials: Option<AmbossSocials>,
+    amboss: Option<AmbossInfo>,
+}
+
+#[derive(Deserialize)]
+struct AmbossSocials {
+    info: Option<PeerContact>,
+}
+
+#[derive(Deserialize)]
+struct AmbossInfo {
+    #[serde(default)]
+    communities: Vec<AmbossCommunity>,
+}
+
+#[derive(Deserialize)]
+struct AmbossCommunity {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OneMlNode {
+    noderank: Option<OneMlRank>,
+}
+
+#[derive(Deserialize)]
+struct OneMlRank {
+    capacity: Option<i64>,
+}
+
+/// Talks to the Amboss GraphQL API and 1ML's node pages.
+struct PeerMetadataClient {
+    http_client: Client,
+    amboss_api_url: String,
+    amboss_api_key: Option<String>,
+    one_ml_api_url: String,
+}
+
+impl PeerMetadataClient {
+    fn new(config: &Config) -> ServiceResult<Self> {
+        let http_client = Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        Ok(Self {
+            http_client,
+            amboss_api_url: config.amboss_api_url.clone(),
+            amboss_api_key: config.amboss_api_key.clone(),
+            one_ml_api_url: config.one_ml_api_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Asks both services about a node. Fails only if neither answers.
+    async fn fetch(&self, pubkey: &str) -> ServiceResult<PeerMetadata> {
+        let (amboss, rank) = tokio::join!(self.fetch_amboss(pubkey), self.fetch_one_ml(pubkey));
+        if let (Err(amboss_error), Err(one_ml_error)) = (&amboss, &rank) {
+            return Err(ServiceError::external_service(format!(
+                "Peer metadata unavailable: {amboss_error}; {one_ml_error}"
+            )));
+        }
+
+        Ok(to_metadata(
+            pubkey,
+            amboss.unwrap_or_default(),
+            rank.ok().flatten(),
+        ))
+    }
+
+    async fn fetch_amboss(&self, pubkey: &str) -> ServiceResult<AmbossNode> {
+        let mut request = self.http_client.post(&self.amboss_api_url).json(&json!({
+            "query": AMBOSS_NODE_QUERY,
+            "variables": { "pubkey": pubkey },
+        }));
+        if let Some(api_key) = &self.amboss_api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response: AmbossResponse = send(request, "Amboss").await?;
+        Ok(response.data.and_then(|data| data.node).unwrap_or_default())
+    }
+
+    async fn fetch_one_ml(&self, pubkey: &str) -> ServiceResult<Option<i64>> {
+        let request = self
+            .http_client
+            .get(format!("{}/node/{}/json", self.one_ml_api_url, pubkey));
+        let node: OneMlNode = send(request, "1ML").await?;
+        Ok(node.noderank.and_then(|rank| rank.capacity))
+    }
+}
+
+async fn send<T: for<'de> Deserialize<'de>>(
+    request: reqwest::RequestBuilder,
+    service: &str,
+) -> ServiceResult<T> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ServiceError::external_service(format!("{service} unreachable: {e}")))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ServiceError::external_service(format!(
+            "{service} returned {status}"
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| ServiceError::external_service(format!("Invalid {service} response: {e}")))
+}
+
+fn to_metadata(pubkey: &str, amboss: AmbossNode, rank: Option<i64>) -> PeerMetadata {
+    PeerMetadata {
+        pubkey: pubkey.to_string(),
+        community_tags: amboss
+            .amboss
+            .map(|info| info.communities.into_iter().map(|c| c.name).collect())
+            .unwrap_or_default(),
+        rank,
+        contact: amboss
+            .socials
+            .and_then(|socials| socials.info)
+            .unwrap_or_default(),
+        fetched_at: Utc::now(),
+    }
+}
+
+/// Service layer for third-party peer metadata.
+pub struct PeerEnrichmentService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> PeerEnrichmentService<'a> {
+    /// Creates a new PeerEnrichmentService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns what is known about each of `pubkeys`, keyed by pubkey.
+    /// Empty when enrichment is disabled.
+    pub async fn get_metadata(&self, pubkeys: &[String]) -> HashMap<String, PeerMetadata> {
+        let config = match Config::from_env() {
+            Ok(config) if config.peer_enrichment_enabled => config,
+            Ok(_) => return HashMap::new(),
+            Err(e) => {
+                tracing::error!("Failed to load config for peer enrichment: {}", e);
+                return HashMap::new();
+            }
+        };
+        let client = match PeerMetadataClient::new(&config) {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Failed to create peer metadata client: {}", e);
+                return HashMap::new();
+            }
+        };
+
+        futures::stream::iter(pubkeys)
+            .map(|pubkey| self.lookup(&client, pubkey))
+            .buffer_unordered(MAX_CONCURRENT_LOOKUPS)
+            .filter_map(|metadata| async move { metadata })
+            .map(|metadata| (metadata.pubkey.clone(), metadata))
+            .collect()
+            .await
+    }
+
+    async fn lookup(&self, client: &PeerMetadataClient, pubkey: &str) -> Option<PeerMetadata> {
+        let repo = PeerMetadataRepository::new(self.pool);
+        let cached = match repo.get(pubkey).await {
+            Ok(cached) => cached,
+            Err(e) => {
+                tracing::warn!("Failed to read cached metadata for {}: {}", pubkey, e);
+                None
+            }
+        };
+        if let Some(cached) = &cached {
+            if cached.fetched_at > Utc::now() - Duration::hours(CACHE_TTL_HOURS) {
+                return Some(cached.clone());
+            }
+        }
+
+        match client.fetch(pubkey).await {
+            Ok(metadata) => {
+                if let Err(e) = repo.upsert(&metadata).await {
+                    tracing::warn!("Failed to cache metadata for {}: {}", pubkey, e);
+                }
+                Some(metadata)
+            }
+            Err(e) => {
+                tracing::debug!("Failed to fetch metadata for {}: {}", pubkey, e);
+                cached
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_metadata() {
+        let response: AmbossResponse = serde_json::from_str(
+            r#"{
+                "data": {
+                    "getNode": {
+                        "socials": { "info": { "email": null, "twitter": "nodegaze", "telegram": null, "website": "https://nodegaze.dev" } },
+                        "amboss": { "communities": [{ "name": "Plebnet" }, { "name": "LN Routing" }] }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let metadata = to_metadata("02ab", response.data.unwrap().node.unwrap(), Some(42));
+        assert_eq!(metadata.community_tags, vec!["Plebnet", "LN Routing"]);
+        assert_eq!(metadata.contact.twitter.as_deref(), Some("nodegaze"));
+        assert_eq!(metadata.rank, Some(42));
+
+        let unknown: AmbossResponse =
+            serde_json::from_str(r#"{ "data": { "getNode": null } }"#).unwrap();
+        let metadata = to_metadata(
+            "02ab",
+            unknown.data.and_then(|data| data.node).unwrap_or_default(),
+            None,
+        );
+        assert!(metadata.community_tags.is_empty());
+        assert!(metadata.contact.email.is_none());
+    }
+}
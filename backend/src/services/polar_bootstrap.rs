@@ -0,0 +1,423 @@
+//! Registers the nodes of a local Polar regtest network.
+//!
+//! Polar keeps each network in `~/.polar/networks/<id>` and describes all of
+//! them, including the host ports their containers publish, in the
+//! `networks.json` next to those directories. Bootstrapping a network reads
+//! that file, connects to every LND and CLN node over its gRPC port using the
+//! TLS files and macaroons under the network's `volumes` directory, stores
+//! credentials for each under a user of its own (credentials are one per
+//! user) and starts its event stream.
+//!
+//! The endpoint reads files from the server's disk, so it is off unless
+//! `POLAR_NETWORKS_DIR` is set, and only networks inside that directory can
+//! be bootstrapped.
+
+use crate::config::Config;
+use crate::database::models::{CreateCredential, CreateUser, RoleAccessLevel};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::role_repository::RoleRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::event_manager::start_event_stream;
+use crate::services::node_manager::{
+    ClnConnection, ClnNode, LightningClient, LndConnection, connect_lnd,
+};
+use crate::utils::NodeId;
+use crate::utils::jwt::NodeCredentials;
+use crate::utils::password::hash_password;
+use expanduser::expanduser;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PolarBootstrapRequest {
+    /// Network directory, e.g. `~/.polar/networks/1`
+    #[validate(length(min = 1, message = "Network directory is required"))]
+    pub network_dir: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BootstrapStatus {
+    Registered,
+    AlreadyRegistered,
+    Skipped,
+    Failed,
+}
+
+/// Outcome for one node of the network
+#[derive(Debug, Serialize)]
+pub struct BootstrappedNode {
+    pub name: String,
+    pub implementation: String,
+    pub node_id: Option<String>,
+    pub status: BootstrapStatus,
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PolarNetworks {
+    networks: Vec<PolarNetwork>,
+}
+
+#[derive(Deserialize)]
+struct PolarNetwork {
+    id: u64,
+    path: String,
+    nodes: PolarNodes,
+}
+
+#[derive(Deserialize)]
+struct PolarNodes {
+    #[serde(default)]
+    lightning: Vec<PolarLightningNode>,
+}
+
+#[derive(Deserialize)]
+struct PolarLightningNode {
+    name: String,
+    /// `LND`, `c-lightning`, `eclair` or `litd`
+    implementation: String,
+    ports: PolarPorts,
+}
+
+#[derive(Deserialize)]
+struct PolarPorts {
+    grpc: Option<u16>,
+}
+
+pub struct PolarBootstrapService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> PolarBootstrapService<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Registers every supported node of the network with the account.
+    ///
+    /// A node that can't be reached is reported as failed without stopping
+    /// the others; nodes the account already has are left untouched.
+    pub async fn bootstrap(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        request: PolarBootstrapRequest,
+    ) -> ServiceResult<Vec<BootstrappedNode>> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let config = Config::from_env()
+            .map_err(|e| ServiceError::internal_error(format!("Config error: {e}")))?;
+        let networks_dir = config.polar_networks_dir.ok_or_else(|| {
+            ServiceError::invalid_operation("Polar bootstrap is disabled on this server")
+        })?;
+        let network_dir = resolve_network_dir(&networks_dir, &request.network_dir)?;
+        let network = load_network(&network_dir).await?;
+
+        let mut results = Vec::with_capacity(network.nodes.lightning.len());
+        for node in &network.nodes.lightning {
+            let result = match self
+                .register_node(account_id, user_id, &network, &network_dir, node)
+                .await
+            {
+                Ok(result) => result,
+                Err(e) => BootstrappedNode {
+                    name: node.name.clone(),
+                    implementation: node.implementation.clone(),
+                    node_id: None,
+                    status: BootstrapStatus::Failed,
+                    message: Some(e.to_string()),
+                },
+            };
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    async fn register_node(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        network: &PolarNetwork,
+        network_dir: &Path,
+        node: &PolarLightningNode,
+    ) -> ServiceResult<BootstrappedNode> {
+        let outcome = |node_id: Option<String>, status, message: Option<String>| BootstrappedNode {
+            name: node.name.clone(),
+            implementation: node.implementation.clone(),
+            node_id,
+            status,
+            message,
+        };
+
+        let Some(grpc_port) = node.ports.grpc else {
+            return Ok(outcome(
+                None,
+                BootstrapStatus::Skipped,
+                Some("Node does not expose a gRPC port".to_string()),
+            ));
+        };
+        let address = format!("https://127.0.0.1:{grpc_port}");
+        let path = |parts: &[&str]| -> String {
+            parts
+                .iter()
+                .fold(network_dir.join("volumes"), |path, part| path.join(part))
+                .display()
+                .to_string()
+        };
+
+        // Polar starts every node with its name as the alias.
+        let id = NodeId::Alias(node.name.clone());
+        let name = node.name.as_str();
+        let (info, node_type, macaroon, tls_cert, client_cert, client_key, ca_cert) =
+            match node.implementation.as_str() {
+                "LND" => {
+                    let macaroon =
+                        path(&["lnd", name, "data/chain/bitcoin/regtest/admin.macaroon"]);
+                    let cert = path(&["lnd", name, "tls.cert"]);
+                    let client = connect_lnd(LndConnection {
+                        id,
+                        address: address.clone(),
+                        macaroon: macaroon.clone(),
+                        cert: cert.clone(),
+                    })
+                    .await
+                    .map_err(|e| ServiceError::external_service(e.to_string()))?;
+                    (
+                        client.get_info().clone(),
+                        "lnd",
+                        macaroon,
+                        cert,
+                        None,
+                        None,
+                        None,
+                    )
+                }
+                "c-lightning" => {
+                    let tls_file =
+                        |file: &str| path(&["c-lightning", name, "lightningd/regtest", file]);
+                    let connection = ClnConnection {
+                        id,
+                        address: address.clone(),
+                        ca_cert: tls_file("ca.pem"),
+                        client_cert: tls_file("client.pem"),
+                        client_key: tls_file("client-key.pem"),
+                    };
+                    let client = ClnNode::new(connection.clone())
+                        .await
+                        .map_err(|e| ServiceError::external_service(e.to_string()))?;
+                    (
+                        client.info.clone(),
+                        "cln",
+                        String::new(),
+                        String::new(),
+                        Some(connection.client_cert),
+                        Some(connection.client_key),
+                        Some(connection.ca_cert),
+                    )
+                }
+                other => {
+                    return Ok(outcome(
+                        None,
+                        BootstrapStatus::Skipped,
+                        Some(format!("{other} nodes are not supported")),
+                    ));
+                }
+            };
+
+        let node_id = info.pubkey.to_string();
+        let credential_repo = CredentialRepository::new(self.pool);
+        if credential_repo
+            .get_credential_by_account_and_node_id(account_id, &node_id)
+            .await?
+            .is_some()
+        {
+            return Ok(outcome(
+                Some(node_id),
+                BootstrapStatus::AlreadyRegistered,
+                None,
+            ));
+        }
+
+        let node_user = self
+            .create_node_user(account_id, network.id, &node.name)
+            .await?;
+        let credential = credential_repo
+            .create_credential(CreateCredential {
+                id: Uuid::now_v7().to_string(),
+                user_id: node_user.clone(),
+                account_id: account_id.to_string(),
+                node_id: node_id.clone(),
+                node_alias: info.alias,
+                macaroon,
+                tls_cert,
+                address,
+                node_type: Some(node_type.to_string()),
+                client_cert,
+                client_key,
+                ca_cert,
+            })
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "node_bootstrapped",
+                "node",
+                Some(&node_id),
+                &json!({
+                    "network_id": network.id,
+                    "name": node.name,
+                    "implementation": node.implementation,
+                }),
+            )
+            .await?;
+
+        let message = start_event_stream(
+            self.pool.clone(),
+            account_id.to_string(),
+            node_user,
+            &NodeCredentials::from(credential),
+        )
+        .await
+        .err()
+        .map(|e| format!("Registered, but the event stream failed to start: {e}"));
+
+        Ok(outcome(Some(node_id), BootstrapStatus::Registered, message))
+    }
+
+    /// Creates the read-write member that owns a bootstrapped node's
+    /// credentials and returns its id.
+    async fn create_node_user(
+        &self,
+        account_id: &str,
+        network_id: u64,
+        name: &str,
+    ) -> ServiceResult<String> {
+        let username = format!("polar-{network_id}-{}", name.to_lowercase());
+        let user_repo = UserRepository::new(self.pool);
+        if user_repo.username_exists(&username).await? {
+            return Err(ServiceError::already_exists(
+                "User with username",
+                &username,
+            ));
+        }
+
+        let role = RoleRepository::new(self.pool)
+            .get_role_by_name("Member")
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Role", "Member"))?;
+
+        let mut password = [0u8; 24];
+        rand::thread_rng().fill_bytes(&mut password);
+        let password_hash = hash_password(&hex::encode(password)).await?;
+
+        let user = user_repo
+            .create_user(CreateUser {
+                id: Uuid::now_v7().to_string(),
+                account_id: account_id.to_string(),
+                role_id: role.id,
+                email: format!("{username}@polar.localhost"),
+                username,
+                password_hash,
+                role_access_level: RoleAccessLevel::ReadWrite,
+            })
+            .await?;
+        // Nobody reads mail at a Polar node's placeholder address.
+        user_repo.mark_email_verified(&user.id).await?;
+
+        Ok(user.id)
+    }
+}
+
+/// Resolves the requested network directory, which must be a directory
+/// directly inside `networks_dir` so that the `networks.json` next to it is
+/// Polar's own.
+fn resolve_network_dir(networks_dir: &str, requested: &str) -> ServiceResult<PathBuf> {
+    let networks_dir = expanduser(networks_dir)
+        .and_then(std::fs::canonicalize)
+        .map_err(|e| ServiceError::internal_error(format!("Invalid POLAR_NETWORKS_DIR: {e}")))?;
+    let network_dir = expanduser(requested)
+        .and_then(std::fs::canonicalize)
+        .map_err(|e| ServiceError::validation(format!("Invalid network directory: {e}")))?;
+
+    if network_dir.parent() != Some(networks_dir.as_path()) || !network_dir.is_dir() {
+        return Err(ServiceError::permission_denied(format!(
+            "Network directory must be a directory inside {}",
+            networks_dir.display()
+        )));
+    }
+
+    Ok(network_dir)
+}
+
+/// Finds the network stored in `network_dir` in Polar's `networks.json`.
+async fn load_network(network_dir: &Path) -> ServiceResult<PolarNetwork> {
+    let networks_file: PathBuf = network_dir
+        .parent()
+        .map(|parent| parent.join("networks.json"))
+        .ok_or_else(|| ServiceError::validation("Network directory has no parent directory"))?;
+    let contents = tokio::fs::read_to_string(&networks_file)
+        .await
+        .map_err(|e| {
+            ServiceError::validation(format!("Cannot read {}: {e}", networks_file.display()))
+        })?;
+    let networks: PolarNetworks = serde_json::from_str(&contents).map_err(|e| {
+        ServiceError::validation(format!(
+            "{} is not a Polar networks file: {e}",
+            networks_file.display()
+        ))
+    })?;
+
+    let dir_name = network_dir.file_name().and_then(|name| name.to_str());
+    networks
+        .networks
+        .into_iter()
+        .find(|network| {
+            Path::new(&network.path) == network_dir
+                || dir_name == Some(network.id.to_string().as_str())
+        })
+        .ok_or_else(|| ServiceError::not_found("Polar network", &network_dir.display().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_dir_must_be_inside_networks_dir() {
+        let root = std::env::temp_dir().join(format!("polar-{}", Uuid::now_v7()));
+        let networks_dir = root.join("networks");
+        std::fs::create_dir_all(networks_dir.join("1")).unwrap();
+        std::fs::create_dir_all(root.join("elsewhere")).unwrap();
+        let networks = networks_dir.to_str().unwrap();
+
+        let resolved = resolve_network_dir(networks, networks_dir.join("1").to_str().unwrap());
+        assert_eq!(
+            resolved.unwrap(),
+            std::fs::canonicalize(networks_dir.join("1")).unwrap()
+        );
+
+        for requested in [
+            networks_dir.clone(),
+            networks_dir.join("1/../../elsewhere"),
+            root.join("elsewhere"),
+            networks_dir.join("missing"),
+        ] {
+            assert!(resolve_network_dir(networks, requested.to_str().unwrap()).is_err());
+        }
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,204 @@
+//! Self-service profile management.
+//!
+//! Users can change their display name directly. A new email address is only
+//! applied after the user follows the verification link sent to it, and new
+//! users get the same link for the address they signed up or were invited
+//! with.
+
+use crate::config::Config;
+use crate::database::models::{ProfileResponse, UpdateProfileRequest, User};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::email_change_repository::EmailChangeRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::email_service::EmailService;
+use crate::utils::generate_random_string::generate_random_string;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+use validator::Validate;
+
+/// How long an email verification link stays valid
+const EMAIL_VERIFICATION_TTL_HOURS: i64 = 24;
+
+/// Service layer for the signed-in user's own profile.
+pub struct ProfileService<'a> {
+    pool: &'a SqlitePool,
+    email_service: Option<EmailService>,
+}
+
+impl<'a> ProfileService<'a> {
+    /// Creates a new ProfileService instance.
+    pub fn new(pool: &'a SqlitePool, config: &Config) -> Self {
+        let email_service = config
+            .email_config()
+            .and_then(|email_config| match EmailService::new(email_config) {
+                Ok(service) => Some(service),
+                Err(e) => {
+                    tracing::warn!("Failed to initialize email service: {}", e);
+                    None
+                }
+            });
+
+        Self {
+            pool,
+            email_service,
+        }
+    }
+
+    /// Updates the user's display name and/or starts an email change.
+    pub async fn update_profile(
+        &self,
+        user_id: &str,
+        request: UpdateProfileRequest,
+    ) -> ServiceResult<ProfileResponse> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let user_repo = UserRepository::new(self.pool);
+        let mut user = user_repo
+            .get_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("User", user_id))?;
+
+        if let Some(display_name) = request.display_name {
+            let display_name = display_name.trim().to_string();
+            user_repo
+                .update_display_name(&user.id, Some(&display_name))
+                .await?;
+            user.display_name = Some(display_name);
+        }
+
+        if let Some(email) = request.email {
+            if email.eq_ignore_ascii_case(&user.email) {
+                return Err(ServiceError::validation(
+                    "New email is the same as the current one",
+                ));
+            }
+            if user_repo.email_exists_excluding(&email, &user.id).await? {
+                return Err(ServiceError::already_exists("User with email", &email));
+            }
+            if self.email_service.is_none() {
+                return Err(ServiceError::invalid_operation(
+                    "Email changes require email delivery to be configured",
+                ));
+            }
+
+            let request = EmailChangeRepository::new(self.pool)
+                .create_request(
+                    &Uuid::now_v7().to_string(),
+                    &user.id,
+                    &email,
+                    &generate_random_string(32),
+                    Utc::now() + Duration::hours(EMAIL_VERIFICATION_TTL_HOURS),
+                )
+                .await?;
+
+            self.try_send_verification(&user, &request.new_email, &request.token);
+        }
+
+        let pending_email = EmailChangeRepository::new(self.pool)
+            .get_pending_email(&user.id)
+            .await?
+            .filter(|email| !email.eq_ignore_ascii_case(&user.email));
+
+        Ok(ProfileResponse {
+            user,
+            pending_email,
+        })
+    }
+
+    /// Starts verification of a new user's address. Without email delivery
+    /// there is no way to verify it, so the address is accepted as is.
+    pub async fn start_email_verification(&self, user: &User) -> ServiceResult<()> {
+        if self.email_service.is_none() {
+            UserRepository::new(self.pool)
+                .mark_email_verified(&user.id)
+                .await?;
+            return Ok(());
+        }
+
+        let request = EmailChangeRepository::new(self.pool)
+            .create_request(
+                &Uuid::now_v7().to_string(),
+                &user.id,
+                &user.email,
+                &generate_random_string(32),
+                Utc::now() + Duration::hours(EMAIL_VERIFICATION_TTL_HOURS),
+            )
+            .await?;
+
+        self.try_send_verification(user, &request.new_email, &request.token);
+        Ok(())
+    }
+
+    /// Sends a fresh verification link for the user's current address.
+    pub async fn resend_email_verification(&self, user_id: &str) -> ServiceResult<()> {
+        let user_repo = UserRepository::new(self.pool);
+        let user = user_repo
+            .get_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("User", user_id))?;
+
+        if user_repo.is_email_verified(&user.id).await? {
+            return Err(ServiceError::invalid_operation(
+                "Email address is already verified",
+            ));
+        }
+
+        self.start_email_verification(&user).await
+    }
+
+    /// Applies the email change, or confirms the address, a verification
+    /// token was issued for.
+    pub async fn verify_email(&self, token: &str) -> ServiceResult<User> {
+        let repo = EmailChangeRepository::new(self.pool);
+        let request = repo
+            .get_pending_request_by_token(token)
+            .await?
+            .filter(|r| r.expires_at > Utc::now())
+            .ok_or_else(|| {
+                ServiceError::validation("Verification token is invalid or has expired")
+            })?;
+
+        let user_repo = UserRepository::new(self.pool);
+        // The address may have been claimed by someone else since the request.
+        if user_repo
+            .email_exists_excluding(&request.new_email, &request.user_id)
+            .await?
+        {
+            return Err(ServiceError::already_exists(
+                "User with email",
+                &request.new_email,
+            ));
+        }
+
+        repo.apply_request(&request).await?;
+
+        user_repo
+            .get_user_by_id(&request.user_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("User", &request.user_id))
+    }
+
+    fn try_send_verification(&self, user: &User, new_email: &str, token: &str) {
+        let Some(email_service) = self.email_service.clone() else {
+            return;
+        };
+        let name = user
+            .display_name
+            .clone()
+            .unwrap_or_else(|| user.username.clone());
+        let new_email = new_email.to_string();
+        let token = token.to_string();
+
+        tokio::spawn(async move {
+            if let Err(e) = email_service
+                .send_email_verification(&new_email, &name, &token)
+                .await
+            {
+                tracing::error!("Failed to send verification email to {}: {}", new_email, e);
+            }
+        });
+    }
+}
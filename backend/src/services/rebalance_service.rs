@@ -0,0 +1,537 @@
+//! Circular rebalancing and its scheduler.
+//!
+//! A rebalance pays an invoice of our own, leaving through a channel with
+//! spare local balance and returning through one that is short of it. It can
+//! be started by hand, or by the hourly `AutoRebalance` scheduled task, which
+//! tops up every channel that has drifted below its target. Automatic runs
+//! stay within a monthly fee budget and pause during blackout windows. Every
+//! attempt, with its route and cost, goes into `rebalance_attempts`.
+//!
+//! Only LND nodes can rebalance.
+
+use crate::database::models::{
+    CreateRebalanceRequest, RebalanceAttempt, RebalanceHop, RebalancePolicy, RebalanceTarget,
+    UpdateRebalancePolicyRequest,
+};
+use crate::errors::{LightningError, ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::rebalance_repository::RebalanceRepository;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use crate::utils::{ChannelState, RebalanceOutcome, ShortChannelID};
+use bitcoin::secp256k1::PublicKey;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Attempts returned when no limit is given
+const DEFAULT_ATTEMPT_LIMIT: i64 = 100;
+const MAX_ATTEMPT_LIMIT: i64 = 1_000;
+
+/// Share of capacity channels without a target are treated as aiming for
+const UNTARGETED_LOCAL_PCT: f64 = 50.0;
+
+/// Local and total balance of one channel, in sats.
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelBalance {
+    pub channel_id: u64,
+    pub local_sat: u64,
+    pub capacity_sat: u64,
+}
+
+/// A rebalance an automatic run intends to try.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlannedRebalance {
+    pub outgoing_channel_id: u64,
+    pub incoming_channel_id: u64,
+    pub amount_sat: u64,
+}
+
+/// Pairs every channel below its target (by more than `tolerance_pct`) with
+/// the channel that has the most local balance to spare.
+pub fn plan_rebalances(
+    channels: &[ChannelBalance],
+    targets: &HashMap<u64, f64>,
+    tolerance_pct: f64,
+) -> Vec<PlannedRebalance> {
+    let target_sat = |channel: &ChannelBalance| {
+        let pct = targets
+            .get(&channel.channel_id)
+            .copied()
+            .unwrap_or(UNTARGETED_LOCAL_PCT);
+        (channel.capacity_sat as f64 * pct / 100.0) as u64
+    };
+
+    let mut surplus: Vec<(u64, u64)> = channels
+        .iter()
+        .filter_map(|channel| {
+            let spare = channel.local_sat.saturating_sub(target_sat(channel));
+            (spare > 0).then_some((channel.channel_id, spare))
+        })
+        .collect();
+
+    let mut plan = Vec::new();
+    for channel in channels {
+        let Some(target_pct) = targets.get(&channel.channel_id) else {
+            continue;
+        };
+        let local_pct = channel.local_sat as f64 * 100.0 / channel.capacity_sat.max(1) as f64;
+        if local_pct >= target_pct - tolerance_pct {
+            continue;
+        }
+
+        let deficit = target_sat(channel) - channel.local_sat;
+        let Some(source) = surplus
+            .iter_mut()
+            .filter(|(id, spare)| *id != channel.channel_id && *spare > 0)
+            .max_by_key(|(_, spare)| *spare)
+        else {
+            continue;
+        };
+
+        let amount_sat = deficit.min(source.1);
+        source.1 -= amount_sat;
+        plan.push(PlannedRebalance {
+            outgoing_channel_id: source.0,
+            incoming_channel_id: channel.channel_id,
+            amount_sat,
+        });
+    }
+
+    plan
+}
+
+/// Start of the calendar month (UTC) that `now` falls in.
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+/// Turns a rebalance's outcome into an attempt record. Nodes that cannot
+/// rebalance at all produce an error instead.
+fn build_attempt(
+    node_credentials: &NodeCredentials,
+    account_id: &str,
+    planned: &PlannedRebalance,
+    result: Result<RebalanceOutcome, LightningError>,
+    created_by: Option<&str>,
+) -> Result<RebalanceAttempt, LightningError> {
+    let (fee_msat, route, succeeded, error) = match result {
+        Ok(outcome) => (
+            outcome.fee_msat,
+            outcome
+                .route
+                .into_iter()
+                .map(|hop| RebalanceHop {
+                    pubkey: hop.pubkey.to_string(),
+                    channel_id: hop.chan_id.to_string(),
+                    amount_to_forward_sat: hop.amount_to_forward,
+                    fee_sat: hop.fee.unwrap_or_default(),
+                })
+                .collect(),
+            outcome.succeeded,
+            outcome.failure_reason,
+        ),
+        Err(e @ LightningError::Unsupported(_)) => return Err(e),
+        Err(e) => (0, Vec::new(), false, Some(e.to_string())),
+    };
+
+    Ok(RebalanceAttempt {
+        id: Uuid::now_v7().to_string(),
+        account_id: account_id.to_string(),
+        node_id: node_credentials.node_id.clone(),
+        outgoing_channel_id: planned.outgoing_channel_id.to_string(),
+        incoming_channel_id: planned.incoming_channel_id.to_string(),
+        amount_sat: planned.amount_sat as i64,
+        fee_msat: fee_msat as i64,
+        route: json!(route).to_string(),
+        succeeded,
+        error,
+        is_automatic: created_by.is_none(),
+        created_by: created_by.map(str::to_string),
+        created_at: Utc::now(),
+    })
+}
+
+/// Service layer for rebalancing.
+pub struct RebalanceService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RebalanceService<'a> {
+    /// Creates a new RebalanceService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Rebalances between two of the node's channels and waits for the result.
+    pub async fn create_rebalance(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_credentials: &NodeCredentials,
+        request: CreateRebalanceRequest,
+    ) -> ServiceResult<RebalanceAttempt> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        let parse = |id: &str| {
+            ShortChannelID::from_str(id)
+                .map_err(|e| ServiceError::validation(format!("Invalid channel ID {id}: {e}")))
+        };
+        let outgoing = parse(&request.outgoing_channel_id)?;
+        let incoming = parse(&request.incoming_channel_id)?;
+        if outgoing.0 == incoming.0 {
+            return Err(ServiceError::validation(
+                "Outgoing and incoming channels must differ",
+            ));
+        }
+
+        let public_key = PublicKey::from_str(&node_credentials.node_id)
+            .map_err(|e| ServiceError::validation(format!("Invalid node ID: {e}")))?;
+        let client = create_node_client(node_credentials, public_key)
+            .await
+            .map_err(|(_, body)| ServiceError::external_service(body))?;
+
+        let last_hop = client
+            .get_channel_info(&incoming)
+            .await
+            .map_err(|e| ServiceError::external_service(e.to_string()))?
+            .remote_pubkey;
+        let planned = PlannedRebalance {
+            outgoing_channel_id: outgoing.0,
+            incoming_channel_id: incoming.0,
+            amount_sat: request.amount_sat,
+        };
+        let result = client
+            .rebalance(
+                outgoing.0,
+                &last_hop,
+                request.amount_sat * 1000,
+                request.amount_sat * request.max_fee_ppm / 1000,
+            )
+            .await;
+
+        let attempt = build_attempt(
+            node_credentials,
+            account_id,
+            &planned,
+            result,
+            Some(user_id),
+        )
+        .map_err(|e| ServiceError::invalid_operation(e.to_string()))?;
+        RebalanceRepository::new(self.pool)
+            .create_attempt(&attempt)
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "rebalance_attempted",
+                "node",
+                Some(&node_credentials.node_id),
+                &json!({
+                    "attempt_id": attempt.id,
+                    "outgoing_channel_id": attempt.outgoing_channel_id,
+                    "incoming_channel_id": attempt.incoming_channel_id,
+                    "amount_sat": attempt.amount_sat,
+                    "succeeded": attempt.succeeded,
+                }),
+            )
+            .await?;
+
+        Ok(attempt)
+    }
+
+    /// Returns a node's auto-rebalancing settings; without any, it is off.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<RebalancePolicy> {
+        let policy = RebalanceRepository::new(self.pool)
+            .get_policy(account_id, node_id)
+            .await?;
+
+        Ok(policy.unwrap_or_else(|| RebalancePolicy {
+            account_id: account_id.to_string(),
+            node_id: node_id.to_string(),
+            is_enabled: false,
+            targets: "[]".to_string(),
+            tolerance_pct: 10.0,
+            max_fee_ppm: 500,
+            monthly_budget_sat: 0,
+            blackout_windows: "[]".to_string(),
+            updated_by: user_id.to_string(),
+            updated_at: None,
+        }))
+    }
+
+    /// Replaces a node's auto-rebalancing settings.
+    pub async fn update_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: UpdateRebalancePolicyRequest,
+    ) -> ServiceResult<RebalancePolicy> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        if request
+            .blackout_windows
+            .iter()
+            .any(|window| window.ends_at <= window.starts_at)
+        {
+            return Err(ServiceError::validation(
+                "Blackout windows must end after they start",
+            ));
+        }
+
+        let mut targets = Vec::with_capacity(request.targets.len());
+        for target in &request.targets {
+            let channel_id = ShortChannelID::from_str(target.channel_id.trim()).map_err(|_| {
+                ServiceError::validation(format!("Invalid channel ID: {}", target.channel_id))
+            })?;
+            targets.push(RebalanceTarget {
+                channel_id: channel_id.to_string(),
+                target_local_pct: target.target_local_pct,
+            });
+        }
+
+        CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+
+        let policy = RebalanceRepository::new(self.pool)
+            .upsert_policy(&RebalancePolicy {
+                account_id: account_id.to_string(),
+                node_id: node_id.to_string(),
+                is_enabled: request.is_enabled,
+                targets: json!(targets).to_string(),
+                tolerance_pct: request.tolerance_pct,
+                max_fee_ppm: request.max_fee_ppm,
+                monthly_budget_sat: request.monthly_budget_sat,
+                blackout_windows: json!(request.blackout_windows).to_string(),
+                updated_by: user_id.to_string(),
+                updated_at: None,
+            })
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "rebalance_policy_updated",
+                "node",
+                Some(node_id),
+                &json!({
+                    "is_enabled": policy.is_enabled,
+                    "targets": policy.targets(),
+                    "tolerance_pct": policy.tolerance_pct,
+                    "max_fee_ppm": policy.max_fee_ppm,
+                    "monthly_budget_sat": policy.monthly_budget_sat,
+                    "blackout_windows": policy.blackout_windows(),
+                }),
+            )
+            .await?;
+
+        Ok(policy)
+    }
+
+    /// Lists a node's rebalance attempts, newest first.
+    pub async fn list_attempts(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        limit: Option<i64>,
+    ) -> ServiceResult<Vec<RebalanceAttempt>> {
+        let limit = limit
+            .unwrap_or(DEFAULT_ATTEMPT_LIMIT)
+            .clamp(1, MAX_ATTEMPT_LIMIT);
+
+        Ok(RebalanceRepository::new(self.pool)
+            .get_attempts(account_id, node_id, limit)
+            .await?)
+    }
+}
+
+/// Runs the enabled auto-rebalancing policies of every node in the account.
+pub async fn run_account_rebalancing(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let policies = RebalanceRepository::new(pool)
+        .get_enabled_policies(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let credential_repo = CredentialRepository::new(pool);
+    let now = Utc::now();
+
+    let mut failures = Vec::new();
+    for policy in policies {
+        if policy
+            .blackout_windows()
+            .iter()
+            .any(|window| window.contains(now))
+        {
+            tracing::info!(
+                "Skipping auto-rebalance of {} during blackout",
+                policy.node_id
+            );
+            continue;
+        }
+
+        let credential = match credential_repo
+            .get_credential_by_account_and_node_id(account_id, &policy.node_id)
+            .await
+        {
+            Ok(Some(credential)) => credential,
+            Ok(None) => continue,
+            Err(e) => {
+                failures.push(format!("{}: {e}", policy.node_id));
+                continue;
+            }
+        };
+
+        if let Err(e) = run_policy(pool, &policy, &NodeCredentials::from(credential)).await {
+            failures.push(format!("{}: {e}", policy.node_id));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Auto-rebalancing failed for {}",
+            failures.join("; ")
+        ))
+    }
+}
+
+/// Tops up a node's under-target channels while its monthly budget lasts.
+async fn run_policy(
+    pool: &SqlitePool,
+    policy: &RebalancePolicy,
+    node_credentials: &NodeCredentials,
+) -> Result<(), String> {
+    let repo = RebalanceRepository::new(pool);
+    let spent_msat = repo
+        .get_automatic_fees_since(&policy.account_id, &policy.node_id, month_start(Utc::now()))
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut budget_msat = (policy.monthly_budget_sat * 1000 - spent_msat).max(0) as u64;
+    if budget_msat == 0 {
+        return Ok(());
+    }
+
+    let targets: HashMap<u64, f64> = policy
+        .targets()
+        .into_iter()
+        .filter_map(|target| Some((target.channel_id.parse().ok()?, target.target_local_pct)))
+        .collect();
+
+    let public_key = PublicKey::from_str(&node_credentials.node_id).map_err(|e| e.to_string())?;
+    let client = create_node_client(node_credentials, public_key)
+        .await
+        .map_err(|(_, body)| body)?;
+    let channels: Vec<ChannelBalance> = client
+        .list_channels()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|channel| matches!(channel.channel_state, ChannelState::Active))
+        .map(|channel| ChannelBalance {
+            channel_id: channel.chan_id.0,
+            local_sat: channel.local_balance,
+            capacity_sat: channel.capacity,
+        })
+        .collect();
+
+    for planned in plan_rebalances(&channels, &targets, policy.tolerance_pct) {
+        let max_fee_msat = (planned.amount_sat * policy.max_fee_ppm as u64 / 1000).min(budget_msat);
+        if max_fee_msat == 0 {
+            break;
+        }
+
+        let last_hop = client
+            .get_channel_info(&ShortChannelID(planned.incoming_channel_id))
+            .await
+            .map_err(|e| e.to_string())?
+            .remote_pubkey;
+        let result = client
+            .rebalance(
+                planned.outgoing_channel_id,
+                &last_hop,
+                planned.amount_sat * 1000,
+                max_fee_msat,
+            )
+            .await;
+
+        let attempt = build_attempt(node_credentials, &policy.account_id, &planned, result, None)
+            .map_err(|e| e.to_string())?;
+        repo.create_attempt(&attempt)
+            .await
+            .map_err(|e| e.to_string())?;
+        budget_msat = budget_msat.saturating_sub(attempt.fee_msat as u64);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(channel_id: u64, local_sat: u64) -> ChannelBalance {
+        ChannelBalance {
+            channel_id,
+            local_sat,
+            capacity_sat: 1_000_000,
+        }
+    }
+
+    #[test]
+    fn test_plan_rebalances_fills_from_largest_surplus() {
+        let channels = [
+            channel(1, 100_000),
+            channel(2, 900_000),
+            channel(3, 700_000),
+        ];
+        let targets = HashMap::from([(1, 50.0)]);
+
+        let plan = plan_rebalances(&channels, &targets, 10.0);
+
+        assert_eq!(
+            plan,
+            vec![PlannedRebalance {
+                outgoing_channel_id: 2,
+                incoming_channel_id: 1,
+                amount_sat: 400_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_rebalances_respects_tolerance() {
+        let channels = [channel(1, 450_000), channel(2, 900_000)];
+        let targets = HashMap::from([(1, 50.0)]);
+
+        assert!(plan_rebalances(&channels, &targets, 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_month_start() {
+        let now = Utc.with_ymd_and_hms(2025, 8, 17, 13, 45, 0).unwrap();
+        assert_eq!(
+            month_start(now),
+            Utc.with_ymd_and_hms(2025, 8, 1, 0, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Operational reports over a node's channels.
+//!
+//! The stale channel report lists channels that neither forwarded nor carried
+//! one of our payments within a window. Activity comes from the node's
+//! forwarding history and payment list, peer details from the graph mirror.
+//! Each channel's opportunity cost is what its local balance would have
+//! earned at a chosen yearly rate over the time it sat idle.
+
+use crate::database::models::{StaleChannel, StaleChannelQuery, StaleChannelReport};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::graph_repository::GraphRepository;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use crate::utils::{ChannelState, PaymentState};
+use bitcoin::secp256k1::PublicKey;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const DEFAULT_STALE_DAYS: i64 = 30;
+const MAX_STALE_DAYS: i64 = 365;
+const DEFAULT_ANNUAL_RATE_PCT: f64 = 5.0;
+
+/// What `local_sat` would have earned at `annual_rate_pct` over `idle_days`.
+pub fn opportunity_cost_sat(local_sat: u64, annual_rate_pct: f64, idle_days: i64) -> u64 {
+    (local_sat as f64 * annual_rate_pct / 100.0 * idle_days.max(0) as f64 / 365.0).round() as u64
+}
+
+fn from_unix(secs: u64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(secs as i64, 0)
+}
+
+/// Service layer for channel reports.
+pub struct ReportService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ReportService<'a> {
+    /// Creates a new ReportService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the node's channels idle for at least the requested number of
+    /// days, costliest first.
+    pub async fn get_stale_channels(
+        &self,
+        node_credentials: &NodeCredentials,
+        query: StaleChannelQuery,
+    ) -> ServiceResult<StaleChannelReport> {
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_STALE_DAYS)
+            .clamp(1, MAX_STALE_DAYS);
+        let annual_rate_pct = query.annual_rate_pct.unwrap_or(DEFAULT_ANNUAL_RATE_PCT);
+        if !(0.0..=100.0).contains(&annual_rate_pct) {
+            return Err(ServiceError::validation(
+                "Annual rate must be between 0 and 100 percent",
+            ));
+        }
+
+        let now = Utc::now();
+        let since = now - Duration::days(days);
+        let node_id = &node_credentials.node_id;
+
+        let public_key = PublicKey::from_str(node_id)
+            .map_err(|e| ServiceError::validation(format!("Invalid node ID: {e}")))?;
+        let client = create_node_client(node_credentials, public_key)
+            .await
+            .map_err(|(_, body)| ServiceError::external_service(body))?;
+        let channels = client
+            .list_channels()
+            .await
+            .map_err(|e| ServiceError::external_service(e.to_string()))?;
+        let forwards = client
+            .list_forwards(since.timestamp() as u64)
+            .await
+            .map_err(|e| ServiceError::external_service(e.to_string()))?;
+        let payments = client
+            .list_payments()
+            .await
+            .map_err(|e| ServiceError::external_service(e.to_string()))?;
+        drop(client);
+
+        let mut last_activity: HashMap<u64, u64> = HashMap::new();
+        let mut record = |channel_id: u64, timestamp: u64| {
+            let latest = last_activity.entry(channel_id).or_default();
+            *latest = (*latest).max(timestamp);
+        };
+        for forward in &forwards {
+            record(forward.chan_id_in.0, forward.timestamp);
+            record(forward.chan_id_out.0, forward.timestamp);
+        }
+        for payment in payments
+            .iter()
+            .filter(|payment| payment.state == PaymentState::Settled)
+        {
+            if let (Some(channel_id), Some(timestamp)) = (
+                payment.channel_id,
+                payment.completed_at.or(payment.creation_time),
+            ) {
+                record(channel_id.0, timestamp);
+            }
+        }
+
+        let graph_repo = GraphRepository::new(self.pool);
+        let channel_peers = graph_repo.get_channel_peers(node_id, node_id).await?;
+        let peers: HashMap<String, (String, Option<u64>)> = graph_repo
+            .get_nodes(node_id)
+            .await?
+            .into_iter()
+            .map(|node| (node.pubkey, (node.alias, node.last_update)))
+            .collect();
+
+        let mut stale = Vec::new();
+        for channel in channels {
+            if matches!(
+                channel.channel_state,
+                ChannelState::Opening | ChannelState::Closing | ChannelState::Closed
+            ) {
+                continue;
+            }
+            let last_activity_at = last_activity
+                .get(&channel.chan_id.0)
+                .and_then(|secs| from_unix(*secs));
+            if last_activity_at.is_some_and(|at| at >= since) {
+                continue;
+            }
+
+            let active = matches!(channel.channel_state, ChannelState::Active);
+            let peer_pubkey = channel_peers.get(&channel.chan_id.0.to_string()).cloned();
+            let peer = peer_pubkey.as_ref().and_then(|pubkey| peers.get(pubkey));
+            let gossip_seen = peer
+                .and_then(|(_, last_update)| *last_update)
+                .max(channel.last_update)
+                .and_then(from_unix);
+            let idle_days = last_activity_at.map_or(days, |at| (now - at).num_days().max(days));
+
+            stale.push(StaleChannel {
+                channel_id: channel.chan_id.to_string(),
+                peer_alias: peer
+                    .map(|(alias, _)| alias.clone())
+                    .or(channel.alias.clone()),
+                peer_pubkey,
+                capacity_sat: channel.capacity,
+                local_balance_sat: channel.local_balance,
+                active,
+                peer_last_seen: if active { Some(now) } else { gossip_seen },
+                last_activity_at,
+                idle_days,
+                opportunity_cost_sat: opportunity_cost_sat(
+                    channel.local_balance,
+                    annual_rate_pct,
+                    idle_days,
+                ),
+            });
+        }
+        stale.sort_by(|a, b| b.opportunity_cost_sat.cmp(&a.opportunity_cost_sat));
+
+        Ok(StaleChannelReport {
+            days,
+            annual_rate_pct,
+            total_local_balance_sat: stale.iter().map(|c| c.local_balance_sat).sum(),
+            total_opportunity_cost_sat: stale.iter().map(|c| c.opportunity_cost_sat).sum(),
+            channels: stale,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opportunity_cost_sat() {
+        assert_eq!(opportunity_cost_sat(1_000_000, 5.0, 365), 50_000);
+        assert_eq!(opportunity_cost_sat(1_000_000, 5.0, 73), 10_000);
+        assert_eq!(opportunity_cost_sat(1_000_000, 5.0, 0), 0);
+    }
+}
@@ -0,0 +1,213 @@
+//! Per-account data retention.
+//!
+//! Accounts choose how many days of events, notification deliveries, audit
+//! logs and balance history to keep. The daily `DataRetention` scheduled task
+//! prunes anything older; a preview reports what a set of windows would remove
+//! before it is saved.
+
+use crate::api::idempotency::IDEMPOTENCY_KEY_TTL_HOURS;
+use crate::database::models::{
+    RetentionEstimate, RetentionPreview, RetentionSettings, UpdateRetentionRequest,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::idempotency_repository::IdempotencyRepository;
+use crate::repositories::retention_repository::RetentionRepository;
+use crate::services::billing::BillingService;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy)]
+enum Category {
+    Events,
+    Deliveries,
+    AuditLogs,
+    BalanceHistory,
+}
+
+impl Category {
+    const ALL: [Category; 4] = [
+        Category::Events,
+        Category::Deliveries,
+        Category::AuditLogs,
+        Category::BalanceHistory,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Category::Events => "events",
+            Category::Deliveries => "deliveries",
+            Category::AuditLogs => "audit_logs",
+            Category::BalanceHistory => "balance_history",
+        }
+    }
+
+    fn days(self, settings: &RetentionSettings) -> Option<i64> {
+        match self {
+            Category::Events => settings.events_days,
+            Category::Deliveries => settings.deliveries_days,
+            Category::AuditLogs => settings.audit_logs_days,
+            Category::BalanceHistory => settings.balance_history_days,
+        }
+    }
+
+    async fn estimate(
+        self,
+        repo: &RetentionRepository<'_>,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> anyhow::Result<(i64, i64)> {
+        match self {
+            Category::Events => repo.estimate_events(account_id, cutoff).await,
+            Category::Deliveries => repo.estimate_deliveries(account_id, cutoff).await,
+            Category::AuditLogs => repo.estimate_audit_logs(account_id, cutoff).await,
+            Category::BalanceHistory => repo.estimate_balance_history(account_id, cutoff).await,
+        }
+    }
+
+    async fn prune(
+        self,
+        repo: &RetentionRepository<'_>,
+        account_id: &str,
+        cutoff: DateTime<Utc>,
+    ) -> anyhow::Result<u64> {
+        match self {
+            Category::Events => repo.prune_events(account_id, cutoff).await,
+            Category::Deliveries => repo.prune_deliveries(account_id, cutoff).await,
+            Category::AuditLogs => repo.prune_audit_logs(account_id, cutoff).await,
+            Category::BalanceHistory => repo.prune_balance_history(account_id, cutoff).await,
+        }
+    }
+}
+
+fn settings_from_request(account_id: &str, request: UpdateRetentionRequest) -> RetentionSettings {
+    RetentionSettings {
+        account_id: account_id.to_string(),
+        events_days: request.events_days,
+        deliveries_days: request.deliveries_days,
+        audit_logs_days: request.audit_logs_days,
+        balance_history_days: request.balance_history_days,
+        updated_at: None,
+    }
+}
+
+/// Service layer for retention settings.
+pub struct RetentionService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RetentionService<'a> {
+    /// Creates a new RetentionService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns an account's settings; accounts that never saved any keep everything.
+    pub async fn get_settings(&self, account_id: &str) -> ServiceResult<RetentionSettings> {
+        let settings = RetentionRepository::new(self.pool)
+            .get_settings(account_id)
+            .await?;
+
+        Ok(settings.unwrap_or_else(|| {
+            settings_from_request(
+                account_id,
+                UpdateRetentionRequest {
+                    events_days: None,
+                    deliveries_days: None,
+                    audit_logs_days: None,
+                    balance_history_days: None,
+                },
+            )
+        }))
+    }
+
+    /// Reports how much data the given windows would remove if applied now.
+    pub async fn preview(
+        &self,
+        account_id: &str,
+        request: UpdateRetentionRequest,
+    ) -> ServiceResult<RetentionPreview> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let settings = settings_from_request(account_id, request);
+        let repo = RetentionRepository::new(self.pool);
+        let now = Utc::now();
+
+        let mut estimates = Vec::new();
+        for category in Category::ALL {
+            let Some(days) = category.days(&settings) else {
+                continue;
+            };
+            let (rows, bytes) = category
+                .estimate(&repo, account_id, now - Duration::days(days))
+                .await?;
+            estimates.push(RetentionEstimate {
+                category: category.name().to_string(),
+                rows,
+                bytes,
+            });
+        }
+
+        Ok(RetentionPreview {
+            total_rows: estimates.iter().map(|e| e.rows).sum(),
+            total_bytes: estimates.iter().map(|e| e.bytes).sum(),
+            estimates,
+        })
+    }
+
+    /// Saves an account's settings. They take effect on the next pruning run.
+    pub async fn update_settings(
+        &self,
+        account_id: &str,
+        request: UpdateRetentionRequest,
+    ) -> ServiceResult<RetentionSettings> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        BillingService::new(self.pool)
+            .check_retention(account_id, &request)
+            .await?;
+
+        Ok(RetentionRepository::new(self.pool)
+            .upsert_settings(&settings_from_request(account_id, request))
+            .await?)
+    }
+}
+
+/// Prunes data past every account's retention windows.
+pub async fn prune_expired_data(pool: &SqlitePool) -> Result<(), String> {
+    let repo = RetentionRepository::new(pool);
+    let now = Utc::now();
+
+    for settings in repo.get_all_settings().await.map_err(|e| e.to_string())? {
+        for category in Category::ALL {
+            let Some(days) = category.days(&settings) else {
+                continue;
+            };
+            let removed = category
+                .prune(&repo, &settings.account_id, now - Duration::days(days))
+                .await
+                .map_err(|e| e.to_string())?;
+            if removed > 0 {
+                tracing::info!(
+                    "Pruned {} {} row(s) for account {}",
+                    removed,
+                    category.name(),
+                    settings.account_id
+                );
+            }
+        }
+    }
+
+    let removed = IdempotencyRepository::new(pool)
+        .delete_expired(now - Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS))
+        .await
+        .map_err(|e| e.to_string())?;
+    if removed > 0 {
+        tracing::info!("Pruned {} expired idempotency key(s)", removed);
+    }
+
+    Ok(())
+}
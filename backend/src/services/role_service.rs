@@ -0,0 +1,253 @@
+//! Role business logic service.
+//!
+//! Besides the built-in Admin and Member roles, account admins can define
+//! custom roles made up of permission scopes and hand them to users directly
+//! or through invites.
+
+use crate::database::models::{CreateRole, Role, User};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::role_repository::RoleRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::utils::jwt::Claims;
+use serde_json::json;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Names of the built-in roles, which custom roles may not reuse.
+const RESERVED_ROLE_NAMES: [&str; 2] = ["Admin", "Member"];
+
+fn is_reserved_name(name: &str) -> bool {
+    RESERVED_ROLE_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(name.trim()))
+}
+
+/// The nodes left to a user limited both by their role and by their own
+/// scope: those in both lists, or whichever list there is. `None` means
+/// every node of the account.
+pub fn combine_node_scopes(
+    role_scope: Option<Vec<String>>,
+    user_scope: Option<Vec<String>>,
+) -> Option<Vec<String>> {
+    match (role_scope, user_scope) {
+        (Some(role_scope), Some(user_scope)) => Some(
+            role_scope
+                .into_iter()
+                .filter(|node_id| user_scope.contains(node_id))
+                .collect(),
+        ),
+        (role_scope, user_scope) => role_scope.or(user_scope),
+    }
+}
+
+/// Service layer for roles and role assignment.
+pub struct RoleService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RoleService<'a> {
+    /// Creates a new RoleService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the built-in roles followed by the account's custom roles.
+    pub async fn list_roles(&self, account_id: &str) -> ServiceResult<Vec<Role>> {
+        Ok(RoleRepository::new(self.pool)
+            .get_roles_for_account(account_id)
+            .await?)
+    }
+
+    /// Defines a custom role for the caller's account.
+    pub async fn create_role(&self, claims: &Claims, request: CreateRole) -> ServiceResult<Role> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let name = request.name.trim();
+        if is_reserved_name(name) {
+            return Err(ServiceError::validation(format!(
+                "'{name}' is reserved for a built-in role"
+            )));
+        }
+
+        let mut permissions = request.permissions;
+        permissions.sort();
+        permissions.dedup();
+
+        let node_ids = match request.node_ids {
+            Some(node_ids) => Some(
+                self.validate_node_scope(claims.account_id(), node_ids)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let role = RoleRepository::new(self.pool)
+            .create_role(
+                &Uuid::now_v7().to_string(),
+                claims.account_id(),
+                name,
+                &permissions,
+                node_ids.as_deref(),
+            )
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("UNIQUE constraint failed") {
+                    ServiceError::already_exists("Role", name)
+                } else {
+                    ServiceError::Database { source: e }
+                }
+            })?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                claims.account_id(),
+                Some(claims.user_id()),
+                "role_created",
+                "role",
+                Some(&role.id),
+                &json!({
+                    "name": role.name,
+                    "permissions": permissions,
+                    "node_ids": node_ids,
+                }),
+            )
+            .await?;
+
+        Ok(role)
+    }
+
+    /// Checks that a node scope names only nodes of the account, returning
+    /// it sorted and without duplicates.
+    pub async fn validate_node_scope(
+        &self,
+        account_id: &str,
+        mut node_ids: Vec<String>,
+    ) -> ServiceResult<Vec<String>> {
+        if node_ids.is_empty() {
+            return Err(ServiceError::validation("At least one node is required"));
+        }
+
+        let account_nodes: Vec<String> = CredentialRepository::new(self.pool)
+            .get_credentials_by_account_id(account_id)
+            .await?
+            .into_iter()
+            .map(|credential| credential.node_id)
+            .collect();
+        if let Some(unknown) = node_ids.iter().find(|id| !account_nodes.contains(id)) {
+            return Err(ServiceError::not_found("Node", unknown));
+        }
+
+        node_ids.sort();
+        node_ids.dedup();
+        Ok(node_ids)
+    }
+
+    /// Resolves a role that can be given to a member of `account_id`: Member
+    /// or one of the account's own custom roles.
+    pub async fn get_assignable_role(
+        &self,
+        account_id: &str,
+        role_id: &str,
+    ) -> ServiceResult<Role> {
+        let role = RoleRepository::new(self.pool)
+            .get_role_by_id(role_id)
+            .await?
+            .filter(|r| r.account_id.is_none() || r.account_id.as_deref() == Some(account_id))
+            .ok_or_else(|| ServiceError::not_found("Role", role_id))?;
+
+        if role.is_built_in() && role.name == "Admin" {
+            return Err(ServiceError::invalid_operation(
+                "The Admin role cannot be assigned",
+            ));
+        }
+
+        Ok(role)
+    }
+
+    /// Gives a user in the caller's account a different role. Takes effect
+    /// the next time the user's token is issued.
+    pub async fn assign_role(
+        &self,
+        claims: &Claims,
+        user_id: &str,
+        role_id: &str,
+    ) -> ServiceResult<User> {
+        let user_repo = UserRepository::new(self.pool);
+        let user = user_repo
+            .get_user_by_id(user_id)
+            .await?
+            .filter(|u| u.account_id == claims.account_id())
+            .ok_or_else(|| ServiceError::not_found("User", user_id))?;
+
+        let is_account_admin = user_repo
+            .get_admin_user_by_account_id(&user.account_id)
+            .await?
+            .is_some_and(|admin| admin.id == user.id);
+        if is_account_admin {
+            return Err(ServiceError::invalid_operation(
+                "The account admin's role cannot be changed",
+            ));
+        }
+
+        let role = self
+            .get_assignable_role(claims.account_id(), role_id)
+            .await?;
+
+        if !user_repo.update_user_role(&user.id, &role.id).await? {
+            return Err(ServiceError::not_found("User", user_id));
+        }
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                claims.account_id(),
+                Some(claims.user_id()),
+                "role_assigned",
+                "user",
+                Some(&user.id),
+                &json!({ "from_role_id": user.role_id, "to_role_id": role.id }),
+            )
+            .await?;
+
+        Ok(User {
+            role_id: role.id,
+            ..user
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_role_names_are_reserved() {
+        assert!(is_reserved_name("Admin"));
+        assert!(is_reserved_name(" member "));
+        assert!(!is_reserved_name("Analyst"));
+    }
+
+    #[test]
+    fn node_scopes_narrow_each_other() {
+        let nodes = |ids: &[&str]| Some(ids.iter().map(|id| id.to_string()).collect::<Vec<_>>());
+
+        assert_eq!(combine_node_scopes(None, None), None);
+        assert_eq!(
+            combine_node_scopes(nodes(&["a", "b"]), None),
+            nodes(&["a", "b"])
+        );
+        assert_eq!(combine_node_scopes(None, nodes(&["b"])), nodes(&["b"]));
+        assert_eq!(
+            combine_node_scopes(nodes(&["a", "b"]), nodes(&["b", "c"])),
+            nodes(&["b"])
+        );
+        assert_eq!(
+            combine_node_scopes(nodes(&["a"]), nodes(&["c"])),
+            nodes(&[])
+        );
+    }
+}
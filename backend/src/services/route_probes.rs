@@ -0,0 +1,490 @@
+//! Synthetic probing of important routes.
+//!
+//! A node can opt in to having a handful of routes probed: a destination,
+//! optionally reached through a specific channel of ours. Every ten minutes
+//! the `RouteProbe` scheduled task sends each one a payment under a random
+//! payment hash. No invoice has that hash, so the payment always fails; what
+//! matters is where. A rejection by the destination itself means the route
+//! carried the amount, anything else means it did not, and nothing is ever
+//! paid. Each outcome goes into `probe_results`, and a route that carried its
+//! last probe but fails the next raises a Warning event.
+//!
+//! Only LND nodes can probe.
+
+use crate::database::models::{
+    CreateEvent, EventSeverity, EventType, ProbePolicy, ProbeReport, ProbeReportQuery, ProbeResult,
+    ProbeTarget, RouteHealth, UpdateProbePolicyRequest,
+};
+use crate::errors::{LightningError, ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::graph_repository::GraphRepository;
+use crate::repositories::maintenance_repository::MaintenanceRepository;
+use crate::repositories::probe_repository::ProbeRepository;
+use crate::services::event_service::EventService;
+use crate::services::graph_sync::percentile;
+use crate::services::maintenance::covers;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use crate::utils::{ProbeOutcome, ShortChannelID};
+use bitcoin::secp256k1::PublicKey;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+use validator::Validate;
+
+const DEFAULT_REPORT_DAYS: i64 = 7;
+
+/// Probe results are kept this long, which also bounds the report window
+const MAX_REPORT_DAYS: i64 = 30;
+
+/// Summarises the probes of each target route, least reliable first.
+/// `results` must be oldest first.
+pub fn route_health(
+    results: &[ProbeResult],
+    alias: impl Fn(&str) -> Option<String>,
+) -> Vec<RouteHealth> {
+    let mut groups: HashMap<(&str, Option<&str>), Vec<&ProbeResult>> = HashMap::new();
+    for result in results {
+        groups
+            .entry((
+                result.destination.as_str(),
+                result.outgoing_channel_id.as_deref(),
+            ))
+            .or_default()
+            .push(result);
+    }
+
+    let mut routes: Vec<RouteHealth> = groups
+        .into_iter()
+        .filter_map(|((destination, outgoing_channel_id), probes)| {
+            let last = probes.last()?;
+            let mut latencies: Vec<i64> = probes
+                .iter()
+                .filter(|probe| probe.reached)
+                .map(|probe| probe.latency_ms)
+                .collect();
+            latencies.sort_unstable();
+
+            Some(RouteHealth {
+                destination: destination.to_string(),
+                alias: alias(destination),
+                outgoing_channel_id: outgoing_channel_id.map(str::to_string),
+                probes: probes.len(),
+                reached: latencies.len(),
+                success_rate_pct: latencies.len() as f64 * 100.0 / probes.len() as f64,
+                p50_latency_ms: percentile(&latencies, 50.0),
+                last_reached: last.reached,
+                last_failure_reason: last.failure_reason.clone(),
+                last_probed_at: last.probed_at,
+            })
+        })
+        .collect();
+    routes.sort_by(|a, b| {
+        a.success_rate_pct
+            .total_cmp(&b.success_rate_pct)
+            .then_with(|| a.destination.cmp(&b.destination))
+            .then_with(|| a.outgoing_channel_id.cmp(&b.outgoing_channel_id))
+    });
+    routes
+}
+
+/// Whether a probe result means a healthy route just started failing. A
+/// route that has never been probed, or was already failing, doesn't count.
+pub fn started_failing(previous: Option<&ProbeResult>, reached: bool) -> bool {
+    !reached && previous.is_some_and(|previous| previous.reached)
+}
+
+/// Service layer for route probing settings and results.
+pub struct ProbeService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ProbeService<'a> {
+    /// Creates a new ProbeService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns a node's probing settings; without any, probing is off.
+    pub async fn get_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<ProbePolicy> {
+        let policy = ProbeRepository::new(self.pool)
+            .get_policy(account_id, node_id)
+            .await?;
+
+        Ok(policy.unwrap_or_else(|| ProbePolicy {
+            account_id: account_id.to_string(),
+            node_id: node_id.to_string(),
+            is_enabled: false,
+            targets: "[]".to_string(),
+            amount_sat: 10_000,
+            max_fee_sat: 10,
+            updated_by: user_id.to_string(),
+            updated_at: None,
+        }))
+    }
+
+    /// Replaces a node's probing settings.
+    pub async fn update_policy(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: UpdateProbePolicyRequest,
+    ) -> ServiceResult<ProbePolicy> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let mut targets: Vec<ProbeTarget> = Vec::with_capacity(request.targets.len());
+        for target in &request.targets {
+            let destination = PublicKey::from_str(target.destination.trim()).map_err(|_| {
+                ServiceError::validation(format!("Invalid destination: {}", target.destination))
+            })?;
+            if destination.to_string() == node_id {
+                return Err(ServiceError::validation("A node cannot probe itself"));
+            }
+            let outgoing_channel_id = match target.outgoing_channel_id.as_deref() {
+                Some(channel_id) => Some(
+                    ShortChannelID::from_str(channel_id.trim())
+                        .map_err(|_| {
+                            ServiceError::validation(format!("Invalid channel ID: {channel_id}"))
+                        })?
+                        .to_string(),
+                ),
+                None => None,
+            };
+            let target = ProbeTarget {
+                destination: destination.to_string(),
+                outgoing_channel_id,
+            };
+            if targets.contains(&target) {
+                return Err(ServiceError::validation(format!(
+                    "Route to {} is listed twice",
+                    target.destination
+                )));
+            }
+            targets.push(target);
+        }
+
+        CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+
+        let policy = ProbeRepository::new(self.pool)
+            .upsert_policy(&ProbePolicy {
+                account_id: account_id.to_string(),
+                node_id: node_id.to_string(),
+                is_enabled: request.is_enabled,
+                targets: json!(targets).to_string(),
+                amount_sat: request.amount_sat,
+                max_fee_sat: request.max_fee_sat,
+                updated_by: user_id.to_string(),
+                updated_at: None,
+            })
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "probe_policy_updated",
+                "node",
+                Some(node_id),
+                &json!({
+                    "is_enabled": policy.is_enabled,
+                    "targets": policy.targets(),
+                    "amount_sat": policy.amount_sat,
+                    "max_fee_sat": policy.max_fee_sat,
+                }),
+            )
+            .await?;
+
+        Ok(policy)
+    }
+
+    /// Reports how reliably each probed route of the node carried its probes
+    /// over the requested number of days.
+    pub async fn get_report(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        query: ProbeReportQuery,
+    ) -> ServiceResult<ProbeReport> {
+        let days = query
+            .days
+            .unwrap_or(DEFAULT_REPORT_DAYS)
+            .clamp(1, MAX_REPORT_DAYS);
+        let results = ProbeRepository::new(self.pool)
+            .get_results_since(account_id, node_id, Utc::now() - Duration::days(days))
+            .await?;
+
+        let aliases: HashMap<String, String> = GraphRepository::new(self.pool)
+            .get_nodes(node_id)
+            .await?
+            .into_iter()
+            .map(|node| (node.pubkey, node.alias))
+            .collect();
+
+        Ok(ProbeReport {
+            days,
+            routes: route_health(&results, |pubkey| aliases.get(pubkey).cloned()),
+        })
+    }
+}
+
+/// Probes the routes of every node in the account that has probing enabled.
+pub async fn run_account_probes(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let policies = ProbeRepository::new(pool)
+        .get_enabled_policies(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let credential_repo = CredentialRepository::new(pool);
+
+    let mut failures = Vec::new();
+    for policy in policies {
+        let credential = match credential_repo
+            .get_credential_by_account_and_node_id(account_id, &policy.node_id)
+            .await
+        {
+            Ok(Some(credential)) => credential,
+            Ok(None) => continue,
+            Err(e) => {
+                failures.push(format!("{}: {e}", policy.node_id));
+                continue;
+            }
+        };
+        let user_id = credential.user_id.clone();
+        let node_alias = credential.node_alias.clone();
+
+        if let Err(e) = probe_node(
+            pool,
+            &policy,
+            &user_id,
+            &node_alias,
+            &NodeCredentials::from(credential),
+        )
+        .await
+        {
+            failures.push(format!("{}: {e}", policy.node_id));
+        }
+    }
+
+    let cutoff = Utc::now() - Duration::days(MAX_REPORT_DAYS);
+    if let Err(e) = ProbeRepository::new(pool)
+        .delete_results_before(account_id, cutoff)
+        .await
+    {
+        failures.push(format!("pruning: {e}"));
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Route probing failed for {}", failures.join("; ")))
+    }
+}
+
+/// Probes each of a node's target routes once, warning about routes that
+/// stopped carrying probes.
+async fn probe_node(
+    pool: &SqlitePool,
+    policy: &ProbePolicy,
+    user_id: &str,
+    node_alias: &str,
+    node_credentials: &NodeCredentials,
+) -> Result<(), String> {
+    let targets = policy.targets();
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    // Routes through a node being worked on fail for reasons of our own.
+    let now = Utc::now();
+    let maintenance = MaintenanceRepository::new(pool)
+        .get_windows_since(&policy.account_id, &policy.node_id, now)
+        .await
+        .map_err(|e| e.to_string())?;
+    if covers(&maintenance, now) {
+        return Ok(());
+    }
+
+    let public_key = PublicKey::from_str(&policy.node_id).map_err(|e| e.to_string())?;
+    let client = create_node_client(node_credentials, public_key)
+        .await
+        .map_err(|(_, body)| body)?;
+    let repo = ProbeRepository::new(pool);
+
+    for target in targets {
+        let destination = PublicKey::from_str(&target.destination).map_err(|e| e.to_string())?;
+        let outgoing = match target.outgoing_channel_id.as_deref() {
+            Some(channel_id) => Some(
+                ShortChannelID::from_str(channel_id)
+                    .map_err(|e| e.to_string())?
+                    .0,
+            ),
+            None => None,
+        };
+        let outcome = match client
+            .probe(
+                &destination,
+                policy.amount_sat as u64 * 1000,
+                outgoing,
+                policy.max_fee_sat as u64 * 1000,
+            )
+            .await
+        {
+            Ok(outcome) => outcome,
+            Err(e @ LightningError::Unsupported(_)) => return Err(e.to_string()),
+            Err(e) => ProbeOutcome {
+                reached: false,
+                latency_ms: 0,
+                failure_reason: Some(e.to_string()),
+            },
+        };
+
+        let previous = repo
+            .get_last_result(
+                &policy.account_id,
+                &policy.node_id,
+                &target.destination,
+                target.outgoing_channel_id.as_deref(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        let result = ProbeResult {
+            id: Uuid::now_v7().to_string(),
+            account_id: policy.account_id.clone(),
+            node_id: policy.node_id.clone(),
+            destination: target.destination.clone(),
+            outgoing_channel_id: target.outgoing_channel_id.clone(),
+            reached: outcome.reached,
+            latency_ms: outcome.latency_ms as i64,
+            failure_reason: outcome.failure_reason,
+            probed_at: Utc::now(),
+        };
+        repo.create_result(&result)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if started_failing(previous.as_ref(), result.reached) {
+            raise_probe_failed(pool, policy, user_id, node_alias, &result).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Raises a Warning event for a route that stopped carrying probes.
+async fn raise_probe_failed(
+    pool: &SqlitePool,
+    policy: &ProbePolicy,
+    user_id: &str,
+    node_alias: &str,
+    result: &ProbeResult,
+) -> Result<(), String> {
+    let reason = result.failure_reason.as_deref().unwrap_or("unknown reason");
+    let description = match &result.outgoing_channel_id {
+        Some(channel_id) => format!(
+            "Probes of {} sats no longer reach {} through channel {channel_id}: {reason}",
+            policy.amount_sat, result.destination
+        ),
+        None => format!(
+            "Probes of {} sats no longer reach {}: {reason}",
+            policy.amount_sat, result.destination
+        ),
+    };
+
+    let event = CreateEvent {
+        id: Uuid::now_v7().to_string(),
+        account_id: policy.account_id.clone(),
+        user_id: user_id.to_string(),
+        node_id: policy.node_id.clone(),
+        node_alias: node_alias.to_string(),
+        event_type: EventType::RouteProbeFailed,
+        severity: EventSeverity::Warning,
+        title: "Route Probe Failed".to_string(),
+        description,
+        data: json!({
+            "destination": result.destination,
+            "outgoing_channel_id": result.outgoing_channel_id,
+            "amount_sat": policy.amount_sat,
+            "failure_reason": result.failure_reason,
+        })
+        .to_string(),
+        notifications_id: None,
+        timestamp: Utc::now(),
+    };
+
+    EventService::new(pool)
+        .create_and_dispatch_event(event)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(destination: &str, reached: bool, latency_ms: i64) -> ProbeResult {
+        ProbeResult {
+            id: Uuid::now_v7().to_string(),
+            account_id: "account".to_string(),
+            node_id: "node".to_string(),
+            destination: destination.to_string(),
+            outgoing_channel_id: None,
+            reached,
+            latency_ms,
+            failure_reason: (!reached).then(|| "FailureReasonNoRoute".to_string()),
+            probed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_started_failing() {
+        let healthy = result("a", true, 900);
+        let failing = result("a", false, 0);
+
+        assert!(started_failing(Some(&healthy), false));
+        assert!(!started_failing(Some(&healthy), true));
+        assert!(!started_failing(Some(&failing), false));
+        assert!(!started_failing(None, false));
+    }
+
+    #[test]
+    fn test_route_health() {
+        let results = [
+            result("a", true, 800),
+            result("b", true, 500),
+            result("a", true, 1200),
+            result("a", false, 0),
+            result("b", true, 700),
+        ];
+        let routes = route_health(&results, |pubkey| {
+            (pubkey == "b").then(|| "bob".to_string())
+        });
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].destination, "a");
+        assert_eq!(routes[0].probes, 3);
+        assert_eq!(routes[0].reached, 2);
+        assert!(!routes[0].last_reached);
+        assert_eq!(
+            routes[0].last_failure_reason.as_deref(),
+            Some("FailureReasonNoRoute")
+        );
+        assert_eq!(routes[1].alias.as_deref(), Some("bob"));
+        assert_eq!(routes[1].success_rate_pct, 100.0);
+        assert!(routes[1].last_reached);
+    }
+}
@@ -0,0 +1,151 @@
+//! Saved filter views.
+//!
+//! A view is a named set of query parameters and a sort for one list
+//! endpoint, saved per user so the dashboard can offer it as one click
+//! instead of rebuilding the URL. Views are only stored here; the endpoint
+//! validates the parameters when the view is used.
+
+use crate::database::models::{CreateSavedViewRequest, SavedView, SavedViewResponse};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::saved_view_repository::SavedViewRepository;
+use chrono::Utc;
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Checks an endpoint is a bare API path, e.g. `/payments`, and drops any
+/// trailing slash.
+fn normalize_endpoint(endpoint: &str) -> ServiceResult<String> {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    let valid = endpoint.starts_with('/')
+        && endpoint.len() > 1
+        && endpoint
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_'));
+    if !valid {
+        return Err(ServiceError::validation(
+            "Endpoint must be an API path such as /payments, without a query string",
+        ));
+    }
+    Ok(endpoint.to_string())
+}
+
+/// Checks every filter can be sent as a query parameter: a string, number or
+/// boolean, or a list of them for comma-separated parameters.
+fn validate_filters(filters: &Map<String, Value>) -> ServiceResult<()> {
+    let is_scalar =
+        |value: &Value| matches!(value, Value::String(_) | Value::Number(_) | Value::Bool(_));
+    for (name, value) in filters {
+        let valid = match value {
+            Value::Array(values) => values.iter().all(is_scalar),
+            value => is_scalar(value),
+        };
+        if !valid {
+            return Err(ServiceError::validation(format!(
+                "Filter '{name}' must be a string, number, boolean or a list of them"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Service layer for saved filter views.
+pub struct SavedViewService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SavedViewService<'a> {
+    /// Creates a new SavedViewService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the user's views, optionally only those for one endpoint.
+    pub async fn list_views(
+        &self,
+        user_id: &str,
+        endpoint: Option<&str>,
+    ) -> ServiceResult<Vec<SavedViewResponse>> {
+        let endpoint = endpoint.map(normalize_endpoint).transpose()?;
+        let views = SavedViewRepository::new(self.pool)
+            .get_views_by_user_id(user_id, endpoint.as_deref())
+            .await?;
+        Ok(views.into_iter().map(SavedViewResponse::from).collect())
+    }
+
+    /// Saves a view under a name the user hasn't used yet.
+    pub async fn create_view(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        request: CreateSavedViewRequest,
+    ) -> ServiceResult<SavedViewResponse> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        let name = request.name.trim().to_string();
+        if name.is_empty() {
+            return Err(ServiceError::validation("Name is required"));
+        }
+        let endpoint = normalize_endpoint(&request.endpoint)?;
+        validate_filters(&request.filters)?;
+
+        let repo = SavedViewRepository::new(self.pool);
+        if repo.name_exists(user_id, &name).await? {
+            return Err(ServiceError::already_exists("Saved view with name", &name));
+        }
+
+        let view = repo
+            .create_view(&SavedView {
+                id: Uuid::now_v7().to_string(),
+                account_id: account_id.to_string(),
+                user_id: user_id.to_string(),
+                name,
+                endpoint,
+                filters: Value::Object(request.filters).to_string(),
+                sort: request.sort.map(|sort| sort.trim().to_string()),
+                created_at: Utc::now(),
+            })
+            .await?;
+        Ok(SavedViewResponse::from(view))
+    }
+
+    /// Deletes one of the user's views.
+    pub async fn delete_view(&self, user_id: &str, id: &str) -> ServiceResult<()> {
+        if !SavedViewRepository::new(self.pool)
+            .delete_view(id, user_id)
+            .await?
+        {
+            return Err(ServiceError::not_found("Saved view", id));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_endpoint() {
+        assert_eq!(normalize_endpoint(" /payments/ ").unwrap(), "/payments");
+        assert!(normalize_endpoint("/payments?states=failed").is_err());
+        assert!(normalize_endpoint("payments").is_err());
+        assert!(normalize_endpoint("/").is_err());
+    }
+
+    #[test]
+    fn test_validate_filters() {
+        let filters = json!({
+            "states": ["failed"],
+            "operator": "gt",
+            "value": 100000,
+        });
+        assert!(validate_filters(filters.as_object().unwrap()).is_ok());
+
+        let nested = json!({ "value": { "gt": 100000 } });
+        assert!(validate_filters(nested.as_object().unwrap()).is_err());
+    }
+}
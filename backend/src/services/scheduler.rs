@@ -0,0 +1,596 @@
+//! Cron-driven scheduler for recurring maintenance tasks.
+//!
+//! Each row in `scheduled_tasks` carries a cron expression and the time of its
+//! next run. A single ticker enqueues due tasks onto the job queue, so runs
+//! inherit the queue's persistence and retries, and records when the following
+//! run is due. Balance snapshots, event digests, graph syncs, fee automation,
+//! auto-rebalancing, payment latency recording, route probing, notification
+//! endpoint checks and usage rollups exist once per account; price backfills,
+//! database backups, retention pruning and Lightning billing are system-wide.
+
+use crate::config::Config;
+use crate::database::models::{
+    BalanceSnapshot, EventSeverity, Job, JobType, ScheduledTask, TaskRunStatus, TaskType,
+    UpdateScheduledTaskRequest,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::account_repository::AccountRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::event_repository::EventRepository;
+use crate::repositories::scheduled_task_repository::ScheduledTaskRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::services::account_settings_service::AccountSettingsService;
+use crate::services::email_service::EmailService;
+use crate::services::fee_automation::run_account_fee_automation;
+use crate::services::forward_history::backfill_account_forwards;
+use crate::services::graph_sync::sync_account_graphs;
+use crate::services::job_queue::JobQueue;
+use crate::services::lightning_billing::run_lightning_billing;
+use crate::services::notification_health::check_account_endpoints;
+use crate::services::payment_latency::record_account_latencies;
+use crate::services::rebalance_service::run_account_rebalancing;
+use crate::services::retention_service::prune_expired_data;
+use crate::services::route_probes::run_account_probes;
+use crate::services::usage_meter::roll_up_account_usage;
+use crate::utils::ChannelState;
+use crate::utils::cron::CronSchedule;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use crate::utils::sats_to_usd::PriceConverter;
+use bitcoin::secp256k1::PublicKey;
+use chrono::{DateTime, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
+use validator::Validate;
+
+/// How often the scheduler checks for due tasks.
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum number of missing hourly prices fetched in one backfill run.
+const MAX_BACKFILL_HOURS: i64 = 48;
+
+const BACKUP_FILE_PREFIX: &str = "nodegaze-";
+
+/// Payload of a `ScheduledTask` job.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduledTaskJob {
+    pub task_id: String,
+}
+
+/// Cron expression a task is created with.
+pub fn default_schedule(task_type: &TaskType) -> &'static str {
+    match task_type {
+        TaskType::BalanceSnapshot => "0 * * * *",
+        TaskType::PriceBackfill => "5 * * * *",
+        TaskType::EventDigest => "0 8 * * *",
+        TaskType::DatabaseBackup => "0 3 * * *",
+        TaskType::DataRetention => "30 2 * * *",
+        TaskType::GraphSync => "15 */6 * * *",
+        TaskType::FeeAutomation => "*/30 * * * *",
+        TaskType::AutoRebalance => "45 * * * *",
+        TaskType::PaymentLatency => "*/15 * * * *",
+        TaskType::ForwardHistory => "20 * * * *",
+        TaskType::RouteProbe => "*/10 * * * *",
+        TaskType::NotificationHealthCheck => "*/5 * * * *",
+        TaskType::UsageRollup => "*/15 * * * *",
+        TaskType::LightningBilling => "25 * * * *",
+    }
+}
+
+/// Service layer for inspecting and configuring scheduled tasks.
+pub struct SchedulerService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SchedulerService<'a> {
+    /// Creates a new SchedulerService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates any missing system-wide tasks.
+    pub async fn ensure_system_tasks(&self) -> ServiceResult<()> {
+        for task_type in [
+            TaskType::PriceBackfill,
+            TaskType::DatabaseBackup,
+            TaskType::DataRetention,
+            TaskType::LightningBilling,
+        ] {
+            self.ensure_task(task_type, None).await?;
+        }
+        Ok(())
+    }
+
+    /// Creates any missing per-account tasks for an account.
+    pub async fn ensure_account_tasks(&self, account_id: &str) -> ServiceResult<()> {
+        for task_type in [
+            TaskType::BalanceSnapshot,
+            TaskType::EventDigest,
+            TaskType::GraphSync,
+            TaskType::FeeAutomation,
+            TaskType::AutoRebalance,
+            TaskType::PaymentLatency,
+            TaskType::ForwardHistory,
+            TaskType::RouteProbe,
+            TaskType::NotificationHealthCheck,
+            TaskType::UsageRollup,
+        ] {
+            self.ensure_task(task_type, Some(account_id)).await?;
+        }
+        Ok(())
+    }
+
+    async fn ensure_task(
+        &self,
+        task_type: TaskType,
+        account_id: Option<&str>,
+    ) -> ServiceResult<()> {
+        let expression = default_schedule(&task_type);
+        let next_run_at = CronSchedule::parse(expression)
+            .map_err(ServiceError::internal_error)?
+            .next_after(Utc::now());
+
+        ScheduledTaskRepository::new(self.pool)
+            .ensure_task(
+                &Uuid::now_v7().to_string(),
+                task_type,
+                account_id,
+                expression,
+                next_run_at,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Lists the account's tasks, together with system-wide tasks for the
+    /// operator's account.
+    pub async fn list_tasks(
+        &self,
+        account_id: &str,
+        is_operator: bool,
+    ) -> ServiceResult<Vec<ScheduledTask>> {
+        Ok(ScheduledTaskRepository::new(self.pool)
+            .get_tasks(account_id, is_operator)
+            .await?)
+    }
+
+    /// Changes the schedule or active flag of one of the account's tasks.
+    ///
+    /// System-wide tasks are shared by every account and cannot be changed here.
+    pub async fn update_task(
+        &self,
+        account_id: &str,
+        task_id: &str,
+        request: UpdateScheduledTaskRequest,
+    ) -> ServiceResult<ScheduledTask> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+
+        let repo = ScheduledTaskRepository::new(self.pool);
+        let task = repo
+            .get_task_by_id(task_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Scheduled task", task_id))?;
+
+        match task.account_id.as_deref() {
+            Some(owner) if owner == account_id => {}
+            Some(_) => return Err(ServiceError::not_found("Scheduled task", task_id)),
+            None => {
+                return Err(ServiceError::permission_denied(
+                    "System tasks cannot be modified",
+                ));
+            }
+        }
+
+        let cron_expression = request.cron_expression.unwrap_or(task.cron_expression);
+        let schedule = CronSchedule::parse(&cron_expression).map_err(ServiceError::validation)?;
+        let is_active = request.is_active.unwrap_or(task.is_active);
+        let next_run_at = if is_active {
+            schedule.next_after(Utc::now())
+        } else {
+            None
+        };
+
+        Ok(repo
+            .update_task(task_id, &schedule.to_string(), is_active, next_run_at)
+            .await?)
+    }
+
+    /// Queues a run of one of the account's tasks right away, leaving its
+    /// schedule untouched.
+    pub async fn run_task_now(&self, account_id: &str, task_id: &str) -> ServiceResult<Job> {
+        let task = ScheduledTaskRepository::new(self.pool)
+            .get_task_by_id(task_id)
+            .await?
+            .filter(|task| task.account_id.as_deref() == Some(account_id))
+            .ok_or_else(|| ServiceError::not_found("Scheduled task", task_id))?;
+
+        JobQueue::new(self.pool)
+            .enqueue(
+                JobType::ScheduledTask,
+                &ScheduledTaskJob { task_id: task.id },
+                Some(account_id),
+                None,
+            )
+            .await
+    }
+}
+
+/// Creates missing tasks and starts the scheduler loop.
+pub async fn start_scheduler(pool: SqlitePool) {
+    if let Err(e) = ensure_all_tasks(&pool).await {
+        tracing::error!("Failed to create scheduled tasks: {}", e);
+    }
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = enqueue_due_tasks(&pool).await {
+                tracing::error!("Scheduler tick failed: {}", e);
+            }
+            tokio::time::sleep(TICK_INTERVAL).await;
+        }
+    });
+
+    tracing::info!("Started task scheduler");
+}
+
+async fn ensure_all_tasks(pool: &SqlitePool) -> ServiceResult<()> {
+    let service = SchedulerService::new(pool);
+    service.ensure_system_tasks().await?;
+    for account_id in AccountRepository::new(pool)
+        .get_active_account_ids()
+        .await?
+    {
+        service.ensure_account_tasks(&account_id).await?;
+    }
+    Ok(())
+}
+
+async fn enqueue_due_tasks(pool: &SqlitePool) -> ServiceResult<()> {
+    let now = Utc::now();
+    let repo = ScheduledTaskRepository::new(pool);
+
+    for task in repo.get_due_tasks(now).await? {
+        let next_run_at = match CronSchedule::parse(&task.cron_expression) {
+            Ok(schedule) => schedule.next_after(now),
+            Err(e) => {
+                tracing::error!("Task {} has an invalid schedule: {}", task.id, e);
+                None
+            }
+        };
+
+        JobQueue::new(pool)
+            .enqueue(
+                JobType::ScheduledTask,
+                &ScheduledTaskJob {
+                    task_id: task.id.clone(),
+                },
+                task.account_id.as_deref(),
+                None,
+            )
+            .await?;
+        repo.mark_queued(&task.id, now, next_run_at).await?;
+
+        tracing::debug!("Queued {} task {}", task.task_type, task.id);
+    }
+
+    Ok(())
+}
+
+/// Runs a scheduled task from its job payload and records the outcome on the task.
+pub async fn run_scheduled_task(pool: &SqlitePool, payload: &str) -> Result<(), String> {
+    let job: ScheduledTaskJob = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+    let repo = ScheduledTaskRepository::new(pool);
+    let task = repo
+        .get_task_by_id(&job.task_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Scheduled task {} no longer exists", job.task_id))?;
+
+    let result = match (&task.task_type, task.account_id.as_deref()) {
+        (TaskType::BalanceSnapshot, Some(account_id)) => {
+            capture_balance_snapshots(pool, account_id).await
+        }
+        (TaskType::EventDigest, Some(account_id)) => {
+            send_event_digest(pool, &task, account_id).await
+        }
+        (TaskType::GraphSync, Some(account_id)) => sync_account_graphs(pool, account_id).await,
+        (TaskType::FeeAutomation, Some(account_id)) => {
+            run_account_fee_automation(pool, account_id).await
+        }
+        (TaskType::AutoRebalance, Some(account_id)) => {
+            run_account_rebalancing(pool, account_id).await
+        }
+        (TaskType::PaymentLatency, Some(account_id)) => {
+            record_account_latencies(pool, account_id).await
+        }
+        (TaskType::ForwardHistory, Some(account_id)) => {
+            backfill_account_forwards(pool, account_id).await
+        }
+        (TaskType::RouteProbe, Some(account_id)) => run_account_probes(pool, account_id).await,
+        (TaskType::NotificationHealthCheck, Some(account_id)) => {
+            check_account_endpoints(pool, account_id).await
+        }
+        (TaskType::UsageRollup, Some(account_id)) => roll_up_account_usage(pool, account_id).await,
+        (TaskType::PriceBackfill, _) => backfill_prices(pool).await,
+        (TaskType::DatabaseBackup, _) => backup_database(pool).await,
+        (TaskType::DataRetention, _) => prune_expired_data(pool).await,
+        (TaskType::LightningBilling, _) => run_lightning_billing(pool).await,
+        (task_type, None) => Err(format!("{task_type} task has no account")),
+    };
+
+    let recorded = match &result {
+        Ok(()) => {
+            repo.record_result(&task.id, TaskRunStatus::Succeeded, None)
+                .await
+        }
+        Err(e) => {
+            repo.record_result(&task.id, TaskRunStatus::Failed, Some(e))
+                .await
+        }
+    };
+    if let Err(e) = recorded {
+        tracing::error!("Failed to record outcome of task {}: {}", task.id, e);
+    }
+
+    result
+}
+
+/// Records the channel balances of every node connected to the account.
+async fn capture_balance_snapshots(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let credentials = CredentialRepository::new(pool)
+        .get_credentials_by_account_id(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let btc_price = PriceConverter::new().fetch_btc_price().await.ok();
+    let repo = ScheduledTaskRepository::new(pool);
+
+    let mut failures = Vec::new();
+    for credential in credentials {
+        let node_credentials = NodeCredentials::from(credential);
+        let node_id = node_credentials.node_id.clone();
+
+        let snapshot = async {
+            let public_key = PublicKey::from_str(&node_id).map_err(|e| e.to_string())?;
+            // Goes through the node's concurrency budget like any dashboard request.
+            let client = create_node_client(&node_credentials, public_key)
+                .await
+                .map_err(|(_, body)| body)?;
+            let channels = client.list_channels().await.map_err(|e| e.to_string())?;
+
+            Ok::<_, String>(BalanceSnapshot {
+                id: Uuid::now_v7().to_string(),
+                account_id: account_id.to_string(),
+                node_id: node_id.clone(),
+                local_balance_sat: channels.iter().map(|c| c.local_balance as i64).sum(),
+                remote_balance_sat: channels.iter().map(|c| c.remote_balance as i64).sum(),
+                capacity_sat: channels.iter().map(|c| c.capacity as i64).sum(),
+                num_channels: channels.len() as i64,
+                num_active_channels: channels
+                    .iter()
+                    .filter(|c| matches!(c.channel_state, ChannelState::Active))
+                    .count() as i64,
+                btc_price_usd: btc_price,
+                captured_at: Utc::now(),
+                created_at: Utc::now(),
+            })
+        }
+        .await;
+
+        match snapshot {
+            Ok(snapshot) => {
+                if let Err(e) = repo.create_balance_snapshot(&snapshot).await {
+                    failures.push(format!("{node_id}: {e}"));
+                }
+            }
+            Err(e) => failures.push(format!("{node_id}: {e}")),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Snapshot failed for {}", failures.join("; ")))
+    }
+}
+
+/// Fills in missing hourly BTC prices up to the current hour.
+async fn backfill_prices(pool: &SqlitePool) -> Result<(), String> {
+    let repo = ScheduledTaskRepository::new(pool);
+    let converter = PriceConverter::new();
+    let current_hour = Utc::now()
+        .duration_trunc(chrono::Duration::hours(1))
+        .map_err(|e| e.to_string())?;
+
+    let latest = repo
+        .get_latest_price_time()
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut hour = match latest {
+        Some(latest) => (latest + chrono::Duration::hours(1))
+            .max(current_hour - chrono::Duration::hours(MAX_BACKFILL_HOURS)),
+        None => current_hour,
+    };
+
+    while hour < current_hour {
+        let price = converter
+            .fetch_historical_btc_price(hour.timestamp())
+            .await
+            .map_err(|e| e.to_string())?;
+        repo.insert_price(&Uuid::now_v7().to_string(), price, hour)
+            .await
+            .map_err(|e| e.to_string())?;
+        hour += chrono::Duration::hours(1);
+    }
+
+    let price = converter
+        .fetch_btc_price()
+        .await
+        .map_err(|e| e.to_string())?;
+    repo.insert_price(&Uuid::now_v7().to_string(), price, current_hour)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Emails the account admin a summary of events since the previous run.
+async fn send_event_digest(
+    pool: &SqlitePool,
+    task: &ScheduledTask,
+    account_id: &str,
+) -> Result<(), String> {
+    let config = Config::from_env().map_err(|e| e.to_string())?;
+    let Some(email_config) = config.email_config() else {
+        tracing::debug!(
+            "Email is not configured, skipping digest for {}",
+            account_id
+        );
+        return Ok(());
+    };
+
+    // The digest covers one schedule period, ending at this run.
+    let period_end = task.last_run_at.unwrap_or_else(Utc::now);
+    let since = task
+        .next_run_at
+        .map(|next| period_end - (next - period_end))
+        .unwrap_or_else(|| period_end - chrono::Duration::days(1));
+
+    let event_repo = EventRepository::new(pool);
+    let mut counts = Vec::new();
+    for severity in [
+        EventSeverity::Critical,
+        EventSeverity::Warning,
+        EventSeverity::Info,
+    ] {
+        let count = event_repo
+            .count_events_by_account_and_severity_since(account_id, &severity, since)
+            .await
+            .map_err(|e| e.to_string())?;
+        counts.push((severity, count));
+    }
+
+    if counts.iter().all(|(_, count)| *count == 0) {
+        return Ok(());
+    }
+
+    let Some(admin) = UserRepository::new(pool)
+        .get_admin_user_by_account_id(account_id)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Err("Account has no admin user".to_string());
+    };
+
+    let tz = AccountSettingsService::new(pool)
+        .get_settings(account_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .tz();
+
+    let summary: Vec<String> = counts
+        .iter()
+        .map(|(severity, count)| format!("{severity}: {count}"))
+        .collect();
+    let text = format!(
+        "Events since {}:\n{}\n\nView them at {}/events",
+        format_time(since, tz),
+        summary.join("\n"),
+        config.base_url
+    );
+    let html = format!(
+        "<p>Events since {}:</p><ul>{}</ul><p><a href=\"{}/events\">View events</a></p>",
+        format_time(since, tz),
+        summary
+            .iter()
+            .map(|line| format!("<li>{line}</li>"))
+            .collect::<String>(),
+        config.base_url
+    );
+
+    EmailService::new(email_config)
+        .map_err(|e| e.to_string())?
+        .send_email(&admin.email, "Your NodeGaze event digest", &html, &text)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn format_time(time: DateTime<Utc>, tz: chrono_tz::Tz) -> String {
+    time.with_timezone(&tz)
+        .format("%Y-%m-%d %H:%M %Z")
+        .to_string()
+}
+
+/// Writes a consistent copy of the database to the backup directory and prunes old copies.
+async fn backup_database(pool: &SqlitePool) -> Result<(), String> {
+    let config = Config::from_env().map_err(|e| e.to_string())?;
+    let dir = Path::new(&config.backup_dir);
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("Failed to create backup directory: {e}"))?;
+
+    let path = dir.join(format!(
+        "{BACKUP_FILE_PREFIX}{}.db",
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Backup failed: {e}"))?;
+
+    tracing::info!("Wrote database backup to {}", path.display());
+    prune_backups(dir, config.backup_retention).await
+}
+
+async fn prune_backups(dir: &Path, retention: usize) -> Result<(), String> {
+    let mut entries = tokio::fs::read_dir(dir).await.map_err(|e| e.to_string())?;
+    let mut backups = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(BACKUP_FILE_PREFIX) && name.ends_with(".db") {
+            backups.push(entry.path());
+        }
+    }
+
+    // Timestamped names sort chronologically.
+    backups.sort();
+    let excess = backups.len().saturating_sub(retention.max(1));
+    for path in backups.into_iter().take(excess) {
+        tokio::fs::remove_file(&path)
+            .await
+            .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_schedules_parse() {
+        for task_type in [
+            TaskType::BalanceSnapshot,
+            TaskType::PriceBackfill,
+            TaskType::EventDigest,
+            TaskType::DatabaseBackup,
+            TaskType::DataRetention,
+            TaskType::GraphSync,
+            TaskType::FeeAutomation,
+            TaskType::AutoRebalance,
+            TaskType::PaymentLatency,
+            TaskType::ForwardHistory,
+            TaskType::RouteProbe,
+            TaskType::NotificationHealthCheck,
+            TaskType::UsageRollup,
+            TaskType::LightningBilling,
+        ] {
+            assert!(CronSchedule::parse(default_schedule(&task_type)).is_ok());
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Client for the parts of the Stripe API used by billing.
+//!
+//! Checkout sessions are created with form-encoded requests authenticated by
+//! the secret key. Webhook deliveries carry a `Stripe-Signature` header: an
+//! HMAC-SHA256 of `<timestamp>.<body>` keyed with the endpoint's signing
+//! secret, which is checked before any event is trusted.
+
+use crate::database::models::CheckoutSession;
+use crate::errors::{ServiceError, ServiceResult};
+use bitcoin::hashes::{Hash, HashEngine, Hmac, HmacEngine, sha256};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+
+/// How far a webhook's signed timestamp may be from now, in seconds.
+pub const WEBHOOK_TOLERANCE_SECS: i64 = 300;
+
+/// A webhook event; only the fields billing reads are kept.
+#[derive(Debug, Deserialize)]
+pub struct StripeEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: StripeEventData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StripeEventData {
+    pub object: Value,
+}
+
+/// What a new checkout session subscribes the customer to.
+#[derive(Debug)]
+pub struct CheckoutParams<'a> {
+    pub price_id: &'a str,
+    pub account_id: &'a str,
+    pub plan: &'a str,
+    /// Existing Stripe customer to charge; otherwise one is created
+    pub customer_id: Option<&'a str>,
+    pub customer_email: Option<&'a str>,
+    pub success_url: &'a str,
+    pub cancel_url: &'a str,
+}
+
+/// Checks a webhook's `Stripe-Signature` header against its raw body.
+pub fn verify_signature(
+    payload: &[u8],
+    header: &str,
+    secret: &str,
+    now: i64,
+) -> Result<(), String> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or("Signature header has no timestamp")?;
+    if (now - timestamp).abs() > WEBHOOK_TOLERANCE_SECS {
+        return Err("Signature timestamp is outside the tolerance".to_string());
+    }
+
+    let mut engine = HmacEngine::<sha256::Hash>::new(secret.as_bytes());
+    engine.input(timestamp.to_string().as_bytes());
+    engine.input(b".");
+    engine.input(payload);
+    let expected = hex::encode(Hmac::<sha256::Hash>::from_engine(engine).to_byte_array());
+
+    if signatures
+        .iter()
+        .any(|signature| constant_time_eq(signature.as_bytes(), expected.as_bytes()))
+    {
+        Ok(())
+    } else {
+        Err("No matching signature".to_string())
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Talks to the Stripe API.
+pub struct StripeClient {
+    http_client: Client,
+    base_url: String,
+    secret_key: String,
+}
+
+impl StripeClient {
+    /// Creates a client for the Stripe API at `base_url`.
+    pub fn new(base_url: &str, secret_key: &str) -> ServiceResult<Self> {
+        let http_client = Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .map_err(|e| ServiceError::internal_error(e.to_string()))?;
+
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            secret_key: secret_key.to_string(),
+        })
+    }
+
+    /// Creates a subscription checkout session. The account and plan are
+    /// recorded on both the session and the subscription it creates, so the
+    /// webhook can tell which account an event belongs to.
+    pub async fn create_checkout_session(
+        &self,
+        params: CheckoutParams<'_>,
+    ) -> ServiceResult<CheckoutSession> {
+        let mut form = vec![
+            ("mode", "subscription".to_string()),
+            ("line_items[0][price]", params.price_id.to_string()),
+            ("line_items[0][quantity]", "1".to_string()),
+            ("client_reference_id", params.account_id.to_string()),
+            ("success_url", params.success_url.to_string()),
+            ("cancel_url", params.cancel_url.to_string()),
+            ("metadata[account_id]", params.account_id.to_string()),
+            ("metadata[plan]", params.plan.to_string()),
+            (
+                "subscription_data[metadata][account_id]",
+                params.account_id.to_string(),
+            ),
+            ("subscription_data[metadata][plan]", params.plan.to_string()),
+        ];
+        match (params.customer_id, params.customer_email) {
+            (Some(customer_id), _) => form.push(("customer", customer_id.to_string())),
+            (None, Some(email)) => form.push(("customer_email", email.to_string())),
+            (None, None) => {}
+        }
+
+        let response = self
+            .http_client
+            .post(format!("{}/v1/checkout/sessions", self.base_url))
+            .bearer_auth(&self.secret_key)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| ServiceError::external_service(format!("Stripe unreachable: {e}")))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ServiceError::external_service(format!(
+                "Stripe rejected the checkout session ({status}): {body}"
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ServiceError::external_service(format!("Invalid Stripe response: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(payload: &[u8], secret: &str, timestamp: i64) -> String {
+        let mut engine = HmacEngine::<sha256::Hash>::new(secret.as_bytes());
+        engine.input(format!("{timestamp}.").as_bytes());
+        engine.input(payload);
+        hex::encode(Hmac::<sha256::Hash>::from_engine(engine).to_byte_array())
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let payload = br#"{"id":"evt_1"}"#;
+        let now = 1_757_000_000;
+        let signature = sign(payload, "whsec_test", now);
+
+        let header = format!("t={now},v1=deadbeef,v1={signature}");
+        assert!(verify_signature(payload, &header, "whsec_test", now + 10).is_ok());
+        assert!(verify_signature(payload, &header, "whsec_other", now).is_err());
+        assert!(verify_signature(b"{}", &header, "whsec_test", now).is_err());
+        assert!(
+            verify_signature(
+                payload,
+                &header,
+                "whsec_test",
+                now + WEBHOOK_TOLERANCE_SECS + 1
+            )
+            .is_err()
+        );
+        assert!(verify_signature(payload, &format!("v1={signature}"), "whsec_test", now).is_err());
+    }
+}
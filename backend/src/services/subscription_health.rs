@@ -0,0 +1,423 @@
+//! Health of the long-lived streams held open against each node.
+//!
+//! Every background subscription (node events, graph updates, the HTLC
+//! interceptor and the channel acceptor) reports when it connects, drops,
+//! receives an item and how long that item took to process. A stream that
+//! is still marked connected but hasn't delivered anything in a long time has
+//! most likely died without the node closing it.
+//!
+//! Event types of the node event stream can also be paused and resumed here.
+//! Resuming one restarts the stream if it is no longer connected. Admins can
+//! restart any subscription directly.
+
+use crate::database::models::{EventSubscriptionStatus, EventType};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::channel_acceptor_repository::ChannelAcceptorRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::event_subscription_repository::EventSubscriptionRepository;
+use crate::repositories::htlc_interceptor_repository::HtlcInterceptorRepository;
+use crate::services::channel_acceptor::start_acceptor;
+use crate::services::event_manager::{STREAMED_EVENT_TYPES, start_event_stream};
+use crate::services::graph_sync::restart_subscription;
+use crate::services::htlc_interceptor::start_interceptor;
+use crate::utils::jwt::NodeCredentials;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionKind {
+    Events,
+    Graph,
+    HtlcInterceptor,
+    ChannelAcceptor,
+}
+
+/// Counters of one subscription since the server started.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionStats {
+    pub node_id: String,
+    pub kind: SubscriptionKind,
+    pub connected: bool,
+    /// When the subscription first connected
+    pub started_at: DateTime<Utc>,
+    /// When the current (or last) connection was made
+    pub connected_at: DateTime<Utc>,
+    /// Seconds the current connection has been up, None while disconnected
+    pub uptime_secs: Option<i64>,
+    pub events_received: u64,
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// Times the stream was re-established after dropping
+    pub reconnects: u64,
+    pub last_error: Option<String>,
+    /// Time taken to process the most recent item
+    pub last_lag_ms: Option<u64>,
+    pub max_lag_ms: Option<u64>,
+}
+
+/// Subscription stats keyed by (node_id, kind)
+static SUBSCRIPTIONS: OnceLock<Mutex<HashMap<(String, SubscriptionKind), SubscriptionStats>>> =
+    OnceLock::new();
+
+fn subscriptions() -> &'static Mutex<HashMap<(String, SubscriptionKind), SubscriptionStats>> {
+    SUBSCRIPTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Records that a subscription's stream is up. Connecting again after the
+/// first time counts as a reconnect.
+pub fn record_connected(node_id: &str, kind: SubscriptionKind) {
+    let now = Utc::now();
+    let mut subscriptions = subscriptions().lock().unwrap();
+    subscriptions
+        .entry((node_id.to_string(), kind))
+        .and_modify(|stats| {
+            stats.connected = true;
+            stats.connected_at = now;
+            stats.reconnects += 1;
+        })
+        .or_insert_with(|| SubscriptionStats {
+            node_id: node_id.to_string(),
+            kind,
+            connected: true,
+            started_at: now,
+            connected_at: now,
+            uptime_secs: None,
+            events_received: 0,
+            last_event_at: None,
+            reconnects: 0,
+            last_error: None,
+            last_lag_ms: None,
+            max_lag_ms: None,
+        });
+}
+
+/// Records that a subscription's stream ended, with the error if it failed.
+pub fn record_disconnected(node_id: &str, kind: SubscriptionKind, error: Option<String>) {
+    let mut subscriptions = subscriptions().lock().unwrap();
+    if let Some(stats) = subscriptions.get_mut(&(node_id.to_string(), kind)) {
+        stats.connected = false;
+        if error.is_some() {
+            stats.last_error = error;
+        }
+    }
+}
+
+/// Records an item received on a subscription.
+pub fn record_event(node_id: &str, kind: SubscriptionKind) {
+    let mut subscriptions = subscriptions().lock().unwrap();
+    if let Some(stats) = subscriptions.get_mut(&(node_id.to_string(), kind)) {
+        stats.events_received += 1;
+        stats.last_event_at = Some(Utc::now());
+    }
+}
+
+/// Records how long an item took from arriving to being handled.
+pub fn record_lag(node_id: &str, kind: SubscriptionKind, lag: Duration) {
+    let lag_ms = lag.as_millis() as u64;
+    let mut subscriptions = subscriptions().lock().unwrap();
+    if let Some(stats) = subscriptions.get_mut(&(node_id.to_string(), kind)) {
+        stats.last_lag_ms = Some(lag_ms);
+        stats.max_lag_ms = Some(stats.max_lag_ms.map_or(lag_ms, |max| max.max(lag_ms)));
+    }
+}
+
+fn is_connected(node_id: &str, kind: SubscriptionKind) -> bool {
+    let subscriptions = subscriptions().lock().unwrap();
+    subscriptions
+        .get(&(node_id.to_string(), kind))
+        .is_some_and(|stats| stats.connected)
+}
+
+/// Parses an event type from a path, accepting only those a node streams.
+fn parse_streamed_event_type(event_type: &str) -> ServiceResult<EventType> {
+    EventType::from_str(event_type)
+        .ok()
+        .filter(|event_type| STREAMED_EVENT_TYPES.contains(event_type))
+        .ok_or_else(|| {
+            ServiceError::validation(format!(
+                "{event_type} is not an event type of the node event stream"
+            ))
+        })
+}
+
+fn node_subscriptions(node_ids: &[&str]) -> Vec<SubscriptionStats> {
+    let now = Utc::now();
+    let subscriptions = subscriptions().lock().unwrap();
+    let mut stats: Vec<_> = subscriptions
+        .iter()
+        .filter(|((id, _), _)| node_ids.contains(&id.as_str()))
+        .map(|(_, stats)| SubscriptionStats {
+            uptime_secs: stats
+                .connected
+                .then(|| (now - stats.connected_at).num_seconds()),
+            ..stats.clone()
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        a.node_id
+            .cmp(&b.node_id)
+            .then(a.started_at.cmp(&b.started_at))
+    });
+    stats
+}
+
+pub struct SubscriptionService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SubscriptionService<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the subscriptions held open against one of the account's nodes.
+    pub async fn get_subscriptions(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<Vec<SubscriptionStats>> {
+        self.get_node_credentials(account_id, node_id).await?;
+        Ok(node_subscriptions(&[node_id]))
+    }
+
+    /// Lists the subscriptions of every node of the account.
+    pub async fn get_account_subscriptions(
+        &self,
+        account_id: &str,
+    ) -> ServiceResult<Vec<SubscriptionStats>> {
+        let credentials = CredentialRepository::new(self.pool)
+            .get_credentials_by_account_id(account_id)
+            .await?;
+        let node_ids: Vec<&str> = credentials.iter().map(|c| c.node_id.as_str()).collect();
+        Ok(node_subscriptions(&node_ids))
+    }
+
+    /// Restarts one of a node's subscriptions. Interceptors and acceptors are
+    /// only restarted while their policy is enabled, and a connected event
+    /// stream is left alone since it can't be replaced without losing events.
+    pub async fn restart(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        kind: SubscriptionKind,
+    ) -> ServiceResult<Vec<SubscriptionStats>> {
+        let node_credentials = self.get_node_credentials(account_id, node_id).await?;
+
+        match kind {
+            SubscriptionKind::Events => {
+                if is_connected(node_id, kind) {
+                    return Err(ServiceError::validation(
+                        "The node's event stream is connected",
+                    ));
+                }
+                start_event_stream(
+                    self.pool.clone(),
+                    account_id.to_string(),
+                    user_id.to_string(),
+                    &node_credentials,
+                )
+                .await
+                .map_err(|e| {
+                    ServiceError::external_service(format!("Failed to restart event stream: {e}"))
+                })?;
+            }
+            SubscriptionKind::Graph => restart_subscription(self.pool.clone(), node_credentials),
+            SubscriptionKind::HtlcInterceptor => {
+                HtlcInterceptorRepository::new(self.pool)
+                    .get_policy(account_id, node_id)
+                    .await?
+                    .filter(|policy| policy.is_enabled)
+                    .ok_or_else(|| {
+                        ServiceError::validation("The node's HTLC interceptor is not enabled")
+                    })?;
+                start_interceptor(self.pool.clone(), account_id, node_id);
+            }
+            SubscriptionKind::ChannelAcceptor => {
+                ChannelAcceptorRepository::new(self.pool)
+                    .get_policy(account_id, node_id)
+                    .await?
+                    .filter(|policy| policy.is_enabled)
+                    .ok_or_else(|| {
+                        ServiceError::validation("The node's channel acceptor is not enabled")
+                    })?;
+                start_acceptor(self.pool.clone(), account_id, node_id);
+            }
+        }
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "subscription_restarted",
+                "node",
+                Some(node_id),
+                &json!({ "kind": kind }),
+            )
+            .await?;
+
+        Ok(node_subscriptions(&[node_id]))
+    }
+
+    /// Shows whether an event type of the node is being ingested.
+    pub async fn get_event_subscription(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        event_type: &str,
+    ) -> ServiceResult<EventSubscriptionStatus> {
+        let event_type = parse_streamed_event_type(event_type)?;
+        self.get_node_credentials(account_id, node_id).await?;
+        self.event_subscription_status(account_id, node_id, &event_type)
+            .await
+    }
+
+    /// Resumes ingesting an event type, restarting the node's event stream
+    /// if it has stopped.
+    pub async fn subscribe(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        event_type: &str,
+    ) -> ServiceResult<EventSubscriptionStatus> {
+        let event_type = parse_streamed_event_type(event_type)?;
+        let node_credentials = self.get_node_credentials(account_id, node_id).await?;
+
+        let resumed = EventSubscriptionRepository::new(self.pool)
+            .resume(account_id, node_id, &event_type.to_string())
+            .await?;
+
+        let restarted = !is_connected(node_id, SubscriptionKind::Events);
+        if restarted {
+            start_event_stream(
+                self.pool.clone(),
+                account_id.to_string(),
+                user_id.to_string(),
+                &node_credentials,
+            )
+            .await
+            .map_err(|e| {
+                ServiceError::external_service(format!("Failed to restart event stream: {e}"))
+            })?;
+        }
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "event_subscription_resumed",
+                "node",
+                Some(node_id),
+                &json!({
+                    "event_type": event_type.to_string(),
+                    "was_paused": resumed,
+                    "stream_restarted": restarted,
+                }),
+            )
+            .await?;
+
+        self.event_subscription_status(account_id, node_id, &event_type)
+            .await
+    }
+
+    /// Stops ingesting an event type. The node's stream stays open so other
+    /// event types keep flowing.
+    pub async fn unsubscribe(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        event_type: &str,
+    ) -> ServiceResult<EventSubscriptionStatus> {
+        let event_type = parse_streamed_event_type(event_type)?;
+        self.get_node_credentials(account_id, node_id).await?;
+
+        EventSubscriptionRepository::new(self.pool)
+            .pause(account_id, node_id, &event_type.to_string(), user_id)
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "event_subscription_paused",
+                "node",
+                Some(node_id),
+                &json!({ "event_type": event_type.to_string() }),
+            )
+            .await?;
+
+        self.event_subscription_status(account_id, node_id, &event_type)
+            .await
+    }
+
+    async fn get_node_credentials(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<NodeCredentials> {
+        let credential = CredentialRepository::new(self.pool)
+            .get_credential_by_account_and_node_id(account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Node", node_id))?;
+
+        Ok(NodeCredentials::from(credential))
+    }
+
+    async fn event_subscription_status(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        event_type: &EventType,
+    ) -> ServiceResult<EventSubscriptionStatus> {
+        let event_type = event_type.to_string();
+        let paused = EventSubscriptionRepository::new(self.pool)
+            .get_paused_types(account_id, node_id)
+            .await?
+            .into_iter()
+            .find(|paused| paused.event_type == event_type);
+
+        Ok(EventSubscriptionStatus {
+            node_id: node_id.to_string(),
+            is_paused: paused.is_some(),
+            paused_by: paused.as_ref().map(|p| p.paused_by.clone()),
+            paused_at: paused.map(|p| p.paused_at),
+            stream_connected: is_connected(node_id, SubscriptionKind::Events),
+            event_type,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_keeps_counters() {
+        let node_id = "test-node-reconnect";
+        record_connected(node_id, SubscriptionKind::Graph);
+        record_event(node_id, SubscriptionKind::Graph);
+        record_lag(node_id, SubscriptionKind::Graph, Duration::from_millis(40));
+        record_lag(node_id, SubscriptionKind::Graph, Duration::from_millis(10));
+        record_disconnected(node_id, SubscriptionKind::Graph, Some("reset".to_string()));
+        record_connected(node_id, SubscriptionKind::Graph);
+
+        let stats = node_subscriptions(&[node_id]);
+        assert_eq!(stats.len(), 1);
+        assert!(stats[0].connected);
+        assert!(stats[0].uptime_secs.is_some());
+        assert_eq!(stats[0].reconnects, 1);
+        assert_eq!(stats[0].events_received, 1);
+        assert_eq!(stats[0].last_lag_ms, Some(10));
+        assert_eq!(stats[0].max_lag_ms, Some(40));
+        assert_eq!(stats[0].last_error.as_deref(), Some("reset"));
+    }
+}
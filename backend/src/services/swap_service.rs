@@ -0,0 +1,433 @@
+//! Rebalancing through Boltz swaps.
+//!
+//! A submarine swap adds outbound liquidity: the node issues an invoice,
+//! Boltz returns an address to lock on-chain funds at, and pays the invoice
+//! once they confirm. A reverse swap adds inbound liquidity: the node pays
+//! Boltz's hold invoice (optionally out of a chosen channel) and Boltz locks
+//! the amount, less fees, on-chain for us.
+//!
+//! Every swap's keys and preimage are kept encrypted as rescue data. Claiming
+//! a reverse swap's lockup and refunding a failed submarine swap need a
+//! MuSig2 signing session nodegaze does not run, so operators export the
+//! rescue data into Boltz's rescue tool for those steps.
+//!
+//! A single tracker polls Boltz for every open swap and records each status
+//! change as a `SwapUpdated` event.
+
+use crate::config::Config;
+use crate::database::models::{
+    CreateEvent, CreateSwapRequest, EventSeverity, EventType, Swap, SwapQuote, SwapType,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::swap_repository::SwapRepository;
+use crate::services::boltz::{BoltzClient, is_final, is_successful, quote};
+use crate::services::event_service::EventService;
+use crate::utils::ShortChannelID;
+use crate::utils::crypto::StringCrypto;
+use crate::utils::handlers_common::create_node_client;
+use crate::utils::jwt::NodeCredentials;
+use bitcoin::hashes::{Hash, sha256};
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use chrono::Utc;
+use rand::RngCore;
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
+use validator::Validate;
+
+/// How often open swaps are checked with Boltz
+const TRACK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Expiry of the invoice a submarine swap pays
+const SUBMARINE_INVOICE_EXPIRY_SECS: u64 = 24 * 60 * 60;
+
+/// Routing fee budget for a reverse swap's invoice when none is given, in ppm
+const DEFAULT_ROUTING_FEE_PPM: u64 = 5_000;
+
+fn random_bytes() -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn new_keypair() -> ServiceResult<(SecretKey, PublicKey)> {
+    let secret_key = SecretKey::from_slice(&random_bytes())
+        .map_err(|e| ServiceError::internal_error(e.to_string()))?;
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+    Ok((secret_key, public_key))
+}
+
+fn encrypt_rescue_data(data: &Value) -> ServiceResult<String> {
+    StringCrypto::from_env()
+        .and_then(|crypto| crypto.encrypt(&data.to_string()))
+        .map_err(|e| ServiceError::internal_error(format!("Failed to encrypt swap keys: {e}")))
+}
+
+fn boltz_client() -> ServiceResult<BoltzClient> {
+    let config = Config::from_env().map_err(|e| ServiceError::internal_error(e.to_string()))?;
+    BoltzClient::new(&config.boltz_api_url)
+}
+
+/// Service layer for rebalancing swaps.
+pub struct SwapService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SwapService<'a> {
+    /// Creates a new SwapService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Prices a swap at Boltz's current fees.
+    pub async fn get_quote(
+        &self,
+        swap_type: SwapType,
+        amount_sat: u64,
+    ) -> ServiceResult<SwapQuote> {
+        let pair = boltz_client()?.get_pair(swap_type).await?;
+        Ok(quote(swap_type, amount_sat, &pair))
+    }
+
+    /// Creates a swap with Boltz and starts the node's side of it.
+    pub async fn create_swap(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_credentials: &NodeCredentials,
+        request: CreateSwapRequest,
+    ) -> ServiceResult<Swap> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        let channel_id = request
+            .channel_id
+            .as_deref()
+            .map(ShortChannelID::from_str)
+            .transpose()
+            .map_err(|e| ServiceError::validation(format!("Invalid channel ID: {e}")))?;
+        if channel_id.is_some() && request.swap_type == SwapType::Submarine {
+            return Err(ServiceError::validation(
+                "Only reverse swaps can be pinned to a channel",
+            ));
+        }
+
+        let boltz = boltz_client()?;
+        let pair = boltz.get_pair(request.swap_type).await?;
+        let quote = quote(request.swap_type, request.amount_sat, &pair);
+        if request.amount_sat < quote.min_amount_sat || request.amount_sat > quote.max_amount_sat {
+            return Err(ServiceError::validation(format!(
+                "Boltz swaps between {} and {} sats",
+                quote.min_amount_sat, quote.max_amount_sat
+            )));
+        }
+
+        let public_key = PublicKey::from_str(&node_credentials.node_id)
+            .map_err(|e| ServiceError::validation(format!("Invalid node ID: {e}")))?;
+        let client = create_node_client(node_credentials, public_key)
+            .await
+            .map_err(|(_, body)| ServiceError::external_service(body))?;
+        let (secret_key, swap_public_key) = new_keypair()?;
+
+        let mut swap = Swap {
+            id: Uuid::now_v7().to_string(),
+            account_id: account_id.to_string(),
+            node_id: node_credentials.node_id.clone(),
+            swap_type: request.swap_type,
+            boltz_swap_id: String::new(),
+            status: "swap.created".to_string(),
+            is_final: false,
+            amount_sat: request.amount_sat as i64,
+            onchain_amount_sat: 0,
+            service_fee_sat: quote.service_fee_sat as i64,
+            miner_fee_sat: quote.miner_fee_sat as i64,
+            invoice: String::new(),
+            lockup_address: String::new(),
+            timeout_block_height: 0,
+            channel_id: channel_id.map(|id| id.to_string()),
+            rescue_data: String::new(),
+            created_by: user_id.to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        };
+
+        match request.swap_type {
+            SwapType::Submarine => {
+                let invoice = client
+                    .create_invoice(
+                        request.amount_sat * 1000,
+                        "nodegaze submarine swap",
+                        SUBMARINE_INVOICE_EXPIRY_SECS,
+                    )
+                    .await
+                    .map_err(|e| ServiceError::external_service(e.to_string()))?;
+                let response = boltz
+                    .create_submarine_swap(&invoice, &swap_public_key.to_string(), &pair.hash)
+                    .await?;
+
+                swap.boltz_swap_id = response.id;
+                swap.invoice = invoice;
+                swap.lockup_address = response.address.clone();
+                swap.onchain_amount_sat = response.expected_amount as i64;
+                // Boltz's expected amount is authoritative for what it costs.
+                swap.miner_fee_sat =
+                    response.expected_amount as i64 - swap.amount_sat - swap.service_fee_sat;
+                swap.timeout_block_height = response.timeout_block_height as i64;
+                swap.rescue_data = encrypt_rescue_data(&json!({
+                    "id": swap.boltz_swap_id,
+                    "type": "submarine",
+                    "refundPrivateKey": hex::encode(secret_key.secret_bytes()),
+                    "claimPublicKey": response.claim_public_key,
+                    "swapTree": response.swap_tree,
+                    "lockupAddress": response.address,
+                    "timeoutBlockHeight": response.timeout_block_height,
+                }))?;
+            }
+            SwapType::Reverse => {
+                let preimage = random_bytes();
+                let preimage_hash = sha256::Hash::hash(&preimage);
+                let response = boltz
+                    .create_reverse_swap(
+                        &preimage_hash.to_string(),
+                        &swap_public_key.to_string(),
+                        request.amount_sat,
+                        &pair.hash,
+                    )
+                    .await?;
+
+                swap.boltz_swap_id = response.id;
+                swap.invoice = response.invoice;
+                swap.lockup_address = response.lockup_address.clone();
+                swap.onchain_amount_sat = response.onchain_amount as i64;
+                swap.miner_fee_sat =
+                    swap.amount_sat - response.onchain_amount as i64 - swap.service_fee_sat;
+                swap.timeout_block_height = response.timeout_block_height as i64;
+                swap.rescue_data = encrypt_rescue_data(&json!({
+                    "id": swap.boltz_swap_id,
+                    "type": "reverse",
+                    "preimage": hex::encode(preimage),
+                    "claimPrivateKey": hex::encode(secret_key.secret_bytes()),
+                    "refundPublicKey": response.refund_public_key,
+                    "swapTree": response.swap_tree,
+                    "lockupAddress": response.lockup_address,
+                    "timeoutBlockHeight": response.timeout_block_height,
+                }))?;
+            }
+        }
+
+        // Stored before paying so the swap is tracked even if the payment fails.
+        let swap = SwapRepository::new(self.pool).create_swap(&swap).await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "swap_created",
+                "node",
+                Some(&swap.node_id),
+                &json!({
+                    "swap_id": swap.id,
+                    "boltz_swap_id": swap.boltz_swap_id,
+                    "swap_type": swap.swap_type,
+                    "amount_sat": swap.amount_sat,
+                    "channel_id": swap.channel_id,
+                }),
+            )
+            .await?;
+
+        if swap.swap_type == SwapType::Reverse {
+            let max_fee_sat = request
+                .max_routing_fee_sat
+                .unwrap_or(request.amount_sat * DEFAULT_ROUTING_FEE_PPM / 1_000_000);
+            client
+                .send_payment(&swap.invoice, max_fee_sat, channel_id.map(u64::from))
+                .await
+                .map_err(|e| {
+                    ServiceError::external_service(format!(
+                        "Swap {} was created but paying its invoice failed: {e}",
+                        swap.id
+                    ))
+                })?;
+        }
+
+        Ok(swap)
+    }
+
+    /// Lists the node's swaps, newest first.
+    pub async fn list_swaps(&self, account_id: &str, node_id: &str) -> ServiceResult<Vec<Swap>> {
+        Ok(SwapRepository::new(self.pool)
+            .get_swaps(account_id, node_id)
+            .await?)
+    }
+
+    /// Returns one of the node's swaps.
+    pub async fn get_swap(&self, account_id: &str, node_id: &str, id: &str) -> ServiceResult<Swap> {
+        SwapRepository::new(self.pool)
+            .get_swap(account_id, node_id, id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Swap", id))
+    }
+
+    /// Decrypts a swap's keys for use with Boltz's rescue tool.
+    pub async fn get_rescue_data(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        id: &str,
+    ) -> ServiceResult<Value> {
+        let swap = self.get_swap(account_id, node_id, id).await?;
+        let rescue_data = StringCrypto::from_env()
+            .and_then(|crypto| crypto.decrypt(&swap.rescue_data))
+            .map_err(|e| {
+                ServiceError::internal_error(format!("Failed to decrypt swap keys: {e}"))
+            })?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "swap_rescue_data_viewed",
+                "node",
+                Some(node_id),
+                &json!({ "swap_id": swap.id }),
+            )
+            .await?;
+
+        serde_json::from_str(&rescue_data).map_err(|e| ServiceError::internal_error(e.to_string()))
+    }
+
+    /// Fees of completed swaps pinned to a channel, if there were any.
+    pub async fn get_channel_swap_cost(
+        &self,
+        node_id: &str,
+        channel_id: &ShortChannelID,
+    ) -> ServiceResult<Option<u64>> {
+        let fees = SwapRepository::new(self.pool)
+            .get_channel_swap_fees(node_id, &channel_id.to_string())
+            .await?;
+        Ok((fees > 0).then_some(fees as u64))
+    }
+}
+
+/// Starts the loop that follows open swaps until Boltz reports a final status.
+pub async fn start_swap_tracker(pool: SqlitePool) {
+    let boltz = match boltz_client() {
+        Ok(boltz) => boltz,
+        Err(e) => {
+            tracing::error!("Swap tracker not started: {}", e);
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = track_open_swaps(&pool, &boltz).await {
+                tracing::error!("Swap tracking failed: {}", e);
+            }
+            tokio::time::sleep(TRACK_INTERVAL).await;
+        }
+    });
+
+    tracing::info!("Started swap tracker");
+}
+
+async fn track_open_swaps(pool: &SqlitePool, boltz: &BoltzClient) -> Result<(), String> {
+    let repo = SwapRepository::new(pool);
+    for swap in repo.get_open_swaps().await.map_err(|e| e.to_string())? {
+        let status = match boltz.get_status(&swap.boltz_swap_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::warn!("Failed to check swap {}: {}", swap.id, e);
+                continue;
+            }
+        };
+        if status == swap.status {
+            continue;
+        }
+
+        let finished = is_final(swap.swap_type, &status);
+        repo.update_status(&swap.id, &status, finished)
+            .await
+            .map_err(|e| e.to_string())?;
+        record_swap_update(pool, &swap, &status).await;
+    }
+    Ok(())
+}
+
+async fn record_swap_update(pool: &SqlitePool, swap: &Swap, status: &str) {
+    let direction = match swap.swap_type {
+        SwapType::Submarine => "Submarine",
+        SwapType::Reverse => "Reverse",
+    };
+    let (severity, title, description) = if is_successful(swap.swap_type, status) {
+        (
+            EventSeverity::Info,
+            "Swap Completed",
+            format!(
+                "{direction} swap of {} sats completed: {status}",
+                swap.amount_sat
+            ),
+        )
+    } else if is_final(swap.swap_type, status) {
+        (
+            EventSeverity::Warning,
+            "Swap Failed",
+            format!(
+                "{direction} swap of {} sats failed: {status}. Funds locked on-chain can be recovered with the swap's rescue data.",
+                swap.amount_sat
+            ),
+        )
+    } else if swap.swap_type == SwapType::Reverse && status == "transaction.confirmed" {
+        (
+            EventSeverity::Warning,
+            "Swap Ready To Claim",
+            format!(
+                "Boltz locked {} sats on-chain for reverse swap {}. Claim them with the swap's rescue data before block {}.",
+                swap.onchain_amount_sat, swap.id, swap.timeout_block_height
+            ),
+        )
+    } else {
+        (
+            EventSeverity::Info,
+            "Swap Updated",
+            format!(
+                "{direction} swap of {} sats is now {status}",
+                swap.amount_sat
+            ),
+        )
+    };
+
+    let event = CreateEvent {
+        id: Uuid::now_v7().to_string(),
+        account_id: swap.account_id.clone(),
+        user_id: swap.created_by.clone(),
+        node_id: swap.node_id.clone(),
+        node_alias: String::new(),
+        event_type: EventType::SwapUpdated,
+        severity,
+        title: title.to_string(),
+        description,
+        data: json!({
+            "swap_id": swap.id,
+            "boltz_swap_id": swap.boltz_swap_id,
+            "swap_type": swap.swap_type,
+            "status": status,
+            "amount_sat": swap.amount_sat,
+            "total_fee_sat": swap.service_fee_sat + swap.miner_fee_sat,
+        })
+        .to_string(),
+        notifications_id: None,
+        timestamp: Utc::now(),
+    };
+
+    if let Err(e) = EventService::new(pool)
+        .create_and_dispatch_event(event)
+        .await
+    {
+        tracing::warn!("Failed to record update of swap {}: {}", swap.id, e);
+    }
+}
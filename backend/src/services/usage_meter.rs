@@ -0,0 +1,246 @@
+//! Per-account usage metering for fair-use enforcement and billing.
+//!
+//! Authenticated API requests are counted in memory as they arrive. The
+//! `UsageRollup` scheduled task moves those counts into `account_usage`,
+//! recounts the events stored and notifications delivered today and
+//! yesterday, and records how many nodes the account has connected. Requests
+//! counted since the last rollup are lost if the process stops.
+
+use crate::database::models::{AccountUsage, AccountUsageDay, AccountUsageTotals};
+use crate::errors::ServiceResult;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::usage_repository::UsageRepository;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+const DEFAULT_USAGE_DAYS: i64 = 30;
+const MAX_USAGE_DAYS: i64 = 365;
+
+static USAGE_METER: OnceLock<UsageMeter> = OnceLock::new();
+
+/// In-memory API request counts per account and UTC day, not yet rolled up.
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    requests: Mutex<HashMap<(String, NaiveDate), i64>>,
+}
+
+impl UsageMeter {
+    /// Returns the process-wide meter.
+    pub fn global() -> &'static UsageMeter {
+        USAGE_METER.get_or_init(UsageMeter::default)
+    }
+
+    /// Counts one API request made on behalf of an account.
+    pub fn record_request(&self, account_id: &str) {
+        let day = Utc::now().date_naive();
+        *self
+            .requests
+            .lock()
+            .unwrap()
+            .entry((account_id.to_string(), day))
+            .or_insert(0) += 1;
+    }
+
+    /// Removes and returns an account's pending counts by day.
+    pub fn take(&self, account_id: &str) -> Vec<(NaiveDate, i64)> {
+        let mut requests = self.requests.lock().unwrap();
+        let days: Vec<NaiveDate> = requests
+            .keys()
+            .filter(|(id, _)| id == account_id)
+            .map(|(_, day)| *day)
+            .collect();
+        days.into_iter()
+            .filter_map(|day| {
+                requests
+                    .remove(&(account_id.to_string(), day))
+                    .map(|count| (day, count))
+            })
+            .collect()
+    }
+
+    /// Puts back counts that could not be saved.
+    pub fn restore(&self, account_id: &str, counts: &[(NaiveDate, i64)]) {
+        let mut requests = self.requests.lock().unwrap();
+        for (day, count) in counts {
+            *requests.entry((account_id.to_string(), *day)).or_insert(0) += count;
+        }
+    }
+}
+
+/// Builds a usage report with one entry per day of the window ending on
+/// `today`, filling days without a row with zeros.
+pub fn usage_report(
+    account_id: &str,
+    recorded: Vec<AccountUsageDay>,
+    today: NaiveDate,
+    days: i64,
+) -> AccountUsage {
+    let mut recorded: HashMap<NaiveDate, AccountUsageDay> = recorded
+        .into_iter()
+        .map(|usage| (usage.day, usage))
+        .collect();
+    let first_day = today - Duration::days(days - 1);
+    let series: Vec<AccountUsageDay> = first_day
+        .iter_days()
+        .take(days as usize)
+        .map(|day| {
+            recorded.remove(&day).unwrap_or(AccountUsageDay {
+                day,
+                api_requests: 0,
+                events_stored: 0,
+                notifications_sent: 0,
+                nodes_connected: 0,
+            })
+        })
+        .collect();
+
+    let totals = series
+        .iter()
+        .fold(AccountUsageTotals::default(), |mut totals, usage| {
+            totals.api_requests += usage.api_requests;
+            totals.events_stored += usage.events_stored;
+            totals.notifications_sent += usage.notifications_sent;
+            totals.peak_nodes_connected = totals.peak_nodes_connected.max(usage.nodes_connected);
+            totals
+        });
+
+    AccountUsage {
+        account_id: account_id.to_string(),
+        days,
+        totals,
+        series,
+    }
+}
+
+/// Service layer for reading account usage.
+pub struct UsageService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> UsageService<'a> {
+    /// Creates a new UsageService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns an account's usage over the last `days` days, today included.
+    pub async fn get_usage(
+        &self,
+        account_id: &str,
+        days: Option<i64>,
+    ) -> ServiceResult<AccountUsage> {
+        let days = days.unwrap_or(DEFAULT_USAGE_DAYS).clamp(1, MAX_USAGE_DAYS);
+        let today = Utc::now().date_naive();
+        let recorded = UsageRepository::new(self.pool)
+            .get_usage(account_id, today - Duration::days(days - 1), today)
+            .await?;
+
+        Ok(usage_report(account_id, recorded, today, days))
+    }
+}
+
+/// Rolls an account's usage up into `account_usage`.
+pub async fn roll_up_account_usage(pool: &SqlitePool, account_id: &str) -> Result<(), String> {
+    let repo = UsageRepository::new(pool);
+    let today = Utc::now().date_naive();
+
+    let pending = UsageMeter::global().take(account_id);
+    for (index, (day, count)) in pending.iter().enumerate() {
+        if let Err(e) = repo.add_api_requests(account_id, *day, *count).await {
+            UsageMeter::global().restore(account_id, &pending[index..]);
+            return Err(e.to_string());
+        }
+    }
+
+    let nodes_connected = CredentialRepository::new(pool)
+        .get_credentials_by_account_id(account_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .len() as i64;
+
+    // Yesterday is recounted so events and deliveries that landed after its
+    // last rollup are not missed; its node count is left as it was.
+    for (day, nodes_connected) in [(today - Duration::days(1), 0), (today, nodes_connected)] {
+        let from = day_start(day);
+        let to = day_start(day + Duration::days(1));
+        let events_stored = repo
+            .count_events_stored(account_id, from, to)
+            .await
+            .map_err(|e| e.to_string())?;
+        let notifications_sent = repo
+            .count_notifications_sent(account_id, from, to)
+            .await
+            .map_err(|e| e.to_string())?;
+        repo.record_counts(
+            account_id,
+            day,
+            events_stored,
+            notifications_sent,
+            nodes_connected,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn day_start(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(day: NaiveDate, api_requests: i64, nodes_connected: i64) -> AccountUsageDay {
+        AccountUsageDay {
+            day,
+            api_requests,
+            events_stored: 10,
+            notifications_sent: 2,
+            nodes_connected,
+        }
+    }
+
+    #[test]
+    fn test_usage_report_fills_missing_days() {
+        let today = NaiveDate::from_ymd_opt(2025, 9, 13).unwrap();
+        let report = usage_report(
+            "account-1",
+            vec![
+                usage(today - Duration::days(2), 100, 1),
+                usage(today, 50, 3),
+                usage(today - Duration::days(9), 999, 9),
+            ],
+            today,
+            3,
+        );
+
+        assert_eq!(report.series.len(), 3);
+        assert_eq!(report.series[0].day, today - Duration::days(2));
+        assert_eq!(report.series[1].api_requests, 0);
+        assert_eq!(report.totals.api_requests, 150);
+        assert_eq!(report.totals.events_stored, 20);
+        assert_eq!(report.totals.notifications_sent, 4);
+        assert_eq!(report.totals.peak_nodes_connected, 3);
+    }
+
+    #[test]
+    fn test_meter_take_and_restore() {
+        let meter = UsageMeter::default();
+        meter.record_request("account-1");
+        meter.record_request("account-1");
+        meter.record_request("account-2");
+
+        let taken = meter.take("account-1");
+        assert_eq!(taken.iter().map(|(_, count)| count).sum::<i64>(), 2);
+        assert!(meter.take("account-1").is_empty());
+
+        meter.restore("account-1", &taken);
+        assert_eq!(meter.take("account-1"), taken);
+        assert_eq!(meter.take("account-2").len(), 1);
+    }
+}
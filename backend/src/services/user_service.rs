@@ -3,11 +3,15 @@
 //! Handles all account-related business operations
 
 use crate::api::common::PaginationFilter;
-use crate::database::models::{RoleAccessLevel, User};
+use crate::database::models::{Role, RoleAccessLevel, User};
 use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::account_membership_repository::AccountMembershipRepository;
+use crate::repositories::node_scope_repository::NodeScopeRepository;
 use crate::repositories::role_repository::RoleRepository;
 use crate::repositories::user_repository::UserRepository;
-use bcrypt::{DEFAULT_COST, hash, verify};
+use crate::services::role_service::combine_node_scopes;
+use crate::utils::jwt::Claims;
+use crate::utils::password::{hash_password, needs_rehash, verify_password};
 use sqlx::SqlitePool;
 
 pub struct UserService<'a> {
@@ -24,37 +28,6 @@ impl<'a> UserService<'a> {
         Self { pool }
     }
 
-    /// Function to hash a password before storing in database
-    ///
-    /// # Arguments
-    /// * `password` - Plain text password to hash
-    ///
-    /// # Returns
-    /// Hashed password string
-    ///
-    /// # Errors
-    /// Returns `ServiceError` if hashing fails
-    fn hash_password(&self, password: &str) -> ServiceResult<String> {
-        hash(password, DEFAULT_COST)
-            .map_err(|e| ServiceError::validation(format!("Password hashing failed: {e}")))
-    }
-
-    /// Function to verify a password against the stored hash
-    ///
-    /// # Arguments
-    /// * `password` - Plain text password to verify
-    /// * `hash` - Stored password hash
-    ///
-    /// # Returns
-    /// `true` if password matches hash, `false` otherwise
-    ///
-    /// # Errors
-    /// Returns `ServiceError` if verification process fails
-    fn verify_password(&self, password: &str, hash: &str) -> ServiceResult<bool> {
-        verify(password, hash)
-            .map_err(|e| ServiceError::validation(format!("Password verification failed: {e}")))
-    }
-
     /// Retrieves a user by ID with existence verification.
     ///
     /// # Arguments
@@ -74,6 +47,39 @@ impl<'a> UserService<'a> {
         Ok(user)
     }
 
+    /// Retrieves a user as a member of `account_id`: their own record for
+    /// their home account, otherwise with the account, role and access level
+    /// of their membership.
+    ///
+    /// # Errors
+    /// Returns `ServiceError::NotFound` if the user doesn't exist or isn't a
+    /// member of the account
+    pub async fn get_user_in_account(&self, id: &str, account_id: &str) -> ServiceResult<User> {
+        let user = self.get_user_required(id).await?;
+        if user.account_id == account_id {
+            return Ok(user);
+        }
+
+        let membership = AccountMembershipRepository::new(self.pool)
+            .get_membership(id, account_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("User", id))?;
+
+        Ok(User {
+            account_id: membership.account_id,
+            role_id: membership.role_id,
+            role_access_level: membership.role_access_level,
+            ..user
+        })
+    }
+
+    /// Retrieves the token's user as a member of the account the token was
+    /// issued for.
+    pub async fn get_user_for_claims(&self, claims: &Claims) -> ServiceResult<User> {
+        self.get_user_in_account(claims.user_id(), claims.account_id())
+            .await
+    }
+
     /// Retrieves an admin user by Account ID.
     ///
     /// # Arguments
@@ -153,12 +159,27 @@ impl<'a> UserService<'a> {
         }
 
         // Verify password
-        if !self.verify_password(password, &user.password_hash)? {
+        if !verify_password(password, &user.password_hash).await? {
             return Err(ServiceError::validation(
                 "Invalid username or password".to_string(),
             ));
         }
 
+        // Upgrade bcrypt and outdated Argon2 hashes while the password is at
+        // hand. A failure leaves the old hash working, so it doesn't block login.
+        if needs_rehash(&user.password_hash) {
+            let rehashed = match hash_password(password).await {
+                Ok(new_hash) => repo
+                    .update_password_hash(&user.id, &new_hash)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = rehashed {
+                tracing::warn!("Failed to rehash password of user {}: {}", user.id, e);
+            }
+        }
+
         Ok(user)
     }
 
@@ -215,4 +236,42 @@ impl<'a> UserService<'a> {
 
         Ok(user)
     }
+
+    /// Checks whether a token issued at `issued_at` (seconds since the epoch)
+    /// predates the user's last session revocation.
+    pub async fn is_token_revoked(&self, user_id: &str, issued_at: usize) -> ServiceResult<bool> {
+        let invalidated_at = UserRepository::new(self.pool)
+            .get_sessions_invalidated_at(user_id)
+            .await?;
+
+        Ok(invalidated_at.is_some_and(|at| (issued_at as i64) < at.timestamp()))
+    }
+
+    /// Replaces the role, access level, permission scopes and node scope in
+    /// the claims with those the token's user currently has in the token's
+    /// account, so role and scope changes apply without waiting for the token
+    /// to expire.
+    pub async fn refresh_claims(&self, claims: &mut Claims) -> ServiceResult<()> {
+        let user = self.get_user_for_claims(claims).await?;
+        let role = RoleRepository::new(self.pool)
+            .get_role_by_id(&user.role_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Role", &user.role_id))?;
+
+        claims.node_scope = self.node_scope(&user, &role).await?;
+        claims.permissions = role.permissions();
+        claims.role = role.name;
+        claims.role_access_level = user.role_access_level;
+        Ok(())
+    }
+
+    /// The nodes of `user.account_id` a user is limited to by their role and
+    /// by the invite or membership that brought them in.
+    pub async fn node_scope(&self, user: &User, role: &Role) -> ServiceResult<Option<Vec<String>>> {
+        let user_scope = NodeScopeRepository::new(self.pool)
+            .get_scope(&user.id, &user.account_id)
+            .await?;
+
+        Ok(combine_node_scopes(role.node_ids(), user_scope))
+    }
 }
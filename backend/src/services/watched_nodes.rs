@@ -0,0 +1,515 @@
+//! Watch-only monitoring of external nodes.
+//!
+//! An account can watch nodes it has no credentials for, such as
+//! competitors or partners, by pubkey. Everything about them is read from
+//! the graph mirror of one of the account's own nodes. Each time that mirror
+//! is fully synced, every watched node is compared with the snapshot taken
+//! at the previous sync, and opened or closed channels, fee policy changes
+//! and announcement changes are recorded as events of their own types.
+
+use crate::database::models::{
+    CreateEvent, CreateWatchedNodeRequest, EventSeverity, EventType, WatchedChannel, WatchedNode,
+    WatchedNodeResponse, WatchedNodeSnapshot,
+};
+use crate::errors::{ServiceError, ServiceResult};
+use crate::repositories::audit_log_repository::AuditLogRepository;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::repositories::graph_repository::GraphRepository;
+use crate::repositories::watched_node_repository::WatchedNodeRepository;
+use crate::services::event_service::EventService;
+use crate::services::graph_sync::percentile;
+use crate::services::peer_announcements::{AnnouncementChange, announcement_change, summarize};
+use bitcoin::secp256k1::PublicKey;
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+use validator::Validate;
+
+/// A difference between two snapshots of a watched node.
+#[derive(Debug, PartialEq)]
+pub enum WatchedNodeChange {
+    ChannelOpened(WatchedChannel),
+    ChannelClosed(WatchedChannel),
+    FeeChanged {
+        previous: WatchedChannel,
+        current: WatchedChannel,
+    },
+    AnnouncementChanged(AnnouncementChange),
+}
+
+/// Lists what changed between two snapshots of a watched node. A policy
+/// seen for the first time is not a change.
+pub fn snapshot_changes(
+    previous: &WatchedNodeSnapshot,
+    current: &WatchedNodeSnapshot,
+) -> Vec<WatchedNodeChange> {
+    let mut changes = Vec::new();
+    if let Some(change) = announcement_change(&previous.announcement, &current.announcement) {
+        changes.push(WatchedNodeChange::AnnouncementChanged(change));
+    }
+
+    let old: HashMap<&str, &WatchedChannel> = previous
+        .channels
+        .iter()
+        .map(|channel| (channel.chan_id.as_str(), channel))
+        .collect();
+    let new: HashMap<&str, &WatchedChannel> = current
+        .channels
+        .iter()
+        .map(|channel| (channel.chan_id.as_str(), channel))
+        .collect();
+
+    for channel in &current.channels {
+        match old.get(channel.chan_id.as_str()) {
+            None => changes.push(WatchedNodeChange::ChannelOpened(channel.clone())),
+            Some(before) => {
+                let policy_changed = before.fee_rate_ppm.is_some()
+                    && channel.fee_rate_ppm.is_some()
+                    && (before.fee_base_msat != channel.fee_base_msat
+                        || before.fee_rate_ppm != channel.fee_rate_ppm
+                        || before.disabled != channel.disabled);
+                if policy_changed {
+                    changes.push(WatchedNodeChange::FeeChanged {
+                        previous: (*before).clone(),
+                        current: channel.clone(),
+                    });
+                }
+            }
+        }
+    }
+    for channel in &previous.channels {
+        if !new.contains_key(channel.chan_id.as_str()) {
+            changes.push(WatchedNodeChange::ChannelClosed(channel.clone()));
+        }
+    }
+
+    changes
+}
+
+fn describe_policy(channel: &WatchedChannel) -> String {
+    let policy = format!(
+        "{} msat + {} ppm",
+        channel.fee_base_msat.unwrap_or_default(),
+        channel.fee_rate_ppm.unwrap_or_default()
+    );
+    if channel.disabled == Some(true) {
+        format!("{policy} (disabled)")
+    } else {
+        policy
+    }
+}
+
+/// Event type, title, description and data recording a change of the
+/// watched node `name`.
+fn describe(
+    name: &str,
+    pubkey: &str,
+    change: &WatchedNodeChange,
+) -> (EventType, &'static str, String, serde_json::Value) {
+    match change {
+        WatchedNodeChange::ChannelOpened(channel) => (
+            EventType::WatchedNodeChannelOpened,
+            "Watched Node Opened a Channel",
+            format!(
+                "{name} opened channel {} with {} ({} sats)",
+                channel.chan_id, channel.peer_pubkey, channel.capacity_sat
+            ),
+            json!({ "watched_pubkey": pubkey, "channel": channel }),
+        ),
+        WatchedNodeChange::ChannelClosed(channel) => (
+            EventType::WatchedNodeChannelClosed,
+            "Watched Node Closed a Channel",
+            format!(
+                "{name} closed channel {} with {} ({} sats)",
+                channel.chan_id, channel.peer_pubkey, channel.capacity_sat
+            ),
+            json!({ "watched_pubkey": pubkey, "channel": channel }),
+        ),
+        WatchedNodeChange::FeeChanged { previous, current } => (
+            EventType::WatchedNodeFeeChanged,
+            "Watched Node Changed Fees",
+            format!(
+                "{name} changed its fees towards {} on channel {} from {} to {}",
+                current.peer_pubkey,
+                current.chan_id,
+                describe_policy(previous),
+                describe_policy(current)
+            ),
+            json!({ "watched_pubkey": pubkey, "previous": previous, "channel": current }),
+        ),
+        WatchedNodeChange::AnnouncementChanged(change) => (
+            EventType::WatchedNodeAnnouncementChanged,
+            "Watched Node Announcement Changed",
+            summarize(change),
+            json!({
+                "watched_pubkey": pubkey,
+                "previous_alias": change.previous_alias,
+                "alias": change.alias,
+                "added_addresses": change.added_addresses,
+                "removed_addresses": change.removed_addresses,
+                "added_features": change.added_features,
+                "removed_features": change.removed_features,
+                "tor_only": change.tor_only,
+            }),
+        ),
+    }
+}
+
+/// Reads what the graph mirror of `node_id` currently knows about `pubkey`.
+/// None when the node isn't in the mirror.
+async fn take_snapshot(
+    pool: &SqlitePool,
+    node_id: &str,
+    pubkey: &str,
+) -> anyhow::Result<Option<WatchedNodeSnapshot>> {
+    let repo = GraphRepository::new(pool);
+    let Some(announcement) = repo.get_announcement(node_id, pubkey).await? else {
+        return Ok(None);
+    };
+    let channels = repo.get_node_channels(node_id, pubkey).await?;
+
+    Ok(Some(WatchedNodeSnapshot {
+        announcement,
+        channels,
+    }))
+}
+
+fn build_response(
+    watched: WatchedNode,
+    snapshot: Option<WatchedNodeSnapshot>,
+) -> WatchedNodeResponse {
+    let (alias, addresses, channels) = match snapshot {
+        Some(snapshot) => (
+            Some(snapshot.announcement.alias),
+            snapshot.announcement.addresses,
+            snapshot.channels,
+        ),
+        None => (None, Vec::new(), Vec::new()),
+    };
+    let mut rates: Vec<i64> = channels
+        .iter()
+        .filter(|channel| channel.disabled == Some(false))
+        .filter_map(|channel| channel.fee_rate_ppm)
+        .collect();
+    rates.sort_unstable();
+
+    WatchedNodeResponse {
+        id: watched.id,
+        pubkey: watched.pubkey,
+        label: watched.label,
+        alias,
+        addresses,
+        channel_count: channels.len(),
+        capacity_sat: channels.iter().map(|channel| channel.capacity_sat).sum(),
+        median_fee_rate_ppm: percentile(&rates, 50.0),
+        channels,
+        last_checked_at: watched.last_checked_at,
+        created_at: watched.created_at,
+    }
+}
+
+/// Service layer for watch-only nodes.
+pub struct WatchedNodeService<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> WatchedNodeService<'a> {
+    /// Creates a new WatchedNodeService instance.
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the nodes watched through `node_id`, as its graph mirror currently sees them.
+    pub async fn list(
+        &self,
+        account_id: &str,
+        node_id: &str,
+    ) -> ServiceResult<Vec<WatchedNodeResponse>> {
+        let watched = WatchedNodeRepository::new(self.pool)
+            .get_for_node(account_id, node_id)
+            .await?;
+
+        let mut responses = Vec::with_capacity(watched.len());
+        for watched in watched {
+            let snapshot = take_snapshot(self.pool, node_id, &watched.pubkey).await?;
+            responses.push(build_response(watched, snapshot));
+        }
+        Ok(responses)
+    }
+
+    /// Returns one watched node with its channels and fee policies.
+    pub async fn get(
+        &self,
+        account_id: &str,
+        node_id: &str,
+        id: &str,
+    ) -> ServiceResult<WatchedNodeResponse> {
+        let watched = WatchedNodeRepository::new(self.pool)
+            .get(id, account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Watched node", id))?;
+        let snapshot = take_snapshot(self.pool, node_id, &watched.pubkey).await?;
+
+        Ok(build_response(watched, snapshot))
+    }
+
+    /// Starts watching an external node through the graph mirror of `node_id`.
+    pub async fn create(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        request: CreateWatchedNodeRequest,
+    ) -> ServiceResult<WatchedNodeResponse> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(ServiceError::validation(validation_errors.to_string()));
+        }
+        let pubkey = PublicKey::from_str(request.pubkey.trim())
+            .map_err(|_| {
+                ServiceError::validation(format!("Invalid public key: {}", request.pubkey))
+            })?
+            .to_string();
+        if pubkey == node_id {
+            return Err(ServiceError::validation("A node cannot watch itself"));
+        }
+
+        let repo = WatchedNodeRepository::new(self.pool);
+        if repo.exists(account_id, node_id, &pubkey).await? {
+            return Err(ServiceError::already_exists("Watched node", &pubkey));
+        }
+
+        // The first snapshot is the baseline later syncs are compared with.
+        let snapshot = take_snapshot(self.pool, node_id, &pubkey).await?;
+        let label = request
+            .label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty());
+        let watched = repo
+            .create(&WatchedNode {
+                id: Uuid::now_v7().to_string(),
+                account_id: account_id.to_string(),
+                node_id: node_id.to_string(),
+                pubkey: pubkey.clone(),
+                label,
+                snapshot: snapshot
+                    .as_ref()
+                    .map(|snapshot| json!(snapshot).to_string()),
+                last_checked_at: snapshot.as_ref().map(|_| Utc::now()),
+                created_by: user_id.to_string(),
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "watched_node_added",
+                "node",
+                Some(node_id),
+                &json!({ "watched_node_id": watched.id, "pubkey": pubkey }),
+            )
+            .await?;
+
+        Ok(build_response(watched, snapshot))
+    }
+
+    /// Stops watching a node.
+    pub async fn delete(
+        &self,
+        account_id: &str,
+        user_id: &str,
+        node_id: &str,
+        id: &str,
+    ) -> ServiceResult<()> {
+        let repo = WatchedNodeRepository::new(self.pool);
+        let watched = repo
+            .get(id, account_id, node_id)
+            .await?
+            .ok_or_else(|| ServiceError::not_found("Watched node", id))?;
+        repo.delete(id, account_id, node_id).await?;
+
+        AuditLogRepository::new(self.pool)
+            .create_log(
+                account_id,
+                Some(user_id),
+                "watched_node_removed",
+                "node",
+                Some(node_id),
+                &json!({ "watched_node_id": id, "pubkey": watched.pubkey }),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Compares every node watched through `node_id` with its previous snapshot
+/// and records an event for each change. Called after a full graph sync.
+pub async fn check_watched_nodes(pool: &SqlitePool, node_id: &str) {
+    let watched = match WatchedNodeRepository::new(pool)
+        .get_by_source(node_id)
+        .await
+    {
+        Ok(watched) => watched,
+        Err(e) => {
+            tracing::error!("Failed to load nodes watched through {}: {}", node_id, e);
+            return;
+        }
+    };
+
+    for watched in watched {
+        if let Err(e) = check_watched_node(pool, &watched).await {
+            tracing::error!(
+                "Failed to check watched node {} of {}: {}",
+                watched.pubkey,
+                node_id,
+                e
+            );
+        }
+    }
+}
+
+async fn check_watched_node(pool: &SqlitePool, watched: &WatchedNode) -> anyhow::Result<()> {
+    // A node that dropped out of the mirror keeps its last snapshot, so its
+    // return isn't reported as every channel reopening.
+    let Some(current) = take_snapshot(pool, &watched.node_id, &watched.pubkey).await? else {
+        return Ok(());
+    };
+    let changes = watched
+        .snapshot()
+        .map(|previous| snapshot_changes(&previous, &current))
+        .unwrap_or_default();
+    WatchedNodeRepository::new(pool)
+        .record_snapshot(&watched.id, &json!(current).to_string(), Utc::now())
+        .await?;
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let Some(credential) = CredentialRepository::new(pool)
+        .get_credential_by_account_and_node_id(&watched.account_id, &watched.node_id)
+        .await?
+    else {
+        return Ok(());
+    };
+    let name = watched
+        .label
+        .clone()
+        .unwrap_or_else(|| current.announcement.alias.clone());
+    let name = if name.is_empty() {
+        watched.pubkey.clone()
+    } else {
+        name
+    };
+
+    let events = changes
+        .iter()
+        .map(|change| {
+            let (event_type, title, description, data) = describe(&name, &watched.pubkey, change);
+            CreateEvent {
+                id: Uuid::now_v7().to_string(),
+                account_id: watched.account_id.clone(),
+                user_id: credential.user_id.clone(),
+                node_id: credential.node_id.clone(),
+                node_alias: credential.node_alias.clone(),
+                event_type,
+                severity: EventSeverity::Info,
+                title: title.to_string(),
+                description,
+                data: data.to_string(),
+                notifications_id: None,
+                timestamp: Utc::now(),
+            }
+        })
+        .collect();
+
+    EventService::new(pool)
+        .create_and_dispatch_events(events)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::GraphNode;
+
+    fn channel(chan_id: &str, fee_rate_ppm: Option<i64>) -> WatchedChannel {
+        WatchedChannel {
+            chan_id: chan_id.to_string(),
+            peer_pubkey: "03bb".to_string(),
+            capacity_sat: 1_000_000,
+            fee_base_msat: fee_rate_ppm.map(|_| 1_000),
+            fee_rate_ppm,
+            disabled: fee_rate_ppm.map(|_| false),
+        }
+    }
+
+    fn snapshot(alias: &str, channels: Vec<WatchedChannel>) -> WatchedNodeSnapshot {
+        WatchedNodeSnapshot {
+            announcement: GraphNode {
+                pubkey: "02aa".to_string(),
+                alias: alias.to_string(),
+                last_update: None,
+                addresses: vec!["203.0.113.7:9735".to_string()],
+                features: vec![7, 13],
+            },
+            channels,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_changes() {
+        let before = snapshot(
+            "rival",
+            vec![
+                channel("1", Some(100)),
+                channel("2", None),
+                channel("3", Some(50)),
+            ],
+        );
+
+        let same = snapshot(
+            "rival",
+            vec![
+                channel("1", Some(100)),
+                channel("2", None),
+                channel("3", Some(50)),
+            ],
+        );
+        assert!(snapshot_changes(&before, &same).is_empty());
+
+        // A policy announced for the first time isn't a fee change.
+        let after = snapshot(
+            "rival v2",
+            vec![
+                channel("1", Some(250)),
+                channel("2", Some(10)),
+                channel("4", None),
+            ],
+        );
+        let changes = snapshot_changes(&before, &after);
+        assert_eq!(changes.len(), 4);
+        assert!(matches!(
+            &changes[0],
+            WatchedNodeChange::AnnouncementChanged(change) if change.alias == "rival v2"
+        ));
+        assert!(matches!(
+            &changes[1],
+            WatchedNodeChange::FeeChanged { previous, current }
+                if previous.fee_rate_ppm == Some(100) && current.fee_rate_ppm == Some(250)
+        ));
+        assert_eq!(
+            changes[2],
+            WatchedNodeChange::ChannelOpened(channel("4", None))
+        );
+        assert_eq!(
+            changes[3],
+            WatchedNodeChange::ChannelClosed(channel("3", Some(50)))
+        );
+    }
+}
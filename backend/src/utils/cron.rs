@@ -0,0 +1,266 @@
+//! Minimal five-field cron expression parser.
+//!
+//! Supports `minute hour day-of-month month day-of-week` with `*`, single
+//! values, ranges (`a-b`), steps (`*/n`, `a-b/n`, `a/n`) and comma-separated
+//! lists, plus the `@hourly`, `@daily`, `@weekly` and `@monthly` shorthands.
+//! As in Vixie cron, when both day fields are restricted a day matches if
+//! either of them does. All times are evaluated in UTC.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Upper bound on the search in [`CronSchedule::next_after`], so expressions
+/// that can never fire (e.g. February 31st) terminate.
+const MAX_SEARCH_STEPS: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+fn parse_number(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    let number: u32 = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    if number < min || number > max {
+        return Err(format!("{number} is outside {min}-{max}"));
+    }
+    Ok(number)
+}
+
+/// Parses one field into a bitmask where bit `n` is set if value `n` matches.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = parse_number(step, 1, max)?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_number(start, min, max)?, parse_number(end, min, max)?)
+        } else {
+            let start = parse_number(range, min, max)?;
+            // `a/n` means "from a to the end of the range, every n"
+            if part.contains('/') {
+                (start, max)
+            } else {
+                (start, start)
+            }
+        };
+
+        if start > end {
+            return Err(format!("Range {start}-{end} is reversed"));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields.as_slice() else {
+            return Err(format!(
+                "Cron expression must have 5 fields, got {}",
+                fields.len()
+            ));
+        };
+
+        let with_field = |name: &str, result: Result<u64, String>| {
+            result.map_err(|e| format!("Invalid {name} field: {e}"))
+        };
+
+        let mut days_of_week = with_field("day-of-week", parse_field(day_of_week, 0, 7))?;
+        // Both 0 and 7 mean Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: with_field("minute", parse_field(minute, 0, 59))?,
+            hours: with_field("hour", parse_field(hour, 0, 23))?,
+            days_of_month: with_field("day-of-month", parse_field(day_of_month, 1, 31))?,
+            months: with_field("month", parse_field(month, 1, 12))?,
+            days_of_week,
+            day_of_month_restricted: *day_of_month != "*",
+            day_of_week_restricted: *day_of_week != "*",
+        })
+    }
+
+    fn matches_day(&self, time: &DateTime<Utc>) -> bool {
+        let dom = self.days_of_month & (1 << time.day()) != 0;
+        let dow = self.days_of_week & (1 << time.weekday().num_days_from_sunday()) != 0;
+
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+
+    /// Returns the first matching minute strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(Duration::minutes(1))?;
+
+        for _ in 0..MAX_SEARCH_STEPS {
+            if self.months & (1 << time.month()) == 0 {
+                // Jump to midnight on the first of next month
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+                continue;
+            }
+            if !self.matches_day(&time) {
+                time = Utc
+                    .with_ymd_and_hms(time.year(), time.month(), time.day(), 0, 0, 0)
+                    .single()?
+                    + Duration::days(1);
+                continue;
+            }
+            if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << time.minute()) == 0 {
+                time += Duration::minutes(1);
+                continue;
+            }
+            return Some(time);
+        }
+
+        None
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for CronSchedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_every_minute() {
+        let schedule = CronSchedule::parse("* * * * *").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 10, 15)),
+            Some(at(2025, 1, 1, 10, 16))
+        );
+    }
+
+    #[test]
+    fn test_hourly_at_offset() {
+        let schedule = CronSchedule::parse("5 * * * *").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 10, 5)),
+            Some(at(2025, 1, 1, 11, 5))
+        );
+    }
+
+    #[test]
+    fn test_steps_and_lists() {
+        let schedule = CronSchedule::parse("*/15 9-17 * * 1-5").unwrap();
+        // Friday 17:50 -> Monday 09:00
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 3, 17, 50)),
+            Some(at(2025, 1, 6, 9, 0))
+        );
+
+        let schedule = CronSchedule::parse("0 8,20 * * *").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 9, 0)),
+            Some(at(2025, 1, 1, 20, 0))
+        );
+    }
+
+    #[test]
+    fn test_month_rollover() {
+        let schedule = CronSchedule::parse("@monthly").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 12, 15, 0, 0)),
+            Some(at(2026, 1, 1, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_day_of_month_or_day_of_week() {
+        // 13th of the month or any Friday
+        let schedule = CronSchedule::parse("0 0 13 * 5").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 0, 0)),
+            Some(at(2025, 1, 3, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_sunday_as_seven() {
+        let schedule = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(
+            schedule.next_after(at(2025, 1, 1, 0, 0)),
+            Some(at(2025, 1, 5, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("* * * 0 *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_impossible_date_never_fires() {
+        let schedule = CronSchedule::parse("0 0 31 2 *").unwrap();
+        assert_eq!(schedule.next_after(at(2025, 1, 1, 0, 0)), None);
+    }
+}
@@ -1,10 +1,23 @@
-//! String encryption/decryption using AES-256-GCM with direct key support.
+//! String encryption/decryption using AES-256-GCM envelope encryption.
+//!
+//! Every value gets its own random data key. The value is encrypted with the
+//! data key, and the data key is wrapped with the server's current
+//! `ENCRYPTION_KEY`. The result records which key version wrapped it:
+//!
+//! ```text
+//! enc:v<version>:<base64 wrapped data key>:<base64 ciphertext>
+//! ```
+//!
+//! Rotating the key means configuring the new key with a higher
+//! `ENCRYPTION_KEY_VERSION`, listing the old one in `ENCRYPTION_KEYS_RETIRED`
+//! and rekeying, which only rewraps the data keys.
 //!
 //! ## Usage
 //!
 //! ```rust
-//! let encrypted = StringCrypto::encrypt("secret data")?;
-//! let decrypted = StringCrypto::decrypt(&encrypted)?;
+//! let crypto = StringCrypto::from_env()?;
+//! let encrypted = crypto.encrypt("secret data")?;
+//! let decrypted = crypto.decrypt(&encrypted)?;
 //! ```
 
 use crate::config::Config;
@@ -14,10 +27,18 @@ use aes_gcm::{
     aead::{Aead, KeyInit},
 };
 use base64::{Engine as _, engine::general_purpose};
+use std::collections::BTreeMap;
+
+/// Marks a value as sealed; followed by the key version
+const SEALED_PREFIX: &str = "enc:v";
+
+const NONCE_LEN: usize = 12;
 
 #[derive(Debug)]
 pub enum CryptoError {
     InvalidKey,
+    /// The value was wrapped with a key version that isn't configured
+    UnknownKeyVersion(u32),
     EncryptionFailed,
     DecryptionFailed,
     InvalidData,
@@ -27,6 +48,9 @@ impl std::fmt::Display for CryptoError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CryptoError::InvalidKey => write!(f, "Invalid encryption key"),
+            CryptoError::UnknownKeyVersion(version) => {
+                write!(f, "Encryption key version {version} is not configured")
+            }
             CryptoError::EncryptionFailed => write!(f, "Encryption failed"),
             CryptoError::DecryptionFailed => write!(f, "Decryption failed"),
             CryptoError::InvalidData => write!(f, "Invalid data format"),
@@ -36,89 +60,210 @@ impl std::fmt::Display for CryptoError {
 
 impl std::error::Error for CryptoError {}
 
-/// AES-256-GCM encryption/decryption for strings.
-pub struct StringCrypto;
+/// Builds a cipher from a configured key: 32 bytes base64 encoded, or raw
+/// bytes padded or truncated to 32.
+fn cipher_for_key(key_str: &str) -> Result<Aes256Gcm, CryptoError> {
+    let key_bytes = if key_str.len() == 44 {
+        // Assume base64 encoded key
+        general_purpose::STANDARD
+            .decode(key_str)
+            .map_err(|_| CryptoError::InvalidKey)?
+    } else {
+        // Use raw string bytes (pad or truncate to 32 bytes)
+        let mut bytes = vec![0u8; 32];
+        let input_bytes = key_str.as_bytes();
+        let copy_len = std::cmp::min(input_bytes.len(), 32);
+        bytes[..copy_len].copy_from_slice(&input_bytes[..copy_len]);
+        bytes
+    };
 
-impl StringCrypto {
-    /// Create a cipher instance from the environment key
-    fn create_cipher() -> Result<Aes256Gcm, CryptoError> {
-        let config = Config::from_env().unwrap();
-        let key_str = config.encryption_key;
-
-        // Decode base64 key or use raw bytes
-        let key_bytes = if key_str.len() == 44 {
-            // Assume base64 encoded key
-            general_purpose::STANDARD
-                .decode(key_str)
-                .map_err(|_| CryptoError::InvalidKey)?
-        } else {
-            // Use raw string bytes (pad or truncate to 32 bytes)
-            let mut bytes = vec![0u8; 32];
-            let input_bytes = key_str.as_bytes();
-            let copy_len = std::cmp::min(input_bytes.len(), 32);
-            bytes[..copy_len].copy_from_slice(&input_bytes[..copy_len]);
-            bytes
-        };
+    if key_bytes.len() != 32 {
+        return Err(CryptoError::InvalidKey);
+    }
 
-        if key_bytes.len() != 32 {
-            return Err(CryptoError::InvalidKey);
-        }
+    let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
+    Ok(Aes256Gcm::new(key))
+}
+
+/// Encrypts under a fresh nonce and returns nonce + ciphertext.
+fn seal_bytes(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
 
-        let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
-        let cipher = Aes256Gcm::new(key);
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|_| CryptoError::EncryptionFailed)?;
 
-        Ok(cipher)
+    let mut result = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    result.extend_from_slice(&nonce_bytes);
+    result.extend_from_slice(&ciphertext);
+    Ok(result)
+}
+
+/// Reverses `seal_bytes`.
+fn open_bytes(cipher: &Aes256Gcm, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if data.len() < NONCE_LEN {
+        return Err(CryptoError::InvalidData);
     }
+    let (nonce_bytes, ciphertext) = data.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| CryptoError::DecryptionFailed)
+}
 
-    /// Encrypt a string and return base64 encoded result.
-    /// Each encryption uses a unique nonce for security.
-    pub fn encrypt(plaintext: &str) -> Result<String, CryptoError> {
-        let cipher = Self::create_cipher()?;
+fn decode(data: &str) -> Result<Vec<u8>, CryptoError> {
+    general_purpose::STANDARD
+        .decode(data)
+        .map_err(|_| CryptoError::InvalidData)
+}
 
-        // Generate random nonce
-        let mut nonce_bytes = [0u8; 12];
-        OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
+/// The parts of a sealed value.
+struct Sealed<'a> {
+    version: u32,
+    wrapped_key: &'a str,
+    ciphertext: &'a str,
+}
 
-        // Encrypt the plaintext
-        let ciphertext = cipher
-            .encrypt(nonce, plaintext.as_bytes())
-            .map_err(|_| CryptoError::EncryptionFailed)?;
+fn parse_sealed(value: &str) -> Option<Sealed<'_>> {
+    let mut parts = value.strip_prefix(SEALED_PREFIX)?.splitn(3, ':');
+    Some(Sealed {
+        version: parts.next()?.parse().ok()?,
+        wrapped_key: parts.next()?,
+        ciphertext: parts.next()?,
+    })
+}
 
-        // Combine nonce + ciphertext
-        let mut result = Vec::new();
-        result.extend_from_slice(&nonce_bytes);
-        result.extend_from_slice(&ciphertext);
+/// AES-256-GCM envelope encryption for strings, holding the current key and
+/// any retired keys still needed to read older values.
+pub struct StringCrypto {
+    current_version: u32,
+    keys: BTreeMap<u32, Aes256Gcm>,
+}
 
-        // Return base64 encoded result
-        Ok(general_purpose::STANDARD.encode(result))
+impl StringCrypto {
+    /// Creates a keyring with `current_key` as `current_version` and the
+    /// given retired keys.
+    pub fn new(
+        current_version: u32,
+        current_key: &str,
+        retired_keys: &[(u32, String)],
+    ) -> Result<Self, CryptoError> {
+        let mut keys = BTreeMap::new();
+        for (version, key) in retired_keys {
+            keys.insert(*version, cipher_for_key(key)?);
+        }
+        keys.insert(current_version, cipher_for_key(current_key)?);
+        Ok(Self {
+            current_version,
+            keys,
+        })
     }
 
-    /// Decrypt a base64 encoded string that was encrypted with `encrypt()`.
-    pub fn decrypt(encrypted_data: &str) -> Result<String, CryptoError> {
-        let cipher = Self::create_cipher()?;
+    /// Create a keyring from the environment keys
+    pub fn from_env() -> Result<Self, CryptoError> {
+        let config = Config::from_env().map_err(|_| CryptoError::InvalidKey)?;
+        Self::new(
+            config.encryption_key_version,
+            &config.encryption_key,
+            &config.retired_encryption_keys,
+        )
+    }
 
-        // Decode base64
-        let data = general_purpose::STANDARD
-            .decode(encrypted_data)
-            .map_err(|_| CryptoError::InvalidData)?;
+    /// Version of the key new values are wrapped with.
+    pub fn current_version(&self) -> u32 {
+        self.current_version
+    }
 
-        if data.len() < 12 {
-            return Err(CryptoError::InvalidData);
-        }
+    /// The key version a stored value was wrapped with, or `None` if it
+    /// isn't sealed.
+    pub fn key_version(value: &str) -> Option<u32> {
+        parse_sealed(value).map(|sealed| sealed.version)
+    }
+
+    fn key(&self, version: u32) -> Result<&Aes256Gcm, CryptoError> {
+        self.keys
+            .get(&version)
+            .ok_or(CryptoError::UnknownKeyVersion(version))
+    }
+
+    fn wrap(&self, data_key: &[u8]) -> Result<String, CryptoError> {
+        let wrapped = seal_bytes(self.key(self.current_version)?, data_key)?;
+        Ok(general_purpose::STANDARD.encode(wrapped))
+    }
+
+    /// Encrypt a string under a fresh data key wrapped with the current key.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, CryptoError> {
+        let mut data_key = [0u8; 32];
+        OsRng.fill_bytes(&mut data_key);
+        let ciphertext = seal_bytes(
+            &Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key)),
+            plaintext.as_bytes(),
+        )?;
 
-        // Extract nonce and ciphertext
-        let (nonce_bytes, ciphertext) = data.split_at(12);
-        let nonce = Nonce::from_slice(nonce_bytes);
+        Ok(format!(
+            "{SEALED_PREFIX}{}:{}:{}",
+            self.current_version,
+            self.wrap(&data_key)?,
+            general_purpose::STANDARD.encode(ciphertext)
+        ))
+    }
 
-        // Decrypt
-        let plaintext = cipher
-            .decrypt(nonce, ciphertext)
-            .map_err(|_| CryptoError::DecryptionFailed)?;
+    /// Decrypt a value produced by `encrypt()`. Values from before envelope
+    /// encryption, base64 of nonce + ciphertext under the key itself, are
+    /// tried against every configured key.
+    pub fn decrypt(&self, encrypted_data: &str) -> Result<String, CryptoError> {
+        let plaintext = match parse_sealed(encrypted_data) {
+            Some(sealed) => {
+                let data_key = open_bytes(self.key(sealed.version)?, &decode(sealed.wrapped_key)?)?;
+                if data_key.len() != 32 {
+                    return Err(CryptoError::InvalidData);
+                }
+                open_bytes(
+                    &Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key)),
+                    &decode(sealed.ciphertext)?,
+                )?
+            }
+            None => {
+                let data = decode(encrypted_data)?;
+                self.keys
+                    .values()
+                    .rev()
+                    .find_map(|cipher| open_bytes(cipher, &data).ok())
+                    .ok_or(CryptoError::DecryptionFailed)?
+            }
+        };
 
-        // Convert back to string
         String::from_utf8(plaintext).map_err(|_| CryptoError::InvalidData)
     }
+
+    /// Reads a column that may still hold plaintext written before it was
+    /// encrypted at rest: sealed values are decrypted, anything else is
+    /// returned as is.
+    pub fn reveal(&self, stored: &str) -> Result<String, CryptoError> {
+        if parse_sealed(stored).is_some() {
+            self.decrypt(stored)
+        } else {
+            Ok(stored.to_string())
+        }
+    }
+
+    /// Rewraps a sealed value's data key with the current key, leaving the
+    /// ciphertext untouched. Returns `None` if it's already current.
+    pub fn rewrap(&self, sealed_value: &str) -> Result<Option<String>, CryptoError> {
+        let sealed = parse_sealed(sealed_value).ok_or(CryptoError::InvalidData)?;
+        if sealed.version == self.current_version {
+            return Ok(None);
+        }
+        let data_key = open_bytes(self.key(sealed.version)?, &decode(sealed.wrapped_key)?)?;
+
+        Ok(Some(format!(
+            "{SEALED_PREFIX}{}:{}:{}",
+            self.current_version,
+            self.wrap(&data_key)?,
+            sealed.ciphertext
+        )))
+    }
 }
 
 /// Generate a new base64-encoded 256-bit encryption key.
@@ -136,12 +281,17 @@ mod tests {
     mod tests {
         use super::*;
 
+        fn crypto() -> StringCrypto {
+            StringCrypto::new(1, &generate_key(), &[]).unwrap()
+        }
+
         #[test]
         fn test_encrypt_decrypt() {
             let original = "Test message";
+            let crypto = crypto();
 
-            let encrypted = StringCrypto::encrypt(original).unwrap();
-            let decrypted = StringCrypto::decrypt(&encrypted).unwrap();
+            let encrypted = crypto.encrypt(original).unwrap();
+            let decrypted = crypto.decrypt(&encrypted).unwrap();
 
             assert_eq!(original, decrypted);
         }
@@ -149,8 +299,9 @@ mod tests {
         #[test]
         fn test_unique_nonces() {
             let msg = "Same message";
-            let enc1 = StringCrypto::encrypt(msg).unwrap();
-            let enc2 = StringCrypto::encrypt(msg).unwrap();
+            let crypto = crypto();
+            let enc1 = crypto.encrypt(msg).unwrap();
+            let enc2 = crypto.encrypt(msg).unwrap();
 
             // Same message should produce different ciphertext
             assert_ne!(enc1, enc2);
@@ -158,8 +309,33 @@ mod tests {
             assert_ne!(enc1, enc2);
 
             // But both should decrypt correctly
-            assert_eq!(StringCrypto::decrypt(&enc1).unwrap(), msg);
-            assert_eq!(StringCrypto::decrypt(&enc2).unwrap(), msg);
+            assert_eq!(crypto.decrypt(&enc1).unwrap(), msg);
+            assert_eq!(crypto.decrypt(&enc2).unwrap(), msg);
+        }
+
+        #[test]
+        fn test_rotation() {
+            let old_key = generate_key();
+            let old = StringCrypto::new(1, &old_key, &[]).unwrap();
+            let sealed = old.encrypt("macaroon").unwrap();
+            let legacy = general_purpose::STANDARD
+                .encode(seal_bytes(&cipher_for_key(&old_key).unwrap(), b"rescue").unwrap());
+
+            let new = StringCrypto::new(2, &generate_key(), &[(1, old_key)]).unwrap();
+            assert_eq!(new.decrypt(&sealed).unwrap(), "macaroon");
+            assert_eq!(new.decrypt(&legacy).unwrap(), "rescue");
+
+            let rewrapped = new.rewrap(&sealed).unwrap().unwrap();
+            assert_eq!(StringCrypto::key_version(&rewrapped), Some(2));
+            assert!(new.rewrap(&rewrapped).unwrap().is_none());
+
+            // Once the old key is dropped, only rewrapped values can be read.
+            let rotated = StringCrypto::new(2, &generate_key(), &[]).unwrap();
+            assert!(matches!(
+                rotated.decrypt(&sealed),
+                Err(CryptoError::UnknownKeyVersion(1))
+            ));
+            assert_eq!(new.reveal("plain").unwrap(), "plain");
         }
     }
 }
@@ -1,67 +1,240 @@
 use crate::api::common::ApiResponse;
-use crate::errors::LightningError;
+use crate::errors::{ErrorCode, LightningError};
+use crate::middleware::retry_after::hint_retry_after;
+use crate::repositories::credential_repository::CredentialRepository;
+use crate::services::cln_rest::{ClnRestConnection, ClnRestNode};
+use crate::services::node_breaker::NodeBreaker;
+use crate::services::node_limiter::NodeLimiter;
 use crate::services::node_manager::{
-    ClnConnection, ClnNode, LightningClient, LndConnection, LndNode,
+    ClnConnection, ClnNode, ConnectionRequest, LightningClient, LndConnection, connect_lnd,
 };
 use crate::utils::NodeId;
-use crate::utils::jwt::{Claims, NodeCredentials};
+use crate::utils::jwt::{Claims, NodeCredentialRef, NodeCredentials};
 use axum::http::StatusCode;
 use bitcoin::secp256k1::PublicKey;
 use lightning::ln::PaymentHash;
+use sqlx::SqlitePool;
+use std::ops::Deref;
 use std::str::FromStr;
+use std::time::Instant;
+use tokio::sync::OwnedSemaphorePermit;
 
-/// Extract credentials from claims
-pub fn extract_node_credentials(claims: &Claims) -> Result<&NodeCredentials, (StatusCode, String)> {
-    claims.node_credentials().ok_or_else(|| {
+/// A node client that holds one of its node's in-flight request slots until dropped.
+pub struct NodeClientHandle {
+    client: Box<dyn LightningClient>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for NodeClientHandle {
+    type Target = dyn LightningClient;
+
+    fn deref(&self) -> &Self::Target {
+        self.client.as_ref()
+    }
+}
+
+/// Extract the node credential from claims, refusing nodes outside the
+/// user's node scope
+pub fn extract_node_credential(
+    claims: &Claims,
+) -> Result<&NodeCredentialRef, (StatusCode, String)> {
+    let node_credential = claims.node_credential().ok_or_else(|| {
         let error_response = ApiResponse::<()>::error(
             "No node credentials found in token".to_string(),
-            "missing_credentials",
+            ErrorCode::NodeCredentialsRequired,
             None,
         );
         (
             StatusCode::UNAUTHORIZED,
             serde_json::to_string(&error_response).unwrap(),
         )
-    })
+    })?;
+
+    if !claims.can_access_node(&node_credential.node_id) {
+        let error_response = ApiResponse::<()>::error(
+            "No access to this node".to_string(),
+            ErrorCode::PermissionDenied,
+            None,
+        );
+        return Err((
+            StatusCode::FORBIDDEN,
+            serde_json::to_string(&error_response).unwrap(),
+        ));
+    }
+
+    Ok(node_credential)
 }
 
-/// Creates and returns a Lightning client (LND or CLN) based on the provided credentials.
+/// The node a request acts on, validated once by the `node_credentials_required`
+/// middleware and passed to handlers as a request extension.
+#[derive(Debug, Clone)]
+pub struct NodeContext {
+    pub credentials: NodeCredentials,
+    /// Connection built from the credentials, with the node's parsed public key
+    connection: ConnectionRequest,
+}
+
+impl NodeContext {
+    /// Loads and decrypts the stored credential the claims point at, then
+    /// builds the node's connection. A credential deleted since the token was
+    /// issued is refused.
+    pub async fn load(pool: &SqlitePool, claims: &Claims) -> Result<Self, (StatusCode, String)> {
+        let node_credential = extract_node_credential(claims)?;
+        let credential = CredentialRepository::new(pool)
+            .get_credential_by_id(&node_credential.credential_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to load node credential: {}", e);
+                let error_response =
+                    ApiResponse::<()>::error("Database error", ErrorCode::DatabaseError, None);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    serde_json::to_string(&error_response).unwrap(),
+                )
+            })?
+            .filter(|credential| {
+                credential.account_id == claims.account_id
+                    && credential.node_id == node_credential.node_id
+            })
+            .ok_or_else(|| {
+                let error_response = ApiResponse::<()>::error(
+                    "Node credentials were removed. Please authenticate your node again.",
+                    ErrorCode::NodeCredentialsRequired,
+                    None,
+                );
+                (
+                    StatusCode::UNAUTHORIZED,
+                    serde_json::to_string(&error_response).unwrap(),
+                )
+            })?;
+
+        Self::new(NodeCredentials::from(credential))
+    }
+
+    /// Parses the node's public key and builds its connection.
+    fn new(credentials: NodeCredentials) -> Result<Self, (StatusCode, String)> {
+        let public_key = parse_public_key(&credentials.node_id)?;
+        let connection = node_connection(&credentials, public_key)?;
+
+        Ok(Self {
+            credentials,
+            connection,
+        })
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.credentials.node_id
+    }
+
+    /// Creates a client for the node, as [`create_node_client`] does.
+    pub async fn connect(&self) -> Result<NodeClientHandle, (StatusCode, String)> {
+        connect_with_guards(self.node_id(), self.connection.clone()).await
+    }
+}
+
+/// Creates and returns a Lightning client (LND, CLN or CLN REST) based on the provided credentials.
+///
+/// Fails fast with 503 while the node's circuit breaker is open. Otherwise
+/// waits for a free slot in the node's concurrency budget first and fails with
+/// 503 if none opens up within the configured queue timeout.
 pub async fn create_node_client(
     node_credentials: &NodeCredentials,
     public_key: PublicKey,
-) -> Result<Box<dyn LightningClient>, (StatusCode, String)> {
-    match node_credentials.node_type.as_str() {
-        "lnd" => {
-            let lnd_node = LndNode::new(LndConnection {
-                id: NodeId::PublicKey(public_key),
-                address: node_credentials.address.clone(),
-                macaroon: node_credentials.macaroon.clone(),
-                cert: node_credentials.tls_cert.clone(),
-            })
-            .await
-            .map_err(|e| handle_node_error(e, "connect to LND node"))?;
+) -> Result<NodeClientHandle, (StatusCode, String)> {
+    let connection = node_connection(node_credentials, public_key)?;
+    connect_with_guards(&node_credentials.node_id, connection).await
+}
+
+async fn connect_with_guards(
+    node_id: &str,
+    connection: ConnectionRequest,
+) -> Result<NodeClientHandle, (StatusCode, String)> {
+    let breaker = NodeBreaker::global();
+    if let Err(open) = breaker.admit(node_id, Instant::now()) {
+        let seconds = open.retry_after_secs();
+        hint_retry_after(seconds);
+        let error_response = ApiResponse::<()>::error(
+            format!("Node is unreachable, retry in {seconds}s"),
+            ErrorCode::NodeCircuitOpen,
+            None,
+        );
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            serde_json::to_string(&error_response).unwrap(),
+        ));
+    }
+
+    let permit = NodeLimiter::global().acquire(node_id).await.map_err(|_| {
+        breaker.release_probe(node_id);
+        hint_retry_after(NodeLimiter::global().retry_after_secs());
+        tracing::warn!("Node {} is saturated, rejecting request", node_id);
+        let error_response = ApiResponse::<()>::error(
+            "Node is busy, please retry shortly".to_string(),
+            ErrorCode::NodeBusy,
+            None,
+        );
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            serde_json::to_string(&error_response).unwrap(),
+        )
+    })?;
 
-            Ok(Box::new(lnd_node))
+    // Only failures to reach the node count against its circuit.
+    let client = match connect_node_client(connection).await {
+        Ok(client) => {
+            breaker.record_success(node_id);
+            client
+        }
+        Err((StatusCode::BAD_GATEWAY, body)) => {
+            breaker.record_failure(node_id, Instant::now());
+            return Err((StatusCode::BAD_GATEWAY, body));
+        }
+        Err(error) => {
+            breaker.release_probe(node_id);
+            return Err(error);
         }
+    };
+
+    Ok(NodeClientHandle {
+        client,
+        _permit: permit,
+    })
+}
+
+/// Builds the connection for a node from its stored credentials.
+fn node_connection(
+    node_credentials: &NodeCredentials,
+    public_key: PublicKey,
+) -> Result<ConnectionRequest, (StatusCode, String)> {
+    match node_credentials.node_type.as_str() {
+        "lnd" => Ok(ConnectionRequest::Lnd(LndConnection {
+            id: NodeId::PublicKey(public_key),
+            address: node_credentials.address.clone(),
+            macaroon: node_credentials.macaroon.clone(),
+            cert: node_credentials.tls_cert.clone(),
+        })),
         "cln" => {
             let (client_cert, client_key, ca_cert) = extract_cln_tls_components(node_credentials)?;
 
-            let cln_node = ClnNode::new(ClnConnection {
+            Ok(ConnectionRequest::Cln(ClnConnection {
                 id: NodeId::PublicKey(public_key),
                 address: node_credentials.address.clone(),
                 ca_cert,
                 client_cert,
                 client_key,
-            })
-            .await
-            .map_err(|e| handle_node_error(e, "connect to CLN node"))?;
-
-            Ok(Box::new(cln_node))
+            }))
         }
+        // The rune is stored in the macaroon column, which plays the same role for LND.
+        "clnrest" => Ok(ConnectionRequest::ClnRest(ClnRestConnection {
+            id: NodeId::PublicKey(public_key),
+            address: node_credentials.address.clone(),
+            rune: node_credentials.macaroon.clone(),
+            ca_cert: node_credentials.ca_cert.clone(),
+        })),
         _ => {
             let error_response = ApiResponse::<()>::error(
                 "Unsupported node type".to_string(),
-                "unsupported_node_type",
+                ErrorCode::UnsupportedNodeType,
                 None,
             );
             Err((
@@ -72,12 +245,40 @@ pub async fn create_node_client(
     }
 }
 
+async fn connect_node_client(
+    connection: ConnectionRequest,
+) -> Result<Box<dyn LightningClient>, (StatusCode, String)> {
+    match connection {
+        ConnectionRequest::Lnd(lnd_connection) => {
+            let lnd_node: Box<dyn LightningClient> = connect_lnd(lnd_connection)
+                .await
+                .map_err(|e| handle_node_error(e, "connect to LND node"))?;
+
+            Ok(lnd_node)
+        }
+        ConnectionRequest::Cln(cln_connection) => {
+            let cln_node = ClnNode::new(cln_connection)
+                .await
+                .map_err(|e| handle_node_error(e, "connect to CLN node"))?;
+
+            Ok(Box::new(cln_node))
+        }
+        ConnectionRequest::ClnRest(cln_rest_connection) => {
+            let cln_rest_node = ClnRestNode::new(cln_rest_connection)
+                .await
+                .map_err(|e| handle_node_error(e, "connect to CLN REST node"))?;
+
+            Ok(Box::new(cln_rest_node))
+        }
+    }
+}
+
 /// Parse hex string into PaymentHash
 pub fn parse_payment_hash(payment_hash: &str) -> Result<PaymentHash, (StatusCode, String)> {
     let payment_hash_bytes = hex::decode(payment_hash).map_err(|e| {
         let error_response = ApiResponse::<()>::error(
             format!("Invalid payment hash format: {e}"),
-            "invalid_payment_hash",
+            ErrorCode::InvalidPaymentHash,
             None,
         );
         (
@@ -89,7 +290,7 @@ pub fn parse_payment_hash(payment_hash: &str) -> Result<PaymentHash, (StatusCode
     if payment_hash_bytes.len() != 32 {
         let error_response = ApiResponse::<()>::error(
             "Payment hash must be 32 bytes".to_string(),
-            "invalid_payment_hash_length",
+            ErrorCode::InvalidPaymentHash,
             None,
         );
         return Err((
@@ -108,7 +309,7 @@ pub fn parse_public_key(node_id: &str) -> Result<PublicKey, (StatusCode, String)
     PublicKey::from_str(node_id).map_err(|e| {
         let error_response = ApiResponse::<()>::error(
             format!("Invalid node public key: {e}"),
-            "invalid_public_key",
+            ErrorCode::InvalidPublicKey,
             None,
         );
         (
@@ -125,7 +326,7 @@ pub fn extract_cln_tls_components(
     let client_cert = node_credentials.client_cert.as_ref().ok_or_else(|| {
         let error_response = ApiResponse::<()>::error(
             "Missing client certificate for CLN".to_string(),
-            "missing_client_cert",
+            ErrorCode::InvalidNodeCredentials,
             None,
         );
         (
@@ -137,7 +338,7 @@ pub fn extract_cln_tls_components(
     let client_key = node_credentials.client_key.as_ref().ok_or_else(|| {
         let error_response = ApiResponse::<()>::error(
             "Missing client key for CLN".to_string(),
-            "missing_client_key",
+            ErrorCode::InvalidNodeCredentials,
             None,
         );
         (
@@ -149,7 +350,7 @@ pub fn extract_cln_tls_components(
     let ca_cert = node_credentials.ca_cert.as_ref().ok_or_else(|| {
         let error_response = ApiResponse::<()>::error(
             "Missing CA certificate for CLN".to_string(),
-            "missing_ca_cert",
+            ErrorCode::InvalidNodeCredentials,
             None,
         );
         (
@@ -164,13 +365,78 @@ pub fn extract_cln_tls_components(
 /// Handle node operation errors
 pub fn handle_node_error(e: LightningError, operation: &str) -> (StatusCode, String) {
     tracing::error!("{} failed: {}", operation, e);
-    let error_response = ApiResponse::<()>::error(
-        format!("Failed to {operation}: {e}"),
-        format!("{}_error", operation.replace(' ', "_")),
-        None,
-    );
-    (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        serde_json::to_string(&error_response).unwrap(),
-    )
+    let code = e.code();
+    let status = match code {
+        ErrorCode::NodeUnreachable | ErrorCode::NodeRequestFailed => StatusCode::BAD_GATEWAY,
+        ErrorCode::NotFound => StatusCode::NOT_FOUND,
+        ErrorCode::UnsupportedOperation => StatusCode::NOT_IMPLEMENTED,
+        ErrorCode::InvalidNodeCredentials => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let error_response =
+        ApiResponse::<()>::error(format!("Failed to {operation}: {e}"), code, None);
+    (status, serde_json::to_string(&error_response).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::RoleAccessLevel;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+
+    #[test]
+    fn test_extract_node_credential_checks_scope() {
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let node_id = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key).to_string();
+        let mut claims = Claims {
+            sub: "user".to_string(),
+            account_id: "account".to_string(),
+            role: "Member".to_string(),
+            role_access_level: RoleAccessLevel::Read,
+            permissions: Vec::new(),
+            node_credential: None,
+            node_scope: None,
+            exp: 0,
+            iat: 0,
+        };
+
+        let (status, _) = extract_node_credential(&claims).unwrap_err();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        claims.node_credential = Some(NodeCredentialRef {
+            credential_id: "credential".to_string(),
+            node_id: node_id.clone(),
+            node_type: "cln".to_string(),
+        });
+        assert_eq!(extract_node_credential(&claims).unwrap().node_id, node_id);
+
+        claims.node_scope = Some(vec!["other-node".to_string()]);
+        let (status, _) = extract_node_credential(&claims).unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_node_context_requires_cln_client_identity() {
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let node_id = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key).to_string();
+        let mut credentials = NodeCredentials {
+            node_id: node_id.clone(),
+            node_alias: "alice".to_string(),
+            node_type: "cln".to_string(),
+            macaroon: String::new(),
+            tls_cert: String::new(),
+            client_cert: Some("client.pem".to_string()),
+            client_key: None,
+            ca_cert: Some("ca.pem".to_string()),
+            address: "https://localhost:9736".to_string(),
+        };
+
+        let (status, _) = NodeContext::new(credentials.clone()).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        credentials.client_key = Some("client-key.pem".to_string());
+        let node_context = NodeContext::new(credentials).unwrap();
+        assert_eq!(node_context.node_id(), node_id);
+        assert!(matches!(node_context.connection, ConnectionRequest::Cln(_)));
+    }
 }
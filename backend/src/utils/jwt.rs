@@ -8,7 +8,7 @@ use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, deco
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
-use crate::database::models::RoleAccessLevel;
+use crate::database::models::{Credential, Permission, RoleAccessLevel};
 use crate::errors::ServiceError;
 
 /// JWT Claims structure containing user and node authentication data
@@ -18,24 +18,58 @@ pub struct Claims {
     pub sub: String,
     /// Account ID
     pub account_id: String,
-    /// User role
+    /// User role; `jwt_auth` replaces it with the user's current role on
+    /// every request
     pub role: String,
-    /// Role access level
+    /// Role access level, refreshed by `jwt_auth` like the role
     pub role_access_level: RoleAccessLevel,
-    /// Node credentials (optional, now unencrypted)
-    pub node_credentials: Option<NodeCredentials>,
+    /// Permission scopes granted by the user's role, refreshed by `jwt_auth`
+    /// like the role
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+    /// Stored node credential the session acts on. Only identifies it: the
+    /// payload is readable by anyone holding the token, so the secrets are
+    /// loaded server-side by `node_credentials_required`
+    #[serde(default)]
+    pub node_credential: Option<NodeCredentialRef>,
+    /// Nodes of the account the user is limited to, refreshed by `jwt_auth`
+    /// like the role; `None` for every node
+    #[serde(default)]
+    pub node_scope: Option<Vec<String>>,
     /// Token expiration timestamp
     pub exp: usize,
     /// Token issued at timestamp
     pub iat: usize,
 }
 
-/// Node credentials stored in JWT (now unencrypted for simplicity)
+/// Stored node credential a token is bound to
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NodeCredentialRef {
+    pub credential_id: String,
+    pub node_id: String,
+    pub node_type: String, // "lnd", "cln" or "clnrest"
+}
+
+impl From<&Credential> for NodeCredentialRef {
+    fn from(credential: &Credential) -> Self {
+        Self {
+            credential_id: credential.id.clone(),
+            node_id: credential.node_id.clone(),
+            node_type: credential
+                .node_type
+                .clone()
+                .unwrap_or_else(|| "lnd".to_string()),
+        }
+    }
+}
+
+/// Decrypted node credentials, read from the database to connect to a node.
+/// Never put in a token.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NodeCredentials {
     pub node_id: String,
     pub node_alias: String,
-    pub node_type: String, // "lnd" or "cln"
+    pub node_type: String, // "lnd", "cln" or "clnrest"
     pub macaroon: String,
     pub tls_cert: String,
     pub client_cert: Option<String>, // For CLN
@@ -44,6 +78,22 @@ pub struct NodeCredentials {
     pub address: String,
 }
 
+impl From<Credential> for NodeCredentials {
+    fn from(credential: Credential) -> Self {
+        Self {
+            node_id: credential.node_id,
+            node_alias: credential.node_alias,
+            node_type: credential.node_type.unwrap_or_else(|| "lnd".to_string()),
+            macaroon: credential.macaroon,
+            tls_cert: credential.tls_cert,
+            client_cert: credential.client_cert,
+            client_key: credential.client_key,
+            ca_cert: credential.ca_cert,
+            address: credential.address,
+        }
+    }
+}
+
 /// JWT token utility for creating and validating tokens
 pub struct JwtUtils {
     encoding_key: EncodingKey,
@@ -70,14 +120,17 @@ impl JwtUtils {
         })
     }
 
-    /// Generate a new JWT token with user and optional node credentials
+    /// Generate a new JWT token with user and the optional node credential it acts on
+    #[allow(clippy::too_many_arguments)]
     pub fn generate_token(
         &self,
         user_id: String,
         account_id: String,
         role: String,
         role_access_level: RoleAccessLevel,
-        node_credentials: Option<NodeCredentials>,
+        permissions: Vec<Permission>,
+        node_credential: Option<NodeCredentialRef>,
+        node_scope: Option<Vec<String>>,
     ) -> Result<String, ServiceError> {
         // Get expires_in from config
         let config = Config::from_env()
@@ -92,7 +145,9 @@ impl JwtUtils {
             account_id,
             role,
             role_access_level,
-            node_credentials,
+            permissions,
+            node_credential,
+            node_scope,
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
         };
@@ -108,10 +163,12 @@ impl JwtUtils {
             .map_err(|e| ServiceError::validation(format!("Token validation failed: {e}")))
     }
 
-    /// Generate a refresh token (longer expiration)
+    /// Generate a refresh token (longer expiration) that keeps the session on
+    /// the account it was issued for
     pub fn generate_refresh_token(
         &self,
         user_id: String,
+        account_id: String,
         role_access_level: RoleAccessLevel,
     ) -> Result<String, ServiceError> {
         let now = Utc::now();
@@ -119,10 +176,12 @@ impl JwtUtils {
 
         let claims = Claims {
             sub: user_id,
-            account_id: String::new(), // Refresh tokens don't need account info
+            account_id,
             role: String::new(),
             role_access_level,
-            node_credentials: None,
+            permissions: Vec::new(),
+            node_credential: None,
+            node_scope: None,
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
         };
@@ -147,11 +206,18 @@ impl Claims {
     }
 
     pub fn has_node_credentials(&self) -> bool {
-        self.node_credentials.is_some()
+        self.node_credential.is_some()
     }
 
-    pub fn node_credentials(&self) -> Option<&NodeCredentials> {
-        self.node_credentials.as_ref()
+    pub fn node_credential(&self) -> Option<&NodeCredentialRef> {
+        self.node_credential.as_ref()
+    }
+
+    /// Check if the user may access one of the account's nodes
+    pub fn can_access_node(&self, node_id: &str) -> bool {
+        self.node_scope
+            .as_ref()
+            .is_none_or(|node_ids| node_ids.iter().any(|id| id == node_id))
     }
 
     /// Check if token has expired
@@ -169,4 +235,9 @@ impl Claims {
     pub fn is_admin(&self) -> bool {
         self.has_role("Admin")
     }
+
+    /// Check if the user's role grants a permission scope
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.is_admin() || self.permissions.contains(&permission)
+    }
 }
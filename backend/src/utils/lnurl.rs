@@ -0,0 +1,76 @@
+//! LNURL encoding and LNURL-auth signature verification (LUD-01 / LUD-04).
+
+use bitcoin::bech32::{self, Bech32, Hrp};
+use bitcoin::secp256k1::{Message, PublicKey, Secp256k1, ecdsa::Signature};
+
+/// Encodes a URL as an uppercase bech32 `LNURL1...` string, the form wallets
+/// expect inside QR codes.
+pub fn encode_lnurl(url: &str) -> Result<String, String> {
+    let hrp = Hrp::parse("lnurl").map_err(|e| format!("Invalid LNURL prefix: {e}"))?;
+    bech32::encode_upper::<Bech32>(hrp, url.as_bytes())
+        .map_err(|e| format!("Failed to encode LNURL: {e}"))
+}
+
+/// Verifies an LNURL-auth response.
+///
+/// `sig` is a hex DER-encoded ECDSA signature over the raw 32 bytes of `k1`,
+/// produced by the compressed public key `key`.
+pub fn verify_auth_signature(k1: &str, sig: &str, key: &str) -> Result<(), String> {
+    let k1_bytes = hex::decode(k1).map_err(|_| "k1 must be hex encoded".to_string())?;
+    let message =
+        Message::from_digest_slice(&k1_bytes).map_err(|_| "k1 must be 32 bytes".to_string())?;
+
+    let sig_bytes = hex::decode(sig).map_err(|_| "Signature must be hex encoded".to_string())?;
+    let mut signature =
+        Signature::from_der(&sig_bytes).map_err(|_| "Signature must be DER encoded".to_string())?;
+    // Some wallets emit high-S signatures; libsecp256k1 only accepts the low-S form.
+    signature.normalize_s();
+
+    let key_bytes = hex::decode(key).map_err(|_| "Linking key must be hex encoded".to_string())?;
+    let linking_key =
+        PublicKey::from_slice(&key_bytes).map_err(|_| "Invalid linking key".to_string())?;
+
+    Secp256k1::verification_only()
+        .verify_ecdsa(&message, &signature, &linking_key)
+        .map_err(|_| "Signature verification failed".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::SecretKey;
+
+    fn sign(k1: &[u8; 32], secret: &SecretKey) -> (String, String) {
+        let secp = Secp256k1::new();
+        let message = Message::from_digest_slice(k1).unwrap();
+        let signature = secp.sign_ecdsa(&message, secret);
+        let key = PublicKey::from_secret_key(&secp, secret);
+        (
+            hex::encode(&*signature.serialize_der()),
+            hex::encode(key.serialize()),
+        )
+    }
+
+    #[test]
+    fn test_valid_signature_verifies() {
+        let k1 = [7u8; 32];
+        let secret = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let (sig, key) = sign(&k1, &secret);
+
+        assert!(verify_auth_signature(&hex::encode(k1), &sig, &key).is_ok());
+    }
+
+    #[test]
+    fn test_signature_over_other_k1_is_rejected() {
+        let secret = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let (sig, key) = sign(&[7u8; 32], &secret);
+
+        assert!(verify_auth_signature(&hex::encode([8u8; 32]), &sig, &key).is_err());
+    }
+
+    #[test]
+    fn test_encode_lnurl_prefix() {
+        let lnurl = encode_lnurl("https://example.com/auth/lnurl/callback?tag=login").unwrap();
+        assert!(lnurl.starts_with("LNURL1"));
+    }
+}
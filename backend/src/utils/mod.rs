@@ -3,6 +3,7 @@
 //! This module serves as a repository for small, reusable helper functions
 //! or traits that do not fit into other specific domain modules.
 
+use crate::database::models::{ChannelNote, PeerMetadata};
 use crate::errors::LightningError;
 use bitcoin::Txid;
 use bitcoin::secp256k1::PublicKey;
@@ -13,11 +14,16 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
+pub mod cron;
 pub mod crypto;
 pub mod generate_random_string;
 pub mod handlers_common;
 pub mod jwt;
+pub mod lnurl;
+pub mod password;
+pub mod redact;
 pub mod sats_to_usd;
+pub mod states;
 
 /// Represents a node id, either by its public key or alias.
 #[derive(Serialize, Debug, Clone)]
@@ -113,6 +119,21 @@ pub struct ChannelDetails {
     pub total_satoshis_received: Option<u64>,
     pub channel_age_blocks: Option<u32>,
     pub opening_cost_sat: Option<u64>,
+    /// On-chain fee we paid for the funding transaction (0 if the peer opened it)
+    pub funding_fee_sat: Option<u64>,
+    /// Transaction that closed the channel, for closed channels
+    pub closing_txid: Option<Txid>,
+    /// On-chain fee we paid for the closing transaction (0 if the peer opened
+    /// the channel, as the opener pays it)
+    pub closing_fee_sat: Option<u64>,
+    /// Fees paid for completed swaps that rebalanced this channel
+    pub rebalance_cost_sat: Option<u64>,
+    /// Amboss/1ML metadata of the peer, when peer enrichment is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_metadata: Option<PeerMetadata>,
+    /// Notes kept on the channel and on its peer
+    #[serde(default)]
+    pub notes: Vec<ChannelNote>,
     pub initiator: Option<bool>,
     pub txid: Option<Txid>,
     pub vout: Option<u32>,
@@ -123,6 +144,7 @@ pub struct ChannelDetails {
 #[derive(Debug, Serialize)]
 pub struct ChannelSummary {
     pub chan_id: ShortChannelID,
+    pub remote_pubkey: String,
     pub alias: Option<String>,
     pub channel_state: ChannelState,
     pub private: bool,
@@ -131,6 +153,31 @@ pub struct ChannelSummary {
     pub capacity: u64,
     pub last_update: Option<u64>,
     pub uptime: Option<u64>,
+    /// Notes kept on the channel and on its peer
+    pub notes: Vec<ChannelNote>,
+}
+
+/// Whether a pending channel is waiting on its funding or its closing transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingChannelKind {
+    Opening,
+    Closing,
+}
+
+/// A channel whose funding or closing transaction hasn't fully confirmed yet.
+#[derive(Debug, Serialize)]
+pub struct PendingChannel {
+    pub kind: PendingChannelKind,
+    pub channel_point: String,
+    pub remote_pubkey: String,
+    pub capacity_sat: u64,
+    /// Funding transaction while opening, closing transaction while closing
+    /// (None until the node has broadcast it)
+    pub txid: Option<String>,
+    pub confirmations: Option<u32>,
+    /// Confirmations left until the transaction is final
+    pub confirmations_remaining: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -145,13 +192,54 @@ pub struct CustomInvoice {
     pub payment_request: String,
     pub expiry: Option<u64>,
     pub state: InvoiceStatus,
+    /// What the payer actually paid, when the node reports it
+    pub amount_paid_msat: Option<u64>,
+    /// Set when the amount paid differs from the invoice value
+    pub payment_mismatch: Option<PaymentMismatch>,
     pub is_keysend: Option<bool>,
+    /// Text message a keysend payer attached to the payment
+    pub keysend_message: Option<String>,
     pub is_amp: Option<bool>,
     pub payment_addr: Option<String>,
     pub htlcs: Option<Vec<InvoiceHtlc>>,
     pub features: Option<HashMap<u32, Feature>>,
 }
 
+/// How the amount paid for an invoice differs from its value.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMismatch {
+    Overpaid,
+    /// Paid in part, like a hold invoice whose HTLCs don't add up to its value
+    Underpaid,
+}
+
+impl PaymentMismatch {
+    /// Compares what was paid with what was asked. Invoices without an
+    /// amount take anything, and an unpaid invoice isn't a mismatch.
+    pub fn between(value_msat: u64, amount_paid_msat: u64) -> Option<Self> {
+        if value_msat == 0 || amount_paid_msat == 0 {
+            None
+        } else if amount_paid_msat > value_msat {
+            Some(Self::Overpaid)
+        } else if amount_paid_msat < value_msat {
+            Some(Self::Underpaid)
+        } else {
+            None
+        }
+    }
+}
+
+/// An invoice the node issued for a BOLT12 offer, one per invoice request.
+#[derive(Debug, Clone)]
+pub struct OfferInvoice {
+    pub state: InvoiceStatus,
+    pub amount_received_msat: Option<u64>,
+    pub paid_at: Option<u64>,
+    /// Free-form note the payer attached to the invoice request
+    pub payer_note: Option<String>,
+}
+
 /// Represents a node's routing policy for forwarding payments
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NodePolicy {
@@ -244,6 +332,140 @@ pub struct PaymentSummary {
     pub invoice: Option<String>,
     pub payment_hash: String,
     pub completed_at: Option<u64>,
+    /// Our channel the payment left or arrived through, when the node reports it
+    pub channel_id: Option<ShortChannelID>,
+    /// Text message attached to a received keysend payment
+    pub keysend_message: Option<String>,
+}
+
+/// Progress of an outgoing payment, emitted each time it changes while tracked.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PaymentProgress {
+    pub payment_hash: String,
+    pub state: PaymentState,
+    pub amount_msat: u64,
+    pub fee_msat: u64,
+    pub failure_reason: Option<String>,
+    pub attempts: Vec<PaymentAttempt>,
+}
+
+/// A node in the public channel graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub pubkey: String,
+    pub alias: String,
+    pub last_update: Option<u64>,
+    /// Announced addresses as `host:port`
+    pub addresses: Vec<String>,
+    /// Feature bits set in the announcement, ascending
+    pub features: Vec<u32>,
+}
+
+/// A public channel in the graph with the routing policies its ends advertise.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphChannel {
+    pub chan_id: u64,
+    /// The lexicographically smaller of the two node keys, as in BOLT 7
+    pub node1_pub: String,
+    pub node2_pub: String,
+    pub capacity_sat: u64,
+    pub policies: Vec<NodePolicy>,
+}
+
+/// A node's view of the public channel graph.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NetworkGraph {
+    pub nodes: Vec<GraphNode>,
+    pub channels: Vec<GraphChannel>,
+}
+
+/// An incremental change to the channel graph.
+#[derive(Debug)]
+pub enum GraphUpdate {
+    Node(GraphNode),
+    /// A channel was announced or one of its ends updated its policy. Only the
+    /// policies included changed.
+    Channel(GraphChannel),
+    ChannelClosed(u64),
+}
+
+/// A payment the node forwarded between two of its channels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Forward {
+    /// Unix time the forward settled, in seconds
+    pub timestamp: u64,
+    /// Unix time the incoming HTLC arrived, when the source reports it
+    pub received_at: Option<u64>,
+    pub chan_id_in: ShortChannelID,
+    pub chan_id_out: ShortChannelID,
+    /// HTLC ids on the incoming and outgoing channel, which identify the
+    /// circuit; LND's forwarding history doesn't report them
+    pub incoming_htlc_id: Option<u64>,
+    pub outgoing_htlc_id: Option<u64>,
+    pub amt_in_msat: u64,
+    pub amt_out_msat: u64,
+    pub fee_msat: u64,
+}
+
+/// How long a settled outgoing payment took, for latency tracking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentLatency {
+    pub payment_hash: String,
+    pub destination: Option<String>,
+    /// Our channel the successful attempt left through, when the node reports it
+    pub first_hop: Option<ShortChannelID>,
+    /// Unix time the payment settled, in seconds
+    pub settled_at: u64,
+    /// From the payment being started to its preimage coming back
+    pub settle_ms: u64,
+    /// Round trip of the successful HTLC alone, excluding pathfinding and
+    /// earlier failed attempts. Only LND records per-attempt timings.
+    pub htlc_ms: Option<u64>,
+}
+
+/// Outcome of a circular payment that moved liquidity between two of our channels.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RebalanceOutcome {
+    pub succeeded: bool,
+    /// Routing fees paid, zero unless the payment succeeded
+    pub fee_msat: u64,
+    /// Route of the successful attempt, or of the last failed one
+    pub route: Vec<Hop>,
+    pub failure_reason: Option<String>,
+}
+
+/// Outcome of a probe: a payment to a destination with a payment hash no
+/// invoice has, which can only fail.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProbeOutcome {
+    /// The destination rejected the unknown payment hash, so the route
+    /// carried the amount all the way
+    pub reached: bool,
+    /// Time until the probe resolved
+    pub latency_ms: u64,
+    /// Why the probe didn't reach the destination
+    pub failure_reason: Option<String>,
+}
+
+/// Outcome of checking a signed message against the node.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageVerification {
+    pub valid: bool,
+    /// Hex-encoded public key recovered from the signature
+    pub pubkey: String,
+}
+
+/// A single route attempt (HTLC) of an outgoing payment.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PaymentAttempt {
+    pub attempt_id: u64,
+    pub state: PaymentState,
+    pub amount_msat: u64,
+    pub fee_msat: u64,
+    pub hop_count: usize,
+    pub attempt_time: Option<u64>,
+    pub resolve_time: Option<u64>,
+    pub failure_reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -291,6 +513,8 @@ pub struct Hop {
     pub expiry: Option<u64>,
 }
 
+/// Canonical payment state across node implementations; see [`states`] for
+/// how each backend's vocabulary maps onto it.
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Copy)]
 pub enum PaymentState {
     Inflight,
@@ -304,8 +528,11 @@ pub enum PaymentType {
     Outgoing,
     Incoming,
     Forwarded,
+    /// Received without an invoice, pushed by the payer
+    Keysend,
 }
 
+/// Canonical invoice status across node implementations.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub enum InvoiceStatus {
     #[default]
@@ -315,6 +542,7 @@ pub enum InvoiceStatus {
     Failed,
 }
 
+/// Canonical channel state across node implementations.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub enum ChannelState {
     Opening, // funding tx not confirmed
@@ -380,7 +608,7 @@ pub mod serde_address {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        if s.starts_with("https://") || s.starts_with("http://") {
+        if s.starts_with("https://") || s.starts_with("http://") || s.starts_with("mock://") {
             Ok(s)
         } else {
             Ok(format!("https://{s}"))
@@ -399,6 +627,19 @@ where
         .to_string())
 }
 
+pub fn deserialize_optional_path<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| {
+            expanduser(s)
+                .map(|path| path.display().to_string())
+                .map_err(serde::de::Error::custom)
+        })
+        .transpose()
+}
+
 mod node_features_serde {
     use super::*;
     pub fn serialize<S>(features: &NodeFeatures, serializer: S) -> Result<S::Ok, S::Error>
@@ -450,19 +691,6 @@ impl From<ShortChannelID> for u64 {
     }
 }
 
-impl FromStr for PaymentState {
-    type Err = String;
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        match input.to_lowercase().as_str() {
-            "inflight" => Ok(PaymentState::Inflight),
-            "failed" => Ok(PaymentState::Failed),
-            "settled" => Ok(PaymentState::Settled),
-            _ => Err(format!("Invalid payment state: {input}")),
-        }
-    }
-}
-
 impl Display for PaymentState {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let state = match self {
@@ -492,6 +720,7 @@ impl FromStr for PaymentType {
             "outgoing" => Ok(PaymentType::Outgoing),
             "incoming" => Ok(PaymentType::Incoming),
             "forwarded" => Ok(PaymentType::Forwarded),
+            "keysend" => Ok(PaymentType::Keysend),
             _ => Err(format!("Invalid payment type: {input}")),
         }
     }
@@ -503,6 +732,7 @@ impl Display for PaymentType {
             PaymentType::Outgoing => "outgoing",
             PaymentType::Incoming => "incoming",
             PaymentType::Forwarded => "forwarded",
+            PaymentType::Keysend => "keysend",
         };
         write!(f, "{payment_type}")
     }
@@ -514,6 +744,7 @@ impl PaymentType {
             PaymentType::Outgoing => "outgoing",
             PaymentType::Incoming => "incoming",
             PaymentType::Forwarded => "forwarded",
+            PaymentType::Keysend => "keysend",
         }
     }
 }
@@ -564,20 +795,6 @@ impl Display for InvoiceStatus {
     }
 }
 
-impl FromStr for InvoiceStatus {
-    type Err = String;
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        match input.to_lowercase().as_str() {
-            "settled" => Ok(InvoiceStatus::Settled),
-            "open" => Ok(InvoiceStatus::Open),
-            "expired" => Ok(InvoiceStatus::Expired),
-            "failed" => Ok(InvoiceStatus::Failed),
-            _ => Err(format!("Invalid invoice status: {input}")),
-        }
-    }
-}
-
 impl Display for ChannelState {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let state = match self {
@@ -591,19 +808,3 @@ impl Display for ChannelState {
         write!(f, "{state}")
     }
 }
-
-impl FromStr for ChannelState {
-    type Err = String;
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        match input.to_lowercase().as_str() {
-            "opening" => Ok(ChannelState::Opening),
-            "active" => Ok(ChannelState::Active),
-            "disabled" => Ok(ChannelState::Disabled),
-            "closing" => Ok(ChannelState::Closing),
-            "closed" => Ok(ChannelState::Closed),
-            "failed" => Ok(ChannelState::Failed),
-            _ => Err(format!("Invalid channel state: {input}")),
-        }
-    }
-}
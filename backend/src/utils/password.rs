@@ -0,0 +1,190 @@
+//! Password hashing and the password policy.
+//!
+//! Passwords are hashed with Argon2id into PHC strings, which carry their own
+//! parameters. Hashes written before the switch are bcrypt; they still
+//! verify, and `needs_rehash` flags them, along with Argon2 hashes made with
+//! weaker parameters than the current ones, so the next successful login can
+//! replace them. Hashing and verifying run on the blocking thread pool, as
+//! each takes tens of milliseconds of CPU.
+
+use crate::errors::{ServiceError, ServiceResult};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+
+/// Memory cost in KiB; with the iterations and lanes below this is the
+/// OWASP recommended minimum for Argon2id
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+
+const ARGON2_ITERATIONS: u32 = 2;
+
+const ARGON2_PARALLELISM: u32 = 1;
+
+const MIN_PASSWORD_LENGTH: usize = 12;
+
+const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Passwords this long are accepted without mixing character classes, so
+/// passphrases of plain words pass
+const PASSPHRASE_LENGTH: usize = 20;
+
+/// Character classes a shorter password must draw from
+const MIN_CHARACTER_CLASSES: usize = 3;
+
+fn argon2() -> ServiceResult<Argon2<'static>> {
+    let params = Params::new(
+        ARGON2_MEMORY_KIB,
+        ARGON2_ITERATIONS,
+        ARGON2_PARALLELISM,
+        None,
+    )
+    .map_err(|e| ServiceError::internal_error(format!("Invalid Argon2 parameters: {e}")))?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+fn is_bcrypt(hash: &str) -> bool {
+    hash.starts_with("$2")
+}
+
+/// Runs password work on the blocking thread pool.
+async fn spawn_blocking<T, F>(work: F) -> ServiceResult<T>
+where
+    F: FnOnce() -> ServiceResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| ServiceError::internal_error(format!("Password task failed: {e}")))?
+}
+
+/// Hashes a password for storage.
+pub async fn hash_password(password: &str) -> ServiceResult<String> {
+    let password = password.to_string();
+    spawn_blocking(move || hash_blocking(&password)).await
+}
+
+fn hash_blocking(password: &str) -> ServiceResult<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    argon2()?
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| ServiceError::internal_error(format!("Password hashing failed: {e}")))
+}
+
+/// Checks a password against a stored Argon2 or legacy bcrypt hash.
+pub async fn verify_password(password: &str, hash: &str) -> ServiceResult<bool> {
+    let (password, hash) = (password.to_string(), hash.to_string());
+    spawn_blocking(move || verify_blocking(&password, &hash)).await
+}
+
+fn verify_blocking(password: &str, hash: &str) -> ServiceResult<bool> {
+    if is_bcrypt(hash) {
+        return bcrypt::verify(password, hash).map_err(|e| {
+            ServiceError::internal_error(format!("Password verification failed: {e}"))
+        });
+    }
+
+    let parsed = PasswordHash::new(hash)
+        .map_err(|e| ServiceError::internal_error(format!("Invalid password hash: {e}")))?;
+    // Verifying uses the parameters stored in the hash, not the current ones.
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
+}
+
+/// Whether a stored hash should be replaced by a fresh one: it's bcrypt, or
+/// Argon2 with other than the current algorithm and parameters.
+pub fn needs_rehash(hash: &str) -> bool {
+    if is_bcrypt(hash) {
+        return true;
+    }
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return true;
+    };
+    let Ok(params) = Params::try_from(&parsed) else {
+        return true;
+    };
+    parsed.algorithm != Algorithm::Argon2id.ident()
+        || params.m_cost() < ARGON2_MEMORY_KIB
+        || params.t_cost() < ARGON2_ITERATIONS
+        || params.p_cost() < ARGON2_PARALLELISM
+}
+
+/// Enforces the password policy on a new password of `username`: 12 to 128
+/// characters, not containing the username, and mixing at least three of
+/// lowercase, uppercase, digits and symbols unless it's 20 characters or more.
+pub fn validate_password_strength(password: &str, username: &str) -> ServiceResult<()> {
+    let length = password.chars().count();
+    if length < MIN_PASSWORD_LENGTH {
+        return Err(ServiceError::validation(format!(
+            "Password must be at least {MIN_PASSWORD_LENGTH} characters"
+        )));
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        return Err(ServiceError::validation(format!(
+            "Password must be at most {MAX_PASSWORD_LENGTH} characters"
+        )));
+    }
+
+    let username = username.trim().to_lowercase();
+    if !username.is_empty() && password.to_lowercase().contains(&username) {
+        return Err(ServiceError::validation(
+            "Password must not contain the username",
+        ));
+    }
+
+    let mut first = password.chars();
+    if first
+        .next()
+        .is_some_and(|first_char| first.all(|c| c == first_char))
+    {
+        return Err(ServiceError::validation(
+            "Password must not repeat a single character",
+        ));
+    }
+
+    let classes = [
+        password.chars().any(|c| c.is_lowercase()),
+        password.chars().any(|c| c.is_uppercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_alphanumeric()),
+    ]
+    .into_iter()
+    .filter(|&present| present)
+    .count();
+    if length < PASSPHRASE_LENGTH && classes < MIN_CHARACTER_CLASSES {
+        return Err(ServiceError::validation(format!(
+            "Password must mix at least {MIN_CHARACTER_CLASSES} of lowercase letters, uppercase letters, digits and symbols, or be at least {PASSPHRASE_LENGTH} characters"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hash_and_rehash() {
+        let hash = hash_password("correct horse").await.unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse", &hash).await.unwrap());
+        assert!(!verify_password("wrong horse", &hash).await.unwrap());
+        assert!(!needs_rehash(&hash));
+
+        let legacy = bcrypt::hash("correct horse", 4).unwrap();
+        assert!(verify_password("correct horse", &legacy).await.unwrap());
+        assert!(needs_rehash(&legacy));
+    }
+
+    #[test]
+    fn test_validate_password_strength() {
+        assert!(validate_password_strength("Sh0rt!", "alice").is_err());
+        assert!(validate_password_strength("alice-Secret-99", "Alice").is_err());
+        assert!(validate_password_strength("alllowercaseletters", "bob").is_err());
+        assert!(validate_password_strength("aaaaaaaaaaaaaaaaaaaaaa", "bob").is_err());
+        assert!(validate_password_strength("Tangerine-Kite-42", "bob").is_ok());
+        assert!(validate_password_strength("purple monkey dishwasher", "bob").is_ok());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::errors::LightningError;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio::sync::RwLock;
@@ -10,9 +11,17 @@ struct MempoolPrice {
     usd: f64,
 }
 
+/// BTC price per currency code; the feed also reports its `time` here.
+type MempoolPrices = HashMap<String, f64>;
+
+#[derive(Deserialize)]
+struct MempoolHistoricalPrices {
+    prices: Vec<MempoolPrice>,
+}
+
 #[derive(Clone)]
 struct PriceCache {
-    price: f64,
+    prices: MempoolPrices,
     last_updated: SystemTime,
 }
 
@@ -52,37 +61,83 @@ impl PriceConverter {
         self.get_btc_price().await
     }
 
+    /// Fetch BTC price in a fiat currency the feed quotes, e.g. "EUR" (cached or API)
+    pub async fn fetch_btc_price_in(&self, currency: &str) -> Result<f64, LightningError> {
+        self.get_btc_price_in(currency).await
+    }
+
+    /// Fetch the BTC price closest to a unix timestamp (uncached)
+    pub async fn fetch_historical_btc_price(&self, timestamp: i64) -> Result<f64, LightningError> {
+        let response = self
+            .client
+            .get("https://mempool.space/api/v1/historical-price")
+            .query(&[
+                ("currency", "USD".to_string()),
+                ("timestamp", timestamp.to_string()),
+            ])
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .map_err(|e| LightningError::NetworkError(e.to_string()))?;
+
+        let price_data: MempoolHistoricalPrices = response
+            .json()
+            .await
+            .map_err(|e| LightningError::Parse(e.to_string()))?;
+
+        price_data
+            .prices
+            .first()
+            .map(|p| p.usd)
+            .ok_or_else(|| LightningError::Parse(format!("No price returned for {timestamp}")))
+    }
+
     async fn get_btc_price(&self) -> Result<f64, LightningError> {
+        self.get_btc_price_in("USD").await
+    }
+
+    async fn get_btc_price_in(&self, currency: &str) -> Result<f64, LightningError> {
+        let quote = |prices: &MempoolPrices| {
+            prices
+                .get(currency)
+                .copied()
+                .ok_or_else(|| LightningError::Parse(format!("No BTC price in {currency}")))
+        };
+
         // Check cache first (read lock)
-        if let Some(cached_price) = self.check_cache().await {
-            return Ok(cached_price);
+        if let Some(cached_prices) = self.check_cache().await {
+            return quote(&cached_prices);
         }
 
-        // Cache miss or expired - fetch fresh price
-        match self.fetch_btc_price_from_api().await {
-            Ok(price) => {
-                self.update_cache(price).await;
-                Ok(price)
+        // Cache miss or expired - fetch fresh prices
+        match self.fetch_btc_prices_from_api().await {
+            Ok(prices) => {
+                let price = quote(&prices);
+                self.update_cache(prices).await;
+                price
             }
             Err(e) => {
                 // Fallback to stale cache if available
-                self.cache.read().await.as_ref().map(|c| c.price).ok_or(e)
+                match self.cache.read().await.as_ref() {
+                    Some(c) => quote(&c.prices),
+                    None => Err(e),
+                }
             }
         }
     }
 
-    async fn check_cache(&self) -> Option<f64> {
+    async fn check_cache(&self) -> Option<MempoolPrices> {
         let cache = self.cache.read().await;
         cache.as_ref().and_then(|c| {
             c.last_updated
                 .elapsed()
                 .ok()
                 .filter(|&elapsed| elapsed < Self::CACHE_DURATION)
-                .map(|_| c.price)
+                .map(|_| c.prices.clone())
         })
     }
 
-    async fn fetch_btc_price_from_api(&self) -> Result<f64, LightningError> {
+    async fn fetch_btc_prices_from_api(&self) -> Result<MempoolPrices, LightningError> {
         let response = self
             .client
             .get("https://mempool.space/api/v1/prices")
@@ -91,18 +146,16 @@ impl PriceConverter {
             .await
             .map_err(|e| LightningError::NetworkError(e.to_string()))?;
 
-        let price_data: MempoolPrice = response
+        response
             .json()
             .await
-            .map_err(|e| LightningError::Parse(e.to_string()))?;
-
-        Ok(price_data.usd)
+            .map_err(|e| LightningError::Parse(e.to_string()))
     }
 
-    async fn update_cache(&self, price: f64) {
+    async fn update_cache(&self, prices: MempoolPrices) {
         let mut cache = self.cache.write().await;
         *cache = Some(PriceCache {
-            price,
+            prices,
             last_updated: SystemTime::now(),
         });
     }
@@ -0,0 +1,404 @@
+//! Canonical channel, payment and invoice states.
+//!
+//! LND and CLN describe the same lifecycle in different words: a finished
+//! payment is `SUCCEEDED` in LND and `complete` in CLN, a paid invoice is
+//! `SETTLED` in one and `paid` in the other. Every backend maps its states
+//! into [`ChannelState`], [`PaymentState`] and [`InvoiceStatus`] through the
+//! functions here, and filters parse the backend terms as synonyms of the
+//! canonical names, so `states=complete` and `states=settled` select the same
+//! payments on either node type.
+//!
+//! | Canonical            | LND                            | CLN                                      |
+//! |----------------------|--------------------------------|------------------------------------------|
+//! | payment `inflight`   | `IN_FLIGHT`, `UNKNOWN`; invoice `OPEN`, `ACCEPTED` | pay `pending`; invoice `unpaid` |
+//! | payment `settled`    | `SUCCEEDED`; invoice `SETTLED` | pay `complete`; invoice `paid`           |
+//! | payment `failed`     | `FAILED`; invoice `CANCELED`   | pay `failed`; invoice `expired`          |
+//! | invoice `open`       | `OPEN`, `ACCEPTED`             | `unpaid` before its expiry               |
+//! | invoice `settled`    | `SETTLED`                      | `paid`                                   |
+//! | invoice `expired`    | —                              | `expired`, or `unpaid` past its expiry   |
+//! | invoice `failed`     | `CANCELED`                     | —                                        |
+//! | channel `opening`    | pending open                   | `OPENINGD`, `*_AWAITING_LOCKIN`, `DUALOPEND_*` |
+//! | channel `active`     | `active`                       | `CHANNELD_NORMAL`                        |
+//! | channel `disabled`   | not `active`                   | any other state                          |
+//! | channel `closing`    | waiting close, force closing   | `CHANNELD_SHUTTING_DOWN`, `CLOSINGD_*`, `AWAITING_UNILATERAL`, `FUNDING_SPEND_SEEN` |
+//! | channel `closed`     | closed                         | `ONCHAIN`                                |
+
+use super::{ChannelState, InvoiceStatus, PaymentState};
+use std::str::FromStr;
+use tonic_lnd::lnrpc::{htlc_attempt::HtlcStatus, invoice::InvoiceState, payment::PaymentStatus};
+
+/// CLN channel states, in the order of the gRPC enum.
+const CLN_CHANNEL_STATES: [&str; 14] = [
+    "OPENINGD",
+    "CHANNELD_AWAITING_LOCKIN",
+    "CHANNELD_NORMAL",
+    "CHANNELD_SHUTTING_DOWN",
+    "CLOSINGD_SIGEXCHANGE",
+    "CLOSINGD_COMPLETE",
+    "AWAITING_UNILATERAL",
+    "FUNDING_SPEND_SEEN",
+    "ONCHAIN",
+    "DUALOPEND_OPEN_INIT",
+    "DUALOPEND_AWAITING_LOCKIN",
+    "CHANNELD_AWAITING_SPLICE",
+    "DUALOPEND_OPEN_COMMITTED",
+    "DUALOPEND_OPEN_COMMIT_READY",
+];
+
+/// CLN `listpays` statuses, in the order of the gRPC enum.
+const CLN_PAY_STATUSES: [&str; 3] = ["pending", "complete", "failed"];
+
+/// CLN invoice statuses, in the order of the gRPC enum.
+const CLN_INVOICE_STATUSES: [&str; 3] = ["unpaid", "paid", "expired"];
+
+/// Names accepted for each payment state, canonical name first.
+const PAYMENT_STATE_NAMES: [(PaymentState, &[&str]); 3] = [
+    (
+        PaymentState::Inflight,
+        &[
+            "inflight",
+            "in_flight",
+            "pending",
+            "unpaid",
+            "open",
+            "accepted",
+        ],
+    ),
+    (
+        PaymentState::Settled,
+        &[
+            "settled",
+            "succeeded",
+            "success",
+            "complete",
+            "completed",
+            "paid",
+        ],
+    ),
+    (
+        PaymentState::Failed,
+        &["failed", "failure", "canceled", "cancelled", "expired"],
+    ),
+];
+
+/// Names accepted for each invoice status, canonical name first.
+const INVOICE_STATUS_NAMES: [(InvoiceStatus, &[&str]); 4] = [
+    (
+        InvoiceStatus::Settled,
+        &["settled", "paid", "complete", "completed"],
+    ),
+    (
+        InvoiceStatus::Open,
+        &["open", "unpaid", "pending", "accepted"],
+    ),
+    (InvoiceStatus::Expired, &["expired"]),
+    (InvoiceStatus::Failed, &["failed", "canceled", "cancelled"]),
+];
+
+/// Names accepted for each channel state, canonical name first.
+const CHANNEL_STATE_NAMES: [(ChannelState, &[&str]); 6] = [
+    (
+        ChannelState::Opening,
+        &["opening", "pending_open", "awaiting_lockin"],
+    ),
+    (ChannelState::Active, &["active", "normal", "online"]),
+    (ChannelState::Disabled, &["disabled", "inactive", "offline"]),
+    (
+        ChannelState::Closing,
+        &[
+            "closing",
+            "pending_close",
+            "waiting_close",
+            "force_closing",
+            "shutting_down",
+        ],
+    ),
+    (ChannelState::Closed, &["closed", "onchain"]),
+    (ChannelState::Failed, &["failed"]),
+];
+
+/// Looks `input` up in a synonym table, ignoring case and treating `-` and
+/// spaces like `_`.
+fn parse_synonym<T: Clone>(table: &[(T, &[&str])], input: &str) -> Option<T> {
+    let normalized = input.trim().to_lowercase().replace(['-', ' '], "_");
+    table
+        .iter()
+        .find(|(_, names)| names.contains(&normalized.as_str()))
+        .map(|(state, _)| state.clone())
+}
+
+/// A CLN gRPC enum value as the name CLN's JSON API uses for it.
+fn cln_name(names: &'static [&'static str], code: i32) -> Option<&'static str> {
+    usize::try_from(code)
+        .ok()
+        .and_then(|i| names.get(i))
+        .copied()
+}
+
+impl FromStr for PaymentState {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_synonym(&PAYMENT_STATE_NAMES, input)
+            .ok_or_else(|| format!("Invalid payment state: {input}"))
+    }
+}
+
+impl FromStr for InvoiceStatus {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_synonym(&INVOICE_STATUS_NAMES, input)
+            .ok_or_else(|| format!("Invalid invoice status: {input}"))
+    }
+}
+
+impl FromStr for ChannelState {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_synonym(&CHANNEL_STATE_NAMES, input)
+            .ok_or_else(|| format!("Invalid channel state: {input}"))
+    }
+}
+
+/// State of an LND payment.
+pub fn lnd_payment_state(status: PaymentStatus) -> PaymentState {
+    match status {
+        PaymentStatus::Unknown | PaymentStatus::InFlight => PaymentState::Inflight,
+        PaymentStatus::Succeeded => PaymentState::Settled,
+        PaymentStatus::Failed => PaymentState::Failed,
+    }
+}
+
+/// State of a single LND payment attempt.
+pub fn lnd_htlc_state(status: HtlcStatus) -> PaymentState {
+    match status {
+        HtlcStatus::InFlight => PaymentState::Inflight,
+        HtlcStatus::Succeeded => PaymentState::Settled,
+        HtlcStatus::Failed => PaymentState::Failed,
+    }
+}
+
+/// State of a payment received through an LND invoice.
+pub fn lnd_invoice_payment_state(state: InvoiceState) -> PaymentState {
+    match state {
+        InvoiceState::Open | InvoiceState::Accepted => PaymentState::Inflight,
+        InvoiceState::Settled => PaymentState::Settled,
+        InvoiceState::Canceled => PaymentState::Failed,
+    }
+}
+
+/// Status of an LND invoice. An accepted (held) invoice is still open.
+pub fn lnd_invoice_status(state: InvoiceState) -> InvoiceStatus {
+    match state {
+        InvoiceState::Open | InvoiceState::Accepted => InvoiceStatus::Open,
+        InvoiceState::Settled => InvoiceStatus::Settled,
+        InvoiceState::Canceled => InvoiceStatus::Failed,
+    }
+}
+
+/// State of an open LND channel; LND only tells active from inactive.
+pub fn lnd_channel_state(active: bool) -> ChannelState {
+    if active {
+        ChannelState::Active
+    } else {
+        ChannelState::Disabled
+    }
+}
+
+/// State of a CLN `listpays` payment.
+pub fn cln_pay_state(status: &str) -> PaymentState {
+    match status {
+        "pending" => PaymentState::Inflight,
+        "complete" => PaymentState::Settled,
+        _ => PaymentState::Failed,
+    }
+}
+
+/// [`cln_pay_state`] for the gRPC enum value.
+pub fn cln_pay_state_code(code: i32) -> PaymentState {
+    cln_pay_state(cln_name(&CLN_PAY_STATUSES, code).unwrap_or_default())
+}
+
+/// State of a payment received through a CLN invoice, or None for a status
+/// CLN doesn't document.
+pub fn cln_invoice_payment_state(status: &str) -> Option<PaymentState> {
+    match status {
+        "unpaid" => Some(PaymentState::Inflight),
+        "paid" => Some(PaymentState::Settled),
+        "expired" => Some(PaymentState::Failed),
+        _ => None,
+    }
+}
+
+/// [`cln_invoice_payment_state`] for the gRPC enum value.
+pub fn cln_invoice_payment_state_code(code: i32) -> Option<PaymentState> {
+    cln_name(&CLN_INVOICE_STATUSES, code).and_then(cln_invoice_payment_state)
+}
+
+/// Status of a CLN invoice. CLN only marks an invoice `expired` once it
+/// notices, so an unpaid one past `expires_at` is reported expired already.
+pub fn cln_invoice_status(status: &str, expires_at: u64) -> InvoiceStatus {
+    match status {
+        "paid" => InvoiceStatus::Settled,
+        "expired" => InvoiceStatus::Expired,
+        _ if expires_at <= chrono::Utc::now().timestamp() as u64 => InvoiceStatus::Expired,
+        _ => InvoiceStatus::Open,
+    }
+}
+
+/// [`cln_invoice_status`] for the gRPC enum value.
+pub fn cln_invoice_status_code(code: i32, expires_at: u64) -> InvoiceStatus {
+    cln_invoice_status(
+        cln_name(&CLN_INVOICE_STATUSES, code).unwrap_or_default(),
+        expires_at,
+    )
+}
+
+/// State of a CLN channel.
+pub fn cln_channel_state(state: &str) -> ChannelState {
+    match state {
+        "OPENINGD"
+        | "CHANNELD_AWAITING_LOCKIN"
+        | "DUALOPEND_OPEN_INIT"
+        | "DUALOPEND_AWAITING_LOCKIN"
+        | "DUALOPEND_OPEN_COMMITTED"
+        | "DUALOPEND_OPEN_COMMIT_READY" => ChannelState::Opening,
+        "CHANNELD_NORMAL" => ChannelState::Active,
+        "CHANNELD_SHUTTING_DOWN"
+        | "CLOSINGD_SIGEXCHANGE"
+        | "CLOSINGD_COMPLETE"
+        | "AWAITING_UNILATERAL"
+        | "FUNDING_SPEND_SEEN" => ChannelState::Closing,
+        "ONCHAIN" => ChannelState::Closed,
+        _ => ChannelState::Disabled,
+    }
+}
+
+/// [`cln_channel_state`] for the gRPC enum value.
+pub fn cln_channel_state_code(code: i32) -> ChannelState {
+    cln_channel_state(cln_name(&CLN_CHANNEL_STATES, code).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_synonyms() {
+        for (input, expected) in [
+            ("settled", PaymentState::Settled),
+            ("complete", PaymentState::Settled),
+            ("Completed", PaymentState::Settled),
+            ("SUCCEEDED", PaymentState::Settled),
+            ("in-flight", PaymentState::Inflight),
+            ("pending", PaymentState::Inflight),
+            ("expired", PaymentState::Failed),
+        ] {
+            assert_eq!(input.parse::<PaymentState>(), Ok(expected), "{input}");
+        }
+        assert!("done".parse::<PaymentState>().is_err());
+
+        assert_eq!(
+            "paid".parse::<InvoiceStatus>().unwrap().to_string(),
+            "settled"
+        );
+        assert_eq!(
+            "cancelled".parse::<InvoiceStatus>().unwrap().to_string(),
+            "failed"
+        );
+        assert_eq!(
+            "unpaid".parse::<InvoiceStatus>().unwrap().to_string(),
+            "open"
+        );
+        assert_eq!(
+            "onchain".parse::<ChannelState>().unwrap().to_string(),
+            "closed"
+        );
+        assert_eq!(
+            "pending open".parse::<ChannelState>().unwrap().to_string(),
+            "opening"
+        );
+        assert_eq!(
+            "inactive".parse::<ChannelState>().unwrap().to_string(),
+            "disabled"
+        );
+    }
+
+    #[test]
+    fn test_canonical_names_round_trip() {
+        for (state, names) in PAYMENT_STATE_NAMES {
+            assert_eq!(state.to_string(), names[0]);
+        }
+        for (status, names) in INVOICE_STATUS_NAMES {
+            assert_eq!(status.to_string(), names[0]);
+        }
+        for (state, names) in CHANNEL_STATE_NAMES {
+            assert_eq!(state.to_string(), names[0]);
+        }
+    }
+
+    #[test]
+    fn test_lnd_mapping() {
+        assert_eq!(
+            lnd_payment_state(PaymentStatus::Unknown),
+            PaymentState::Inflight
+        );
+        assert_eq!(
+            lnd_payment_state(PaymentStatus::Succeeded),
+            PaymentState::Settled
+        );
+        assert_eq!(
+            lnd_payment_state(PaymentStatus::Failed),
+            PaymentState::Failed
+        );
+        assert_eq!(lnd_htlc_state(HtlcStatus::Succeeded), PaymentState::Settled);
+        assert_eq!(
+            lnd_invoice_payment_state(InvoiceState::Accepted),
+            PaymentState::Inflight
+        );
+        assert_eq!(
+            lnd_invoice_payment_state(InvoiceState::Canceled),
+            PaymentState::Failed
+        );
+        assert_eq!(
+            lnd_invoice_status(InvoiceState::Accepted).to_string(),
+            "open"
+        );
+        assert_eq!(
+            lnd_invoice_status(InvoiceState::Canceled).to_string(),
+            "failed"
+        );
+        assert_eq!(lnd_channel_state(false).to_string(), "disabled");
+    }
+
+    #[test]
+    fn test_cln_mapping() {
+        // The gRPC codes and the JSON names agree.
+        assert_eq!(cln_pay_state_code(1), cln_pay_state("complete"));
+        assert_eq!(cln_pay_state_code(0), PaymentState::Inflight);
+        assert_eq!(cln_pay_state_code(7), PaymentState::Failed);
+        assert_eq!(
+            cln_invoice_payment_state_code(2),
+            Some(PaymentState::Failed)
+        );
+        assert_eq!(cln_invoice_payment_state("bogus"), None);
+        assert_eq!(cln_invoice_status_code(1, 0).to_string(), "settled");
+        assert_eq!(cln_invoice_status("unpaid", 0).to_string(), "expired");
+        assert_eq!(cln_invoice_status("unpaid", u64::MAX).to_string(), "open");
+
+        for (code, name) in CLN_CHANNEL_STATES.iter().enumerate() {
+            assert_eq!(
+                cln_channel_state_code(code as i32).to_string(),
+                cln_channel_state(name).to_string()
+            );
+        }
+        assert_eq!(cln_channel_state_code(2).to_string(), "active");
+        assert_eq!(
+            cln_channel_state("AWAITING_UNILATERAL").to_string(),
+            "closing"
+        );
+        assert_eq!(cln_channel_state_code(-1).to_string(), "disabled");
+    }
+}